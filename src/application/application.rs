@@ -1,8 +1,9 @@
 use std::cell::RefCell;
+use std::error::Error;
 use std::fs::File;
 use std::io::Read;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use crate::application::constraint::{default_frac_observable_time,
                                      default_max_altitude,
@@ -23,20 +24,157 @@ use crate::application::observer::{default_elevation,
                       default_name,
                       default_timezone,
                       Observer};
+use crate::application::config_check::{config_tree, validate_config};
+use crate::application::equipment::Equipment;
 use crate::application::time::{Time};
+use crate::application::preferences::Preferences;
+use crate::application::target::Target;
+use crate::application::undo::UndoStack;
+use crate::application::yaml_patch::patch_yaml;
+use crate::utils::i18n::set_language;
 
 pub const DEFAULT_TARGET_LIST: &str = "OpenNGC";
 pub const DEFAULT_TYPE_FILTER: &str = "";
 pub const DEFAULT_OUTPUT_DIR: &str = "output";
 
+// Version of the configuration file format, increased when the format changes
+pub const CONFIG_VERSION: u32 = 1;
+
+// Top level entries known by this version, others are ignored when loading
+const CONFIG_SECTIONS: &[&str] = &["config_version", "observer", "time", "environment",
+                                   "constraints", "targets", "equipment", "preferences"];
+
+// File extensions accepted for configuration files, the first one is used by default
+pub const CONFIG_EXTENSIONS: &[&str] = &["yaml", "yml", "json", "toml"];
+
+/// ConfigFormat enum
+///
+/// File format of a configuration, selected by the file extension. All formats hold the same
+/// sections, YAML is the default and the format of the bundled `config.yaml`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigFormat {
+    Yaml,
+    Json,
+    Toml,
+}
+
+impl ConfigFormat {
+    pub fn from_extension(extension: &str) -> Option<ConfigFormat> {
+        match extension.to_ascii_lowercase().as_str() {
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            "json" => Some(ConfigFormat::Json),
+            "toml" => Some(ConfigFormat::Toml),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<ConfigFormat> {
+        path.extension().and_then(|e| e.to_str()).and_then(ConfigFormat::from_extension)
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ConfigFormat::Yaml => "yaml",
+            ConfigFormat::Json => "json",
+            ConfigFormat::Toml => "toml",
+        }
+    }
+
+    pub fn parse(&self, contents: &str) -> Result<Application, Box<dyn Error>> {
+        Ok(match self {
+            ConfigFormat::Yaml => serde_yaml::from_str(contents)?,
+            ConfigFormat::Json => serde_json::from_str(contents)?,
+            ConfigFormat::Toml => toml::from_str(contents)?,
+        })
+    }
+
+    // The selected date is dropped unless it should be remembered
+    pub fn to_string(self, application: &Application) -> Result<String, Box<dyn Error>> {
+        let remember_date = application.preferences.remember_date;
+        Ok(match self {
+            ConfigFormat::Yaml => serde_yaml::to_string(&yaml_value(application)?)?,
+            ConfigFormat::Json => {
+                let mut value = serde_json::to_value(application)?;
+                if let (false, Some(object)) = (remember_date, value.as_object_mut()) {
+                    object.remove("time");
+                }
+                serde_json::to_string_pretty(&value)?
+            }
+            ConfigFormat::Toml => {
+                let mut value = toml::Value::try_from(application)?;
+                if let (false, Some(table)) = (remember_date, value.as_table_mut()) {
+                    table.remove("time");
+                }
+                toml::to_string_pretty(&value)?
+            }
+        })
+    }
+
+    /// Text of the configuration written over an existing file. YAML files keep their comments,
+    /// key order and the text of the values that did not change, the other formats and files that
+    /// do not load are written again from scratch.
+    pub fn update(&self, original: &str, application: &Application) -> Result<String, Box<dyn Error>> {
+        match (self, loaded_config(original, *self)) {
+            (ConfigFormat::Yaml, Ok(loaded)) => {
+                Ok(patch_yaml(original, &yaml_value(&loaded)?, &yaml_value(application)?))
+            }
+            _ => self.to_string(application),
+        }
+    }
+
+    // Generic tree of the file, used to report the sections of older formats
+    fn to_yaml_value(self, contents: &str) -> Option<serde_yaml::Value> {
+        config_tree(contents, self).ok()
+    }
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Application {
+    #[serde(default)]
+    pub config_version: u32,
     pub observer: Observer,
-    #[serde(skip_serializing)]
-    #[serde(skip_deserializing)]
+    // Selected date, only saved and restored with `preferences.remember_date`
+    #[serde(default)]
     pub time: Time,
     pub environment: Environment,
     pub constraints: Constraints,
+    #[serde(default)]
+    pub targets: Vec<Target>,
+    #[serde(default)]
+    pub equipment: Equipment,
+    #[serde(default)]
+    pub preferences: Preferences,
+    // What was changed in the last configuration loaded from an older format
+    #[serde(skip)]
+    pub migration_notes: Vec<String>,
+    // Fields of the last configuration loaded that are not known and were ignored
+    #[serde(skip)]
+    pub config_warnings: Vec<String>,
+    // Settings edits that can be undone, not saved with the configuration
+    #[serde(skip)]
+    pub history: UndoStack,
+}
+
+// YAML tree written for the application, the selected date is dropped unless it should be
+// remembered
+fn yaml_value(application: &Application) -> Result<serde_yaml::Value, Box<dyn Error>> {
+    let mut value = serde_yaml::to_value(application)?;
+    if let (false, Some(mapping)) = (application.preferences.remember_date, value.as_mapping_mut()) {
+        mapping.remove("time");
+    }
+    Ok(value)
+}
+
+// Application of a configuration file as it is used once loaded, values out of range are errors
+fn loaded_config(contents: &str, format: ConfigFormat) -> Result<Application, Box<dyn Error>> {
+    let (mut config, warnings) = validate_config(contents, format)?;
+    config.migration_notes = migration_notes(contents, format);
+    config.config_warnings = warnings.iter().map(|warning| warning.to_string()).collect();
+    config.config_version = CONFIG_VERSION;
+    if !config.preferences.remember_date {
+        config.time = Time::default();
+    }
+    Ok(config)
 }
 
 // Function to return default values for Config
@@ -47,7 +185,8 @@ fn default_config() -> (Observer, Time, Environment, Constraints) {
             latitude: default_lat(),
             longitude: default_lon(),
             elevation: default_elevation(),
-            timezone: default_timezone()
+            timezone: default_timezone(),
+            zone: None,
         },
         Time::default(),
         Environment {
@@ -68,7 +207,17 @@ fn default_config() -> (Observer, Time, Environment, Constraints) {
     )
 }
 
-pub fn load_from_yaml(file_path: &str, application: &mut Rc<RefCell<Application>>) -> Result<(), Box<dyn std::error::Error>> {
+/// Reads a configuration without making it the current one, e.g. to compare its observatory with
+/// others. The format is selected by the extension like `load_config`.
+pub fn read_config(file_path: &str) -> Result<Application, Box<dyn Error>> {
+    let format = ConfigFormat::from_path(Path::new(file_path)).unwrap_or(ConfigFormat::Yaml);
+    log::debug!("configuration read path={} format={:?}", file_path, format);
+    loaded_config(&std::fs::read_to_string(file_path)?, format)
+}
+
+// Loads a configuration, the format is selected by the extension and defaults to YAML
+pub fn load_config(file_path: &str, application: &mut Rc<RefCell<Application>>) -> Result<(), Box<dyn Error>> {
+    let format = ConfigFormat::from_path(Path::new(file_path)).unwrap_or(ConfigFormat::Yaml);
     let mut contents = String::new();
 
     // Try to open the file
@@ -78,39 +227,181 @@ pub fn load_from_yaml(file_path: &str, application: &mut Rc<RefCell<Application>
                 return Err(Box::new(e));
             }
 
-            match serde_yaml::from_str(&contents) {
+            match loaded_config(&contents, format) {
                 Ok(config) => {
+                    log::info!("configuration loaded path={} format={:?} targets={} migrated={}",
+                               file_path, format, config.targets.len(), !config.migration_notes.is_empty());
+                    for warning in &config.config_warnings {
+                        log::warn!("configuration field ignored path={} {}", file_path, warning);
+                    }
+                    set_language(config.preferences.language);
                     *application.borrow_mut() = config;
                     Ok(())
                 }
                 Err(e) => {
-                    Err(Box::new(e))
+                    log::warn!("configuration not loaded path={} format={:?} error={}", file_path, format, e);
+                    Err(e)
                 }
             }
         }
-        Err(_) => {
+        Err(e) => {
             // File not found or unreadable, use default values
-            println!("YAML configuration file not found. Using default values. {:?}", file_path);
+            log::warn!("configuration not found, using defaults path={} error={}", file_path, e);
             let (observer, time, environment, constraints) = default_config();
             *application.borrow_mut() = Application {
+                config_version: CONFIG_VERSION,
                 observer,
                 time,
                 environment,
                 constraints,
+                targets: Vec::new(),
+                equipment: Equipment::default(),
+                preferences: Preferences::default(),
+                migration_notes: Vec::new(),
+                config_warnings: Vec::new(),
+                history: UndoStack::default(),
             };
             Ok(())
         }
     }
 }
 
-pub fn save_to_yaml(file_path: PathBuf, application: &mut Rc<RefCell<Application>>) -> Result<(), Box<dyn std::error::Error>> {
-    let f = std::fs::OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(file_path)?; // TODO Treat errors when writing
+/// Changes applied when loading a configuration written in an older format, empty when the file
+/// is already in the current format
+pub fn migration_notes(contents: &str, format: ConfigFormat) -> Vec<String> {
+    let mut notes: Vec<String> = Vec::new();
+    let value = match format.to_yaml_value(contents) {
+        Some(value) => value,
+        None => return notes,
+    };
+    let mapping = match value.as_mapping() {
+        Some(mapping) => mapping,
+        None => return notes,
+    };
+
+    let version = mapping.get("config_version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+    if version >= CONFIG_VERSION {
+        return notes;
+    }
+
+    notes.push(format!("Configuration format updated from version {} to {}", version, CONFIG_VERSION));
+    for section in ["targets", "preferences"] {
+        if !mapping.contains_key(section) {
+            notes.push(format!("Added section '{}' with default values", section));
+        }
+    }
+    for key in mapping.keys().filter_map(|key| key.as_str()) {
+        if !CONFIG_SECTIONS.contains(&key) {
+            notes.push(format!("Section '{}' is not used, it is kept only when saving over this file", key));
+        }
+    }
+    notes.push("Save the configuration to keep the new format".to_string());
+    notes
+}
 
-    serde_yaml::to_writer(f, &*application.borrow())?; // Borrow immutably and dereference
+// Saves the configuration in the format given by the extension, YAML when it is not known. An
+// existing YAML file is updated in place
+pub fn save_config(file_path: PathBuf, application: &mut Rc<RefCell<Application>>) -> Result<(), Box<dyn Error>> {
+    let format = ConfigFormat::from_path(&file_path).unwrap_or(ConfigFormat::Yaml);
+    let contents = match std::fs::read_to_string(&file_path) {
+        Ok(original) => format.update(&original, &application.borrow())?,
+        Err(_) => format.to_string(&application.borrow())?,
+    };
+    std::fs::write(&file_path, contents)?;
+    log::info!("configuration saved path={} format={:?}", file_path.display(), format);
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::fuzz::Fuzzer;
+
+    const YAML_TOKENS: &[&str] = &[
+        "-", ":", " ", "\n", "nan", "inf", "\"", "[", "{", "1e309", "99:99", "~", "&a", "*a",
+        "!!int", "é", "2024-13-45",
+    ];
+
+    #[test]
+    fn test_time_round_trip() {
+        let path = std::env::temp_dir().join("skycalc_test_time_round_trip.yaml");
+        let mut application = Rc::new(RefCell::new(Application::default()));
+        let date = Time::new(2024, 12, 11, 21, 5, 9);
+
+        for remember_date in [true, false] {
+            application.borrow_mut().time = date.clone();
+            application.borrow_mut().preferences.remember_date = remember_date;
+            save_config(path.clone(), &mut application).unwrap();
+
+            let mut loaded = Rc::new(RefCell::new(Application::default()));
+            load_config(path.to_str().unwrap(), &mut loaded).unwrap();
+            assert_eq!(loaded.borrow().time == date, remember_date);
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_config_formats() {
+        let mut config = ConfigFormat::Yaml.parse(include_str!("../../config.yaml")).unwrap();
+        config.config_version = CONFIG_VERSION;
+        let mut application = Rc::new(RefCell::new(config));
+        application.borrow_mut().targets.push(Target::new("M42", 83.82, -5.39, 85.0));
+
+        for format in [ConfigFormat::Yaml, ConfigFormat::Json, ConfigFormat::Toml] {
+            let path = std::env::temp_dir()
+                .join(format!("skycalc_test_config_formats.{}", format.extension()));
+            save_config(path.clone(), &mut application).unwrap();
+
+            let mut loaded = Rc::new(RefCell::new(Application::default()));
+            load_config(path.to_str().unwrap(), &mut loaded).unwrap();
+            let (original, loaded) = (application.borrow(), loaded.borrow());
+            assert_eq!(loaded.observer.name, original.observer.name);
+            assert_eq!(loaded.observer.latitude, original.observer.latitude);
+            assert_eq!(loaded.observer.longitude, original.observer.longitude);
+            assert_eq!(loaded.observer.timezone, original.observer.timezone);
+            assert_eq!(loaded.constraints.min_altitude, original.constraints.min_altitude);
+            assert_eq!(loaded.targets.len(), original.targets.len());
+            assert!(loaded.migration_notes.is_empty());
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_update_yaml() {
+        let original = include_str!("../../config.yaml");
+        let mut application = loaded_config(original, ConfigFormat::Yaml).unwrap();
+        // Unchanged values keep their text, only the sections missing in the file are added
+        let unchanged = ConfigFormat::Yaml.update(original, &application).unwrap();
+        assert!(unchanged.starts_with(original.trim_end()));
+        assert!(unchanged.contains("\npreferences:\n"));
+
+        application.constraints.min_altitude = 25;
+        application.observer.timezone = -2.0;
+        let updated = ConfigFormat::Yaml.update(original, &application).unwrap();
+        assert!(updated.contains("  min_altitude: 25  # in deg above horizon\n"));
+        assert!(updated.contains("  timezone: -2.0 # (-/+)HH:MM, (-/+)HH.HH\n"));
+        assert!(updated.contains("  latitude: 23d 06m S #"));
+        assert!(updated.contains("others:\n"));
+        let reloaded = ConfigFormat::Yaml.parse(&updated).unwrap();
+        assert_eq!(reloaded.constraints.min_altitude, 25);
+        assert_eq!(reloaded.observer.latitude, application.observer.latitude);
+    }
+
+    #[test]
+    fn test_load_yaml_fuzz() {
+        let base = include_str!("../../config.yaml");
+        let mut fuzzer = Fuzzer::new(0x2545_F491_4F6C_DD1D);
+        for _ in 0..5_000 {
+            let contents = fuzzer.mutate(base, YAML_TOKENS);
+            migration_notes(&contents, ConfigFormat::Yaml);
+            let _ = validate_config(&contents, ConfigFormat::Yaml);
+            if let Ok(config) = serde_yaml::from_str::<Application>(&contents) {
+                let observer = &config.observer;
+                assert!(observer.latitude.is_finite() && observer.latitude.abs() <= 90.0);
+                assert!(observer.timezone.is_finite() && observer.timezone.abs() <= 14.0);
+                assert!(config.time.to_jd().is_finite());
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Crash-safe copy of the settings being edited. Observatory data typed in the field is only in
+// memory until the configuration is saved, so the state is written to autosave.yaml in the
+// configuration directory once a minute while it differs from what was loaded. A clean exit
+// removes the file, finding it at the next start means the program did not close properly and
+// its state can be recovered. The file is written next to its final name and renamed over it,
+// a crash while writing leaves the previous copy.
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use crate::application::application::{Application, ConfigFormat};
+use crate::application::recent::config_directory;
+
+pub const AUTOSAVE_FILE: &str = "autosave.yaml";
+
+/// Time between two autosaves
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Autosave struct
+///
+/// Writes the application state when it changed, call `tick` from the event loop and `finish`
+/// on a clean exit.
+///
+/// # Attributes
+///
+/// * `path` - File the state is written to
+/// * `saved` - Contents of the last state written, or loaded at start
+/// * `last_check` - Time of the last comparison with the current state
+#[derive(Debug)]
+pub struct Autosave {
+    path: PathBuf,
+    saved: String,
+    last_check: Instant,
+}
+
+/// Autosave file of the current user
+pub fn autosave_path() -> PathBuf {
+    config_directory().join(AUTOSAVE_FILE)
+}
+
+/// Drops a declined autosave
+pub fn discard_autosave(path: &Path) {
+    if let Err(e) = std::fs::remove_file(path) {
+        log::warn!("autosave not removed path={} error={}", path.display(), e);
+    }
+}
+
+// The autosave holds the configuration as it would be saved
+fn contents(application: &Application) -> Result<String, Box<dyn Error>> {
+    ConfigFormat::Yaml.to_string(application)
+}
+
+impl Autosave {
+    /// Starts with the state as loaded, nothing is written until it changes
+    pub fn new(path: PathBuf, application: &Application) -> Self {
+        Autosave {
+            path,
+            saved: contents(application).unwrap_or_default(),
+            last_check: Instant::now(),
+        }
+    }
+
+    /// Autosaves when the interval went by since the last check
+    pub fn tick(&mut self, application: &Application) {
+        if self.last_check.elapsed() < AUTOSAVE_INTERVAL {
+            return;
+        }
+        self.last_check = Instant::now();
+        if let Err(e) = self.save_if_changed(application) {
+            log::warn!("autosave failed path={} error={}", self.path.display(), e);
+        }
+    }
+
+    /// Writes the state when it differs from the last one written, true when it was written
+    pub fn save_if_changed(&mut self, application: &Application) -> Result<bool, Box<dyn Error>> {
+        let current = contents(application)?;
+        if current == self.saved {
+            return Ok(false);
+        }
+        if let Some(directory) = self.path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        let temporary = self.path.with_extension("yaml.tmp");
+        std::fs::write(&temporary, &current)?;
+        std::fs::rename(&temporary, &self.path)?;
+        log::debug!("autosaved path={} bytes={}", self.path.display(), current.len());
+        self.saved = current;
+        Ok(true)
+    }
+
+    /// Clean exit, the autosave is not needed anymore
+    pub fn finish(self) {
+        if self.path.exists() {
+            discard_autosave(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_autosave() {
+        let directory = std::env::temp_dir().join("skycalc_test_autosave");
+        let _ = std::fs::remove_dir_all(&directory);
+        let path = directory.join(AUTOSAVE_FILE);
+        let mut application = Application::default();
+        let mut autosave = Autosave::new(path.clone(), &application);
+
+        // nothing to recover while the state is the one loaded
+        assert!(!autosave.save_if_changed(&application).unwrap());
+        assert!(!path.is_file());
+
+        // an edit is written once
+        application.observer.name = Some("Field site".to_string());
+        assert!(autosave.save_if_changed(&application).unwrap());
+        assert!(!autosave.save_if_changed(&application).unwrap());
+        let recovered = ConfigFormat::Yaml.parse(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(recovered.observer.name.as_deref(), Some("Field site"));
+
+        // a clean exit leaves nothing behind
+        autosave.finish();
+        assert!(!path.is_file());
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,292 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Avoidance zones: circles around bright solar system objects that scheduled targets should
+// stay out of during their observation window.
+
+use crate::application::{
+    moon::moon_topocentric_position,
+    observer::Observer,
+    target::Target,
+    transformations::angular_separation,
+};
+
+// Step used to follow a target along its window, in days (10 minutes)
+const STEP: f64 = 10.0 / 1440.0;
+
+// Points of the outline of a zone on the sky chart
+const OUTLINE_POINTS: usize = 72;
+
+/// AvoidanceBody enum
+///
+/// Solar system objects with an avoidance zone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AvoidanceBody {
+    Moon,
+}
+
+impl AvoidanceBody {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AvoidanceBody::Moon => "Moon",
+        }
+    }
+
+    /// Topocentric equatorial coordinates (ra, dec) of the body in degrees at a JD UTC
+    pub fn position(&self, observer: &Observer, jd: f64) -> (f64, f64) {
+        match self {
+            AvoidanceBody::Moon => {
+                let (ra, dec, _) = moon_topocentric_position(
+                    observer.latitude,
+                    observer.longitude,
+                    observer.elevation as f64,
+                    jd,
+                );
+                (ra, dec)
+            }
+        }
+    }
+}
+
+/// AvoidanceZone struct
+///
+/// Circle of `radius` degrees centered on a solar system object.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AvoidanceZone {
+    pub body: AvoidanceBody,
+    pub radius: f64,
+}
+
+impl AvoidanceZone {
+    pub fn new(body: AvoidanceBody, radius: f64) -> AvoidanceZone {
+        AvoidanceZone { body, radius }
+    }
+
+    /// Center of the zone (ra, dec) in degrees seen by the observer at a JD UTC
+    pub fn center(&self, observer: &Observer, jd: f64) -> (f64, f64) {
+        self.body.position(observer, jd)
+    }
+
+    /// Angular distance in degrees between the zone center and a position at a JD UTC
+    pub fn separation(&self, observer: &Observer, ra: f64, dec: f64, jd: f64) -> f64 {
+        let (center_ra, center_dec) = self.center(observer, jd);
+        angular_separation(ra, dec, center_ra, center_dec)
+    }
+
+    pub fn contains(&self, observer: &Observer, ra: f64, dec: f64, jd: f64) -> bool {
+        self.separation(observer, ra, dec, jd) < self.radius
+    }
+}
+
+/// AvoidanceConflict struct
+///
+/// Closest approach of a target to an avoidance zone it enters.
+///
+/// # Attributes
+///
+/// * `body` - Object of the zone entered
+/// * `jd` - Time of closest approach (JD UTC)
+/// * `separation` - Separation at closest approach in degrees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AvoidanceConflict {
+    pub body: AvoidanceBody,
+    pub jd: f64,
+    pub separation: f64,
+}
+
+/// Zones of the observation plan, the Moon radius in degrees comes from the preferences
+pub fn avoidance_zones(moon_radius: i64) -> Vec<AvoidanceZone> {
+    vec![AvoidanceZone::new(AvoidanceBody::Moon, moon_radius as f64)]
+}
+
+/// Avoidance zones entered by the target between jd_start and jd_end (JD UTC) while it is above
+/// the horizon, one conflict per zone at its closest approach
+pub fn avoidance_conflicts(
+    target: &Target,
+    observer: &Observer,
+    zones: &[AvoidanceZone],
+    jd_start: f64,
+    jd_end: f64,
+) -> Vec<AvoidanceConflict> {
+    let mut conflicts: Vec<AvoidanceConflict> = Vec::new();
+    for zone in zones {
+        let mut closest: Option<AvoidanceConflict> = None;
+        let mut jd = jd_start;
+        while jd <= jd_end {
+            if target.altitude(observer, jd) > 0.0 {
+                let separation = zone.separation(observer, target.ra, target.dec, jd);
+                if separation < zone.radius && closest.is_none_or(|c| separation < c.separation) {
+                    closest = Some(AvoidanceConflict { body: zone.body, jd, separation });
+                }
+            }
+            jd += STEP;
+        }
+        conflicts.extend(closest);
+    }
+    conflicts
+}
+
+/// Point (ra, dec) in degrees `radius` degrees away from (ra, dec) at a position angle measured
+/// from the north through the east
+pub fn circle_point(ra: f64, dec: f64, radius: f64, angle: f64) -> (f64, f64) {
+    let (dec, radius, angle) = (dec.to_radians(), radius.to_radians(), angle.to_radians());
+    let point_dec = (dec.sin() * radius.cos() + dec.cos() * radius.sin() * angle.cos()).asin();
+    let delta_ra = (angle.sin() * radius.sin() * dec.cos())
+        .atan2(radius.cos() - dec.sin() * point_dec.sin());
+    ((ra + delta_ra.to_degrees()).rem_euclid(360.0), point_dec.to_degrees())
+}
+
+/// Position on a polar sky chart of radius 1 for an altitude and azimuth in degrees, the zenith
+/// at the center, the horizon on the circle, north up and east on the left as seen looking up
+pub fn chart_position(altitude: f64, azimuth: f64) -> (f64, f64) {
+    let r = (90.0 - altitude) / 90.0;
+    let azimuth = azimuth.to_radians();
+    (-r * azimuth.sin(), -r * azimuth.cos())
+}
+
+/// (altitude, azimuth) points in degrees of a path or an outline on the sky chart
+pub type ChartPath = Vec<(f64, f64)>;
+
+/// AvoidanceChart struct
+///
+/// Sky chart of the observation window: the paths of the targets and the avoidance zones, as
+/// (altitude, azimuth) points in degrees.
+///
+/// # Attributes
+///
+/// * `zones` - Object and outline of each zone at the start, the middle and the end of the window
+/// * `moon` - Path of the Moon during the window
+/// * `targets` - Name, path and whether the target enters a zone
+#[derive(Debug, Clone, PartialEq)]
+pub struct AvoidanceChart {
+    pub zones: Vec<(AvoidanceBody, ChartPath)>,
+    pub moon: ChartPath,
+    pub targets: Vec<(String, ChartPath, bool)>,
+}
+
+impl AvoidanceChart {
+    pub fn new(observer: &Observer, targets: &[Target], zones: &[AvoidanceZone], jd_start: f64,
+               jd_end: f64) -> AvoidanceChart {
+        let steps = ((jd_end - jd_start) / STEP).ceil().max(1.0) as usize;
+        let jds: Vec<f64> = (0..=steps).map(|i| jd_start + (jd_end - jd_start) * i as f64 / steps as f64).collect();
+        let path = |ra: f64, dec: f64| -> ChartPath {
+            jds.iter().map(|&jd| observer.altaz(ra, dec, jd)).collect()
+        };
+        let moon = jds
+            .iter()
+            .map(|&jd| {
+                let (ra, dec) = AvoidanceBody::Moon.position(observer, jd);
+                observer.altaz(ra, dec, jd)
+            })
+            .collect();
+        let outlines = zones
+            .iter()
+            .flat_map(|zone| {
+                [jd_start, (jd_start + jd_end) / 2.0, jd_end].map(|jd| {
+                    let (ra, dec) = zone.center(observer, jd);
+                    let outline = (0..=OUTLINE_POINTS)
+                        .map(|i| {
+                            let angle = 360.0 * i as f64 / OUTLINE_POINTS as f64;
+                            let (ra, dec) = circle_point(ra, dec, zone.radius, angle);
+                            observer.altaz(ra, dec, jd)
+                        })
+                        .collect();
+                    (zone.body, outline)
+                })
+            })
+            .collect();
+        AvoidanceChart {
+            zones: outlines,
+            moon,
+            targets: targets
+                .iter()
+                .map(|t| {
+                    let conflicts = avoidance_conflicts(t, observer, zones, jd_start, jd_end);
+                    (t.name.clone(), path(t.ra, t.dec), !conflicts.is_empty())
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::application::avoidance::{avoidance_conflicts, avoidance_zones, chart_position, circle_point,
+                                        AvoidanceBody, AvoidanceChart};
+    use crate::application::observer::Observer;
+    use crate::application::target::Target;
+    use crate::application::time::Time;
+    use crate::application::transformations::angular_separation;
+
+    #[test]
+    fn test_circle_point() {
+        for (ra, dec) in [(10.0, 0.0), (200.0, 45.0), (350.0, -70.0)] {
+            for angle in [0.0, 90.0, 135.0, 270.0] {
+                let (point_ra, point_dec) = circle_point(ra, dec, 20.0, angle);
+                assert!((angular_separation(ra, dec, point_ra, point_dec) - 20.0).abs() < 1e-6);
+            }
+        }
+        // north and east of the center
+        assert!((circle_point(10.0, 0.0, 20.0, 0.0).1 - 20.0).abs() < 1e-9);
+        assert!((circle_point(10.0, 0.0, 20.0, 90.0).0 - 30.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_chart_position() {
+        let close = |a: (f64, f64), b: (f64, f64)| (a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9;
+        assert!(close(chart_position(90.0, 123.0), (0.0, 0.0)));
+        assert!(close(chart_position(0.0, 0.0), (0.0, -1.0)));
+        assert!(close(chart_position(0.0, 90.0), (-1.0, 0.0)));
+        assert!(close(chart_position(45.0, 180.0), (0.0, 0.5)));
+    }
+
+    #[test]
+    fn test_avoidance_conflicts() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        // full Moon of 2025 March 14, high in the sky at local midnight
+        let jd = Time::new(2025, 3, 14, 3, 0, 0).to_jd();
+        let (moon_ra, moon_dec) = AvoidanceBody::Moon.position(&observer, jd);
+        let zones = avoidance_zones(30);
+        let (start, end) = (jd - 1.0 / 24.0, jd + 1.0 / 24.0);
+
+        let near = Target::new("Near", moon_ra, moon_dec + 5.0, 10.0);
+        let conflicts = avoidance_conflicts(&near, &observer, &zones, start, end);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].body, AvoidanceBody::Moon);
+        assert!(conflicts[0].separation < 6.0);
+        assert!(conflicts[0].jd >= start && conflicts[0].jd <= end);
+
+        // a smaller radius leaves it out, as does a target on the other side of the sky
+        assert!(avoidance_conflicts(&near, &observer, &avoidance_zones(3), start, end).is_empty());
+        let far = Target::new("Far", (moon_ra + 180.0) % 360.0, -moon_dec, 10.0);
+        assert!(avoidance_conflicts(&far, &observer, &zones, start, end).is_empty());
+
+        let chart = AvoidanceChart::new(&observer, &[near, far], &zones, start, end);
+        assert_eq!(chart.zones.len(), 3);
+        assert_eq!(chart.targets.iter().map(|t| t.2).collect::<Vec<bool>>(), vec![true, false]);
+        assert_eq!(chart.moon.len(), chart.targets[0].1.len());
+        // the outline is the radius away from the Moon
+        let (moon_altitude, _) = chart.moon[chart.moon.len() / 2];
+        let highest = chart.zones[1].1.iter().map(|p| p.0).fold(f64::MIN, f64::max);
+        assert!(highest <= (moon_altitude + 30.0).min(90.0) + 0.5);
+    }
+}
@@ -0,0 +1,180 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Accuracy of the night computations. The darkness events scan a grid of Sun and Moon
+// altitudes, a finer grid and the full lunar theory give times to the second at the cost of
+// many Moon positions per night, a coarse grid and the short lunar series are enough for a
+// quick look on a slow machine. Refraction lifts the Sun and the Moon by about 34' at the
+// horizon, turning it off gives the geometric rise and set times.
+
+use serde::{Deserialize, Serialize};
+use crate::application::moon::MOON_HORIZON;
+use crate::application::sun::TwilightType;
+
+/// Refraction at the horizon in degrees, included in the rise and set altitudes
+pub const HORIZON_REFRACTION: f64 = 34.0 / 60.0;
+
+/// Coarsest and finest grids, every 15 minutes and every 10 seconds
+pub const MIN_GRID_POINTS: usize = 96;
+pub const MAX_GRID_POINTS: usize = 8_640;
+
+/// Lunar theory used for the Moon positions
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MoonModel {
+    /// Short series of the main terms, a few tenths of a degree
+    Low,
+    /// Full Meeus (ELP 2000-82) series, a few arcseconds
+    #[default]
+    High,
+}
+
+/// CalculationSettings struct
+///
+/// Trade between speed and accuracy of the Sun, Moon and darkness computations.
+///
+/// # Attributes
+///
+/// * `grid_points` - Sun and Moon altitudes computed per day by the darkness search
+/// * `moon_model` - Lunar theory of the Moon positions
+/// * `refraction` - Include the atmospheric refraction in the rise and set altitudes
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct CalculationSettings {
+    #[serde(default = "default_grid_points")]
+    pub grid_points: usize,
+    #[serde(default)]
+    pub moon_model: MoonModel,
+    #[serde(default = "default_refraction")]
+    pub refraction: bool,
+}
+
+pub fn default_grid_points() -> usize {
+    1_440
+}
+
+pub fn default_refraction() -> bool {
+    true
+}
+
+impl Default for CalculationSettings {
+    fn default() -> Self {
+        CalculationSettings {
+            grid_points: default_grid_points(),
+            moon_model: MoonModel::default(),
+            refraction: default_refraction(),
+        }
+    }
+}
+
+/// Grid and lunar theory offered together in the preferences
+#[derive(Debug, Clone, Copy)]
+pub struct AccuracyPreset {
+    pub name: &'static str,
+    pub grid_points: usize,
+    pub moon_model: MoonModel,
+}
+
+/// Presets offered in the preferences, from the fastest to the most precise: a grid every 5
+/// minutes with the short lunar series, every minute and every 10 seconds with the full theory
+pub const ACCURACY_PRESETS: [AccuracyPreset; 3] = [
+    AccuracyPreset { name: "Fast", grid_points: 288, moon_model: MoonModel::Low },
+    AccuracyPreset { name: "Standard", grid_points: 1_440, moon_model: MoonModel::High },
+    AccuracyPreset { name: "Precise", grid_points: 8_640, moon_model: MoonModel::High },
+];
+
+impl CalculationSettings {
+    /// Grid points per day within the supported range, a hand edited configuration may hold any
+    /// value
+    pub fn points_per_day(&self) -> usize {
+        self.grid_points.clamp(MIN_GRID_POINTS, MAX_GRID_POINTS)
+    }
+
+    /// Hours between two points of the grid
+    pub fn hours_per_point(&self) -> f64 {
+        24.0 / self.points_per_day() as f64
+    }
+
+    /// Index in `ACCURACY_PRESETS` of the preset with this grid and lunar theory, None for
+    /// custom settings
+    pub fn preset(&self) -> Option<usize> {
+        ACCURACY_PRESETS.iter()
+            .position(|p| p.grid_points == self.grid_points && p.moon_model == self.moon_model)
+    }
+
+    /// Settings with the grid and the lunar theory of a preset, the refraction is kept
+    pub fn with_preset(mut self, preset: &AccuracyPreset) -> Self {
+        self.grid_points = preset.grid_points;
+        self.moon_model = preset.moon_model;
+        self
+    }
+
+    /// Altitude of the Sun's center at the twilight angle, sunrise and sunset drop the
+    /// refraction when it is off. The twilight angles are geometric already.
+    pub fn twilight_angle(&self, twilight: TwilightType) -> f64 {
+        match twilight {
+            TwilightType::RiseSet if !self.refraction => twilight.angle() + HORIZON_REFRACTION,
+            _ => twilight.angle(),
+        }
+    }
+
+    /// Topocentric altitude of the Moon's center at rise and set
+    pub fn moon_horizon(&self) -> f64 {
+        if self.refraction { MOON_HORIZON } else { MOON_HORIZON + HORIZON_REFRACTION }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::application::calculation::{CalculationSettings, MoonModel, ACCURACY_PRESETS};
+    use crate::application::environment::Environment;
+    use crate::application::darkness::Darkness;
+    use crate::application::observer::Observer;
+    use crate::application::sun::{RiseSetType, Sun, TwilightType};
+    use crate::application::time::Time;
+
+    #[test]
+    fn test_calculation_settings() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let time = Time::new(2025, 3, 14, 12, 0, 0);
+        let environment = Environment::default();
+
+        // the geometric sunset comes a few minutes before the apparent one
+        let geometric = CalculationSettings { refraction: false, ..CalculationSettings::default() };
+        let apparent = Sun::new(&observer, &time, &environment)
+            .get_sunset_utc(RiseSetType::Next, TwilightType::RiseSet).jd().unwrap();
+        let sunset = Sun::new(&observer, &time, &environment).with_settings(geometric)
+            .get_sunset_utc(RiseSetType::Next, TwilightType::RiseSet).jd().unwrap();
+        assert!((apparent - sunset) * 1_440.0 > 2.0 && (apparent - sunset) * 1_440.0 < 4.0);
+
+        // the fast preset agrees with the standard one within its grid step, the Moon rises
+        // during the night after the last quarter
+        let time = Time::new(2025, 3, 21, 12, 0, 0);
+        let fast = CalculationSettings::default().with_preset(&ACCURACY_PRESETS[0]);
+        assert_eq!(fast.moon_model, MoonModel::Low);
+        assert_eq!(fast.preset(), Some(0));
+        assert_eq!(CalculationSettings::default().preset(), Some(1));
+        let standard = Darkness::new(&observer, &time, &environment).moon_free_duration(TwilightType::NauticalTwilight);
+        let coarse = Darkness::new(&observer, &time, &environment).with_settings(fast)
+            .moon_free_duration(TwilightType::NauticalTwilight);
+        assert!((standard - coarse).abs() <= 2.0 * fast.hours_per_point());
+    }
+}
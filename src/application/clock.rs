@@ -0,0 +1,158 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Wall clock jumps for the windows that follow the current time. When the computer sleeps the
+// event loop stops, after resume the windows must notice that hours went by and recompute
+// instead of showing the night and forecast from before the sleep.
+
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use crate::application::time::Time;
+
+/// Difference between the wall clock and the loop clock, in seconds, taken as a jump
+pub const JUMP_SECONDS: f64 = 30.0;
+
+/// Pause of the loop, in seconds, taken as a jump even when the loop clock counted it. The
+/// monotonic clock stops during sleep on Linux but may keep counting on other systems.
+pub const STALL_SECONDS: f64 = 300.0;
+
+fn wall_seconds() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0)
+}
+
+/// WallClock struct
+///
+/// Detects sleep and resume, and clock changes, between two iterations of an event loop.
+///
+/// # Attributes
+///
+/// * `last_wall` - Wall clock at the previous tick, seconds since the Unix epoch
+/// * `last_tick` - Monotonic clock at the previous tick
+#[derive(Debug, Clone)]
+pub struct WallClock {
+    last_wall: f64,
+    last_tick: Instant,
+}
+
+impl Default for WallClock {
+    fn default() -> Self {
+        WallClock {
+            last_wall: wall_seconds(),
+            last_tick: Instant::now(),
+        }
+    }
+}
+
+impl WallClock {
+    /// Call once per loop iteration, the wall clock jump in seconds after a sleep or a clock
+    /// change, None while both clocks agree
+    pub fn tick(&mut self) -> Option<f64> {
+        let elapsed = self.last_tick.elapsed().as_secs_f64();
+        self.last_tick = Instant::now();
+        self.advance(wall_seconds(), elapsed)
+    }
+
+    fn advance(&mut self, wall: f64, elapsed: f64) -> Option<f64> {
+        let gap = wall - self.last_wall;
+        self.last_wall = wall;
+        let drift = gap - elapsed;
+        (drift.abs() > JUMP_SECONDS || gap > STALL_SECONDS).then_some(gap)
+    }
+}
+
+/// Observing night of a UTC Julian Date, counted from local noon to local noon so that the
+/// hours after midnight belong to the night that started the evening before
+pub fn observing_night(jd: f64, timezone: f64) -> i64 {
+    observing_night_at(jd, timezone, 12.0)
+}
+
+/// Observing night of a UTC Julian Date with the night changing at `rollover_hour` local time
+/// instead of noon, the night keeps the number of the local date it starts on
+pub fn observing_night_at(jd: f64, timezone: f64, rollover_hour: f64) -> i64 {
+    (jd + (timezone + 12.0 - rollover_hour) / 24.0).floor() as i64
+}
+
+/// Time of a UTC Julian Date with the local date the observing night started on, the date the
+/// darkness window shows for that night. The UTC date is the next day in the evening west of
+/// Greenwich and after midnight everywhere.
+pub fn observing_night_date(jd: f64, timezone: f64, rollover_hour: f64) -> Time {
+    let mut time = Time::from_jd(jd);
+    let start = Time::from_jd(jd + (timezone - rollover_hour) / 24.0);
+    (time.year, time.month, time.day) = (start.year, start.month, start.day);
+    time
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::application::time::Time;
+
+    #[test]
+    fn test_wall_clock() {
+        let mut clock = WallClock::default();
+        let start = clock.last_wall;
+
+        // loop running normally
+        assert_eq!(clock.advance(start + 0.032, 0.032), None);
+        // six hours of sleep the loop clock did not see
+        assert_eq!(clock.advance(start + 21600.032, 0.040), Some(21600.0));
+        // clock set back by a time sync after resume
+        assert!(clock.advance(start + 21540.1, 0.032).unwrap() < 0.0);
+        // sleep counted by the loop clock, caught by the pause
+        assert!(clock.advance(start + 25140.1, 3600.0).is_some());
+        // a few seconds blocked in a nested dialog is not a jump
+        assert_eq!(clock.advance(start + 25150.1, 10.0), None);
+    }
+
+    #[test]
+    fn test_observing_night() {
+        // 2025-03-28 21:00 and 2025-03-29 03:00 local at UTC-3 are the same night
+        let evening = Time::new(2025, 3, 29, 0, 0, 0).to_jd();
+        let morning = Time::new(2025, 3, 29, 6, 0, 0).to_jd();
+        let next_evening = Time::new(2025, 3, 30, 0, 0, 0).to_jd();
+        assert_eq!(observing_night(evening, -3.0), observing_night(morning, -3.0));
+        assert_eq!(observing_night(next_evening, -3.0), observing_night(evening, -3.0) + 1);
+        // local noon starts the next night
+        let noon = Time::new(2025, 3, 29, 15, 0, 0).to_jd();
+        assert_eq!(observing_night(noon, -3.0), observing_night(next_evening, -3.0));
+
+        // with the rollover at 06:00 local the morning still belongs to the night before
+        let dawn = Time::new(2025, 3, 29, 8, 0, 0).to_jd();
+        let after_rollover = Time::new(2025, 3, 29, 9, 30, 0).to_jd();
+        assert_eq!(observing_night_at(dawn, -3.0, 6.0), observing_night(evening, -3.0));
+        assert_eq!(observing_night_at(after_rollover, -3.0, 6.0), observing_night(next_evening, -3.0));
+        assert_eq!(observing_night_at(noon, -3.0, 12.0), observing_night(noon, -3.0));
+
+        // 22:00 local on the 28th at UTC-3 is the 29th in UTC, the night is the one of the 28th
+        let night = observing_night_date(Time::new(2025, 3, 29, 1, 0, 0).to_jd(), -3.0, 12.0);
+        assert_eq!((night.year, night.month, night.day, night.hour), (2025, 3, 28, 1));
+        let night = observing_night_date(dawn, -3.0, 6.0);
+        assert_eq!((night.month, night.day), (3, 28));
+        let night = observing_night_date(after_rollover, -3.0, 6.0);
+        assert_eq!((night.month, night.day), (3, 29));
+        // east of Greenwich the evening is still the same date in UTC
+        let night = observing_night_date(Time::new(2025, 3, 28, 20, 0, 0).to_jd(), 2.0, 12.0);
+        assert_eq!((night.month, night.day), (3, 28));
+    }
+}
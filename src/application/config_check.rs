@@ -0,0 +1,254 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+
+// Validation of a configuration file as it is loaded. The parser alone reads an out of range
+// latitude as 0 and skips a misspelled key, here values out of their range are errors naming the
+// field and its line, and fields this version does not know are warnings.
+
+use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
+use serde_yaml::Value;
+use crate::application::application::{Application, ConfigFormat};
+use crate::application::constraint::{ALTITUDE_RANGE, MAX_TARGETS_RANGE, MOON_SEPARATION_RANGE,
+                                     OBSERVABLE_TIME_RANGE, SIZE_RANGE};
+use crate::application::observer::{try_degrees_from_str, try_elevation_from_str, try_timezone_from_str};
+
+/// Environment values accepted in a configuration: temperature in °C, relative humidity in
+/// percent and pressure in hPa, 0 when it was never set
+pub const TEMPERATURE_RANGE: RangeInclusive<i64> = -60..=60;
+pub const HUMIDITY_RANGE: RangeInclusive<i64> = 0..=100;
+pub const PRESSURE_RANGE: RangeInclusive<i64> = 0..=1100;
+
+// Sections whose keys are compared with the ones this version writes
+const CHECKED_SECTIONS: [&str; 5] = ["observer", "environment", "constraints", "equipment", "preferences"];
+
+// Parser of an observer field written as text
+type TextCheck = fn(&str) -> Result<(), String>;
+
+// Observer fields written as text, checked with the parsers of the observatory setup
+const TEXT_FIELDS: [(&str, TextCheck); 4] = [
+    ("latitude", |text| try_degrees_from_str(text, -90.0, 90.0).map(|_| ())),
+    ("longitude", |text| try_degrees_from_str(text, -180.0, 180.0).map(|_| ())),
+    ("elevation", |text| try_elevation_from_str(text).map(|_| ())),
+    ("timezone", |text| try_timezone_from_str(text).map(|_| ())),
+];
+
+// Number fields and their ranges, the temperature may have decimals
+const RANGE_FIELDS: [(&str, &str, RangeInclusive<i64>); 10] = [
+    ("environment", "temperature", TEMPERATURE_RANGE),
+    ("environment", "humidity", HUMIDITY_RANGE),
+    ("environment", "pressure", PRESSURE_RANGE),
+    ("constraints", "min_altitude", ALTITUDE_RANGE),
+    ("constraints", "max_altitude", ALTITUDE_RANGE),
+    ("constraints", "min_size", SIZE_RANGE),
+    ("constraints", "max_size", SIZE_RANGE),
+    ("constraints", "moon_separation", MOON_SEPARATION_RANGE),
+    ("constraints", "frac_observable_time", OBSERVABLE_TIME_RANGE),
+    // 0 in configurations saved before the constraints were set, no target is listed
+    ("constraints", "max_targets", 0..=*MAX_TARGETS_RANGE.end()),
+];
+
+/// ConfigIssue struct
+///
+/// Problem found in a configuration file.
+///
+/// # Attributes
+///
+/// * `line` - Line of the field in the file, from 1, None when the message already tells it
+/// * `field` - Section and key of the field, e.g. `observer.latitude`, empty for the whole file
+/// * `message` - What is wrong, with the expected range when there is one
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigIssue {
+    pub line: Option<usize>,
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "Line {}: ", line)?;
+        }
+        if !self.field.is_empty() {
+            write!(f, "{}: ", self.field)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Issues that keep a configuration from loading, one per line of the message
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError(pub Vec<ConfigIssue>);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lines: Vec<String> = self.0.iter().map(|issue| issue.to_string()).collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Error for ConfigError {}
+
+/// Generic tree of a configuration file, the error tells where the syntax is wrong
+pub(crate) fn config_tree(contents: &str, format: ConfigFormat) -> Result<Value, String> {
+    match format {
+        ConfigFormat::Yaml => serde_yaml::from_str(contents).map_err(|e| e.to_string()),
+        ConfigFormat::Json => serde_json::from_str::<serde_json::Value>(contents)
+            .map_err(|e| e.to_string())
+            .and_then(|value| serde_yaml::to_value(value).map_err(|e| e.to_string())),
+        ConfigFormat::Toml => toml::from_str::<toml::Value>(contents)
+            .map_err(|e| e.to_string())
+            .and_then(|value| serde_yaml::to_value(value).map_err(|e| e.to_string())),
+    }
+}
+
+// Line of `key` in `section`, the first one after the start of the section, None when not found
+fn field_line(contents: &str, format: ConfigFormat, section: &str, key: &str) -> Option<usize> {
+    let starts = |line: &str, name: &str| {
+        let line = line.trim_start();
+        match format {
+            ConfigFormat::Yaml => line.strip_prefix(name).is_some_and(|rest| rest.starts_with(':')),
+            ConfigFormat::Json => line.starts_with(&format!("\"{}\"", name)),
+            ConfigFormat::Toml => line == format!("[{}]", name)
+                || line.strip_prefix(name).is_some_and(|rest| rest.trim_start().starts_with('=')),
+        }
+    };
+    let mut lines = contents.lines().enumerate();
+    lines.find(|(_, line)| starts(line, section))?;
+    lines.find(|(_, line)| starts(line, key)).map(|(i, _)| i + 1)
+}
+
+/// Checks a configuration file and parses it. Values out of range, wrong types and syntax errors
+/// are returned together as the error; with none, the application comes with the warnings about
+/// the fields that are ignored.
+pub fn validate_config(contents: &str, format: ConfigFormat) -> Result<(Application, Vec<ConfigIssue>), ConfigError> {
+    let tree = config_tree(contents, format).map_err(|message| {
+        ConfigError(vec![ConfigIssue { line: None, field: String::new(), message }])
+    })?;
+    let issue = |section: &str, key: &str, message: String| ConfigIssue {
+        line: field_line(contents, format, section, key),
+        field: format!("{}.{}", section, key),
+        message,
+    };
+    let field = |section: &str, key: &str| tree.get(section).and_then(|fields| fields.get(key));
+    let mut errors: Vec<ConfigIssue> = Vec::new();
+    let mut warnings: Vec<ConfigIssue> = Vec::new();
+
+    for (key, check) in TEXT_FIELDS {
+        let text = match field("observer", key) {
+            Some(Value::String(text)) => text.clone(),
+            Some(Value::Number(number)) => number.to_string(),
+            _ => continue,
+        };
+        if let Err(message) = check(&text) {
+            errors.push(issue("observer", key, message));
+        }
+    }
+    for (section, key, range) in RANGE_FIELDS {
+        if let Some(value) = field(section, key).and_then(Value::as_f64) {
+            if !(*range.start() as f64..=*range.end() as f64).contains(&value) {
+                errors.push(issue(section, key, format!("{} is out of range, from {} to {}", value, range.start(),
+                                                        range.end())));
+            }
+        }
+    }
+    let value = |key: &str| field("constraints", key).and_then(Value::as_i64);
+    if let (Some(min), Some(max)) = (value("min_altitude"), value("max_altitude")) {
+        if min > max {
+            errors.push(issue("constraints", "min_altitude", format!("{} is above max_altitude {}", min, max)));
+        }
+    }
+    if let (Some(min), Some(max)) = (value("min_size"), value("max_size")) {
+        if min > max {
+            errors.push(issue("constraints", "min_size", format!("{} is larger than max_size {}", min, max)));
+        }
+    }
+
+    // keys of the sections as this version writes them
+    let known = serde_yaml::to_value(Application::default()).unwrap_or(Value::Null);
+    for section in CHECKED_SECTIONS {
+        let (Some(Value::Mapping(fields)), Some(Value::Mapping(known_fields))) = (tree.get(section), known.get(section))
+        else {
+            continue;
+        };
+        for key in fields.keys().filter(|key| !known_fields.contains_key(*key)) {
+            let name = key.as_str().map_or_else(|| format!("{:?}", key), |name| name.to_string());
+            warnings.push(issue(section, &name, "is not a known field and is ignored".to_string()));
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(ConfigError(errors));
+    }
+    // wrong types and missing sections, the message of the parser tells the field and the line
+    let application = format.parse(contents).map_err(|e| {
+        ConfigError(vec![ConfigIssue { line: None, field: String::new(), message: e.to_string() }])
+    })?;
+    Ok((application, warnings))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_validate_config() {
+        let base = include_str!("../../config.yaml");
+        let (application, warnings) = validate_config(base, ConfigFormat::Yaml).unwrap();
+        assert_eq!(application.constraints.min_altitude, 30);
+        assert!(warnings.is_empty());
+
+        // out of range values name the field, its line and the range
+        let wrong = base.replace("latitude: 23d 06m S", "latitude: 95").replace("humidity: 45", "humidity: 120");
+        let errors = validate_config(&wrong, ConfigFormat::Yaml).unwrap_err().0;
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].to_string(), "Line 4: observer.latitude: 95 is out of range, from -90 to 90");
+        assert_eq!(errors[1].to_string(), "Line 13: environment.humidity: 120 is out of range, from 0 to 100");
+
+        let crossed = base.replace("min_altitude: 30", "min_altitude: 85");
+        let errors = validate_config(&crossed, ConfigFormat::Yaml).unwrap_err().0;
+        assert_eq!(errors[0].field, "constraints.min_altitude");
+        assert_eq!(errors[0].line, Some(16));
+
+        // an unknown field loads with a warning
+        let misspelled = base.replace("moon_separation: 45", "moon_separation: 45\n  moon_sep: 30");
+        let (_, warnings) = validate_config(&misspelled, ConfigFormat::Yaml).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].to_string(), "Line 21: constraints.moon_sep: is not a known field and is ignored");
+
+        // wrong types and syntax errors keep the message of the parser, which has the line
+        let typed = base.replace("max_targets: 60", "max_targets: many");
+        let message = validate_config(&typed, ConfigFormat::Yaml).unwrap_err().to_string();
+        assert!(message.contains("max_targets") && message.contains("line"), "{}", message);
+        assert!(validate_config("observer: [", ConfigFormat::Yaml).is_err());
+
+        // the other formats find the lines too
+        let json = "{\n  \"observer\": {\n    \"latitude\": 91,\n    \"longitude\": 0\n  }\n}";
+        let errors = validate_config(json, ConfigFormat::Json).unwrap_err().0;
+        assert_eq!(errors[0].line, Some(3));
+        let toml = "[observer]\nlatitude = \"10\"\nlongitude = \"200\"\n";
+        let errors = validate_config(toml, ConfigFormat::Toml).unwrap_err().0;
+        assert_eq!((errors[0].field.as_str(), errors[0].line), ("observer.longitude", Some(3)));
+    }
+}
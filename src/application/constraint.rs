@@ -20,12 +20,19 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
 // IN THE SOFTWARE.
 
-// TODO Implement test
 #![allow(dead_code, unused_variables)]
 
 use serde::{Deserialize, Deserializer, Serialize};
+use std::ops::RangeInclusive;
 
-#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+// Values accepted in the constraint setup, sizes in arcminutes and angles in degrees
+pub const ALTITUDE_RANGE: RangeInclusive<i64> = 0..=90;
+pub const SIZE_RANGE: RangeInclusive<i64> = 0..=3600;
+pub const MOON_SEPARATION_RANGE: RangeInclusive<i64> = 0..=180;
+pub const OBSERVABLE_TIME_RANGE: RangeInclusive<i64> = 0..=100;
+pub const MAX_TARGETS_RANGE: RangeInclusive<i64> = 1..=1000;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
 pub struct Constraints {
     #[serde(
         default = "default_min_altitude",
@@ -223,6 +230,34 @@ impl Constraints {
     }
 }
 
+impl Constraints {
+    /// Checks the values are in their ranges and the minimums are not above the maximums, the
+    /// message tells the user what to fix
+    pub fn check(&self) -> Result<(), String> {
+        let ranges = [
+            ("Min altitude", self.min_altitude, &ALTITUDE_RANGE),
+            ("Max altitude", self.max_altitude, &ALTITUDE_RANGE),
+            ("Min size", self.min_size, &SIZE_RANGE),
+            ("Max size", self.max_size, &SIZE_RANGE),
+            ("Moon separation", self.moon_separation, &MOON_SEPARATION_RANGE),
+            ("Observable time", self.frac_observable_time, &OBSERVABLE_TIME_RANGE),
+            ("Max targets", self.max_targets, &MAX_TARGETS_RANGE),
+        ];
+        for (name, value, range) in ranges {
+            if !range.contains(&value) {
+                return Err(format!("{} must be from {} to {}", name, range.start(), range.end()));
+            }
+        }
+        if self.min_altitude > self.max_altitude {
+            return Err("Min altitude is above max altitude".to_string());
+        }
+        if self.min_size > self.max_size {
+            return Err("Min size is larger than max size".to_string());
+        }
+        Ok(())
+    }
+}
+
 // TODO Update
 impl std::fmt::Display for Constraints {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -233,3 +268,20 @@ impl std::fmt::Display for Constraints {
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let constraints = Constraints::default().new(20, 80, 10, 300, 45, 50, 50, false);
+        assert!(constraints.check().is_ok());
+        let low = Constraints { min_altitude: 85, ..constraints.clone() };
+        assert_eq!(low.check().unwrap_err(), "Min altitude is above max altitude");
+        let targets = Constraints { max_targets: 0, ..constraints.clone() };
+        assert_eq!(targets.check().unwrap_err(), "Max targets must be from 1 to 1000");
+        let size = Constraints { min_size: 400, ..constraints };
+        assert!(size.check().is_err());
+    }
+}
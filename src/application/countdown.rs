@@ -0,0 +1,158 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Upcoming Sun, twilight and Moon events counted down from the current time, for the countdown
+// panel used at the telescope.
+
+use crate::application::calculation::CalculationSettings;
+use crate::application::environment::Environment;
+use crate::application::moon::Moon;
+use crate::application::observer::Observer;
+use crate::application::sun::{CrossingDirection, RiseSetType, Sun, TwilightType};
+use crate::application::time::Time;
+
+/// Countdown struct
+///
+/// Next occurrence of an event.
+///
+/// # Attributes
+///
+/// * `event` - Name of the event
+/// * `jd` - Julian Date of the event in UTC
+#[derive(Debug, Clone, PartialEq)]
+pub struct Countdown {
+    pub event: &'static str,
+    pub jd: f64,
+}
+
+// Sun crossings in night order: name, twilight and direction
+pub(crate) const SUN_EVENTS: [(&str, TwilightType, CrossingDirection); 8] = [
+    ("Sunset", TwilightType::RiseSet, CrossingDirection::Setting),
+    ("Civil twilight end", TwilightType::CivilTwilight, CrossingDirection::Setting),
+    ("Nautical twilight end", TwilightType::NauticalTwilight, CrossingDirection::Setting),
+    ("Astronomical darkness", TwilightType::AstronomicalTwilight, CrossingDirection::Setting),
+    ("Astronomical twilight start", TwilightType::AstronomicalTwilight, CrossingDirection::Rising),
+    ("Nautical twilight start", TwilightType::NauticalTwilight, CrossingDirection::Rising),
+    ("Civil twilight start", TwilightType::CivilTwilight, CrossingDirection::Rising),
+    ("Sunrise", TwilightType::RiseSet, CrossingDirection::Rising),
+];
+
+/// Next occurrence after jd_now of every Sun, twilight and Moon event, sorted by time
+///
+/// The rise/set searches cover one night (local noon to local noon), so the night before, the
+/// current one and the next one are searched to find the first occurrence after jd_now. Events
+/// that do not happen (polar day or night, circumpolar Moon) are left out. Sunrise, sunset and the
+/// Moon follow the refraction of the accuracy settings.
+pub fn upcoming_events(observer: &Observer, environment: &Environment, settings: CalculationSettings,
+                       jd_now: f64) -> Vec<Countdown> {
+    let mut events: Vec<Countdown> = Vec::new();
+    let mut add = |event: &'static str, jd: Option<f64>| {
+        let Some(jd) = jd.filter(|&jd| jd > jd_now) else { return };
+        match events.iter_mut().find(|c| c.event == event) {
+            Some(countdown) if countdown.jd <= jd => {}
+            Some(countdown) => countdown.jd = jd,
+            None => events.push(Countdown { event, jd }),
+        }
+    };
+
+    for day in [-1.0, 0.0, 1.0] {
+        let time = Time::from_jd(jd_now + day);
+        let sun = Sun::new(observer, &time, environment).with_settings(settings);
+        for (event, twilight, direction) in SUN_EVENTS {
+            add(event, sun.crossing_time(settings.twilight_angle(twilight), direction, RiseSetType::Next).jd());
+        }
+        let moon = Moon::new(observer, &time, environment).with_settings(settings);
+        add("Moonrise", moon.get_moonrise_utc(RiseSetType::Next).jd());
+        add("Moonset", moon.get_moonset_utc(RiseSetType::Next).jd());
+    }
+
+    events.sort_by(|a, b| a.jd.total_cmp(&b.jd));
+    events
+}
+
+/// Time left as "1h12m05s", or "12m05s" under an hour
+pub fn format_countdown(days: f64) -> String {
+    let seconds = (days.max(0.0) * 86_400.0).round() as i64;
+    let (hours, minutes, seconds) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    if hours > 0 {
+        format!("{}h{:02}m{:02}s", hours, minutes, seconds)
+    } else {
+        format!("{}m{:02}s", minutes, seconds)
+    }
+}
+
+/// Status bar line: local time with its UTC offset, UTC time, JD, observatory and time left to
+/// the next event
+pub fn status_line(observer: &Observer, now: &Time, next: Option<&Countdown>) -> String {
+    let jd = now.to_jd();
+    let mut fields = vec![
+        format!("{}", observer.local_time(jd)),
+        format!("UTC {}", now.to_string(Some("hhmmss"))),
+        format!("JD {:.5}", jd),
+    ];
+    if let Some(name) = &observer.name {
+        fields.push(name.clone());
+    }
+    if let Some(next) = next {
+        fields.push(format!("{} in {}", next.event, format_countdown(next.jd - jd)));
+    }
+    fields.join("   |   ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_upcoming_events() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let environment = Environment::default();
+        // 2025-03-20 15:00 local, sunset at 18:17 and sunrise at 06:10 the next morning
+        let now = Time::new(2025, 3, 20, 18, 0, 0).to_jd();
+        let events = upcoming_events(&observer, &environment, CalculationSettings::default(), now);
+
+        assert_eq!(events.len(), 10);
+        assert_eq!(events[0].event, "Sunset");
+        assert!((events[0].jd - now - (3.0 + 17.0 / 60.0) / 24.0).abs() < 2.0 / 1440.0);
+        assert!(events.windows(2).all(|pair| pair[0].jd <= pair[1].jd));
+        assert!(events.iter().all(|c| c.jd > now && c.jd < now + 1.5));
+    }
+
+    #[test]
+    fn test_status_line() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let now = Time::new(2025, 3, 20, 21, 0, 0);
+        let next = Countdown { event: "Astronomical darkness", jd: now.to_jd() + 83.0 / 1440.0 };
+        assert_eq!(status_line(&observer, &now, Some(&next)),
+                   "2025-03-20 18:00:00 UTC-03:00   |   UTC 21:00:00   |   JD 2460755.37500   |   \
+                    Astronomical darkness in 1h23m00s");
+        assert_eq!(status_line(&observer, &now, None),
+                   "2025-03-20 18:00:00 UTC-03:00   |   UTC 21:00:00   |   JD 2460755.37500");
+    }
+
+    #[test]
+    fn test_format_countdown() {
+        assert_eq!(format_countdown(1.0 / 24.0 + 12.0 / 1440.0), "1h12m00s");
+        assert_eq!(format_countdown(125.0 / 86_400.0), "2m05s");
+        assert_eq!(format_countdown(-1.0), "0m00s");
+    }
+}
@@ -20,18 +20,24 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
 // IN THE SOFTWARE.
 
+use crate::application::calculation::CalculationSettings;
 use crate::application::environment::Environment;
-use crate::application::moon::moon_alt_az_grid_utc;
+use crate::application::formatting::{format_darkness, FormatTime};
+use crate::application::preferences::Preferences;
+use std::rc::Rc;
+use crate::application::ephemeris_cache::Grid;
+use crate::application::moon::Moon;
 use crate::application::observer::Observer;
-use crate::application::sun::{sun_alt_az_grid_utc, TwilightType};
+use crate::application::sun::{Sun, SunEvent, TwilightType};
 use crate::application::sun::TwilightType::{AstronomicalTwilight, CivilTwilight, NauticalTwilight, RiseSet};
-use crate::application::time::Time;
+use crate::application::time::{LocalTime, Time};
 
 #[derive(Debug)]
 pub struct Darkness<'a> {
     pub observer: &'a Observer,
     pub time: &'a Time,
     pub environment: &'a Environment,
+    pub settings: CalculationSettings,
 }
 
 impl<'a> Darkness<'a> {
@@ -40,35 +46,31 @@ impl<'a> Darkness<'a> {
             observer,
             time,
             environment,
+            settings: CalculationSettings::default(),
         }
     }
 
-    pub fn darkness_utc(&self, twilight: TwilightType) -> (f64, f64) {
-        const NUM_POINTS: usize = 1440;
-        let target_night_start = (self.time.to_jd() + 0.5).floor() + 3.0 / 24.0;
-        let target_night_end = target_night_start + 1.0;
+    /// Darkness with the accuracy settings of the preferences instead of the defaults
+    pub fn with_settings(mut self, settings: CalculationSettings) -> Self {
+        self.settings = settings;
+        self
+    }
 
-        let sun = sun_alt_az_grid_utc(
-            self.observer.latitude,
-            self.observer.longitude,
-            target_night_start,
-            target_night_end,
-            NUM_POINTS,
-        );
+    // Sun below the twilight angle and Moon below the horizon at a row of the night grids
+    fn is_dark(&self, sun: &(f64, f64, f64), moon: &(f64, f64, f64), twilight: TwilightType) -> bool {
+        sun.1 <= self.settings.twilight_angle(twilight) && moon.1 <= self.settings.moon_horizon()
+    }
 
-        let moon = moon_alt_az_grid_utc(
-            self.observer.latitude,
-            self.observer.longitude,
-            target_night_start,
-            target_night_end,
-            NUM_POINTS,
-        );
+    // Darkness window, when there is no darkness both events tell if the Sun never gets low enough
+    // (AlwaysUp) or if the Moon prevents it (MoonUp)
+    pub fn darkness_utc(&self, twilight: TwilightType) -> (SunEvent, SunEvent) {
+        let (sun, moon) = self.night_grid();
 
         let darkness: Vec<f64> = sun
             .iter()
             .zip(moon.iter())
             .filter_map(|(sun, moon)| {
-                if sun.1 <= twilight.angle() && moon.1 <= 0.125 {
+                if self.is_dark(sun, moon, twilight) {
                     Some(sun.0)
                 } else {
                     None
@@ -76,176 +78,250 @@ impl<'a> Darkness<'a> {
             })
             .collect();
 
-        if darkness.is_empty() {
-            (0.0, 0.0)
-        } else {
-            let start = darkness.iter().cloned().reduce(f64::min).unwrap_or(0.0);
-            let end = darkness.iter().cloned().reduce(f64::max).unwrap_or(0.0);
-            (start, end)
+        log::trace!("darkness searched twilight={:?} points={} dark_points={} moon_model={:?} refraction={}",
+                    twilight, sun.len(), darkness.len(), self.settings.moon_model, self.settings.refraction);
+        match (
+            darkness.iter().cloned().reduce(f64::min),
+            darkness.iter().cloned().reduce(f64::max),
+        ) {
+            (Some(start), Some(end)) => (SunEvent::At(start), SunEvent::At(end)),
+            _ if sun.iter().all(|sun| sun.1 > self.settings.twilight_angle(twilight)) => {
+                (SunEvent::AlwaysUp, SunEvent::AlwaysUp)
+            }
+            _ => (SunEvent::MoonUp, SunEvent::MoonUp),
         }
     }
 
-    fn darkness_utc_helper(&self, twilight: TwilightType) -> (f64, f64) {
+    /// Hours with the Sun below the twilight angle and the Moon below the horizon, the Moon may
+    /// rise and set during the night so this can be shorter than the darkness window
+    pub fn moon_free_duration(&self, twilight: TwilightType) -> f64 {
+        let (sun, moon) = self.night_grid();
+        let points = sun
+            .iter()
+            .zip(moon.iter())
+            .filter(|(sun, moon)| self.is_dark(sun, moon, twilight))
+            .count();
+        points as f64 * self.settings.hours_per_point()
+    }
+
+    // Sun and Moon (jd, alt, az) of the night at the grid resolution of the settings (every
+    // minute by default), shared with the other darkness events of the night through the
+    // ephemeris cache
+    fn night_grid(&self) -> (Rc<Grid>, Rc<Grid>) {
+        let points = self.settings.points_per_day();
+        let sun = Sun::new(self.observer, self.time, self.environment).night_alt_az_grid(points);
+        let moon = Moon::new(self.observer, self.time, self.environment)
+            .with_settings(self.settings)
+            .night_alt_az_grid(points);
+        (sun, moon)
+    }
+
+    fn darkness_utc_helper(&self, twilight: TwilightType) -> (SunEvent, SunEvent) {
         self.darkness_utc(twilight)
     }
 
-    pub fn get_darkness_utc_riseset(&self) -> (f64, f64) {
+    pub fn get_darkness_utc_riseset(&self) -> (SunEvent, SunEvent) {
         self.darkness_utc_helper(RiseSet)
     }
 
-    pub fn get_darkness_utc_civil(&self) -> (f64, f64) {
+    pub fn get_darkness_utc_civil(&self) -> (SunEvent, SunEvent) {
         self.darkness_utc_helper(CivilTwilight)
     }
 
-    pub fn get_darkness_utc_nautical(&self) -> (f64, f64) {
+    pub fn get_darkness_utc_nautical(&self) -> (SunEvent, SunEvent) {
         self.darkness_utc_helper(NauticalTwilight)
     }
 
-    pub fn get_darkness_utc_astronomical(&self) -> (f64, f64) {
+    pub fn get_darkness_utc_astronomical(&self) -> (SunEvent, SunEvent) {
         self.darkness_utc_helper(AstronomicalTwilight)
     }
 
-    pub fn get_darkness_utc_astronomical_or_nautical(&self) -> (&'static str, (f64, f64)) {
+    pub fn get_darkness_utc_astronomical_or_nautical(&self) -> (&'static str, (SunEvent, SunEvent)) {
         let astronomical_darkness = self.get_darkness_utc_astronomical();
+        if astronomical_darkness.0.jd().is_some() {
+            return ("astronomical", astronomical_darkness);
+        }
         let nautical_darkness = self.get_darkness_utc_nautical();
-        if astronomical_darkness == (0.0, 0.0) {
-            if nautical_darkness == (0.0, 0.0) {
-                ("none", (0.0, 0.0))
-            } else {
-                ("nautical", self.get_darkness_utc_nautical())
-            }
+        if nautical_darkness.0.jd().is_some() {
+            ("nautical", nautical_darkness)
         } else {
-            ("astronomical", astronomical_darkness)
+            ("none", nautical_darkness)
         }
     }
 
-    fn to_local_time(&self, utc_darkness: (f64, f64)) -> (f64, f64) {
-        match utc_darkness {
-            (start, end) if start == 0.0 && end == 0.0 => (0.0, 0.0),
-            (start, end) => {
-                let offset = self.observer.timezone / 24.0;
-                (start + offset, end + offset)
-            }
-        }
+    fn to_local_time(&self, utc_darkness: (SunEvent, SunEvent)) -> (SunEvent<LocalTime>, SunEvent<LocalTime>) {
+        let offset = self.observer.timezone;
+        (utc_darkness.0.local(offset), utc_darkness.1.local(offset))
     }
 
-    pub fn get_darkness_local_riseset(&self) -> (f64, f64) {
+    pub fn get_darkness_local_riseset(&self) -> (SunEvent<LocalTime>, SunEvent<LocalTime>) {
         self.to_local_time(self.get_darkness_utc_riseset())
     }
 
-    pub fn get_darkness_local_civil(&self) -> (f64, f64) {
+    pub fn get_darkness_local_civil(&self) -> (SunEvent<LocalTime>, SunEvent<LocalTime>) {
         self.to_local_time(self.get_darkness_utc_civil())
     }
 
-    pub fn get_darkness_local_nautical(&self) -> (f64, f64) {
+    pub fn get_darkness_local_nautical(&self) -> (SunEvent<LocalTime>, SunEvent<LocalTime>) {
         self.to_local_time(self.get_darkness_utc_nautical())
     }
 
-    pub fn get_darkness_local_astronomical(&self) -> (f64, f64) {
+    pub fn get_darkness_local_astronomical(&self) -> (SunEvent<LocalTime>, SunEvent<LocalTime>) {
         self.to_local_time(self.get_darkness_utc_astronomical())
     }
 
-    pub fn get_darkness_local_astronomical_or_nautical(&self) -> (&str, (f64, f64)) {
+    pub fn get_darkness_local_astronomical_or_nautical(
+        &self,
+    ) -> (&str, (SunEvent<LocalTime>, SunEvent<LocalTime>)) {
         let utc = self.get_darkness_utc_astronomical_or_nautical();
         (utc.0, self.to_local_time(utc.1))
     }
 
-    fn format_darkness_time<F>(&self, time_selector: F, start: bool, format: Option<&str>) -> String
+    fn format_darkness_time<T, F>(&self, time_selector: F, start: bool, preferences: &Preferences) -> String
     where
-        F: Fn() -> (f64, f64),
+        T: FormatTime,
+        F: Fn() -> (SunEvent<T>, SunEvent<T>),
     {
-        let (start_time, end_time) = time_selector();
-        let time = if start { start_time } else { end_time };
-        Time::from_jd(time).to_string(format)
+        let (start_event, end_event) = time_selector();
+        let event = if start { start_event } else { end_event };
+        format_darkness(event, preferences)
     }
 
-    pub fn get_darkness_utc_riseset_start_str(&self, format: Option<&str>) -> String {
-        self.format_darkness_time(|| self.get_darkness_utc_riseset(), true, format)
+    pub fn get_darkness_utc_riseset_start_str(&self, preferences: &Preferences) -> String {
+        self.format_darkness_time(|| self.get_darkness_utc_riseset(), true, preferences)
     }
 
-    pub fn get_darkness_utc_riseset_end_str(&self, format: Option<&str>) -> String {
-        self.format_darkness_time(|| self.get_darkness_utc_riseset(), false, format)
+    pub fn get_darkness_utc_riseset_end_str(&self, preferences: &Preferences) -> String {
+        self.format_darkness_time(|| self.get_darkness_utc_riseset(), false, preferences)
     }
 
-    pub fn get_darkness_utc_civil_start_str(&self, format: Option<&str>) -> String {
-        self.format_darkness_time(|| self.get_darkness_utc_civil(), true, format)
+    pub fn get_darkness_utc_civil_start_str(&self, preferences: &Preferences) -> String {
+        self.format_darkness_time(|| self.get_darkness_utc_civil(), true, preferences)
     }
 
-    pub fn get_darkness_local_utc_end_str(&self, format: Option<&str>) -> String {
-        self.format_darkness_time(|| self.get_darkness_utc_civil(), false, format)
+    pub fn get_darkness_local_utc_end_str(&self, preferences: &Preferences) -> String {
+        self.format_darkness_time(|| self.get_darkness_utc_civil(), false, preferences)
     }
 
-    pub fn get_darkness_utc_nautical_start_str(&self, format: Option<&str>) -> String {
-        self.format_darkness_time(|| self.get_darkness_utc_nautical(), true, format)
+    pub fn get_darkness_utc_nautical_start_str(&self, preferences: &Preferences) -> String {
+        self.format_darkness_time(|| self.get_darkness_utc_nautical(), true, preferences)
     }
 
-    pub fn get_darkness_utc_nautical_end_str(&self, format: Option<&str>) -> String {
-        self.format_darkness_time(|| self.get_darkness_utc_nautical(), false, format)
+    pub fn get_darkness_utc_nautical_end_str(&self, preferences: &Preferences) -> String {
+        self.format_darkness_time(|| self.get_darkness_utc_nautical(), false, preferences)
     }
 
-    pub fn get_darkness_utc_astronomical_start_str(&self, format: Option<&str>) -> String {
-        self.format_darkness_time(|| self.get_darkness_utc_astronomical(), true, format)
+    pub fn get_darkness_utc_astronomical_start_str(&self, preferences: &Preferences) -> String {
+        self.format_darkness_time(|| self.get_darkness_utc_astronomical(), true, preferences)
     }
 
-    pub fn get_darkness_utc_astronomical_end_str(&self, format: Option<&str>) -> String {
-        self.format_darkness_time(|| self.get_darkness_utc_astronomical(), false, format)
+    pub fn get_darkness_utc_astronomical_end_str(&self, preferences: &Preferences) -> String {
+        self.format_darkness_time(|| self.get_darkness_utc_astronomical(), false, preferences)
     }
 
     pub fn get_darkness_utc_astronomical_or_nautical_str(
         &self,
-        format: Option<&str>,
+        preferences: &Preferences,
     ) -> (&str, String) {
         let local = self.get_darkness_utc_astronomical_or_nautical();
-        (local.0, self.format_darkness_time(|| local.1, true, format))
+        (local.0, self.format_darkness_time(|| local.1, true, preferences))
     }
-    pub fn get_darkness_local_riseset_start_str(&self, format: Option<&str>) -> String {
-        self.format_darkness_time(|| self.get_darkness_local_riseset(), true, format)
+    pub fn get_darkness_local_riseset_start_str(&self, preferences: &Preferences) -> String {
+        self.format_darkness_time(|| self.get_darkness_local_riseset(), true, preferences)
     }
 
-    pub fn get_darkness_local_riseset_end_str(&self, format: Option<&str>) -> String {
-        self.format_darkness_time(|| self.get_darkness_local_riseset(), false, format)
+    pub fn get_darkness_local_riseset_end_str(&self, preferences: &Preferences) -> String {
+        self.format_darkness_time(|| self.get_darkness_local_riseset(), false, preferences)
     }
 
-    pub fn get_darkness_local_civil_start_str(&self, format: Option<&str>) -> String {
-        self.format_darkness_time(|| self.get_darkness_local_civil(), true, format)
+    pub fn get_darkness_local_civil_start_str(&self, preferences: &Preferences) -> String {
+        self.format_darkness_time(|| self.get_darkness_local_civil(), true, preferences)
     }
 
-    pub fn get_darkness_local_civil_end_str(&self, format: Option<&str>) -> String {
-        self.format_darkness_time(|| self.get_darkness_local_civil(), false, format)
+    pub fn get_darkness_local_civil_end_str(&self, preferences: &Preferences) -> String {
+        self.format_darkness_time(|| self.get_darkness_local_civil(), false, preferences)
     }
 
-    pub fn get_darkness_local_nautical_start_str(&self, format: Option<&str>) -> String {
-        self.format_darkness_time(|| self.get_darkness_local_nautical(), true, format)
+    pub fn get_darkness_local_nautical_start_str(&self, preferences: &Preferences) -> String {
+        self.format_darkness_time(|| self.get_darkness_local_nautical(), true, preferences)
     }
 
-    pub fn get_darkness_local_nautical_end_str(&self, format: Option<&str>) -> String {
-        self.format_darkness_time(|| self.get_darkness_local_nautical(), false, format)
+    pub fn get_darkness_local_nautical_end_str(&self, preferences: &Preferences) -> String {
+        self.format_darkness_time(|| self.get_darkness_local_nautical(), false, preferences)
     }
 
-    pub fn get_darkness_local_astronomical_start_str(&self, format: Option<&str>) -> String {
-        self.format_darkness_time(|| self.get_darkness_local_astronomical(), true, format)
+    pub fn get_darkness_local_astronomical_start_str(&self, preferences: &Preferences) -> String {
+        self.format_darkness_time(|| self.get_darkness_local_astronomical(), true, preferences)
     }
 
-    pub fn get_darkness_local_astronomical_end_str(&self, format: Option<&str>) -> String {
-        self.format_darkness_time(|| self.get_darkness_local_astronomical(), false, format)
+    pub fn get_darkness_local_astronomical_end_str(&self, preferences: &Preferences) -> String {
+        self.format_darkness_time(|| self.get_darkness_local_astronomical(), false, preferences)
     }
 
     pub fn get_darkness_local_astronomical_or_nautical_start_str(
         &self,
-        format: Option<&str>,
+        preferences: &Preferences,
     ) -> (&str, String) {
         let local = self.get_darkness_local_astronomical_or_nautical();
-        (local.0, self.format_darkness_time(|| local.1, true, format))
+        (local.0, self.format_darkness_time(|| local.1, true, preferences))
     }
 
     pub fn get_darkness_local_astronomical_or_nautical_end_str(
         &self,
-        format: Option<&str>,
+        preferences: &Preferences,
     ) -> (&str, String) {
         let local = self.get_darkness_local_astronomical_or_nautical();
         (
             local.0,
-            self.format_darkness_time(|| local.1, false, format),
+            self.format_darkness_time(|| local.1, false, preferences),
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::application::darkness::Darkness;
+    use crate::application::environment::Environment;
+    use crate::application::observer::Observer;
+    use crate::application::sun::SunEvent;
+    use crate::application::time::Time;
+
+    #[test]
+    fn test_darkness_blocked() {
+        // Amsterdam around the June 2025 full Moon, the Sun never gets to -18° and the Moon is up
+        // during the whole nautical darkness
+        let observer = Observer::location(None, "52.37", "4.9", 0, "2");
+        let time = Time::new(2025, 6, 10, 12, 0, 0);
+        let environment = Environment::default();
+        let darkness = Darkness::new(&observer, &time, &environment);
+        assert_eq!(darkness.get_darkness_utc_astronomical(), (SunEvent::AlwaysUp, SunEvent::AlwaysUp));
+        assert_eq!(darkness.get_darkness_utc_nautical(), (SunEvent::MoonUp, SunEvent::MoonUp));
+
+        // New Moon a few days later, darkness starts and ends
+        let time = Time::new(2025, 6, 25, 12, 0, 0);
+        let darkness = Darkness::new(&observer, &time, &environment);
+        let (start, end) = darkness.get_darkness_utc_nautical();
+        assert!(start.jd().unwrap() < end.jd().unwrap());
+    }
+
+    #[test]
+    fn test_darkness_local_noon() {
+        // the night runs from local noon to the next local noon. At the 2024 July new Moon the
+        // astronomical darkness of the 5th lasts from 18:54 to 05:29 in São Paulo (UTC-3) and from
+        // 20:48 to 02:44 in Tokyo (UTC+9).
+        let time = Time::new(2024, 7, 5, 12, 0, 0);
+        let environment = Environment::default();
+        for (lat, lon, tz, start, end) in [("-23.55", "-46.63", "-3", (5, 18, 54), (6, 5, 29)),
+                                           ("35.68", "139.69", "9", (5, 20, 48), (6, 2, 44))] {
+            let observer = Observer::location(None, lat, lon, 0, tz);
+            let local_jd = |(day, hour, minute): (u64, u64, u64)| {
+                Time::new(2024, 7, day, hour, minute, 0).to_jd() - observer.timezone / 24.0
+            };
+            let darkness = Darkness::new(&observer, &time, &environment);
+            let (dark_start, dark_end) = darkness.get_darkness_utc_astronomical();
+            let (dark_start, dark_end) = (dark_start.jd().unwrap(), dark_end.jd().unwrap());
+            assert!((dark_start - local_jd(start)).abs() * 1440.0 < 3.0, "{} {}", tz, dark_start);
+            assert!((dark_end - local_jd(end)).abs() * 1440.0 < 3.0, "{} {}", tz, dark_end);
+        }
+    }
+}
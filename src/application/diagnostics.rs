@@ -0,0 +1,72 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Everything a bug report needs in one text: version and system, where the configuration and
+// the log are, the settings in use, the intermediate values of the selected night (the same as
+// the calculation inspector) and the most recent log lines.
+
+use crate::application::application::{Application, CONFIG_VERSION};
+use crate::application::ephemeris_cache::cached_grids;
+use crate::application::inspector::inspect_night;
+use crate::application::logging::{log_level, log_path, recent_lines, LOG_LEVEL_VARIABLE};
+use crate::application::recent::config_directory;
+
+/// Diagnostics of the application state, as report lines
+pub fn diagnostics(application: &Application) -> Vec<String> {
+    let preferences = &application.preferences;
+    let calculation = preferences.calculation;
+
+    let mut lines: Vec<String> = Vec::new();
+    lines.push("System:".to_string());
+    lines.push(format!("\n   - Version                 : {}", env!("CARGO_PKG_VERSION")));
+    lines.push(format!("\n   - Platform                : {} {}", std::env::consts::OS, std::env::consts::ARCH));
+    lines.push(format!("\n   - Configuration directory : {}", config_directory().display()));
+    lines.push(format!("\n   - Log file                : {}", log_path().display()));
+    lines.push(format!("\n   - Log level               : {} ({})",
+                       log_level(std::env::var(LOG_LEVEL_VARIABLE).ok().as_deref()), LOG_LEVEL_VARIABLE));
+
+    lines.push("\n\nConfiguration:".to_string());
+    lines.push(format!("\n   - Format version          : {} (current {})", application.config_version, CONFIG_VERSION));
+    lines.push(format!("\n   - Observatory             : {}", application.observer.name.as_deref().unwrap_or("-")));
+    lines.push(format!("\n   - Targets                 : {}", application.targets.len()));
+    lines.push(format!("\n   - Language                : {}", preferences.language.name()));
+    lines.push(format!("\n   - Grid points per day     : {} (used {})", calculation.grid_points,
+                       calculation.points_per_day()));
+    lines.push(format!("\n   - Moon model              : {:?}", calculation.moon_model));
+    lines.push(format!("\n   - Refraction              : {}", calculation.refraction));
+    lines.push(format!("\n   - Ephemeris grids cached  : {}", cached_grids()));
+    for note in &application.migration_notes {
+        lines.push(format!("\n   - Migration               : {}", note));
+    }
+
+    lines.push("\n\n".to_string());
+    lines.extend(inspect_night(&application.observer, &application.time, &application.environment,
+                               preferences));
+
+    let recent = recent_lines();
+    lines.push(format!("\nRecent log ({} lines):", recent.len()));
+    for line in recent {
+        lines.push(format!("\n   {}", line));
+    }
+    lines.push("\n".to_string());
+    lines
+}
@@ -280,14 +280,15 @@ pub fn nutation(t: f64) -> (f64, f64, f64) {
 
 #[cfg(test)]
 mod test {
-    use crate::earth::nutation;
-    use assert_approx_eq::assert_approx_eq;
+    use crate::application::earth::nutation;
 
     #[test]
     fn test_nutation() {
-        let (dphi, deps, eps0) = nutation(1987.0);
-        assert_approx_eq!(dphi, -0.001_052_203, 1e-6);
-        assert_approx_eq!(deps, 0.002_623_056, 1e-6);
-        assert_approx_eq!(eps0, 23.440946389, 1e-6);
+        // Meeus, Astronomical Algorithms, example 22.a: 1987 April 10 at 0h TD,
+        // T = -0.127296372348, delta psi -3.788", delta epsilon +9.443", epsilon0 23°26'27.407"
+        let (dphi, deps, eps0) = nutation(-0.127_296_372_348);
+        assert!((dphi + 0.001_052_203).abs() < 1e-6);
+        assert!((deps - 0.002_623_056).abs() < 1e-6);
+        assert!((eps0 - 23.440_946_389).abs() < 1e-6);
     }
 }
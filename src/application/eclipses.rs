@@ -0,0 +1,388 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Eclipse prediction based on Meeus, Astronomical Algorithms, chapters 49 and 54.
+//
+// Times are computed in dynamical time (TD) and converted to UTC with ΔT.
+
+use crate::application::{
+    moon::{moon_altitude, moon_topocentric_position},
+    observer::Observer,
+    sun::{sun_alt_az_from_jd, sun_distance_from_jd, sun_position_from_jd},
+    time::{decimal_year, delta_t},
+    transformations::angular_separation,
+};
+use crate::utils::utils::{constrain_360, cosd, sind};
+
+const MINUTE: f64 = 1.0 / 1440.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LunarEclipseType {
+    Penumbral,
+    Partial,
+    Total,
+}
+
+impl LunarEclipseType {
+    pub fn description(&self) -> &'static str {
+        match self {
+            LunarEclipseType::Penumbral => "Penumbral lunar eclipse",
+            LunarEclipseType::Partial => "Partial lunar eclipse",
+            LunarEclipseType::Total => "Total lunar eclipse",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SolarEclipseType {
+    Partial,
+    Annular,
+    Total,
+    Hybrid,
+}
+
+impl SolarEclipseType {
+    pub fn description(&self) -> &'static str {
+        match self {
+            SolarEclipseType::Partial => "Partial solar eclipse",
+            SolarEclipseType::Annular => "Annular solar eclipse",
+            SolarEclipseType::Total => "Total solar eclipse",
+            SolarEclipseType::Hybrid => "Hybrid solar eclipse",
+        }
+    }
+}
+
+/// Lunar eclipse circumstances, all times are JD(UTC)
+///
+/// * `p1`, `p4` - start and end of the penumbral phase
+/// * `u1`, `u4` - start and end of the partial phase (umbral eclipses only)
+/// * `u2`, `u3` - start and end of totality (total eclipses only)
+/// * `visible` - the Moon is above the observer's horizon at some time between p1 and p4
+#[derive(Debug, Clone)]
+pub struct LunarEclipse {
+    pub eclipse_type: LunarEclipseType,
+    pub maximum: f64,
+    pub penumbral_magnitude: f64,
+    pub umbral_magnitude: f64,
+    pub p1: f64,
+    pub u1: Option<f64>,
+    pub u2: Option<f64>,
+    pub u3: Option<f64>,
+    pub u4: Option<f64>,
+    pub p4: f64,
+    pub visible: bool,
+}
+
+/// Local circumstances of a solar eclipse, all times are JD(UTC)
+///
+/// * `eclipse_type` - global type of the eclipse (the observer may only see a partial phase)
+/// * `first_contact`, `last_contact` - while the Sun is above the horizon
+/// * `magnitude` - maximum fraction of the solar diameter covered by the Moon
+#[derive(Debug, Clone)]
+pub struct SolarEclipse {
+    pub eclipse_type: SolarEclipseType,
+    pub first_contact: f64,
+    pub maximum: f64,
+    pub last_contact: f64,
+    pub magnitude: f64,
+}
+
+// Quantities of Meeus ch. 54 for the lunation k (integer for new moon, +0.5 for full moon)
+struct EclipseElements {
+    // time of maximum, JD UTC
+    jd: f64,
+    gamma: f64,
+    u: f64,
+    mprime: f64,
+}
+
+fn eclipse_elements(k: f64) -> Option<EclipseElements> {
+    let t = k / 1236.85;
+    let t2 = t * t;
+    let t3 = t2 * t;
+    let t4 = t3 * t;
+
+    let f = constrain_360(
+        160.7108 + 390.670_502_84 * k - 0.001_611_8 * t2 - 0.000_002_27 * t3 + 0.000_000_011 * t4,
+    );
+    // no eclipse possible if the Moon is too far from the node
+    if sind(f).abs() > 0.36 {
+        return None;
+    }
+
+    let jde = 2_451_550.097_66 + 29.530_588_861 * k + 0.000_154_37 * t2 - 0.000_000_150 * t3
+        + 0.000_000_000_73 * t4;
+    let e = 1.0 - 0.002_516 * t - 0.000_007_4 * t2;
+    let m = constrain_360(2.5534 + 29.105_356_70 * k - 0.000_001_4 * t2 - 0.000_000_11 * t3);
+    let mprime = constrain_360(
+        201.5643 + 385.816_935_28 * k + 0.010_758_2 * t2 + 0.000_012_38 * t3
+            - 0.000_000_058 * t4,
+    );
+    let omega = constrain_360(124.7746 - 1.563_755_88 * k + 0.002_067_2 * t2 + 0.000_002_15 * t3);
+    let f1 = f - 0.02665 * sind(omega);
+    let a1 = 299.77 + 0.107_408 * k - 0.009_173 * t2;
+
+    let is_full_moon = k.fract().abs() > 0.25;
+    let mut correction = if is_full_moon {
+        -0.4065 * sind(mprime) + 0.1727 * e * sind(m)
+    } else {
+        -0.4075 * sind(mprime) + 0.1721 * e * sind(m)
+    };
+    correction += 0.0161 * sind(2.0 * mprime) - 0.0097 * sind(2.0 * f1)
+        + 0.0073 * e * sind(mprime - m)
+        - 0.0050 * e * sind(mprime + m)
+        - 0.0023 * sind(mprime - 2.0 * f1)
+        + 0.0021 * e * sind(2.0 * m)
+        + 0.0012 * sind(mprime + 2.0 * f1)
+        + 0.0006 * e * sind(2.0 * mprime + m)
+        - 0.0004 * sind(3.0 * mprime)
+        - 0.0003 * e * sind(m + 2.0 * f1)
+        + 0.0003 * sind(a1)
+        - 0.0002 * e * sind(m - 2.0 * f1)
+        - 0.0002 * e * sind(2.0 * mprime - m)
+        - 0.0002 * sind(omega);
+
+    let p = 0.2070 * e * sind(m) + 0.0024 * e * sind(2.0 * m) - 0.0392 * sind(mprime)
+        + 0.0116 * sind(2.0 * mprime)
+        - 0.0073 * e * sind(mprime + m)
+        + 0.0067 * e * sind(mprime - m)
+        + 0.0118 * sind(2.0 * f1);
+    let q = 5.2207 - 0.0048 * e * cosd(m) + 0.0020 * e * cosd(2.0 * m) - 0.3299 * cosd(mprime)
+        - 0.0060 * e * cosd(mprime + m)
+        + 0.0041 * e * cosd(mprime - m);
+    let w = cosd(f1).abs();
+    let gamma = (p * cosd(f1) + q * sind(f1)) * (1.0 - 0.0048 * w);
+    let u = 0.0059 + 0.0046 * e * cosd(m) - 0.0182 * cosd(mprime) + 0.0004 * cosd(2.0 * mprime)
+        - 0.0005 * cosd(m + mprime);
+
+    Some(EclipseElements {
+        jd: jde + correction - delta_t(decimal_year(jde)) / 86_400.0,
+        gamma,
+        u,
+        mprime,
+    })
+}
+
+// Lunations (integer k) that may fall between jd_start and jd_end
+fn lunations(jd_start: f64, jd_end: f64) -> std::ops::RangeInclusive<i64> {
+    let k_start = ((jd_start - 2_451_550.097_66) / 29.530_588_861).floor() as i64 - 1;
+    let k_end = ((jd_end - 2_451_550.097_66) / 29.530_588_861).ceil() as i64 + 1;
+    k_start..=k_end
+}
+
+/// Lunar eclipses with maximum between jd_start and jd_end (JD UTC)
+pub fn lunar_eclipses(observer: &Observer, jd_start: f64, jd_end: f64) -> Vec<LunarEclipse> {
+    let mut eclipses: Vec<LunarEclipse> = Vec::new();
+    for k in lunations(jd_start, jd_end) {
+        let el = match eclipse_elements(k as f64 + 0.5) {
+            Some(el) => el,
+            None => continue,
+        };
+        if el.jd < jd_start || el.jd > jd_end {
+            continue;
+        }
+
+        let gamma = el.gamma.abs();
+        let penumbral_magnitude = (1.5573 + el.u - gamma) / 0.5450;
+        let umbral_magnitude = (1.0128 - el.u - gamma) / 0.5450;
+        if penumbral_magnitude <= 0.0 {
+            continue;
+        }
+
+        // semidurations in days
+        let n = 0.5458 + 0.0400 * cosd(el.mprime);
+        let semiduration = |radius: f64| -> Option<f64> {
+            let x = radius * radius - gamma * gamma;
+            if x > 0.0 { Some(x.sqrt() / n / 24.0) } else { None }
+        };
+        let penumbral = semiduration(1.5573 + el.u).unwrap_or(0.0);
+        let partial = semiduration(1.0128 - el.u);
+        let total = semiduration(0.4678 - el.u);
+
+        let eclipse_type = if total.is_some() {
+            LunarEclipseType::Total
+        } else if partial.is_some() {
+            LunarEclipseType::Partial
+        } else {
+            LunarEclipseType::Penumbral
+        };
+
+        let p1 = el.jd - penumbral;
+        let p4 = el.jd + penumbral;
+        let moon = moon_altitude(observer.latitude, observer.longitude, observer.elevation as f64);
+        let mut jd = p1;
+        let mut visible = false;
+        while jd <= p4 {
+            if moon(jd) > 0.0 {
+                visible = true;
+                break;
+            }
+            jd += 5.0 * MINUTE;
+        }
+
+        eclipses.push(LunarEclipse {
+            eclipse_type,
+            maximum: el.jd,
+            penumbral_magnitude,
+            umbral_magnitude,
+            p1,
+            u1: partial.map(|s| el.jd - s),
+            u2: total.map(|s| el.jd - s),
+            u3: total.map(|s| el.jd + s),
+            u4: partial.map(|s| el.jd + s),
+            p4,
+            visible,
+        });
+    }
+    eclipses
+}
+
+// Sun and topocentric Moon separation and semi-diameters, all in degrees
+fn sun_moon_disks(observer: &Observer, jd: f64) -> (f64, f64, f64) {
+    let (sun_ra, sun_dec) = sun_position_from_jd(jd);
+    let sun_sd = 959.63 / sun_distance_from_jd(jd) / 3600.0;
+
+    let (topo_ra, topo_dec, moon_distance) = moon_topocentric_position(
+        observer.latitude,
+        observer.longitude,
+        observer.elevation as f64,
+        jd,
+    );
+    let moon_sd = 358_473_400.0 / moon_distance / 3600.0;
+
+    (angular_separation(sun_ra, sun_dec, topo_ra, topo_dec), sun_sd, moon_sd)
+}
+
+/// Solar eclipses visible from the observer location with maximum between jd_start and
+/// jd_end (JD UTC). Only the part of the eclipse with the Sun above the horizon is reported.
+pub fn solar_eclipses(observer: &Observer, jd_start: f64, jd_end: f64) -> Vec<SolarEclipse> {
+    let mut eclipses: Vec<SolarEclipse> = Vec::new();
+    for k in lunations(jd_start, jd_end) {
+        let el = match eclipse_elements(k as f64) {
+            Some(el) => el,
+            None => continue,
+        };
+        if el.jd < jd_start || el.jd > jd_end {
+            continue;
+        }
+
+        let gamma = el.gamma.abs();
+        if gamma > 1.5433 + el.u {
+            continue;
+        }
+        let eclipse_type = if gamma > 0.9972 {
+            SolarEclipseType::Partial
+        } else if el.u < 0.0 {
+            SolarEclipseType::Total
+        } else if el.u < 0.004_64 * (1.0 - gamma * gamma).sqrt() {
+            SolarEclipseType::Hybrid
+        } else {
+            SolarEclipseType::Annular
+        };
+
+        // local circumstances: scan 4 hours around the geocentric maximum
+        let mut first_contact: Option<f64> = None;
+        let mut last_contact = 0.0;
+        let mut maximum = 0.0;
+        let mut magnitude: f64 = 0.0;
+        let mut jd = el.jd - 4.0 / 24.0;
+        while jd <= el.jd + 4.0 / 24.0 {
+            let (separation, sun_sd, moon_sd) = sun_moon_disks(observer, jd);
+            let (sun_ra, sun_dec) = sun_position_from_jd(jd);
+            let sun_alt =
+                sun_alt_az_from_jd(observer.latitude, observer.longitude, sun_ra, sun_dec, jd).0;
+            if separation < sun_sd + moon_sd && sun_alt > -0.8333 {
+                first_contact.get_or_insert(jd);
+                last_contact = jd;
+                let covered = (sun_sd + moon_sd - separation) / (2.0 * sun_sd);
+                if covered > magnitude {
+                    magnitude = covered;
+                    maximum = jd;
+                }
+            }
+            jd += MINUTE;
+        }
+
+        if let Some(first_contact) = first_contact {
+            eclipses.push(SolarEclipse {
+                eclipse_type,
+                first_contact,
+                maximum,
+                last_contact,
+                magnitude,
+            });
+        }
+    }
+    eclipses
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::application::time::Time;
+
+    #[test]
+    fn test_lunar_eclipses() {
+        // Total lunar eclipse of 2022 November 8, greatest eclipse at 10:59 UTC, seen from Hawaii
+        let observer = Observer::location(None, "21.3", "-157.8", 0, "-10");
+        let start = Time::new(2022, 11, 1, 0, 0, 0).to_jd();
+        let eclipses = lunar_eclipses(&observer, start, start + 14.0);
+        assert_eq!(eclipses.len(), 1);
+        let eclipse = &eclipses[0];
+        assert_eq!(eclipse.eclipse_type, LunarEclipseType::Total);
+        assert!((eclipse.maximum - Time::new(2022, 11, 8, 10, 59, 0).to_jd()).abs() < 5.0 * MINUTE);
+        assert!((eclipse.umbral_magnitude - 1.359).abs() < 0.02);
+        assert!(eclipse.p1 < eclipse.u1.unwrap() && eclipse.u1 < eclipse.u2 && eclipse.u3 < eclipse.u4);
+        assert!(eclipse.visible);
+
+        // Penumbral eclipse of 2023 May 5 at 17:23 UTC, in the afternoon of São Paulo
+        let sao_paulo = Observer::location(None, "-23.5", "-46.6", 760, "-3");
+        let start = Time::new(2023, 5, 1, 0, 0, 0).to_jd();
+        let eclipses = lunar_eclipses(&sao_paulo, start, start + 14.0);
+        assert_eq!(eclipses.len(), 1);
+        assert_eq!(eclipses[0].eclipse_type, LunarEclipseType::Penumbral);
+        assert_eq!(eclipses[0].u1, None);
+        assert!(!eclipses[0].visible);
+
+        // No eclipse at the full Moon of 2024 January 25
+        let start = Time::new(2024, 1, 20, 0, 0, 0).to_jd();
+        assert!(lunar_eclipses(&observer, start, start + 10.0).is_empty());
+    }
+
+    #[test]
+    fn test_solar_eclipses() {
+        // Total solar eclipse of 2024 April 8 from Dallas, totality at about 18:42 UTC
+        let observer = Observer::location(None, "32.78", "-96.80", 140, "-5");
+        let start = Time::new(2024, 4, 1, 0, 0, 0).to_jd();
+        let eclipses = solar_eclipses(&observer, start, start + 14.0);
+        assert_eq!(eclipses.len(), 1);
+        let eclipse = &eclipses[0];
+        assert_eq!(eclipse.eclipse_type, SolarEclipseType::Total);
+        assert!((eclipse.maximum - Time::new(2024, 4, 8, 18, 42, 0).to_jd()).abs() < 5.0 * MINUTE);
+        assert!((eclipse.first_contact - Time::new(2024, 4, 8, 17, 23, 0).to_jd()).abs() < 5.0 * MINUTE);
+        assert!(eclipse.magnitude > 0.99);
+
+        // The same eclipse is not seen from São Paulo
+        let observer = Observer::location(None, "-23.5", "-46.6", 760, "-3");
+        assert!(solar_eclipses(&observer, start, start + 14.0).is_empty());
+    }
+}
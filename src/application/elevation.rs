@@ -0,0 +1,150 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Site elevation from SRTM height tiles (.hgt) kept in a local directory, no network needed.
+//
+// Each tile covers one degree square and is named after its south west corner, e.g. S24W047.hgt
+// for latitudes -24..-23 and longitudes -47..-46. Samples are big endian 16 bit heights in meters
+// stored row by row from north to south, 1201 x 1201 (3 arc seconds) or 3601 x 3601 (1 arc second).
+
+use std::fs::read;
+use std::path::Path;
+
+// Height of samples without data
+const VOID: i16 = -32768;
+
+/// DemTile struct
+///
+/// Heights of one SRTM tile.
+///
+/// # Attributes
+///
+/// * `south` - Latitude of the south edge in degrees
+/// * `west` - Longitude of the west edge in degrees
+/// * `size` - Samples per row and per column
+/// * `heights` - Heights in meters, row by row from north to south
+#[derive(Debug, Clone)]
+pub struct DemTile {
+    pub south: f64,
+    pub west: f64,
+    pub size: usize,
+    pub heights: Vec<i16>,
+}
+
+/// Name of the tile holding a location, e.g. "S24W047.hgt"
+pub fn tile_name(latitude: f64, longitude: f64) -> String {
+    let south = latitude.floor() as i64;
+    let west = longitude.floor() as i64;
+    format!(
+        "{}{:02}{}{:03}.hgt",
+        if south < 0 { 'S' } else { 'N' },
+        south.abs(),
+        if west < 0 { 'W' } else { 'E' },
+        west.abs()
+    )
+}
+
+impl DemTile {
+    pub fn from_bytes(south: f64, west: f64, bytes: &[u8]) -> Result<DemTile, String> {
+        let samples = bytes.len() / 2;
+        let size = (samples as f64).sqrt().round() as usize;
+        if !bytes.len().is_multiple_of(2) || size < 2 || size * size != samples {
+            return Err(format!("Invalid elevation tile size ({} bytes)", bytes.len()));
+        }
+        let heights = bytes
+            .chunks_exact(2)
+            .map(|pair| i16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        Ok(DemTile { south, west, size, heights })
+    }
+
+    /// Tile holding the location in a directory of SRTM tiles
+    pub fn load(directory: &str, latitude: f64, longitude: f64) -> Result<DemTile, String> {
+        let path = Path::new(directory).join(tile_name(latitude, longitude));
+        let bytes = read(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        DemTile::from_bytes(latitude.floor(), longitude.floor(), &bytes)
+    }
+
+    fn height(&self, row: usize, column: usize) -> Option<f64> {
+        let height = self.heights[row * self.size + column];
+        if height == VOID {
+            None
+        } else {
+            Some(height as f64)
+        }
+    }
+
+    /// Elevation in meters interpolated between the four nearest samples, None outside the tile
+    /// or next to samples without data
+    pub fn elevation_at(&self, latitude: f64, longitude: f64) -> Option<i64> {
+        let steps = (self.size - 1) as f64;
+        let y = (self.south + 1.0 - latitude) * steps;
+        let x = (longitude - self.west) * steps;
+        if !(0.0..=steps).contains(&y) || !(0.0..=steps).contains(&x) {
+            return None;
+        }
+        let row = (y.floor() as usize).min(self.size - 2);
+        let column = (x.floor() as usize).min(self.size - 2);
+        let (dy, dx) = (y - row as f64, x - column as f64);
+
+        let north = self.height(row, column)? * (1.0 - dx) + self.height(row, column + 1)? * dx;
+        let south = self.height(row + 1, column)? * (1.0 - dx) + self.height(row + 1, column + 1)? * dx;
+        Some((north * (1.0 - dy) + south * dy).round() as i64)
+    }
+}
+
+/// Elevation in meters of a location from the SRTM tiles in a directory
+pub fn lookup_elevation(directory: &str, latitude: f64, longitude: f64) -> Result<i64, String> {
+    DemTile::load(directory, latitude, longitude)?
+        .elevation_at(latitude, longitude)
+        .ok_or(format!("No elevation data at {:.4}, {:.4}", latitude, longitude))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 3 x 3 samples rising from west to east, with a void in the south east corner
+    fn tile() -> DemTile {
+        let heights: [i16; 9] = [100, 200, 300, 100, 200, 300, 100, 200, VOID];
+        let bytes: Vec<u8> = heights.iter().flat_map(|h| h.to_be_bytes()).collect();
+        DemTile::from_bytes(-24.0, -47.0, &bytes).unwrap()
+    }
+
+    #[test]
+    fn test_tile_name() {
+        assert_eq!(tile_name(-23.1, -46.5), "S24W047.hgt");
+        assert_eq!(tile_name(28.76, -17.88), "N28W018.hgt");
+        assert_eq!(tile_name(0.5, 0.5), "N00E000.hgt");
+    }
+
+    #[test]
+    fn test_elevation_at() {
+        let tile = tile();
+        assert_eq!(tile.size, 3);
+        assert_eq!(tile.elevation_at(-23.0, -47.0), Some(100));
+        assert_eq!(tile.elevation_at(-23.25, -46.75), Some(150));
+        assert_eq!(tile.elevation_at(-23.9, -46.1), None);
+        assert_eq!(tile.elevation_at(-22.5, -46.5), None);
+        assert!(DemTile::from_bytes(-24.0, -47.0, &[0; 7]).is_err());
+    }
+}
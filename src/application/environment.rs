@@ -24,14 +24,15 @@
 #![allow(dead_code, unused_variables)]
 
 use serde::{Deserialize, Deserializer, Serialize};
+use crate::application::weather::HourlyForecast;
 
-#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+#[derive(Default, Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Environment {
     #[serde(
         default = "default_temperature",
         deserialize_with = "deserialize_temperature"
     )]
-    pub temperature: i64,
+    pub temperature: f64,
     #[serde(
         default = "default_humidity",
         deserialize_with = "deserialize_humidity"
@@ -45,8 +46,8 @@ pub struct Environment {
 }
 
 // Default value functions for Environment fields
-pub fn default_temperature() -> i64 {
-    20
+pub fn default_temperature() -> f64 {
+    20.0
 }
 
 pub fn default_humidity() -> i64 {
@@ -57,11 +58,11 @@ pub fn default_pressure() -> i64 {
     1010
 }
 
-fn deserialize_temperature<'de, D>(deserializer: D) -> Result<i64, D::Error>
+fn deserialize_temperature<'de, D>(deserializer: D) -> Result<f64, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let value: Option<i64> = Option::deserialize(deserializer)?;
+    let value: Option<f64> = Option::deserialize(deserializer)?;
     // If the value is None (either missing or null), use the default value
     match value {
         Some(value) => Ok(value),
@@ -94,7 +95,7 @@ where
 }
 
 impl Environment {
-    pub fn new(self, pressure: i64, temperature: i64, humidity: i64) -> Environment {
+    pub fn new(self, pressure: i64, temperature: f64, humidity: i64) -> Environment {
         Environment {
             pressure,
             temperature,
@@ -102,6 +103,122 @@ impl Environment {
             ..self
         }
     }
+
+    /// Dew point in °C of the temperature and relative humidity
+    pub fn dew_point(&self) -> f64 {
+        dew_point(self.temperature, self.humidity as f64)
+    }
+}
+
+// Magnus formula coefficients over water, within 0.4 °C from -45 °C to 60 °C
+const MAGNUS_A: f64 = 17.62;
+const MAGNUS_B: f64 = 243.12;
+
+/// Dew point in °C of a temperature in °C and a relative humidity in percent, humidities below 1%
+/// are taken as 1% so the result stays finite
+pub fn dew_point(temperature: f64, humidity: f64) -> f64 {
+    let gamma = (humidity.clamp(1.0, 100.0) / 100.0).ln() + MAGNUS_A * temperature / (MAGNUS_B + temperature);
+    MAGNUS_B * gamma / (MAGNUS_A - gamma)
+}
+
+/// Cooling of the air after sunset under a clear sky in °C per square root of the hours since
+/// sunset (Brunt), about 8 °C over an 8 hour night
+pub const NIGHT_COOLING: f64 = 3.0;
+
+/// Gaps between the air temperature and the dew point, in °C, at or below which dew is likely on
+/// unheated optics and at or below which it should be watched for
+pub const DEW_HIGH_GAP: f64 = 2.0;
+pub const DEW_MODERATE_GAP: f64 = 5.0;
+
+/// Risk of dew on optics from the gap between the air temperature and the dew point
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DewRisk {
+    Low,
+    Moderate,
+    High,
+    /// High with the air at or below freezing, frost instead of dew
+    Frost,
+}
+
+impl DewRisk {
+    pub fn new(temperature: f64, dew_point: f64) -> DewRisk {
+        let gap = temperature - dew_point;
+        if gap <= DEW_HIGH_GAP {
+            if temperature <= 0.0 { DewRisk::Frost } else { DewRisk::High }
+        } else if gap <= DEW_MODERATE_GAP {
+            DewRisk::Moderate
+        } else {
+            DewRisk::Low
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DewRisk::Low => "low",
+            DewRisk::Moderate => "moderate",
+            DewRisk::High => "high",
+            DewRisk::Frost => "frost",
+        }
+    }
+}
+
+/// DewHour struct
+///
+/// # Attributes
+///
+/// * `jd` - Start of the hour (JD UTC)
+/// * `temperature` - Expected air temperature in °C
+/// * `dew_point` - Dew point in °C
+/// * `risk` - Risk of dew on optics
+/// * `forecast` - Temperature and humidity of a forecast, false for a rough estimate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DewHour {
+    pub jd: f64,
+    pub temperature: f64,
+    pub dew_point: f64,
+    pub risk: DewRisk,
+    pub forecast: bool,
+}
+
+/// Rough estimate of the dew risk at a time (JD UTC) of the night. The environment temperature is
+/// taken at sunset and the air cools as under a clear sky while its moisture, and so the dew point,
+/// is kept. The air does not cool below the dew point, the water condenses instead. Wind, clouds
+/// and air masses moving in are ignored, `forecast_dew_hour` is used when a forecast is available.
+pub fn dew_hour(environment: &Environment, sunset: f64, jd: f64) -> DewHour {
+    let dew_point = environment.dew_point();
+    let cooling = NIGHT_COOLING * ((jd - sunset).max(0.0) * 24.0).sqrt();
+    let temperature = (environment.temperature - cooling).max(dew_point);
+    DewHour { jd, temperature, dew_point, risk: DewRisk::new(temperature, dew_point), forecast: false }
+}
+
+/// Dew risk of an hour (JD UTC) from its forecast air temperature in °C and relative humidity in
+/// percent
+pub fn forecast_dew_hour(jd: f64, temperature: f64, humidity: f64) -> DewHour {
+    let dew_point = dew_point(temperature, humidity);
+    DewHour { jd, temperature, dew_point, risk: DewRisk::new(temperature, dew_point), forecast: true }
+}
+
+/// Dew risk at a time (JD UTC) of the night from the nearest forecast hour, up to an hour away, with
+/// a temperature and a humidity, the rough estimate of `dew_hour` otherwise
+pub fn night_dew_hour(environment: &Environment, sunset: f64, jd: f64, forecast: &[HourlyForecast]) -> DewHour {
+    forecast
+        .iter()
+        .filter_map(|hour| Some((hour.jd, hour.temperature?, hour.humidity?)))
+        .filter(|(hour, _, _)| (hour - jd).abs() * 24.0 <= 1.0 + 1e-6)
+        .min_by(|a, b| (a.0 - jd).abs().total_cmp(&(b.0 - jd).abs()))
+        .map_or_else(|| dew_hour(environment, sunset, jd),
+                     |(_, temperature, humidity)| forecast_dew_hour(jd, temperature, humidity))
+}
+
+/// Dew risk on the whole hours (UTC) from sunset to sunrise, see `night_dew_hour`
+pub fn night_dew_risk(environment: &Environment, sunset: f64, sunrise: f64,
+                      forecast: &[HourlyForecast]) -> Vec<DewHour> {
+    let first_hour = (sunset * 24.0).ceil() as i64;
+    (first_hour..)
+        .map(|hour| hour as f64 / 24.0)
+        .take_while(|&jd| jd < sunrise)
+        .map(|jd| night_dew_hour(environment, sunset, jd, forecast))
+        .collect()
 }
 
 impl std::fmt::Display for Environment {
@@ -113,3 +230,65 @@ impl std::fmt::Display for Environment {
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dew_point() {
+        // 20 °C at 50% is about 9.3 °C, saturated air is at its dew point
+        assert!((dew_point(20.0, 50.0) - 9.26).abs() < 0.05);
+        assert!((dew_point(10.0, 100.0) - 10.0).abs() < 1e-9);
+        assert!(dew_point(20.0, 0.0).is_finite());
+
+        let humid = Environment { temperature: 15.0, humidity: 60, pressure: 1010 };
+        let dew_point = humid.dew_point();
+        let hours = night_dew_risk(&humid, 0.3, 0.7, &[]);
+        // whole hours from sunset to sunrise, cooling down to the dew point
+        assert_eq!(hours.len(), 9);
+        assert!(((hours[0].jd * 24.0) - (hours[0].jd * 24.0).round()).abs() < 1e-6);
+        assert!(hours.windows(2).all(|w| w[1].temperature <= w[0].temperature));
+        assert!(hours.iter().all(|hour| hour.temperature >= dew_point));
+        assert_ne!(hours[0].risk, DewRisk::High);
+        assert_eq!(hours.last().unwrap().risk, DewRisk::High);
+
+        let dry = Environment { temperature: 25.0, humidity: 20, pressure: 1010 };
+        assert!(night_dew_risk(&dry, 0.3, 0.7, &[]).iter().all(|hour| hour.risk == DewRisk::Low));
+        assert_eq!(DewRisk::new(-2.0, -3.0), DewRisk::Frost);
+    }
+
+    #[test]
+    fn test_dew_risk_thresholds() {
+        // air at 15 °C at sunset cools to 9 °C in 4 hours, dew is likely from a dew point of 7 °C
+        // (about 59% at sunset) and to be watched for from 4 °C (about 48%)
+        let risk = |humidity: i64| {
+            let environment = Environment { temperature: 15.0, humidity, pressure: 1010 };
+            dew_hour(&environment, 0.0, 4.0 / 24.0).risk
+        };
+        assert_eq!(risk(40), DewRisk::Low);
+        assert_eq!(risk(50), DewRisk::Moderate);
+        assert_eq!(risk(57), DewRisk::Moderate);
+        assert_eq!(risk(61), DewRisk::High);
+
+        // forecast air at 10 °C, dew is likely from about 87% and to be watched for from about 71%
+        let risk = |humidity: f64| forecast_dew_hour(0.0, 10.0, humidity).risk;
+        assert_eq!(risk(65.0), DewRisk::Low);
+        assert_eq!(risk(75.0), DewRisk::Moderate);
+        assert_eq!(risk(85.0), DewRisk::Moderate);
+        assert_eq!(risk(90.0), DewRisk::High);
+        assert_eq!(forecast_dew_hour(0.0, -1.0, 95.0).risk, DewRisk::Frost);
+
+        // the nearest forecast hour with a temperature and a humidity replaces the estimate, hours
+        // further than an hour from one keep it
+        let dry = Environment { temperature: 25.0, humidity: 20, pressure: 1010 };
+        let hour = |jd: f64, humidity: Option<f64>| HourlyForecast {
+            jd, cloud_cover: 0.0, temperature: Some(10.0), humidity
+        };
+        let forecast = [hour(10.0 / 24.0, Some(95.0)), hour(11.0 / 24.0, None), hour(14.0 / 24.0, None)];
+        let hours = night_dew_risk(&dry, 0.44, 0.6, &forecast);
+        assert_eq!(hours.len(), 4);
+        assert!(hours[0].forecast && hours[0].risk == DewRisk::High);
+        assert!(hours[1..].iter().all(|hour| !hour.forecast && hour.risk == DewRisk::Low));
+    }
+}
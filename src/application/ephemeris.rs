@@ -0,0 +1,196 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Typed results for programmatic use: angles in radians and times as chrono UTC date-times,
+// so callers do not need to parse the display strings or convert from degrees.
+
+use chrono::{DateTime, Duration, Utc};
+use crate::application::calculation::CalculationSettings;
+use crate::application::darkness::Darkness;
+use crate::application::environment::Environment;
+use crate::application::moon::{moon_topocentric_position, Moon};
+use crate::application::observer::Observer;
+use crate::application::sun::RiseSetType::Next;
+use crate::application::sun::TwilightType::{AstronomicalTwilight, CivilTwilight, NauticalTwilight,
+                                            RiseSet};
+use crate::application::sun::{sun_alt_az_from_jd, sun_position_from_jd, Sun, SunEvent};
+use crate::application::time::Time;
+
+// Julian Date of the Unix epoch, 1970-01-01 00:00 UTC
+const JD_UNIX_EPOCH: f64 = 2_440_587.5;
+const MILLISECONDS_PER_DAY: f64 = 86_400_000.0;
+
+/// Equatorial struct
+///
+/// Right ascension and declination in radians.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Equatorial {
+    pub ra: f64,
+    pub dec: f64,
+}
+
+/// Horizontal struct
+///
+/// Altitude and azimuth (from north, through east) in radians.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Horizontal {
+    pub alt: f64,
+    pub az: f64,
+}
+
+/// NightTimes struct
+///
+/// Events of the selected night as UTC date-times, None when the event does not happen, and
+/// durations as chrono durations. Typed counterpart of `formatting::NightEvents`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NightTimes {
+    pub sunset: Option<DateTime<Utc>>,
+    pub sunrise: Option<DateTime<Utc>>,
+    pub civil_end: Option<DateTime<Utc>>,
+    pub civil_start: Option<DateTime<Utc>>,
+    pub nautical_end: Option<DateTime<Utc>>,
+    pub nautical_start: Option<DateTime<Utc>>,
+    pub astronomical_end: Option<DateTime<Utc>>,
+    pub astronomical_start: Option<DateTime<Utc>>,
+    pub moonrise: Option<DateTime<Utc>>,
+    pub moonset: Option<DateTime<Utc>>,
+    pub darkness_start: Option<DateTime<Utc>>,
+    pub darkness_end: Option<DateTime<Utc>>,
+    pub night_length: Option<Duration>,
+    pub astronomical_darkness: Option<Duration>,
+    pub moon_free_darkness: Duration,
+}
+
+/// UTC date-time of a Julian Date, to the millisecond, None outside chrono's range
+pub fn jd_to_datetime(jd: f64) -> Option<DateTime<Utc>> {
+    if !jd.is_finite() {
+        return None;
+    }
+    let milliseconds = ((jd - JD_UNIX_EPOCH) * MILLISECONDS_PER_DAY).round();
+    if milliseconds.abs() >= i64::MAX as f64 {
+        return None;
+    }
+    DateTime::from_timestamp_millis(milliseconds as i64)
+}
+
+/// Julian Date of a UTC date-time
+pub fn datetime_to_jd(time: DateTime<Utc>) -> f64 {
+    JD_UNIX_EPOCH + time.timestamp_millis() as f64 / MILLISECONDS_PER_DAY
+}
+
+// Hours to a chrono duration, to the second
+fn hours_to_duration(hours: f64) -> Duration {
+    Duration::seconds((hours * 3_600.0).round() as i64)
+}
+
+/// Geocentric position of the Sun
+pub fn sun_equatorial(time: DateTime<Utc>) -> Equatorial {
+    let (ra, dec) = sun_position_from_jd(datetime_to_jd(time));
+    Equatorial { ra: ra.to_radians(), dec: dec.to_radians() }
+}
+
+/// Altitude and azimuth of the Sun seen by the observer, without refraction
+pub fn sun_horizontal(observer: &Observer, time: DateTime<Utc>) -> Horizontal {
+    let jd = datetime_to_jd(time);
+    let (ra, dec) = sun_position_from_jd(jd);
+    let (alt, az) = sun_alt_az_from_jd(observer.latitude, observer.longitude, ra, dec, jd);
+    Horizontal { alt: alt.to_radians(), az: az.to_radians() }
+}
+
+/// Topocentric position of the Moon and its geocentric distance in km
+pub fn moon_equatorial(observer: &Observer, time: DateTime<Utc>) -> (Equatorial, f64) {
+    let (ra, dec, distance) = moon_topocentric_position(
+        observer.latitude, observer.longitude, observer.elevation as f64, datetime_to_jd(time));
+    (Equatorial { ra: ra.to_radians(), dec: dec.to_radians() }, distance)
+}
+
+/// Altitude and azimuth of the Moon seen by the observer, without refraction
+pub fn moon_horizontal(observer: &Observer, time: DateTime<Utc>) -> Horizontal {
+    let jd = datetime_to_jd(time);
+    let (ra, dec, _) = moon_topocentric_position(
+        observer.latitude, observer.longitude, observer.elevation as f64, jd);
+    // same conversion as for the Sun, only the equatorial position differs
+    let (alt, az) = sun_alt_az_from_jd(observer.latitude, observer.longitude, ra, dec, jd);
+    Horizontal { alt: alt.to_radians(), az: az.to_radians() }
+}
+
+impl SunEvent {
+    /// UTC date-time of the event, None when the event does not happen
+    pub fn to_datetime(self) -> Option<DateTime<Utc>> {
+        self.jd().and_then(jd_to_datetime)
+    }
+}
+
+impl NightTimes {
+    pub fn new(observer: &Observer, time: &Time, environment: &Environment,
+               settings: CalculationSettings) -> NightTimes {
+        let sun = Sun::new(observer, time, environment).with_settings(settings);
+        let moon = Moon::new(observer, time, environment).with_settings(settings);
+        let darkness = Darkness::new(observer, time, environment).with_settings(settings);
+        let (darkness_start, darkness_end) = darkness.darkness_utc(AstronomicalTwilight);
+
+        NightTimes {
+            sunset: sun.get_sunset_utc(Next, RiseSet).to_datetime(),
+            sunrise: sun.get_sunrise_utc(Next, RiseSet).to_datetime(),
+            civil_end: sun.get_sunset_utc(Next, CivilTwilight).to_datetime(),
+            civil_start: sun.get_sunrise_utc(Next, CivilTwilight).to_datetime(),
+            nautical_end: sun.get_sunset_utc(Next, NauticalTwilight).to_datetime(),
+            nautical_start: sun.get_sunrise_utc(Next, NauticalTwilight).to_datetime(),
+            astronomical_end: sun.get_sunset_utc(Next, AstronomicalTwilight).to_datetime(),
+            astronomical_start: sun.get_sunrise_utc(Next, AstronomicalTwilight).to_datetime(),
+            moonrise: moon.get_moonrise_utc(Next).to_datetime(),
+            moonset: moon.get_moonset_utc(Next).to_datetime(),
+            darkness_start: darkness_start.to_datetime(),
+            darkness_end: darkness_end.to_datetime(),
+            night_length: sun.night_duration(RiseSet).map(hours_to_duration),
+            astronomical_darkness: sun.night_duration(AstronomicalTwilight).map(hours_to_duration),
+            moon_free_darkness: hours_to_duration(darkness.moon_free_duration(AstronomicalTwilight)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{TimeZone, Utc};
+    use super::*;
+
+    #[test]
+    fn test_jd_datetime() {
+        let time = Utc.with_ymd_and_hms(2024, 11, 22, 12, 0, 0).unwrap();
+        assert_eq!(datetime_to_jd(time), 2_460_637.0);
+        assert_eq!(jd_to_datetime(2_460_637.0), Some(time));
+        assert_eq!(jd_to_datetime(f64::NAN), None);
+        assert_eq!(jd_to_datetime(1e300), None);
+    }
+
+    #[test]
+    fn test_sun_radians() {
+        // Meeus, Astronomical Algorithms, example 25.a: 1992 October 13 at 0h TD
+        let time = Utc.with_ymd_and_hms(1992, 10, 13, 0, 0, 0).unwrap();
+        let sun = sun_equatorial(time);
+        let (ra, dec) = sun_position_from_jd(datetime_to_jd(time));
+        assert!((sun.ra - ra.to_radians()).abs() < 1e-12);
+        assert!((sun.dec - dec.to_radians()).abs() < 1e-12);
+        assert!((sun.ra.to_degrees() - 198.38).abs() < 0.05);
+        assert!((sun.dec.to_degrees() + 7.78).abs() < 0.05);
+    }
+}
@@ -0,0 +1,190 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Memoization of the Sun and Moon (jd, alt, az) grids of a night. The Darkness window rebuilds
+// its night events every frame and each darkness event scans a grid of a minute resolution, the
+// Moon grid alone takes 1441 high precision positions. Grids are kept for the most recent keys
+// (location, night, resolution and lunar theory), a changed observer or date gives a new key and the grids of
+// the previous ones are dropped as newer ones come in.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Instant;
+use crate::application::calculation::MoonModel;
+use crate::application::moon::moon_alt_az_grid_utc;
+use crate::application::sun::sun_alt_az_grid_utc;
+
+/// (jd, alt, az) rows of a Sun or Moon grid
+pub type Grid = Vec<(f64, f64, f64)>;
+
+// Grids kept, the Sun and the Moon of a few nights
+const CACHE_CAPACITY: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GridBody {
+    Sun,
+    Moon(MoonModel),
+}
+
+// Floating point values are compared by their bits, a grid is reused for identical inputs only
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct GridKey {
+    body: GridBody,
+    latitude: u64,
+    longitude: u64,
+    elevation: u64,
+    jd_start: u64,
+    jd_end: u64,
+    num_points: usize,
+}
+
+impl GridKey {
+    fn new(body: GridBody, lat: f64, lon: f64, elevation: f64, (jd_start, jd_end): (f64, f64),
+           num_points: usize) -> Self {
+        GridKey {
+            body,
+            latitude: lat.to_bits(),
+            longitude: lon.to_bits(),
+            elevation: elevation.to_bits(),
+            jd_start: jd_start.to_bits(),
+            jd_end: jd_end.to_bits(),
+            num_points,
+        }
+    }
+}
+
+/// EphemerisCache struct
+///
+/// Sun and Moon alt/az grids by location, night, resolution and lunar theory, the least recently used grid
+/// is dropped when `capacity` grids are kept.
+#[derive(Debug)]
+pub struct EphemerisCache {
+    grids: Vec<(GridKey, Rc<Grid>)>,
+    capacity: usize,
+}
+
+impl EphemerisCache {
+    pub fn new(capacity: usize) -> Self {
+        EphemerisCache { grids: Vec::new(), capacity: capacity.max(1) }
+    }
+
+    // Cached grid for the key, built and kept when missing, most recently used last
+    fn grid(&mut self, key: GridKey, build: impl FnOnce() -> Grid) -> Rc<Grid> {
+        let grid = match self.grids.iter().position(|(k, _)| *k == key) {
+            Some(index) => self.grids.remove(index).1,
+            None => {
+                let started = Instant::now();
+                let grid = Rc::new(build());
+                log::debug!("grid computed body={:?} points={} jd_start={:.5} elapsed_ms={}", key.body,
+                            key.num_points, f64::from_bits(key.jd_start), started.elapsed().as_millis());
+                grid
+            }
+        };
+        if self.grids.len() >= self.capacity {
+            self.grids.remove(0);
+        }
+        self.grids.push((key, Rc::clone(&grid)));
+        grid
+    }
+
+    /// Sun grid as built by `sun_alt_az_grid_utc` over `span`, (start, end) UTC Julian Dates
+    pub fn sun_grid(&mut self, lat: f64, lon: f64, span: (f64, f64), num_points: usize) -> Rc<Grid> {
+        let (jd_start, jd_end) = span;
+        let key = GridKey::new(GridBody::Sun, lat, lon, 0.0, span, num_points);
+        self.grid(key, || sun_alt_az_grid_utc(lat, lon, jd_start, jd_end, num_points))
+    }
+
+    /// Moon grid as built by `moon_alt_az_grid_utc` over `span`, (start, end) UTC Julian Dates
+    pub fn moon_grid(&mut self, lat: f64, lon: f64, elevation: f64, span: (f64, f64), num_points: usize,
+                     model: MoonModel) -> Rc<Grid> {
+        let (jd_start, jd_end) = span;
+        let key = GridKey::new(GridBody::Moon(model), lat, lon, elevation, span, num_points);
+        self.grid(key, || moon_alt_az_grid_utc(lat, lon, elevation, jd_start, jd_end, num_points, model))
+    }
+
+    /// Grids kept
+    pub fn len(&self) -> usize {
+        self.grids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.grids.is_empty()
+    }
+
+    /// Drops every grid
+    pub fn clear(&mut self) {
+        self.grids.clear();
+    }
+}
+
+thread_local! {
+    // Shared by Sun, Moon and Darkness, the application computes on the GUI thread only
+    static CACHE: RefCell<EphemerisCache> = RefCell::new(EphemerisCache::new(CACHE_CAPACITY));
+}
+
+/// Grids kept by the shared cache
+pub fn cached_grids() -> usize {
+    CACHE.with(|cache| cache.borrow().len())
+}
+
+/// Sun grid from the shared cache
+pub fn cached_sun_grid(lat: f64, lon: f64, jd_start: f64, jd_end: f64, num_points: usize) -> Rc<Grid> {
+    CACHE.with(|cache| cache.borrow_mut().sun_grid(lat, lon, (jd_start, jd_end), num_points))
+}
+
+/// Moon grid from the shared cache
+pub fn cached_moon_grid(lat: f64, lon: f64, elevation: f64, jd_start: f64, jd_end: f64,
+                        num_points: usize, model: MoonModel) -> Rc<Grid> {
+    CACHE.with(|cache| cache.borrow_mut().moon_grid(lat, lon, elevation, (jd_start, jd_end), num_points, model))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ephemeris_cache() {
+        let mut cache = EphemerisCache::new(3);
+        let start = 2_460_763.0;
+        let sun = cache.sun_grid(-23.1, -46.5, (start, start + 1.0), 144);
+        assert_eq!(*sun, sun_alt_az_grid_utc(-23.1, -46.5, start, start + 1.0, 144));
+
+        // same key, same grid
+        assert!(Rc::ptr_eq(&sun, &cache.sun_grid(-23.1, -46.5, (start, start + 1.0), 144)));
+        assert_eq!(cache.len(), 1);
+
+        // another observer, night or resolution is another grid
+        let moved = cache.sun_grid(-23.2, -46.5, (start, start + 1.0), 144);
+        assert!(!Rc::ptr_eq(&sun, &moved));
+        cache.sun_grid(-23.1, -46.5, (start + 1.0, start + 2.0), 144);
+        assert_eq!(cache.len(), 3);
+
+        // the least recently used grid is dropped first
+        cache.moon_grid(-23.1, -46.5, 780.0, (start, start + 1.0), 144, MoonModel::High);
+        assert_eq!(cache.len(), 3);
+        assert!(Rc::ptr_eq(&moved, &cache.sun_grid(-23.2, -46.5, (start, start + 1.0), 144)));
+        assert!(!Rc::ptr_eq(&sun, &cache.sun_grid(-23.1, -46.5, (start, start + 1.0), 144)));
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,297 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Telescopes and cameras of the observer. The field of view and the image scale of the active
+// pair tell which targets frame well: a target much smaller than the field is a few pixels
+// across, one larger than the field needs a mosaic.
+
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+
+// Values accepted in the equipment setup, lengths in mm and pixels in µm
+pub const FOCAL_LENGTH_RANGE: RangeInclusive<f64> = 10.0..=20_000.0;
+pub const APERTURE_RANGE: RangeInclusive<f64> = 10.0..=2_000.0;
+pub const PIXEL_SIZE_RANGE: RangeInclusive<f64> = 0.5..=30.0;
+pub const RESOLUTION_RANGE: RangeInclusive<u32> = 100..=20_000;
+
+/// Share of the short side of the field a target fills at most to frame with some margin, and
+/// at least to be more than a small spot
+pub const FILL_MAX: f64 = 0.9;
+pub const FILL_MIN: f64 = 0.1;
+
+// Arc seconds per radian / 1000, for µm over mm
+const ARCSEC_PER_RADIAN_MILLI: f64 = 206.264_806;
+
+/// Telescope struct
+///
+/// # Attributes
+///
+/// * `name` - Name of the profile, unique among the telescopes
+/// * `focal_length` - Effective focal length in mm, with any reducer or barlow
+/// * `aperture` - Aperture in mm
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Telescope {
+    pub name: String,
+    pub focal_length: f64,
+    pub aperture: f64,
+}
+
+/// Camera struct
+///
+/// # Attributes
+///
+/// * `name` - Name of the profile, unique among the cameras
+/// * `pixel_size` - Pixel size in µm
+/// * `width` - Sensor width in pixels
+/// * `height` - Sensor height in pixels
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Camera {
+    pub name: String,
+    pub pixel_size: f64,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Equipment struct
+///
+/// # Attributes
+///
+/// * `telescopes` - Telescope profiles
+/// * `cameras` - Camera profiles
+/// * `active_telescope` - Name of the telescope in use, None when no setup is selected
+/// * `active_camera` - Name of the camera in use
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Equipment {
+    #[serde(default)]
+    pub telescopes: Vec<Telescope>,
+    #[serde(default)]
+    pub cameras: Vec<Camera>,
+    #[serde(default)]
+    pub active_telescope: Option<String>,
+    #[serde(default)]
+    pub active_camera: Option<String>,
+}
+
+/// FieldOfView struct
+///
+/// # Attributes
+///
+/// * `width` - Field along the sensor width in arc minutes
+/// * `height` - Field along the sensor height in arc minutes
+/// * `scale` - Image scale in arc seconds per pixel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldOfView {
+    pub width: f64,
+    pub height: f64,
+    pub scale: f64,
+}
+
+/// How a target of known size frames in the field of view
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TargetFit {
+    /// The size of the target is not known
+    Unknown,
+    /// Smaller than a tenth of the field
+    Small,
+    Fits,
+    /// Larger than the field, needs a mosaic or a shorter focal length
+    Large,
+}
+
+impl TargetFit {
+    pub fn description(&self) -> &'static str {
+        match self {
+            TargetFit::Unknown => "",
+            TargetFit::Small => "small",
+            TargetFit::Fits => "fits",
+            TargetFit::Large => "too large",
+        }
+    }
+}
+
+fn check_range<T: PartialOrd + std::fmt::Display>(name: &str, value: T, range: &RangeInclusive<T>)
+                                                    -> Result<(), String> {
+    if range.contains(&value) {
+        Ok(())
+    } else {
+        Err(format!("{} must be from {} to {}", name, range.start(), range.end()))
+    }
+}
+
+impl Telescope {
+    pub fn focal_ratio(&self) -> f64 {
+        self.focal_length / self.aperture
+    }
+
+    pub fn check(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Telescope name is empty".to_string());
+        }
+        check_range("Focal length", self.focal_length, &FOCAL_LENGTH_RANGE)?;
+        check_range("Aperture", self.aperture, &APERTURE_RANGE)
+    }
+}
+
+impl Camera {
+    /// Sensor width and height in mm
+    pub fn sensor_size(&self) -> (f64, f64) {
+        (self.width as f64 * self.pixel_size / 1000.0, self.height as f64 * self.pixel_size / 1000.0)
+    }
+
+    pub fn check(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Camera name is empty".to_string());
+        }
+        check_range("Pixel size", self.pixel_size, &PIXEL_SIZE_RANGE)?;
+        check_range("Width", self.width, &RESOLUTION_RANGE)?;
+        check_range("Height", self.height, &RESOLUTION_RANGE)
+    }
+}
+
+/// Field of view and image scale of a camera at the focus of a telescope
+pub fn field_of_view(telescope: &Telescope, camera: &Camera) -> FieldOfView {
+    let (sensor_width, sensor_height) = camera.sensor_size();
+    let angle = |side: f64| 2.0 * (side / (2.0 * telescope.focal_length)).atan().to_degrees() * 60.0;
+    FieldOfView {
+        width: angle(sensor_width),
+        height: angle(sensor_height),
+        scale: ARCSEC_PER_RADIAN_MILLI * camera.pixel_size / telescope.focal_length,
+    }
+}
+
+impl FieldOfView {
+    /// Share of the short side of the field the target spans, size in arc minutes
+    pub fn fill(&self, size: f64) -> f64 {
+        size / self.width.min(self.height)
+    }
+
+    /// How a target of `size` arc minutes frames, 0 is an unknown size
+    pub fn fit(&self, size: f64) -> TargetFit {
+        if size <= 0.0 {
+            return TargetFit::Unknown;
+        }
+        match self.fill(size) {
+            fill if fill > FILL_MAX => TargetFit::Large,
+            fill if fill < FILL_MIN => TargetFit::Small,
+            _ => TargetFit::Fits,
+        }
+    }
+
+    /// e.g. 100.9' x 67.5', 0.97"/px
+    pub fn description(&self) -> String {
+        format!("{:.1}' x {:.1}', {:.2}\"/px", self.width, self.height, self.scale)
+    }
+}
+
+impl Equipment {
+    pub fn telescope(&self, name: &str) -> Option<&Telescope> {
+        self.telescopes.iter().find(|t| t.name == name)
+    }
+
+    pub fn camera(&self, name: &str) -> Option<&Camera> {
+        self.cameras.iter().find(|c| c.name == name)
+    }
+
+    /// Telescope and camera in use, None until both are selected
+    pub fn active(&self) -> Option<(&Telescope, &Camera)> {
+        let telescope = self.telescope(self.active_telescope.as_deref()?)?;
+        let camera = self.camera(self.active_camera.as_deref()?)?;
+        Some((telescope, camera))
+    }
+
+    /// Field of view of the setup in use
+    pub fn active_field_of_view(&self) -> Option<FieldOfView> {
+        self.active().map(|(telescope, camera)| field_of_view(telescope, camera))
+    }
+
+    /// Adds the telescope, or replaces the one with the same name
+    pub fn save_telescope(&mut self, telescope: Telescope) {
+        match self.telescopes.iter_mut().find(|t| t.name == telescope.name) {
+            Some(existing) => *existing = telescope,
+            None => self.telescopes.push(telescope),
+        }
+    }
+
+    /// Adds the camera, or replaces the one with the same name
+    pub fn save_camera(&mut self, camera: Camera) {
+        match self.cameras.iter_mut().find(|c| c.name == camera.name) {
+            Some(existing) => *existing = camera,
+            None => self.cameras.push(camera),
+        }
+    }
+
+    /// Removes a telescope, it is no longer active
+    pub fn remove_telescope(&mut self, name: &str) {
+        self.telescopes.retain(|t| t.name != name);
+        if self.active_telescope.as_deref() == Some(name) {
+            self.active_telescope = None;
+        }
+    }
+
+    /// Removes a camera, it is no longer active
+    pub fn remove_camera(&mut self, name: &str) {
+        self.cameras.retain(|c| c.name != name);
+        if self.active_camera.as_deref() == Some(name) {
+            self.active_camera = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_field_of_view() {
+        // 800 mm refractor with an APS-C sensor of 6248 x 4176 pixels of 3.76 µm
+        let telescope = Telescope { name: "Refractor".to_string(), focal_length: 800.0, aperture: 130.0 };
+        let camera = Camera { name: "APS-C".to_string(), pixel_size: 3.76, width: 6248, height: 4176 };
+        assert!(telescope.check().is_ok() && camera.check().is_ok());
+        let fov = field_of_view(&telescope, &camera);
+        assert!((fov.width - 100.9).abs() < 0.1);
+        assert!((fov.height - 67.5).abs() < 0.1);
+        assert!((fov.scale - 0.969).abs() < 0.001);
+        assert!((telescope.focal_ratio() - 6.15).abs() < 0.01);
+
+        // M 31 is too large, M 8 fits and M 57 is a small spot
+        assert_eq!(fov.fit(190.0), TargetFit::Large);
+        assert_eq!(fov.fit(45.0), TargetFit::Fits);
+        assert_eq!(fov.fit(1.4), TargetFit::Small);
+        assert_eq!(fov.fit(0.0), TargetFit::Unknown);
+
+        let mut equipment = Equipment::default();
+        equipment.save_telescope(telescope.clone());
+        equipment.save_camera(camera);
+        assert!(equipment.active_field_of_view().is_none());
+        equipment.active_telescope = Some("Refractor".to_string());
+        equipment.active_camera = Some("APS-C".to_string());
+        assert_eq!(equipment.active_field_of_view(), Some(fov));
+
+        // same name replaces, removing the active one clears the setup
+        equipment.save_telescope(Telescope { focal_length: 600.0, ..telescope });
+        assert_eq!(equipment.telescopes.len(), 1);
+        assert!(equipment.active_field_of_view().unwrap().width > fov.width);
+        equipment.remove_telescope("Refractor");
+        assert!(equipment.active().is_none());
+        assert!(Telescope { name: " ".to_string(), focal_length: 800.0, aperture: 130.0 }.check().is_err());
+    }
+}
@@ -0,0 +1,458 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Shared formatting of computed events, used by the dialogs and the reports so that both show
+// the same strings for the same values.
+
+use crate::application::{
+    darkness::Darkness,
+    environment::Environment,
+    moon::{moon_libration, Moon},
+    observer::Observer,
+    preferences::Preferences,
+    sun::CrossingDirection::{Rising, Setting},
+    sun::LightBand::GoldenHour,
+    sun::RiseSetType::Next,
+    sun::{Sun, SunEvent},
+    sun::TwilightType::{AstronomicalTwilight, CivilTwilight, NauticalTwilight, RiseSet},
+    time::{LocalTime, Time},
+};
+use crate::utils::angle::format_dms;
+
+pub const NEVER_RISES: &str = "Never Rises";
+pub const NEVER_SETS: &str = "Never Sets";
+
+// Narrowest time column of the reports, "14-11 22:05" with the default format
+const MIN_TIME_WIDTH: usize = 11;
+pub const ALWAYS_UP: &str = "Always up";
+pub const ALWAYS_DOWN: &str = "Always down";
+pub const MOON_UP: &str = "Moon up";
+pub const NO_DARKNESS: &str = "No darkness";
+
+// JD rounded to the nearest second when seconds are shown (Time::from_jd rounds to the second)
+// or to the nearest minute
+fn displayed_jd(jd: f64, preferences: &Preferences) -> f64 {
+    if preferences.show_seconds {
+        jd
+    } else {
+        (jd * 1440.0).round() / 1440.0
+    }
+}
+
+// Formats a JD with the preferred format, the clock formats follow the TimeFormatter of the
+// preferences (12/24 hour, date order and seconds)
+pub fn format_jd(jd: f64, preferences: &Preferences) -> String {
+    preferences.time_formatter().format(&Time::from_jd(displayed_jd(jd, preferences)), &preferences.time_format)
+}
+
+// Formats the clock time of a JD with the preferred 12 or 24-hour clock and seconds, without the
+// date, for tables with a row per day
+pub fn format_clock(jd: f64, preferences: &Preferences) -> String {
+    preferences.time_formatter().clock(&Time::from_jd(displayed_jd(jd, preferences)))
+}
+
+// Formats a local time with the preferred format, like format_jd, with the UTC offset in the
+// formats that carry a zone
+pub fn format_local(time: LocalTime, preferences: &Preferences) -> String {
+    let time = LocalTime::new(displayed_jd(time.jd, preferences), time.offset);
+    match preferences.time_format.as_str() {
+        "utc" | "isot" | "jd" | "mjd" => time.to_string(Some(&preferences.time_format)),
+        format => preferences.time_formatter().format(&time.to_local(), format),
+    }
+}
+
+/// Width of the local time columns of the reports: the longest time of the preferred format, and
+/// never narrower than the texts sharing the columns like "Never Sets"
+pub fn time_width(preferences: &Preferences) -> usize {
+    // two digit day, month and hour, as wide as any time of the format
+    let widest = LocalTime::new(Time::new(2024, 12, 22, 22, 22, 22).to_jd(), 0.0);
+    format_local(widest, preferences).chars().count().max(MIN_TIME_WIDTH)
+}
+
+// Formats the wall clock time of a local time like format_clock, for tables with a row per day
+pub fn format_local_clock(time: LocalTime, preferences: &Preferences) -> String {
+    let time = LocalTime::new(displayed_jd(time.jd, preferences), time.offset);
+    preferences.time_formatter().clock(&time.to_local())
+}
+
+/// Times of events, a JD (UTC) or a LocalTime, formatted with the preferred format
+pub trait FormatTime: Copy {
+    fn format(self, preferences: &Preferences) -> String;
+}
+
+impl FormatTime for f64 {
+    fn format(self, preferences: &Preferences) -> String {
+        format_jd(self, preferences)
+    }
+}
+
+impl FormatTime for LocalTime {
+    fn format(self, preferences: &Preferences) -> String {
+        format_local(self, preferences)
+    }
+}
+
+// Formats an apparent diameter given in arc seconds, in arc minutes from one arc minute up
+pub fn format_diameter(diameter: f64) -> String {
+    if diameter >= 60.0 {
+        format!("{:.1}'", diameter / 60.0)
+    } else {
+        format!("{:.1}\"", diameter)
+    }
+}
+
+// Formats an elongation from the Sun, east (evening sky) or west (morning sky) of it
+pub fn format_elongation(elongation: f64) -> String {
+    format!("{:.1}° {}", elongation.abs(), if elongation >= 0.0 { "E" } else { "W" })
+}
+
+// Formats a latitude in decimal degrees or, with the preference, as D° M' S" N/S
+pub fn format_latitude(latitude: f64, preferences: &Preferences) -> String {
+    if preferences.dms_coordinates {
+        format_dms(latitude, true)
+    } else {
+        format!("{:.6}", latitude)
+    }
+}
+
+// Formats a longitude in decimal degrees or, with the preference, as D° M' S" E/W
+pub fn format_longitude(longitude: f64, preferences: &Preferences) -> String {
+    if preferences.dms_coordinates {
+        format_dms(longitude, false)
+    } else {
+        format!("{:.6}", longitude)
+    }
+}
+
+// Formats an elevation in meters in the unit of the preferences, with the symbol
+pub fn format_elevation(elevation: i64, preferences: &Preferences) -> String {
+    let unit = preferences.units.elevation;
+    format!("{} {}", unit.format(elevation), unit.symbol())
+}
+
+// Formats a rise/set event, events that do not happen are shown with the reason
+pub fn format_event<T: FormatTime>(event: SunEvent<T>, preferences: &Preferences) -> String {
+    match event {
+        SunEvent::At(time) => time.format(preferences),
+        SunEvent::NeverRises => NEVER_RISES.to_string(),
+        SunEvent::NeverSets => NEVER_SETS.to_string(),
+        SunEvent::AlwaysUp => ALWAYS_UP.to_string(),
+        SunEvent::AlwaysDown => ALWAYS_DOWN.to_string(),
+        SunEvent::MoonUp => MOON_UP.to_string(),
+    }
+}
+
+// Formats a darkness start or end, any missing event means there is no darkness
+pub fn format_darkness<T: FormatTime>(event: SunEvent<T>, preferences: &Preferences) -> String {
+    match event {
+        SunEvent::At(time) => time.format(preferences),
+        _ => NO_DARKNESS.to_string(),
+    }
+}
+
+// Formats a duration in hours as hh:mm, "-" when it can not be computed
+pub fn format_duration(hours: Option<f64>) -> String {
+    match hours {
+        Some(hours) => {
+            let minutes = (hours.max(0.0) * 60.0).round() as i64;
+            format!("{:02}:{:02}", minutes / 60, minutes % 60)
+        }
+        None => "-".to_string(),
+    }
+}
+
+// Formats the equation of time in minutes as a signed +mm m ss s, e.g. "+13m 42s"
+pub fn format_equation_of_time(minutes: f64) -> String {
+    let seconds = (minutes * 60.0).round() as i64;
+    let sign = if seconds < 0 { "-" } else { "+" };
+    format!("{}{}m {:02}s", sign, seconds.abs() / 60, seconds.abs() % 60)
+}
+
+const COMPASS_POINTS: [&str; 16] = [
+    "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW", "NNW",
+];
+
+// Nearest of the 16 compass points to an azimuth in degrees from north through east
+pub fn compass_point(azimuth: f64) -> &'static str {
+    COMPASS_POINTS[((azimuth.rem_euclid(360.0) / 22.5).round() as usize) % 16]
+}
+
+// Formats a rise or set azimuth with its compass point, e.g. "248° WSW", "-" when the body does
+// not rise or set
+pub fn format_direction(azimuth: Option<f64>) -> String {
+    match azimuth {
+        Some(azimuth) => format!("{:.0}° {}", azimuth.rem_euclid(360.0).round() % 360.0, compass_point(azimuth)),
+        None => "-".to_string(),
+    }
+}
+
+// Formats an altitude and azimuth with the compass point, e.g. "12.3° / 248° WSW"
+pub fn format_alt_az((altitude, azimuth): (f64, f64)) -> String {
+    format!("{:.1}° / {}", altitude, format_direction(Some(azimuth)))
+}
+
+// Event followed by where it happens, e.g. "18:42 at 248° WSW", the event alone when it does not
+// happen
+fn event_at(event: &str, direction: &str) -> String {
+    if direction == "-" {
+        event.to_string()
+    } else {
+        format!("{} at {}", event, direction)
+    }
+}
+
+// Describes a body that stays up or down (polar day, polar night or circumpolar Moon) and when
+// that ends, None when the body rises and sets normally
+pub fn format_circumpolar<T>(body: &str, event: SunEvent<T>, jd: f64, end_utc: Option<f64>,
+                             timezone: f64, preferences: &Preferences) -> Option<String> {
+    let state = match event {
+        SunEvent::AlwaysUp => "up",
+        SunEvent::AlwaysDown => "down",
+        _ => return None,
+    };
+    Some(match end_utc {
+        Some(end) => format!(
+            "{} continuously {} for {} days, until {}",
+            body,
+            state,
+            (end - jd).ceil() as i64,
+            format_local(LocalTime::new(end, timezone), preferences)
+        ),
+        None => format!("{} continuously {}", body, state),
+    })
+}
+
+/// NightEvents struct
+///
+/// Display strings, in local time, of every event of the selected night.
+#[derive(Debug, Clone, Default)]
+pub struct NightEvents {
+    pub sunset: String,
+    pub sunrise: String,
+    pub sunset_direction: String,
+    pub sunrise_direction: String,
+    pub civil_end: String,
+    pub civil_start: String,
+    pub nautical_end: String,
+    pub nautical_start: String,
+    pub astronomical_end: String,
+    pub astronomical_start: String,
+    pub sun_transit: String,
+    pub sun_max_altitude: String,
+    pub golden_evening_start: String,
+    pub blue_evening_start: String,
+    pub blue_morning_end: String,
+    pub golden_morning_end: String,
+    pub moonrise: String,
+    pub moonset: String,
+    pub moonrise_direction: String,
+    pub moonset_direction: String,
+    pub moon_transit: String,
+    pub moon_max_altitude: String,
+    pub moon_libration: String,
+    pub moon_axis: String,
+    pub dso_astronomical_start: String,
+    pub dso_astronomical_end: String,
+    pub dso_nautical_start: String,
+    pub dso_nautical_end: String,
+    pub night_length: String,
+    pub civil_twilight: String,
+    pub nautical_twilight: String,
+    pub astronomical_twilight: String,
+    pub astronomical_darkness: String,
+    pub moon_free_darkness: String,
+    pub sun_circumpolar: Option<String>,
+    pub moon_circumpolar: Option<String>,
+}
+
+impl NightEvents {
+    pub fn new(observer: &Observer, time: &Time, environment: &Environment,
+               preferences: &Preferences) -> NightEvents {
+        let settings = preferences.calculation;
+        let sun = Sun::new(observer, time, environment).with_settings(settings);
+        let moon = Moon::new(observer, time, environment).with_settings(settings);
+        let darkness = Darkness::new(observer, time, environment).with_settings(settings);
+        let jd = time.to_jd();
+
+        // The end of a polar day/night is only searched when the body does not rise or set
+        let sunset = sun.get_sunset_local(Next, RiseSet);
+        let sun_circumpolar = match sunset {
+            SunEvent::AlwaysUp | SunEvent::AlwaysDown => format_circumpolar(
+                "Sun", sunset, jd, sun.get_circumpolar_end_utc(RiseSet), observer.timezone,
+                preferences),
+            _ => None,
+        };
+        let moonset = moon.get_moonset_local(Next);
+        let moon_circumpolar = match moonset {
+            SunEvent::AlwaysUp | SunEvent::AlwaysDown => format_circumpolar(
+                "Moon", moonset, jd, moon.get_circumpolar_end_utc(), observer.timezone, preferences),
+            _ => None,
+        };
+
+        // Golden hour ends when the blue hour starts, the blue hour ends with civil twilight, so
+        // the golden hour crossings give both bands. Only searched when they are displayed.
+        let ((golden_evening_start, blue_evening_start), (blue_morning_end, golden_morning_end)) =
+            if preferences.light_bands {
+                (sun.get_band_local_str(GoldenHour, Setting, preferences),
+                 sun.get_band_local_str(GoldenHour, Rising, preferences))
+            } else {
+                Default::default()
+            };
+
+        // Transits, the Moon skips one night about once a month
+        let sun_transit = sun.get_transit_local();
+        let moon_transit = moon.get_transit_local();
+
+        // Libration at the selected time, it changes by less than a degree during a night
+        let libration = moon_libration(jd);
+
+        NightEvents {
+            sunset: sun.get_sunset_local_str(Next, RiseSet, preferences),
+            sunrise: sun.get_sunrise_local_str(Next, RiseSet, preferences),
+            sunset_direction: format_direction(sunset.time().map(|set| sun.azimuth(set.jd))),
+            sunrise_direction: format_direction(sun.get_sunrise_azimuth(Next, RiseSet)),
+            civil_end: sun.get_sunset_local_str(Next, CivilTwilight, preferences),
+            civil_start: sun.get_sunrise_local_str(Next, CivilTwilight, preferences),
+            nautical_end: sun.get_sunset_local_str(Next, NauticalTwilight, preferences),
+            nautical_start: sun.get_sunrise_local_str(Next, NauticalTwilight, preferences),
+            astronomical_end: sun.get_sunset_local_str(Next, AstronomicalTwilight, preferences),
+            astronomical_start: sun.get_sunrise_local_str(Next, AstronomicalTwilight, preferences),
+            sun_transit: format_local(sun_transit.time, preferences),
+            sun_max_altitude: format!("{:.1}°", sun_transit.altitude),
+            golden_evening_start,
+            blue_evening_start,
+            blue_morning_end,
+            golden_morning_end,
+            moonrise: moon.get_moonrise_local_str(Next, preferences),
+            moonset: moon.get_moonset_local_str(Next, preferences),
+            moonrise_direction: format_direction(moon.get_moonrise_azimuth(Next)),
+            moonset_direction: format_direction(moonset.time().map(|set| moon.azimuth(set.jd))),
+            moon_transit: moon_transit.map_or("-".to_string(), |transit| format_local(transit.time, preferences)),
+            moon_max_altitude: moon_transit.map_or("-".to_string(), |transit| format!("{:.1}°", transit.altitude)),
+            moon_libration: format!("l {:+.2}°  b {:+.2}°", libration.longitude, libration.latitude),
+            moon_axis: format!("{:.1}°", libration.position_angle),
+            dso_astronomical_start: darkness.get_darkness_local_astronomical_start_str(preferences),
+            dso_astronomical_end: darkness.get_darkness_local_astronomical_end_str(preferences),
+            dso_nautical_start: darkness.get_darkness_local_nautical_start_str(preferences),
+            dso_nautical_end: darkness.get_darkness_local_nautical_end_str(preferences),
+            night_length: format_duration(sun.night_duration(RiseSet)),
+            civil_twilight: format_duration(sun.twilight_duration(CivilTwilight)),
+            nautical_twilight: format_duration(sun.twilight_duration(NauticalTwilight)),
+            astronomical_twilight: format_duration(sun.twilight_duration(AstronomicalTwilight)),
+            astronomical_darkness: format_duration(sun.night_duration(AstronomicalTwilight)),
+            moon_free_darkness: format_duration(Some(darkness.moon_free_duration(AstronomicalTwilight))),
+            sun_circumpolar,
+            moon_circumpolar,
+        }
+    }
+}
+
+// Plain text summary of the night in local time, in the order the events happen, to paste in
+// chats or notes
+pub fn night_summary(observer: &Observer, time: &Time, events: &NightEvents) -> String {
+    let site = match &observer.name {
+        Some(name) => format!(" at {}", name),
+        None => String::new(),
+    };
+    let mut lines = vec![format!("Night of {}{} ({})", time.to_string(Some("yyyymmdd")), site,
+                                 observer.local_time(time.to_jd()).utc_offset())];
+    lines.push(format!("Sunset: {}", event_at(&events.sunset, &events.sunset_direction)));
+    lines.push(format!("Civil twilight end: {}", events.civil_end));
+    lines.push(format!("Nautical twilight end: {}", events.nautical_end));
+    lines.push(format!("Astronomical twilight end: {}", events.astronomical_end));
+    lines.push(format!("DSO astronomical darkness: {} - {}", events.dso_astronomical_start,
+                       events.dso_astronomical_end));
+    lines.push(format!("DSO nautical darkness: {} - {}", events.dso_nautical_start, events.dso_nautical_end));
+    lines.push(format!("Moonrise: {}", event_at(&events.moonrise, &events.moonrise_direction)));
+    lines.push(format!("Moonset: {}", event_at(&events.moonset, &events.moonset_direction)));
+    lines.push(format!("Astronomical twilight start: {}", events.astronomical_start));
+    lines.push(format!("Nautical twilight start: {}", events.nautical_start));
+    lines.push(format!("Civil twilight start: {}", events.civil_start));
+    lines.push(format!("Sunrise: {}", event_at(&events.sunrise, &events.sunrise_direction)));
+    lines.extend(events.sun_circumpolar.iter().cloned());
+    lines.extend(events.moon_circumpolar.iter().cloned());
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_format_direction() {
+        assert_eq!(compass_point(0.0), "N");
+        assert_eq!(compass_point(359.0), "N");
+        assert_eq!(compass_point(248.0), "WSW");
+        assert_eq!(compass_point(-90.0), "W");
+        assert_eq!(format_direction(Some(247.6)), "248° WSW");
+        assert_eq!(format_direction(Some(359.7)), "0° N");
+        assert_eq!(format_direction(None), "-");
+        assert_eq!(format_alt_az((-12.34, 247.6)), "-12.3° / 248° WSW");
+        assert_eq!(event_at("18:42", "248° WSW"), "18:42 at 248° WSW");
+        assert_eq!(event_at(NEVER_SETS, "-"), NEVER_SETS);
+    }
+
+    #[test]
+    fn test_time_width() {
+        let mut preferences = Preferences::default();
+        assert_eq!(time_width(&preferences), 11);
+        preferences.hour12 = true;
+        preferences.show_seconds = true;
+        let width = time_width(&preferences);
+        assert_eq!(width, "14-11 10:05:09 PM".len());
+        // every time of the day fits the column
+        for hour in 0..24 {
+            let time = LocalTime::new(Time::new(2024, 1, 5, hour, 5, 9).to_jd(), 0.0);
+            assert!(format_local(time, &preferences).chars().count() <= width);
+        }
+    }
+
+    #[test]
+    fn test_night_summary() {
+        // Paris in January, the events in the order they happen and nothing circumpolar
+        let observer = Observer::location(Some("Paris".to_string()), "48.85", "2.35", 0, "1");
+        let time = Time::new(2025, 1, 20, 12, 0, 0);
+        let events = NightEvents::new(&observer, &time, &Environment::default(), &Preferences::default());
+        let summary = night_summary(&observer, &time, &events);
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(lines.len(), 13);
+        assert_eq!(lines[0], "Night of 2025-01-20 at Paris (UTC+01:00)");
+        assert_eq!(lines[1], format!("Sunset: {} at {}", events.sunset, events.sunset_direction));
+        assert_eq!(lines[5], format!("DSO astronomical darkness: {} - {}", events.dso_astronomical_start,
+                                     events.dso_astronomical_end));
+        let titles: Vec<&str> = lines[1..].iter().map(|line| line.split(':').next().unwrap()).collect();
+        assert_eq!(titles, ["Sunset", "Civil twilight end", "Nautical twilight end", "Astronomical twilight end",
+                            "DSO astronomical darkness", "DSO nautical darkness", "Moonrise", "Moonset",
+                            "Astronomical twilight start", "Nautical twilight start", "Civil twilight start",
+                            "Sunrise"]);
+
+        // without a name the site is left out
+        let unnamed = Observer::location(None, "48.85", "2.35", 0, "1");
+        assert!(night_summary(&unnamed, &time, &events).starts_with("Night of 2025-01-20 (UTC+01:00)\n"));
+    }
+
+    #[test]
+    fn test_format_elongation() {
+        assert_eq!(format_elongation(47.17), "47.2° E");
+        assert_eq!(format_elongation(-22.04), "22.0° W");
+    }
+}
@@ -0,0 +1,121 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Intermediate values behind the results of a night, for the calculation inspector. Meant to
+// compare against other planetarium software: every crossing lists the search window, the coarse
+// scan step that brackets it and the refined time.
+
+use crate::application::countdown::SUN_EVENTS;
+use crate::application::darkness::Darkness;
+use crate::application::environment::Environment;
+use crate::application::formatting::{format_jd, format_local};
+use crate::application::moon::moon_altitude;
+use crate::application::observer::Observer;
+use crate::application::preferences::Preferences;
+use crate::application::rise_set_solver::{bracket_crossing, find_crossing, COARSE_STEPS, TOLERANCE};
+use crate::application::sun::{sun_altitude, sun_position_from_jd, CrossingDirection, SunEvent,
+                              TwilightType};
+use crate::application::time::{decimal_year, delta_t, Time};
+
+// Search window, bracket and refined time of one crossing
+fn crossing_lines(name: &str, altitude: &dyn Fn(f64) -> f64, window: (f64, f64), horizon: f64,
+                  is_rising: bool, observer: &Observer, preferences: &Preferences) -> Vec<String> {
+    let mut lines = vec![format!("\n   - {} (horizon {:.4}°)", name, horizon)];
+    match bracket_crossing(&altitude, window.0, window.1, horizon, is_rising) {
+        Ok(bracket) => {
+            lines.push(format!("\n     bracket  JD {:.6} ({:+.4}°)  ..  JD {:.6} ({:+.4}°)",
+                               bracket.jd_low, bracket.altitude_low, bracket.jd_high,
+                               bracket.altitude_high));
+            // same call as the reports, so the refined time matches them
+            if let Ok(jd) = find_crossing(altitude, window.0, window.1, horizon, is_rising) {
+                lines.push(format!("\n     refined  JD {:.6} ({:+.6}° from horizon)  UTC {}  local {}",
+                                   jd, altitude(jd) - horizon, format_jd(jd, preferences),
+                                   format_local(observer.local_time(jd), preferences)));
+            }
+        }
+        Err(reason) => lines.push(format!("\n     no crossing: {:?}", reason)),
+    }
+    lines
+}
+
+/// Intermediate values of the night of the selected time, as report lines
+pub fn inspect_night(observer: &Observer, time: &Time, environment: &Environment,
+                     preferences: &Preferences) -> Vec<String> {
+    let jd = time.to_jd();
+    let offset = observer.timezone / 24.0;
+    // night goes from local noon to next local noon
+    let window_start = (jd + 0.5).floor() - offset;
+    let window = (window_start, window_start + 1.0);
+    let start = Time::from_jd(window_start);
+    let year = decimal_year(jd);
+
+    let mut lines: Vec<String> = Vec::new();
+    lines.push("Input:".to_string());
+    lines.push(format!("\n   - Selected time           : {}", time.to_string(Some("utc"))));
+    lines.push(format!("\n   - JD (UTC)                : {:.6}", jd));
+    lines.push(format!("\n   - Delta T                 : {:.2} s (year {:.3})", delta_t(year), year));
+    lines.push(format!("\n   - JD (TT)                 : {:.6}", time.to_jd_tt()));
+    lines.push(format!("\n   - Time zone               : {:+.2} h = {:+.6} d", observer.timezone, offset));
+    lines.push(format!("\n   - Location                : lat {:.6}°  lon {:.6}°  elevation {} m",
+                       observer.latitude, observer.longitude, observer.elevation));
+    lines.push("\n\nNight window (local noon to local noon, floor(JD + 0.5) - tz / 24):".to_string());
+    lines.push(format!("\n   - Start                   : JD {:.6}  UTC {}", window.0, start.to_string(Some("utc"))));
+    lines.push(format!("\n   - End                     : JD {:.6}", window.1));
+    lines.push(format!("\n   - GST / LST at start      : {:.4}° / {:.4}°", start.to_gst(),
+                       observer.local_sidereal_time(&start)));
+    let (sun_ra, sun_dec) = sun_position_from_jd(window.0);
+    lines.push(format!("\n   - Sun RA / Dec at start   : {:.4}° / {:.4}°", sun_ra, sun_dec));
+
+    lines.push(format!("\n\nSolver: {} coarse steps ({:.1} min), bisection to {:.1} s",
+                       COARSE_STEPS, 1440.0 / COARSE_STEPS as f64, TOLERANCE * 86_400.0));
+
+    lines.push("\n\nSun (crossings in this night window only, the reports also search later nights):".to_string());
+    let sun = sun_altitude(observer.latitude, observer.longitude);
+    for (name, twilight, direction) in SUN_EVENTS {
+        let is_rising = direction == CrossingDirection::Rising;
+        lines.extend(crossing_lines(name, &sun, window, preferences.calculation.twilight_angle(twilight), is_rising,
+                                    observer, preferences));
+    }
+
+    lines.push("\n\nMoon (topocentric, crossings in this night window only):".to_string());
+    let moon = moon_altitude(observer.latitude, observer.longitude, observer.elevation as f64);
+    for (name, is_rising) in [("Moonrise", true), ("Moonset", false)] {
+        lines.extend(crossing_lines(name, &moon, window, preferences.calculation.moon_horizon(), is_rising, observer,
+                                    preferences));
+    }
+
+    lines.push("\n\nDarkness grid (1 minute, Sun below the twilight angle and Moon below the horizon):".to_string());
+    let darkness = Darkness::new(observer, time, environment).with_settings(preferences.calculation);
+    for (name, twilight) in [("Nautical", TwilightType::NauticalTwilight),
+                             ("Astronomical", TwilightType::AstronomicalTwilight)] {
+        let (first, last) = darkness.darkness_utc(twilight);
+        let event = |event: SunEvent| match event.jd() {
+            Some(jd) => format!("JD {:.6} (local {})", jd, format_local(observer.local_time(jd), preferences)),
+            None => format!("{:?}", event),
+        };
+        lines.push(format!("\n   - {:12} : first {}  last {}  dark minutes {:.0}",
+                           name, event(first), event(last),
+                           darkness.moon_free_duration(twilight) * 60.0));
+    }
+    lines.push("\n".to_string());
+    lines
+}
@@ -0,0 +1,147 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Sky brightness at the observer location from a user supplied grid, with the Bortle class
+// estimated from the zenith brightness.
+//
+// The grid is a CSV file with one point per line: latitude, longitude (degrees) and zenith sky
+// brightness in mag/arcsec² (SQM). Blank lines, comments starting with '#' and a header line are
+// skipped, e.g. a subset of the World Atlas of artificial night sky brightness converted to SQM.
+
+use std::fs::read_to_string;
+use crate::utils::utils::{cosd, sind};
+
+// Points further than this from the observer (degrees) are not used
+const MAX_DISTANCE: f64 = 0.5;
+
+/// SkyBrightnessGrid struct
+///
+/// Points (latitude, longitude, sqm) read from a CSV grid.
+#[derive(Debug, Clone, Default)]
+pub struct SkyBrightnessGrid {
+    pub points: Vec<(f64, f64, f64)>,
+}
+
+impl SkyBrightnessGrid {
+    pub fn from_csv(contents: &str) -> Result<SkyBrightnessGrid, String> {
+        let mut points: Vec<(f64, f64, f64)> = Vec::new();
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+            let values: Vec<f64> = fields.iter().filter_map(|f| f.parse::<f64>().ok()).collect();
+            if fields.len() != 3 || values.len() != 3 {
+                if number == 0 {
+                    continue; // header
+                }
+                return Err(format!("Invalid sky brightness point at line {}: {}", number + 1, line));
+            }
+            points.push((values[0], values[1], values[2]));
+        }
+        if points.is_empty() {
+            return Err("No sky brightness points found".to_string());
+        }
+        Ok(SkyBrightnessGrid { points })
+    }
+
+    pub fn load(file_path: &str) -> Result<SkyBrightnessGrid, String> {
+        let contents = read_to_string(file_path).map_err(|e| format!("{}: {}", file_path, e))?;
+        SkyBrightnessGrid::from_csv(&contents)
+    }
+
+    /// Sky brightness (mag/arcsec²) of the point nearest to the location, None when no point is
+    /// within 0.5°
+    pub fn sqm_at(&self, latitude: f64, longitude: f64) -> Option<f64> {
+        self.points
+            .iter()
+            .map(|&(lat, lon, sqm)| (distance(latitude, longitude, lat, lon), sqm))
+            .filter(|&(d, _)| d <= MAX_DISTANCE)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, sqm)| sqm)
+    }
+}
+
+// Great circle distance in degrees
+fn distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let cos_d = sind(lat1) * sind(lat2) + cosd(lat1) * cosd(lat2) * cosd(lon1 - lon2);
+    cos_d.clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+/// Bortle class (1 - 9) for a zenith sky brightness in mag/arcsec²
+pub fn bortle_class(sqm: f64) -> u8 {
+    match sqm {
+        s if s >= 21.99 => 1,
+        s if s >= 21.89 => 2,
+        s if s >= 21.69 => 3,
+        s if s >= 20.49 => 4,
+        s if s >= 19.50 => 5,
+        s if s >= 18.94 => 6,
+        s if s >= 18.38 => 7,
+        s if s >= 18.00 => 8,
+        _ => 9,
+    }
+}
+
+/// Description of the sky brightness at a location, e.g. "SQM 21.20 (Bortle 4)"
+pub fn sky_brightness_description(file_path: &str, latitude: f64, longitude: f64) -> String {
+    match SkyBrightnessGrid::load(file_path) {
+        Ok(grid) => sky_brightness_at(&grid, latitude, longitude),
+        Err(e) => e,
+    }
+}
+
+pub fn sky_brightness_at(grid: &SkyBrightnessGrid, latitude: f64, longitude: f64) -> String {
+    match grid.sqm_at(latitude, longitude) {
+        Some(sqm) => format!("SQM {:.2} (Bortle {})", sqm, bortle_class(sqm)),
+        None => "No sky brightness data for this location".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const GRID: &str = "latitude,longitude,sqm\n\
+                        # Sao Paulo and surroundings\n\
+                        -23.55,-46.63,17.5\n\
+                        -23.00,-46.40,20.1\n\
+                        -22.50,-45.60,21.4\n";
+
+    #[test]
+    fn test_sqm_at() {
+        let grid = SkyBrightnessGrid::from_csv(GRID).unwrap();
+        assert_eq!(grid.points.len(), 3);
+        assert_eq!(grid.sqm_at(-23.10, -46.35), Some(20.1));
+        assert_eq!(grid.sqm_at(10.0, 10.0), None);
+        assert!(SkyBrightnessGrid::from_csv("-23.5,-46.6\n-23.0,x,20.0").is_err());
+    }
+
+    #[test]
+    fn test_bortle_class() {
+        assert_eq!(bortle_class(22.0), 1);
+        assert_eq!(bortle_class(21.4), 4);
+        assert_eq!(bortle_class(20.1), 5);
+        assert_eq!(bortle_class(17.5), 9);
+    }
+}
@@ -0,0 +1,163 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Log of the application, for bug reports. The records go to skycalc.log in the configuration
+// directory and the most recent lines stay in memory for the diagnostics window. The log file is
+// rotated at start when it grew past a megabyte, keeping a few older ones next to it. The level
+// defaults to info and is set with the SKYCALC_LOG environment variable (error, warn, info,
+// debug or trace). Messages are written as a short event followed by key=value fields.
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use log::LevelFilter;
+use crate::application::recent::config_directory;
+
+pub const LOG_FILE: &str = "skycalc.log";
+
+/// Environment variable with the log level
+pub const LOG_LEVEL_VARIABLE: &str = "SKYCALC_LOG";
+
+// Size past which the log is rotated at start, and rotated files kept (skycalc.log.1 newest)
+const MAX_LOG_SIZE: u64 = 1_048_576;
+const ROTATED_FILES: usize = 3;
+
+/// Lines kept in memory for the diagnostics window
+pub const RECENT_LINES: usize = 200;
+
+// Written by every thread that logs, the calculations run on the rayon pool too
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Log file of the current user
+pub fn log_path() -> PathBuf {
+    config_directory().join(LOG_FILE)
+}
+
+/// Level from the environment variable, info when it is not set or not a level
+pub fn log_level(value: Option<&str>) -> LevelFilter {
+    value.and_then(|v| v.trim().parse().ok()).unwrap_or(LevelFilter::Info)
+}
+
+// Renames skycalc.log to skycalc.log.1, .1 to .2 and so on when the log is larger than
+// `max_size`, the oldest one is dropped
+fn rotate(path: &Path, max_size: u64, kept: usize) -> std::io::Result<()> {
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.len() > max_size => {}
+        _ => return Ok(()),
+    }
+    let rotated = |n: usize| PathBuf::from(format!("{}.{}", path.display(), n));
+    let _ = std::fs::remove_file(rotated(kept));
+    for n in (1..kept).rev() {
+        if rotated(n).exists() {
+            std::fs::rename(rotated(n), rotated(n + 1))?;
+        }
+    }
+    std::fs::rename(path, rotated(1))
+}
+
+fn remember(line: String) {
+    if let Ok(mut recent) = RECENT.lock() {
+        if recent.len() >= RECENT_LINES {
+            recent.pop_front();
+        }
+        recent.push_back(line);
+    }
+}
+
+/// Most recent log lines, oldest first
+pub fn recent_lines() -> Vec<String> {
+    RECENT.lock().map(|recent| recent.iter().cloned().collect()).unwrap_or_default()
+}
+
+/// Starts the logger, call once before the first window. The lines are kept in memory even when
+/// the log file can not be written.
+pub fn init_logging() -> Result<(), Box<dyn Error>> {
+    let level = log_level(std::env::var(LOG_LEVEL_VARIABLE).ok().as_deref());
+    let memory = fern::Output::call(|record| remember(record.args().to_string()));
+    let mut dispatch = fern::Dispatch::new()
+        .format(|out, message, record| {
+            out.finish(format_args!("{} {:5} {}: {}",
+                                    chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+                                    record.level(), record.target(), message))
+        })
+        .level(level)
+        .chain(memory);
+
+    let path = log_path();
+    let file = std::fs::create_dir_all(config_directory())
+        .and_then(|_| rotate(&path, MAX_LOG_SIZE, ROTATED_FILES))
+        .and_then(|_| fern::log_file(&path));
+    let file_error = match file {
+        Ok(file) => {
+            dispatch = dispatch.chain(file);
+            None
+        }
+        Err(e) => Some(e),
+    };
+    dispatch.apply()?;
+
+    log::info!("started version={} level={} log={}", env!("CARGO_PKG_VERSION"), level, path.display());
+    if let Some(e) = file_error {
+        log::warn!("log file unavailable path={} error={}", path.display(), e);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::application::logging::{log_level, rotate};
+    use log::LevelFilter;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_log_rotation() {
+        let directory = std::env::temp_dir().join("skycalc_test_log_rotation");
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("skycalc.log");
+        let rotated = |n: usize| PathBuf::from(format!("{}.{}", path.display(), n));
+
+        // small logs are kept
+        std::fs::write(&path, "first").unwrap();
+        rotate(&path, 10, 2).unwrap();
+        assert!(path.exists() && !rotated(1).exists());
+
+        // large ones move down, the oldest is dropped
+        std::fs::write(&path, "first run, long enough").unwrap();
+        rotate(&path, 10, 2).unwrap();
+        std::fs::write(&path, "second run, long enough").unwrap();
+        rotate(&path, 10, 2).unwrap();
+        std::fs::write(&path, "third run, long enough").unwrap();
+        rotate(&path, 10, 2).unwrap();
+        assert!(!path.exists());
+        assert_eq!(std::fs::read_to_string(rotated(1)).unwrap(), "third run, long enough");
+        assert_eq!(std::fs::read_to_string(rotated(2)).unwrap(), "second run, long enough");
+        assert!(!rotated(3).exists());
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(log_level(Some("debug")), LevelFilter::Debug);
+        assert_eq!(log_level(Some(" WARN ")), LevelFilter::Warn);
+        assert_eq!(log_level(Some("loud")), LevelFilter::Info);
+        assert_eq!(log_level(None), LevelFilter::Info);
+    }
+}
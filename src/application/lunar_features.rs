@@ -0,0 +1,184 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Embedded table of named lunar features for the observing notes of the night. Positions are
+// selenographic latitude and longitude in degrees, longitude positive to the east (towards Mare
+// Crisium), good to a few tenths of a degree.
+
+use crate::application::moon::moon_colongitude;
+use crate::utils::utils::{cosd, sind};
+
+// Features with the Sun between these altitudes are listed as near the terminator, the long
+// shadows of a low Sun show the relief best
+pub const TERMINATOR_MIN_SUN_ALTITUDE: f64 = -2.0;
+pub const TERMINATOR_MAX_SUN_ALTITUDE: f64 = 10.0;
+
+/// LunarFeature struct
+///
+/// Named crater, sea, mountain range or valley of the near side.
+///
+/// # Attributes
+///
+/// * `name` - IAU name
+/// * `latitude` - Selenographic latitude in degrees, positive to the north
+/// * `longitude` - Selenographic longitude in degrees, positive to the east
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LunarFeature {
+    pub name: &'static str,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+const fn feature(name: &'static str, latitude: f64, longitude: f64) -> LunarFeature {
+    LunarFeature { name, latitude, longitude }
+}
+
+// East to west, the order the morning terminator crosses them after new Moon
+pub const LUNAR_FEATURES: &[LunarFeature] = &[
+    feature("Langrenus", -8.9, 60.9),
+    feature("Petavius", -25.3, 60.4),
+    feature("Mare Crisium", 17.0, 59.1),
+    feature("Messier", -1.9, 47.6),
+    feature("Proclus", 16.1, 46.8),
+    feature("Atlas", 46.7, 44.4),
+    feature("Hercules", 46.7, 39.1),
+    feature("Posidonius", 31.9, 30.0),
+    feature("Theophilus", -11.4, 26.3),
+    feature("Cyrillus", -13.2, 24.0),
+    feature("Catharina", -18.1, 23.4),
+    feature("Aristoteles", 50.2, 17.4),
+    feature("Eudoxus", 44.3, 16.3),
+    feature("Maurolycus", -41.8, 14.0),
+    feature("Vallis Alpes", 48.5, 3.2),
+    feature("Walter", -33.0, 0.7),
+    feature("Ptolemaeus", -9.2, -1.8),
+    feature("Arzachel", -18.3, -1.9),
+    feature("Alphonsus", -13.4, -2.9),
+    feature("Montes Apenninus", 18.9, -3.7),
+    feature("Archimedes", 29.7, -4.0),
+    feature("Rupes Recta", -22.1, -7.8),
+    feature("Plato", 51.6, -9.4),
+    feature("Eratosthenes", 14.5, -11.3),
+    feature("Tycho", -43.3, -11.4),
+    feature("Clavius", -58.6, -14.7),
+    feature("Copernicus", 9.6, -20.1),
+    feature("Sinus Iridum", 44.1, -31.5),
+    feature("Kepler", 8.1, -38.0),
+    feature("Gassendi", -17.6, -40.0),
+    feature("Aristarchus", 23.7, -47.5),
+    feature("Schickard", -44.4, -54.6),
+    feature("Grimaldi", -5.2, -68.6),
+];
+
+/// Terminator enum
+///
+/// `Morning` terminator where the Sun rises on the Moon, east of the lit part before full Moon,
+/// and `Evening` terminator where it sets, after full Moon.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Terminator {
+    Morning,
+    Evening,
+}
+
+impl Terminator {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Terminator::Morning => "sunrise",
+            Terminator::Evening => "sunset",
+        }
+    }
+}
+
+/// Lunar feature near the terminator with the altitude of the Sun over it in degrees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TerminatorFeature {
+    pub feature: LunarFeature,
+    pub sun_altitude: f64,
+    pub terminator: Terminator,
+}
+
+/// Altitude of the Sun in degrees over a feature and the terminator it is closer to, from the
+/// colongitude and the latitude of the subsolar point
+pub fn sun_altitude(feature: &LunarFeature, colongitude: f64, subsolar_latitude: f64) -> (f64, Terminator) {
+    // hour angle of the Sun from the local sunrise
+    let angle = colongitude + feature.longitude;
+    let altitude = (sind(subsolar_latitude) * sind(feature.latitude)
+        + cosd(subsolar_latitude) * cosd(feature.latitude) * sind(angle))
+    .clamp(-1.0, 1.0)
+    .asin()
+    .to_degrees();
+    let terminator = if cosd(angle) >= 0.0 { Terminator::Morning } else { Terminator::Evening };
+    (altitude, terminator)
+}
+
+/// Features with the Sun between TERMINATOR_MIN_SUN_ALTITUDE and TERMINATOR_MAX_SUN_ALTITUDE at
+/// `jd` (UTC), north to south
+pub fn features_near_terminator(jd: f64) -> Vec<TerminatorFeature> {
+    let (colongitude, subsolar_latitude) = moon_colongitude(jd);
+    let mut features: Vec<TerminatorFeature> = LUNAR_FEATURES
+        .iter()
+        .map(|feature| {
+            let (sun_altitude, terminator) = sun_altitude(feature, colongitude, subsolar_latitude);
+            TerminatorFeature { feature: *feature, sun_altitude, terminator }
+        })
+        .filter(|f| (TERMINATOR_MIN_SUN_ALTITUDE..=TERMINATOR_MAX_SUN_ALTITUDE).contains(&f.sun_altitude))
+        .collect();
+    features.sort_by(|a, b| b.feature.latitude.total_cmp(&a.feature.latitude));
+    features
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::application::time::delta_t;
+
+    #[test]
+    fn test_lunar_feature_table() {
+        for (i, feature) in LUNAR_FEATURES.iter().enumerate() {
+            assert!((-90.0..=90.0).contains(&feature.latitude), "{}", feature.name);
+            assert!((-90.0..=90.0).contains(&feature.longitude), "{}", feature.name);
+            assert!(!LUNAR_FEATURES[i + 1..].iter().any(|f| f.name == feature.name), "{}", feature.name);
+        }
+        assert!(LUNAR_FEATURES.windows(2).all(|w| w[0].longitude >= w[1].longitude));
+    }
+
+    #[test]
+    fn test_features_near_terminator() {
+        // Meeus example 53.a, colongitude 22.11°: morning terminator just west of Copernicus
+        let jd_ut = 2_448_724.5 - delta_t(1992.28) / 86_400.0;
+        let features = features_near_terminator(jd_ut);
+        let names: Vec<&str> = features.iter().map(|f| f.feature.name).collect();
+        assert!(names.contains(&"Copernicus"));
+        assert!(names.contains(&"Tycho"));
+        // Aristarchus still in the night, Theophilus under a high Sun
+        assert!(!names.contains(&"Aristarchus"));
+        assert!(!names.contains(&"Theophilus"));
+        assert!(features.iter().all(|f| f.terminator == Terminator::Morning));
+        assert!(features.windows(2).all(|w| w[0].feature.latitude >= w[1].feature.latitude));
+
+        // evening terminator at the same longitude half a lunation later
+        let copernicus = LUNAR_FEATURES.iter().find(|f| f.name == "Copernicus").unwrap();
+        let (altitude, terminator) = sun_altitude(copernicus, 22.11 + 176.0, 0.0);
+        assert!(altitude > 0.0 && altitude < 10.0);
+        assert_eq!(terminator, Terminator::Evening);
+    }
+}
@@ -0,0 +1,98 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+
+// Zenith and meridian at a given time, and the targets near the meridian within the altitude
+// constraints: the ones at their highest, with the least air mass, to image now.
+
+use crate::application::constraint::Constraints;
+use crate::application::observer::Observer;
+use crate::application::target::{Target, NEAR_TRANSIT_HOUR_ANGLE};
+use crate::application::time::gst_from_jd;
+
+/// Right ascension and declination of the zenith in degrees at a JD (UTC), equinox of date. The
+/// right ascension is the one on the meridian, the local sidereal time.
+pub fn zenith(observer: &Observer, jd: f64) -> (f64, f64) {
+    ((gst_from_jd(jd) + observer.longitude).rem_euclid(360.0), observer.latitude)
+}
+
+/// MeridianTarget struct
+///
+/// # Attributes
+///
+/// * `target` - Target of the configuration
+/// * `hour_angle` - Hour angle in hours, negative east of the meridian, before the transit
+/// * `altitude` - Altitude in degrees
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeridianTarget {
+    pub target: Target,
+    pub hour_angle: f64,
+    pub altitude: f64,
+}
+
+/// Targets within NEAR_TRANSIT_HOUR_ANGLE (1 hour) of the meridian and within the altitude
+/// constraints at a JD (UTC), east to west so the ones still to transit come first
+pub fn near_meridian(observer: &Observer, jd: f64, targets: &[Target], constraints: &Constraints) -> Vec<MeridianTarget> {
+    let altitudes = (constraints.min_altitude as f64)..=(constraints.max_altitude as f64);
+    let mut near: Vec<MeridianTarget> = targets
+        .iter()
+        .map(|target| MeridianTarget {
+            target: target.clone(),
+            hour_angle: target.hour_angle(observer, jd) / 15.0,
+            altitude: target.altitude(observer, jd),
+        })
+        .filter(|m| m.hour_angle.abs() <= NEAR_TRANSIT_HOUR_ANGLE / 15.0 && altitudes.contains(&m.altitude))
+        .collect();
+    near.sort_by(|a, b| a.hour_angle.total_cmp(&b.hour_angle));
+    near
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::application::time::Time;
+
+    #[test]
+    fn test_near_meridian() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let jd = Time::new(2024, 12, 1, 4, 0, 0).to_jd();
+        let (ra, dec) = zenith(&observer, jd);
+        assert_eq!(dec, observer.latitude);
+        assert!((ra - observer.local_sidereal_time(&Time::new(2024, 12, 1, 4, 0, 0))).abs() < 1e-6);
+        // on the zenith the altitude is 90°
+        assert!((observer.altaz(ra, dec, jd).0 - 90.0).abs() < 1e-6);
+
+        let constraints = Constraints { min_altitude: 30, max_altitude: 90, ..Constraints::default() };
+        let targets = [
+            Target::new("On the meridian", ra, -10.0, 0.0),
+            Target::new("West", ra - 10.0, -30.0, 0.0),
+            Target::new("East", ra + 10.0, -30.0, 0.0),
+            Target::new("Low", ra, 70.0, 0.0),
+            Target::new("Two hours west", ra - 30.0, -23.0, 0.0),
+        ];
+        let near = near_meridian(&observer, jd, &targets, &constraints);
+        let names: Vec<&str> = near.iter().map(|m| m.target.name.as_str()).collect();
+        assert_eq!(names, ["East", "On the meridian", "West"]);
+        assert!((near[0].hour_angle + 10.0 / 15.0).abs() < 1e-6);
+        assert!(near.iter().all(|m| m.altitude >= 30.0));
+    }
+}
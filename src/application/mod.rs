@@ -8,4 +8,44 @@ pub mod moon;
 pub mod sun;
 pub mod transformations;
 pub mod darkness;
-pub mod reports;
\ No newline at end of file
+pub mod reports;
+pub mod target;
+pub mod eclipses;
+pub mod preferences;
+pub mod formatting;
+pub mod rise_set_solver;
+pub mod avoidance;
+pub mod sites;
+pub mod weather;
+pub mod light_pollution;
+pub mod elevation;
+pub mod countdown;
+pub mod inspector;
+pub mod ephemeris;
+pub mod ephemeris_cache;
+pub mod recent;
+pub mod scripting;
+pub mod undo;
+pub mod yaml_patch;
+pub mod quality;
+pub mod clock;
+pub mod observability;
+pub mod stars;
+pub mod satellites;
+pub mod solar_system;
+pub mod mount;
+pub mod timeline;
+pub mod session;
+pub mod schedule;
+pub mod lunar_features;
+pub mod calculation;
+pub mod logging;
+pub mod diagnostics;
+pub mod autosave;
+pub mod equipment;
+pub mod mosaic;
+pub mod config_check;
+pub mod observatory_library;
+pub mod meridian;
+pub mod units;
+pub mod timezones;
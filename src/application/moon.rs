@@ -24,28 +24,61 @@
 #![allow(dead_code, unused_variables)]
 
 use crate::application::{
+    calculation::{CalculationSettings, MoonModel},
     earth::nutation,
     environment::Environment,
+    ephemeris_cache::{cached_moon_grid, Grid},
+    formatting::{format_event, FormatTime},
     observer::Observer,
-    sun::RiseSetType,
-    time::Time,
-    transformations::equatorial_to_altaz,
+    preferences::Preferences,
+    rise_set_solver::find_crossing,
+    sun::{sun_distance_from_jd, sun_ecliptic_longitude_from_jd, sun_position_from_jd, RiseSetType, SunEvent, SunRS, Transit},
+    time::{gst_from_jd, julian_centuries_tt, LocalTime, Time},
+    transformations::{angular_separation, equatorial_to_altaz_jd, hour_angle_jd, topocentric_equatorial},
 };
 use crate::utils::utils::{
     constrain_360,
     cosd,
-    cross_horizon,
     sind,
     tand,
-    two_point_interpolation
 };
 use libm::atan2;
 use std::f64::consts::PI;
+use std::rc::Rc;
 
-#[derive(Debug)]
+// Topocentric altitude of the Moon's center at rise and set: refraction (34') plus the mean
+// semi-diameter (15.5'). The parallax is already in the topocentric position.
+pub const MOON_HORIZON: f64 = -0.825;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MoonRS {
     NeverRise,
     NeverSet,
+    AlwaysUp,
+    AlwaysDown,
+}
+
+impl From<SunRS> for MoonRS {
+    fn from(reason: SunRS) -> MoonRS {
+        match reason {
+            SunRS::NeverRise => MoonRS::NeverRise,
+            SunRS::NeverSet => MoonRS::NeverSet,
+            SunRS::AlwaysUp => MoonRS::AlwaysUp,
+            SunRS::AlwaysDown => MoonRS::AlwaysDown,
+        }
+    }
+}
+
+impl From<Result<f64, MoonRS>> for SunEvent {
+    fn from(result: Result<f64, MoonRS>) -> SunEvent {
+        match result {
+            Ok(jd) => SunEvent::At(jd),
+            Err(MoonRS::NeverRise) => SunEvent::NeverRises,
+            Err(MoonRS::NeverSet) => SunEvent::NeverSets,
+            Err(MoonRS::AlwaysUp) => SunEvent::AlwaysUp,
+            Err(MoonRS::AlwaysDown) => SunEvent::AlwaysDown,
+        }
+    }
 }
 
 // D, M, Mprime, F
@@ -243,6 +276,12 @@ const LUNAR_COEFF: &[&[f64]] = &[
 ];
 
 pub fn moon_position_low_precision(t: f64) -> (f64, f64) {
+    let (ra, dec, _) = moon_low_precision(t);
+    (ra, dec)
+}
+
+// Short series of the Astronomical Almanac, (ra, dec) in degrees and the horizontal parallax
+fn moon_low_precision(t: f64) -> (f64, f64, f64) {
     let l = 218.32 + 481_267.881 * t + 6.29 * sind(135.0 + 477_198.87 * t)
         - 1.27 * sind(259.3 - 413_335.36 * t)
         + 0.66 * sind(235.7 + 890_534.22 * t)
@@ -270,12 +309,49 @@ pub fn moon_position_low_precision(t: f64) -> (f64, f64) {
         ra += 2.0 * PI;
     }
     let dec = n.asin();
-    (ra.to_degrees(), dec.to_degrees())
+    (ra.to_degrees(), dec.to_degrees(), p)
+}
+
+/// Geocentric (ra, dec) in degrees and distance in km of the Moon with the selected lunar theory,
+/// `t` in Julian centuries (TT)
+pub fn moon_position(t: f64, model: MoonModel) -> (f64, f64, f64) {
+    match model {
+        MoonModel::High => moon_position_high_precision(t),
+        MoonModel::Low => {
+            let (ra, dec, parallax) = moon_low_precision(t);
+            (ra, dec, 6_378.14 / sind(parallax))
+        }
+    }
 }
 
 pub fn moon_position_high_precision(t: f64) -> (f64, f64, f64) {
-    //let t = jd2000_century_from_date(y, month, d);
+    let (true_lon, true_lat, radius) = moon_ecliptic_position(t);
+
+    // apparent longitude
+    let (delta_phi, _, mut eps) = nutation(t);
+    let apparent_lon = true_lon + delta_phi;
 
+    eps = eps.to_radians();
+
+    let right_ascension = constrain_360(
+        atan2(
+            eps.cos() * sind(apparent_lon) - eps.sin() * tand(true_lat),
+            cosd(apparent_lon),
+        )
+        .to_degrees(),
+    );
+
+    let declination = (sind(true_lat) * eps.cos()
+        + eps.sin() * sind(apparent_lon) * cosd(true_lat))
+    .asin()
+    .to_degrees();
+
+    (right_ascension, declination, radius)
+}
+
+// Geocentric ecliptic longitude and latitude (degrees, mean equinox of date) and distance (km) of
+// the Moon, Meeus, Astronomical Algorithms, ch. 47
+fn moon_ecliptic_position(t: f64) -> (f64, f64, f64) {
     // mean longitude of the Moon
     let lprime = constrain_360(
         218.316_447_7 + 481_267.881_234_21 * t - 0.001_578_6 * t * t + t * t * t / 538_841.0
@@ -361,121 +437,407 @@ pub fn moon_position_high_precision(t: f64) -> (f64, f64, f64) {
     let true_lat = sigmab / 1e6;
     let radius = 385_000.56 + sigmar / 1e3;
 
-    // apparent longitude
-    let (delta_phi, _, mut eps) = nutation(t);
-    let apparent_lon = true_lon + delta_phi;
+    (true_lon, true_lat, radius)
+}
 
-    eps = eps.to_radians();
+/// Libration struct
+///
+/// Geocentric libration of the Moon and position angle of its axis, in degrees. The librations
+/// are the selenographic longitude and latitude of the sub-Earth point: a positive longitude
+/// turns the east limb (Mare Crisium side) towards the Earth, a positive latitude the north limb.
+///
+/// # Attributes
+///
+/// * `longitude` - Total libration in longitude (optical and physical)
+/// * `latitude` - Total libration in latitude (optical and physical)
+/// * `position_angle` - Position angle of the Moon's north pole, from the celestial north through
+///   east
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Libration {
+    pub longitude: f64,
+    pub latitude: f64,
+    pub position_angle: f64,
+}
 
-    let right_ascension = constrain_360(
-        atan2(
-            eps.cos() * sind(apparent_lon) - eps.sin() * tand(true_lat),
-            cosd(apparent_lon),
-        )
-        .to_degrees(),
+// Astronomical unit in km
+const AU_KM: f64 = 149_597_870.7;
+
+// Inclination of the mean lunar equator to the ecliptic
+const LUNAR_EQUATOR_INCLINATION: f64 = 1.542_42;
+
+/// Libration and axis position angle of the Moon for a JD(UTC), Meeus, Astronomical Algorithms,
+/// ch. 53 (optical librations to ~0.01°, physical librations to ~0.001°)
+pub fn moon_libration(jd: f64) -> Libration {
+    let t = julian_centuries_tt(jd);
+    let (true_lon, lat, _) = moon_ecliptic_position(t);
+    let (ra, _, _) = moon_position_high_precision(t);
+    let (delta_phi, delta_eps, eps0) = nutation(t);
+    let lon = true_lon + delta_phi;
+    let eps = eps0 + delta_eps;
+    let terms = libration_terms(t);
+    let i = LUNAR_EQUATOR_INCLINATION;
+
+    let (longitude, latitude) = selenographic(lon, lat, delta_phi, &terms);
+
+    // position angle of the axis (eq. 53.3)
+    let v = terms.omega + delta_phi + terms.sigma / sind(i);
+    let x = sind(i + terms.rho) * sind(v);
+    let y = sind(i + terms.rho) * cosd(v) * cosd(eps) - cosd(i + terms.rho) * sind(eps);
+    let omega_axis = atan2(x, y).to_degrees();
+    let sin_p = (x * x + y * y).sqrt() * cosd(ra - omega_axis) / cosd(latitude);
+
+    Libration {
+        longitude,
+        latitude,
+        position_angle: constrain_360(sin_p.clamp(-1.0, 1.0).asin().to_degrees()),
+    }
+}
+
+// Arguments of the libration in degrees: argument of latitude F, mean ascending node Ω and the
+// physical libration terms ρ, σ and τ (Meeus, ch. 53)
+struct LibrationTerms {
+    f: f64,
+    omega: f64,
+    rho: f64,
+    sigma: f64,
+    tau: f64,
+}
+
+fn libration_terms(t: f64) -> LibrationTerms {
+    // fundamental arguments in degrees, as in moon_ecliptic_position
+    let d = constrain_360(
+        297.850_192_1 + 445_267.111_403_4 * t - 0.001_881_9 * t * t + t * t * t / 545_868.0
+            - t * t * t * t / 113_065_000.0,
+    );
+    let m = constrain_360(
+        357.529_109_2 + 35_999.050_290_9 * t - 0.000_153_6 * t * t + t * t * t / 24_490_000.0,
+    );
+    let mprime = constrain_360(
+        134.963_396_4 + 477_198.867_505_5 * t + 0.008_741_4 * t * t + t * t * t / 69_699.0
+            - t * t * t * t / 14_712_000.0,
+    );
+    let f = constrain_360(
+        93.272_095_0 + 483_202.017_523_3 * t - 0.003_653_9 * t * t - t * t * t / 3_526_000.0
+            + t * t * t * t / 863_310_000.0,
+    );
+    // longitude of the mean ascending node of the lunar orbit
+    let omega = constrain_360(
+        125.044_547_9 - 1_934.136_289_1 * t + 0.002_075_4 * t * t + t * t * t / 467_441.0
+            - t * t * t * t / 60_616_000.0,
     );
+    let e = 1.0 - 0.002_516 * t - 0.000_007_4 * t * t;
+    let k1 = 119.75 + 131.849 * t;
+    let k2 = 72.56 + 20.186 * t;
 
-    let declination = (sind(true_lat) * eps.cos()
-        + eps.sin() * sind(apparent_lon) * cosd(true_lat))
-    .asin()
+    // physical librations
+    let rho = -0.027_52 * cosd(mprime) - 0.022_45 * sind(f) + 0.006_84 * cosd(mprime - 2.0 * f)
+        - 0.002_93 * cosd(2.0 * f) - 0.000_85 * cosd(2.0 * f - 2.0 * d)
+        - 0.000_54 * cosd(mprime - 2.0 * d) - 0.000_20 * sind(mprime + f)
+        - 0.000_20 * cosd(mprime + 2.0 * f) - 0.000_20 * cosd(mprime - f)
+        + 0.000_14 * cosd(mprime + 2.0 * f - 2.0 * d);
+    let sigma = -0.028_16 * sind(mprime) + 0.022_44 * cosd(f) - 0.006_82 * sind(mprime - 2.0 * f)
+        - 0.002_79 * sind(2.0 * f) - 0.000_83 * sind(2.0 * f - 2.0 * d)
+        + 0.000_69 * sind(mprime - 2.0 * d) + 0.000_40 * cosd(mprime + f)
+        - 0.000_25 * sind(2.0 * mprime) - 0.000_23 * sind(mprime + 2.0 * f)
+        + 0.000_20 * cosd(mprime - f) + 0.000_19 * sind(mprime - f)
+        + 0.000_13 * sind(mprime + 2.0 * f - 2.0 * d) - 0.000_10 * cosd(mprime - 3.0 * f);
+    let tau = 0.025_20 * e * sind(m) + 0.004_73 * sind(2.0 * mprime - 2.0 * f)
+        - 0.004_67 * sind(mprime) + 0.003_96 * sind(k1) + 0.002_76 * sind(2.0 * mprime - 2.0 * d)
+        + 0.001_96 * sind(omega) - 0.001_83 * cosd(mprime - f) + 0.001_15 * sind(mprime - 2.0 * d)
+        - 0.000_96 * sind(mprime - d) + 0.000_46 * sind(2.0 * f - 2.0 * d)
+        - 0.000_39 * sind(mprime - f) - 0.000_32 * sind(mprime - m - d)
+        + 0.000_27 * sind(2.0 * mprime - m - 2.0 * d) + 0.000_23 * sind(k2)
+        - 0.000_14 * sind(2.0 * d) + 0.000_14 * cosd(2.0 * mprime - 2.0 * f)
+        - 0.000_12 * sind(mprime - 2.0 * f) - 0.000_12 * sind(2.0 * mprime)
+        + 0.000_11 * sind(2.0 * mprime - 2.0 * m - 2.0 * d);
+
+    LibrationTerms { f, omega, rho, sigma, tau }
+}
+
+// Selenographic longitude and latitude of the point under a direction of apparent ecliptic
+// longitude lon and latitude lat, optical (eq. 53.1) plus physical librations
+fn selenographic(lon: f64, lat: f64, delta_phi: f64, terms: &LibrationTerms) -> (f64, f64) {
+    let i = LUNAR_EQUATOR_INCLINATION;
+    let w = lon - delta_phi - terms.omega;
+    let a = atan2(
+        sind(w) * cosd(lat) * cosd(i) - sind(lat) * sind(i),
+        cosd(w) * cosd(lat),
+    )
     .to_degrees();
+    let optical_lon = constrain_360(a - terms.f + 180.0) - 180.0;
+    let optical_lat = (-sind(w) * cosd(lat) * sind(i) - sind(lat) * cosd(i)).asin().to_degrees();
 
-    (right_ascension, declination, radius)
+    let physical_lon = -terms.tau + (terms.rho * cosd(a) + terms.sigma * sind(a)) * tand(optical_lat);
+    let physical_lat = terms.sigma * cosd(a) - terms.rho * sind(a);
+    (optical_lon + physical_lon, optical_lat + physical_lat)
+}
+
+/// Selenographic colongitude of the Sun and latitude of the subsolar point in degrees for a
+/// JD(UTC), Meeus, Astronomical Algorithms, ch. 53
+///
+/// The colongitude is 90° minus the selenographic longitude of the Sun, the longitude of the
+/// morning terminator: about 0° at first quarter, 90° at full Moon, 180° at last quarter and
+/// 270° at new Moon.
+pub fn moon_colongitude(jd: f64) -> (f64, f64) {
+    let t = julian_centuries_tt(jd);
+    let (true_lon, lat, distance) = moon_ecliptic_position(t);
+    let (delta_phi, _, _) = nutation(t);
+    let lon = true_lon + delta_phi;
+    let terms = libration_terms(t);
+
+    // heliocentric direction of the Moon, corrected for the Moon-Earth distance
+    let sun_lon = sun_ecliptic_longitude_from_jd(jd) + delta_phi;
+    let ratio = distance / (sun_distance_from_jd(jd) * AU_KM);
+    let lon_h = sun_lon + 180.0 + ratio * 57.296 * cosd(lat) * sind(sun_lon - lon);
+    let lat_h = ratio * lat;
+
+    let (l0, b0) = selenographic(lon_h, lat_h, delta_phi, &terms);
+    ((90.0 - l0).rem_euclid(360.0), b0)
+}
+
+// Illuminated fraction of the Moon's disk (0.0 - 1.0) for a JD(UTC).
+// Phase angle from Meeus, Astronomical Algorithms, eq. 48.4 (accurate to ~0.5 deg in phase angle).
+pub fn moon_illumination(jd: f64) -> f64 {
+    let t = julian_centuries_tt(jd);
+    let d = constrain_360(
+        297.850_192_1 + 445_267.111_403_4 * t - 0.001_881_9 * t * t + t * t * t / 545_868.0
+            - t * t * t * t / 113_065_000.0,
+    );
+    let m = constrain_360(
+        357.529_109_2 + 35_999.050_290_9 * t - 0.000_153_6 * t * t + t * t * t / 24_490_000.0,
+    );
+    let mprime = constrain_360(
+        134.963_396_4 + 477_198.867_505_5 * t + 0.008_741_4 * t * t + t * t * t / 69_699.0
+            - t * t * t * t / 14_712_000.0,
+    );
+    let phase_angle = 180.0 - d - 6.289 * sind(mprime) + 2.100 * sind(m)
+        - 1.274 * sind(2.0 * d - mprime)
+        - 0.658 * sind(2.0 * d)
+        - 0.214 * sind(2.0 * mprime)
+        - 0.110 * sind(d);
+    (1.0 + cosd(phase_angle)) / 2.0
+}
+
+// Full Moons closer than this (km) are flagged as supermoons, about 90% of the way from the
+// mean apogee to the mean perigee
+pub const SUPERMOON_DISTANCE: f64 = 360_000.0;
+
+// Geocentric distance of the Moon in km for a JD(UTC)
+pub fn moon_distance(jd: f64) -> f64 {
+    moon_ecliptic_position(julian_centuries_tt(jd)).2
+}
+
+/// Apsis enum
+///
+/// Closest (`Perigee`) and farthest (`Apogee`) point of the lunar orbit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Apsis {
+    Perigee,
+    Apogee,
+}
+
+impl Apsis {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Apsis::Perigee => "perigee",
+            Apsis::Apogee => "apogee",
+        }
+    }
+}
+
+/// Perigee or apogee of the Moon: JD(UTC) and geocentric distance in km
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ApsisEvent {
+    pub apsis: Apsis,
+    pub jd: f64,
+    pub distance: f64,
+}
+
+// Local maxima of f between jd_start and jd_end, found on a scan every `step` days and refined by
+// golden section search to a few seconds
+pub(crate) fn local_maxima<F: Fn(f64) -> f64>(f: F, jd_start: f64, jd_end: f64, step: f64) -> Vec<f64> {
+    const TOLERANCE: f64 = 1e-4; // days
+    let ratio = (5.0_f64.sqrt() - 1.0) / 2.0;
+    // one step beyond each end to catch maxima right at the ends
+    let count = ((jd_end - jd_start) / step).ceil() as usize + 2;
+    let samples: Vec<(f64, f64)> = (0..=count)
+        .map(|i| {
+            let jd = jd_start + (i as f64 - 1.0) * step;
+            (jd, f(jd))
+        })
+        .collect();
+
+    samples
+        .windows(3)
+        .filter(|w| w[1].1 > w[0].1 && w[1].1 >= w[2].1)
+        .map(|w| {
+            let (mut low, mut high) = (w[0].0, w[2].0);
+            while high - low > TOLERANCE {
+                let left = high - ratio * (high - low);
+                let right = low + ratio * (high - low);
+                if f(left) < f(right) {
+                    low = left;
+                } else {
+                    high = right;
+                }
+            }
+            (low + high) / 2.0
+        })
+        .filter(|&jd| jd >= jd_start && jd < jd_end)
+        .collect()
+}
+
+/// Perigees and apogees of the Moon between jd_start and jd_end (UTC), in time order
+pub fn moon_apsides(jd_start: f64, jd_end: f64) -> Vec<ApsisEvent> {
+    const STEP: f64 = 0.5; // days, apsides are about 14 days apart
+    let event = |apsis: Apsis| move |jd: f64| ApsisEvent { apsis, jd, distance: moon_distance(jd) };
+    let mut apsides: Vec<ApsisEvent> = local_maxima(|jd| -moon_distance(jd), jd_start, jd_end, STEP)
+        .into_iter()
+        .map(event(Apsis::Perigee))
+        .chain(local_maxima(moon_distance, jd_start, jd_end, STEP).into_iter().map(event(Apsis::Apogee)))
+        .collect();
+    apsides.sort_by(|a, b| a.jd.total_cmp(&b.jd));
+    apsides
+}
+
+/// Next perigee or apogee of the Moon after jd (UTC), within an anomalistic month
+pub fn next_apsis(jd: f64, apsis: Apsis) -> Option<ApsisEvent> {
+    const ANOMALISTIC_MONTH: f64 = 27.6; // days, with a margin
+    moon_apsides(jd, jd + ANOMALISTIC_MONTH).into_iter().find(|event| event.apsis == apsis)
+}
+
+/// Full Moons between jd_start and jd_end (UTC), as the maxima of the Sun - Moon elongation,
+/// within an hour of the opposition in longitude
+pub fn full_moons(jd_start: f64, jd_end: f64) -> Vec<f64> {
+    const STEP: f64 = 1.0; // days, the elongation has a single maximum per lunation
+    let elongation = |jd: f64| {
+        let (moon_ra, moon_dec, _) = moon_position_high_precision(julian_centuries_tt(jd));
+        let (sun_ra, sun_dec) = sun_position_from_jd(jd);
+        angular_separation(moon_ra, moon_dec, sun_ra, sun_dec)
+    };
+    local_maxima(elongation, jd_start, jd_end, STEP)
+}
+
+/// Whether the full Moon at jd (UTC) is a supermoon, closer than `SUPERMOON_DISTANCE`
+pub fn is_supermoon(jd: f64) -> bool {
+    moon_distance(jd) < SUPERMOON_DISTANCE
+}
+
+// Topocentric (ra, dec) in degrees and geocentric distance in km of the Moon for a JD(UTC),
+// corrected for the lunar parallax seen from the observer's location and elevation (meters).
+pub fn moon_topocentric_position(lat: f64, lon: f64, elevation: f64, jd: f64) -> (f64, f64, f64) {
+    moon_topocentric_position_model(lat, lon, elevation, jd, MoonModel::High)
+}
+
+// Topocentric position with the selected lunar theory
+pub fn moon_topocentric_position_model(lat: f64, lon: f64, elevation: f64, jd: f64,
+                                       model: MoonModel) -> (f64, f64, f64) {
+    let (ra, dec, distance) = moon_position(julian_centuries_tt(jd), model);
+    let parallax = (6_378.14 / distance).asin().to_degrees();
+    let (topo_ra, topo_dec) =
+        topocentric_equatorial(lat, lon, elevation, ra, dec, parallax, gst_from_jd(jd));
+    (topo_ra, topo_dec, distance)
 }
 
 pub fn moon_alt_az_grid_utc(
     lat: f64,
     lon: f64,
+    elevation: f64,
     jd_start: f64,
     jd_end: f64,
     num_points: usize,
+    model: MoonModel,
 ) -> Vec<(f64, f64, f64)> {
-    let mut grid: Vec<(f64, f64, f64)> = Vec::new();
+    // num_points+1 rows computed serially into one allocation, the nights of the multi-night
+    // reports are the ones spread over the processor cores
+    let mut grid: Vec<(f64, f64, f64)> = Vec::with_capacity(num_points + 1);
     let inc = (jd_end - jd_start) / num_points as f64;
     for i in 0..=num_points {
         let jd = jd_start + inc * i as f64;
-        let t = (jd - 2_451_545.0) / 36_525.0; // jd2000 century
-        let (ra, dec, _) = moon_position_high_precision(t);
-        let date = Time::from_jd(jd);
-        let (alt, az) = equatorial_to_altaz(
-            lat,
-            lon,
-            ra,
-            dec,
-            date.year,
-            date.month,
-            date.day,
-            date.hour,
-            date.minute,
-            date.second,
-        );
+        let (ra, dec, _) = moon_topocentric_position_model(lat, lon, elevation, jd, model);
+        let (alt, az) = equatorial_to_altaz_jd(lat, lon, ra, dec, jd);
         grid.push((jd, alt, az));
     }
     grid
 }
 
-pub fn moonrise_utc_grid(lat: f64, lon: f64, jd: f64, tz: f64) -> Result<f64, MoonRS> {
-    let num_points = 288;
-    let target_night_start = (jd + 0.5).floor() + tz / 24.0; // Noon @ local time
-    let target_night_end = target_night_start + 1.0;
-    let moon = moon_alt_az_grid_utc(lat, lon, target_night_start, target_night_end, num_points);
-    let v = cross_horizon(moon, 0.125, true);
-    if v.is_empty() {
-        Err(MoonRS::NeverRise)
-    } else {
-        Ok(two_point_interpolation(
-            v[0].0, v[0].2, v[0].1, v[0].3, 0.125,
-        ))
+// Altitude of the Moon as a function of JD, for the rise/set solver
+pub fn moon_altitude(lat: f64, lon: f64, elevation: f64) -> impl Fn(f64) -> f64 {
+    moon_altitude_model(lat, lon, elevation, MoonModel::High)
+}
+
+// Altitude of the Moon with the selected lunar theory
+pub fn moon_altitude_model(lat: f64, lon: f64, elevation: f64, model: MoonModel) -> impl Fn(f64) -> f64 {
+    move |jd| {
+        let (ra, dec, _) = moon_topocentric_position_model(lat, lon, elevation, jd, model);
+        equatorial_to_altaz_jd(lat, lon, ra, dec, jd).0
     }
 }
 
+pub fn moonrise_utc_grid(lat: f64, lon: f64, elevation: f64, jd: f64, tz: f64,
+                       settings: CalculationSettings) -> Result<f64, MoonRS> {
+    let target_night_start = (jd + 0.5).floor() - tz / 24.0; // Noon @ local time
+    let target_night_end = target_night_start + 1.0;
+    let altitude = moon_altitude_model(lat, lon, elevation, settings.moon_model);
+    find_crossing(altitude, target_night_start, target_night_end, settings.moon_horizon(), true)
+        .map_err(|reason| MoonRS::from(SunRS::from_no_crossing(reason, SunRS::NeverRise)))
+}
+
 pub fn next_moonrise_utc(
     lat: f64,
     lon: f64,
+    elevation: f64,
     jd: f64,
     tz: f64,
     max_days: u32,
+    settings: CalculationSettings,
 ) -> Result<f64, MoonRS> {
     let mut current_jd = jd;
+    let mut result = Err(MoonRS::NeverRise);
     for _ in 0..max_days {
         // Limit to 2 days of iterations
-        match moonrise_utc_grid(lat, lon, current_jd, tz) {
-            Ok(moonrise) => return Ok(moonrise),
-            Err(MoonRS::NeverRise) => current_jd += 1.0, // Skip to the next day
-            Err(e) => return Err(e),
+        result = moonrise_utc_grid(lat, lon, elevation, current_jd, tz, settings);
+        if result.is_ok() {
+            break;
         }
+        current_jd += 1.0; // Skip to the next day
     }
-    Err(MoonRS::NeverRise) // Return error if no moon rise is found within the range
+    result // Keeps the reason if no moon rise is found within the range
 }
 
 pub fn previous_moonrise_utc(
     lat: f64,
     lon: f64,
+    elevation: f64,
     jd: f64,
     tz: f64,
     max_days: u32,
+    settings: CalculationSettings,
 ) -> Result<f64, MoonRS> {
     let mut current_jd = jd - 1.0;
+    let mut result = Err(MoonRS::NeverRise);
     for _ in 0..max_days {
         // Limit to 2 days of iterations
-        match moonrise_utc_grid(lat, lon, current_jd, tz) {
-            Ok(moonrise) => return Ok(moonrise),
-            Err(MoonRS::NeverRise) => current_jd -= 1.0, // Skip to the next day
-            Err(e) => return Err(e),
+        result = moonrise_utc_grid(lat, lon, elevation, current_jd, tz, settings);
+        if result.is_ok() {
+            break;
         }
+        current_jd -= 1.0; // Skip to the previous day
     }
-    Err(MoonRS::NeverRise) // Return error if no moon rise is found within the range
+    result // Keeps the reason if no moon rise is found within the range
 }
 
 pub fn nearest_moonrise_utc(
     lat: f64,
     lon: f64,
+    elevation: f64,
     jd: f64,
     tz: f64,
     max_days: u32,
+    settings: CalculationSettings,
 ) -> Result<f64, MoonRS> {
-    let next = next_moonrise_utc(lat, lon, jd, tz, max_days);
-    let previous = previous_moonrise_utc(lat, lon, jd, tz, max_days);
+    let next = next_moonrise_utc(lat, lon, elevation, jd, tz, max_days, settings);
+    let previous = previous_moonrise_utc(lat, lon, elevation, jd, tz, max_days, settings);
 
     match (next, previous) {
         (Ok(next_moonrise), Ok(previous_moonrise)) => {
@@ -491,68 +853,70 @@ pub fn nearest_moonrise_utc(
     }
 }
 
-pub fn moonset_utc_grid(lat: f64, lon: f64, jd: f64, tz: f64) -> Result<f64, MoonRS> {
-    let num_points = 288;
-    let target_night_start = (jd + 0.5).floor() + tz / 24.0; // Noon @ local time
+pub fn moonset_utc_grid(lat: f64, lon: f64, elevation: f64, jd: f64, tz: f64,
+                       settings: CalculationSettings) -> Result<f64, MoonRS> {
+    let target_night_start = (jd + 0.5).floor() - tz / 24.0; // Noon @ local time
     let target_night_end = target_night_start + 1.0;
-    let moon = moon_alt_az_grid_utc(lat, lon, target_night_start, target_night_end, num_points);
-    let v = cross_horizon(moon, 0.125, false);
-    if v.is_empty() {
-        Err(MoonRS::NeverSet)
-    } else {
-        Ok(two_point_interpolation(
-            v[0].0, v[0].2, v[0].1, v[0].3, 0.125,
-        ))
-    }
+    let altitude = moon_altitude_model(lat, lon, elevation, settings.moon_model);
+    find_crossing(altitude, target_night_start, target_night_end, settings.moon_horizon(), false)
+        .map_err(|reason| MoonRS::from(SunRS::from_no_crossing(reason, SunRS::NeverSet)))
 }
 
 pub fn next_moonset_utc(
     lat: f64,
     lon: f64,
+    elevation: f64,
     jd: f64,
     tz: f64,
     max_days: u32,
+    settings: CalculationSettings,
 ) -> Result<f64, MoonRS> {
     let mut current_jd = jd;
+    let mut result = Err(MoonRS::NeverSet);
     for _ in 0..max_days {
         // Limit to 2 days of iterations
-        match moonset_utc_grid(lat, lon, current_jd, tz) {
-            Ok(moonset) => return Ok(moonset),
-            Err(MoonRS::NeverSet) => current_jd += 1.0, // Skip to the next day
-            Err(e) => return Err(e),
+        result = moonset_utc_grid(lat, lon, elevation, current_jd, tz, settings);
+        if result.is_ok() {
+            break;
         }
+        current_jd += 1.0; // Skip to the next day
     }
-    Err(MoonRS::NeverSet) // Return error if no moon set is found within the range
+    result // Keeps the reason if no moon set is found within the range
 }
 
 pub fn previous_moonset_utc(
     lat: f64,
     lon: f64,
+    elevation: f64,
     jd: f64,
     tz: f64,
     max_days: u32,
+    settings: CalculationSettings,
 ) -> Result<f64, MoonRS> {
     let mut current_jd = jd - 1.0;
+    let mut result = Err(MoonRS::NeverSet);
     for _ in 0..max_days {
         // Limit to 2 days of iterations
-        match moonrise_utc_grid(lat, lon, current_jd, tz) {
-            Ok(moonset) => return Ok(moonset),
-            Err(MoonRS::NeverSet) => current_jd -= 1.0, // Skip to the next day
-            Err(e) => return Err(e),
+        result = moonset_utc_grid(lat, lon, elevation, current_jd, tz, settings);
+        if result.is_ok() {
+            break;
         }
+        current_jd -= 1.0; // Skip to the previous day
     }
-    Err(MoonRS::NeverSet) // Return error if no moon set is found within the range
+    result // Keeps the reason if no moon set is found within the range
 }
 
 pub fn nearest_moonset_utc(
     lat: f64,
     lon: f64,
+    elevation: f64,
     jd: f64,
     tz: f64,
     max_days: u32,
+    settings: CalculationSettings,
 ) -> Result<f64, MoonRS> {
-    let next = next_moonset_utc(lat, lon, jd, tz, max_days);
-    let previous = previous_moonset_utc(lat, lon, jd, tz, max_days);
+    let next = next_moonset_utc(lat, lon, elevation, jd, tz, max_days, settings);
+    let previous = previous_moonset_utc(lat, lon, elevation, jd, tz, max_days, settings);
 
     match (next, previous) {
         (Ok(next_moonset), Ok(previous_moonset)) => {
@@ -568,130 +932,327 @@ pub fn nearest_moonset_utc(
     }
 }
 
+// First rise or set of the Moon after jd, searched one day at a time. Used to find the end of a
+// period where the Moon stays above or below the horizon.
+pub fn moon_circumpolar_end_utc(lat: f64, lon: f64, elevation: f64, jd: f64, tz: f64,
+                                max_days: u32, settings: CalculationSettings) -> Option<f64> {
+    let mut current_jd = jd;
+    for _ in 0..max_days {
+        let moonrise = moonrise_utc_grid(lat, lon, elevation, current_jd, tz, settings).ok();
+        let moonset = moonset_utc_grid(lat, lon, elevation, current_jd, tz, settings).ok();
+        match (moonrise, moonset) {
+            (Some(moonrise), Some(moonset)) => return Some(moonrise.min(moonset)),
+            (Some(event), None) | (None, Some(event)) => return Some(event),
+            (None, None) => current_jd += 1.0, // Skip to the next day
+        }
+    }
+    None
+}
+
 pub struct Moon<'a> {
     pub observer: &'a Observer,
     pub time: &'a Time,
     pub environment: &'a Environment,
+    pub settings: CalculationSettings,
 }
 
+// Signature shared by the nearest/next/previous moonrise and moonset searches
+type MoonCrossingFn = fn(f64, f64, f64, f64, f64, u32, CalculationSettings) -> Result<f64, MoonRS>;
+
 impl<'a> Moon<'a> {
     pub fn new(observer: &'a Observer, time: &'a Time, environment: &'a Environment) -> Moon<'a> {
         Moon {
             observer,
             time,
             environment,
+            settings: CalculationSettings::default(),
         }
     }
 
-    fn get_moon_event_utc<F>(
+    /// Moon with the accuracy settings of the preferences instead of the defaults
+    pub fn with_settings(mut self, settings: CalculationSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Moon (jd, alt, az) from local noon to the next local noon in `num_points` steps, from the
+    /// shared ephemeris cache
+    pub fn night_alt_az_grid(&self, num_points: usize) -> Rc<Grid> {
+        let night_start = (self.time.to_jd() + 0.5).floor() - self.observer.timezone / 24.0;
+        cached_moon_grid(self.observer.latitude, self.observer.longitude, self.observer.elevation as f64,
+                         night_start, night_start + 1.0, num_points, self.settings.moon_model)
+    }
+
+    fn get_moon_event_utc(
         &self,
         rise_set_type: RiseSetType,
-        nearest_fn: F,
-        next_fn: F,
-        previous_fn: F,
-    ) -> f64
-    where
-        F: Fn(f64, f64, f64, f64, u32) -> Result<f64, MoonRS>,
-    {
+        nearest_fn: MoonCrossingFn,
+        next_fn: MoonCrossingFn,
+        previous_fn: MoonCrossingFn,
+    ) -> SunEvent {
         const MAX_DAYS: u32 = 2; // number of days to look forward or backward
         let latitude = self.observer.latitude;
         let longitude = self.observer.longitude;
+        let elevation = self.observer.elevation as f64;
         let jd = self.time.to_jd();
         let timezone = self.observer.timezone;
 
-        match rise_set_type {
-            RiseSetType::Nearest => {
-                nearest_fn(latitude, longitude, jd, timezone, MAX_DAYS).unwrap_or(0.0)
-            }
-            RiseSetType::Next => {
-                next_fn(latitude, longitude, jd, timezone, MAX_DAYS).unwrap_or(0.0)
-            }
-            RiseSetType::Previous => {
-                previous_fn(latitude, longitude, jd, timezone, MAX_DAYS).unwrap_or(0.0)
-            }
-        }
+        let result = match rise_set_type {
+            RiseSetType::Nearest => nearest_fn(latitude, longitude, elevation, jd, timezone, MAX_DAYS, self.settings),
+            RiseSetType::Next => next_fn(latitude, longitude, elevation, jd, timezone, MAX_DAYS, self.settings),
+            RiseSetType::Previous => previous_fn(latitude, longitude, elevation, jd, timezone, MAX_DAYS, self.settings),
+        };
+        SunEvent::from(result)
     }
 
-    pub fn get_moonrise_utc(&self, rise_set_type: RiseSetType) -> f64 {
+    pub fn get_moonrise_utc(&self, rise_set_type: RiseSetType) -> SunEvent {
         self.get_moon_event_utc(
             rise_set_type,
-            nearest_moonrise_utc as fn(f64, f64, f64, f64, u32) -> Result<f64, MoonRS>,
-            next_moonrise_utc as fn(f64, f64, f64, f64, u32) -> Result<f64, MoonRS>,
-            previous_moonrise_utc as fn(f64, f64, f64, f64, u32) -> Result<f64, MoonRS>,
+            nearest_moonrise_utc,
+            next_moonrise_utc,
+            previous_moonrise_utc,
         )
     }
 
-    pub fn get_moonset_utc(&self, rise_set_type: RiseSetType) -> f64 {
+    pub fn get_moonset_utc(&self, rise_set_type: RiseSetType) -> SunEvent {
         self.get_moon_event_utc(
             rise_set_type,
-            nearest_moonset_utc as fn(f64, f64, f64, f64, u32) -> Result<f64, MoonRS>,
-            next_moonset_utc as fn(f64, f64, f64, f64, u32) -> Result<f64, MoonRS>,
-            previous_moonset_utc as fn(f64, f64, f64, f64, u32) -> Result<f64, MoonRS>,
+            nearest_moonset_utc,
+            next_moonset_utc,
+            previous_moonset_utc,
         )
     }
 
-    pub fn get_moonrise_local(&self, rise_set_type: RiseSetType) -> f64 {
-        let utc = self.get_moonrise_utc(rise_set_type);
-        if utc == 0.0 {
-            0.0
-        } else {
-            utc + self.observer.timezone / 24.0
-        }
+    /// Topocentric azimuth of the Moon in degrees from north through east at a JD (UTC)
+    pub fn azimuth(&self, jd: f64) -> f64 {
+        let (latitude, longitude) = (self.observer.latitude, self.observer.longitude);
+        let (ra, dec, _) = moon_topocentric_position_model(latitude, longitude, self.observer.elevation as f64,
+                                                           jd, self.settings.moon_model);
+        equatorial_to_altaz_jd(latitude, longitude, ra, dec, jd).1
     }
 
-    pub fn get_moonset_local(&self, rise_set_type: RiseSetType) -> f64 {
-        let utc = self.get_moonset_utc(rise_set_type);
-        if utc == 0.0 {
-            0.0
-        } else {
-            utc + self.observer.timezone / 24.0
-        }
+    /// Azimuth where the Moon rises, None when it does not rise
+    pub fn get_moonrise_azimuth(&self, rise_set_type: RiseSetType) -> Option<f64> {
+        self.get_moonrise_utc(rise_set_type).jd().map(|jd| self.azimuth(jd))
+    }
+
+    /// Azimuth where the Moon sets, None when it does not set
+    pub fn get_moonset_azimuth(&self, rise_set_type: RiseSetType) -> Option<f64> {
+        self.get_moonset_utc(rise_set_type).jd().map(|jd| self.azimuth(jd))
+    }
+
+    /// End of a period where the Moon stays up or down (first rise or set after the selected
+    /// time) in UTC
+    pub fn get_circumpolar_end_utc(&self) -> Option<f64> {
+        const MAX_DAYS: u32 = 30; // a lunar month
+        moon_circumpolar_end_utc(
+            self.observer.latitude,
+            self.observer.longitude,
+            self.observer.elevation as f64,
+            self.time.to_jd(),
+            self.observer.timezone,
+            MAX_DAYS,
+            self.settings,
+        )
     }
 
-    fn get_moon_event_str<F>(
+    /// Upper transit of the Moon in UTC during the night (local noon to the next local noon),
+    /// None on the nights it transits just before and after the window
+    pub fn get_transit_utc(&self) -> Option<Transit> {
+        let (latitude, longitude) = (self.observer.latitude, self.observer.longitude);
+        let elevation = self.observer.elevation as f64;
+        let model = self.settings.moon_model;
+        let night_start = (self.time.to_jd() + 0.5).floor() - self.observer.timezone / 24.0;
+        // hour angle -180 to 180, crossing zero upwards at the upper transit
+        let hour_angle = |jd: f64| {
+            let (ra, _, _) = moon_topocentric_position_model(latitude, longitude, elevation, jd, model);
+            (hour_angle_jd(longitude, ra, jd) + 540.0) % 360.0 - 180.0
+        };
+        let transit = find_crossing(hour_angle, night_start, night_start + 1.0, 0.0, true).ok()?;
+        Some(Transit { time: transit, altitude: moon_altitude_model(latitude, longitude, elevation, model)(transit) })
+    }
+
+    pub fn get_transit_local(&self) -> Option<Transit<LocalTime>> {
+        self.get_transit_utc().map(|transit| transit.local(self.observer.timezone))
+    }
+
+    pub fn get_moonrise_local(&self, rise_set_type: RiseSetType) -> SunEvent<LocalTime> {
+        self.get_moonrise_utc(rise_set_type).local(self.observer.timezone)
+    }
+
+    pub fn get_moonset_local(&self, rise_set_type: RiseSetType) -> SunEvent<LocalTime> {
+        self.get_moonset_utc(rise_set_type).local(self.observer.timezone)
+    }
+
+    fn get_moon_event_str<T, F>(
         &self,
         rise_set_type: RiseSetType,
-        format: Option<&str>,
+        preferences: &Preferences,
         event_fn: F,
-        never_message: &str,
     ) -> String
     where
-        F: Fn(&Self, RiseSetType) -> f64,
+        T: FormatTime,
+        F: Fn(&Self, RiseSetType) -> SunEvent<T>,
     {
-        let event_time = event_fn(self, rise_set_type);
-        if event_time == 0.0 {
-            never_message.to_string()
-        } else {
-            Time::from_jd(event_time).to_string(format)
-        }
+        format_event(event_fn(self, rise_set_type), preferences)
     }
 
-    pub fn get_moonrise_utc_str(&self, rise_set_type: RiseSetType, format: Option<&str>) -> String {
-        self.get_moon_event_str(rise_set_type, format, Moon::get_moonrise_utc, "Never Rises")
+    pub fn get_moonrise_utc_str(&self, rise_set_type: RiseSetType, preferences: &Preferences) -> String {
+        self.get_moon_event_str(rise_set_type, preferences, Moon::get_moonrise_utc)
     }
 
     pub fn get_moonrise_local_str(
         &self,
         rise_set_type: RiseSetType,
-        format: Option<&str>,
+        preferences: &Preferences,
     ) -> String {
-        self.get_moon_event_str(
-            rise_set_type,
-            format,
-            Moon::get_moonrise_local,
-            "Never Rises",
-        )
+        self.get_moon_event_str(rise_set_type, preferences, Moon::get_moonrise_local)
     }
 
-    pub fn get_moonset_utc_str(&self, rise_set_type: RiseSetType, format: Option<&str>) -> String {
-        self.get_moon_event_str(rise_set_type, format, Moon::get_moonset_utc, "Never Sets")
+    pub fn get_moonset_utc_str(&self, rise_set_type: RiseSetType, preferences: &Preferences) -> String {
+        self.get_moon_event_str(rise_set_type, preferences, Moon::get_moonset_utc)
     }
 
     pub fn get_moonset_local_str(
         &self,
         rise_set_type: RiseSetType,
-        format: Option<&str>,
+        preferences: &Preferences,
     ) -> String {
-        self.get_moon_event_str(rise_set_type, format, Moon::get_moonset_local, "Never Sets")
+        self.get_moon_event_str(rise_set_type, preferences, Moon::get_moonset_local)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::application::calculation::CalculationSettings;
+    use crate::application::environment::Environment;
+    use crate::application::moon::{full_moons, is_supermoon, moon_altitude, moon_apsides,
+                                   moon_colongitude, moon_illumination, moon_libration,
+                                   moon_position_low_precision, moonrise_utc_grid,
+                                   moonset_utc_grid, next_apsis, Apsis, Moon};
+    use crate::application::observer::Observer;
+    use crate::application::sun::{RiseSetType, Sun, TwilightType};
+    use crate::application::time::{delta_t, julian_centuries_tt, Time};
+
+    #[test]
+    fn test_moon_low_precision() {
+        // Meeus, Astronomical Algorithms, example 47.a: 1992 April 12 at 0h TD, ra 134.688470°
+        // and dec 13.768368°, within the few tenths of a degree of the low precision series
+        let jd_ut = 2_448_724.5 - delta_t(1992.28) / 86_400.0;
+        let (ra, dec) = moon_position_low_precision(julian_centuries_tt(jd_ut));
+        assert!((ra - 134.688_470).abs() < 0.3);
+        assert!((dec - 13.768_368).abs() < 0.3);
+    }
+
+    #[test]
+    fn test_moon_illumination() {
+        // Meeus, Astronomical Algorithms, example 48.a: 1992 April 12 at 0h TD, k = 0.6786
+        let jd_ut = 2_448_724.5 - delta_t(1992.28) / 86_400.0;
+        assert!((moon_illumination(jd_ut) - 0.678_6).abs() < 0.005);
+    }
+
+    #[test]
+    fn test_moon_libration() {
+        // Meeus, Astronomical Algorithms, example 53.a: 1992 April 12 at 0h TD
+        let jd_ut = 2_448_724.5 - delta_t(1992.28) / 86_400.0;
+        let libration = moon_libration(jd_ut);
+        assert!((libration.longitude + 1.23).abs() < 0.01);
+        assert!((libration.latitude - 4.20).abs() < 0.01);
+        assert!((libration.position_angle - 15.08).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_moon_colongitude() {
+        // Meeus, Astronomical Algorithms, example 53.a: l0 = 67.89°, b0 = 1.46°, c0 = 22.11°
+        let jd_ut = 2_448_724.5 - delta_t(1992.28) / 86_400.0;
+        let (colongitude, subsolar_latitude) = moon_colongitude(jd_ut);
+        assert!((colongitude - 22.11).abs() < 0.05);
+        assert!((subsolar_latitude - 1.46).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_moon_transit() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        // full Moon, transit around local midnight
+        let time = Time::new(2025, 3, 14, 12, 0, 0);
+        let environment = Environment::default();
+        let moon = Moon::new(&observer, &time, &environment);
+        let transit = moon.get_transit_utc().unwrap();
+
+        let night_start = (time.to_jd() + 0.5).floor() + 3.0 / 24.0;
+        assert!(transit.time > night_start && transit.time < night_start + 1.0);
+        let altitude = moon_altitude(observer.latitude, observer.longitude, observer.elevation as f64);
+        assert!(altitude(transit.time - 0.01) < transit.altitude);
+        assert!(altitude(transit.time + 0.01) < transit.altitude);
+    }
+
+    #[test]
+    fn test_moon_rise_set_azimuth() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        // full Moon, rises in the east and sets in the west within its declination of ±28.6°
+        let time = Time::new(2025, 3, 14, 12, 0, 0);
+        let environment = Environment::default();
+        let moon = Moon::new(&observer, &time, &environment);
+        let rise = moon.get_moonrise_azimuth(RiseSetType::Next).unwrap();
+        let set = moon.get_moonset_azimuth(RiseSetType::Next).unwrap();
+        assert!((rise - 90.0).abs() < 32.0);
+        assert!((set - 270.0).abs() < 32.0);
+        // opposite the Sun, near the antisolar point on the horizon
+        let sun = Sun::new(&observer, &time, &environment);
+        let sun_set = sun.get_sunset_azimuth(RiseSetType::Next, TwilightType::RiseSet).unwrap();
+        assert!((rise - (sun_set - 180.0)).abs() < 15.0);
+    }
+
+    #[test]
+    fn test_moon_apsides() {
+        // Meeus, Astronomical Algorithms, example 50.a: apogee on 1988 October 7 at 20h30m TD
+        // with parallax 3240.679", 405 978 km
+        let jd = Time::new(1988, 10, 1, 0, 0, 0).to_jd();
+        let apogee = next_apsis(jd, Apsis::Apogee).unwrap();
+        assert!((apogee.jd - 2_447_442.354_3).abs() < 0.05);
+        assert!((apogee.distance - 405_978.0).abs() < 50.0);
+
+        // perigees and apogees alternate about two weeks apart
+        let apsides = moon_apsides(jd, jd + 365.0);
+        assert!(apsides.len() >= 26);
+        for pair in apsides.windows(2) {
+            assert_ne!(pair[0].apsis, pair[1].apsis);
+            assert!(pair[1].jd - pair[0].jd > 10.0 && pair[1].jd - pair[0].jd < 18.0);
+        }
+    }
+
+    #[test]
+    fn test_full_moons() {
+        // full Moons of 2025 March 14 06:55 UTC (total lunar eclipse) and November 5 13:19 UTC,
+        // a day from perigee
+        let start = Time::new(2025, 1, 1, 0, 0, 0).to_jd();
+        let full = full_moons(start, start + 365.0);
+        assert_eq!(full.len(), 12);
+        let march = Time::new(2025, 3, 14, 6, 55, 0).to_jd();
+        let november = Time::new(2025, 11, 5, 13, 19, 0).to_jd();
+        let nearest = |jd: f64| full.iter().map(|f| (f - jd).abs()).fold(f64::MAX, f64::min);
+        assert!(nearest(march) < 0.05);
+        assert!(nearest(november) < 0.05);
+        assert!(!is_supermoon(march));
+        assert!(is_supermoon(november));
+    }
+
+    #[test]
+    fn test_moon_rise_set_local_noon() {
+        // the searches run from local noon to the next local noon. Around the 2024 June full Moon
+        // Tokyo (UTC+9) sees the Moon rise at 18:37 on the 21st and set at 03:57 on the 22nd,
+        // São Paulo (UTC-3) at 18:04 on the 22nd and 08:21 on the 23rd.
+        let settings = CalculationSettings::default();
+        for (lat, lon, tz, day, rise, set) in [(35.68, 139.69, 9.0, 21, (21, 18, 37), (22, 3, 57)),
+                                               (-23.55, -46.63, -3.0, 22, (22, 18, 4), (23, 8, 21))] {
+            let jd = Time::new(2024, 6, day, 12, 0, 0).to_jd();
+            let local_jd = |(day, hour, minute): (u64, u64, u64)| {
+                Time::new(2024, 6, day, hour, minute, 0).to_jd() - tz / 24.0
+            };
+            let moonrise = moonrise_utc_grid(lat, lon, 0.0, jd, tz, settings).unwrap();
+            let moonset = moonset_utc_grid(lat, lon, 0.0, jd, tz, settings).unwrap();
+            assert!((moonrise - local_jd(rise)).abs() * 1440.0 < 5.0, "{} {}", tz, moonrise);
+            assert!((moonset - local_jd(set)).abs() * 1440.0 < 5.0, "{} {}", tz, moonset);
+        }
     }
 }
@@ -0,0 +1,228 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Mosaics of targets larger than the field of view: a grid of overlapping panels laid on the
+// tangent plane at the target, with the long side of the sensor along the right ascension. Each
+// panel is observable for a slightly different part of the night.
+
+use std::ops::RangeInclusive;
+use crate::application::constraint::Constraints;
+use crate::application::environment::Environment;
+use crate::application::equipment::FieldOfView;
+use crate::application::observability::{observation_window, NightSamples};
+use crate::application::observer::Observer;
+use crate::application::target::{Target, MAX_SIZE};
+use crate::application::time::Time;
+
+/// Overlap between neighbour panels in percent of the field, and the default one
+pub const OVERLAP_RANGE: RangeInclusive<f64> = 0.0..=50.0;
+pub const DEFAULT_OVERLAP: f64 = 20.0;
+
+/// Panels along a side at most, larger grids are not planned as one mosaic
+pub const MAX_PANELS_PER_SIDE: usize = 10;
+
+/// Panel struct
+///
+/// # Attributes
+///
+/// * `row` - Row of the panel, 0 is the northernmost
+/// * `column` - Column of the panel, 0 is the easternmost, to the left with north up
+/// * `ra` - Right ascension of the panel center in degrees
+/// * `dec` - Declination of the panel center in degrees
+#[derive(Debug, Clone, PartialEq)]
+pub struct Panel {
+    pub row: usize,
+    pub column: usize,
+    pub ra: f64,
+    pub dec: f64,
+}
+
+impl Panel {
+    /// Name of the panel as on a chart, rows by letter and columns by number, e.g. B3
+    pub fn name(&self) -> String {
+        format!("{}{}", (b'A' + self.row as u8) as char, self.column + 1)
+    }
+}
+
+/// Mosaic struct
+///
+/// # Attributes
+///
+/// * `rows` - Panels along the declination
+/// * `columns` - Panels along the right ascension
+/// * `width` - Width covered by the grid in arc minutes, along the right ascension
+/// * `height` - Height covered by the grid in arc minutes
+/// * `panels` - Panels by row from the north, east to west in a row
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mosaic {
+    pub rows: usize,
+    pub columns: usize,
+    pub width: f64,
+    pub height: f64,
+    pub panels: Vec<Panel>,
+}
+
+/// Part of the night a panel is within the altitude constraints
+///
+/// # Attributes
+///
+/// * `start` - First time within the constraints, UTC Julian Date, None when never
+/// * `end` - Last time within the constraints, UTC Julian Date
+/// * `hours` - Hours within the constraints, gaps left out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PanelWindow {
+    pub start: Option<f64>,
+    pub end: Option<f64>,
+    pub hours: f64,
+}
+
+// Panels to cover `size` with fields of `field` overlapping by `overlap` (0 to 1), and the step
+// between panel centers, all in arc minutes
+fn panels_along(size: f64, field: f64, overlap: f64) -> (usize, f64) {
+    let step = field * (1.0 - overlap);
+    if size <= field {
+        (1, step)
+    } else {
+        (((size - field) / step - 1e-9).ceil() as usize + 1, step)
+    }
+}
+
+// Position `east` and `north` arc minutes away from (ra, dec) on the tangent plane, in degrees
+fn offset_position(ra: f64, dec: f64, east: f64, north: f64) -> (f64, f64) {
+    let (xi, eta) = ((east / 60.0).to_radians(), (north / 60.0).to_radians());
+    let (sin_dec, cos_dec) = dec.to_radians().sin_cos();
+    let denominator = cos_dec - eta * sin_dec;
+    let panel_ra = ra.to_radians() + xi.atan2(denominator);
+    let panel_dec = (sin_dec + eta * cos_dec).atan2((xi * xi + denominator * denominator).sqrt());
+    (panel_ra.to_degrees().rem_euclid(360.0), panel_dec.to_degrees())
+}
+
+/// Mosaic covering `width` by `height` arc minutes around a target with the field of view,
+/// neighbour panels overlapping by `overlap` percent. Fails when the area or the overlap are out
+/// of range or more than MAX_PANELS_PER_SIDE panels would be needed along a side.
+pub fn plan_mosaic(target: &Target, width: f64, height: f64, fov: &FieldOfView, overlap: f64)
+                   -> Result<Mosaic, String> {
+    for (name, size) in [("Width", width), ("Height", height)] {
+        if !(size > 0.0 && size <= MAX_SIZE) {
+            return Err(format!("{} {} is out of range, from 0 to {} arc minutes", name, size, MAX_SIZE));
+        }
+    }
+    if !OVERLAP_RANGE.contains(&overlap) {
+        return Err(format!("Overlap {} is out of range, from {} to {}%", overlap, OVERLAP_RANGE.start(),
+                           OVERLAP_RANGE.end()));
+    }
+    let (columns, column_step) = panels_along(width, fov.width, overlap / 100.0);
+    let (rows, row_step) = panels_along(height, fov.height, overlap / 100.0);
+    if columns > MAX_PANELS_PER_SIDE || rows > MAX_PANELS_PER_SIDE {
+        return Err(format!("{} x {} panels are needed, at most {} along a side, use a shorter focal length",
+                           columns, rows, MAX_PANELS_PER_SIDE));
+    }
+
+    let mut panels = Vec::with_capacity(rows * columns);
+    for row in 0..rows {
+        let north = ((rows - 1) as f64 / 2.0 - row as f64) * row_step;
+        for column in 0..columns {
+            let east = ((columns - 1) as f64 / 2.0 - column as f64) * column_step;
+            let (ra, dec) = offset_position(target.ra, target.dec, east, north);
+            panels.push(Panel { row, column, ra, dec });
+        }
+    }
+    Ok(Mosaic {
+        rows,
+        columns,
+        width: fov.width + (columns - 1) as f64 * column_step,
+        height: fov.height + (rows - 1) as f64 * row_step,
+        panels,
+    })
+}
+
+/// Window of each panel for the samples of a night, in the order of the panels
+pub fn panel_windows(mosaic: &Mosaic, observer: &Observer, samples: &NightSamples,
+                     constraints: &Constraints) -> Vec<PanelWindow> {
+    let step_hours = match samples.jd.as_slice() {
+        [first, second, ..] => (second - first) * 24.0,
+        _ => 0.0,
+    };
+    let altitudes = (constraints.min_altitude as f64)..=(constraints.max_altitude as f64);
+    mosaic
+        .panels
+        .iter()
+        .map(|panel| {
+            let inside: Vec<f64> = samples
+                .jd
+                .iter()
+                .copied()
+                .filter(|&jd| altitudes.contains(&observer.altaz(panel.ra, panel.dec, jd).0))
+                .collect();
+            PanelWindow {
+                start: inside.first().copied(),
+                end: inside.last().copied(),
+                hours: inside.len() as f64 * step_hours,
+            }
+        })
+        .collect()
+}
+
+/// Windows of the panels for the night of `time`, None when there is no observation window
+pub fn night_panel_windows(mosaic: &Mosaic, observer: &Observer, time: &Time, environment: &Environment,
+                           constraints: &Constraints) -> Option<Vec<PanelWindow>> {
+    let window = observation_window(observer, time, environment, constraints)?;
+    let samples = NightSamples::new(observer, window);
+    Some(panel_windows(mosaic, observer, &samples, constraints))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::application::transformations::angular_separation;
+
+    #[test]
+    fn test_plan_mosaic() {
+        let fov = FieldOfView { width: 100.0, height: 70.0, scale: 1.5 };
+        let m31 = Target::new("M31", 10.68, 41.27, 190.0);
+
+        // 190' with 80' steps needs 3 columns, 190' with 56' steps needs 4 rows
+        let mosaic = plan_mosaic(&m31, 190.0, 190.0, &fov, 20.0).unwrap();
+        assert_eq!((mosaic.columns, mosaic.rows), (3, 4));
+        assert_eq!(mosaic.panels.len(), 12);
+        assert!(mosaic.width >= 190.0 && mosaic.height >= 190.0);
+        assert_eq!(mosaic.panels[0].name(), "A1");
+        assert_eq!(mosaic.panels[11].name(), "D3");
+
+        // neighbour centers one step apart, the first panel north east of the target
+        let (a1, a2, b1) = (&mosaic.panels[0], &mosaic.panels[1], &mosaic.panels[3]);
+        assert!((angular_separation(a1.ra, a1.dec, a2.ra, a2.dec) * 60.0 - 80.0).abs() < 0.5);
+        assert!((angular_separation(a1.ra, a1.dec, b1.ra, b1.dec) * 60.0 - 56.0).abs() < 0.5);
+        assert!(a1.ra > m31.ra && a1.dec > m31.dec);
+
+        // a target inside the field is one panel at its position
+        let single = plan_mosaic(&m31, 60.0, 60.0, &fov, 20.0).unwrap();
+        assert_eq!(single.panels, vec![Panel { row: 0, column: 0, ra: m31.ra, dec: m31.dec }]);
+
+        // an exact multiple of the step adds no panel
+        assert_eq!(plan_mosaic(&m31, 180.0, 70.0, &fov, 20.0).unwrap().columns, 2);
+
+        assert!(plan_mosaic(&m31, 1200.0, 1200.0, &fov, 20.0).is_err());
+        assert!(plan_mosaic(&m31, 190.0, 190.0, &fov, 60.0).is_err());
+        assert!(plan_mosaic(&m31, 0.0, 190.0, &fov, 20.0).is_err());
+    }
+}
@@ -0,0 +1,437 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Site synchronization with the mount driver, so the observatory of skycalc and the one the mount
+// uses for its pointing model stay the same. Drivers are behind the MountDriver trait:
+//
+// - ASCOM Alpaca telescopes through the REST API (sitelatitude, sitelongitude, siteelevation and
+//   utcdate), Alpaca has no UTC offset so it is left alone
+// - INDI mounts through the XML protocol of the INDI server (GEO_COORD and TIME_UTC), longitudes
+//   go from 0° to 360° east there
+//
+// Drivers are given as an address, alpaca://host:port/device with the device number or
+// indi://host:port/device with the device name, the ports default to 11111 and 7624.
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+use serde_json::Value;
+use crate::application::observer::Observer;
+use crate::application::time::Time;
+use crate::application::weather::http_request;
+use crate::utils::utils::constrain_360;
+
+const ALPACA_PORT: u16 = 11111;
+const INDI_PORT: u16 = 7624;
+const INDI_TIMEOUT: Duration = Duration::from_secs(3);
+// Alpaca client id of skycalc, any number that identifies it in the driver logs
+const ALPACA_CLIENT_ID: u32 = 7207;
+
+/// MountSite struct
+///
+/// Site kept by the mount driver.
+///
+/// # Attributes
+///
+/// * `latitude` - Latitude in degrees, north positive
+/// * `longitude` - Longitude in degrees (-180 - 180), east positive
+/// * `elevation` - Elevation in meters
+/// * `utc_offset` - UTC offset in hours, None when the driver does not keep it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MountSite {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub elevation: f64,
+    pub utc_offset: Option<f64>,
+}
+
+impl MountSite {
+    pub fn from_observer(observer: &Observer) -> MountSite {
+        MountSite {
+            latitude: observer.latitude,
+            longitude: observer.longitude,
+            elevation: observer.elevation as f64,
+            utc_offset: Some(observer.timezone),
+        }
+    }
+
+    /// Observer with the site of the mount, the name and what the driver does not keep are
+    /// taken from `observer`
+    pub fn to_observer(self, observer: &Observer) -> Observer {
+        let mut observer = observer.clone();
+        observer.latitude = self.latitude;
+        observer.longitude = self.longitude;
+        observer.elevation = self.elevation.round() as i64;
+        if let Some(utc_offset) = self.utc_offset {
+            observer.timezone = utc_offset;
+        }
+        observer
+    }
+}
+
+/// Mount driver holding the site
+pub trait MountDriver {
+    fn name(&self) -> String;
+
+    /// Site set in the driver
+    fn pull_site(&self) -> Result<MountSite, String>;
+
+    /// Sets the site and the clock of the driver, `time` is the current UTC time
+    fn push_site(&self, site: &MountSite, time: &Time) -> Result<(), String>;
+}
+
+/// Driver from an address like alpaca://localhost:11111/0 or indi://localhost/Telescope Simulator
+pub fn mount_driver(address: &str) -> Result<Box<dyn MountDriver>, String> {
+    let (scheme, rest) = address.trim().split_once("://").ok_or("Mount address without a scheme")?;
+    let (authority, device) = rest.split_once('/').unwrap_or((rest, ""));
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (host, Some(port.parse::<u16>().map_err(|_| format!("Invalid port {}", port))?)),
+        None => (authority, None),
+    };
+    if host.is_empty() {
+        return Err("Mount address without a host".to_string());
+    }
+    match scheme.to_ascii_lowercase().as_str() {
+        "alpaca" => Ok(Box::new(Alpaca {
+            host: host.to_string(),
+            port: port.unwrap_or(ALPACA_PORT),
+            device: if device.is_empty() {
+                0
+            } else {
+                device.parse().map_err(|_| format!("Invalid Alpaca device number {}", device))?
+            },
+        })),
+        "indi" if device.is_empty() => Err("INDI address without a device name".to_string()),
+        "indi" => Ok(Box::new(Indi {
+            host: host.to_string(),
+            port: port.unwrap_or(INDI_PORT),
+            device: device.to_string(),
+        })),
+        _ => Err(format!("Unknown mount driver {}, use alpaca:// or indi://", scheme)),
+    }
+}
+
+/// ASCOM Alpaca telescope
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alpaca {
+    pub host: String,
+    pub port: u16,
+    pub device: u32,
+}
+
+impl Alpaca {
+    fn url(&self, member: &str) -> String {
+        format!("http://{}:{}/api/v1/telescope/{}/{}", self.host, self.port, self.device, member)
+    }
+
+    fn get(&self, member: &str) -> Result<f64, String> {
+        let url = format!("{}?ClientID={}", self.url(member), ALPACA_CLIENT_ID);
+        let body = http_request("GET", &url, None)?;
+        alpaca_value(&body)?.as_f64().ok_or(format!("Invalid {} from the mount", member))
+    }
+
+    fn put(&self, member: &str, parameter: &str, value: &str) -> Result<(), String> {
+        let client_id = ALPACA_CLIENT_ID.to_string();
+        let form = [(parameter, value), ("ClientID", client_id.as_str())];
+        let body = http_request("PUT", &self.url(member), Some(&form))?;
+        alpaca_value(&body).map(|_| ())
+    }
+}
+
+impl MountDriver for Alpaca {
+    fn name(&self) -> String {
+        format!("Alpaca telescope {} at {}:{}", self.device, self.host, self.port)
+    }
+
+    fn pull_site(&self) -> Result<MountSite, String> {
+        Ok(MountSite {
+            latitude: self.get("sitelatitude")?,
+            longitude: self.get("sitelongitude")?,
+            elevation: self.get("siteelevation")?,
+            utc_offset: None,
+        })
+    }
+
+    fn push_site(&self, site: &MountSite, time: &Time) -> Result<(), String> {
+        self.put("sitelatitude", "SiteLatitude", &site.latitude.to_string())?;
+        self.put("sitelongitude", "SiteLongitude", &site.longitude.to_string())?;
+        self.put("siteelevation", "SiteElevation", &site.elevation.to_string())?;
+        // ISO 8601 with the colons escaped for the form
+        let utc = time.to_utc().format("%Y-%m-%dT%H:%M:%S.000Z").to_string().replace(':', "%3A");
+        self.put("utcdate", "UTCDate", &utc)
+    }
+}
+
+// Value of an Alpaca response, the driver error when it reports one
+pub(crate) fn alpaca_value(json: &str) -> Result<Value, String> {
+    let response: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    match response.get("ErrorNumber").and_then(|n| n.as_i64()) {
+        Some(0) | None => Ok(response.get("Value").cloned().unwrap_or(Value::Null)),
+        Some(number) => Err(format!(
+            "Mount error {}: {}",
+            number,
+            response.get("ErrorMessage").and_then(|m| m.as_str()).unwrap_or("")
+        )),
+    }
+}
+
+/// INDI mount
+#[derive(Debug, Clone, PartialEq)]
+pub struct Indi {
+    pub host: String,
+    pub port: u16,
+    pub device: String,
+}
+
+impl Indi {
+    // The address is resolved first, a plain connect to an unreachable host blocks for the timeout
+    // of the system
+    fn connect(&self) -> Result<TcpStream, String> {
+        let address = (self.host.as_str(), self.port)
+            .to_socket_addrs()
+            .map_err(|e| e.to_string())?
+            .next()
+            .ok_or(format!("Unable to resolve {}", self.host))?;
+        let stream = TcpStream::connect_timeout(&address, INDI_TIMEOUT).map_err(|e| e.to_string())?;
+        stream.set_read_timeout(Some(INDI_TIMEOUT)).map_err(|e| e.to_string())?;
+        stream.set_write_timeout(Some(INDI_TIMEOUT)).map_err(|e| e.to_string())?;
+        Ok(stream)
+    }
+
+    // Asks the server for the properties of the device, it also forwards the later updates of
+    // them on this connection
+    fn get_properties(&self, stream: &mut TcpStream) -> Result<(), String> {
+        let request = format!("<getProperties version=\"1.7\" device=\"{}\"/>\n", xml_escape(&self.device));
+        stream.write_all(request.as_bytes()).map_err(|e| e.to_string())
+    }
+}
+
+// Reads the INDI stream until `done` holds for what was read, the server closes the connection or
+// goes quiet. The server keeps the connection open and sends updates of other properties, so the
+// reading also stops after INDI_TIMEOUT in total.
+fn read_indi(stream: &mut TcpStream, done: impl Fn(&str) -> bool) -> Result<String, String> {
+    let start = Instant::now();
+    let mut xml = String::new();
+    let mut buffer = [0u8; 4096];
+    while start.elapsed() < INDI_TIMEOUT {
+        match stream.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => {
+                xml.push_str(&String::from_utf8_lossy(&buffer[..n]));
+                if done(&xml) {
+                    break;
+                }
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => break,
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+    Ok(xml)
+}
+
+impl MountDriver for Indi {
+    fn name(&self) -> String {
+        format!("INDI {} at {}:{}", self.device, self.host, self.port)
+    }
+
+    fn pull_site(&self) -> Result<MountSite, String> {
+        let mut stream = self.connect()?;
+        self.get_properties(&mut stream)?;
+
+        // the server sends the properties of the device one after the other, read until the site
+        // is complete
+        let xml = read_indi(&mut stream, |xml| {
+            indi_vector(xml, "defNumberVector", "GEO_COORD").is_some()
+                && indi_vector(xml, "defTextVector", "TIME_UTC").is_some()
+        })?;
+        parse_indi_site(&xml).ok_or(format!("{} did not send its site (GEO_COORD)", self.device))
+    }
+
+    fn push_site(&self, site: &MountSite, time: &Time) -> Result<(), String> {
+        let device = xml_escape(&self.device);
+        let mut xml = format!(
+            "<newNumberVector device=\"{}\" name=\"GEO_COORD\">\
+             <oneNumber name=\"LAT\">{}</oneNumber>\
+             <oneNumber name=\"LONG\">{}</oneNumber>\
+             <oneNumber name=\"ELEV\">{}</oneNumber>\
+             </newNumberVector>\n",
+            device, site.latitude, constrain_360(site.longitude), site.elevation
+        );
+        xml.push_str(&format!(
+            "<newTextVector device=\"{}\" name=\"TIME_UTC\">\
+             <oneText name=\"UTC\">{}</oneText>\
+             <oneText name=\"OFFSET\">{:.2}</oneText>\
+             </newTextVector>\n",
+            device,
+            time.to_utc().format("%Y-%m-%dT%H:%M:%S"),
+            site.utc_offset.unwrap_or(0.0)
+        ));
+        let mut stream = self.connect()?;
+        self.get_properties(&mut stream)?;
+        stream.write_all(xml.as_bytes()).map_err(|e| e.to_string())?;
+
+        // the driver answers with the state of the properties once it has set them
+        let replies = read_indi(&mut stream, |xml| indi_push_result(xml, &self.device, false).is_some())?;
+        indi_push_result(&replies, &self.device, true).unwrap_or(Ok(()))
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// Body of the first property vector with the tag and name in an INDI stream
+fn indi_vector<'a>(xml: &'a str, tag: &str, name: &str) -> Option<&'a str> {
+    let mut rest = xml;
+    loop {
+        let start = rest.find(&format!("<{}", tag))?;
+        let after = &rest[start..];
+        let head_end = after.find('>')?;
+        let end = after.find(&format!("</{}>", tag))?;
+        if after[..head_end].contains(&format!("name=\"{}\"", name)) {
+            return Some(&after[head_end + 1..end]);
+        }
+        rest = &after[end..];
+    }
+}
+
+// State of the last property vector with the tag and name in an INDI stream, e.g. Ok or Alert
+fn indi_state<'a>(xml: &'a str, tag: &str, name: &str) -> Option<&'a str> {
+    let head = xml
+        .match_indices(&format!("<{}", tag))
+        .filter_map(|(start, _)| Some(&xml[start..start + xml[start..].find('>')?]))
+        .filter(|head| head.contains(&format!("name=\"{}\"", name)))
+        .last()?;
+    let state = &head[head.find("state=\"")? + 7..];
+    Some(&state[..state.find('"')?])
+}
+
+// Outcome of a site sent to an INDI device from the replies read so far, None while the device is
+// still setting it. Once `finished` reading, drivers without a clock only have to confirm the
+// coordinates.
+pub(crate) fn indi_push_result(xml: &str, device: &str, finished: bool) -> Option<Result<(), String>> {
+    let geo = indi_state(xml, "setNumberVector", "GEO_COORD");
+    let time = indi_state(xml, "setTextVector", "TIME_UTC");
+    let set = |state: Option<&str>| matches!(state, Some("Ok") | Some("Idle"));
+    if geo == Some("Alert") || time == Some("Alert") {
+        Some(Err(format!("{} refused the site", device)))
+    } else if set(geo) && (set(time) || finished) {
+        Some(Ok(()))
+    } else if finished {
+        Some(Err(format!("{} did not confirm the site (GEO_COORD)", device)))
+    } else {
+        None
+    }
+}
+
+// Text of the element with the name in a property vector body, e.g. <defNumber name="LAT" ...>
+fn indi_element<'a>(vector: &'a str, name: &str) -> Option<&'a str> {
+    let start = vector.find(&format!("name=\"{}\"", name))?;
+    let after = &vector[start..];
+    let text = &after[after.find('>')? + 1..];
+    Some(text[..text.find('<')?].trim())
+}
+
+// INDI numbers are decimal or sexagesimal (d:m:s)
+fn indi_number(text: &str) -> Option<f64> {
+    let mut parts = text.split(':');
+    let first = parts.next()?.trim();
+    let sign = if first.starts_with('-') { -1.0 } else { 1.0 };
+    let mut value = first.parse::<f64>().ok()?.abs();
+    for (part, scale) in parts.zip([60.0, 3600.0]) {
+        value += part.trim().parse::<f64>().ok()? / scale;
+    }
+    Some(sign * value)
+}
+
+// Site from the GEO_COORD and TIME_UTC definitions of an INDI stream
+pub(crate) fn parse_indi_site(xml: &str) -> Option<MountSite> {
+    let geo = indi_vector(xml, "defNumberVector", "GEO_COORD")?;
+    let longitude = indi_number(indi_element(geo, "LONG")?)?;
+    Some(MountSite {
+        latitude: indi_number(indi_element(geo, "LAT")?)?,
+        longitude: if longitude > 180.0 { longitude - 360.0 } else { longitude },
+        elevation: indi_number(indi_element(geo, "ELEV")?)?,
+        utc_offset: indi_vector(xml, "defTextVector", "TIME_UTC")
+            .and_then(|time| indi_element(time, "OFFSET"))
+            .and_then(indi_number),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_mount_driver() {
+        assert_eq!(mount_driver("alpaca://localhost").unwrap().name(), "Alpaca telescope 0 at localhost:11111");
+        assert_eq!(mount_driver("ALPACA://192.168.0.5:32323/1").unwrap().name(),
+                   "Alpaca telescope 1 at 192.168.0.5:32323");
+        assert_eq!(mount_driver("indi://localhost/Telescope Simulator").unwrap().name(),
+                   "INDI Telescope Simulator at localhost:7624");
+        assert!(mount_driver("indi://localhost:7624").is_err());
+        assert!(mount_driver("alpaca://localhost:port/0").is_err());
+        assert!(mount_driver("ascom://localhost/0").is_err());
+        assert!(mount_driver("localhost").is_err());
+
+        assert_eq!(alpaca_value(r#"{"Value":-23.1,"ErrorNumber":0,"ErrorMessage":""}"#).unwrap(), -23.1);
+        assert_eq!(alpaca_value(r#"{"ErrorNumber":1025,"ErrorMessage":"Invalid value"}"#).unwrap_err(),
+                   "Mount error 1025: Invalid value");
+
+        let xml = r#"<defSwitchVector device="Telescope Simulator" name="CONNECTION" state="Ok">
+<defSwitch name="CONNECT" label="Connect">On</defSwitch></defSwitchVector>
+<defTextVector device="Telescope Simulator" name="TIME_UTC" label="UTC" state="Idle">
+<defText name="UTC" label="UTC Time">2025-03-14T12:00:00</defText>
+<defText name="OFFSET" label="UTC Offset">-3.00</defText></defTextVector>
+<defNumberVector device="Telescope Simulator" name="GEO_COORD" label="Scope Location" state="Ok">
+<defNumber name="LAT" label="Lat (dd:mm:ss)" format="%010.6m" min="-90" max="90" step="0">
+-23:06:00</defNumber>
+<defNumber name="LONG" label="Lon (dd:mm:ss)" format="%010.6m" min="0" max="360" step="0">
+313.5</defNumber>
+<defNumber name="ELEV" label="Elevation (m)" format="%g" min="-200" max="10000" step="0">
+780</defNumber></defNumberVector>"#;
+        let site = parse_indi_site(xml).unwrap();
+        assert!((site.latitude + 23.1).abs() < 1e-9);
+        assert!((site.longitude + 46.5).abs() < 1e-9);
+        assert_eq!(site.elevation, 780.0);
+        assert_eq!(site.utc_offset, Some(-3.0));
+        assert!(parse_indi_site(&xml[..200]).is_none());
+
+        let device = "Telescope Simulator";
+        let busy = r#"<setNumberVector device="Telescope Simulator" name="GEO_COORD" state="Busy" timeout="60">
+<oneNumber name="LAT">-23.1</oneNumber></setNumberVector>"#;
+        let geo = r#"<setNumberVector device="Telescope Simulator" name="GEO_COORD" state="Ok" timeout="60">
+<oneNumber name="LAT">-23.1</oneNumber></setNumberVector>"#;
+        let time = r#"<setTextVector device="Telescope Simulator" name="TIME_UTC" state="Ok">
+<oneText name="UTC">2025-03-14T12:00:00</oneText></setTextVector>"#;
+        let refused = r#"<setTextVector device="Telescope Simulator" name="TIME_UTC" state="Alert"></setTextVector>"#;
+        assert_eq!(indi_push_result(xml, device, false), None);
+        assert_eq!(indi_push_result(busy, device, false), None);
+        assert_eq!(indi_push_result(&format!("{}{}", busy, geo), device, false), None);
+        assert_eq!(indi_push_result(&format!("{}{}{}", busy, geo, time), device, false), Some(Ok(())));
+        assert_eq!(indi_push_result(geo, device, true), Some(Ok(())));
+        assert_eq!(indi_push_result(&format!("{}{}", geo, refused), device, false),
+                   Some(Err("Telescope Simulator refused the site".to_string())));
+        assert_eq!(indi_push_result(busy, device, true),
+                   Some(Err("Telescope Simulator did not confirm the site (GEO_COORD)".to_string())));
+    }
+}
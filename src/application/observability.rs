@@ -0,0 +1,805 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Observability of targets during the night: how much of the observation window each target
+// spends within the altitude constraints and how close the Moon gets. The up tonight list is
+// built from these, for the custom targets of the configuration and any other list of targets.
+
+use rayon::prelude::*;
+use crate::application::constraint::Constraints;
+use crate::application::darkness::Darkness;
+use crate::application::environment::Environment;
+use crate::application::moon::{moon_illumination, moon_topocentric_position, MOON_HORIZON};
+use crate::application::observer::Observer;
+use crate::application::sun::{sun_alt_az_grid_utc, Sun, TwilightType};
+use crate::application::target::Target;
+use crate::application::time::Time;
+use crate::application::transformations::{angular_separation, field_rotation_rate, parallactic_angle};
+
+// Time between samples of the observation window, in days (10 minutes)
+const SAMPLE_STEP: f64 = 10.0 / 1440.0;
+
+// Samples of each night in the yearly scan, from local noon to the next local noon (15 minutes)
+const YEAR_NIGHT_POINTS: usize = 96;
+
+// Broadband imaging is recommended while the Moon is down, a thin Moon is up or the target is
+// far enough from it, narrowband only otherwise
+const BROADBAND_MAX_ILLUMINATION: f64 = 0.25;
+const BROADBAND_MIN_SEPARATION: f64 = 90.0;
+
+pub const MONTH_NAMES: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun",
+                                     "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+/// Observability struct
+///
+/// Target visibility during the observation window.
+///
+/// # Attributes
+///
+/// * `target` - Target
+/// * `fraction` - Fraction of the window with the target between the minimum and maximum altitude
+/// * `hours` - Hours with the target between the minimum and maximum altitude
+/// * `max_altitude` - Highest altitude of the target during the window, in degrees
+/// * `best_jd` - Julian Date (UTC) of the highest altitude during the window
+/// * `moon_separation` - Smallest Moon separation while the Moon is up, None when it stays down
+#[derive(Debug, Clone)]
+pub struct Observability {
+    pub target: Target,
+    pub fraction: f64,
+    pub hours: f64,
+    pub max_altitude: f64,
+    pub best_jd: f64,
+    pub moon_separation: Option<f64>,
+}
+
+/// NightSamples struct
+///
+/// Times of the observation window with the Moon position, shared by all targets of a night.
+///
+/// # Attributes
+///
+/// * `jd` - Julian Dates (UTC) of the samples
+/// * `moon` - Moon (ra, dec, altitude) in degrees at each sample
+/// * `moon_illumination` - Illuminated fraction of the Moon at each sample
+#[derive(Debug, Clone)]
+pub struct NightSamples {
+    pub jd: Vec<f64>,
+    pub moon: Vec<(f64, f64, f64)>,
+    pub moon_illumination: Vec<f64>,
+}
+
+/// Observation window in UTC Julian Dates: the astronomical darkness (nautical when there is
+/// none) when the constraints use darkness, sunset to sunrise otherwise. None when the window
+/// does not happen that night.
+pub fn observation_window(observer: &Observer, time: &Time, environment: &Environment,
+                          constraints: &Constraints) -> Option<(f64, f64)> {
+    let darkness = Darkness::new(observer, time, environment);
+    let (start, end) = if constraints.use_darkness {
+        darkness.get_darkness_utc_astronomical_or_nautical().1
+    } else {
+        darkness.get_darkness_utc_riseset()
+    };
+    match (start.jd(), end.jd()) {
+        (Some(start), Some(end)) if end > start => Some((start, end)),
+        _ => None,
+    }
+}
+
+impl NightSamples {
+    pub fn new(observer: &Observer, window: (f64, f64)) -> NightSamples {
+        let count = ((window.1 - window.0) / SAMPLE_STEP).floor() as usize + 1;
+        let jd: Vec<f64> = (0..count).map(|i| window.0 + i as f64 * SAMPLE_STEP).collect();
+        let moon = jd
+            .iter()
+            .map(|&jd| {
+                let (ra, dec, _) = moon_topocentric_position(observer.latitude, observer.longitude,
+                                                             observer.elevation as f64, jd);
+                (ra, dec, altitude(observer, ra, dec, jd))
+            })
+            .collect();
+        let moon_illumination = jd.iter().map(|&jd| moon_illumination(jd)).collect();
+        NightSamples { jd, moon, moon_illumination }
+    }
+}
+
+fn altitude(observer: &Observer, ra: f64, dec: f64, jd: f64) -> f64 {
+    observer.altaz(ra, dec, jd).0
+}
+
+/// Observability of a target for the samples of a night
+pub fn observability(target: &Target, observer: &Observer, samples: &NightSamples,
+                     constraints: &Constraints) -> Observability {
+    let mut inside = 0;
+    let mut max_altitude = f64::MIN;
+    let mut best_jd = samples.jd.first().copied().unwrap_or(0.0);
+    let mut moon_separation: Option<f64> = None;
+    for (&jd, &(moon_ra, moon_dec, moon_altitude)) in samples.jd.iter().zip(samples.moon.iter()) {
+        let altitude = altitude(observer, target.ra, target.dec, jd);
+        if altitude >= constraints.min_altitude as f64 && altitude <= constraints.max_altitude as f64 {
+            inside += 1;
+        }
+        if altitude > max_altitude {
+            max_altitude = altitude;
+            best_jd = jd;
+        }
+        if moon_altitude > MOON_HORIZON {
+            let separation = angular_separation(target.ra, target.dec, moon_ra, moon_dec);
+            moon_separation = Some(moon_separation.map_or(separation, |s| s.min(separation)));
+        }
+    }
+
+    let count = samples.jd.len().max(1);
+    Observability {
+        target: target.clone(),
+        fraction: inside as f64 / count as f64,
+        hours: inside as f64 * SAMPLE_STEP * 24.0,
+        max_altitude,
+        best_jd,
+        moon_separation,
+    }
+}
+
+/// True when the target passes the size, observable time and Moon separation constraints. An
+/// unknown size (0) is not filtered out.
+pub fn meets_constraints(observability: &Observability, constraints: &Constraints) -> bool {
+    let size = observability.target.size;
+    let size_ok = size <= 0.0
+        || (size >= constraints.min_size as f64 && size <= constraints.max_size as f64);
+    let time_ok = observability.fraction * 100.0 >= constraints.frac_observable_time as f64;
+    let moon_ok = observability
+        .moon_separation
+        .is_none_or(|separation| separation >= constraints.moon_separation as f64);
+    size_ok && time_ok && moon_ok
+}
+
+/// Observability of every target for the night of `time`, None when there is no observation
+/// window
+pub fn night_observability(observer: &Observer, time: &Time, environment: &Environment,
+                           constraints: &Constraints, targets: &[Target]) -> Option<Vec<Observability>> {
+    let window = observation_window(observer, time, environment, constraints)?;
+    let samples = NightSamples::new(observer, window);
+    Some(targets.iter().map(|target| observability(target, observer, &samples, constraints)).collect())
+}
+
+/// Targets up tonight: the ones that meet the constraints, the longest observable first, at most
+/// `max_targets` of them. Empty when there is no observation window.
+pub fn up_tonight(observer: &Observer, time: &Time, environment: &Environment,
+                  constraints: &Constraints, targets: &[Target]) -> Vec<Observability> {
+    let mut up: Vec<Observability> = night_observability(observer, time, environment, constraints, targets)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|o| meets_constraints(o, constraints))
+        .collect();
+    up.sort_by(|a, b| b.hours.total_cmp(&a.hours));
+    up.truncate(constraints.max_targets.max(0) as usize);
+    up
+}
+
+/// Filters recommended for imaging a target with the Moon around
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FilterClass {
+    Broadband,
+    Narrowband,
+}
+
+impl FilterClass {
+    pub fn description(&self) -> &'static str {
+        match self {
+            FilterClass::Broadband => "broadband OK",
+            FilterClass::Narrowband => "narrowband only",
+        }
+    }
+}
+
+/// FilterSegment struct
+///
+/// Part of the night with the same filter recommendation for a target.
+///
+/// # Attributes
+///
+/// * `class` - Recommended filters
+/// * `start` - Start, UTC Julian Date
+/// * `end` - End, UTC Julian Date
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FilterSegment {
+    pub class: FilterClass,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// Filters recommended for a Moon altitude (degrees), illuminated fraction (0.0 - 1.0) and
+/// separation from the target (degrees)
+pub fn filter_class(moon_altitude: f64, illumination: f64, separation: f64) -> FilterClass {
+    if moon_altitude <= MOON_HORIZON
+        || illumination <= BROADBAND_MAX_ILLUMINATION
+        || separation >= BROADBAND_MIN_SEPARATION
+    {
+        FilterClass::Broadband
+    } else {
+        FilterClass::Narrowband
+    }
+}
+
+/// Night window of the filter plan in UTC Julian Dates, like the observation window but with
+/// the Sun alone as the plan is about the Moon being up. None when the night has no darkness.
+pub fn filter_window(observer: &Observer, time: &Time, environment: &Environment,
+                     constraints: &Constraints) -> Option<(f64, f64)> {
+    const NUM_POINTS: usize = 1440;
+    let sun = Sun::new(observer, time, environment).night_alt_az_grid(NUM_POINTS);
+    let twilights: &[TwilightType] = if constraints.use_darkness {
+        &[TwilightType::AstronomicalTwilight, TwilightType::NauticalTwilight]
+    } else {
+        &[TwilightType::RiseSet]
+    };
+    twilights.iter().find_map(|twilight| {
+        let dark = sun.iter().filter(|sun| sun.1 <= twilight.angle()).map(|sun| sun.0);
+        let start = dark.clone().reduce(f64::min)?;
+        let end = dark.reduce(f64::max)?;
+        (end > start).then_some((start, end))
+    })
+}
+
+/// Splits the samples of a night with the target within the altitude constraints into parts
+/// where broadband imaging is fine and parts where the Moon leaves narrowband only, in time order
+pub fn filter_plan(target: &Target, observer: &Observer, samples: &NightSamples,
+                   constraints: &Constraints) -> Vec<FilterSegment> {
+    let mut spans: Vec<(FilterClass, f64, f64)> = Vec::new();
+    let moon = samples.moon.iter().zip(samples.moon_illumination.iter());
+    for (&jd, (&(moon_ra, moon_dec, moon_altitude), &illumination)) in samples.jd.iter().zip(moon) {
+        let altitude = altitude(observer, target.ra, target.dec, jd);
+        if altitude < constraints.min_altitude as f64 || altitude > constraints.max_altitude as f64 {
+            continue;
+        }
+        let separation = angular_separation(target.ra, target.dec, moon_ra, moon_dec);
+        extend_or_push(&mut spans, filter_class(moon_altitude, illumination, separation), jd, jd + SAMPLE_STEP);
+    }
+    spans
+        .into_iter()
+        .map(|(class, start, end)| FilterSegment { class, start, end })
+        .collect()
+}
+
+/// Filter plan of every target for the night of `time`, None when the night has no darkness
+pub fn night_filter_plan(observer: &Observer, time: &Time, environment: &Environment,
+                         constraints: &Constraints, targets: &[Target]) -> Option<Vec<Vec<FilterSegment>>> {
+    let window = filter_window(observer, time, environment, constraints)?;
+    let samples = NightSamples::new(observer, window);
+    Some(targets.iter().map(|target| filter_plan(target, observer, &samples, constraints)).collect())
+}
+
+/// Hours of the segments recommended for the filter class
+pub fn filter_hours(segments: &[FilterSegment], class: FilterClass) -> f64 {
+    segments
+        .iter()
+        .filter(|s| s.class == class)
+        .map(|s| (s.end - s.start) * 24.0)
+        .sum()
+}
+
+/// FieldRotation struct
+///
+/// Field rotation of a target seen by an alt-az mount while it is within the altitude
+/// constraints during the observation window.
+///
+/// # Attributes
+///
+/// * `max_rate` - Highest rotation rate, degrees per minute
+/// * `max_rate_jd` - Julian Date (UTC) of the highest rate
+/// * `total` - Rotation of the field while the target is within the constraints, degrees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldRotation {
+    pub max_rate: f64,
+    pub max_rate_jd: f64,
+    pub total: f64,
+}
+
+impl FieldRotation {
+    /// Longest exposure in seconds before the field rotates by `tolerance` degrees at the
+    /// highest rate
+    pub fn max_exposure(&self, tolerance: f64) -> f64 {
+        if self.max_rate > 0.0 { tolerance / self.max_rate * 60.0 } else { f64::INFINITY }
+    }
+}
+
+/// Field rotation of a target for the samples of a night, None when the target is never within
+/// the altitude constraints
+pub fn field_rotation(target: &Target, observer: &Observer, samples: &NightSamples,
+                      constraints: &Constraints) -> Option<FieldRotation> {
+    let mut rotation: Option<FieldRotation> = None;
+    let mut previous: Option<f64> = None;
+    for &jd in &samples.jd {
+        let (altitude, azimuth) = observer.altaz(target.ra, target.dec, jd);
+        if altitude < constraints.min_altitude as f64 || altitude > constraints.max_altitude as f64 {
+            previous = None;
+            continue;
+        }
+        let rate = field_rotation_rate(observer.latitude, altitude, azimuth).abs();
+        let angle = parallactic_angle(observer.latitude, observer.hour_angle(target.ra, jd), target.dec);
+        // the angle wraps at +-180 degrees north of the zenith
+        let step = previous.map_or(0.0, |previous| (angle - previous + 540.0).rem_euclid(360.0) - 180.0);
+        previous = Some(angle);
+        let current = rotation.get_or_insert(FieldRotation { max_rate: rate, max_rate_jd: jd, total: 0.0 });
+        current.total += step.abs();
+        if rate > current.max_rate {
+            current.max_rate = rate;
+            current.max_rate_jd = jd;
+        }
+    }
+    rotation
+}
+
+/// Field rotation of a target over the observation window of the night of `time`, None when
+/// there is no window or the target is never within the altitude constraints
+pub fn night_field_rotation(observer: &Observer, time: &Time, environment: &Environment,
+                            constraints: &Constraints, target: &Target) -> Option<FieldRotation> {
+    let window = observation_window(observer, time, environment, constraints)?;
+    let samples = NightSamples::new(observer, window);
+    field_rotation(target, observer, &samples, constraints)
+}
+
+// Shortest block of the plan in samples (30 minutes), a target is not swapped for a higher one
+// before this to avoid slewing back and forth between targets at similar altitudes
+const PLAN_MIN_BLOCK: usize = 3;
+
+/// PlanBlock struct
+///
+/// Part of the night allocated to one target.
+///
+/// # Attributes
+///
+/// * `target` - Index of the target in the target list
+/// * `start` - Start, UTC Julian Date
+/// * `end` - End, UTC Julian Date
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlanBlock {
+    pub target: usize,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// NightPlan struct
+///
+/// Schedule of the targets over the observation window of a night, each sample goes to the
+/// highest target within the altitude and Moon separation constraints and the samples without
+/// any are left idle.
+///
+/// # Attributes
+///
+/// * `window` - Observation window, UTC Julian Dates
+/// * `blocks` - Blocks allocated to the targets, in time order
+/// * `idle` - Gaps with no target within the constraints, UTC Julian Dates in time order
+#[derive(Debug, Clone, PartialEq)]
+pub struct NightPlan {
+    pub window: (f64, f64),
+    pub blocks: Vec<PlanBlock>,
+    pub idle: Vec<(f64, f64)>,
+}
+
+impl NightPlan {
+    /// Hours of the observation window
+    pub fn available_hours(&self) -> f64 {
+        (self.window.1 - self.window.0) * 24.0
+    }
+
+    /// Hours allocated to the target at `index` of the target list
+    pub fn allocated_hours(&self, index: usize) -> f64 {
+        self.blocks
+            .iter()
+            .filter(|b| b.target == index)
+            .map(|b| (b.end - b.start) * 24.0)
+            .sum()
+    }
+
+    /// Hours with no target within the constraints
+    pub fn idle_hours(&self) -> f64 {
+        self.idle.iter().map(|(start, end)| (end - start) * 24.0).sum()
+    }
+}
+
+// Merges a sample into the last block or gap when it continues it
+fn extend_or_push<T: PartialEq>(spans: &mut Vec<(T, f64, f64)>, key: T, start: f64, end: f64) {
+    match spans.last_mut() {
+        Some(last) if last.0 == key && (last.2 - start).abs() < 1e-9 => last.2 = end,
+        _ => spans.push((key, start, end)),
+    }
+}
+
+/// Plans the targets over the samples of a night. Each sample before the end of `window` covers
+/// SAMPLE_STEP (the last one up to the end of the window) and goes to one target or is idle:
+///
+/// 1. A target is eligible when its altitude is within the altitude constraints and, while the
+///    Moon is above MOON_HORIZON, it is at least the Moon separation constraint away from it.
+/// 2. The target of the previous sample stays while it is eligible and has had fewer than
+///    PLAN_MIN_BLOCK samples in a row.
+/// 3. Otherwise the sample goes to the highest eligible target, the first one of `targets` when
+///    several are equally high.
+/// 4. With no eligible target the sample is idle.
+///
+/// Consecutive samples of the same target make one block and consecutive idle samples one gap,
+/// so the blocks and the gaps cover the window without overlapping.
+pub fn plan_night(observer: &Observer, samples: &NightSamples, window: (f64, f64), targets: &[Target],
+                  constraints: &Constraints) -> NightPlan {
+    let mut spans: Vec<(Option<usize>, f64, f64)> = Vec::new();
+    let mut current: Option<usize> = None;
+    let mut current_length = 0;
+    for (&jd, &(moon_ra, moon_dec, moon_altitude)) in samples.jd.iter().zip(samples.moon.iter()) {
+        // the last sample may fall on the end of the window
+        if jd >= window.1 {
+            break;
+        }
+        let eligible: Vec<(usize, f64)> = targets
+            .iter()
+            .enumerate()
+            .map(|(i, target)| (i, altitude(observer, target.ra, target.dec, jd)))
+            .filter(|&(i, altitude)| {
+                let moon_ok = moon_altitude <= MOON_HORIZON
+                    || angular_separation(targets[i].ra, targets[i].dec, moon_ra, moon_dec)
+                        >= constraints.moon_separation as f64;
+                altitude >= constraints.min_altitude as f64 && altitude <= constraints.max_altitude as f64
+                    && moon_ok
+            })
+            .collect();
+
+        let keep = current.filter(|c| current_length < PLAN_MIN_BLOCK && eligible.iter().any(|(i, _)| i == c));
+        let chosen = keep.or_else(|| {
+            eligible
+                .iter()
+                .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+                .map(|&(i, _)| i)
+        });
+        if chosen == current {
+            current_length += 1;
+        } else {
+            current = chosen;
+            current_length = 1;
+        }
+        let end = (jd + SAMPLE_STEP).min(window.1);
+        extend_or_push(&mut spans, chosen, jd, end);
+    }
+
+    let blocks = spans
+        .iter()
+        .filter_map(|&(target, start, end)| target.map(|target| PlanBlock { target, start, end }))
+        .collect();
+    let idle = spans
+        .iter()
+        .filter(|(target, _, _)| target.is_none())
+        .map(|&(_, start, end)| (start, end))
+        .collect();
+    NightPlan { window, blocks, idle }
+}
+
+/// Plan of the targets for the night of `time`, None when there is no observation window or no
+/// targets to plan
+pub fn night_plan(observer: &Observer, time: &Time, environment: &Environment,
+                  constraints: &Constraints, targets: &[Target]) -> Option<NightPlan> {
+    if targets.is_empty() {
+        return None;
+    }
+    let window = observation_window(observer, time, environment, constraints)?;
+    let samples = NightSamples::new(observer, window);
+    Some(plan_night(observer, &samples, window, targets, constraints))
+}
+
+/// MonthObservability struct
+///
+/// Darkness of a month and the part of it with the target within the altitude constraints.
+///
+/// # Attributes
+///
+/// * `month` - Month, 1 to 12
+/// * `dark_hours` - Hours of darkness in the month, astronomical or sunset to sunrise following
+///   the constraints
+/// * `observable_hours` - Hours of darkness with the target between the minimum and maximum altitude
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MonthObservability {
+    pub month: u64,
+    pub dark_hours: f64,
+    pub observable_hours: f64,
+}
+
+impl MonthObservability {
+    /// Fraction of the darkness of the month the target can be observed
+    pub fn fraction(&self) -> f64 {
+        if self.dark_hours > 0.0 { self.observable_hours / self.dark_hours } else { 0.0 }
+    }
+}
+
+/// Scans every night of `year` and adds up the darkness and the hours with the target within
+/// the altitude constraints for each month
+pub fn yearly_observability(target: &Target, observer: &Observer, year: i64,
+                            constraints: &Constraints) -> [MonthObservability; 12] {
+    let hours_per_point = 24.0 / YEAR_NIGHT_POINTS as f64;
+    let twilight = if constraints.use_darkness { TwilightType::AstronomicalTwilight } else { TwilightType::RiseSet };
+    let offset = observer.timezone / 24.0;
+    let mut months = [MonthObservability::default(); 12];
+    for (i, month) in months.iter_mut().enumerate() {
+        month.month = i as u64 + 1;
+    }
+
+    let start = Time::new(year, 1, 1, 0, 0, 0).to_jd();
+    let end = Time::new(year + 1, 1, 1, 0, 0, 0).to_jd();
+    let count = (end - start).round() as usize;
+
+    // nights are independent and computed in parallel: (month index, dark hours, observable hours)
+    let nights: Vec<(usize, f64, f64)> = (0..count).into_par_iter().map(|i| {
+        // night goes from local noon to next local noon and counts for the month of its evening
+        let jd = start + i as f64;
+        let night_start = (jd + 0.5).floor() - offset;
+        let sun = sun_alt_az_grid_utc(observer.latitude, observer.longitude, night_start, night_start + 1.0,
+                                      YEAR_NIGHT_POINTS);
+        let mut dark_hours = 0.0;
+        let mut observable_hours = 0.0;
+        for &(sample_jd, sun_altitude, _) in sun.iter().take(YEAR_NIGHT_POINTS) {
+            if sun_altitude > twilight.angle() {
+                continue;
+            }
+            dark_hours += hours_per_point;
+            let altitude = altitude(observer, target.ra, target.dec, sample_jd);
+            if altitude >= constraints.min_altitude as f64 && altitude <= constraints.max_altitude as f64 {
+                observable_hours += hours_per_point;
+            }
+        }
+        (Time::from_jd(jd).month as usize - 1, dark_hours, observable_hours)
+    }).collect();
+
+    // added up in date order, the sums do not depend on the scheduling
+    for (month, dark_hours, observable_hours) in nights {
+        months[month].dark_hours += dark_hours;
+        months[month].observable_hours += observable_hours;
+    }
+    months
+}
+
+/// Months with the most observable darkness first, months when the target is never within the
+/// constraints are left out
+pub fn best_months(months: &[MonthObservability], count: usize) -> Vec<MonthObservability> {
+    let mut best: Vec<MonthObservability> = months.iter().copied().filter(|m| m.observable_hours > 0.0).collect();
+    best.sort_by(|a, b| b.observable_hours.total_cmp(&a.observable_hours));
+    best.truncate(count);
+    best
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_up_tonight() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let time = Time::new(2024, 12, 1, 12, 0, 0);
+        let environment = Environment::default();
+        let mut constraints = Constraints {
+            min_altitude: 30,
+            max_altitude: 80,
+            min_size: 10,
+            max_size: 300,
+            moon_separation: 45,
+            frac_observable_time: 50,
+            max_targets: 60,
+            use_darkness: true,
+        };
+
+        let window = observation_window(&observer, &time, &environment, &constraints).unwrap();
+        assert!(window.1 - window.0 > 0.2 && window.1 - window.0 < 0.5);
+
+        // M42 culminates near midnight in December, Polaris never rises in the south
+        let targets = [
+            Target::new("M42", 83.82, -5.39, 65.0),
+            Target::new("Polaris", 37.95, 89.26, 0.0),
+            Target::new("Tiny", 83.82, -5.39, 1.0),
+        ];
+        let samples = NightSamples::new(&observer, window);
+        let m42 = observability(&targets[0], &observer, &samples, &constraints);
+        assert!(m42.fraction > 0.5, "{:?}", m42);
+        assert!(m42.max_altitude > 60.0);
+        let polaris = observability(&targets[1], &observer, &samples, &constraints);
+        assert_eq!(polaris.fraction, 0.0);
+
+        // below min_size and never up are left out
+        let up = up_tonight(&observer, &time, &environment, &constraints, &targets);
+        assert_eq!(up.iter().map(|o| o.target.name.as_str()).collect::<Vec<_>>(), ["M42"]);
+
+        constraints.max_targets = 0;
+        assert!(up_tonight(&observer, &time, &environment, &constraints, &targets).is_empty());
+    }
+
+    #[test]
+    fn test_filter_plan() {
+        assert_eq!(filter_class(-5.0, 1.0, 10.0), FilterClass::Broadband);
+        assert_eq!(filter_class(30.0, 0.1, 10.0), FilterClass::Broadband);
+        assert_eq!(filter_class(30.0, 1.0, 120.0), FilterClass::Broadband);
+        assert_eq!(filter_class(30.0, 1.0, 60.0), FilterClass::Narrowband);
+
+        // full Moon in Virgo, up all night: M65 is about 20° away, M42 about 100° away in the west
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let time = Time::new(2025, 3, 14, 12, 0, 0);
+        let constraints = Constraints {
+            min_altitude: 20,
+            max_altitude: 90,
+            use_darkness: true,
+            ..Default::default()
+        };
+        let targets = [
+            Target::new("M65", 169.73, 13.09, 10.0),
+            Target::new("M42", 83.82, -5.39, 65.0),
+        ];
+        let environment = Environment::default();
+        // the Moon is up all night so there is no darkness window, the filter plan has the Sun alone
+        assert!(observation_window(&observer, &time, &environment, &constraints).is_none());
+        let night = night_filter_plan(&observer, &time, &environment, &constraints, &targets).unwrap();
+        assert!(filter_hours(&night[0], FilterClass::Narrowband) > 2.0, "{:?}", night[0]);
+        assert_eq!(filter_hours(&night[0], FilterClass::Broadband), 0.0);
+        assert!(filter_hours(&night[1], FilterClass::Broadband) > 2.0, "{:?}", night[1]);
+        assert_eq!(filter_hours(&night[1], FilterClass::Narrowband), 0.0);
+
+        // segments are in time order without overlapping
+        for segments in &night {
+            assert!(segments.windows(2).all(|w| w[0].end <= w[1].start + 1e-9));
+        }
+    }
+
+    #[test]
+    fn test_field_rotation() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let samples = NightSamples::new(&observer, (2460646.4, 2460646.7));
+        let constraints = Constraints {
+            min_altitude: 30,
+            max_altitude: 90,
+            ..Default::default()
+        };
+
+        // M42 crosses the meridian 18° from the zenith, the field turns fastest there
+        let m42 = Target::new("M42", 83.82, -5.39, 65.0);
+        let rotation = field_rotation(&m42, &observer, &samples, &constraints).unwrap();
+        let transit_ha = observer.hour_angle(m42.ra, rotation.max_rate_jd);
+        assert!(!(10.0..=350.0).contains(&transit_ha), "{:?} {}", rotation, transit_ha);
+        assert!(rotation.max_rate > 0.7 && rotation.max_rate < 0.8, "{:?}", rotation);
+        assert!(rotation.total > 60.0 && rotation.total < 180.0, "{:?}", rotation);
+        assert!((rotation.max_exposure(0.1) - 6.0 / rotation.max_rate).abs() < 1e-9);
+
+        // never above 30 degrees from latitude -23
+        let polaris = Target::new("Polaris", 37.95, 89.26, 0.0);
+        assert!(field_rotation(&polaris, &observer, &samples, &constraints).is_none());
+    }
+
+    #[test]
+    fn test_yearly_observability() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let constraints = Constraints {
+            min_altitude: 30,
+            max_altitude: 90,
+            use_darkness: true,
+            ..Default::default()
+        };
+
+        // M42 is an evening and night target of the southern summer, lost in the Sun in June
+        let m42 = Target::new("M42", 83.82, -5.39, 85.0);
+        let months = yearly_observability(&m42, &observer, 2025, &constraints);
+        assert!(months.iter().all(|m| m.dark_hours > 100.0 && m.fraction() <= 1.0));
+        assert!(months[11].fraction() > 0.5, "{:?}", months[11]);
+        assert_eq!(months[5].observable_hours, 0.0);
+        let best = best_months(&months, 3);
+        assert_eq!(best.len(), 3);
+        assert!(best.iter().all(|m| [11, 12, 1, 2].contains(&m.month)), "{:?}", best);
+
+        // never above 30 degrees from latitude -23
+        let polaris = Target::new("Polaris", 37.95, 89.26, 0.0);
+        let months = yearly_observability(&polaris, &observer, 2025, &constraints);
+        assert!(best_months(&months, 3).is_empty());
+    }
+
+    #[test]
+    fn test_night_plan() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let time = Time::new(2024, 12, 1, 12, 0, 0);
+        let environment = Environment::default();
+        let constraints = Constraints {
+            min_altitude: 30,
+            max_altitude: 90,
+            min_size: 0,
+            max_size: 1200,
+            moon_separation: 0,
+            frac_observable_time: 0,
+            max_targets: 60,
+            use_darkness: true,
+        };
+        // NGC 253 sets around midnight and M42 rises late in the evening, Polaris is never up
+        let targets = [
+            Target::new("NGC 253", 11.89, -25.29, 27.0),
+            Target::new("M42", 83.82, -5.39, 65.0),
+            Target::new("Polaris", 37.95, 89.26, 0.0),
+        ];
+        let plan = night_plan(&observer, &time, &environment, &constraints, &targets).unwrap();
+
+        let total = plan.allocated_hours(0) + plan.allocated_hours(1) + plan.allocated_hours(2)
+            + plan.idle_hours();
+        assert!((total - plan.available_hours()).abs() < 1e-6);
+        assert!(plan.allocated_hours(0) > 1.0);
+        assert!(plan.allocated_hours(1) > 1.0);
+        assert_eq!(plan.allocated_hours(2), 0.0);
+
+        // blocks and gaps follow each other without overlapping
+        let mut spans: Vec<(f64, f64)> = plan.blocks.iter().map(|b| (b.start, b.end))
+            .chain(plan.idle.iter().copied())
+            .collect();
+        spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+        assert!((spans[0].0 - plan.window.0).abs() < 1e-9);
+        assert!(spans.windows(2).all(|w| (w[0].1 - w[1].0).abs() < 1e-9));
+        assert!((spans[spans.len() - 1].1 - plan.window.1).abs() < 1e-9);
+        assert!(plan.blocks.iter().all(|b| b.end > b.start));
+
+        assert!(night_plan(&observer, &time, &environment, &constraints, &[]).is_none());
+    }
+
+    // Samples from `start` with the Moon fixed at (ra, dec, altitude)
+    fn fixed_samples(start: f64, count: usize, moon: (f64, f64, f64)) -> NightSamples {
+        let jd: Vec<f64> = (0..count).map(|i| start + i as f64 * SAMPLE_STEP).collect();
+        NightSamples { moon: vec![moon; count], moon_illumination: vec![1.0; count], jd }
+    }
+
+    #[test]
+    fn test_plan_night_rules() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let constraints = Constraints {
+            min_altitude: 30,
+            max_altitude: 90,
+            moon_separation: 20,
+            ..Default::default()
+        };
+        // A sets and B rises, 15° each side of the meridian halfway between the second and the
+        // third samples, so B is the highest from the third sample on
+        let start = 2460646.6;
+        let crossing = start + 1.5 * SAMPLE_STEP;
+        let lst = observer.hour_angle(0.0, crossing);
+        let a = Target::new("A", (lst - 15.0).rem_euclid(360.0), -23.0, 10.0);
+        let b = Target::new("B", (lst + 15.0).rem_euclid(360.0), -23.0, 10.0);
+        let window = (start, start + 8.5 * SAMPLE_STEP);
+        let moon_down = fixed_samples(start, 10, (0.0, 0.0, -30.0));
+
+        // A keeps the first PLAN_MIN_BLOCK samples, then B to the end of the window
+        let plan = plan_night(&observer, &moon_down, window, &[a.clone(), b.clone()], &constraints);
+        let switch = start + PLAN_MIN_BLOCK as f64 * SAMPLE_STEP;
+        assert_eq!(plan.blocks, vec![
+            PlanBlock { target: 0, start, end: switch },
+            PlanBlock { target: 1, start: switch, end: window.1 },
+        ]);
+        assert!(plan.idle.is_empty());
+        assert!((plan.allocated_hours(0) + plan.allocated_hours(1) - plan.available_hours()).abs() < 1e-9);
+
+        // the Moon up next to B leaves the whole window to A, down it does not matter
+        let moon_up = fixed_samples(start, 10, (b.ra, b.dec + 5.0, 40.0));
+        let plan = plan_night(&observer, &moon_up, window, &[a.clone(), b.clone()], &constraints);
+        assert_eq!(plan.blocks, vec![PlanBlock { target: 0, start, end: window.1 }]);
+
+        // equally high targets go to the first one
+        let plan = plan_night(&observer, &moon_down, window, &[b.clone(), b.clone()], &constraints);
+        assert_eq!(plan.blocks, vec![PlanBlock { target: 0, start, end: window.1 }]);
+
+        // no eligible target leaves the window idle
+        let polaris = Target::new("Polaris", 37.95, 89.26, 0.0);
+        let plan = plan_night(&observer, &moon_down, window, &[polaris], &constraints);
+        assert!(plan.blocks.is_empty());
+        assert_eq!(plan.idle, vec![window]);
+        assert!((plan.idle_hours() - plan.available_hours()).abs() < 1e-9);
+    }
+}
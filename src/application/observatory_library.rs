@@ -0,0 +1,477 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+
+// Observatories of the user, kept in a per user file next to the recent configurations, and the
+// importers of site definitions from other planetarium and capture software: Stellarium
+// location files, N.I.N.A. profiles and Cartes du Ciel configurations.
+
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::application::observer::{try_degrees_from_str, try_timezone_from_str, Observer, MAX_ELEVATION,
+                                   MIN_ELEVATION};
+use crate::application::recent::config_directory;
+use crate::application::sites::{fold, search_sites};
+use crate::application::timezones::{find_zone, nearest_city_zone};
+
+const LIBRARY_FILE: &str = "observatories.yaml";
+
+/// ObservatoryLibrary struct
+///
+/// # Attributes
+///
+/// * `sites` - Observatories of the user, in the order they were added
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct ObservatoryLibrary {
+    #[serde(default)]
+    pub sites: Vec<Observer>,
+}
+
+impl ObservatoryLibrary {
+    /// Library file of the current user
+    pub fn path() -> PathBuf {
+        config_directory().join(LIBRARY_FILE)
+    }
+
+    /// Reads the library, empty when the file does not exist or can not be read
+    pub fn load_from(path: &Path) -> ObservatoryLibrary {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_yaml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        std::fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn load() -> ObservatoryLibrary {
+        ObservatoryLibrary::load_from(&ObservatoryLibrary::path())
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.save_to(&ObservatoryLibrary::path())
+    }
+
+    /// Adds the sites, replacing the ones with the same name. Returns how many were new.
+    pub fn add(&mut self, sites: &[Observer]) -> usize {
+        let mut added = 0;
+        for site in sites {
+            match self.sites.iter_mut().find(|s| s.name == site.name) {
+                Some(existing) => *existing = site.clone(),
+                None => {
+                    self.sites.push(site.clone());
+                    added += 1;
+                }
+            }
+        }
+        added
+    }
+
+    /// Observatories of the library and the embedded sites whose name contains the query, ignoring
+    /// case and accents, the library first. The flag is true for the library ones.
+    pub fn search(&self, query: &str) -> Vec<(Observer, bool)> {
+        let folded = fold(query.trim());
+        let mut found: Vec<(Observer, bool)> = self
+            .sites
+            .iter()
+            .filter(|site| fold(site.name.as_deref().unwrap_or_default()).contains(&folded))
+            .map(|site| (site.clone(), true))
+            .collect();
+        found.extend(search_sites(query).into_iter().map(|site| (site.to_observer(), false)));
+        found
+    }
+}
+
+/// Software the site definitions come from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SiteFormat {
+    Stellarium,
+    Nina,
+    CartesDuCiel,
+}
+
+impl SiteFormat {
+    /// Format of the contents of a file: XML is a N.I.N.A. profile, `ObsLatitude` keys are a Cartes
+    /// du Ciel configuration, anything else is read as Stellarium location lines
+    pub fn detect(contents: &str) -> SiteFormat {
+        if contents.trim_start_matches('\u{feff}').trim_start().starts_with('<') {
+            SiteFormat::Nina
+        } else if contents.contains("ObsLatitude") {
+            SiteFormat::CartesDuCiel
+        } else {
+            SiteFormat::Stellarium
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SiteFormat::Stellarium => "Stellarium",
+            SiteFormat::Nina => "N.I.N.A.",
+            SiteFormat::CartesDuCiel => "Cartes du Ciel",
+        }
+    }
+}
+
+/// Sites read from a file of another software
+///
+/// # Attributes
+///
+/// * `format` - Format the file was read as
+/// * `sites` - Sites converted to observers
+/// * `notes` - Entries skipped and values estimated, to show to the user
+#[derive(Debug, Clone, PartialEq)]
+pub struct SiteImport {
+    pub format: SiteFormat,
+    pub sites: Vec<Observer>,
+    pub notes: Vec<String>,
+}
+
+// Standard time offset of a timezone of another software given as an offset, None when it is not
+// one. "Etc/GMT+3" is 3 hours west of Greenwich.
+fn import_timezone(text: &str) -> Option<f64> {
+    let text = text.trim();
+    if let Some(hours) = text.strip_prefix("Etc/GMT") {
+        return if hours.is_empty() { Some(0.0) } else { try_timezone_from_str(hours).ok().map(|h| -h) };
+    }
+    let offset = ["UTC", "GMT"].iter().find_map(|prefix| text.strip_prefix(prefix)).unwrap_or(text);
+    if offset.is_empty() {
+        return Some(0.0);
+    }
+    try_timezone_from_str(offset).ok()
+}
+
+// Timezone of a site for the import notes
+fn timezone_text(timezone: Option<&str>) -> &str {
+    timezone.filter(|t| !t.trim().is_empty()).unwrap_or("(none)")
+}
+
+// Observer of a site converted from another software. A tz database name is kept with its standard
+// offset, a missing or unknown timezone is guessed as the zone of the nearest city and, without
+// one, estimated from the longitude.
+fn import_site(name: &str, latitude: f64, longitude: f64, elevation: f64, timezone: Option<&str>,
+               notes: &mut Vec<String>) -> Observer {
+    let (timezone, zone) = match timezone.and_then(import_timezone) {
+        Some(offset) => (offset, None),
+        None => match (timezone.and_then(find_zone), nearest_city_zone(latitude, longitude)) {
+            (Some(zone), _) => (zone.offset, Some(zone.name.to_string())),
+            (None, Some(zone)) => {
+                notes.push(format!("{}: timezone {} set to {} (UTC{:+}) from the location, check it before applying",
+                                   name, timezone_text(timezone), zone.name, zone.offset));
+                (zone.offset, Some(zone.name.to_string()))
+            }
+            (None, None) => {
+                let estimate = (longitude / 15.0).round();
+                notes.push(format!("{}: timezone {} estimated from the longitude as UTC{:+}, check it before applying",
+                                   name, timezone_text(timezone), estimate));
+                (estimate, None)
+            }
+        },
+    };
+    Observer {
+        name: Some(name.to_string()),
+        latitude,
+        longitude,
+        elevation: (elevation.round() as i64).clamp(MIN_ELEVATION, MAX_ELEVATION),
+        timezone,
+        zone,
+    }
+}
+
+// Elevation in meters of the text, 0 when empty
+fn import_elevation(text: &str) -> Result<f64, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(0.0);
+    }
+    text.parse::<f64>()
+        .ok()
+        .filter(|elevation| elevation.is_finite())
+        .ok_or_else(|| format!("'{}' is not an elevation", text))
+}
+
+// Stellarium location lines, tab separated: name, region, country, role, population, latitude,
+// longitude, altitude, light pollution, timezone, planet and landscape. Angles are decimal degrees
+// with a hemisphere letter like 48.8534N or DMS.
+fn read_stellarium(contents: &str) -> SiteImport {
+    let mut import = SiteImport { format: SiteFormat::Stellarium, sites: Vec::new(), notes: Vec::new() };
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim_start_matches('\u{feff}');
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split('\t').map(|field| field.trim()).collect();
+        let field = |index: usize| fields.get(index).copied().unwrap_or_default();
+        if fields.len() < 7 || field(0).is_empty() {
+            import.notes.push(format!("Line {}: skipped, not a Stellarium location", i + 1));
+            continue;
+        }
+        if !field(10).is_empty() && !field(10).eq_ignore_ascii_case("Earth") {
+            import.notes.push(format!("Line {}: {} skipped, it is on {}", i + 1, field(0), field(10)));
+            continue;
+        }
+        let values = (
+            try_degrees_from_str(field(5), -90.0, 90.0),
+            try_degrees_from_str(field(6), -180.0, 180.0),
+            import_elevation(field(7)),
+        );
+        let (latitude, longitude, elevation) = match values {
+            (Ok(latitude), Ok(longitude), Ok(elevation)) => (latitude, longitude, elevation),
+            (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+                import.notes.push(format!("Line {}: {} skipped, {}", i + 1, field(0), e));
+                continue;
+            }
+        };
+        let name = match field(2) {
+            "" => field(0).to_string(),
+            country => format!("{}, {}", field(0), country),
+        };
+        let site = import_site(&name, latitude, longitude, elevation, Some(field(9)), &mut import.notes);
+        import.sites.push(site);
+    }
+    import
+}
+
+// Text of the XML elements without children by their path from the root, namespace prefixes
+// left out, e.g. ("Profile/AstrometrySettings/Latitude", "-23.1")
+fn xml_values(contents: &str) -> Vec<(String, String)> {
+    let mut path: Vec<String> = Vec::new();
+    let mut values = Vec::new();
+    let mut rest = contents;
+    while let Some(open) = rest.find('<') {
+        let text = &rest[..open];
+        rest = &rest[open + 1..];
+        if let Some(comment) = rest.strip_prefix("!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(close) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[..close];
+        rest = &rest[close + 1..];
+        if tag.starts_with('?') || tag.starts_with('!') || tag.ends_with('/') {
+            continue;
+        }
+        if tag.starts_with('/') {
+            if !text.trim().is_empty() {
+                values.push((path.join("/"), xml_unescape(text.trim())));
+            }
+            path.pop();
+        } else {
+            let name = tag.split_whitespace().next().unwrap_or_default();
+            path.push(name.rsplit(':').next().unwrap_or(name).to_string());
+        }
+    }
+    values
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+// N.I.N.A. profile, the site is in AstrometrySettings in decimal degrees, positive north and east,
+// and the name of the profile is a child of the root. Profiles have no timezone.
+fn read_nina(contents: &str) -> Result<SiteImport, String> {
+    let mut import = SiteImport { format: SiteFormat::Nina, sites: Vec::new(), notes: Vec::new() };
+    let values = xml_values(contents);
+    let setting = |key: &str| {
+        values
+            .iter()
+            .find(|(path, _)| path.ends_with(&format!("AstrometrySettings/{}", key)))
+            .map(|(_, value)| value.as_str())
+    };
+    let (Some(latitude), Some(longitude)) = (setting("Latitude"), setting("Longitude")) else {
+        return Err("No AstrometrySettings with Latitude and Longitude, it is not a N.I.N.A. profile".to_string());
+    };
+    let latitude = try_degrees_from_str(latitude, -90.0, 90.0)?;
+    let longitude = try_degrees_from_str(longitude, -180.0, 180.0)?;
+    let elevation = import_elevation(setting("Elevation").unwrap_or_default())?;
+    let name = values
+        .iter()
+        .find(|(path, _)| path.split('/').count() == 2 && path.ends_with("/Name"))
+        .map_or("N.I.N.A. profile", |(_, name)| name.as_str());
+    let site = import_site(name, latitude, longitude, elevation, None, &mut import.notes);
+    import.sites.push(site);
+    Ok(import)
+}
+
+// Cartes du Ciel configuration, the site keys are ObsName, ObsLatitude, ObsLongitude, ObsAltitude
+// and ObsTZ, numbered the same for each site of a list. Longitudes are positive west.
+fn read_cartes_du_ciel(contents: &str) -> SiteImport {
+    let mut import = SiteImport { format: SiteFormat::CartesDuCiel, sites: Vec::new(), notes: Vec::new() };
+    // keys of each site by number, in the order of the file
+    let mut numbered: Vec<(String, Vec<(String, String)>)> = Vec::new();
+    for line in contents.lines() {
+        let Some((key, value)) = line.trim().split_once('=') else {
+            continue;
+        };
+        let Some(key) = key.trim().strip_prefix("Obs") else {
+            continue;
+        };
+        let number_start = key.find(|c: char| c.is_ascii_digit()).unwrap_or(key.len());
+        let (key, number) = key.split_at(number_start);
+        let value = value.trim().trim_matches('"').to_string();
+        match numbered.iter_mut().find(|(n, _)| n == number) {
+            Some((_, keys)) => keys.push((key.to_string(), value)),
+            None => numbered.push((number.to_string(), vec![(key.to_string(), value)])),
+        }
+    }
+    for (number, keys) in numbered {
+        let key = |name: &str| keys.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str());
+        let (Some(latitude), Some(longitude)) = (key("Latitude"), key("Longitude")) else {
+            continue;
+        };
+        let name = match (key("Name").filter(|n| !n.is_empty()), key("Country").filter(|c| !c.is_empty())) {
+            (Some(name), Some(country)) => format!("{}, {}", name, country),
+            (Some(name), None) => name.to_string(),
+            (None, _) => format!("Cartes du Ciel site {}", number),
+        };
+        let values = (
+            try_degrees_from_str(latitude, -90.0, 90.0),
+            try_degrees_from_str(longitude, -180.0, 180.0),
+            import_elevation(key("Altitude").unwrap_or_default()),
+        );
+        match values {
+            (Ok(latitude), Ok(longitude), Ok(elevation)) => {
+                let timezone = key("TZ").or(key("TimeZone"));
+                let site = import_site(&name, latitude, -longitude, elevation, timezone, &mut import.notes);
+                import.sites.push(site);
+            }
+            (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+                import.notes.push(format!("{} skipped, {}", name, e));
+            }
+        }
+    }
+    import
+}
+
+/// Sites of a Stellarium location file, a N.I.N.A. profile or a Cartes du Ciel configuration, the
+/// format detected from the contents. Fails when no site can be read.
+pub fn import_sites(contents: &str) -> Result<SiteImport, String> {
+    let format = SiteFormat::detect(contents);
+    let import = match format {
+        SiteFormat::Stellarium => read_stellarium(contents),
+        SiteFormat::Nina => read_nina(contents)?,
+        SiteFormat::CartesDuCiel => read_cartes_du_ciel(contents),
+    };
+    if import.sites.is_empty() {
+        let mut message = format!("No site found in the {} file", format.name());
+        for note in import.notes.iter().take(3) {
+            message.push_str(&format!("\n{}", note));
+        }
+        return Err(message);
+    }
+    Ok(import)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_import_sites() {
+        let stellarium = "# Stellarium user locations\n\
+Piracaia\tSão Paulo\tBrazil\tX\t25\t23.0541S\t46.3581W\t780\t4\tUTC-03:00\tEarth\t\n\
+Home\t\tFrance\tX\t0\t48d51'24\"N\t2.3488E\t42\t\tEurope/Paris\tEarth\t\n\
+Gusev\t\t\tX\t0\t14.5S\t175.4E\t0\t\t\tMars\t\n\
+broken line\n";
+        let import = import_sites(stellarium).unwrap();
+        assert_eq!(import.format, SiteFormat::Stellarium);
+        assert_eq!(import.sites.len(), 2);
+        let piracaia = &import.sites[0];
+        assert_eq!(piracaia.name.as_deref(), Some("Piracaia, Brazil"));
+        assert!((piracaia.latitude + 23.0541).abs() < 1e-9 && (piracaia.longitude + 46.3581).abs() < 1e-9);
+        assert_eq!((piracaia.elevation, piracaia.timezone), (780, -3.0));
+        assert_eq!(piracaia.zone, None);
+        // a tz database name is kept with its standard offset
+        let home = &import.sites[1];
+        assert!((home.latitude - 48.8567).abs() < 1e-4);
+        assert_eq!((home.timezone, home.zone.as_deref()), (1.0, Some("Europe/Paris")));
+        assert_eq!(import.notes.len(), 2);
+
+        let nina = r#"<?xml version="1.0" encoding="utf-8"?>
+<Profile xmlns="http://schemas.datacontract.org/2004/07/NINA.Profile" xmlns:i="http://www.w3.org/2001/XMLSchema-instance">
+  <AstrometrySettings>
+    <Elevation>1864</Elevation>
+    <HorizonFilePath i:nil="true"/>
+    <Latitude>-22.5344</Latitude>
+    <Longitude>-45.5825</Longitude>
+  </AstrometrySettings>
+  <FilterWheelSettings><FilterWheelFilters><FilterInfo><Name>Red</Name></FilterInfo></FilterWheelFilters></FilterWheelSettings>
+  <!-- <Name>commented</Name> -->
+  <Name>Pico dos Dias &amp; friends</Name>
+</Profile>"#;
+        let import = import_sites(nina).unwrap();
+        assert_eq!(import.format, SiteFormat::Nina);
+        let site = &import.sites[0];
+        assert_eq!(site.name.as_deref(), Some("Pico dos Dias & friends"));
+        assert_eq!((site.latitude, site.longitude, site.elevation), (-22.5344, -45.5825, 1864));
+        // profiles have no timezone, the one of the location is suggested and noted
+        assert_eq!((site.timezone, site.zone.as_deref()), (-3.0, Some("America/Sao_Paulo")));
+        assert_eq!(import.notes.len(), 1);
+
+        // longitudes of Cartes du Ciel are positive west
+        let cdc = "[observatory]\nObsLatitude=46.2\nObsLongitude=-6.15\nObsAltitude=420\nObsTZ=Etc/GMT-1\n\
+ObsName=Geneva\nObsCountry=Switzerland\n[favorites]\nObsName1=Tucson\nObsLatitude1=32.2226\n\
+ObsLongitude1=110.9747\nObsAltitude1=728\nObsTZ1=-7\n";
+        let import = import_sites(cdc).unwrap();
+        assert_eq!(import.format, SiteFormat::CartesDuCiel);
+        assert_eq!(import.sites.len(), 2);
+        assert_eq!(import.sites[0].name.as_deref(), Some("Geneva, Switzerland"));
+        assert_eq!((import.sites[0].longitude, import.sites[0].timezone), (6.15, 1.0));
+        assert_eq!((import.sites[1].longitude, import.sites[1].timezone), (-110.9747, -7.0));
+        assert!(import.notes.is_empty());
+
+        assert!(import_sites("<Profile><Name>Empty</Name></Profile>").is_err());
+        assert!(import_sites("no sites here").is_err());
+    }
+
+    #[test]
+    fn test_observatory_library() {
+        let path = std::env::temp_dir().join("skycalc_test_library").join(LIBRARY_FILE);
+        let mut library = ObservatoryLibrary::default();
+        let site = Observer::location(Some("Backyard".to_string()), "-23.1", "-46.5", 780, "-3");
+        assert_eq!(library.add(std::slice::from_ref(&site)), 1);
+        let moved = Observer { elevation: 800, ..site };
+        assert_eq!(library.add(std::slice::from_ref(&moved)), 0);
+        assert_eq!(library.sites, vec![moved]);
+
+        // library sites come before the embedded ones
+        let found = library.search("");
+        assert_eq!(found[0].0.name.as_deref(), Some("Backyard"));
+        assert!(found[0].1 && !found[1].1);
+        assert!(library.search("backy").len() == 1);
+
+        library.save_to(&path).unwrap();
+        assert_eq!(ObservatoryLibrary::load_from(&path), library);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
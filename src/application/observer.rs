@@ -24,13 +24,17 @@ use chrono::{NaiveTime, Timelike};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 use serde::ser::SerializeStruct;
+use crate::application::time::{LocalTime, Time};
+use crate::application::transformations::{equatorial_to_altaz_jd, hour_angle_jd};
+use crate::utils::angle::format_dms;
+use crate::utils::utils::constrain_360;
 
 pub fn degrees_from_str(input: &str, min: f64, max: f64) -> f64 {
     let input_trimmed = input.trim();
 
     // First, try parsing as decimal degrees
     if let Ok(deg) = input_trimmed.parse::<f64>() {
-        if deg < min || deg > max {
+        if !deg.is_finite() || deg < min || deg > max {
             return 0.0;
         }
 
@@ -41,10 +45,55 @@ pub fn degrees_from_str(input: &str, min: f64, max: f64) -> f64 {
     parse_dms(input_trimmed, min, max)
 }
 
+// Parses degrees like `degrees_from_str`, with the message to show when the text is not an angle
+// or is out of range instead of falling back to 0.0
+pub fn try_degrees_from_str(input: &str, min: f64, max: f64) -> Result<f64, String> {
+    let input_trimmed = input.trim();
+    if input_trimmed.is_empty() {
+        return Err("Enter an angle".to_string());
+    }
+
+    let deg = match input_trimmed.parse::<f64>() {
+        Ok(deg) => deg,
+        Err(_) => dms_value(input_trimmed).ok_or_else(|| {
+            format!("'{}' is not an angle, use decimal degrees or DMS like 23d 06m S", input_trimmed)
+        })?,
+    };
+    if !deg.is_finite() || deg < min || deg > max {
+        return Err(format!("{} is out of range, from {} to {}", input_trimmed, min, max));
+    }
+    Ok(deg)
+}
+
+// Decimal degrees of a DMS string, None when a part is not a number
+fn dms_value(dms: &str) -> Option<f64> {
+    let dms = dms.to_lowercase();
+    let parts: Vec<&str> = dms
+        .split(&['d', 'm', 's', '°', '\'', '\"', ' ', 'n', 'w', 'e'][..])
+        .filter(|part| !part.is_empty())
+        .collect();
+    if parts.is_empty() || parts.len() > 3 {
+        return None;
+    }
+
+    let mut value = 0.0;
+    for (part, scale) in parts.iter().zip([1.0, 60.0, 3600.0]) {
+        value += part.parse::<f64>().ok()? / scale;
+    }
+    match dms.chars().last() {
+        Some('s') | Some('w') => Some(-value),
+        _ => Some(value),
+    }
+}
+
 // Parses a DMS (degrees, minutes, seconds) string into decimal degrees within the specified range.
 pub fn parse_dms(dms: &str, min: f64, max: f64) -> f64 {
     let dms = dms.to_lowercase();
-    let parts: Vec<&str> = dms.split(&['d', 'm', 's', '°', '\'', '\"', ' ', 'n', 'w', 'e'][..]).collect();
+    // Consecutive separators, e.g. "23d 06m S", give empty parts that are skipped
+    let parts: Vec<&str> = dms
+        .split(&['d', 'm', 's', '°', '\'', '\"', ' ', 'n', 'w', 'e'][..])
+        .filter(|part| !part.is_empty())
+        .collect();
 
     if parts.is_empty() {
         return 0.0;
@@ -81,45 +130,79 @@ pub fn parse_dms(dms: &str, min: f64, max: f64) -> f64 {
     let decimal_deg = direction * (deg + min_val / 60.0 + sec / 3600.0);
 
     // Ensure the value is within the specified range
-    if decimal_deg < min || decimal_deg > max {
+    if !decimal_deg.is_finite() || decimal_deg < min || decimal_deg > max {
         return 0.0;
     }
     decimal_deg
 }
 
-// Parse timezone from string, e.g., "+05:30" or "-02:00" or "3.5"
+// Timezone offsets range from UTC-12 to UTC+14
+const MAX_TIMEZONE: f64 = 14.0;
+
+// Parse timezone from string, e.g., "+05:30" or "-02:00" or "3.5", defaulting to UTC when out of
+// range
 pub fn timezone_from_str(input: &str) -> f64 {
     let input_trimmed = input.trim();
 
-    // First, try parsing as decimal degrees
-    if let Ok(deg) = input_trimmed.parse::<f64>() {
-        return deg;
+    // First, try parsing as decimal hours, if not decimal, try parsing as HM (Hours, Minutes)
+    let timezone = match input_trimmed.parse::<f64>() {
+        Ok(hours) => hours,
+        Err(_) => parse_hm(input_trimmed),
+    };
+
+    if !timezone.is_finite() || timezone.abs() > MAX_TIMEZONE {
+        return default_timezone();
     }
+    timezone
+}
 
-    // If not decimal, try parsing as HM (Hours, Minutes)
-    parse_hm(input_trimmed)
+// Parses a timezone like `timezone_from_str`, with the message to show when the text is not an
+// offset or is out of range instead of falling back to UTC
+pub fn try_timezone_from_str(input: &str) -> Result<f64, String> {
+    let input_trimmed = input.trim();
+    let timezone = match input_trimmed.parse::<f64>() {
+        Ok(hours) => hours,
+        Err(_) => hm_value(input_trimmed)
+            .ok_or_else(|| format!("'{}' is not an offset, use hours like -3 or 5.5 or +05:30", input_trimmed))?,
+    };
+    if !timezone.is_finite() || timezone.abs() > MAX_TIMEZONE {
+        return Err(format!("{} is out of range, from -{} to +{} hours", input_trimmed, MAX_TIMEZONE, MAX_TIMEZONE));
+    }
+    Ok(timezone)
+}
+
+// Elevations accepted in the observatory setup, in meters
+pub const MIN_ELEVATION: i64 = 0;
+pub const MAX_ELEVATION: i64 = 9000;
+
+// Parses an elevation in whole meters, with the message to show when it is not valid
+pub fn try_elevation_from_str(input: &str) -> Result<i64, String> {
+    let input_trimmed = input.trim();
+    let elevation = input_trimmed
+        .parse::<i64>()
+        .map_err(|_| format!("'{}' is not an elevation, use whole meters", input_trimmed))?;
+    if !(MIN_ELEVATION..=MAX_ELEVATION).contains(&elevation) {
+        return Err(format!("{} is out of range, from {} to {} m", elevation, MIN_ELEVATION, MAX_ELEVATION));
+    }
+    Ok(elevation)
 }
 
 // Parses a HM (hour, minutes) string into decimal hours.
 pub fn parse_hm(hm: &str) -> f64 {
-    // Check for a leading '-' to handle negative times
-    let is_negative = hm.starts_with('-');
-    let time_part = if is_negative {
-        &hm[1..] // Remove the '-' for parsing
-    } else {
-        hm
-    };
+    hm_value(hm).unwrap_or(0.0) // Return 0.0 if parsing fails
+}
 
-    if let Ok(time) = NaiveTime::parse_from_str(time_part, "%H:%M") {
-        let decimal_hours = time.hour() as f64 + time.minute() as f64 / 60.0;
-        return if is_negative {
-            -decimal_hours
-        } else {
-            decimal_hours
-        };
-    }
+// Decimal hours of a HM string, None when it is not a time
+fn hm_value(hm: &str) -> Option<f64> {
+    // Check for a leading '-' to handle negative times, a leading '+' is accepted too
+    let (is_negative, time_part) = match hm.strip_prefix('-') {
+        Some(time_part) => (true, time_part),
+        None => (false, hm.strip_prefix('+').unwrap_or(hm)),
+    };
 
-    0.0 // Return 0.0 if parsing fails
+    let time = NaiveTime::parse_from_str(time_part, "%H:%M").ok()?;
+    let decimal_hours = time.hour() as f64 + time.minute() as f64 / 60.0;
+    Some(if is_negative { -decimal_hours } else { decimal_hours })
 }
 
 /// Observer struct
@@ -132,6 +215,9 @@ pub fn parse_hm(hm: &str) -> f64 {
 /// * `lat` - Latitude of the observer in degrees
 /// * `lon` - Longitude of the observer in degrees
 /// * `elevation` - Elevation of the observer in meters
+/// * `timezone` - Standard time offset from UTC in hours
+/// * `zone` - Name of the timezone in the tz database, e.g. "Europe/Paris", when known (see
+///   `timezones`)
 ///
 /// # Methods
 ///
@@ -141,18 +227,18 @@ pub fn parse_hm(hm: &str) -> f64 {
 ///
 /// # Examples
 ///
-/// ```no_run
-
-/// use observer::{Observer, Time};
+/// ```
+/// use skycalc::application::observer::Observer;
+/// use skycalc::application::time::Time;
 ///
-/// let observer = Observer::location(-23.1, -46.5, 780, Some("Piracaia".to_string()));
+/// let observer = Observer::location(Some("Piracaia".to_string()), "-23.1", "-46.5", 780, "-3");
 /// let time = Time::new(2024, 11, 14, 12, 0, 0);
 /// let lst = observer.local_sidereal_time(&time);
 /// println!("Local sidereal time: {}", lst);
-/// assert_eq!(lst, 315.09169822871746);
+/// assert!((lst - 187.5813177432865).abs() < 1e-6);
 /// ```
 
-#[derive(Debug, Default, Clone, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
 pub struct Observer {
     #[serde(default = "default_name")]
     pub name: Option<String>,
@@ -176,6 +262,8 @@ pub struct Observer {
         deserialize_with = "deserialize_timezone"
     )]
     pub timezone: f64,
+    #[serde(default)]
+    pub zone: Option<String>,
 }
 
 // Default value functions for Observer fields
@@ -199,13 +287,32 @@ pub fn default_timezone() -> f64 {
     0.0 // Default timezone is UTC
 }
 
+// Text of a value written either as a string or as a number, YAML reads both as text but JSON
+// and TOML keep the numbers saved by the application as numbers
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TextOrNumber {
+    Text(String),
+    Number(f64),
+}
+
+fn deserialize_text<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match TextOrNumber::deserialize(deserializer)? {
+        TextOrNumber::Text(text) => text,
+        TextOrNumber::Number(number) => number.to_string(),
+    })
+}
+
 // Custom deserializer for latitude
 fn deserialize_latitude<'de, D>(deserializer: D) -> Result<f64, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let value = String::deserialize(deserializer)?;
-    Ok(degrees_from_str(&value, -180.0, 180.0))
+    let value = deserialize_text(deserializer)?;
+    Ok(degrees_from_str(&value, -90.0, 90.0))
 }
 
 impl Serialize for Observer {
@@ -213,12 +320,17 @@ impl Serialize for Observer {
     where
         S: Serializer,
     {
-        let mut s = serializer.serialize_struct("Observer", 5)?;
+        let mut s = serializer.serialize_struct("Observer", 6)?;
         s.serialize_field("name", &self.name)?;
         s.serialize_field("latitude", &self.latitude)?;
         s.serialize_field("longitude", &self.longitude)?;
         s.serialize_field("elevation", &self.elevation)?;
         s.serialize_field("timezone", &self.timezone)?;
+        // configurations without a named zone stay as they were
+        match &self.zone {
+            Some(zone) => s.serialize_field("zone", zone)?,
+            None => s.skip_field("zone")?,
+        }
         s.end()
     }
 }
@@ -228,7 +340,7 @@ fn deserialize_longitude<'de, D>(deserializer: D) -> Result<f64, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let value = String::deserialize(deserializer)?;
+    let value = deserialize_text(deserializer)?;
     Ok(degrees_from_str(&value, -180.0, 180.0))
 }
 
@@ -249,7 +361,7 @@ fn deserialize_timezone<'de, D>(deserializer: D) -> Result<f64, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let value = String::deserialize(deserializer)?;
+    let value = deserialize_text(deserializer)?;
     Ok(timezone_from_str(&value))
 }
 
@@ -281,26 +393,27 @@ impl Observer {
     ///
     /// # Examples
     ///
-    /// ```no_run
-    /// use observer::Observer;
+    /// ```
+    /// use skycalc::application::observer::Observer;
     ///
-    /// let observer = Observer::new(-23.1, -46.5, 780, Some("Piracaia".to_string()));
-    /// assert_eq!(observer.lat, -23.1);
-    /// assert_eq!(observer.lon, -46.5);
+    /// let observer = Observer::location(Some("Piracaia".to_string()), "-23.1", "-46.5", 780, "-3");
+    /// assert_eq!(observer.latitude, -23.1);
+    /// assert_eq!(observer.longitude, -46.5);
     /// assert_eq!(observer.elevation, 780);
+    /// assert_eq!(observer.timezone, -3.0);
     /// assert_eq!(observer.name, Some("Piracaia".to_string()));
-    /// println!("{}", observer.to_string());
+    /// println!("{}", observer);
     /// ```
     ///
-    /// ```no_run
-    /// use observer::Observer;
+    /// ```
+    /// use skycalc::application::observer::Observer;
     ///
-    /// let observer = Observer::location("23d 06m S", "46d 30m W", 780, None);
-    /// assert_eq!(observer.lat, -23.1);
-    /// assert_eq!(observer.lon, -46.5);
+    /// let observer = Observer::location(None, "23d 06m S", "46d 30m W", 780, "-3");
+    /// assert!((observer.latitude + 23.1).abs() < 1e-9);
+    /// assert!((observer.longitude + 46.5).abs() < 1e-9);
     /// assert_eq!(observer.elevation, 780);
     /// assert_eq!(observer.name, None);
-    /// println!("{}", observer.to_string());
+    /// println!("{}", observer);
     /// ```
     pub fn location(
         name: Option<String>,
@@ -319,9 +432,51 @@ impl Observer {
             longitude,
             elevation,
             timezone,
+            zone: None,
         }
     }
 
+    /// Calculate the local sidereal time at a given time
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - Time (UTC)
+    ///
+    /// # Returns
+    ///
+    /// * `f64` - Local sidereal time in degrees
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skycalc::application::observer::Observer;
+    /// use skycalc::application::time::Time;
+    ///
+    /// let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+    /// let time = Time::new(2024, 11, 14, 12, 0, 0);
+    /// let lst = observer.local_sidereal_time(&time);
+    /// assert!((lst - 187.5813177432865).abs() < 1e-6);
+    /// ```
+    pub fn local_sidereal_time(&self, time: &Time) -> f64 {
+        constrain_360(time.to_gst() + self.longitude)
+    }
+
+    /// Local time of a JD (UTC) with the observer's UTC offset
+    pub fn local_time(&self, jd: f64) -> LocalTime {
+        LocalTime::new(jd, self.timezone)
+    }
+
+    /// Hour angle in degrees (0 - 360) of a right ascension in degrees at a JD (UTC)
+    pub fn hour_angle(&self, ra: f64, jd: f64) -> f64 {
+        hour_angle_jd(self.longitude, ra, jd)
+    }
+
+    /// Altitude and azimuth (from north, through east) in degrees of an equatorial position in
+    /// degrees at a JD (UTC)
+    pub fn altaz(&self, ra: f64, dec: f64, jd: f64) -> (f64, f64) {
+        equatorial_to_altaz_jd(self.latitude, self.longitude, ra, dec, jd)
+    }
+
     /// Convert the Observer to a string
     ///
     /// # Returns
@@ -330,20 +485,18 @@ impl Observer {
     ///
     /// # Examples
     ///
-    /// ```no_run
-    /// use observer::Observer;
+    /// ```
+    /// use skycalc::application::observer::Observer;
     ///
-    /// let observer = Observer::location("-23.1", "-46.5", 780, Some("Piracaia".to_string()));
-    /// assert_eq!(observer.to_string(), "Name: Piracaia, Lat: -23.1, Lon: -46.5, Elevation: 780");
-    /// println!("{}", observer.to_string_decimal());
+    /// let observer = Observer::location(Some("Piracaia".to_string()), "-23.1", "-46.5", 780, "-3");
+    /// assert_eq!(observer.to_string_decimal(), "Piracaia, lat: -23.1, lon: -46.5, elevation: 780 m, tz: -3.00 h");
     /// ```
     ///
-    /// ```no_run
-    /// use observer::Observer;
+    /// ```
+    /// use skycalc::application::observer::Observer;
     ///
-    /// let observer = Observer::location("-23.1", "-46.5", 780, None);
-    /// assert_eq!(observer.to_string(), "Name: My observatory, Lat: -23.1, Lon: -46.5, Elevation: 780");
-    /// println!("{}", observer.to_string());
+    /// let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+    /// assert_eq!(observer.to_string_decimal(), "My observatory, lat: -23.1, lon: -46.5, elevation: 780 m, tz: -3.00 h");
     /// ```
     pub fn to_string_decimal(&self) -> String {
         if let Some(name) = &self.name {
@@ -358,15 +511,24 @@ impl Observer {
         )
     }
 
-    // TODO Create to_string_dms
+    /// Same as `to_string_decimal` with the coordinates in degrees, minutes and seconds
+    ///
+    /// ```
+    /// use skycalc::application::observer::Observer;
+    ///
+    /// let observer = Observer::location(Some("Piracaia".to_string()), "-23.1", "-46.51", 780, "-3");
+    /// assert_eq!(observer.to_string_dms(),
+    ///            "Piracaia, lat: 23° 6' 0.0\" S, lon: 46° 30' 36.0\" W, elevation: 780 m, tz: -3.00 h");
+    /// ```
     pub fn to_string_dms(&self) -> String {
-        // if let Some(name) = &self.name {
-        //     return format!("{}, lat: {}, lon: {}, elevation: {} m, tz: {:03}:{:02} h",
-        //                    name, self.latitude, self.longitude, self.elevation, self.timezone.0, self.timezone.1)
-        // }
-        // format!("My observatory, lat: {}, lon: {}, elevation: {} m, tz: {:03}:{:02} h",
-        //         self.latitude, self.longitude, self.elevation, self.timezone.0, self.timezone.1)
-        "".to_string()
+        format!(
+            "{}, lat: {}, lon: {}, elevation: {} m, tz: {:3.2} h",
+            self.name.as_deref().unwrap_or("My observatory"),
+            format_dms(self.latitude, true),
+            format_dms(self.longitude, false),
+            self.elevation,
+            self.timezone
+        )
     }
 }
 
@@ -388,3 +550,61 @@ impl fmt::Display for Observer {
     }
 }
 
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::application::time::from_str_or_now;
+    use crate::utils::fuzz::{Fuzzer, TEXT_TOKENS};
+
+    #[test]
+    fn test_parse_dms() {
+        assert!((degrees_from_str("23d 06m S", -90.0, 90.0) + 23.1).abs() < 1e-9);
+        assert!((degrees_from_str("46d 30m 36s W", -180.0, 180.0) + 46.51).abs() < 1e-9);
+        assert_eq!(degrees_from_str("nan", -90.0, 90.0), 0.0);
+        assert_eq!(timezone_from_str("-03:30"), -3.5);
+        assert_eq!(timezone_from_str("-"), 0.0);
+        assert_eq!(timezone_from_str("1e309"), 0.0);
+    }
+
+    #[test]
+    fn test_to_string_dms() {
+        let observer = Observer::location(Some("Piracaia".to_string()), "-23.1", "-46.51", 780, "-3");
+        assert_eq!(observer.to_string_dms(),
+                   "Piracaia, lat: 23° 6' 0.0\" S, lon: 46° 30' 36.0\" W, elevation: 780 m, tz: -3.00 h");
+        assert_eq!(format_dms(10.99999, true), "11° 0' 0.0\" N");
+        let parsed = try_degrees_from_str(&format_dms(-46.51, false), -180.0, 180.0).unwrap();
+        assert!((parsed + 46.51).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_try_parsers() {
+        assert!((try_degrees_from_str("23d 06m S", -90.0, 90.0).unwrap() + 23.1).abs() < 1e-9);
+        assert_eq!(try_degrees_from_str(" -46.5 ", -180.0, 180.0), Ok(-46.5));
+        assert!(try_degrees_from_str("abc", -90.0, 90.0).is_err());
+        assert!(try_degrees_from_str("23x", -90.0, 90.0).is_err());
+        assert!(try_degrees_from_str("91", -90.0, 90.0).is_err());
+        assert!(try_degrees_from_str("", -90.0, 90.0).is_err());
+        assert_eq!(try_timezone_from_str("+05:30"), Ok(5.5));
+        assert!(try_timezone_from_str("15").is_err());
+        assert!(try_timezone_from_str("UTC").is_err());
+        assert_eq!(try_elevation_from_str("780"), Ok(780));
+        assert!(try_elevation_from_str("-5").is_err());
+        assert!(try_elevation_from_str("7.5").is_err());
+    }
+
+    #[test]
+    fn test_parsers_fuzz() {
+        let mut fuzzer = Fuzzer::new(0x9E37_79B9_7F4A_7C15);
+        for _ in 0..50_000 {
+            let input = fuzzer.string(TEXT_TOKENS, 8);
+            let latitude = degrees_from_str(&input, -90.0, 90.0);
+            assert!(latitude.is_finite() && latitude.abs() <= 90.0, "{:?}", input);
+            let timezone = timezone_from_str(&input);
+            assert!(timezone.is_finite() && timezone.abs() <= MAX_TIMEZONE, "{:?}", input);
+            let time = from_str_or_now(&input);
+            assert!(time.to_jd().is_finite(), "{:?}", input);
+            time.to_utc();
+        }
+    }
+}
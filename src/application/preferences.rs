@@ -0,0 +1,310 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use serde::{Deserialize, Serialize};
+use crate::application::calculation::CalculationSettings;
+use crate::application::session::WindowGeometry;
+use crate::application::time::TimeFormatter;
+use crate::application::units::Units;
+use crate::utils::i18n::Language;
+
+/// Color themes offered under View/Themes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    Dark,
+    #[default]
+    Black,
+    Gray,
+}
+
+/// Themes in the order of the enum
+pub const THEMES: [Theme; 3] = [Theme::Dark, Theme::Black, Theme::Gray];
+
+impl Theme {
+    /// Name shown in the View/Themes menu
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::Dark => "Dark",
+            Theme::Black => "Black",
+            Theme::Gray => "Gray",
+        }
+    }
+}
+
+/// Widget themes offered under View/Themes/Widgets, the look of buttons, frames and scrollbars
+/// drawn under the color theme. Standard keeps the GTK scheme of FLTK.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WidgetStyle {
+    #[default]
+    Standard,
+    Classic,
+    Aero,
+    Metro,
+    Greybird,
+    Dark,
+}
+
+/// Widget styles in the order of the enum
+pub const WIDGET_STYLES: [WidgetStyle; 6] = [
+    WidgetStyle::Standard,
+    WidgetStyle::Classic,
+    WidgetStyle::Aero,
+    WidgetStyle::Metro,
+    WidgetStyle::Greybird,
+    WidgetStyle::Dark,
+];
+
+impl WidgetStyle {
+    /// Name shown in the View/Themes/Widgets menu
+    pub fn name(&self) -> &'static str {
+        match self {
+            WidgetStyle::Standard => "Standard",
+            WidgetStyle::Classic => "Classic",
+            WidgetStyle::Aero => "Aero",
+            WidgetStyle::Metro => "Metro",
+            WidgetStyle::Greybird => "Greybird",
+            WidgetStyle::Dark => "Dark",
+        }
+    }
+}
+
+/// Appearance struct
+///
+/// Color theme and widget style, always applied together so the colors of the theme are drawn
+/// over the widgets of the style.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+pub struct Appearance {
+    #[serde(default)]
+    pub theme: Theme,
+    #[serde(default)]
+    pub widgets: WidgetStyle,
+}
+
+/// Preferences struct
+///
+/// User preferences shared by the dialogs and the reports.
+///
+/// # Attributes
+///
+/// * `time_format` - Format used to display event times (see `Time::to_string`)
+/// * `show_seconds` - Show seconds in event times instead of rounding to the nearest minute
+/// * `hour12` - Show event times on a 12-hour clock with AM/PM
+/// * `month_first` - Show event dates as month-day instead of day-month
+/// * `month_names` - Show the month of event dates as its abbreviation in the current language
+/// * `confirm_overwrite` - Ask before replacing an export of the same night
+/// * `html_timeline` - Include the night timeline in the HTML darkness report
+/// * `solar_midnight` - Center the night timeline on solar midnight instead of clock midnight
+/// * `light_bands` - Show the golden and blue hours in the darkness dialog and report
+/// * `zodiacal_light` - Add the zodiacal light windows to the darkness report
+/// * `lunar_observing` - Add the lunar colongitude and the features near the terminator to the darkness report
+/// * `alignment_stars` - Add the go-to alignment stars at nautical dusk to the darkness report
+/// * `field_rotation` - Show the field rotation of alt-az mounts in the target details
+/// * `weather_forecast` - Query a weather forecast for the selected night (needs internet access)
+/// * `sky_brightness_file` - CSV grid with the zenith sky brightness (see `light_pollution`)
+/// * `dem_directory` - Directory with SRTM height tiles for the elevation lookup (see `elevation`)
+/// * `remember_date` - Save the selected date with the configuration and restore it when loading
+/// * `date_rollover` - What the darkness dialog does when a new observing night starts while
+///   tonight is shown
+/// * `rollover_hour` - Local hour (0 - 23) the observing night changes at
+/// * `scripts` - Run the user scripts for custom target columns and report lines (see `scripting`)
+/// * `dms_coordinates` - Show the observatory coordinates in degrees, minutes and seconds
+/// * `ui_scale` - Interface scale factor, `None` follows the system setting of each screen
+/// * `satellites` - List the visible satellite passes in the darkness report (see `satellites`)
+/// * `tle_file` - Two-line elements of the satellites, `None` downloads the Celestrak visual group
+/// * `mount_address` - Mount driver the observatory site is synchronized with (see `mount`)
+/// * `language` - Language of the interface and the reports (see `i18n`)
+/// * `units` - Units the elevation, temperature and pressure are shown and typed in, the
+///   configuration stays metric (see `units`)
+/// * `appearance` - Color theme and widget style chosen under View/Themes, `None` keeps the ones
+///   of the last session
+/// * `window` - Main window geometry at the last exit, None before the first one
+/// * `night_vision` - Night vision red palette over the theme
+/// * `open_tools` - Menu paths of the tool windows left open, opened again at startup
+/// * `calculation` - Grid resolution, lunar theory and refraction of the night computations
+///   (see `calculation`)
+/// * `moon_avoidance` - Radius in degrees of the avoidance zone around the Moon (see `avoidance`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Preferences {
+    #[serde(default = "default_time_format")]
+    pub time_format: String,
+    #[serde(default)]
+    pub show_seconds: bool,
+    #[serde(default)]
+    pub hour12: bool,
+    #[serde(default)]
+    pub month_first: bool,
+    #[serde(default)]
+    pub month_names: bool,
+    #[serde(default)]
+    pub confirm_overwrite: bool,
+    #[serde(default = "default_html_timeline")]
+    pub html_timeline: bool,
+    #[serde(default)]
+    pub solar_midnight: bool,
+    #[serde(default)]
+    pub light_bands: bool,
+    #[serde(default)]
+    pub zodiacal_light: bool,
+    #[serde(default)]
+    pub lunar_observing: bool,
+    #[serde(default)]
+    pub alignment_stars: bool,
+    #[serde(default)]
+    pub field_rotation: bool,
+    #[serde(default)]
+    pub weather_forecast: bool,
+    #[serde(default)]
+    pub sky_brightness_file: Option<String>,
+    #[serde(default)]
+    pub dem_directory: Option<String>,
+    #[serde(default)]
+    pub remember_date: bool,
+    #[serde(default)]
+    pub date_rollover: DateRollover,
+    #[serde(default = "default_rollover_hour")]
+    pub rollover_hour: u32,
+    #[serde(default)]
+    pub scripts: bool,
+    #[serde(default)]
+    pub dms_coordinates: bool,
+    #[serde(default)]
+    pub ui_scale: Option<f32>,
+    #[serde(default)]
+    pub satellites: bool,
+    #[serde(default)]
+    pub tle_file: Option<String>,
+    #[serde(default)]
+    pub mount_address: Option<String>,
+    #[serde(default)]
+    pub language: Language,
+    #[serde(default)]
+    pub units: Units,
+    #[serde(default)]
+    pub appearance: Option<Appearance>,
+    #[serde(default)]
+    pub window: Option<WindowGeometry>,
+    #[serde(default)]
+    pub night_vision: bool,
+    #[serde(default)]
+    pub open_tools: Vec<String>,
+    #[serde(default)]
+    pub calculation: CalculationSettings,
+    #[serde(default = "default_moon_avoidance")]
+    pub moon_avoidance: i64,
+}
+
+pub fn default_time_format() -> String {
+    "short".to_string()
+}
+
+pub fn default_html_timeline() -> bool {
+    true
+}
+
+pub fn default_rollover_hour() -> u32 {
+    12
+}
+
+pub fn default_moon_avoidance() -> i64 {
+    30
+}
+
+/// What happens to the selected date when a new observing night starts while tonight is shown
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateRollover {
+    /// Move to the new night
+    #[default]
+    Advance,
+    /// Ask before moving to the new night
+    Prompt,
+    /// Keep the selected date
+    Off,
+}
+
+/// Date rollovers offered in the preferences, in the order of the enum
+pub const DATE_ROLLOVERS: [DateRollover; 3] = [DateRollover::Advance, DateRollover::Prompt, DateRollover::Off];
+
+impl DateRollover {
+    /// Name shown in the preferences
+    pub fn name(&self) -> &'static str {
+        match self {
+            DateRollover::Advance => "Move to it",
+            DateRollover::Prompt => "Ask",
+            DateRollover::Off => "Keep the date",
+        }
+    }
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Preferences {
+            time_format: default_time_format(),
+            show_seconds: false,
+            hour12: false,
+            month_first: false,
+            month_names: false,
+            confirm_overwrite: false,
+            html_timeline: default_html_timeline(),
+            solar_midnight: false,
+            light_bands: false,
+            zodiacal_light: false,
+            lunar_observing: false,
+            alignment_stars: false,
+            field_rotation: false,
+            weather_forecast: false,
+            sky_brightness_file: None,
+            dem_directory: None,
+            remember_date: false,
+            date_rollover: DateRollover::default(),
+            rollover_hour: default_rollover_hour(),
+            scripts: false,
+            dms_coordinates: false,
+            ui_scale: None,
+            satellites: false,
+            tle_file: None,
+            mount_address: None,
+            language: Language::default(),
+            units: Units::default(),
+            appearance: None,
+            window: None,
+            night_vision: false,
+            open_tools: Vec::new(),
+            calculation: CalculationSettings::default(),
+            moon_avoidance: default_moon_avoidance(),
+        }
+    }
+}
+
+impl Preferences {
+    /// Formatter of the event times with the clock, date order and seconds preferences
+    pub fn time_formatter(&self) -> TimeFormatter {
+        TimeFormatter {
+            hour12: self.hour12,
+            month_first: self.month_first,
+            month_names: self.month_names,
+            seconds: self.show_seconds,
+        }
+    }
+}
@@ -0,0 +1,252 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Night quality score from the Moon free darkness and, when a forecast was available, the cloud
+// cover. Scores of the nights seen in the Darkness window are kept in a small per user history
+// file, so past nights keep the weather they were forecast with.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use crate::application::observer::Observer;
+use crate::application::recent::config_directory;
+use crate::application::reports::{calendar_nights, CalendarNight};
+use crate::application::time::Time;
+
+// Hours of Moon free darkness that make a perfect night
+pub const FULL_NIGHT_HOURS: f64 = 8.0;
+// Nights before and after today in the trends
+pub const TREND_DAYS: usize = 90;
+// Nights kept per site, the oldest are dropped first
+const MAX_NIGHTS: usize = 730;
+const HISTORY_FILE: &str = "quality_history.yaml";
+
+// Nights recorded from several threads read and write the history file one at a time
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+/// NightQuality struct
+///
+/// # Attributes
+///
+/// * `date` - Local date of the evening, YYYY-MM-DD
+/// * `darkness` - Astronomical darkness in hours
+/// * `moon_free` - Astronomical darkness with the Moon below the horizon in hours
+/// * `illumination` - Illuminated fraction of the Moon at local midnight
+/// * `cloud_cover` - Mean cloud cover of the night hours in percent, None without a forecast
+/// * `score` - Quality from 0 (no usable darkness) to 100
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NightQuality {
+    pub date: String,
+    pub darkness: f64,
+    pub moon_free: f64,
+    pub illumination: f64,
+    #[serde(default)]
+    pub cloud_cover: Option<f64>,
+    pub score: f64,
+}
+
+/// Quality from 0 to 100. Darkness with the Moon up counts for the dark part of the Moon's disk,
+/// a forecast scales the result by the clear fraction of the sky.
+pub fn quality_score(darkness: f64, moon_free: f64, illumination: f64, cloud_cover: Option<f64>) -> f64 {
+    let moonlit = (darkness - moon_free).max(0.0) * (1.0 - illumination.clamp(0.0, 1.0));
+    let clear = cloud_cover.map_or(1.0, |cloud_cover| 1.0 - (cloud_cover / 100.0).clamp(0.0, 1.0));
+    (100.0 * ((moon_free + moonlit) / FULL_NIGHT_HOURS).min(1.0) * clear).round()
+}
+
+impl NightQuality {
+    pub(crate) fn from_calendar(night: &CalendarNight, cloud_cover: Option<f64>) -> NightQuality {
+        NightQuality {
+            date: night.date.to_string(Some("yyyymmdd")),
+            darkness: night.darkness,
+            moon_free: night.moon_free,
+            illumination: night.illumination,
+            cloud_cover,
+            score: quality_score(night.darkness, night.moon_free, night.illumination, cloud_cover),
+        }
+    }
+
+    /// Quality of the night starting on the date of `time`
+    pub fn compute(observer: &Observer, time: &Time, cloud_cover: Option<f64>) -> NightQuality {
+        let night = &calendar_nights(observer, time.to_jd(), 1)[0];
+        NightQuality::from_calendar(night, cloud_cover)
+    }
+
+    pub fn description(&self) -> String {
+        let clouds = match self.cloud_cover {
+            Some(cloud_cover) => format!(", clouds {:.0}%", cloud_cover),
+            None => String::new(),
+        };
+        format!(
+            "{}: score {:.0}, {:.1} h dark, {:.1} h Moon free, Moon {:.0}%{}",
+            self.date, self.score, self.darkness, self.moon_free, self.illumination * 100.0, clouds
+        )
+    }
+}
+
+/// QualityHistory struct
+///
+/// # Attributes
+///
+/// * `sites` - Recorded nights by site, oldest first. Sites are keyed by their coordinates so
+///   renaming an observatory keeps its history.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct QualityHistory {
+    #[serde(default)]
+    pub sites: BTreeMap<String, Vec<NightQuality>>,
+}
+
+fn site_key(observer: &Observer) -> String {
+    format!("{:.4} {:.4}", observer.latitude, observer.longitude)
+}
+
+impl QualityHistory {
+    /// History file of the current user
+    pub fn path() -> PathBuf {
+        config_directory().join(HISTORY_FILE)
+    }
+
+    /// Reads the history, empty when the file does not exist or can not be read
+    pub fn load_from(path: &Path) -> QualityHistory {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_yaml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        std::fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn load() -> QualityHistory {
+        QualityHistory::load_from(&QualityHistory::path())
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.save_to(&QualityHistory::path())
+    }
+
+    /// Records a night, replacing an earlier record of the same date unless only that one had
+    /// a forecast
+    pub fn record(&mut self, observer: &Observer, night: NightQuality) {
+        let nights = self.sites.entry(site_key(observer)).or_default();
+        match nights.binary_search_by(|n| n.date.cmp(&night.date)) {
+            Ok(i) if nights[i].cloud_cover.is_some() && night.cloud_cover.is_none() => {}
+            Ok(i) => nights[i] = night,
+            Err(i) => nights.insert(i, night),
+        }
+        if nights.len() > MAX_NIGHTS {
+            nights.drain(..nights.len() - MAX_NIGHTS);
+        }
+    }
+
+    /// Records a night in the history file of the current user, from any thread. The file is read
+    /// and written again under a lock, so nights recorded meanwhile are kept.
+    pub fn record_night(observer: &Observer, night: NightQuality) -> Result<(), String> {
+        let _lock = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut history = QualityHistory::load();
+        history.record(observer, night);
+        history.save().map_err(|e| e.to_string())
+    }
+
+    pub fn night(&self, observer: &Observer, date: &str) -> Option<&NightQuality> {
+        let nights = self.sites.get(&site_key(observer))?;
+        nights.binary_search_by(|n| n.date.as_str().cmp(date)).ok().map(|i| &nights[i])
+    }
+}
+
+/// Nights from `days_before` nights before the date of `today` to `days_after` nights after it.
+/// Recorded nights are taken from the history, the others are computed without weather.
+pub fn quality_trend(observer: &Observer, history: &QualityHistory, today: &Time, days_before: usize,
+                     days_after: usize) -> Vec<NightQuality> {
+    calendar_nights(observer, today.to_jd() - days_before as f64, days_before + 1 + days_after)
+        .iter()
+        .map(|night| {
+            let computed = NightQuality::from_calendar(night, None);
+            history.night(observer, &computed.date).cloned().unwrap_or(computed)
+        })
+        .collect()
+}
+
+/// Index of the first night of the `length` consecutive nights with the best mean score, and that
+/// mean. None when there are fewer nights.
+pub fn best_stretch(nights: &[NightQuality], length: usize) -> Option<(usize, f64)> {
+    if length == 0 || nights.len() < length {
+        return None;
+    }
+    nights
+        .windows(length)
+        .map(|window| window.iter().map(|n| n.score).sum::<f64>() / length as f64)
+        .enumerate()
+        .fold(None, |best: Option<(usize, f64)>, (i, mean)| match best {
+            Some((_, best_mean)) if best_mean >= mean => best,
+            _ => Some((i, mean)),
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_quality_score() {
+        assert_eq!(quality_score(9.0, 9.0, 0.0, None), 100.0);
+        assert_eq!(quality_score(6.0, 2.0, 1.0, None), 25.0);
+        assert_eq!(quality_score(6.0, 2.0, 0.5, Some(50.0)), 25.0);
+        assert_eq!(quality_score(0.0, 0.0, 0.0, None), 0.0);
+    }
+
+    #[test]
+    fn test_quality_history() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let night = |date: &str, score: f64, cloud_cover: Option<f64>| NightQuality {
+            date: date.to_string(), darkness: 8.0, moon_free: 8.0, illumination: 0.0, cloud_cover, score,
+        };
+        let mut history = QualityHistory::default();
+        history.record(&observer, night("2025-03-29", 40.0, Some(60.0)));
+        history.record(&observer, night("2025-03-28", 90.0, None));
+        history.record(&observer, night("2025-03-29", 100.0, None));
+        assert_eq!(history.night(&observer, "2025-03-29").unwrap().score, 40.0);
+        assert_eq!(history.night(&observer, "2025-03-28").unwrap().score, 90.0);
+
+        let path = std::env::temp_dir().join("skycalc_test_quality").join(HISTORY_FILE);
+        history.save_to(&path).unwrap();
+        assert_eq!(QualityHistory::load_from(&path), history);
+        std::fs::remove_file(path).unwrap();
+
+        let today = Time::new(2025, 3, 29, 12, 0, 0);
+        let trend = quality_trend(&observer, &history, &today, 1, 2);
+        assert_eq!(trend.len(), 4);
+        assert_eq!(trend[0].date, "2025-03-28");
+        assert_eq!(trend[1].cloud_cover, Some(60.0));
+        assert!(trend[3].cloud_cover.is_none() && trend[3].darkness > 0.0);
+
+        let scores: Vec<NightQuality> = [10.0, 80.0, 90.0, 20.0].iter()
+            .map(|&score| night("", score, None)).collect();
+        assert_eq!(best_stretch(&scores, 2), Some((1, 85.0)));
+        assert_eq!(best_stretch(&scores, 5), None);
+    }
+}
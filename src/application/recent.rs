@@ -0,0 +1,132 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Recently used configuration files, kept outside the configurations in a small per user state
+// file so the list survives loading other configurations.
+
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+
+// Number of configurations listed under File/Configuration/Recent
+pub const MAX_RECENT: usize = 8;
+const RECENT_FILE: &str = "recent.yaml";
+
+/// RecentConfigs struct
+///
+/// # Attributes
+///
+/// * `files` - Paths of the configurations, most recent first
+/// * `auto_load` - Load the most recent configuration at startup
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct RecentConfigs {
+    #[serde(default)]
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub auto_load: bool,
+}
+
+// Per user configuration directory: %APPDATA%\skycalc on Windows, $XDG_CONFIG_HOME/skycalc
+// or ~/.config/skycalc elsewhere, the working directory when none is set
+pub fn config_directory() -> PathBuf {
+    let base = std::env::var_os("APPDATA")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+    match base {
+        Some(base) => base.join("skycalc"),
+        None => PathBuf::from("."),
+    }
+}
+
+impl RecentConfigs {
+    /// State file of the current user
+    pub fn path() -> PathBuf {
+        config_directory().join(RECENT_FILE)
+    }
+
+    /// Reads the list, empty when the file does not exist or can not be read
+    pub fn load_from(path: &Path) -> RecentConfigs {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_yaml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        std::fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn load() -> RecentConfigs {
+        RecentConfigs::load_from(&RecentConfigs::path())
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.save_to(&RecentConfigs::path())
+    }
+
+    /// Moves a configuration to the top of the list, the oldest entries are dropped
+    pub fn add(&mut self, file_path: &Path) {
+        let file_path = std::fs::canonicalize(file_path).unwrap_or_else(|_| file_path.to_path_buf());
+        let file_path = file_path.to_string_lossy().to_string();
+        self.files.retain(|file| *file != file_path);
+        self.files.insert(0, file_path);
+        self.files.truncate(MAX_RECENT);
+    }
+
+    pub fn remove(&mut self, file_path: &str) {
+        self.files.retain(|file| file != file_path);
+    }
+
+    /// Most recent configuration, None when the list is empty
+    pub fn last(&self) -> Option<&str> {
+        self.files.first().map(|file| file.as_str())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_recent_configs() {
+        let path = std::env::temp_dir().join("skycalc_test_recent").join(RECENT_FILE);
+        let mut recent = RecentConfigs::default();
+        for i in 0..MAX_RECENT + 2 {
+            recent.add(Path::new(&format!("/observatories/site{}.yaml", i)));
+        }
+        recent.add(Path::new("/observatories/site3.yaml"));
+        assert_eq!(recent.files.len(), MAX_RECENT);
+        assert_eq!(recent.last(), Some("/observatories/site3.yaml"));
+        assert_eq!(recent.files.iter().filter(|f| f.ends_with("site3.yaml")).count(), 1);
+
+        recent.auto_load = true;
+        recent.save_to(&path).unwrap();
+        assert_eq!(RecentConfigs::load_from(&path), recent);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(RecentConfigs::load_from(&path), RecentConfigs::default());
+    }
+}
@@ -20,20 +20,50 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
 // IN THE SOFTWARE.
 
-use std::fs::File;
+use std::fs::{read_to_string, File};
 use std::io::Write;
+use chrono::Duration;
 use crate::application::{
+    calculation::CalculationSettings,
     constraint::Constraints,
     darkness::{Darkness},
-    environment::Environment,
-    moon::Moon,
+    eclipses::{lunar_eclipses, solar_eclipses, LunarEclipseType},
+    environment::{night_dew_hour, night_dew_risk, DewHour, DewRisk, Environment},
+    equipment::{Equipment, FieldOfView, TargetFit},
+    formatting::{format_clock, format_diameter, format_duration, format_elongation, format_equation_of_time,
+                 format_local, format_local_clock, time_width, NightEvents},
+    light_pollution::sky_brightness_description,
+    lunar_features::features_near_terminator,
+    mosaic::{night_panel_windows, Mosaic, PanelWindow},
+    moon::{full_moons, is_supermoon, moon_altitude, moon_apsides, moon_colongitude, moon_illumination,
+           moon_topocentric_position, MOON_HORIZON},
+    observability::{filter_hours, night_filter_plan, night_observability, night_plan, up_tonight, FilterClass,
+                    FilterSegment, NightPlan, Observability},
     observer::Observer,
-    sun::RiseSetType::{Nearest, Next, Previous},
-    sun::Sun,
+    preferences::Preferences,
+    rise_set_solver::find_crossing,
+    satellites::{load_tles, visible_passes, MAX_PASS_MAGNITUDE},
+    scripting::Scripts,
+    solar_system::{body_events, body_position, ephemeris_times, night_window, planet_events, BodyEvents, PlanetEvent,
+                   BODIES},
+    stars::{alignment_stars, session_start, AlignmentStar, ALIGNMENT_STARS},
+    sun::{equation_of_time, season_events, sun_alt_az_grid_utc, sun_altitude, Sun, TwilightType},
+    sun::CrossingDirection::{Rising, Setting},
+    sun::RiseSetType::Next,
     sun::TwilightType::{AstronomicalTwilight, CivilTwilight, NauticalTwilight, RiseSet},
-    time::Time,
+    target::Target,
+    time::{gst_from_jd, LocalTime, Time},
+    timeline::{above_horizon, hour_label, hourly_conditions, AltitudeChart, HourConditions, NightTimeline,
+               MOON_UP_RGB},
+    transformations::{angular_separation, ecliptic_horizon_angle, J2000_OBLIQUITY},
+    weather::{night_forecast, ForecastProvider, HourlyForecast, OpenMeteo},
 };
+use crate::utils::angle::{format_dec_dms, format_ra_hms, split_sexagesimal};
 use crate::utils::definers::APP_VERSION;
+use crate::utils::i18n::{month_abbreviation, month_name, tr};
+use rayon::prelude::*;
+use rhai::Map;
+use serde_json::{json, Value};
 
 pub(crate) fn header_section() -> Vec<String> {
     let mut header: Vec<String> = Vec::new();
@@ -44,120 +74,2502 @@ pub(crate) fn header_section() -> Vec<String> {
     header
 }
 
-pub(crate) fn observer_section(observer: &Observer) -> Vec<String> {
+pub const DARKNESS_REPORT_FILE: &str = "skycalc.txt";
+pub const MOON_SEPARATION_REPORT_FILE: &str = "skycalc_moon_separation.csv";
+pub const NINA_SEQUENCE_FILE: &str = "skycalc_nina.json";
+pub const DARKNESS_CALENDAR_FILE: &str = "skycalc_darkness_calendar.csv";
+pub const DARKNESS_CALENDAR_IMAGE_FILE: &str = "skycalc_darkness_calendar.svg";
+pub const ALTITUDE_CHART_SVG_FILE: &str = "skycalc_altitude.svg";
+pub const ALTITUDE_CHART_PNG_FILE: &str = "skycalc_altitude.png";
+pub const ALMANAC_REPORT_FILE: &str = "skycalc_almanac.txt";
+pub const ALMANAC_CSV_FILE: &str = "skycalc_almanac.csv";
+pub const UP_TONIGHT_REPORT_FILE: &str = "skycalc_up_tonight.csv";
+pub const MOSAIC_REPORT_FILE: &str = "skycalc_mosaic.csv";
+pub const HOURLY_CONDITIONS_FILE: &str = "skycalc_hourly.csv";
+pub const SKYSAFARI_LIST_FILE: &str = "skycalc.skylist";
+pub const ASTROPLANNER_LIST_FILE: &str = "skycalc_astroplanner.txt";
+pub const ALIGNMENT_STARS_FILE: &str = "skycalc_alignment.csv";
+pub const SOLAR_SYSTEM_REPORT_FILE: &str = "skycalc_solar_system.txt";
+pub const SOLAR_SYSTEM_CSV_FILE: &str = "skycalc_solar_system.csv";
+pub const PLANET_EVENTS_REPORT_FILE: &str = "skycalc_planet_events.txt";
+
+const STAMP_PREFIX: &str = "SkyCalc export for night ";
+const STAMP_GENERATED: &str = " generated ";
+
+// Identifies an export: selected night, generation time in UTC and in the observer's local time
+pub fn export_stamp(observer: &Observer, night: &str, generated: &Time) -> String {
+    let offset = Duration::seconds((observer.timezone * 3600.0).round() as i64);
+    let local = Time::from_utc(generated.to_utc() + offset);
+    format!("{}{}{}{} UTC ({} local)", STAMP_PREFIX, night, STAMP_GENERATED, generated, local)
+}
+
+/// Night recorded in the stamp of an existing export, None if the file does not exist or has
+/// no stamp
+pub fn existing_export_night(file_path: &str) -> Option<String> {
+    let contents = read_to_string(file_path).ok()?;
+    let start = contents.find(STAMP_PREFIX)? + STAMP_PREFIX.len();
+    let length = contents[start..].find(STAMP_GENERATED)?;
+    Some(contents[start..start + length].to_string())
+}
+
+/// Night description used in the stamp of a darkness report or a N.I.N.A. sequence
+pub fn export_night(time: &Time) -> String {
+    time.to_string(Some("yyyymmdd"))
+}
+
+/// Month description used in the stamp of an almanac export
+pub fn export_month(year: i64, month: u64) -> String {
+    format!("{:04}-{:02}", year, month)
+}
+
+/// Night description used in the stamp of a Moon separation export
+pub fn export_night_range(start: &Time, end: &Time) -> String {
+    format!("{} to {}", start.to_string(Some("yyyymmdd")), end.to_string(Some("yyyymmdd")))
+}
+
+pub(crate) fn stamp_section(observer: &Observer, time: &Time, generated: &Time) -> Vec<String> {
+    vec![export_stamp(observer, &export_night(time), generated), "\n\n".to_string()]
+}
+
+pub(crate) fn observer_section(observer: &Observer, preferences: &Preferences) -> Vec<String> {
     let mut obs: Vec<String> = Vec::new();
-    obs.push("Observatory:".to_string());
+    obs.push(format!("{}:", tr("Observatory")));
     obs.push("\n   - ".to_string());
-    obs.push(observer.to_string_decimal());
+    obs.push(if preferences.dms_coordinates {
+        observer.to_string_dms()
+    } else {
+        observer.to_string_decimal()
+    });
     obs
 }
 
+pub(crate) fn sky_brightness_section(observer: &Observer, preferences: &Preferences) -> Vec<String> {
+    let mut sky: Vec<String> = Vec::new();
+    if let Some(file_path) = &preferences.sky_brightness_file {
+        sky.push(format!("\n   - {}: ", tr("Sky brightness")));
+        sky.push(sky_brightness_description(file_path, observer.latitude, observer.longitude));
+    }
+    sky
+}
+
 pub(crate) fn environment_section(environment: &Environment) -> Vec<String> {
-    let mut env: Vec<String> = Vec::new();
-    env.push("\n   - ".to_string());
-    env.push(environment.to_string());
-    env.push("\n\n".to_string());
-    env
+    vec!["\n   - ".to_string(), environment.to_string(), "\n\n".to_string()]
 }
 
 pub(crate) fn night_section(time: &Time) -> Vec<String> {
     let start = time;
     let end = Time::from_jd(start.to_jd() + 1.0);
     let mut night: Vec<String> = Vec::new();
-    night.push(format!("Info for night:  {:10} to {:10} in local time", start.to_string(Some("yyyymmdd")), end.to_string(Some("yyyymmdd"))));
+    night.push(format!("{}:  {:10} {} {:10} {}", tr("Info for night"), start.to_string(Some("yyyymmdd")), tr("to"),
+                       end.to_string(Some("yyyymmdd")), tr("in local time")));
     night.push("\n\n".to_string());
     night
 }
 
-pub(crate) fn moon_section(observer: &Observer, time: &Time, environment: &Environment) -> Vec<String> {
-    let moon = Moon::new(&observer, &time, &environment);
-    let moonrise = moon.get_moonrise_local_str(Next, Some("short"));
-    let moonset = moon.get_moonset_local_str(Next, Some("short"));
+pub(crate) fn moon_section(events: &NightEvents, preferences: &Preferences) -> Vec<String> {
+    let width = time_width(preferences);
     let mut moon_vec: Vec<String> = Vec::new();
-    moon_vec.push("Moon:".to_string());
-    moon_vec.push(format!("\n   - Rise                    : {:11}   Set   : {:11}   ", moonrise, moonset));
+    moon_vec.push(format!("{}:", tr("Moon")));
+    moon_vec.push(format!("\n   - {:<24}: {:width$}   {:<6}: {:width$}   ", tr("Rise"), events.moonrise, tr("Set"), events.moonset));
+    moon_vec.push(format!("\n   - {:<24}: {:width$}   {:<6}: {:width$}   ", tr("Rise azimuth"), events.moonrise_direction, tr("Set"),
+                          events.moonset_direction));
+    moon_vec.push(format!("\n   - {:<24}: {:width$}   {:<6}: {:width$}   ", tr("Transit"), events.moon_transit, tr("Alt"),
+                          events.moon_max_altitude));
+    moon_vec.push(format!("\n   - {:<24}: {}   {} : {}", tr("Libration"), events.moon_libration, tr("Axis PA"), events.moon_axis));
+    if let Some(circumpolar) = &events.moon_circumpolar {
+        moon_vec.push(format!("\n   - {}", circumpolar));
+    }
     moon_vec.push("\n\n".to_string());
     moon_vec
 }
 
-pub(crate) fn sun_section(observer: &Observer, time: &Time, environment: &Environment) -> Vec<String> {
-    let sun = Sun::new(&observer, &time, &environment);
-    let sunrise = sun.get_sunrise_local_str(Next, RiseSet, Some("short"));
-    let sunset = sun.get_sunset_local_str(Next, RiseSet, Some("short"));
-    let civil_tw_start = sun.get_sunrise_local_str(Next, CivilTwilight, Some("short"));
-    let civil_tw_end = sun.get_sunset_local_str(Next, CivilTwilight, Some("short"));
-    let nautical_tw_start = sun.get_sunrise_local_str(Next, NauticalTwilight, Some("short"));
-    let nautical_tw_end = sun.get_sunset_local_str(Next, NauticalTwilight, Some("short"));
-    let astronomical_tw_start = sun.get_sunrise_local_str(Next, AstronomicalTwilight, Some("short"));
-    let astronomical_tw_end = sun.get_sunset_local_str(Next, AstronomicalTwilight, Some("short"));
+pub(crate) fn sun_section(events: &NightEvents, preferences: &Preferences) -> Vec<String> {
+    let width = time_width(preferences);
     let mut sun_vec: Vec<String> = Vec::new();
-    sun_vec.push("Sun:".to_string());
-    sun_vec.push(format!("\n   - Set                     : {:11}   Rise  : {:11}   ", sunset, sunrise));
-    sun_vec.push(format!("\n   - Civil Tw end            : {:11}   start : {:11}   ", civil_tw_end, civil_tw_start));
-    sun_vec.push(format!("\n   - Nautical Tw end         : {:11}   start : {:11}   ", nautical_tw_end, nautical_tw_start));
-    sun_vec.push(format!("\n   - Astronomical Tw end     : {:11}   start : {:11}   ", astronomical_tw_end, astronomical_tw_start));
+    sun_vec.push(format!("{}:", tr("Sun")));
+    sun_vec.push(format!("\n   - {:<24}: {:width$}   {:<6}: {:width$}   ", tr("Set"), events.sunset, tr("Rise"), events.sunrise));
+    sun_vec.push(format!("\n   - {:<24}: {:width$}   {:<6}: {:width$}   ", tr("Set azimuth"), events.sunset_direction, tr("Rise"),
+                         events.sunrise_direction));
+    for (end_label, end, start) in [
+        ("Civil Tw end", &events.civil_end, &events.civil_start),
+        ("Nautical Tw end", &events.nautical_end, &events.nautical_start),
+        ("Astronomical Tw end", &events.astronomical_end, &events.astronomical_start),
+    ] {
+        sun_vec.push(format!("\n   - {:<24}: {:width$}   {:<6}: {:width$}   ", tr(end_label), end, tr("start"), start));
+    }
+    sun_vec.push(format!("\n   - {:<24}: {:width$}   {:<6}: {:width$}   ", tr("Transit"), events.sun_transit, tr("Alt"),
+                         events.sun_max_altitude));
+    if let Some(circumpolar) = &events.sun_circumpolar {
+        sun_vec.push(format!("\n   - {}", circumpolar));
+    }
     sun_vec.push("\n\n".to_string());
     sun_vec
 }
 
-pub(crate) fn darkness_section(observer: &Observer, time: &Time, environment: &Environment) -> Vec<String> {
-    let darkness = Darkness::new(&observer, &time, &environment);
-    let sun = Sun::new(&observer, &time, &environment);
-    let astronomical_dso_start = darkness.get_darkness_local_astronomical_start_str(Some("short"));
-    let astronomical_dso_end = darkness.get_darkness_local_astronomical_end_str(Some("short"));
-    let nautical_dso_start = darkness.get_darkness_local_nautical_start_str(Some("short"));
-    let nautical_dso_end = darkness.get_darkness_local_nautical_end_str(Some("short"));
-    let astronomical_nb_start = sun.get_sunset_local_str(Next, AstronomicalTwilight, Some("short"));
-    let astronomical_nb_end = sun.get_sunrise_local_str(Next, AstronomicalTwilight, Some("short"));
-    let nautical_nb_start = sun.get_sunset_local_str(Next, NauticalTwilight, Some("short"));
-    let nautical_nb_end = sun.get_sunrise_local_str(Next, NauticalTwilight, Some("short"));
+// Golden and blue hours of the evening and the morning, in the order they happen
+fn light_band_rows(events: &NightEvents) -> [(&'static str, &str, &str); 4] {
+    [
+        ("Golden hour evening", &events.golden_evening_start, &events.blue_evening_start),
+        ("Blue hour evening", &events.blue_evening_start, &events.civil_end),
+        ("Blue hour morning", &events.civil_start, &events.blue_morning_end),
+        ("Golden hour morning", &events.blue_morning_end, &events.golden_morning_end),
+    ]
+}
+
+pub(crate) fn light_bands_section(events: &NightEvents, preferences: &Preferences) -> Vec<String> {
+    let width = time_width(preferences);
+    let mut bands: Vec<String> = Vec::new();
+    bands.push(format!("{}:", tr("Golden and blue hours")));
+    for (label, start, end) in light_band_rows(events) {
+        bands.push(format!("\n   - {:<24}: {:width$}   {:<6}: {:width$}", tr(label), start, tr("end"), end));
+    }
+    bands.push("\n\n".to_string());
+    bands
+}
+
+// Zodiacal light windows of the night, Sun 18° to 25° below the horizon, with the angle of the
+// ecliptic to the horizon in the middle of each window: the steeper the ecliptic, the higher the
+// light cone rises above the horizon haze
+pub(crate) fn zodiacal_light_section(observer: &Observer, time: &Time, environment: &Environment,
+                                     preferences: &Preferences) -> Vec<String> {
+    let width = time_width(preferences);
+    let sun = Sun::new(observer, time, environment).with_settings(preferences.calculation);
+    let moon_up = moon_altitude(observer.latitude, observer.longitude, observer.elevation as f64);
+    let mut section: Vec<String> = Vec::new();
+    section.push(format!("{}:", tr("Zodiacal light")));
+    for (label, direction) in [("Evening", Setting), ("Morning", Rising)] {
+        let Some((start, end)) = sun.zodiacal_light_window_utc(direction) else {
+            section.push(format!("\n   - {:<24}: {}", tr(label), tr("no astronomical darkness")));
+            continue;
+        };
+        let middle = (start + end) / 2.0;
+        let angle = ecliptic_horizon_angle(observer.latitude, gst_from_jd(middle) + observer.longitude,
+                                           J2000_OBLIQUITY);
+        let quality = if angle >= 60.0 {
+            tr("favorable")
+        } else if angle >= 40.0 {
+            tr("fair")
+        } else {
+            tr("poor")
+        };
+        let moon = if moon_up(middle) > MOON_HORIZON { format!(", {}", tr("Moon up")) } else { String::new() };
+        section.push(format!("\n   - {:<24}: {:width$}   {:<6}: {:width$}   {} {:.0}°, {}{}", tr(label),
+                             format_local(observer.local_time(start), preferences), tr("end"),
+                             format_local(observer.local_time(end), preferences), tr("ecliptic"), angle,
+                             quality, moon));
+    }
+    section.push("\n\n".to_string());
+    section
+}
+
+// Colongitude and illumination of the Moon at local midnight with the named features near the
+// terminator, where the low Sun shows the relief best
+pub(crate) fn lunar_observing_section(observer: &Observer, time: &Time, preferences: &Preferences) -> Vec<String> {
+    let offset = observer.timezone / 24.0;
+    // local midnight of the night that starts at the selected date
+    let midnight = (time.to_jd() + 0.5).floor() + 0.5 - offset;
+    let (colongitude, subsolar_latitude) = moon_colongitude(midnight);
+
+    let mut lunar: Vec<String> = Vec::new();
+    lunar.push(format!("{} ({}):", tr("Lunar observing at local midnight"),
+                       format_local(observer.local_time(midnight), preferences)));
+    lunar.push(format!("\n   - {:<24}: {:5.1}°   {:<13}: {:3.0}%   {:<18}: {:4.1}°", tr("Colongitude"), colongitude,
+                       tr("Illumination"), moon_illumination(midnight) * 100.0, tr("Subsolar latitude"),
+                       subsolar_latitude));
+    let features = features_near_terminator(midnight);
+    if features.is_empty() {
+        lunar.push(format!("\n   - {}", tr("No listed features near the terminator")));
+    }
+    for f in features {
+        lunar.push(format!("\n   - {:<24}: {:<8} {} {:4.1}°   {:5.1}° {}   {:5.1}° {}", f.feature.name,
+                           tr(f.terminator.description()), tr("Sun"), f.sun_altitude, f.feature.latitude.abs(),
+                           if f.feature.latitude >= 0.0 { "N" } else { "S" }, f.feature.longitude.abs(),
+                           if f.feature.longitude >= 0.0 { "E" } else { "W" }));
+    }
+    lunar.push("\n\n".to_string());
+    lunar
+}
+
+pub(crate) fn darkness_section(events: &NightEvents, preferences: &Preferences) -> Vec<String> {
+    let width = time_width(preferences);
     let mut dark: Vec<String> = Vec::new();
-    dark.push("Darkness:".to_string());
-    dark.push(format!("\n   - DSO Astronomical   start: {:11}   end   : {:11}", astronomical_dso_start, astronomical_dso_end));
-    dark.push(format!("\n   - DSO Nautical       start: {:11}   end   : {:11}", nautical_dso_start, nautical_dso_end));
+    let line = |kind: &str, twilight: &'static str, start: &str, end: &str| {
+        format!("\n   - {:<4}{:<15}{:<5}: {:width$}   {:<6}: {:width$}", kind, tr(twilight), tr("start"), start, tr("end"), end)
+    };
+    dark.push(format!("{}:", tr("Darkness")));
+    dark.push(line("DSO", "Astronomical", &events.dso_astronomical_start, &events.dso_astronomical_end));
+    dark.push(line("DSO", "Nautical", &events.dso_nautical_start, &events.dso_nautical_end));
     // TODO Ignore moon in calculations for narrow band
-    dark.push(format!("\n"));
-    dark.push(format!("\n   - NB  Astronomical   start: {:11}   end   : {:11}", astronomical_nb_start, astronomical_nb_end));
-    dark.push(format!("\n   - NB  Nautical       start: {:11}   end   : {:11}", nautical_nb_start, nautical_nb_end));
+    dark.push("\n".to_string());
+    dark.push(line("NB", "Astronomical", &events.astronomical_end, &events.astronomical_start));
+    dark.push(line("NB", "Nautical", &events.nautical_end, &events.nautical_start));
     dark
 }
 
-pub fn darkness_report(observer: &Observer, time: &Time, environment: &Environment) {
+pub(crate) fn durations_section(events: &NightEvents) -> Vec<String> {
+    let mut durations: Vec<String> = Vec::new();
+    durations.push(format!("{} (hh:mm):", tr("Durations")));
+    durations.push(format!("\n   - {:<24}: {:11}   {:<22}: {}", tr("Night"), events.night_length,
+                           tr("Astronomical darkness"), events.astronomical_darkness));
+    durations.push(format!("\n   - {:<24}: {:11}   {:<22}: {}", tr("Civil twilight"), events.civil_twilight,
+                           tr("Moon free darkness"), events.moon_free_darkness));
+    durations.push(format!("\n   - {:<24}: {}", tr("Nautical twilight"), events.nautical_twilight));
+    durations.push(format!("\n   - {:<24}: {}", tr("Astronomical twilight"), events.astronomical_twilight));
+    durations.push("\n\n".to_string());
+    durations
+}
+
+pub(crate) fn weather_section(provider: &str, forecast: &Result<Vec<HourlyForecast>, String>,
+                              observer: &Observer, preferences: &Preferences) -> Vec<String> {
+    let width = time_width(preferences);
+    let mut weather: Vec<String> = Vec::new();
+    weather.push(format!("{} ({}):", tr("Weather"), provider));
+    match forecast {
+        Ok(hours) if hours.is_empty() => weather.push(format!("\n   - {}", tr("No forecast for this night"))),
+        Ok(hours) => {
+            for hour in hours {
+                weather.push(format!("\n   - {:width$}   {}: {:3.0}%",
+                                     format_local(observer.local_time(hour.jd), preferences), tr("clouds"),
+                                     hour.cloud_cover));
+            }
+        }
+        Err(e) => weather.push(format!("\n   - {}: {}", tr("Forecast unavailable"), e)),
+    }
+    weather.push("\n\n".to_string());
+    weather
+}
+
+// Local sidereal time in hours as hh:mm
+fn format_lst(lst: f64) -> String {
+    let minutes = (lst * 60.0).round() as i64 % (24 * 60);
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+// Sun, Moon, sky band, sidereal time and dew risk on each whole hour of the night, the dew risk of
+// the hours in `dew`
+pub(crate) fn hourly_section(observer: &Observer, hours: &[HourConditions], dew: &[DewHour],
+                             preferences: &Preferences) -> Vec<String> {
+    let width = time_width(preferences);
+    let unit = preferences.units.temperature;
+    let mut section: Vec<String> = Vec::new();
+    section.push(format!("{}:", tr("Hour by hour")));
+    if hours.is_empty() {
+        section.push(format!("\n   - {}", tr("The Sun does not set this night")));
+    } else {
+        section.push(format!("\n     {:width$}   {:>6}   {:>6}   {:>6}   {:<12}   {:<5}   {}", "", tr("Sun"),
+                             tr("Moon"), tr("Illum."), tr("Sky"), tr("LST"), tr("Dew")));
+    }
+    for hour in hours {
+        let dew_risk = dew.iter().find(|dew| dew.jd == hour.jd).map_or(String::new(), |dew| {
+            format!("{:4.1}{} {}", unit.difference_from_celsius(dew.temperature - dew.dew_point), unit.symbol(),
+                    tr(dew.risk.name()))
+        });
+        section.push(format!("\n   - {:width$}   {:5.1}°   {:5.1}°   {:5.0}%   {:<12}   {}   {}",
+                             format_local(observer.local_time(hour.jd), preferences), hour.sun_altitude,
+                             hour.moon_altitude, hour.moon_illumination * 100.0, tr(hour.sky.name()),
+                             format_lst(hour.lst), dew_risk));
+    }
+    section.push("\n\n".to_string());
+    section
+}
+
+// Sunset and sunrise (JD UTC) of the night, None when the Sun does not set or rise
+fn night_sun_times(observer: &Observer, time: &Time, environment: &Environment,
+                   preferences: &Preferences) -> Option<(f64, f64)> {
+    let sun = Sun::new(observer, time, environment).with_settings(preferences.calculation);
+    Some((sun.get_sunset_utc(Next, RiseSet).jd()?, sun.get_sunrise_utc(Next, RiseSet).jd()?))
+}
+
+// Hours of the forecast of the night, none when it was not requested or is unavailable
+fn forecast_hours(forecast: &Option<Result<Vec<HourlyForecast>, String>>) -> &[HourlyForecast] {
+    match forecast {
+        Some(Ok(hours)) => hours,
+        _ => &[],
+    }
+}
+
+// Dew risk on the hours of the hour by hour section from the forecast hours, estimated on the
+// hours without one, none when the Sun does not set
+pub(crate) fn hourly_dew(observer: &Observer, time: &Time, environment: &Environment, hours: &[HourConditions],
+                         forecast: &[HourlyForecast], preferences: &Preferences) -> Vec<DewHour> {
+    match night_sun_times(observer, time, environment, preferences) {
+        Some((sunset, _)) => hours
+            .iter()
+            .map(|hour| night_dew_hour(environment, sunset, hour.jd, forecast))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+// Dew point and where it comes from: the range of the forecast when every hour has one, the dew
+// point of the environment as a rough estimate otherwise
+fn dew_point_line(environment: &Environment, hours: &[DewHour], preferences: &Preferences) -> String {
+    let unit = preferences.units.temperature;
+    if !hours.is_empty() && hours.iter().all(|hour| hour.forecast) {
+        let low = hours.iter().map(|hour| hour.dew_point).fold(f64::INFINITY, f64::min);
+        let high = hours.iter().map(|hour| hour.dew_point).fold(f64::NEG_INFINITY, f64::max);
+        return format!("{:.1}{} - {:.1}{}   {}", unit.celsius_to_unit(low), unit.symbol(),
+                       unit.celsius_to_unit(high), unit.symbol(), tr("forecast temperature and humidity"));
+    }
+    let mut line = format!("{:.1}{}   {}: {} {}{}, {} {}%, {}", unit.celsius_to_unit(environment.dew_point()),
+                           unit.symbol(), tr("rough estimate"), tr("temperature at sunset"),
+                           unit.format(environment.temperature), unit.symbol(), tr("humidity"),
+                           environment.humidity, tr("clear sky cooling"));
+    if hours.iter().any(|hour| hour.forecast) {
+        line.push_str(&format!(", {}", tr("forecast on the other hours")));
+    }
+    line
+}
+
+// First hour dew heaters are needed on, "not needed" when the risk stays low
+fn dew_heaters_line(observer: &Observer, hours: &[DewHour], preferences: &Preferences) -> String {
+    hours
+        .iter()
+        .find(|hour| hour.risk != DewRisk::Low)
+        .map_or(tr("not needed").to_string(), |hour| format_local(observer.local_time(hour.jd), preferences))
+}
+
+// Dew point of the environment and the hour dew heaters are needed from, the dew risk of each hour
+// is a column of the hour by hour section
+pub(crate) fn dew_section(observer: &Observer, time: &Time, environment: &Environment, forecast: &[HourlyForecast],
+                          preferences: &Preferences) -> Vec<String> {
+    let mut dew: Vec<String> = Vec::new();
+    dew.push(format!("{}:", tr("Dew risk")));
+    match night_sun_times(observer, time, environment, preferences) {
+        Some((sunset, sunrise)) => {
+            let hours = night_dew_risk(environment, sunset, sunrise, forecast);
+            dew.push(format!("\n   - {:<24}: {}", tr("Dew point"),
+                             dew_point_line(environment, &hours, preferences)));
+            dew.push(format!("\n   - {:<24}: {}", tr("Dew heaters from"),
+                             dew_heaters_line(observer, &hours, preferences)));
+        }
+        None => {
+            dew.push(format!("\n   - {:<24}: {}", tr("Dew point"), dew_point_line(environment, &[], preferences)));
+            dew.push(format!("\n   - {}", tr("No sunset and sunrise this night")));
+        }
+    }
+    dew.push("\n\n".to_string());
+    dew
+}
+
+pub(crate) fn eclipse_section(observer: &Observer, time: &Time,
+                              preferences: &Preferences) -> Vec<String> {
+    let width = time_width(preferences);
+    let offset = observer.timezone / 24.0;
+    let local = |jd: f64| format_local(observer.local_time(jd), preferences);
+    let local_opt = |jd: Option<f64>| jd.map(local).unwrap_or("-".to_string());
+    // night goes from local noon to next local noon
+    let night_start = (time.to_jd() + 0.5).floor() - offset;
+    let night_end = night_start + 1.0;
+
+    let mut eclipse: Vec<String> = Vec::new();
+    for lunar in lunar_eclipses(observer, night_start, night_end) {
+        let magnitude = match lunar.eclipse_type {
+            LunarEclipseType::Penumbral => lunar.penumbral_magnitude,
+            _ => lunar.umbral_magnitude,
+        };
+        let below = if lunar.visible { String::new() } else { format!(", {}", tr("Moon below horizon")) };
+        eclipse.push(format!("\n   - {} {} ({} {:.3}){}", tr("WARNING"), tr(lunar.eclipse_type.description()),
+                             tr("magnitude"), magnitude, below));
+        eclipse.push(format!("\n     P1 {:width$}   U1 {:width$}   U2 {:width$}   {} {:width$}",
+                             local(lunar.p1), local_opt(lunar.u1), local_opt(lunar.u2),
+                             tr("Max"), local(lunar.maximum)));
+        eclipse.push(format!("\n     U3 {:width$}   U4 {:width$}   P4 {:width$}",
+                             local_opt(lunar.u3), local_opt(lunar.u4), local(lunar.p4)));
+    }
+    for solar in solar_eclipses(observer, night_start, night_end) {
+        eclipse.push(format!("\n   - {} {} ({} {:.3})", tr("WARNING"), tr(solar.eclipse_type.description()),
+                             tr("local magnitude"), solar.magnitude));
+        eclipse.push(format!("\n     {} {:width$}   {} {:width$}   {} {:width$}",
+                             tr("Start"), local(solar.first_contact), tr("Max"), local(solar.maximum),
+                             tr("End"), local(solar.last_contact)));
+    }
+
+    if eclipse.is_empty() {
+        return eclipse;
+    }
+    eclipse.insert(0, format!("{}:", tr("Eclipses")));
+    eclipse.push("\n\n".to_string());
+    eclipse
+}
+
+// Observation window hours against the hours planned for each target and the idle gaps, in
+// local time
+pub(crate) fn plan_section(observer: &Observer, plan: &NightPlan, targets: &[Target],
+                           preferences: &Preferences) -> Vec<String> {
+    let span = |start: f64, end: f64| format!("{} {} {}", format_local(observer.local_time(start), preferences),
+                                              tr("to"), format_local(observer.local_time(end), preferences));
+    let available = plan.available_hours();
+    let percent = |hours: f64| if available > 0.0 { hours / available * 100.0 } else { 0.0 };
+    let idle = plan.idle_hours();
+
+    let mut budget: Vec<String> = Vec::new();
+    budget.push(format!("{} (hh:mm):", tr("Target plan")));
+    budget.push(format!("\n   - {:<24}: {:11}   {}", tr("Available"),
+                        format_duration(Some(available)), span(plan.window.0, plan.window.1)));
+    budget.push(format!("\n   - {:<24}: {:11}   {:.0}%", tr("Allocated"),
+                        format_duration(Some(available - idle)), percent(available - idle)));
+    budget.push(format!("\n   - {:<24}: {:11}   {:.0}%", tr("Idle"),
+                        format_duration(Some(idle)), percent(idle)));
+    for (i, target) in targets.iter().enumerate() {
+        let blocks: Vec<String> = plan
+            .blocks
+            .iter()
+            .filter(|b| b.target == i)
+            .map(|b| span(b.start, b.end))
+            .collect();
+        let when = if blocks.is_empty() { tr("not within the constraints").to_string() } else { blocks.join(", ") };
+        budget.push(format!("\n   - {:24}: {:11}   {}", target.name, format_duration(Some(plan.allocated_hours(i))),
+                            when));
+    }
+    for &(start, end) in &plan.idle {
+        budget.push(format!("\n   - {:<24}: {:11}   {}", tr("Idle gap"),
+                            format_duration(Some((end - start) * 24.0)), span(start, end)));
+    }
+    budget.push("\n\n".to_string());
+    budget
+}
+
+// Go-to alignment stars at nautical dusk, spread across azimuths
+pub(crate) fn alignment_section(observer: &Observer, time: &Time, environment: &Environment,
+                                preferences: &Preferences) -> Vec<String> {
+    let mut alignment: Vec<String> = Vec::new();
+    let Some(jd) = session_start(observer, time, environment, preferences.calculation) else {
+        alignment.push(format!("{}:", tr("Alignment stars")));
+        alignment.push(format!("\n   - {}", tr("No nautical dusk this night")));
+        alignment.push("\n\n".to_string());
+        return alignment;
+    };
+    alignment.push(format!("{} ({}):", tr("Alignment stars at nautical dusk"),
+                           format_local(observer.local_time(jd), preferences)));
+    for a in alignment_stars(observer, jd, ALIGNMENT_STARS) {
+        alignment.push(format!("\n   - {:20} {:12} {} {:5.2}   {} {:4.1}°   {} {:5.1}°", a.star.name,
+                               a.star.designation, tr("mag"), a.star.magnitude, tr("alt"), a.altitude, tr("az"),
+                               a.azimuth));
+    }
+    alignment.push("\n\n".to_string());
+    alignment
+}
+
+// Visible passes of the bright satellites during the night, in local time
+pub(crate) fn satellite_section(observer: &Observer, time: &Time, preferences: &Preferences) -> Vec<String> {
+    let width = time_width(preferences);
+    let offset = observer.timezone / 24.0;
+    let local = |jd: f64| format_local(observer.local_time(jd), preferences);
+    // night goes from local noon to next local noon
+    let night_start = (time.to_jd() + 0.5).floor() - offset;
+    let night_end = night_start + 1.0;
+
+    let mut satellites: Vec<String> = Vec::new();
+    satellites.push(format!("{} ({} {:.0}):", tr("Satellite passes"), tr("brighter than magnitude"),
+                            MAX_PASS_MAGNITUDE));
+    match load_tles(preferences.tle_file.as_deref()) {
+        Ok(tles) => {
+            let passes = visible_passes(&tles, observer, night_start, night_end, MAX_PASS_MAGNITUDE);
+            if passes.is_empty() {
+                satellites.push(format!("\n   - {}", tr("No visible passes this night")));
+            }
+            for pass in passes {
+                let (visible_start, visible_end) = pass.visible.unwrap_or((pass.rise, pass.set));
+                satellites.push(format!(
+                    "\n   - {:24} {} {:width$} {} {:width$}   {} {:2.0}° {:width$}   {} {:3.0}° {} {:3.0}°   {} {:4.1}",
+                    pass.name,
+                    tr("visible"),
+                    local(visible_start),
+                    tr("to"),
+                    local(visible_end),
+                    tr("max"),
+                    pass.max_altitude,
+                    local(pass.culmination),
+                    tr("az"),
+                    pass.rise_azimuth,
+                    tr("to"),
+                    pass.set_azimuth,
+                    tr("mag"),
+                    pass.magnitude.unwrap_or(f64::NAN)
+                ));
+            }
+        }
+        Err(e) => satellites.push(format!("\n   - {}: {}", tr("Elements unavailable"), e)),
+    }
+    satellites.push("\n\n".to_string());
+    satellites
+}
+
+// Script variables describing the night: date, latitude, longitude, night_length, darkness and
+// moon_free (hours) and moon_illumination (%) at local midnight
+pub(crate) fn night_variables(observer: &Observer, time: &Time, environment: &Environment,
+                              settings: CalculationSettings) -> Map {
+    let sun = Sun::new(observer, time, environment).with_settings(settings);
+    let darkness = Darkness::new(observer, time, environment).with_settings(settings);
+    let midnight = (time.to_jd() + 0.5).floor() + 0.5 - observer.timezone / 24.0;
+    let mut night = Map::new();
+    night.insert("date".into(), export_night(time).into());
+    night.insert("latitude".into(), observer.latitude.into());
+    night.insert("longitude".into(), observer.longitude.into());
+    night.insert("night_length".into(), sun.night_duration(RiseSet).unwrap_or(0.0).into());
+    night.insert("darkness".into(), sun.night_duration(AstronomicalTwilight).unwrap_or(0.0).into());
+    night.insert("moon_free".into(), darkness.moon_free_duration(AstronomicalTwilight).into());
+    night.insert("moon_illumination".into(), (moon_illumination(midnight) * 100.0).into());
+    night
+}
+
+// Lines of the user scripts, empty when scripts are disabled or none defines a report line
+pub(crate) fn custom_section(observer: &Observer, time: &Time, environment: &Environment,
+                             preferences: &Preferences) -> Vec<String> {
+    let mut custom: Vec<String> = Vec::new();
+    if !preferences.scripts {
+        return custom;
+    }
+    let (scripts, errors) = Scripts::load();
+    let night = night_variables(observer, time, environment, preferences.calculation);
+    for line in scripts.report_lines(&night).iter().chain(errors.iter()) {
+        custom.push(format!("\n   - {}", line));
+    }
+    if custom.is_empty() {
+        return custom;
+    }
+    custom.insert(0, format!("{}:", tr("Custom")));
+    custom.push("\n\n".to_string());
+    custom
+}
+
+pub fn darkness_report(observer: &Observer, time: &Time, environment: &Environment,
+                       preferences: &Preferences) {
+    write_darkness_report(observer, time, environment, preferences, None);
+}
+
+/// Darkness report with the darkness budget of the target plan of the night
+pub fn darkness_plan_report(observer: &Observer, time: &Time, environment: &Environment,
+                            preferences: &Preferences, plan: &NightPlan, targets: &[Target]) {
+    write_darkness_report(observer, time, environment, preferences, Some((plan, targets)));
+}
+
+fn write_darkness_report(observer: &Observer, time: &Time, environment: &Environment,
+                         preferences: &Preferences, plan: Option<(&NightPlan, &[Target])>) {
+    let started = std::time::Instant::now();
+    let events = NightEvents::new(observer, time, environment, preferences);
+
     // Header
     let header_lines = header_section();
     let mut lines = header_lines.join("");
 
+    // Export stamp
+    let stamp_lines = stamp_section(observer, time, &Time::now());
+    lines += &*stamp_lines.join("");
+
     // Observer
-    let observer_lines = observer_section(&observer);
-    lines = lines + &*observer_lines.join("");
+    let observer_lines = observer_section(observer, preferences);
+    lines += &*observer_lines.join("");
+
+    // Sky brightness
+    let sky_lines = sky_brightness_section(observer, preferences);
+    lines += &*sky_lines.join("");
 
     // Environment
-    let environment_lines = environment_section(&environment);
-    lines = lines + &*environment_lines.join("");
+    let environment_lines = environment_section(environment);
+    lines += &*environment_lines.join("");
 
     // Night
-    let night_lines = night_section(&time);
-    lines = lines + &*night_lines.join("");
+    let night_lines = night_section(time);
+    lines += &*night_lines.join("");
 
     // Sun
-    let sun_lines = sun_section(&observer, &time, &environment);
-    lines = lines + &*sun_lines.join("");
+    let sun_lines = sun_section(&events, preferences);
+    lines += &*sun_lines.join("");
+
+    // Golden and blue hours
+    if preferences.light_bands {
+        let band_lines = light_bands_section(&events, preferences);
+        lines += &*band_lines.join("");
+    }
 
     // Moon
-    let moon_lines = moon_section(&observer, &time, &environment);
-    lines = lines + &*moon_lines.join("");
+    let moon_lines = moon_section(&events, preferences);
+    lines += &*moon_lines.join("");
+
+    // Lunar observing
+    if preferences.lunar_observing {
+        let lunar_lines = lunar_observing_section(observer, time, preferences);
+        lines += &lunar_lines.join("");
+    }
+
+    // Zodiacal light
+    if preferences.zodiacal_light {
+        let zodiacal_lines = zodiacal_light_section(observer, time, environment, preferences);
+        lines += &*zodiacal_lines.join("");
+    }
+
+    // Durations
+    let duration_lines = durations_section(&events);
+    lines += &*duration_lines.join("");
+
+    // Forecast of the night, for the dew risk and the weather section
+    let forecast = preferences.weather_forecast.then(|| night_forecast(&OpenMeteo, observer, time));
+
+    // Hour by hour
+    let hours = hourly_conditions(observer, time, &[], preferences.calculation);
+    let dew = hourly_dew(observer, time, environment, &hours, forecast_hours(&forecast), preferences);
+    let hourly_lines = hourly_section(observer, &hours, &dew, preferences);
+    lines += &*hourly_lines.join("");
+
+    // Eclipses
+    let eclipse_lines = eclipse_section(observer, time, preferences);
+    lines += &*eclipse_lines.join("");
+
+    // Dew risk
+    let dew_lines = dew_section(observer, time, environment, forecast_hours(&forecast), preferences);
+    lines += &*dew_lines.join("");
+
+    // Weather
+    if let Some(forecast) = &forecast {
+        let weather_lines = weather_section(OpenMeteo.name(), forecast, observer, preferences);
+        lines += &*weather_lines.join("");
+    }
+
+    // Satellite passes
+    if preferences.satellites {
+        let satellite_lines = satellite_section(observer, time, preferences);
+        lines += &*satellite_lines.join("");
+    }
+
+    // Darkness budget of the target plan
+    if let Some((plan, targets)) = plan {
+        let plan_lines = plan_section(observer, plan, targets, preferences);
+        lines += &*plan_lines.join("");
+    }
+
+    // Alignment stars
+    if preferences.alignment_stars {
+        let alignment_lines = alignment_section(observer, time, environment, preferences);
+        lines += &*alignment_lines.join("");
+    }
 
     // Darkness
-    let darkness_lines = darkness_section(&observer, &time, &environment);
-    lines = lines + &*darkness_lines.join("");
+    let darkness_lines = darkness_section(&events, preferences);
+    lines += &*darkness_lines.join("");
+
+    // User scripts
+    let custom_lines = custom_section(observer, time, environment, preferences);
+    lines += &*custom_lines.join("");
+
+    let mut f = File::create(DARKNESS_REPORT_FILE).expect("Unable to create file");
+    f.write_all(lines.as_bytes()).expect("Unable to write data");
+    log::info!("report exported file={} night={} targets={} elapsed_ms={}", DARKNESS_REPORT_FILE,
+               export_night(time), plan.map_or(0, |(_, targets)| targets.len()), started.elapsed().as_millis());
+}
+
+pub const SITE_COMPARISON_REPORT_FILE: &str = "skycalc_sites.txt";
+
+// Width of a site column in the comparison table, longer names are cut
+const SITE_COLUMN: usize = 18;
+
+/// SiteNight struct
+///
+/// Darkness of one observatory for the site comparison.
+///
+/// # Attributes
+///
+/// * `name` - Observatory name, or the configuration file when it has none
+/// * `events` - Events of the night in the local time of the observatory
+/// * `darkness` - Hours of astronomical darkness, None when only dusk or dawn happens
+/// * `moon_free` - Hours of astronomical darkness with the Moon below the horizon
+pub(crate) struct SiteNight {
+    pub name: String,
+    pub events: NightEvents,
+    pub darkness: Option<f64>,
+    pub moon_free: f64,
+}
+
+impl SiteNight {
+    pub(crate) fn new(name: &str, observer: &Observer, time: &Time, environment: &Environment,
+                      preferences: &Preferences) -> SiteNight {
+        SiteNight {
+            name: name.to_string(),
+            events: NightEvents::new(observer, time, environment, preferences),
+            darkness: Sun::new(observer, time, environment)
+                .with_settings(preferences.calculation)
+                .night_duration(AstronomicalTwilight),
+            moon_free: Darkness::new(observer, time, environment)
+                .with_settings(preferences.calculation)
+                .moon_free_duration(AstronomicalTwilight),
+        }
+    }
+
+    /// Hours of astronomical darkness with the Moon up
+    pub(crate) fn moon_interference(&self) -> Option<f64> {
+        self.darkness.map(|darkness| (darkness - self.moon_free).max(0.0))
+    }
+}
+
+// Darkness of the same night at several observatories side by side, one column per site, and the
+// site with the most Moon free darkness
+pub(crate) fn site_comparison_section(sites: &[SiteNight]) -> Vec<String> {
+    let row = |label: &str, values: Vec<String>| {
+        let columns: String = values
+            .iter()
+            .map(|value| format!("{:<width$}", value.chars().take(SITE_COLUMN - 1).collect::<String>(),
+                                 width = SITE_COLUMN))
+            .collect();
+        format!("\n   {:<24}{}", label, columns).trim_end().to_string()
+    };
+    let mut section: Vec<String> = Vec::new();
+    section.push(format!("{}:", tr("Site comparison")));
+    section.push(row("", sites.iter().map(|site| site.name.clone()).collect()));
+    section.push(row(tr("Darkness start"), sites.iter().map(|site| site.events.astronomical_end.clone()).collect()));
+    section.push(row(tr("Darkness end"), sites.iter().map(|site| site.events.astronomical_start.clone()).collect()));
+    section.push(row(tr("Astronomical darkness"),
+                     sites.iter().map(|site| format_duration(site.darkness)).collect()));
+    section.push(row(tr("Moon up in darkness"),
+                     sites.iter().map(|site| format_duration(site.moon_interference())).collect()));
+    section.push(row(tr("Moon free darkness"),
+                     sites.iter().map(|site| format_duration(Some(site.moon_free))).collect()));
+    section.push(row(tr("DSO astronomical start"),
+                     sites.iter().map(|site| site.events.dso_astronomical_start.clone()).collect()));
+    section.push(row(tr("DSO astronomical end"),
+                     sites.iter().map(|site| site.events.dso_astronomical_end.clone()).collect()));
+    section.push(row(tr("Moon rise"), sites.iter().map(|site| site.events.moonrise.clone()).collect()));
+    section.push(row(tr("Moon set"), sites.iter().map(|site| site.events.moonset.clone()).collect()));
+    section.push(row(tr("Night"), sites.iter().map(|site| site.events.night_length.clone()).collect()));
+    if let Some(best) = sites.iter().filter(|site| site.moon_free > 0.0)
+        .max_by(|a, b| a.moon_free.total_cmp(&b.moon_free)) {
+        section.push(format!("\n\n   {}: {} ({})", tr("Most Moon free darkness"), best.name,
+                             format_duration(Some(best.moon_free))));
+    }
+    section.push("\n\n".to_string());
+    section
+}
+
+/// Comparative darkness report of the same night at several observatories, given with their
+/// names. Times are in the local time of each site, the first site stamps the export.
+pub fn site_comparison_report(sites: &[(String, Observer)], time: &Time, environment: &Environment,
+                              preferences: &Preferences) {
+    let Some((_, first)) = sites.first() else {
+        return;
+    };
+    let nights: Vec<SiteNight> = sites
+        .iter()
+        .map(|(name, observer)| SiteNight::new(name, observer, time, environment, preferences))
+        .collect();
+
+    let mut lines = header_section().join("");
+    lines += &stamp_section(first, time, &Time::now()).join("");
+    lines += &night_section(time).join("");
+    let mut observatories: Vec<String> = vec![format!("{}:", tr("Observatories"))];
+    for (_, observer) in sites {
+        let location = if preferences.dms_coordinates {
+            observer.to_string_dms()
+        } else {
+            observer.to_string_decimal()
+        };
+        observatories.push(format!("\n   - {}", location));
+    }
+    observatories.push("\n\n".to_string());
+    lines += &observatories.join("");
+    lines += &site_comparison_section(&nights).join("");
+
+    let mut f = File::create(SITE_COMPARISON_REPORT_FILE).expect("Unable to create file");
+    f.write_all(lines.as_bytes()).expect("Unable to write data");
+}
+
+pub const DARKNESS_HTML_REPORT_FILE: &str = "skycalc.html";
+pub const DARKNESS_PRINT_REPORT_FILE: &str = "skycalc_print.html";
+
+// Page of the HTML darkness report: dark background, red text and large fonts to read it on a
+// tablet at the telescope. Placeholders between double braces are filled by darkness_html.
+const DARKNESS_HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{{title}}</title>
+<style>
+body { background: #000; color: #d33; font-family: sans-serif; font-size: 1.6em; margin: 1em; }
+h1 { font-size: 1.5em; margin: 0 0 0.3em 0; }
+h2 { font-size: 1.2em; margin: 1em 0 0.3em 0; border-bottom: 1px solid #511; }
+p.stamp { color: #922; font-size: 0.6em; }
+table { border-collapse: collapse; }
+td { padding: 0.15em 1em 0.15em 0; }
+td.time { font-family: monospace; font-size: 1.1em; }
+ul { margin: 0; padding-left: 1.2em; }
+svg { width: 100%; height: auto; }
+svg text { fill: #d33; font-size: 11px; font-family: sans-serif; }
+</style>
+</head>
+<body>
+<h1>{{title}}</h1>
+<p class="stamp">{{stamp}}</p>
+{{timeline}}
+{{sections}}
+</body>
+</html>
+"#;
+
+// Printable page of the darkness report: black on white A4 with the plan and the alignment
+// stars, the browser print dialog opens with the page to print it or save it as PDF.
+const DARKNESS_PRINT_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{{title}}</title>
+<style>
+@page { size: A4 portrait; margin: 15mm; }
+body { background: #fff; color: #000; font-family: serif; font-size: 11pt; margin: 0; }
+h1 { font-size: 18pt; margin: 0 0 0.2em 0; }
+h2 { font-size: 13pt; margin: 0.8em 0 0.3em 0; border-bottom: 1px solid #000; }
+h2, table, pre { break-inside: avoid; }
+p.stamp { color: #444; font-size: 8pt; }
+table { border-collapse: collapse; }
+td { padding: 0.1em 1.5em 0.1em 0; }
+td.time { font-family: monospace; font-weight: bold; }
+ul { margin: 0; padding-left: 1.2em; }
+pre { font-size: 9pt; margin: 0; }
+svg { width: 100%; height: auto; border: 1px solid #000; }
+svg text { fill: #000; font-size: 11px; font-family: sans-serif; }
+@media screen { body { max-width: 180mm; margin: 1em auto; } }
+</style>
+</head>
+<body onload="window.print()">
+<h1>{{title}}</h1>
+<p class="stamp">{{stamp}}</p>
+{{timeline}}
+{{sections}}
+</body>
+</html>
+"#;
+
+// Timeline layout: one column per sample from local noon to the next local noon
+const TIMELINE_STEPS: usize = 288;
+const TIMELINE_WIDTH: f64 = 720.0;
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Section with one table row per pair of events, e.g. ("Set", sunset, "Rise", sunrise)
+fn html_event_section(title: &str, rows: &[(&str, &str, &str, &str)], notes: &[String]) -> String {
+    let mut html = format!("<h2>{}</h2>\n<table>\n", html_escape(title));
+    for (first_label, first, second_label, second) in rows {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td class=\"time\">{}</td><td>{}</td><td class=\"time\">{}</td></tr>\n",
+            html_escape(first_label),
+            html_escape(first),
+            html_escape(second_label),
+            html_escape(second)
+        ));
+    }
+    html.push_str("</table>\n");
+    if !notes.is_empty() {
+        html.push_str("<ul>\n");
+        for note in notes {
+            html.push_str(&format!("<li>{}</li>\n", html_escape(note)));
+        }
+        html.push_str("</ul>\n");
+    }
+    html
+}
+
+// Section with the lines of a text report section kept aligned, the first line is the title
+fn html_text_section(lines: &[String]) -> String {
+    let Some((title, items)) = lines.split_first() else {
+        return String::new();
+    };
+    let items: Vec<String> = items
+        .iter()
+        .map(|line| line.trim_start_matches('\n').trim_start_matches("   - ").trim_end().to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+    format!("<h2>{}</h2>\n<pre>{}</pre>\n", html_escape(title.trim_end_matches(':')),
+            html_escape(&items.join("\n")))
+}
+
+// Inline SVG with twilight shading and Moon up bar from local noon to the next local noon
+fn html_timeline(observer: &Observer, time: &Time, preferences: &Preferences) -> String {
+    let solar_midnight = preferences.solar_midnight;
+    let timeline = NightTimeline::new(observer, time, solar_midnight, TIMELINE_STEPS, preferences.calculation);
+    let column = TIMELINE_WIDTH / TIMELINE_STEPS as f64;
+    let (r, g, b) = MOON_UP_RGB;
+
+    let mut svg = format!(
+        "<svg viewBox=\"0 0 {} 80\" xmlns=\"http://www.w3.org/2000/svg\">\n",
+        TIMELINE_WIDTH
+    );
+    for (i, (sky, &moon_up)) in timeline.sky.iter().zip(&timeline.moon_up).enumerate() {
+        let x = i as f64 * column;
+        svg.push_str(&format!(
+            "<rect x=\"{:.1}\" y=\"0\" width=\"{:.1}\" height=\"40\" fill=\"{}\"/>\n",
+            x, column + 0.1, sky.hex()
+        ));
+        if moon_up {
+            svg.push_str(&format!(
+                "<rect x=\"{:.1}\" y=\"46\" width=\"{:.1}\" height=\"10\" fill=\"#{:02x}{:02x}{:02x}\"/>\n",
+                x, column + 0.1, r, g, b
+            ));
+        }
+    }
+    // hour ticks every 2 hours, labelled in local time starting at noon or in hours from solar
+    // midnight
+    for hour in (0..=24).step_by(2) {
+        let x = hour as f64 * TIMELINE_WIDTH / 24.0;
+        svg.push_str(&format!(
+            "<line x1=\"{:.1}\" y1=\"0\" x2=\"{:.1}\" y2=\"60\" stroke=\"#511\"/>\n",
+            x, x
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"74\" text-anchor=\"middle\">{}</text>\n",
+            x.clamp(8.0, TIMELINE_WIDTH - 8.0), hour_label(hour, solar_midnight)
+        ));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// Page from a template, with an optional inline SVG timeline of the night
+fn html_page(template: &str, observer: &Observer, time: &Time, preferences: &Preferences,
+             timeline: bool, sections: &str, generated: &Time) -> String {
+    let title = match &observer.name {
+        Some(name) => format!("SkyCalc - {} - {}", name, export_night(time)),
+        None => format!("SkyCalc - {}", export_night(time)),
+    };
+    template
+        .replace("{{title}}", &html_escape(&title))
+        .replace("{{stamp}}", &html_escape(&export_stamp(observer, &export_night(time), generated)))
+        .replace("{{timeline}}", &if timeline {
+            html_timeline(observer, time, preferences)
+        } else {
+            String::new()
+        })
+        .replace("{{sections}}", sections)
+}
+
+// Dew risk of each hour from sunset to sunrise as time, air temperature, risk and gap to the dew
+// point, with the dew point and the hour dew heaters are needed from
+fn html_dew_section(observer: &Observer, time: &Time, environment: &Environment, forecast: &[HourlyForecast],
+                    preferences: &Preferences) -> String {
+    let unit = preferences.units.temperature;
+    let Some((sunset, sunrise)) = night_sun_times(observer, time, environment, preferences) else {
+        let notes = [format!("{}: {}", tr("Dew point"), dew_point_line(environment, &[], preferences)),
+                     tr("No sunset and sunrise this night").to_string()];
+        return html_event_section(tr("Dew risk"), &[], &notes);
+    };
+    let hours = night_dew_risk(environment, sunset, sunrise, forecast);
+    let mut notes = vec![format!("{}: {}", tr("Dew point"), dew_point_line(environment, &hours, preferences))];
+    notes.push(format!("{}: {}", tr("Dew heaters from"), dew_heaters_line(observer, &hours, preferences)));
+    let cells: Vec<(String, String, String)> = hours
+        .iter()
+        .map(|hour| (
+            format_local(observer.local_time(hour.jd), preferences),
+            format!("{:.1}{}", unit.celsius_to_unit(hour.temperature), unit.symbol()),
+            format!("{} {:.1}{}", tr("gap"), unit.difference_from_celsius(hour.temperature - hour.dew_point),
+                    unit.symbol()),
+        ))
+        .collect();
+    let rows: Vec<(&str, &str, &str, &str)> = hours
+        .iter()
+        .zip(&cells)
+        .map(|(hour, (local, temperature, gap))| (local.as_str(), temperature.as_str(), tr(hour.risk.name()),
+                                                  gap.as_str()))
+        .collect();
+    html_event_section(tr("Dew risk"), &rows, &notes)
+}
+
+// Sun, Moon, durations, eclipses and darkness sections shared by the HTML pages
+fn darkness_html_sections(observer: &Observer, time: &Time, environment: &Environment,
+                          preferences: &Preferences) -> String {
+    let events = NightEvents::new(observer, time, environment, preferences);
+    let mut sections = String::new();
+    if let Some(file_path) = &preferences.sky_brightness_file {
+        let sky = sky_brightness_description(file_path, observer.latitude, observer.longitude);
+        sections.push_str(&html_event_section(tr("Site"), &[], &[format!("{}: {}", tr("Sky brightness"), sky)]));
+    }
+    sections.push_str(&html_event_section(
+        tr("Sun"),
+        &[
+            (tr("Set"), &events.sunset, tr("Rise"), &events.sunrise),
+            (tr("Set azimuth"), &events.sunset_direction, tr("Rise"), &events.sunrise_direction),
+            (tr("Civil twilight end"), &events.civil_end, tr("start"), &events.civil_start),
+            (tr("Nautical twilight end"), &events.nautical_end, tr("start"), &events.nautical_start),
+            (tr("Astronomical twilight end"), &events.astronomical_end, tr("start"), &events.astronomical_start),
+            (tr("Transit"), &events.sun_transit, tr("Max altitude"), &events.sun_max_altitude),
+        ],
+        &events.sun_circumpolar.iter().cloned().collect::<Vec<String>>(),
+    ));
+    if preferences.light_bands {
+        let rows: Vec<(&str, &str, &str, &str)> = light_band_rows(&events)
+            .into_iter()
+            .map(|(label, start, end)| (tr(label), start, tr("end"), end))
+            .collect();
+        sections.push_str(&html_event_section(tr("Golden and blue hours"), &rows, &[]));
+    }
+    sections.push_str(&html_event_section(
+        tr("Moon"),
+        &[(tr("Rise"), &events.moonrise, tr("Set"), &events.moonset),
+          (tr("Rise azimuth"), &events.moonrise_direction, tr("Set"), &events.moonset_direction),
+          (tr("Transit"), &events.moon_transit, tr("Max altitude"), &events.moon_max_altitude),
+          (tr("Libration"), &events.moon_libration, tr("Axis PA"), &events.moon_axis)],
+        &events.moon_circumpolar.iter().cloned().collect::<Vec<String>>(),
+    ));
+    sections.push_str(&html_event_section(
+        tr("Durations"),
+        &[
+            (tr("Night"), &events.night_length, tr("Astronomical darkness"), &events.astronomical_darkness),
+            (tr("Civil twilight"), &events.civil_twilight, tr("Moon free darkness"), &events.moon_free_darkness),
+            (tr("Nautical twilight"), &events.nautical_twilight, tr("Astronomical twilight"), &events.astronomical_twilight),
+        ],
+        &[],
+    ));
+    let eclipses: Vec<String> = eclipse_section(observer, time, preferences)
+        .iter()
+        .skip(1)
+        .map(|line| line.trim().trim_start_matches("- ").to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+    if !eclipses.is_empty() {
+        sections.push_str(&html_event_section(tr("Eclipses"), &[], &eclipses));
+    }
+    let forecast = preferences.weather_forecast.then(|| night_forecast(&OpenMeteo, observer, time));
+    sections.push_str(&html_dew_section(observer, time, environment, forecast_hours(&forecast), preferences));
+    sections.push_str(&html_event_section(
+        tr("Darkness"),
+        &[
+            (tr("DSO astronomical start"), &events.dso_astronomical_start, tr("end"), &events.dso_astronomical_end),
+            (tr("DSO nautical start"), &events.dso_nautical_start, tr("end"), &events.dso_nautical_end),
+            (tr("NB astronomical start"), &events.astronomical_end, tr("end"), &events.astronomical_start),
+            (tr("NB nautical start"), &events.nautical_end, tr("end"), &events.nautical_start),
+        ],
+        &[],
+    ));
+    sections
+}
+
+/// HTML version of the darkness report, with an optional inline SVG timeline of the night
+pub(crate) fn darkness_html(observer: &Observer, time: &Time, environment: &Environment,
+                            preferences: &Preferences, timeline: bool, generated: &Time) -> String {
+    let sections = darkness_html_sections(observer, time, environment, preferences);
+    html_page(DARKNESS_HTML_TEMPLATE, observer, time, preferences, timeline, &sections, generated)
+}
+
+/// Printable HTML darkness report: observatory, the darkness report sections, the target plan and
+/// the alignment stars, always with the twilight timeline
+pub(crate) fn darkness_print_html(observer: &Observer, time: &Time, environment: &Environment,
+                                  constraints: &Constraints, targets: &[Target], preferences: &Preferences,
+                                  generated: &Time) -> String {
+    let mut sections = html_text_section(&observer_section(observer, preferences));
+    sections.push_str(&darkness_html_sections(observer, time, environment, preferences));
+    if let Some(plan) = night_plan(observer, time, environment, constraints, targets) {
+        sections.push_str(&html_text_section(&plan_section(observer, &plan, targets, preferences)));
+    }
+    sections.push_str(&html_text_section(&alignment_section(observer, time, environment, preferences)));
+    html_page(DARKNESS_PRINT_TEMPLATE, observer, time, preferences, true, &sections, generated)
+}
+
+pub fn darkness_html_report(observer: &Observer, time: &Time, environment: &Environment,
+                            preferences: &Preferences) {
+    let html = darkness_html(observer, time, environment, preferences,
+                             preferences.html_timeline, &Time::now());
+
+    let mut f = File::create(DARKNESS_HTML_REPORT_FILE).expect("Unable to create file");
+    f.write_all(html.as_bytes()).expect("Unable to write data");
+}
+
+pub fn darkness_print_report(observer: &Observer, time: &Time, environment: &Environment,
+                             constraints: &Constraints, targets: &[Target], preferences: &Preferences) {
+    let html = darkness_print_html(observer, time, environment, constraints, targets, preferences,
+                                   &Time::now());
+
+    let mut f = File::create(DARKNESS_PRINT_REPORT_FILE).expect("Unable to create file");
+    f.write_all(html.as_bytes()).expect("Unable to write data");
+}
+
+// Perigees, apogees and full Moons between jd_start and jd_end (UTC), full Moons close to perigee
+// flagged as supermoons
+pub(crate) fn moon_events(jd_start: f64, jd_end: f64) -> Vec<(f64, &'static str)> {
+    let mut events: Vec<(f64, &'static str)> = moon_apsides(jd_start, jd_end)
+        .iter()
+        .map(|event| (event.jd, event.apsis.description()))
+        .chain(full_moons(jd_start, jd_end)
+            .into_iter()
+            .map(|jd| (jd, if is_supermoon(jd) { "supermoon" } else { "full moon" })))
+        .collect();
+    events.sort_by(|a, b| a.0.total_cmp(&b.0));
+    events
+}
+
+// Moon events of the night from local noon (night_start, UTC) to the next local noon
+fn night_moon_events(events: &[(f64, &str)], night_start: f64) -> String {
+    events
+        .iter()
+        .filter(|(jd, _)| *jd >= night_start && *jd < night_start + 1.0)
+        .map(|(_, event)| *event)
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+// One CSV row per night between start and end (inclusive) with the astronomical darkness window
+// in local time, the minimum Moon - target separation during darkness and the Moon illumination
+// at the middle of the darkness window.
+pub(crate) fn moon_separation_section(observer: &Observer, target: &Target, start: &Time,
+                                      end: &Time, preferences: &Preferences) -> Vec<String> {
+    const NUM_POINTS: usize = 288; // 5 minutes resolution
+    let offset = observer.timezone / 24.0;
+    let mut csv: Vec<String> = Vec::new();
+    csv.push("date,darkness_start,darkness_end,min_moon_separation_deg,moon_illumination_pct,moon_events\n".to_string());
+
+    // nights are independent, computed in parallel and collected in date order
+    let count = ((end.to_jd() - start.to_jd()).floor() + 1.0).max(0.0) as usize;
+    let first_night = (start.to_jd() + 0.5).floor() - offset;
+    let events = moon_events(first_night, first_night + count as f64);
+    let rows: Vec<String> = (0..count).into_par_iter().map(|i| {
+        let jd = start.to_jd() + i as f64;
+        let date = Time::from_jd(jd).to_string(Some("yyyymmdd"));
+        // night goes from local noon to next local noon
+        let night_start = (jd + 0.5).floor() - offset;
+        let night_events = night_moon_events(&events, night_start);
+        let sun = sun_alt_az_grid_utc(
+            observer.latitude,
+            observer.longitude,
+            night_start,
+            night_start + 1.0,
+            NUM_POINTS,
+        );
+        let darkness: Vec<f64> = sun
+            .iter()
+            .filter(|sun| sun.1 <= AstronomicalTwilight.angle())
+            .map(|sun| sun.0)
+            .collect();
+
+        if darkness.is_empty() {
+            format!("{},,,,,{}\n", date, night_events)
+        } else {
+            let min_separation = darkness
+                .iter()
+                .map(|&dark_jd| {
+                    let (ra, dec, _) = moon_topocentric_position(
+                        observer.latitude,
+                        observer.longitude,
+                        observer.elevation as f64,
+                        dark_jd,
+                    );
+                    angular_separation(target.ra, target.dec, ra, dec)
+                })
+                .fold(f64::MAX, f64::min);
+            let dark_start = darkness[0];
+            let dark_end = darkness[darkness.len() - 1];
+            let illumination = moon_illumination((dark_start + dark_end) / 2.0);
+            format!(
+                "{},{},{},{:.1},{:.0},{}\n",
+                date,
+                format_local(observer.local_time(dark_start), preferences),
+                format_local(observer.local_time(dark_end), preferences),
+                min_separation,
+                illumination * 100.0,
+                night_events
+            )
+        }
+    }).collect();
+    csv.extend(rows);
+    csv
+}
+
+pub fn moon_separation_report(observer: &Observer, target: &Target, start: &Time, end: &Time,
+                              preferences: &Preferences) {
+    let stamp = export_stamp(observer, &export_night_range(start, end), &Time::now());
+    let lines = moon_separation_section(observer, target, start, end, preferences).join("");
+    let lines = format!("# {}\n{}", stamp, lines);
+
+    let mut f = File::create(MOON_SEPARATION_REPORT_FILE).expect("Unable to create file");
+    f.write_all(lines.as_bytes()).expect("Unable to write data");
+}
+
+/// Night of the darkness calendar, durations in hours, Moon events separated by spaces
+#[derive(Debug, Clone)]
+pub(crate) struct CalendarNight {
+    pub date: Time,
+    pub darkness: f64,
+    pub moon_free: f64,
+    pub illumination: f64,
+    pub moon_events: String,
+}
+
+// Astronomical darkness and Moon free darkness of every night of a year
+pub(crate) fn darkness_calendar(observer: &Observer, year: i64) -> Vec<CalendarNight> {
+    let start = Time::new(year, 1, 1, 0, 0, 0).to_jd();
+    let end = Time::new(year + 1, 1, 1, 0, 0, 0).to_jd();
+    calendar_nights(observer, start, (end - start).round() as usize)
+}
+
+// Astronomical darkness and Moon free darkness of `count` nights from the date of `start` (JD),
+// sampled every 5 minutes from local noon to the next local noon. The illumination is taken at
+// local midnight.
+pub(crate) fn calendar_nights(observer: &Observer, start: f64, count: usize) -> Vec<CalendarNight> {
+    const NUM_POINTS: usize = 288; // 5 minutes resolution
+    const HOURS_PER_POINT: f64 = 24.0 / NUM_POINTS as f64;
+    let offset = observer.timezone / 24.0;
+    let first = (start + 0.5).floor() - 0.5;
+    let events = moon_events(first + 0.5 - offset, first + 0.5 - offset + count as f64);
+
+    // nights are independent, computed in parallel and collected in date order
+    (0..count).into_par_iter().map(|i| {
+        let jd = first + i as f64;
+        // night goes from local noon to next local noon
+        let night_start = (jd + 0.5).floor() - offset;
+        let sun = sun_alt_az_grid_utc(
+            observer.latitude,
+            observer.longitude,
+            night_start,
+            night_start + 1.0,
+            NUM_POINTS,
+        );
+        let moon_up = moon_altitude(observer.latitude, observer.longitude, observer.elevation as f64);
+        // the last point is the next local noon
+        let dark: Vec<f64> = sun
+            .iter()
+            .take(NUM_POINTS)
+            .filter(|sun| sun.1 <= AstronomicalTwilight.angle())
+            .map(|sun| sun.0)
+            .collect();
+        let moon_free = dark.iter().filter(|&&dark_jd| moon_up(dark_jd) <= MOON_HORIZON).count();
+
+        CalendarNight {
+            date: Time::from_jd(jd),
+            darkness: dark.len() as f64 * HOURS_PER_POINT,
+            moon_free: moon_free as f64 * HOURS_PER_POINT,
+            illumination: moon_illumination(night_start + 0.5),
+            moon_events: night_moon_events(&events, night_start),
+        }
+    }).collect()
+}
+
+pub(crate) fn darkness_calendar_section(nights: &[CalendarNight]) -> Vec<String> {
+    let mut csv: Vec<String> = Vec::new();
+    csv.push("date,darkness_hours,moon_free_darkness_hours,moon_illumination_pct,moon_events\n".to_string());
+    for night in nights {
+        csv.push(format!(
+            "{},{:.2},{:.2},{:.0},{}\n",
+            night.date.to_string(Some("yyyymmdd")),
+            night.darkness,
+            night.moon_free,
+            night.illumination * 100.0,
+            night.moon_events
+        ));
+    }
+    csv
+}
+
+// Heatmap cell color, from black (no Moon free darkness) to bright blue (12 hours or more)
+fn calendar_color(hours: f64) -> String {
+    let level = (hours / 12.0).clamp(0.0, 1.0);
+    format!(
+        "rgb({},{},{})",
+        (20.0 + 30.0 * level).round(),
+        (20.0 + 90.0 * level).round(),
+        (20.0 + 200.0 * level).round()
+    )
+}
 
-    let mut f = File::create("skycalc.txt").expect("Unable to create file");
+// Heatmap of the Moon free darkness, one row per month and one column per day
+pub(crate) fn darkness_calendar_svg(observer: &Observer, year: i64, nights: &[CalendarNight]) -> String {
+    const CELL: usize = 22;
+    const LEFT: usize = 40;
+    const TOP: usize = 50;
+    let width = LEFT + 31 * CELL + 10;
+    let height = TOP + 12 * CELL + 40;
+    let title = match &observer.name {
+        Some(name) => format!("Moon free darkness {} - {}", year, name),
+        None => format!("Moon free darkness {}", year),
+    };
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"sans-serif\" font-size=\"11\">\n",
+        width, height
+    );
+    svg.push_str(&format!("<rect width=\"{}\" height=\"{}\" fill=\"#000000\"/>\n", width, height));
+    svg.push_str(&format!("<text x=\"{}\" y=\"20\" fill=\"#ff4040\" font-size=\"14\">{}</text>\n", LEFT, html_escape(&title)));
+    for day in 1..=31 {
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" fill=\"#c03030\" text-anchor=\"middle\">{}</text>\n",
+            LEFT + (day - 1) * CELL + CELL / 2, TOP - 6, day
+        ));
+    }
+    for month in 0..12 {
+        svg.push_str(&format!(
+            "<text x=\"4\" y=\"{}\" fill=\"#c03030\">{}</text>\n",
+            TOP + month * CELL + CELL / 2 + 4, month_abbreviation(month as u64 + 1)
+        ));
+    }
+    for night in nights {
+        let x = LEFT + (night.date.day as usize - 1) * CELL;
+        let y = TOP + (night.date.month as usize - 1) * CELL;
+        svg.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"><title>{} {}h Moon free, {}h dark, Moon {:.0}% {}</title></rect>\n",
+            x, y, CELL - 2, CELL - 2, calendar_color(night.moon_free),
+            night.date.to_string(Some("yyyymmdd")),
+            format_duration(Some(night.moon_free)), format_duration(Some(night.darkness)),
+            night.illumination * 100.0, night.moon_events
+        ));
+        // supermoon nights marked with a dot
+        if night.moon_events.contains("supermoon") {
+            svg.push_str(&format!("<circle cx=\"{}\" cy=\"{}\" r=\"3\" fill=\"#ffd040\"/>\n",
+                                  x + CELL - 7, y + 5));
+        }
+    }
+    let legend_y = TOP + 12 * CELL + 20;
+    for (i, hours) in [0.0, 3.0, 6.0, 9.0, 12.0].iter().enumerate() {
+        let x = LEFT + i * 70;
+        svg.push_str(&format!("<rect x=\"{}\" y=\"{}\" width=\"14\" height=\"14\" fill=\"{}\"/>\n", x, legend_y - 11, calendar_color(*hours)));
+        svg.push_str(&format!("<text x=\"{}\" y=\"{}\" fill=\"#c03030\">{:.0} h</text>\n", x + 18, legend_y, hours));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+pub fn darkness_calendar_report(observer: &Observer, year: i64, image: bool) {
+    let nights = darkness_calendar(observer, year);
+    let stamp = export_stamp(observer, &year.to_string(), &Time::now());
+    let lines = format!("# {}\n{}", stamp, darkness_calendar_section(&nights).join(""));
+
+    let mut f = File::create(DARKNESS_CALENDAR_FILE).expect("Unable to create file");
+    f.write_all(lines.as_bytes()).expect("Unable to write data");
+
+    if image {
+        let svg = darkness_calendar_svg(observer, year, &nights);
+        let mut f = File::create(DARKNESS_CALENDAR_IMAGE_FILE).expect("Unable to create file");
+        f.write_all(svg.as_bytes()).expect("Unable to write data");
+    }
+}
+
+/// Steps of the altitude chart exports, 5 minutes each
+pub const ALTITUDE_CHART_STEPS: usize = 288;
+
+/// Title of the altitude chart exports, the night and the observatory
+pub fn altitude_chart_title(observer: &Observer, time: &Time) -> String {
+    match &observer.name {
+        Some(name) => format!("Altitude {} - {}", export_night(time), name),
+        None => format!("Altitude {}", export_night(time)),
+    }
+}
+
+fn svg_color((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+// Altitude from the horizon to the zenith against the 24 hours of the night, on the twilight
+// bands. The Sun and the Moon are dashed, the targets take the colors in turn.
+pub(crate) fn altitude_chart_svg(chart: &AltitudeChart, title: &str, solar_midnight: bool) -> String {
+    const LEFT: f64 = 40.0;
+    const TOP: f64 = 40.0;
+    const PLOT_WIDTH: f64 = 720.0;
+    const PLOT_HEIGHT: f64 = 300.0;
+    const LEGEND_ROW: f64 = 18.0;
+    let steps = chart.timeline.sky.len().max(1);
+    let column = PLOT_WIDTH / steps as f64;
+    let x = |i: f64| LEFT + i * column;
+    let y = |altitude: f64| TOP + PLOT_HEIGHT * (1.0 - altitude.clamp(0.0, 90.0) / 90.0);
+    let curves = chart.curves();
+    let legend_rows = curves.len().div_ceil(4);
+    let width = LEFT + PLOT_WIDTH + 20.0;
+    let height = TOP + PLOT_HEIGHT + 30.0 + legend_rows as f64 * LEGEND_ROW + 10.0;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"sans-serif\" font-size=\"11\">\n",
+        width, height
+    );
+    svg.push_str(&format!("<rect width=\"{}\" height=\"{}\" fill=\"#000000\"/>\n", width, height));
+    svg.push_str(&format!("<text x=\"{}\" y=\"24\" fill=\"#ff4040\" font-size=\"14\">{}</text>\n", LEFT,
+                          html_escape(title)));
+    for (i, sky) in chart.timeline.sky.iter().enumerate() {
+        svg.push_str(&format!(
+            "<rect x=\"{:.1}\" y=\"{}\" width=\"{:.1}\" height=\"{}\" fill=\"{}\"/>\n",
+            x(i as f64), TOP, column + 0.1, PLOT_HEIGHT, sky.hex()
+        ));
+    }
+    for altitude in (0..=90).step_by(15) {
+        let line_y = y(altitude as f64);
+        svg.push_str(&format!(
+            "<line x1=\"{}\" y1=\"{:.1}\" x2=\"{}\" y2=\"{:.1}\" stroke=\"#511\"/>\n",
+            LEFT, line_y, LEFT + PLOT_WIDTH, line_y
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{:.1}\" fill=\"#c03030\" text-anchor=\"end\">{}°</text>\n",
+            LEFT - 4.0, line_y + 4.0, altitude
+        ));
+    }
+    for hour in (0..=24).step_by(2) {
+        let tick_x = LEFT + hour as f64 * PLOT_WIDTH / 24.0;
+        svg.push_str(&format!(
+            "<line x1=\"{:.1}\" y1=\"{}\" x2=\"{:.1}\" y2=\"{}\" stroke=\"#511\"/>\n",
+            tick_x, TOP, tick_x, TOP + PLOT_HEIGHT
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{}\" fill=\"#c03030\" text-anchor=\"middle\">{}</text>\n",
+            tick_x, TOP + PLOT_HEIGHT + 16.0, hour_label(hour, solar_midnight)
+        ));
+    }
+
+    // curves above the horizon, one polyline per part
+    for &(_, altitudes, rgb, dashed) in &curves {
+        for run in above_horizon(altitudes) {
+            let points: Vec<String> = run
+                .map(|i| format!("{:.1},{:.1}", x(i as f64 + 0.5), y(altitudes[i])))
+                .collect();
+            svg.push_str(&format!(
+                "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"{}/>\n",
+                points.join(" "), svg_color(rgb), if dashed { " stroke-dasharray=\"6 4\"" } else { "" }
+            ));
+        }
+    }
+
+    // legend, four curves per row
+    for (i, &(name, _, rgb, dashed)) in curves.iter().enumerate() {
+        let legend_x = LEFT + (i % 4) as f64 * 180.0;
+        let legend_y = TOP + PLOT_HEIGHT + 36.0 + (i / 4) as f64 * LEGEND_ROW;
+        svg.push_str(&format!(
+            "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{}\" stroke-width=\"2\"{}/>\n",
+            legend_x, legend_y - 4.0, legend_x + 20.0, legend_y - 4.0, svg_color(rgb),
+            if dashed { " stroke-dasharray=\"6 4\"" } else { "" }
+        ));
+        svg.push_str(&format!("<text x=\"{:.1}\" y=\"{:.1}\" fill=\"#c03030\">{}</text>\n", legend_x + 26.0,
+                              legend_y, html_escape(name)));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Altitude chart of the targets in the night of `time` as a standalone SVG file
+pub fn altitude_chart_report(observer: &Observer, time: &Time, targets: &[Target], preferences: &Preferences) {
+    let chart = AltitudeChart::new(observer, time, targets, preferences.solar_midnight, ALTITUDE_CHART_STEPS,
+                                   preferences.calculation);
+    let stamp = export_stamp(observer, &export_night(time), &Time::now());
+    let svg = format!("<!-- {} -->\n{}", stamp,
+                      altitude_chart_svg(&chart, &altitude_chart_title(observer, time), preferences.solar_midnight));
+
+    let mut f = File::create(ALTITUDE_CHART_SVG_FILE).expect("Unable to create file");
+    f.write_all(svg.as_bytes()).expect("Unable to write data");
+}
+
+/// Day of the monthly almanac, events of the local calendar day as UTC Julian Dates, None when
+/// the event does not happen that day, Moon illumination at the local midnight that ends the day
+#[derive(Debug, Clone)]
+pub(crate) struct AlmanacDay {
+    pub date: Time,
+    pub astronomical_start: Option<f64>,
+    pub nautical_start: Option<f64>,
+    pub civil_start: Option<f64>,
+    pub sunrise: Option<f64>,
+    pub sunset: Option<f64>,
+    pub civil_end: Option<f64>,
+    pub nautical_end: Option<f64>,
+    pub astronomical_end: Option<f64>,
+    pub moonrise: Option<f64>,
+    pub moonset: Option<f64>,
+    pub illumination: f64,
+}
+
+impl AlmanacDay {
+    // Events in the order of the columns, with their CSV headers
+    fn events(&self) -> [(&'static str, Option<f64>); 10] {
+        [
+            ("astronomical_start", self.astronomical_start),
+            ("nautical_start", self.nautical_start),
+            ("civil_start", self.civil_start),
+            ("sunrise", self.sunrise),
+            ("sunset", self.sunset),
+            ("civil_end", self.civil_end),
+            ("nautical_end", self.nautical_end),
+            ("astronomical_end", self.astronomical_end),
+            ("moonrise", self.moonrise),
+            ("moonset", self.moonset),
+        ]
+    }
+}
+
+// JD of 0h of the first day of a month and its number of days
+fn month_days(year: i64, month: u64) -> (f64, usize) {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    let first = Time::new(year, month, 1, 0, 0, 0).to_jd();
+    (first, (Time::new(next_year, next_month, 1, 0, 0, 0).to_jd() - first).round() as usize)
+}
+
+// Sun and Moon events of every local day of a month, rise and set with the refraction of the
+// accuracy settings
+pub(crate) fn almanac(observer: &Observer, year: i64, month: u64, settings: CalculationSettings) -> Vec<AlmanacDay> {
+    let (first, count) = month_days(year, month);
+    let offset = observer.timezone / 24.0;
+
+    // days are independent, computed in parallel and collected in date order
+    (0..count).into_par_iter().map(|i| {
+        // local midnight to the next local midnight
+        let start = first + i as f64 - offset;
+        let end = start + 1.0;
+        let sun = sun_altitude(observer.latitude, observer.longitude);
+        let moon = moon_altitude(observer.latitude, observer.longitude, observer.elevation as f64);
+        let sun_crossing = |twilight: TwilightType, rising: bool| {
+            find_crossing(&sun, start, end, settings.twilight_angle(twilight), rising).ok()
+        };
+
+        AlmanacDay {
+            date: Time::from_jd(first + i as f64),
+            astronomical_start: sun_crossing(AstronomicalTwilight, true),
+            nautical_start: sun_crossing(NauticalTwilight, true),
+            civil_start: sun_crossing(CivilTwilight, true),
+            sunrise: sun_crossing(RiseSet, true),
+            sunset: sun_crossing(RiseSet, false),
+            civil_end: sun_crossing(CivilTwilight, false),
+            nautical_end: sun_crossing(NauticalTwilight, false),
+            astronomical_end: sun_crossing(AstronomicalTwilight, false),
+            moonrise: find_crossing(&moon, start, end, settings.moon_horizon(), true).ok(),
+            moonset: find_crossing(&moon, start, end, settings.moon_horizon(), false).ok(),
+            illumination: moon_illumination(end),
+        }
+    }).collect()
+}
+
+// Aligned table with a row per day, local times, in the style of the almanac pages
+pub(crate) fn almanac_section(observer: &Observer, year: i64, month: u64, days: &[AlmanacDay],
+                              preferences: &Preferences) -> Vec<String> {
+    let clock = |jd: Option<f64>| {
+        jd.map_or("-".to_string(), |jd| format_local_clock(observer.local_time(jd), preferences))
+    };
+    // column titles on two lines, in the order of AlmanacDay::events
+    let titles = [
+        ("Astro", "start"), ("Naut", "start"), ("Civil", "start"), ("Sunrise", ""), ("Sunset", ""),
+        ("Civil", "end"), ("Naut", "end"), ("Astro", "end"), ("Moonrise", ""), ("Moonset", ""),
+    ].map(|(first, second)| (tr(first), if second.is_empty() { "" } else { tr(second) }));
+    // every clock time has the same length with the preferred format
+    let width = titles
+        .iter()
+        .map(|(first, second)| first.chars().count().max(second.chars().count()))
+        .fold(format_clock(0.0, preferences).chars().count(), usize::max);
+
+    let mut section: Vec<String> = Vec::new();
+    section.push(format!("{} {} {} ({}):", tr("Almanac"), month_name(month), year, tr("local time")));
+    let mut first_line = format!("\n\n{:<12}", "");
+    let mut second_line = format!("\n{:<12}", tr("Date"));
+    for (first, second) in titles {
+        first_line.push_str(&format!("{:^w$}  ", first, w = width));
+        second_line.push_str(&format!("{:^w$}  ", second, w = width));
+    }
+    first_line.push_str(&format!("{:>5}", tr("Illum")));
+    section.push(first_line);
+    section.push(second_line.trim_end().to_string());
+    for day in days {
+        let mut row = format!("\n{:<12}", day.date.to_string(Some("yyyymmdd")));
+        for (_, event) in day.events() {
+            row.push_str(&format!("{:^w$}  ", clock(event), w = width));
+        }
+        row.push_str(&format!("{:>4.0}%", day.illumination * 100.0));
+        section.push(row);
+    }
+    section.push(format!("\n\n- {}\n\n", tr("the event does not happen that day")));
+    section.append(&mut seasons_section(observer, year, month, preferences));
+    section
+}
+
+// Equinoxes and solstices of the local month, named by the season they start at the observatory
+// latitude with the month name after
+fn seasons_section(observer: &Observer, year: i64, month: u64, preferences: &Preferences) -> Vec<String> {
+    let offset = observer.timezone / 24.0;
+    let (first, count) = month_days(year, month);
+    let events = season_events(first - offset, first + count as f64 - offset);
+    let mut section: Vec<String> = Vec::new();
+    if events.is_empty() {
+        return section;
+    }
+    section.push(format!("{}:\n", tr("Seasons")));
+    for (event, jd) in events {
+        section.push(format!("\n{:<26}{} ({})", tr(event.local_name(observer.latitude)),
+                             format_local(observer.local_time(jd), preferences), tr(event.name())));
+    }
+    section.push("\n\n".to_string());
+    section
+}
+
+pub(crate) fn almanac_csv(observer: &Observer, days: &[AlmanacDay]) -> String {
+    let headers: Vec<&str> = days.first().map_or(Vec::new(), |day| day.events().iter().map(|(h, _)| *h).collect());
+    let mut csv = format!("date,{},moon_illumination_pct\n", headers.join(","));
+    for day in days {
+        let times: Vec<String> = day
+            .events()
+            .iter()
+            // rounded to the nearest minute
+            .map(|(_, event)| event.map_or(String::new(), |jd| {
+                observer.local_time((jd * 1_440.0).round() / 1_440.0).to_local().to_string(Some("hhmm"))
+            }))
+            .collect();
+        csv.push_str(&format!("{},{},{:.0}\n", day.date.to_string(Some("yyyymmdd")), times.join(","),
+                              day.illumination * 100.0));
+    }
+    csv
+}
+
+/// Monthly almanac of the observatory: twilights, sunrise and sunset, moonrise and moonset and the
+/// Moon illumination of every day, as a text table and optionally as CSV
+pub fn almanac_report(observer: &Observer, year: i64, month: u64, csv: bool, preferences: &Preferences) {
+    let days = almanac(observer, year, month, preferences.calculation);
+    let stamp = export_stamp(observer, &export_month(year, month), &Time::now());
+
+    let mut lines = header_section().join("");
+    lines = lines + &*stamp + "\n\n";
+    lines = lines + &*observer_section(observer, preferences).join("") + "\n\n";
+    lines += &almanac_section(observer, year, month, &days, preferences).join("");
+    let mut f = File::create(ALMANAC_REPORT_FILE).expect("Unable to create file");
+    f.write_all(lines.as_bytes()).expect("Unable to write data");
+
+    if csv {
+        let lines = format!("# {}\n{}", stamp, almanac_csv(observer, &days));
+        let mut f = File::create(ALMANAC_CSV_FILE).expect("Unable to create file");
+        f.write_all(lines.as_bytes()).expect("Unable to write data");
+    }
+}
+
+// N.I.N.A. serializes its sequences with Newtonsoft.Json reference handling, so every object
+// carries an "$id" and children point back to their container through "$ref".
+const NINA_ITEMS_TYPE: &str = "System.Collections.ObjectModel.ObservableCollection`1[[NINA.Sequencer.SequenceItem.ISequenceItem, NINA.Sequencer]], System.ObjectModel";
+const NINA_CONDITIONS_TYPE: &str = "System.Collections.ObjectModel.ObservableCollection`1[[NINA.Sequencer.Conditions.ISequenceCondition, NINA.Sequencer]], System.ObjectModel";
+const NINA_TRIGGERS_TYPE: &str = "System.Collections.ObjectModel.ObservableCollection`1[[NINA.Sequencer.Trigger.ISequenceTrigger, NINA.Sequencer]], System.ObjectModel";
+const NINA_SEQUENTIAL_STRATEGY: &str = "NINA.Sequencer.Container.ExecutionStrategy.SequentialStrategy, NINA.Sequencer";
+const NINA_TIME_PROVIDER: &str = "NINA.Sequencer.Utility.DateTimeProvider.TimeProvider, NINA.Sequencer";
+
+fn nina_next_id(id: &mut u32) -> String {
+    *id += 1;
+    id.to_string()
+}
+
+fn nina_collection(id: &mut u32, collection_type: &str, values: Vec<Value>) -> Value {
+    json!({
+        "$id": nina_next_id(id),
+        "$type": collection_type,
+        "$values": values,
+    })
+}
+
+// Hours, minutes and seconds of the local clock, as expected by NINA time based items
+fn nina_clock(local: LocalTime) -> (u64, u64, u64) {
+    let time = local.to_local();
+    (time.hour, time.minute, time.second)
+}
+
+fn nina_target_container(id: &mut u32, parent_id: &str, target: &Target,
+                         dark_window: Option<(LocalTime, LocalTime)>) -> Value {
+    let container_id = nina_next_id(id);
+    let (_, ra_h, ra_m, ra_s) = split_sexagesimal(target.ra / 15.0);
+    let (negative_dec, dec_d, dec_m, dec_s) = split_sexagesimal(target.dec);
+
+    let target_value = json!({
+        "$id": nina_next_id(id),
+        "$type": "NINA.Astrometry.InputTarget, NINA.Astrometry",
+        "Expanded": true,
+        "TargetName": target.name,
+        "PositionAngle": 0.0,
+        "InputCoordinates": {
+            "$id": nina_next_id(id),
+            "$type": "NINA.Astrometry.InputCoordinates, NINA.Astrometry",
+            "RAHours": ra_h,
+            "RAMinutes": ra_m,
+            "RASeconds": ra_s,
+            "NegativeDec": negative_dec,
+            "DecDegrees": dec_d,
+            "DecMinutes": dec_m,
+            "DecSeconds": dec_s,
+        },
+    });
+
+    // Wait for the dark window to open and stop the target when it closes
+    let mut items: Vec<Value> = Vec::new();
+    let mut conditions: Vec<Value> = Vec::new();
+    if let Some((start, end)) = dark_window {
+        let (start_h, start_m, start_s) = nina_clock(start);
+        items.push(json!({
+            "$id": nina_next_id(id),
+            "$type": "NINA.Sequencer.SequenceItem.Utility.WaitForTime, NINA.Sequencer",
+            "Hours": start_h,
+            "Minutes": start_m,
+            "MinutesOffset": 0,
+            "Seconds": start_s,
+            "SelectedProvider": { "$type": NINA_TIME_PROVIDER },
+            "Parent": { "$ref": container_id },
+            "ErrorBehavior": 0,
+            "Attempts": 1,
+        }));
+        let (end_h, end_m, end_s) = nina_clock(end);
+        conditions.push(json!({
+            "$id": nina_next_id(id),
+            "$type": "NINA.Sequencer.Conditions.TimeCondition, NINA.Sequencer",
+            "Hours": end_h,
+            "Minutes": end_m,
+            "MinutesOffset": 0,
+            "Seconds": end_s,
+            "SelectedProvider": { "$type": NINA_TIME_PROVIDER },
+            "Parent": { "$ref": container_id },
+        }));
+    }
+
+    json!({
+        "$id": container_id,
+        "$type": "NINA.Sequencer.Container.DeepSkyObjectContainer, NINA.Sequencer",
+        "Target": target_value,
+        "Strategy": { "$type": NINA_SEQUENTIAL_STRATEGY },
+        "Name": target.name,
+        "Conditions": nina_collection(id, NINA_CONDITIONS_TYPE, conditions),
+        "IsExpanded": true,
+        "Items": nina_collection(id, NINA_ITEMS_TYPE, items),
+        "Triggers": nina_collection(id, NINA_TRIGGERS_TYPE, Vec::new()),
+        "Parent": { "$ref": parent_id },
+        "ErrorBehavior": 0,
+        "Attempts": 1,
+    })
+}
+
+// Builds a N.I.N.A. advanced sequence with one Deep Sky Object container per target. Each
+// container waits for the start of the dark window (astronomical, or nautical when there is
+// no astronomical darkness) and ends when the window closes. Times are in local time.
+pub(crate) fn nina_sequence(observer: &Observer, time: &Time, environment: &Environment,
+                            targets: &[Target], generated: &Time) -> Value {
+    let darkness = Darkness::new(observer, time, environment);
+    let (_, (start, end)) = darkness.get_darkness_local_astronomical_or_nautical();
+    let dark_window = match (start.time(), end.time()) {
+        (Some(start), Some(end)) => Some((start, end)),
+        _ => None,
+    };
+
+    let mut id: u32 = 0;
+    let root_id = nina_next_id(&mut id);
+    let containers: Vec<Value> = targets
+        .iter()
+        .map(|target| nina_target_container(&mut id, &root_id, target, dark_window))
+        .collect();
+
+    json!({
+        "$id": root_id,
+        "$type": "NINA.Sequencer.Container.SequenceRootContainer, NINA.Sequencer",
+        "Strategy": { "$type": NINA_SEQUENTIAL_STRATEGY },
+        "Name": export_stamp(observer, &export_night(time), generated),
+        "Conditions": nina_collection(&mut id, NINA_CONDITIONS_TYPE, Vec::new()),
+        "IsExpanded": true,
+        "Items": nina_collection(&mut id, NINA_ITEMS_TYPE, containers),
+        "Triggers": nina_collection(&mut id, NINA_TRIGGERS_TYPE, Vec::new()),
+        "Parent": null,
+        "ErrorBehavior": 0,
+        "Attempts": 1,
+    })
+}
+
+pub fn nina_sequence_report(observer: &Observer, time: &Time, environment: &Environment,
+                            targets: &[Target]) {
+    let sequence = nina_sequence(observer, time, environment, targets, &Time::now());
+    let json = serde_json::to_string_pretty(&sequence).expect("Unable to serialize sequence");
+
+    let mut f = File::create(NINA_SEQUENCE_FILE).expect("Unable to create file");
+    f.write_all(json.as_bytes()).expect("Unable to write data");
+}
+
+// Quotes a CSV text field when it contains a separator or a quote
+fn csv_text(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+// Filter plan of a target as "broadband OK 21:10 to 23:40; narrowband only ...", in local time
+pub fn filter_segments_text(segments: &[FilterSegment], observer: &Observer,
+                                   preferences: &Preferences) -> String {
+    segments
+        .iter()
+        .map(|s| format!("{} {} to {}", s.class.description(), format_local(observer.local_time(s.start), preferences),
+                         format_local(observer.local_time(s.end), preferences)))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+// One CSV row per target up tonight, the longest observable first, times in local time. The
+// filter columns are empty when the night has no darkness.
+// The field of view columns are empty without an active telescope and camera, or for targets of
+// unknown size
+pub(crate) fn up_tonight_section(observer: &Observer, up: &[Observability], filters: Option<&[Vec<FilterSegment>]>,
+                                 fov: Option<FieldOfView>, preferences: &Preferences) -> Vec<String> {
+    let mut csv: Vec<String> = Vec::new();
+    csv.push("name,ra_deg,dec_deg,size_arcmin,observable_pct,observable_hours,max_altitude_deg,\
+best_time,min_moon_separation_deg,broadband_hours,narrowband_hours,filters,fov_fill_pct,fov_fit\n".to_string());
+    for (i, o) in up.iter().enumerate() {
+        let segments = filters.and_then(|filters| filters.get(i));
+        let fit = fov.map_or(TargetFit::Unknown, |fov| fov.fit(o.target.size));
+        let fill = match fov {
+            Some(fov) if fit != TargetFit::Unknown => format!("{:.0}", fov.fill(o.target.size) * 100.0),
+            _ => String::new(),
+        };
+        csv.push(format!(
+            "{},{:.4},{:.4},{},{:.0},{:.1},{:.1},{},{},{},{},{},{},{}\n",
+            csv_text(&o.target.name),
+            o.target.ra,
+            o.target.dec,
+            o.target.size,
+            o.fraction * 100.0,
+            o.hours,
+            o.max_altitude,
+            format_local(observer.local_time(o.best_jd), preferences),
+            o.moon_separation.map_or(String::new(), |separation| format!("{:.1}", separation)),
+            segments.map_or(String::new(), |s| format!("{:.1}", filter_hours(s, FilterClass::Broadband))),
+            segments.map_or(String::new(), |s| format!("{:.1}", filter_hours(s, FilterClass::Narrowband))),
+            segments.map_or(String::new(), |s| csv_text(&filter_segments_text(s, observer, preferences))),
+            fill,
+            fit.description()
+        ));
+    }
+    csv
+}
+
+// One CSV row per whole hour of the night in local time, with a column for the altitude of each
+// target
+pub(crate) fn hourly_conditions_csv(observer: &Observer, hours: &[HourConditions], targets: &[Target],
+                                    preferences: &Preferences) -> Vec<String> {
+    let mut csv: Vec<String> = Vec::new();
+    let mut header = "time,sun_altitude_deg,moon_altitude_deg,moon_illumination_pct,sky,lst_hours".to_string();
+    for target in targets {
+        header.push_str(&format!(",{}", csv_text(&format!("{} altitude_deg", target.name))));
+    }
+    csv.push(header + "\n");
+    for hour in hours {
+        let mut row = format!("{},{:.1},{:.1},{:.0},{},{:.3}", format_local(observer.local_time(hour.jd), preferences),
+                              hour.sun_altitude, hour.moon_altitude, hour.moon_illumination * 100.0,
+                              hour.sky.name(), hour.lst);
+        for altitude in &hour.target_altitudes {
+            row.push_str(&format!(",{:.1}", altitude));
+        }
+        csv.push(row + "\n");
+    }
+    csv
+}
+
+pub fn hourly_conditions_report(observer: &Observer, time: &Time, targets: &[Target], preferences: &Preferences) {
+    let hours = hourly_conditions(observer, time, targets, preferences.calculation);
+    let stamp = export_stamp(observer, &export_night(time), &Time::now());
+    let lines = format!("# {}\n{}", stamp, hourly_conditions_csv(observer, &hours, targets, preferences).join(""));
+
+    let mut f = File::create(HOURLY_CONDITIONS_FILE).expect("Unable to create file");
+    f.write_all(lines.as_bytes()).expect("Unable to write data");
+}
+
+pub fn up_tonight_report(observer: &Observer, time: &Time, environment: &Environment,
+                         constraints: &Constraints, targets: &[Target], equipment: &Equipment,
+                         preferences: &Preferences) {
+    let up = up_tonight(observer, time, environment, constraints, targets);
+    let up_targets: Vec<Target> = up.iter().map(|o| o.target.clone()).collect();
+    let filters = night_filter_plan(observer, time, environment, constraints, &up_targets);
+    let stamp = export_stamp(observer, &export_night(time), &Time::now());
+    let lines = format!("# {}\n{}", stamp,
+                        up_tonight_section(observer, &up, filters.as_deref(), equipment.active_field_of_view(),
+                                           preferences).join(""));
+
+    let mut f = File::create(UP_TONIGHT_REPORT_FILE).expect("Unable to create file");
+    f.write_all(lines.as_bytes()).expect("Unable to write data");
+}
+
+// One CSV row per mosaic panel, north to south and east to west, times in local time. The window
+// columns are empty when the night has no observation window or the panel never meets the
+// altitude constraints.
+pub(crate) fn mosaic_section(observer: &Observer, target: &Target, mosaic: &Mosaic, windows: Option<&[PanelWindow]>,
+                             preferences: &Preferences) -> Vec<String> {
+    let local = |jd: Option<f64>| jd.map_or(String::new(), |jd| format_local(observer.local_time(jd), preferences));
+    let mut csv: Vec<String> = Vec::new();
+    csv.push("target,panel,row,column,ra_deg,dec_deg,ra_hms,dec_dms,start,end,observable_hours\n".to_string());
+    for (i, panel) in mosaic.panels.iter().enumerate() {
+        let window = windows.and_then(|windows| windows.get(i));
+        csv.push(format!(
+            "{},{},{},{},{:.4},{:.4},{},{},{},{},{}\n",
+            csv_text(&target.name),
+            panel.name(),
+            panel.row + 1,
+            panel.column + 1,
+            panel.ra,
+            panel.dec,
+            format_ra_hms(panel.ra),
+            format_dec_dms(panel.dec),
+            local(window.and_then(|w| w.start)),
+            local(window.and_then(|w| w.end)),
+            window.map_or(String::new(), |w| format!("{:.1}", w.hours))
+        ));
+    }
+    csv
+}
+
+/// Panel centers of a mosaic and their windows in the night of `time`, as CSV for the capture
+/// software
+pub fn mosaic_report(observer: &Observer, time: &Time, environment: &Environment, constraints: &Constraints,
+                     target: &Target, mosaic: &Mosaic, preferences: &Preferences) {
+    let windows = night_panel_windows(mosaic, observer, time, environment, constraints);
+    let stamp = export_stamp(observer, &export_night(time), &Time::now());
+    let lines = format!("# {}\n# {} x {} panels covering {:.1}' x {:.1}'\n{}", stamp, mosaic.columns, mosaic.rows,
+                        mosaic.width, mosaic.height,
+                        mosaic_section(observer, target, mosaic, windows.as_deref(), preferences).join(""));
+
+    let mut f = File::create(MOSAIC_REPORT_FILE).expect("Unable to create file");
+    f.write_all(lines.as_bytes()).expect("Unable to write data");
+}
+
+// Best time annotation of a target for the observing apps, in local time
+fn best_time_note(observability: Option<&Observability>, observer: &Observer, preferences: &Preferences) -> String {
+    match observability {
+        Some(o) if o.hours > 0.0 => format!(
+            "Best {} at {:.0} deg, {:.1} h within constraints",
+            format_local(observer.local_time(o.best_jd), preferences),
+            o.max_altitude,
+            o.hours
+        ),
+        Some(_) => "Not within constraints tonight".to_string(),
+        None => "No observation window tonight".to_string(),
+    }
+}
+
+// Catalogs whose designations SkySafari finds, a prefix followed by the number
+const SKYSAFARI_CATALOGS: [&str; 12] = ["M", "NGC", "IC", "C", "Sh2-", "B", "Cr", "Mel", "UGC", "PGC", "HD", "HIP"];
+
+// Catalog designation of a target name like "M42", "ngc 253" or "Sh2-155", written the way
+// SkySafari lists them ("M 42", "NGC 253"). None for common names like "Orion Nebula".
+fn catalog_designation(name: &str) -> Option<String> {
+    let name = name.trim();
+    SKYSAFARI_CATALOGS.iter().find_map(|catalog| {
+        let prefix = name.get(..catalog.len())?;
+        let number = name[catalog.len()..].trim_start();
+        let is_number = !number.is_empty() && number.chars().all(|c| c.is_ascii_digit());
+        match (prefix.eq_ignore_ascii_case(catalog), is_number, catalog.ends_with('-')) {
+            (true, true, true) => Some(format!("{}{}", catalog, number)),
+            (true, true, false) => Some(format!("{} {}", catalog, number)),
+            _ => None,
+        }
+    })
+}
+
+// SkySafari observing list, objects are matched by name and by catalog number when the name is a
+// catalog designation like NGC 253 or M 42, the coordinates and the best time go in the comment
+pub(crate) fn skysafari_list(observer: &Observer, targets: &[Target],
+                             observability: Option<&[Observability]>, preferences: &Preferences,
+                             stamp: &str) -> String {
+    let mut list = String::from("SkySafariObservingListVersion=3.0\nSortedBy=Default Order\n");
+    for (i, target) in targets.iter().enumerate() {
+        let note = best_time_note(observability.and_then(|o| o.get(i)), observer, preferences);
+        list.push_str("SkyObject=BeginObject\n");
+        list.push_str("\tObjectID=4,-1,-1\n");
+        list.push_str(&format!("\tCommonName={}\n", target.name));
+        if let Some(designation) = catalog_designation(&target.name) {
+            list.push_str(&format!("\tCatalogNumber={}\n", designation));
+        }
+        list.push_str(&format!("\tComment={} - RA {} Dec {} - {}\n", note, format_ra_hms(target.ra),
+                               format_dec_dms(target.dec), stamp));
+        list.push_str("EndObject=SkyObject\n");
+    }
+    list
+}
+
+// AstroPlanner tab delimited import, one object per line with sexagesimal coordinates
+pub(crate) fn astroplanner_list(observer: &Observer, targets: &[Target],
+                                observability: Option<&[Observability]>, preferences: &Preferences,
+                                stamp: &str) -> String {
+    let mut list = String::from("ID\tRA\tDec\tSize\tNotes\n");
+    for (i, target) in targets.iter().enumerate() {
+        let note = best_time_note(observability.and_then(|o| o.get(i)), observer, preferences);
+        list.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{} - {}\n",
+            target.name.replace('\t', " "),
+            format_ra_hms(target.ra),
+            format_dec_dms(target.dec),
+            if target.size > 0.0 { format!("{}'", target.size) } else { String::new() },
+            note,
+            stamp
+        ));
+    }
+    list
+}
+
+// Observability of the night of `time` and stamp for the observing list exports
+fn observing_list_night(observer: &Observer, time: &Time, environment: &Environment,
+                        constraints: &Constraints, targets: &[Target]) -> (Option<Vec<Observability>>, String) {
+    let observability = night_observability(observer, time, environment, constraints, targets);
+    (observability, export_stamp(observer, &export_night(time), &Time::now()))
+}
+
+pub fn skysafari_report(observer: &Observer, time: &Time, environment: &Environment,
+                        constraints: &Constraints, targets: &[Target], preferences: &Preferences) {
+    let (observability, stamp) = observing_list_night(observer, time, environment, constraints, targets);
+    let list = skysafari_list(observer, targets, observability.as_deref(), preferences, &stamp);
+
+    let mut f = File::create(SKYSAFARI_LIST_FILE).expect("Unable to create file");
+    f.write_all(list.as_bytes()).expect("Unable to write data");
+}
+
+pub fn astroplanner_report(observer: &Observer, time: &Time, environment: &Environment,
+                           constraints: &Constraints, targets: &[Target], preferences: &Preferences) {
+    let (observability, stamp) = observing_list_night(observer, time, environment, constraints, targets);
+    let list = astroplanner_list(observer, targets, observability.as_deref(), preferences, &stamp);
+
+    let mut f = File::create(ASTROPLANNER_LIST_FILE).expect("Unable to create file");
+    f.write_all(list.as_bytes()).expect("Unable to write data");
+}
+
+// One CSV row per alignment star, by azimuth, with the session start in local time
+pub(crate) fn alignment_csv(observer: &Observer, jd: f64, stars: &[AlignmentStar],
+                            preferences: &Preferences) -> String {
+    let time = format_local(observer.local_time(jd), preferences);
+    let mut csv = String::from("name,designation,magnitude,ra_deg,dec_deg,time,altitude_deg,azimuth_deg\n");
+    for a in stars {
+        csv.push_str(&format!(
+            "{},{},{:.2},{:.4},{:.4},{},{:.1},{:.1}\n",
+            csv_text(a.star.name),
+            csv_text(a.star.designation),
+            a.star.magnitude,
+            a.star.ra,
+            a.star.dec,
+            time,
+            a.altitude,
+            a.azimuth
+        ));
+    }
+    csv
+}
+
+pub fn alignment_report(observer: &Observer, time: &Time, environment: &Environment,
+                        preferences: &Preferences) {
+    let stamp = export_stamp(observer, &export_night(time), &Time::now());
+    let csv = match session_start(observer, time, environment, preferences.calculation) {
+        Some(jd) => alignment_csv(observer, jd, &alignment_stars(observer, jd, ALIGNMENT_STARS), preferences),
+        None => String::new(),
+    };
+    let lines = format!("# {}\n{}", stamp, csv);
+
+    let mut f = File::create(ALIGNMENT_STARS_FILE).expect("Unable to create file");
+    f.write_all(lines.as_bytes()).expect("Unable to write data");
+}
+
+// Rise, transit and set of every body during the night, in local time
+pub fn solar_system_events(observer: &Observer, time: &Time) -> Vec<BodyEvents> {
+    let (night_start, night_end) = night_window(observer, time);
+    BODIES.iter().map(|&body| body_events(body, observer, night_start, night_end)).collect()
+}
+
+fn format_event_jd(jd: Option<f64>, observer: &Observer, preferences: &Preferences) -> String {
+    jd.map_or("-".to_string(), |jd| format_local(observer.local_time(jd), preferences))
+}
+
+pub(crate) fn solar_system_events_section(observer: &Observer, events: &[BodyEvents],
+                                          preferences: &Preferences) -> Vec<String> {
+    let width = time_width(preferences);
+    let mut section: Vec<String> = Vec::new();
+    section.push(format!("{} ({}):", tr("Rise, transit and set"), tr("local time")));
+    for (body, event) in BODIES.iter().zip(events) {
+        // altitude at the transit, the highest of the night
+        let altitude = event.transit.map_or("-".to_string(), |jd| {
+            format!("{:.1}°", body_position(*body, observer, jd).altitude)
+        });
+        section.push(format!(
+            "\n   - {:8}: {} {:width$}   {} {:width$} {:>6}   {} {:width$}",
+            tr(body.name()),
+            tr("rise"),
+            format_event_jd(event.rise, observer, preferences),
+            tr("transit"),
+            format_event_jd(event.transit, observer, preferences),
+            altitude,
+            tr("set"),
+            format_event_jd(event.set, observer, preferences)
+        ));
+    }
+    section.push("\n\n".to_string());
+    section
+}
+
+// Positions of the Sun, the Moon and the planets at a time
+pub(crate) fn solar_system_section(observer: &Observer, jd: f64, preferences: &Preferences) -> Vec<String> {
+    let mut section: Vec<String> = Vec::new();
+    section.push(format!("{} {} ({}), {} {}:", tr("Positions at"), format_local(observer.local_time(jd), preferences),
+                         tr("local time"), tr("equation of time"), format_equation_of_time(equation_of_time(jd))));
+    for body in BODIES {
+        let p = body_position(body, observer, jd);
+        section.push(format!(
+            "\n   - {:8}: RA {:>12}   Dec {:>13}   alt {:5.1}°   az {:5.1}°   mag {:6.2}   size {:>7}   elong {:>8}",
+            tr(body.name()),
+            format_ra_hms(p.ra),
+            format_dec_dms(p.dec),
+            p.altitude,
+            p.azimuth,
+            p.magnitude,
+            format_diameter(p.diameter),
+            format_elongation(p.elongation)
+        ));
+    }
+    section.push("\n\n".to_string());
+    section
+}
+
+/// Solar system ephemeris at the selected time, or across the night every `interval` minutes
+pub fn solar_system_report(observer: &Observer, time: &Time, environment: &Environment,
+                           interval: Option<u32>, preferences: &Preferences) {
+    let mut lines = header_section().join("");
+    lines += &*stamp_section(observer, time, &Time::now()).join("");
+    lines += &*observer_section(observer, preferences).join("");
+    lines += &*environment_section(environment).join("");
+    lines += &*night_section(time).join("");
+
+    let events = solar_system_events(observer, time);
+    lines += &*solar_system_events_section(observer, &events, preferences).join("");
+    for jd in ephemeris_times(observer, time, environment, interval, preferences.calculation) {
+        lines += &*solar_system_section(observer, jd, preferences).join("");
+    }
+
+    let mut f = File::create(SOLAR_SYSTEM_REPORT_FILE).expect("Unable to create file");
+    f.write_all(lines.as_bytes()).expect("Unable to write data");
+}
+
+// One CSV row per body and time, times in local time
+pub(crate) fn solar_system_csv(observer: &Observer, times: &[f64], events: &[BodyEvents],
+                               preferences: &Preferences) -> String {
+    let mut csv = String::from(
+        "time,body,ra_deg,dec_deg,altitude_deg,azimuth_deg,distance_au,magnitude,diameter_arcsec,elongation_deg,rise,\
+         transit,set\n",
+    );
+    for &jd in times {
+        let time = format_local(observer.local_time(jd), preferences);
+        for (body, event) in BODIES.iter().zip(events) {
+            let p = body_position(*body, observer, jd);
+            csv.push_str(&format!(
+                "{},{},{:.4},{:.4},{:.2},{:.2},{:.6},{:.2},{:.1},{:.2},{},{},{}\n",
+                time,
+                body.name(),
+                p.ra,
+                p.dec,
+                p.altitude,
+                p.azimuth,
+                p.distance,
+                p.magnitude,
+                p.diameter,
+                p.elongation,
+                format_event_jd(event.rise, observer, preferences),
+                format_event_jd(event.transit, observer, preferences),
+                format_event_jd(event.set, observer, preferences)
+            ));
+        }
+    }
+    csv
+}
+
+pub fn solar_system_csv_report(observer: &Observer, time: &Time, environment: &Environment,
+                               interval: Option<u32>, preferences: &Preferences) {
+    let stamp = export_stamp(observer, &export_night(time), &Time::now());
+    let times = ephemeris_times(observer, time, environment, interval, preferences.calculation);
+    let events = solar_system_events(observer, time);
+    let lines = format!("# {}\n{}", stamp, solar_system_csv(observer, &times, &events, preferences));
+
+    let mut f = File::create(SOLAR_SYSTEM_CSV_FILE).expect("Unable to create file");
     f.write_all(lines.as_bytes()).expect("Unable to write data");
 }
 
-// TODO Implement up tonight report based on constraints
-// TODO Add targets
-pub fn up_tonight_report(observer: Observer, time: Time, environment: Environment,
-                         constraints: Constraints) {
+// Oppositions and greatest elongations in local time, with the date since they span months
+pub(crate) fn planet_events_section(observer: &Observer, jd_start: f64, jd_end: f64, events: &[PlanetEvent])
+                                    -> Vec<String> {
+    let date = |jd: f64| {
+        let local = observer.local_time(jd);
+        format!("{} {}", local.to_string(Some("yyyymmdd")), local.to_string(Some("hhmm")))
+    };
+    let mut section: Vec<String> = Vec::new();
+    section.push(format!("{} {} {} {} ({}):", tr("Oppositions and greatest elongations from"), date(jd_start),
+                         tr("to"), date(jd_end), tr("local time")));
+    if events.is_empty() {
+        section.push(format!("\n   - {}", tr("No oppositions or greatest elongations in this range")));
+    }
+    for event in events {
+        section.push(format!(
+            "\n   - {:16}: {:8} {:24}   elong {:>8}   mag {:5.1}   size {:>6}   distance {:.3} au",
+            date(event.jd),
+            tr(event.body.name()),
+            tr(event.kind.description()),
+            format_elongation(event.elongation),
+            event.magnitude,
+            format_diameter(event.diameter),
+            event.distance
+        ));
+    }
+    section.push("\n\n".to_string());
+    section
+}
+
+/// Oppositions of Mars, Jupiter and Saturn and greatest elongations of Mercury and Venus in the
+/// `months` following the selected time
+pub fn planet_events_report(observer: &Observer, time: &Time, months: u32) {
+    const DAYS_PER_MONTH: f64 = 30.436_875;
+    let jd_start = time.to_jd();
+    let jd_end = jd_start + months as f64 * DAYS_PER_MONTH;
+    let mut lines = header_section().join("");
+    lines += &stamp_section(observer, time, &Time::now()).join("");
+    lines += &planet_events_section(observer, jd_start, jd_end, &planet_events(jd_start, jd_end)).join("");
+
+    let mut f = File::create(PLANET_EVENTS_REPORT_FILE).expect("Unable to create file");
+    f.write_all(lines.as_bytes()).expect("Unable to write data");
+}
+
+#[cfg(test)]
+mod test {
+    use crate::application::calculation::CalculationSettings;
+    use crate::application::constraint::Constraints;
+    use crate::application::environment::Environment;
+    use crate::application::equipment::FieldOfView;
+    use crate::application::observability::{night_observability, Observability};
+    use crate::application::observer::Observer;
+    use crate::application::preferences::Preferences;
+    use crate::application::formatting::NightEvents;
+    use crate::application::reports::{almanac, almanac_csv, almanac_section, astroplanner_list, calendar_nights, catalog_designation, darkness_calendar,
+                                      darkness_calendar_section, darkness_calendar_svg, darkness_print_html,
+                                      dew_section, hourly_dew, hourly_section, html_text_section, site_comparison_section,
+                                      skysafari_list, sun_section, up_tonight_section, SiteNight};
+    use crate::application::timeline::hourly_conditions;
+    use crate::application::target::Target;
+    use crate::application::time::Time;
+
+    #[test]
+    fn test_darkness_calendar() {
+        // Amsterdam, no astronomical darkness around the June solstice
+        let observer = Observer::location(None, "52.37", "4.9", 0, "1");
+        let nights = darkness_calendar(&observer, 2024);
+        assert_eq!(nights.len(), 366);
+        assert_eq!(nights[0].date.to_string(Some("yyyymmdd")), "2024-01-01");
+        assert_eq!(nights[365].date.to_string(Some("yyyymmdd")), "2024-12-31");
+        assert!(nights.iter().all(|night| night.moon_free <= night.darkness));
+        assert!(nights.iter().all(|night| (0.0..=1.0).contains(&night.illumination)));
+        let june_21 = &nights[172];
+        assert_eq!(june_21.date.to_string(Some("yyyymmdd")), "2024-06-21");
+        assert_eq!(june_21.darkness, 0.0);
+        assert!(nights[355].darkness > 12.0);
+
+        // one row per night after the header, a cell per night in the image
+        let csv = darkness_calendar_section(&nights);
+        assert_eq!(csv.len(), 367);
+        assert!(csv[1].starts_with("2024-01-01,"));
+        let svg = darkness_calendar_svg(&observer, 2024, &nights);
+        assert_eq!(svg.matches("<rect x=").count(), 366 + 5);
+    }
+
+    #[test]
+    fn test_almanac() {
+        // Amsterdam in June, no astronomical twilight around the solstice
+        let observer = Observer::location(None, "52.37", "4.9", 0, "2");
+        let days = almanac(&observer, 2024, 6, CalculationSettings::default());
+        assert_eq!(days.len(), 30);
+        assert_eq!(days[0].date.to_string(Some("yyyymmdd")), "2024-06-01");
+        assert_eq!(days[29].date.to_string(Some("yyyymmdd")), "2024-06-30");
+        let june_21 = &days[20];
+        assert!(june_21.astronomical_start.is_none() && june_21.astronomical_end.is_none());
+        assert!(june_21.sunrise.unwrap() < june_21.sunset.unwrap());
+        assert!(june_21.civil_start.unwrap() < june_21.sunrise.unwrap());
+        assert!(june_21.sunset.unwrap() < june_21.civil_end.unwrap());
+        assert!(days.iter().all(|day| (0.0..=1.0).contains(&day.illumination)));
+
+        // title, two title lines, a row per day, the note and the June solstice
+        let section = almanac_section(&observer, 2024, 6, &days, &Preferences::default());
+        assert_eq!(section[0], "Almanac June 2024 (local time):");
+        assert!(section[2].starts_with("\nDate "));
+        assert!(section[3].starts_with("\n2024-06-01  "));
+        assert!(section[23].starts_with("\n2024-06-21  "));
+        assert!(section[33].starts_with("\n\n- the event does not happen that day"));
+        assert_eq!(section[34], "Seasons:\n");
+        assert!(section[35].contains("Summer solstice"));
 
-}
\ No newline at end of file
+        // header and a line per day, empty times for the events that do not happen
+        let csv = almanac_csv(&observer, &days);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 31);
+        assert_eq!(lines[0], "date,astronomical_start,nautical_start,civil_start,sunrise,sunset,civil_end,\
+nautical_end,astronomical_end,moonrise,moonset,moon_illumination_pct");
+        let columns: Vec<&str> = lines[21].split(',').collect();
+        assert_eq!(columns.len(), 12);
+        assert_eq!(columns[0], "2024-06-21");
+        assert_eq!((columns[1], columns[8]), ("", ""));
+        assert_eq!(columns[4].len(), 5);
+    }
+
+    #[test]
+    fn test_calendar_nights_parallel() {
+        // the nights computed in parallel are the ones computed one at a time, in date order
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let start = Time::new(2025, 3, 1, 12, 0, 0).to_jd();
+        let nights = calendar_nights(&observer, start, 10);
+        assert_eq!(nights.len(), 10);
+        for (i, night) in nights.iter().enumerate() {
+            let serial = &calendar_nights(&observer, start + i as f64, 1)[0];
+            assert_eq!(format!("{:?}", night), format!("{:?}", serial));
+        }
+    }
+
+    #[test]
+    fn test_html_text_section() {
+        // title without the colon, the items without their bullets, kept aligned and escaped
+        let lines = vec!["Target plan (hh:mm):".to_string(),
+                         "\n   - M42 & M43   : 02:00".to_string(),
+                         "\n   - ".to_string(),
+                         "\n   - <Idle>      : 00:30".to_string(),
+                         "\n\n".to_string()];
+        assert_eq!(html_text_section(&lines),
+                   "<h2>Target plan (hh:mm)</h2>\n<pre>M42 &amp; M43   : 02:00\n&lt;Idle&gt;      : 00:30</pre>\n");
+        assert_eq!(html_text_section(&[]), "");
+    }
+
+    #[test]
+    fn test_darkness_print_html() {
+        // Paris in January, M42 is planned during the night
+        let observer = Observer::location(Some("Paris".to_string()), "48.85", "2.35", 0, "1");
+        let time = Time::new(2025, 1, 20, 12, 0, 0);
+        let targets = vec![Target::new("M42", 83.82, -5.39, 85.0)];
+        let constraints = Constraints { min_altitude: 20, max_altitude: 90, ..Constraints::default() };
+        let generated = Time::new(2025, 1, 20, 10, 0, 0);
+        let html = darkness_print_html(&observer, &time, &Environment::default(), &constraints, &targets,
+                                       &Preferences::default(), &generated);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<body onload=\"window.print()\">"));
+        assert!(html.contains("<h1>SkyCalc - Paris - "));
+        assert!(!html.contains("{{"));
+        // the timeline is always there, the sections in report order
+        assert!(html.contains("<svg viewBox="));
+        let order = ["<h2>Observatory</h2>", "<h2>Sun</h2>", "<h2>Moon</h2>", "<h2>Darkness</h2>",
+                     "<h2>Target plan (hh:mm)</h2>", "<h2>Alignment stars at nautical dusk ("];
+        let positions: Vec<usize> = order.iter().map(|title| html.find(title).expect(title)).collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(html.contains("\nM42 "));
+    }
+
+    #[test]
+    fn test_time_columns() {
+        // the widest times, 12-hour clock with seconds, keep the columns of every row aligned
+        let observer = Observer::location(None, "48.85", "2.35", 0, "1");
+        let time = Time::new(2025, 1, 20, 12, 0, 0);
+        let environment = Environment::default();
+        let preferences = Preferences { hour12: true, show_seconds: true, ..Preferences::default() };
+        let column = |line: &String, c: char| line.chars().position(|x| x == c);
+
+        let events = NightEvents::new(&observer, &time, &environment, &preferences);
+        let sun = sun_section(&events, &preferences);
+        assert!(sun[1].contains(" PM "));
+        let second_column: Vec<Option<usize>> = sun[1..sun.len() - 1]
+            .iter()
+            .map(|line| line.match_indices(": ").nth(1).map(|(i, _)| line[..i].chars().count()))
+            .collect();
+        assert!(second_column.iter().all(|i| i.is_some() && *i == second_column[0]), "{:?}", sun);
+
+        let hours = hourly_conditions(&observer, &time, &[], preferences.calculation);
+        let dew = hourly_dew(&observer, &time, &environment, &hours, &[], &preferences);
+        let hourly = hourly_section(&observer, &hours, &dew, &preferences);
+        let degrees: Vec<Option<usize>> = hourly[2..hourly.len() - 1].iter().map(|line| column(line, '°')).collect();
+        assert!(degrees.len() > 10 && degrees.iter().all(|i| *i == degrees[0]), "{:?}", hourly);
+        // the titles are right aligned over the values
+        let sun_title = hourly[1].find("Sun").map(|i| hourly[1][..i].chars().count() + 2);
+        assert_eq!(sun_title, degrees[0]);
+
+        // every hour after sunset has its dew risk in the last column, under its title
+        assert_eq!(dew.len(), hours.len());
+        let dew_title = hourly[1].find("Dew").map(|i| hourly[1][..i].chars().count());
+        let dew_column = |line: &String| line.rfind("°C").map(|i| line[..i].chars().count() - 4);
+        assert!(hourly[2..hourly.len() - 1].iter().all(|line| dew_column(line) == dew_title), "{:?}", hourly);
+
+        // dew heaters from an hour of the night with humid air
+        let humid = Environment::default().new(1013, 10.0, 90);
+        let dew = dew_section(&observer, &time, &humid, &[], &preferences);
+        assert_eq!(dew.len(), 4);
+        assert!(dew[1].contains("rough estimate"), "{:?}", dew);
+        assert!(dew[2].contains(" PM") || dew[2].contains(" AM"), "{:?}", dew);
+    }
+
+    #[test]
+    fn test_site_comparison() {
+        // same night in Sao Paulo and Amsterdam at the July new Moon, no astronomical darkness north
+        let time = Time::new(2024, 7, 5, 12, 0, 0);
+        let (environment, preferences) = (Environment::default(), Preferences::default());
+        let south = SiteNight::new("Sao Paulo", &Observer::location(None, "-23.1", "-46.5", 780, "-3"), &time,
+                                   &environment, &preferences);
+        let north = SiteNight::new("Amsterdam observatory of the north", &Observer::location(None, "52.37", "4.9", 0,
+                                   "1"), &time, &environment, &preferences);
+        assert!(south.darkness.unwrap() > 10.0);
+        assert!(south.moon_free <= south.darkness.unwrap());
+        assert_eq!(south.moon_interference(), Some(south.darkness.unwrap() - south.moon_free));
+        assert_eq!(north.darkness.unwrap_or(0.0), 0.0);
+        assert_eq!(north.moon_free, 0.0);
+        assert_eq!(north.moon_interference().unwrap_or(0.0), 0.0);
+
+        let section = site_comparison_section(&[south, north]);
+        assert_eq!(section[0], "Site comparison:");
+        // one column per site, long names cut to the column
+        assert_eq!(section[1], "\n                           Sao Paulo         Amsterdam observa");
+        assert!(section[2].starts_with("\n   Darkness start          "));
+        assert!(section.iter().any(|line| line.starts_with("\n\n   Most Moon free darkness: Sao Paulo (")));
+    }
+
+    #[test]
+    fn test_up_tonight_fov() {
+        // 120' x 80' field: M42 is too large, M57 small, NGC 7000 fits and the size of M45 unknown
+        let observer = Observer::location(None, "48.85", "2.35", 0, "1");
+        let up: Vec<Observability> = [("M42", 85.0), ("M57", 1.4), ("NGC 7000", 40.0), ("M45", 0.0)]
+            .into_iter()
+            .map(|(name, size)| Observability {
+                target: Target::new(name, 83.82, -5.39, size),
+                fraction: 0.5,
+                hours: 4.0,
+                max_altitude: 45.0,
+                best_jd: Time::new(2025, 1, 20, 22, 0, 0).to_jd(),
+                moon_separation: None,
+            })
+            .collect();
+        let fov = FieldOfView { width: 120.0, height: 80.0, scale: 1.5 };
+        let preferences = Preferences::default();
+
+        // fill of the short side in % and the fit, both empty for an unknown size
+        let csv = up_tonight_section(&observer, &up, None, Some(fov), &preferences);
+        assert!(csv[0].ends_with(",fov_fill_pct,fov_fit\n"));
+        let columns = |row: &str| row.trim_end().rsplitn(3, ',').take(2).map(String::from).collect::<Vec<_>>();
+        assert_eq!(columns(&csv[1]), ["too large", "106"]);
+        assert_eq!(columns(&csv[2]), ["small", "2"]);
+        assert_eq!(columns(&csv[3]), ["fits", "50"]);
+        assert_eq!(columns(&csv[4]), ["", ""]);
+
+        // no camera and telescope active, the columns stay empty
+        let csv = up_tonight_section(&observer, &up, None, None, &preferences);
+        assert!(csv[1..].iter().all(|row| row.ends_with(",,\n")));
+    }
+
+    #[test]
+    fn test_catalog_designation() {
+        assert_eq!(catalog_designation("M42").as_deref(), Some("M 42"));
+        assert_eq!(catalog_designation("ngc 253").as_deref(), Some("NGC 253"));
+        assert_eq!(catalog_designation("Mel 111").as_deref(), Some("Mel 111"));
+        assert_eq!(catalog_designation("Sh2-155").as_deref(), Some("Sh2-155"));
+        assert_eq!(catalog_designation("Orion Nebula"), None);
+        assert_eq!(catalog_designation("M42 core"), None);
+        assert_eq!(catalog_designation("Cave Nebula"), None);
+    }
+
+    #[test]
+    fn test_observing_lists() {
+        // Paris in January, both targets well up during the night
+        let observer = Observer::location(None, "48.85", "2.35", 0, "1");
+        let time = Time::new(2025, 1, 20, 12, 0, 0);
+        let targets = vec![Target::new("M42", 83.82, -5.39, 85.0), Target::new("Pleiades", 56.75, 24.12, 0.0)];
+        let constraints = Constraints { min_altitude: 20, max_altitude: 90, ..Constraints::default() };
+        let observability = night_observability(&observer, &time, &Environment::default(), &constraints, &targets);
+        let preferences = Preferences::default();
+
+        let skylist = skysafari_list(&observer, &targets, observability.as_deref(), &preferences, "stamp");
+        assert!(skylist.starts_with("SkySafariObservingListVersion=3.0\n"));
+        assert_eq!(skylist.matches("SkyObject=BeginObject").count(), 2);
+        assert!(skylist.contains("\tCommonName=M42\n\tCatalogNumber=M 42\n"));
+        assert!(skylist.contains("\tCommonName=Pleiades\n\tComment="));
+        assert!(!skylist.contains("CatalogNumber=Pleiades"));
+
+        let astroplanner = astroplanner_list(&observer, &targets, observability.as_deref(), &preferences, "stamp");
+        let lines: Vec<&str> = astroplanner.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "ID\tRA\tDec\tSize\tNotes");
+        let columns: Vec<&str> = lines[1].split('\t').collect();
+        assert_eq!(columns[0], "M42");
+        assert_eq!(columns[3], "85'");
+        assert!(columns[4].starts_with("Best ") && columns[4].ends_with(" - stamp"));
+        assert_eq!(lines[2].split('\t').nth(3), Some(""));
+    }
+}
@@ -0,0 +1,318 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Horizon crossing solver shared by the Sun, the Moon and any other body with an altitude
+// function. A coarse scan brackets the crossing and bisection refines it to about a second,
+// instead of interpolating linearly on a fine grid. Passes shorter than a coarse step, such as a
+// Moon grazing the horizon at high latitudes, are found at the altitude extremum between the steps.
+
+/// Number of steps of the coarse scan over the search window (20 minutes for one day)
+pub const COARSE_STEPS: usize = 72;
+
+/// Refinement stops when the bracket is smaller than this (one second, in days)
+pub const TOLERANCE: f64 = 1.0 / 86_400.0;
+
+/// NoCrossing enum
+///
+/// Reason why no crossing was found in the search window.
+///
+/// # Variants
+///
+/// * `AlwaysAbove` - The altitude stays above the horizon
+/// * `AlwaysBelow` - The altitude stays below the horizon
+/// * `OtherDirection` - The horizon is only crossed in the opposite direction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoCrossing {
+    AlwaysAbove,
+    AlwaysBelow,
+    OtherDirection,
+}
+
+/// Bracket struct
+///
+/// Coarse scan step holding a horizon crossing, the altitudes are relative to the horizon.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bracket {
+    pub jd_low: f64,
+    pub altitude_low: f64,
+    pub jd_high: f64,
+    pub altitude_high: f64,
+}
+
+/// Coarse scan step holding the first horizon crossing of a body between two Julian Dates, the
+/// first stage of `find_crossing`
+pub fn bracket_crossing<F>(
+    altitude: &F,
+    jd_start: f64,
+    jd_end: f64,
+    horizon: f64,
+    is_rising: bool,
+) -> Result<Bracket, NoCrossing>
+where
+    F: Fn(f64) -> f64,
+{
+    let step = (jd_end - jd_start) / COARSE_STEPS as f64;
+    let mut above = false;
+    let mut below = false;
+    let mut before = None;
+    let mut previous = (jd_start, altitude(jd_start) - horizon);
+
+    for i in 1..=COARSE_STEPS {
+        let jd = jd_start + step * i as f64;
+        let current = (jd, altitude(jd) - horizon);
+        above |= previous.1 >= 0.0;
+        below |= previous.1 < 0.0;
+
+        // a pass between the steps, the step before was already scanned for a crossing
+        let hidden = before.and_then(|before| {
+            hidden_crossing(altitude, before, previous, current, horizon, is_rising)
+        });
+        if let Some(bracket) = hidden {
+            return Ok(bracket);
+        }
+
+        let crosses = if is_rising {
+            previous.1 < 0.0 && current.1 >= 0.0
+        } else {
+            previous.1 >= 0.0 && current.1 < 0.0
+        };
+        if crosses {
+            return Ok(Bracket {
+                jd_low: previous.0,
+                altitude_low: previous.1,
+                jd_high: current.0,
+                altitude_high: current.1,
+            });
+        }
+        before = Some(previous);
+        previous = current;
+    }
+    above |= previous.1 >= 0.0;
+    below |= previous.1 < 0.0;
+
+    match (above, below) {
+        (true, false) => Err(NoCrossing::AlwaysAbove),
+        (false, true) => Err(NoCrossing::AlwaysBelow),
+        _ => Err(NoCrossing::OtherDirection),
+    }
+}
+
+// Bracket of a pass starting and ending between three coarse steps. `middle` is the step of an
+// altitude maximum below the horizon or a minimum above it. The extremum of a smooth altitude is
+// at most a quarter of the larger change between the steps beyond `middle`, so it is only searched
+// when `middle` is that close to the horizon, and bracketed with the step on the side of the
+// crossing when it is on the other side of the horizon. The altitudes are relative to the horizon.
+fn hidden_crossing<F>(
+    altitude: &F,
+    before: (f64, f64),
+    middle: (f64, f64),
+    after: (f64, f64),
+    horizon: f64,
+    is_rising: bool,
+) -> Option<Bracket>
+where
+    F: Fn(f64) -> f64,
+{
+    let is_maximum = middle.1 < 0.0 && middle.1 >= before.1 && middle.1 >= after.1;
+    let is_minimum = middle.1 >= 0.0 && middle.1 <= before.1 && middle.1 <= after.1;
+    let change = (middle.1 - before.1).abs().max((after.1 - middle.1).abs());
+    if !(is_maximum || is_minimum) || middle.1.abs() > change / 4.0 {
+        return None;
+    }
+    let jd = extremum(|jd| altitude(jd) - horizon, before.0, after.0, is_maximum);
+    let extremum = (jd, altitude(jd) - horizon);
+    if (extremum.1 >= 0.0) != is_maximum {
+        return None;
+    }
+    // a maximum above the horizon rises before it and sets after it, a minimum below the other way
+    let (low, high) = if is_rising == is_maximum { (before, extremum) } else { (extremum, after) };
+    Some(Bracket { jd_low: low.0, altitude_low: low.1, jd_high: high.0, altitude_high: high.1 })
+}
+
+// Golden section search of the JD of the maximum (or minimum) of a function with a single
+// extremum between two JDs
+fn extremum<F>(f: F, jd_low: f64, jd_high: f64, maximum: bool) -> f64
+where
+    F: Fn(f64) -> f64,
+{
+    let sign = if maximum { 1.0 } else { -1.0 };
+    let ratio = (5.0_f64.sqrt() - 1.0) / 2.0;
+    let mut low = jd_low;
+    let mut high = jd_high;
+    while high - low > TOLERANCE {
+        let left = high - ratio * (high - low);
+        let right = low + ratio * (high - low);
+        if sign * f(left) > sign * f(right) {
+            high = right;
+        } else {
+            low = left;
+        }
+    }
+    (low + high) / 2.0
+}
+
+/// Find the first horizon crossing of a body between two Julian Dates
+///
+/// # Arguments
+///
+/// * `altitude` - Altitude of the body in degrees for a given JD
+/// * `jd_start` - Start of the search window
+/// * `jd_end` - End of the search window
+/// * `horizon` - Altitude of the horizon in degrees
+/// * `is_rising` - Search a rising (true) or a setting (false) crossing
+///
+/// # Returns
+///
+/// * `Result<f64, NoCrossing>` - JD of the crossing or the reason why there is none
+///
+/// # Examples
+///
+/// ```
+/// use skycalc::application::rise_set_solver::{find_crossing, TOLERANCE};
+///
+/// // altitude growing 1 degree per hour from -12 at jd 0.0
+/// let crossing = find_crossing(|jd| -12.0 + jd * 24.0, 0.0, 1.0, 0.0, true).unwrap();
+/// assert!((crossing - 0.5).abs() < TOLERANCE);
+/// ```
+pub fn find_crossing<F>(
+    altitude: F,
+    jd_start: f64,
+    jd_end: f64,
+    horizon: f64,
+    is_rising: bool,
+) -> Result<f64, NoCrossing>
+where
+    F: Fn(f64) -> f64,
+{
+    let bracket = bracket_crossing(&altitude, jd_start, jd_end, horizon, is_rising)?;
+    Ok(refine(&altitude, bracket.jd_low, bracket.jd_high, horizon, !is_rising))
+}
+
+// Bisection between two JDs whose altitudes are on opposite sides of the horizon
+fn refine<F>(altitude: &F, jd_low: f64, jd_high: f64, horizon: f64, low_above: bool) -> f64
+where
+    F: Fn(f64) -> f64,
+{
+    let mut low = jd_low;
+    let mut high = jd_high;
+    while high - low > TOLERANCE {
+        let middle = (low + high) / 2.0;
+        if (altitude(middle) >= horizon) == low_above {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    (low + high) / 2.0
+}
+
+#[cfg(test)]
+mod test {
+    use crate::application::rise_set_solver::{bracket_crossing, find_crossing, NoCrossing, COARSE_STEPS, TOLERANCE};
+    use std::f64::consts::TAU;
+
+    // altitude of a body culminating at jd 0.5 with the given maximum, one cycle per day
+    fn daily(maximum: f64) -> impl Fn(f64) -> f64 {
+        move |jd: f64| maximum - 40.0 + 40.0 * (TAU * (jd - 0.5)).cos()
+    }
+
+    #[test]
+    fn test_find_crossing() {
+        // altitude growing 1 degree per hour from -12 at jd 0.0
+        let crossing = find_crossing(|jd| -12.0 + jd * 24.0, 0.0, 1.0, 0.0, true).unwrap();
+        assert!((crossing - 0.5).abs() < TOLERANCE);
+        // the same crossing against a horizon 6 degrees up
+        let crossing = find_crossing(|jd| -12.0 + jd * 24.0, 0.0, 1.0, 6.0, true).unwrap();
+        assert!((crossing - 0.75).abs() < TOLERANCE);
+        // setting at the mirror time
+        let crossing = find_crossing(|jd| 12.0 - jd * 24.0, 0.0, 1.0, 0.0, false).unwrap();
+        assert!((crossing - 0.5).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn test_first_crossing() {
+        // a body up for 6 hours around jd 0.5 rises at 0.375 and sets at 0.625
+        let altitude = daily(40.0 - 40.0 * (TAU / 8.0).cos());
+        let rise = find_crossing(&altitude, 0.0, 1.0, 0.0, true).unwrap();
+        let set = find_crossing(&altitude, 0.0, 1.0, 0.0, false).unwrap();
+        assert!((rise - 0.375).abs() < TOLERANCE);
+        assert!((set - 0.625).abs() < TOLERANCE);
+        // only the first of two rises in a two day window
+        let rise = find_crossing(&altitude, 0.0, 2.0, 0.0, true).unwrap();
+        assert!((rise - 0.375).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn test_grazing_crossing() {
+        // culminating 0.5 degrees over the horizon, up for about 1h05m, which a linear
+        // interpolation on the 20 minute coarse steps gets minutes off
+        let altitude = daily(0.5);
+        let half = (1.0 - 0.5 / 40.0_f64).acos() / TAU;
+        let rise = find_crossing(&altitude, 0.0, 1.0, 0.0, true).unwrap();
+        let set = find_crossing(&altitude, 0.0, 1.0, 0.0, false).unwrap();
+        assert!((rise - (0.5 - half)).abs() < TOLERANCE);
+        assert!((set - (0.5 + half)).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn test_short_pass() {
+        // culminating 0.02 degrees up 10 minutes after a coarse step, up for about 14 minutes
+        // between two steps
+        let peak = 0.5 + 10.0 / 1440.0;
+        let altitude = move |jd: f64| 0.02 - 40.0 + 40.0 * (TAU * (jd - peak)).cos();
+        assert!((0..=COARSE_STEPS).all(|i| altitude(i as f64 / COARSE_STEPS as f64) < 0.0));
+        let half = (1.0 - 0.02 / 40.0_f64).acos() / TAU;
+        let rise = find_crossing(altitude, 0.0, 1.0, 0.0, true).unwrap();
+        let set = find_crossing(altitude, 0.0, 1.0, 0.0, false).unwrap();
+        assert!((rise - (peak - half)).abs() < TOLERANCE);
+        assert!((set - (peak + half)).abs() < TOLERANCE);
+
+        // dipping 0.02 degrees under the horizon between two steps, a grazing midnight Sun
+        let dip = move |jd: f64| -altitude(jd);
+        let set = find_crossing(dip, 0.0, 1.0, 0.0, false).unwrap();
+        let rise = find_crossing(dip, 0.0, 1.0, 0.0, true).unwrap();
+        assert!((set - (peak - half)).abs() < TOLERANCE);
+        assert!((rise - (peak + half)).abs() < TOLERANCE);
+
+        // a maximum under the horizon is no crossing
+        assert_eq!(find_crossing(|jd| altitude(jd) - 0.05, 0.0, 1.0, 0.0, true), Err(NoCrossing::AlwaysBelow));
+    }
+
+    #[test]
+    fn test_no_crossing() {
+        assert_eq!(find_crossing(daily(60.0), 0.0, 1.0, -50.0, true), Err(NoCrossing::AlwaysAbove));
+        assert_eq!(find_crossing(daily(-1.0), 0.0, 1.0, 0.0, false), Err(NoCrossing::AlwaysBelow));
+        // rising all the time, there is nothing to set
+        assert_eq!(find_crossing(|jd| -12.0 + jd * 24.0, 0.0, 1.0, 0.0, false),
+                   Err(NoCrossing::OtherDirection));
+        assert_eq!(bracket_crossing(&|jd| 12.0 - jd * 24.0, 0.0, 1.0, 0.0, true),
+                   Err(NoCrossing::OtherDirection));
+    }
+
+    #[test]
+    fn test_bracket_crossing() {
+        let bracket = bracket_crossing(&|jd| -12.0 + jd * 24.0, 0.0, 1.0, 0.0, true).unwrap();
+        assert!(bracket.jd_low <= 0.5 && bracket.jd_high >= 0.5);
+        assert!(bracket.altitude_low < 0.0 && bracket.altitude_high >= 0.0);
+        assert!(bracket.jd_high - bracket.jd_low < 1.0 / 70.0);
+    }
+}
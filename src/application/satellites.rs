@@ -0,0 +1,877 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Satellite pass prediction from two-line element sets (TLE), e.g. the ISS and the bright
+// satellites of the Celestrak "visual" group.
+//
+// Orbits are propagated with SGP4 (Spacetrack Report #3, with the corrections of Vallado et al.,
+// "Revisiting Spacetrack Report #3", 2006) using the WGS-72 constants the elements are fitted
+// with. Only the near Earth model is implemented, satellites with periods of 225 minutes or more
+// (deep space, SDP4) are skipped, which leaves out geostationary and GPS satellites but none of
+// the naked eye ones. Positions are accurate to a few kilometers near the element epoch, enough
+// for pass times to a few seconds.
+//
+// A pass is visible when the satellite is sunlit (cylindrical Earth shadow) while the sky is dark
+// at the observer (Sun below -6°). The magnitude is estimated from the standard magnitude at
+// 1000 km and 50% illuminated with the phase function of a diffuse sphere.
+
+use std::fs::{read_to_string, write};
+use std::sync::mpsc::channel;
+use std::time::{Duration, SystemTime};
+use crate::application::observer::Observer;
+use crate::application::recent::config_directory;
+use crate::application::sun::{sun_altitude, sun_position_from_jd};
+use crate::application::time::{gst_from_jd, Time};
+use crate::application::weather::http_get;
+use crate::utils::utils::{cosd, sind};
+
+// WGS-72 constants of SGP4
+const EARTH_RADIUS: f64 = 6378.135; // km
+const XKE: f64 = 0.0743669161331734; // sqrt(GM) in earth radii^1.5 / minute
+const J2: f64 = 0.001082616;
+const J3: f64 = -0.00000253881;
+const J4: f64 = -0.00000165597;
+const J3OJ2: f64 = J3 / J2;
+const TWO_THIRDS: f64 = 2.0 / 3.0;
+const TWO_PI: f64 = std::f64::consts::TAU;
+// Periods from this on need the deep space model, in minutes
+const DEEP_SPACE_PERIOD: f64 = 225.0;
+
+// WGS-84 ellipsoid for the observer position
+const WGS84_RADIUS: f64 = 6378.137; // km
+const WGS84_FLATTENING: f64 = 1.0 / 298.257223563;
+
+// Celestrak group of the brightest satellites, over plain HTTP like the weather forecast
+const CELESTRAK_VISUAL_URL: &str = "https://celestrak.org/NORAD/elements/gp.php?GROUP=visual&FORMAT=tle";
+// Downloaded elements are kept in the configuration directory and refreshed after a day
+const CELESTRAK_CACHE_FILE: &str = "visual.tle";
+const CELESTRAK_CACHE_AGE: Duration = Duration::from_secs(86_400);
+// Longest wait for the download, the cached copy is used after it
+const CELESTRAK_TIMEOUT: Duration = Duration::from_secs(20);
+
+// Pass search step and refinement, in days (30 s and 1 s)
+const PASS_STEP: f64 = 30.0 / 86_400.0;
+const PASS_PRECISION: f64 = 1.0 / 86_400.0;
+// Passes lower than this are not reported, satellites near the horizon are hardly seen
+pub const MIN_PASS_ALTITUDE: f64 = 10.0;
+// Passes fainter than this are left out of the report
+pub const MAX_PASS_MAGNITUDE: f64 = 3.0;
+// Sun altitude for a sky dark enough to see satellites
+const DARK_SKY_SUN_ALTITUDE: f64 = -6.0;
+// Standard magnitudes (1000 km, 50% illuminated) of well known satellites by catalog number,
+// the others get a typical value for the bright satellites
+const STANDARD_MAGNITUDES: &[(u32, f64)] = &[(25544, -1.8), (48274, -0.8), (20580, 2.2)];
+const DEFAULT_STANDARD_MAGNITUDE: f64 = 4.0;
+
+/// Tle struct
+///
+/// Mean orbital elements of a two-line element set.
+///
+/// # Attributes
+///
+/// * `name` - Satellite name, from the title line or the catalog number
+/// * `catalog_number` - NORAD catalog number
+/// * `epoch` - Epoch of the elements, Julian Date UTC
+/// * `inclination` - Inclination in degrees
+/// * `raan` - Right ascension of the ascending node in degrees
+/// * `eccentricity` - Eccentricity
+/// * `argument_of_perigee` - Argument of perigee in degrees
+/// * `mean_anomaly` - Mean anomaly in degrees
+/// * `mean_motion` - Mean motion in revolutions per day
+/// * `bstar` - Drag term in inverse earth radii
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tle {
+    pub name: String,
+    pub catalog_number: u32,
+    pub epoch: f64,
+    pub inclination: f64,
+    pub raan: f64,
+    pub eccentricity: f64,
+    pub argument_of_perigee: f64,
+    pub mean_anomaly: f64,
+    pub mean_motion: f64,
+    pub bstar: f64,
+}
+
+// Modulo 10 checksum of the first 68 characters, minus signs count as 1
+fn checksum_ok(line: &str) -> bool {
+    let sum: u32 = line
+        .chars()
+        .take(68)
+        .map(|c| match c {
+            '-' => 1,
+            c => c.to_digit(10).unwrap_or(0),
+        })
+        .sum();
+    line[68..69].parse::<u32>().is_ok_and(|check| check == sum % 10)
+}
+
+fn field(line: &str, start: usize, end: usize) -> Result<f64, String> {
+    let text = line[start..end].trim();
+    text.parse::<f64>().map_err(|_| format!("Invalid TLE field '{}'", text))
+}
+
+// Fields with an assumed leading decimal point and an exponent, e.g. " 28098-4" is 0.28098e-4
+fn exponent_field(line: &str, start: usize, end: usize) -> Result<f64, String> {
+    let text = line[start..end].trim();
+    if text.is_empty() {
+        return Ok(0.0);
+    }
+    let (mantissa, exponent) = text.split_at(text.len() - 2);
+    let (sign, digits) = match mantissa.strip_prefix('-') {
+        Some(digits) => (-1.0, digits),
+        None => (1.0, mantissa.trim_start_matches('+')),
+    };
+    let mantissa = format!("0.{}", digits.trim()).parse::<f64>();
+    let exponent = exponent.replace('+', "").parse::<i32>();
+    match (mantissa, exponent) {
+        (Ok(mantissa), Ok(exponent)) => Ok(sign * mantissa * 10f64.powi(exponent)),
+        _ => Err(format!("Invalid TLE field '{}'", text)),
+    }
+}
+
+impl Tle {
+    /// Elements from the two lines of a set, `name` from the title line when there is one
+    pub fn from_lines(name: Option<&str>, line1: &str, line2: &str) -> Result<Tle, String> {
+        let (line1, line2) = (line1.trim_end(), line2.trim_end());
+        if line1.len() < 69 || line2.len() < 69 || !line1.starts_with("1 ") || !line2.starts_with("2 ") {
+            return Err(format!("Invalid TLE lines:\n{}\n{}", line1, line2));
+        }
+        if !line1.is_ascii() || !line2.is_ascii() || !checksum_ok(line1) || !checksum_ok(line2) {
+            return Err(format!("TLE checksum error:\n{}\n{}", line1, line2));
+        }
+        let catalog_number = line1[2..7].trim().parse::<u32>().map_err(|e| e.to_string())?;
+
+        // Epoch as two digit year and fractional day of the year, 57 to 99 are 1957 to 1999
+        let year = field(line1, 18, 20)? as i64;
+        let year = if year < 57 { 2000 + year } else { 1900 + year };
+        let epoch = Time::new(year, 1, 1, 0, 0, 0).to_jd() - 1.0 + field(line1, 20, 32)?;
+
+        let name = match name.map(|n| n.trim().trim_start_matches("0 ").trim()) {
+            Some(name) if !name.is_empty() => name.to_string(),
+            _ => catalog_number.to_string(),
+        };
+        Ok(Tle {
+            name,
+            catalog_number,
+            epoch,
+            inclination: field(line2, 8, 16)?,
+            raan: field(line2, 17, 25)?,
+            eccentricity: format!("0.{}", line2[26..33].trim()).parse::<f64>().map_err(|e| e.to_string())?,
+            argument_of_perigee: field(line2, 34, 42)?,
+            mean_anomaly: field(line2, 43, 51)?,
+            mean_motion: field(line2, 52, 63)?,
+            bstar: exponent_field(line1, 53, 61)?,
+        })
+    }
+}
+
+/// Element sets of a TLE file, with or without title lines. Blank lines are skipped, a set with
+/// a bad checksum or field and the lines out of any set are skipped with a warning. Only a file
+/// without any valid set is an error.
+pub fn parse_tles(contents: &str) -> Result<Vec<Tle>, String> {
+    let lines: Vec<&str> = contents.lines().filter(|line| !line.trim().is_empty()).collect();
+    let mut tles: Vec<Tle> = Vec::new();
+    let mut first_error: Option<String> = None;
+    let mut i = 0;
+    while i < lines.len() {
+        let (set, length) = if lines[i].starts_with("1 ") && i + 1 < lines.len() && lines[i + 1].starts_with("2 ") {
+            (Tle::from_lines(None, lines[i], lines[i + 1]), 2)
+        } else if i + 2 < lines.len() && lines[i + 1].starts_with("1 ") && lines[i + 2].starts_with("2 ") {
+            (Tle::from_lines(Some(lines[i]), lines[i + 1], lines[i + 2]), 3)
+        } else {
+            (Err(format!("Invalid TLE line {}: {}", i + 1, lines[i])), 1)
+        };
+        match set {
+            Ok(tle) => tles.push(tle),
+            Err(e) => {
+                log::warn!("TLE skipped line={} error={}", i + 1, e);
+                first_error.get_or_insert(e);
+            }
+        }
+        i += length;
+    }
+    match first_error {
+        Some(e) if tles.is_empty() => Err(e),
+        _ => Ok(tles),
+    }
+}
+
+/// Sgp4 struct
+///
+/// SGP4 near Earth propagator initialized from a TLE, the fields are the secular rates and
+/// coefficients of the model named as in the reference implementation.
+#[derive(Debug, Clone)]
+pub struct Sgp4 {
+    epoch: f64,
+    simple: bool,
+    bstar: f64,
+    inclination: f64,
+    raan: f64,
+    eccentricity: f64,
+    argument_of_perigee: f64,
+    mean_anomaly: f64,
+    mean_motion: f64,
+    aycof: f64,
+    con41: f64,
+    cc1: f64,
+    cc4: f64,
+    cc5: f64,
+    d2: f64,
+    d3: f64,
+    d4: f64,
+    delmo: f64,
+    eta: f64,
+    argpdot: f64,
+    omgcof: f64,
+    sinmao: f64,
+    t2cof: f64,
+    t3cof: f64,
+    t4cof: f64,
+    t5cof: f64,
+    x1mth2: f64,
+    x7thm1: f64,
+    mdot: f64,
+    nodedot: f64,
+    xlcof: f64,
+    xmcof: f64,
+    nodecf: f64,
+}
+
+impl Sgp4 {
+    pub fn new(tle: &Tle) -> Result<Sgp4, String> {
+        let no_kozai = tle.mean_motion * TWO_PI / 1440.0;
+        if no_kozai <= 0.0 {
+            return Err(format!("{}: invalid mean motion", tle.name));
+        }
+        let ecco = tle.eccentricity;
+        let inclo = tle.inclination.to_radians();
+        let argpo = tle.argument_of_perigee.to_radians();
+        let mo = tle.mean_anomaly.to_radians();
+        let bstar = tle.bstar;
+
+        // Recover the original mean motion and semi-major axis from the Kozai mean motion
+        let eccsq = ecco * ecco;
+        let omeosq = 1.0 - eccsq;
+        let rteosq = omeosq.sqrt();
+        let cosio = inclo.cos();
+        let cosio2 = cosio * cosio;
+        let ak = (XKE / no_kozai).powf(TWO_THIRDS);
+        let d1 = 0.75 * J2 * (3.0 * cosio2 - 1.0) / (rteosq * omeosq);
+        let del = d1 / (ak * ak);
+        let adel = ak * (1.0 - del * del - del * (1.0 / 3.0 + 134.0 * del * del / 81.0));
+        let del = d1 / (adel * adel);
+        let no = no_kozai / (1.0 + del);
+        if TWO_PI / no >= DEEP_SPACE_PERIOD {
+            return Err(format!("{}: deep space orbits are not supported", tle.name));
+        }
+        let ao = (XKE / no).powf(TWO_THIRDS);
+        let sinio = inclo.sin();
+        let po = ao * omeosq;
+        let con42 = 1.0 - 5.0 * cosio2;
+        let con41 = -con42 - cosio2 - cosio2;
+        let posq = po * po;
+        let rp = ao * (1.0 - ecco);
+        if rp < 1.0 {
+            return Err(format!("{}: perigee below the surface", tle.name));
+        }
+
+        // Atmospheric density parameters, lowered for perigees under 156 km
+        let mut sfour = 78.0 / EARTH_RADIUS + 1.0;
+        let mut qzms24 = ((120.0 - 78.0) / EARTH_RADIUS).powi(4);
+        let perige = (rp - 1.0) * EARTH_RADIUS;
+        if perige < 156.0 {
+            sfour = if perige < 98.0 { 20.0 } else { perige - 78.0 };
+            qzms24 = ((120.0 - sfour) / EARTH_RADIUS).powi(4);
+            sfour = sfour / EARTH_RADIUS + 1.0;
+        }
+        let simple = rp < 220.0 / EARTH_RADIUS + 1.0;
+
+        let pinvsq = 1.0 / posq;
+        let tsi = 1.0 / (ao - sfour);
+        let eta = ao * ecco * tsi;
+        let etasq = eta * eta;
+        let eeta = ecco * eta;
+        let psisq = (1.0 - etasq).abs();
+        let coef = qzms24 * tsi.powi(4);
+        let coef1 = coef / psisq.powf(3.5);
+        let cc2 = coef1 * no * (ao * (1.0 + 1.5 * etasq + eeta * (4.0 + etasq))
+            + 0.375 * J2 * tsi / psisq * con41 * (8.0 + 3.0 * etasq * (8.0 + etasq)));
+        let cc1 = bstar * cc2;
+        let cc3 = if ecco > 1.0e-4 { -2.0 * coef * tsi * J3OJ2 * no * sinio / ecco } else { 0.0 };
+        let x1mth2 = 1.0 - cosio2;
+        let cc4 = 2.0 * no * coef1 * ao * omeosq * (eta * (2.0 + 0.5 * etasq) + ecco * (0.5 + 2.0 * etasq)
+            - J2 * tsi / (ao * psisq) * (-3.0 * con41 * (1.0 - 2.0 * eeta + etasq * (1.5 - 0.5 * eeta))
+            + 0.75 * x1mth2 * (2.0 * etasq - eeta * (1.0 + etasq)) * (2.0 * argpo).cos()));
+        let cc5 = 2.0 * coef1 * ao * omeosq * (1.0 + 2.75 * (etasq + eeta) + eeta * etasq);
+
+        // Secular rates of the mean anomaly, argument of perigee and node
+        let cosio4 = cosio2 * cosio2;
+        let temp1 = 1.5 * J2 * pinvsq * no;
+        let temp2 = 0.5 * temp1 * J2 * pinvsq;
+        let temp3 = -0.46875 * J4 * pinvsq * pinvsq * no;
+        let mdot = no + 0.5 * temp1 * rteosq * con41
+            + 0.0625 * temp2 * rteosq * (13.0 - 78.0 * cosio2 + 137.0 * cosio4);
+        let argpdot = -0.5 * temp1 * con42 + 0.0625 * temp2 * (7.0 - 114.0 * cosio2 + 395.0 * cosio4)
+            + temp3 * (3.0 - 36.0 * cosio2 + 49.0 * cosio4);
+        let xhdot1 = -temp1 * cosio;
+        let nodedot = xhdot1 + (0.5 * temp2 * (4.0 - 19.0 * cosio2) + 2.0 * temp3 * (3.0 - 7.0 * cosio2)) * cosio;
+        let omgcof = bstar * cc3 * argpo.cos();
+        let xmcof = if ecco > 1.0e-4 { -TWO_THIRDS * coef * bstar / eeta } else { 0.0 };
+        let nodecf = 3.5 * omeosq * xhdot1 * cc1;
+        let t2cof = 1.5 * cc1;
+        let xlcof = -0.25 * J3OJ2 * sinio * (3.0 + 5.0 * cosio) / (1.0 + cosio).abs().max(1.5e-12);
+        let aycof = -0.5 * J3OJ2 * sinio;
+        let delmo = (1.0 + eta * mo.cos()).powi(3);
+        let x7thm1 = 7.0 * cosio2 - 1.0;
+
+        // Higher order drag terms, not used for perigees under 220 km
+        let (mut d2, mut d3, mut d4, mut t3cof, mut t4cof, mut t5cof) = (0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+        if !simple {
+            let cc1sq = cc1 * cc1;
+            d2 = 4.0 * ao * tsi * cc1sq;
+            let temp = d2 * tsi * cc1 / 3.0;
+            d3 = (17.0 * ao + sfour) * temp;
+            d4 = 0.5 * temp * ao * tsi * (221.0 * ao + 31.0 * sfour) * cc1;
+            t3cof = d2 + 2.0 * cc1sq;
+            t4cof = 0.25 * (3.0 * d3 + cc1 * (12.0 * d2 + 10.0 * cc1sq));
+            t5cof = 0.2 * (3.0 * d4 + 12.0 * cc1 * d3 + 6.0 * d2 * d2 + 15.0 * cc1sq * (2.0 * d2 + cc1sq));
+        }
+
+        Ok(Sgp4 {
+            epoch: tle.epoch,
+            simple,
+            bstar,
+            inclination: inclo,
+            raan: tle.raan.to_radians(),
+            eccentricity: ecco,
+            argument_of_perigee: argpo,
+            mean_anomaly: mo,
+            mean_motion: no,
+            aycof,
+            con41,
+            cc1,
+            cc4,
+            cc5,
+            d2,
+            d3,
+            d4,
+            delmo,
+            eta,
+            argpdot,
+            omgcof,
+            sinmao: mo.sin(),
+            t2cof,
+            t3cof,
+            t4cof,
+            t5cof,
+            x1mth2,
+            x7thm1,
+            mdot,
+            nodedot,
+            xlcof,
+            xmcof,
+            nodecf,
+        })
+    }
+
+    /// Position in km in the TEME frame (true equator, mean equinox) `minutes` after the epoch,
+    /// an error once the satellite has decayed
+    pub fn position(&self, minutes: f64) -> Result<[f64; 3], String> {
+        let t = minutes;
+
+        // Secular gravity and drag
+        let xmdf = self.mean_anomaly + self.mdot * t;
+        let argpdf = self.argument_of_perigee + self.argpdot * t;
+        let nodedf = self.raan + self.nodedot * t;
+        let mut argpm = argpdf;
+        let mut mm = xmdf;
+        let t2 = t * t;
+        let nodem = nodedf + self.nodecf * t2;
+        let mut tempa = 1.0 - self.cc1 * t;
+        let mut tempe = self.bstar * self.cc4 * t;
+        let mut templ = self.t2cof * t2;
+        if !self.simple {
+            let delomg = self.omgcof * t;
+            let delm = self.xmcof * ((1.0 + self.eta * xmdf.cos()).powi(3) - self.delmo);
+            let temp = delomg + delm;
+            mm = xmdf + temp;
+            argpm = argpdf - temp;
+            let t3 = t2 * t;
+            let t4 = t3 * t;
+            tempa = tempa - self.d2 * t2 - self.d3 * t3 - self.d4 * t4;
+            tempe += self.bstar * self.cc5 * (mm.sin() - self.sinmao);
+            templ += self.t3cof * t3 + t4 * (self.t4cof + t * self.t5cof);
+        }
+
+        let am = (XKE / self.mean_motion).powf(TWO_THIRDS) * tempa * tempa;
+        let mut em = self.eccentricity - tempe;
+        if !(-0.001..1.0).contains(&em) || am <= 0.0 {
+            return Err("Satellite decayed".to_string());
+        }
+        em = em.max(1.0e-6);
+        mm += self.mean_motion * templ;
+        let xlm = mm + argpm + nodem;
+        let nodem = nodem.rem_euclid(TWO_PI);
+        let argpm = argpm.rem_euclid(TWO_PI);
+        let xlm = xlm.rem_euclid(TWO_PI);
+        let mm = (xlm - argpm - nodem).rem_euclid(TWO_PI);
+
+        // Long period periodics
+        let (sinip, cosip) = self.inclination.sin_cos();
+        let axnl = em * argpm.cos();
+        let temp = 1.0 / (am * (1.0 - em * em));
+        let aynl = em * argpm.sin() + temp * self.aycof;
+        let xl = mm + argpm + nodem + temp * self.xlcof * axnl;
+
+        // Kepler's equation
+        let u = (xl - nodem).rem_euclid(TWO_PI);
+        let mut eo1 = u;
+        for _ in 0..10 {
+            let (sineo1, coseo1) = eo1.sin_cos();
+            let mut delta = (u - aynl * coseo1 + axnl * sineo1 - eo1) / (1.0 - coseo1 * axnl - sineo1 * aynl);
+            delta = delta.clamp(-0.95, 0.95);
+            eo1 += delta;
+            if delta.abs() < 1.0e-12 {
+                break;
+            }
+        }
+        let (sineo1, coseo1) = eo1.sin_cos();
+
+        // Short period periodics
+        let ecose = axnl * coseo1 + aynl * sineo1;
+        let esine = axnl * sineo1 - aynl * coseo1;
+        let el2 = axnl * axnl + aynl * aynl;
+        let pl = am * (1.0 - el2);
+        if pl < 0.0 {
+            return Err("Satellite decayed".to_string());
+        }
+        let rl = am * (1.0 - ecose);
+        let betal = (1.0 - el2).sqrt();
+        let temp = esine / (1.0 + betal);
+        let sinu = am / rl * (sineo1 - aynl - axnl * temp);
+        let cosu = am / rl * (coseo1 - axnl + aynl * temp);
+        let su = sinu.atan2(cosu);
+        let sin2u = (cosu + cosu) * sinu;
+        let cos2u = 1.0 - 2.0 * sinu * sinu;
+        let temp = 1.0 / pl;
+        let temp1 = 0.5 * J2 * temp;
+        let temp2 = temp1 * temp;
+        let mrt = rl * (1.0 - 1.5 * temp2 * betal * self.con41) + 0.5 * temp1 * self.x1mth2 * cos2u;
+        if mrt < 1.0 {
+            return Err("Satellite decayed".to_string());
+        }
+        let su = su - 0.25 * temp2 * self.x7thm1 * sin2u;
+        let xnode = nodem + 1.5 * temp2 * cosip * sin2u;
+        let xinc = self.inclination + 1.5 * temp2 * cosip * sinip * cos2u;
+
+        // Orientation vectors
+        let (sinsu, cossu) = su.sin_cos();
+        let (snod, cnod) = xnode.sin_cos();
+        let (sini, cosi) = xinc.sin_cos();
+        let xmx = -snod * cosi;
+        let xmy = cnod * cosi;
+        let ux = xmx * sinsu + cnod * cossu;
+        let uy = xmy * sinsu + snod * cossu;
+        let uz = sini * sinsu;
+        Ok([mrt * ux * EARTH_RADIUS, mrt * uy * EARTH_RADIUS, mrt * uz * EARTH_RADIUS])
+    }
+
+    /// Position in km in the TEME frame at a Julian Date (UTC)
+    pub fn position_at(&self, jd: f64) -> Result<[f64; 3], String> {
+        self.position((jd - self.epoch) * 1440.0)
+    }
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn norm(a: [f64; 3]) -> f64 {
+    dot(a, a).sqrt()
+}
+
+// Earth fixed coordinates of a TEME vector, polar motion is neglected
+fn teme_to_ecef(vector: [f64; 3], jd: f64) -> [f64; 3] {
+    let gst = gst_from_jd(jd);
+    let (sin_gst, cos_gst) = (sind(gst), cosd(gst));
+    [cos_gst * vector[0] + sin_gst * vector[1], -sin_gst * vector[0] + cos_gst * vector[1], vector[2]]
+}
+
+// Earth fixed position of the observer on the WGS-84 ellipsoid, in km
+fn observer_ecef(observer: &Observer) -> [f64; 3] {
+    let e2 = WGS84_FLATTENING * (2.0 - WGS84_FLATTENING);
+    let (sin_lat, cos_lat) = (sind(observer.latitude), cosd(observer.latitude));
+    let n = WGS84_RADIUS / (1.0 - e2 * sin_lat * sin_lat).sqrt();
+    let h = observer.elevation as f64 / 1000.0;
+    [
+        (n + h) * cos_lat * cosd(observer.longitude),
+        (n + h) * cos_lat * sind(observer.longitude),
+        (n * (1.0 - e2) + h) * sin_lat,
+    ]
+}
+
+// Earth fixed unit vector towards the Sun
+fn sun_direction(jd: f64) -> [f64; 3] {
+    let (ra, dec) = sun_position_from_jd(jd);
+    teme_to_ecef([cosd(dec) * cosd(ra), cosd(dec) * sind(ra), sind(dec)], jd)
+}
+
+/// Look struct
+///
+/// Satellite seen from the observer at a given time.
+///
+/// # Attributes
+///
+/// * `altitude` - Altitude in degrees
+/// * `azimuth` - Azimuth in degrees, reckoned from north through east
+/// * `range` - Distance in km
+/// * `sunlit` - Outside the shadow of the Earth
+/// * `magnitude` - Estimated visual magnitude when sunlit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Look {
+    pub altitude: f64,
+    pub azimuth: f64,
+    pub range: f64,
+    pub sunlit: bool,
+    pub magnitude: Option<f64>,
+}
+
+/// Satellite seen from `site` (Earth fixed, km) at `jd` (UTC)
+pub fn look(sgp4: &Sgp4, observer: &Observer, site: [f64; 3], standard_magnitude: f64,
+            jd: f64) -> Result<Look, String> {
+    let position = teme_to_ecef(sgp4.position_at(jd)?, jd);
+    let rho = [position[0] - site[0], position[1] - site[1], position[2] - site[2]];
+    let range = norm(rho);
+    let (sin_lat, cos_lat) = (sind(observer.latitude), cosd(observer.latitude));
+    let (sin_lon, cos_lon) = (sind(observer.longitude), cosd(observer.longitude));
+    let south = sin_lat * cos_lon * rho[0] + sin_lat * sin_lon * rho[1] - cos_lat * rho[2];
+    let east = -sin_lon * rho[0] + cos_lon * rho[1];
+    let zenith = cos_lat * cos_lon * rho[0] + cos_lat * sin_lon * rho[1] + sin_lat * rho[2];
+    let altitude = (zenith / range).asin().to_degrees();
+    let azimuth = east.atan2(-south).to_degrees().rem_euclid(360.0);
+
+    // Cylindrical shadow of the Earth
+    let sun = sun_direction(jd);
+    let along = dot(position, sun);
+    let across = [position[0] - along * sun[0], position[1] - along * sun[1], position[2] - along * sun[2]];
+    let sunlit = along > 0.0 || norm(across) > EARTH_RADIUS;
+
+    // Phase angle at the satellite between the Sun and the observer
+    let magnitude = sunlit.then(|| {
+        let cos_phase = (-dot(rho, sun) / range).clamp(-1.0, 1.0);
+        let phase = cos_phase.acos();
+        let illumination = ((std::f64::consts::PI - phase) * cos_phase + phase.sin()).max(1.0e-6);
+        standard_magnitude + 5.0 * (range / 1000.0).log10() - 2.5 * illumination.log10()
+    });
+    Ok(Look { altitude, azimuth, range, sunlit, magnitude })
+}
+
+/// Standard magnitude of a satellite, known for a few of them and typical for the others
+pub fn standard_magnitude(catalog_number: u32) -> f64 {
+    STANDARD_MAGNITUDES
+        .iter()
+        .find(|(number, _)| *number == catalog_number)
+        .map_or(DEFAULT_STANDARD_MAGNITUDE, |(_, magnitude)| *magnitude)
+}
+
+/// SatellitePass struct
+///
+/// Pass of a satellite above the horizon.
+///
+/// # Attributes
+///
+/// * `name` - Satellite name
+/// * `rise` - Rise (or start of the search), Julian Date UTC
+/// * `rise_azimuth` - Azimuth at rise in degrees
+/// * `culmination` - Time of the highest altitude, Julian Date UTC
+/// * `max_altitude` - Highest altitude in degrees
+/// * `set` - Set (or end of the search), Julian Date UTC
+/// * `set_azimuth` - Azimuth at set in degrees
+/// * `visible` - Part of the pass sunlit above MIN_PASS_ALTITUDE with the sky dark, Julian Dates UTC
+/// * `magnitude` - Brightest estimated magnitude while visible
+#[derive(Debug, Clone, PartialEq)]
+pub struct SatellitePass {
+    pub name: String,
+    pub rise: f64,
+    pub rise_azimuth: f64,
+    pub culmination: f64,
+    pub max_altitude: f64,
+    pub set: f64,
+    pub set_azimuth: f64,
+    pub visible: Option<(f64, f64)>,
+    pub magnitude: Option<f64>,
+}
+
+// Horizon crossing between two times on each side of it
+fn horizon_crossing(altitude: &dyn Fn(f64) -> f64, mut below: f64, mut above: f64) -> f64 {
+    while (above - below).abs() > PASS_PRECISION {
+        let middle = 0.5 * (below + above);
+        if altitude(middle) > 0.0 {
+            above = middle;
+        } else {
+            below = middle;
+        }
+    }
+    0.5 * (below + above)
+}
+
+// Highest altitude within a step of `jd`, by ternary search
+fn culmination(altitude: &dyn Fn(f64) -> f64, jd: f64, start: f64, end: f64) -> f64 {
+    let (mut low, mut high) = ((jd - PASS_STEP).max(start), (jd + PASS_STEP).min(end));
+    while high - low > PASS_PRECISION {
+        let (a, b) = (low + (high - low) / 3.0, high - (high - low) / 3.0);
+        if altitude(a) < altitude(b) {
+            low = a;
+        } else {
+            high = b;
+        }
+    }
+    0.5 * (low + high)
+}
+
+/// Passes of a satellite between `start` and `end` (Julian Dates UTC) in time order, empty when
+/// the elements can not be propagated
+pub fn passes(tle: &Tle, observer: &Observer, start: f64, end: f64) -> Vec<SatellitePass> {
+    let Ok(sgp4) = Sgp4::new(tle) else {
+        return Vec::new();
+    };
+    let site = observer_ecef(observer);
+    let standard = standard_magnitude(tle.catalog_number);
+    let look_at = |jd: f64| look(&sgp4, observer, site, standard, jd);
+    let altitude = |jd: f64| look_at(jd).map_or(-90.0, |l| l.altitude);
+    let sun = sun_altitude(observer.latitude, observer.longitude);
+
+    let mut passes: Vec<SatellitePass> = Vec::new();
+    let mut current: Option<SatellitePass> = None;
+    let mut previous = start;
+    let steps = ((end - start) / PASS_STEP).ceil() as usize;
+    for i in 0..=steps {
+        let jd = (start + i as f64 * PASS_STEP).min(end);
+        let Ok(now) = look_at(jd) else {
+            break;
+        };
+        if now.altitude > 0.0 {
+            let pass = current.get_or_insert_with(|| {
+                let rise = if i == 0 { start } else { horizon_crossing(&altitude, previous, jd) };
+                SatellitePass {
+                    name: tle.name.clone(),
+                    rise,
+                    rise_azimuth: look_at(rise).map_or(now.azimuth, |l| l.azimuth),
+                    culmination: jd,
+                    max_altitude: now.altitude,
+                    set: end,
+                    set_azimuth: now.azimuth,
+                    visible: None,
+                    magnitude: None,
+                }
+            });
+            if now.altitude > pass.max_altitude {
+                pass.max_altitude = now.altitude;
+                pass.culmination = jd;
+            }
+            if now.altitude >= MIN_PASS_ALTITUDE && now.sunlit && sun(jd) < DARK_SKY_SUN_ALTITUDE {
+                pass.visible = Some(pass.visible.map_or((jd, jd), |(first, _)| (first, jd)));
+                if let Some(magnitude) = now.magnitude {
+                    pass.magnitude = Some(pass.magnitude.map_or(magnitude, |m| m.min(magnitude)));
+                }
+            }
+        }
+        if now.altitude <= 0.0 || jd >= end {
+            if let Some(mut pass) = current.take() {
+                if now.altitude <= 0.0 {
+                    pass.set = horizon_crossing(&altitude, jd, previous);
+                }
+                pass.set_azimuth = look_at(pass.set).map_or(now.azimuth, |l| l.azimuth);
+                pass.culmination = culmination(&altitude, pass.culmination, pass.rise, pass.set);
+                pass.max_altitude = altitude(pass.culmination).max(pass.max_altitude);
+                passes.push(pass);
+            }
+        }
+        previous = jd;
+        if jd >= end {
+            break;
+        }
+    }
+    passes
+}
+
+/// Visible passes of the satellites between `start` and `end` (Julian Dates UTC): higher than
+/// MIN_PASS_ALTITUDE and sunlit with a dark sky, brighter than `max_magnitude`, in time order
+pub fn visible_passes(tles: &[Tle], observer: &Observer, start: f64, end: f64,
+                      max_magnitude: f64) -> Vec<SatellitePass> {
+    let mut visible: Vec<SatellitePass> = tles
+        .iter()
+        .flat_map(|tle| passes(tle, observer, start, end))
+        .filter(|pass| pass.visible.is_some() && pass.magnitude.is_some_and(|m| m <= max_magnitude))
+        .collect();
+    visible.sort_by(|a, b| a.rise.total_cmp(&b.rise));
+    visible
+}
+
+// Celestrak visual group, downloaded at most once a day, an old copy is used when the download
+// fails
+fn celestrak_visual() -> Result<String, String> {
+    let cache = config_directory().join(CELESTRAK_CACHE_FILE);
+    let fresh = cache
+        .metadata()
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age < CELESTRAK_CACHE_AGE);
+    if fresh {
+        if let Ok(contents) = read_to_string(&cache) {
+            return Ok(contents);
+        }
+    }
+    // the download runs on its own thread so a stalled connection does not hold the report
+    let (sender, receiver) = channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(http_get(CELESTRAK_VISUAL_URL));
+    });
+    let download = receiver
+        .recv_timeout(CELESTRAK_TIMEOUT)
+        .unwrap_or_else(|_| Err(format!("No answer from Celestrak in {} s", CELESTRAK_TIMEOUT.as_secs())));
+    match download {
+        Ok(body) => {
+            // a copy that can not be saved is downloaded again next time
+            let _ = std::fs::create_dir_all(config_directory()).and_then(|_| write(&cache, &body));
+            Ok(body)
+        }
+        Err(e) => read_to_string(&cache).map_err(|_| e),
+    }
+}
+
+/// Element sets from `tle_file`, or from Celestrak when there is none
+pub fn load_tles(tle_file: Option<&str>) -> Result<Vec<Tle>, String> {
+    let contents = match tle_file {
+        Some(file_path) => read_to_string(file_path).map_err(|e| format!("{}: {}", file_path, e))?,
+        None => celestrak_visual()?,
+    };
+    parse_tles(&contents)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const VANGUARD: &str = "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753
+2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667";
+
+    // ISS elements of 2008-09-20 12:25 UTC
+    const ISS: &str = "1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927
+2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537";
+
+    #[test]
+    fn test_parse_tles() {
+        let tles = parse_tles(VANGUARD).unwrap();
+        assert_eq!(tles.len(), 1);
+        let tle = &tles[0];
+        assert_eq!(tle.name, "5");
+        assert_eq!(tle.catalog_number, 5);
+        assert!((tle.epoch - 2451723.28495062).abs() < 1e-8);
+        assert!((tle.eccentricity - 0.1859667).abs() < 1e-12);
+        assert!((tle.bstar - 0.28098e-4).abs() < 1e-12);
+        assert!((tle.mean_motion - 10.82419157).abs() < 1e-8);
+
+        let named = parse_tles(&format!("VANGUARD 1\n{}\n", VANGUARD)).unwrap();
+        assert_eq!(named[0].name, "VANGUARD 1");
+
+        // one digit changed, the checksum no longer matches
+        assert!(parse_tles(&VANGUARD.replace("34.2682", "34.2683")).is_err());
+        assert!(parse_tles("not a TLE").is_err());
+        assert!(parse_tles("").unwrap().is_empty());
+
+        // bad sets and stray lines are skipped, the good ones kept
+        let mixed = format!("BROKEN\n{}\nstray line\nISS (ZARYA)\n{}\n",
+                            VANGUARD.replace("34.2682", "34.2683"), ISS);
+        let tles = parse_tles(&mixed).unwrap();
+        assert_eq!(tles.len(), 1);
+        assert_eq!(tles[0].name, "ISS (ZARYA)");
+    }
+
+    #[test]
+    fn test_sgp4() {
+        // Verification case 00005 of Vallado et al. 2006, positions in km
+        let sgp4 = Sgp4::new(&parse_tles(VANGUARD).unwrap()[0]).unwrap();
+        let expected = [
+            (0.0, [7022.46529266, -1400.08296755, 0.03995155]),
+            (360.0, [-7154.03120202, -3783.17682504, -3536.19412294]),
+            (720.0, [-7134.59340119, 6531.68641334, 3260.27186483]),
+        ];
+        for (minutes, position) in expected {
+            let computed = sgp4.position(minutes).unwrap();
+            for axis in 0..3 {
+                assert!((computed[axis] - position[axis]).abs() < 0.01, "{} {:?}", minutes, computed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_passes() {
+        let observer = Observer::location(None, "40.0", "-105.0", 1600, "-7");
+        let iss = &parse_tles(ISS).unwrap()[0];
+        let sgp4 = Sgp4::new(iss).unwrap();
+        let site = observer_ecef(&observer);
+        let altitude = |jd: f64| look(&sgp4, &observer, site, standard_magnitude(iss.catalog_number), jd)
+            .unwrap()
+            .altitude;
+
+        // a day from the epoch, the ISS crosses the sky a few times for a few minutes each
+        let (start, end) = (iss.epoch, iss.epoch + 1.0);
+        let all = passes(iss, &observer, start, end);
+        assert!(all.len() >= 3 && all.len() <= 8, "{:?}", all);
+        assert!(all.windows(2).all(|w| w[0].set < w[1].rise));
+        for pass in &all {
+            assert_eq!(pass.name, "25544");
+            assert!(pass.rise < pass.culmination && pass.culmination < pass.set, "{:?}", pass);
+            assert!((pass.set - pass.rise) * 1440.0 < 15.0, "{:?}", pass);
+            assert!(pass.max_altitude > 0.0 && pass.max_altitude <= 90.0);
+            // rise and set on the horizon, the culmination the highest point
+            if pass.rise > start {
+                assert!(altitude(pass.rise).abs() < 0.5, "{:?}", pass);
+            }
+            if pass.set < end {
+                assert!(altitude(pass.set).abs() < 0.5, "{:?}", pass);
+            }
+            assert!(altitude(pass.culmination) >= altitude(pass.culmination - 30.0 / 86_400.0));
+            assert!(altitude(pass.culmination) >= altitude(pass.culmination + 30.0 / 86_400.0));
+            if let Some((first, last)) = pass.visible {
+                assert!(pass.rise <= first && first <= last && last <= pass.set, "{:?}", pass);
+            } else {
+                assert!(pass.magnitude.is_none());
+            }
+        }
+
+        // the visible ones bright enough, in time order, one high pass that day
+        let visible = visible_passes(std::slice::from_ref(iss), &observer, start, end, MAX_PASS_MAGNITUDE);
+        let expected: Vec<&SatellitePass> = all
+            .iter()
+            .filter(|p| p.visible.is_some() && p.magnitude.is_some_and(|m| m <= MAX_PASS_MAGNITUDE))
+            .collect();
+        assert_eq!(visible.iter().collect::<Vec<_>>(), expected);
+        assert_eq!(visible.len(), 1);
+        assert!(visible[0].max_altitude > 60.0 && visible[0].magnitude.unwrap() < -3.0, "{:?}", visible);
+        assert!(visible_passes(std::slice::from_ref(iss), &observer, start, end, -10.0).is_empty());
+        assert!(passes(iss, &observer, start, start).len() <= 1);
+    }
+}
@@ -0,0 +1,373 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Scheduling queries for automation scripts (dome control, imaging sequencers): when the Sun next
+// crosses a twilight angle after a given time at a site. `skycalc schedule` answers them as JSON
+// on stdout, or in the file of --output, without opening a window, e.g.
+//
+//   skycalc schedule --config home.yaml --twilight astronomical --after 2025-03-28T12:00:00Z
+//
+//   {
+//     "site": {"name": "Home", "latitude": -23.1, "longitude": -46.5, "elevation": 780, "timezone": -3.0},
+//     "after": {"utc": "2025-03-28T12:00:00Z", "local": "2025-03-28T09:00:00-03:00", "jd": 2460763.0},
+//     "twilight": "astronomical",
+//     "horizon": -18.0,
+//     "dark_now": false,
+//     "start": {"utc": "2025-03-28T22:33:41Z", "local": "2025-03-28T19:33:41-03:00", "jd": ...},
+//     "end": {"utc": "2025-03-29T08:18:12Z", "local": "2025-03-29T05:18:12-03:00", "jd": ...}
+//   }
+//
+// `start` is the next time the Sun sinks below the horizon angle and `end` the next time it rises
+// above it, both strictly after `after`. When it is already dark `end` comes before `start`.
+// Events not found within a year (polar summer or winter) are null.
+//
+// Release builds on Windows have no console of their own, the command attaches to the console of
+// the shell it was started from so the output and errors are shown there. Scripts that redirect
+// the output get it either way.
+
+use serde::Serialize;
+use crate::application::application::read_config;
+use crate::application::calculation::CalculationSettings;
+use crate::application::observer::{try_degrees_from_str, try_elevation_from_str, try_timezone_from_str,
+                                   Observer};
+use crate::application::recent::RecentConfigs;
+use crate::application::rise_set_solver::find_crossing;
+use crate::application::sites::search_sites;
+use crate::application::sun::{sun_altitude, TwilightType};
+use crate::application::time::{parse_time, LocalTime, Time};
+
+pub const SCHEDULE_COMMAND: &str = "schedule";
+
+// Days searched for a crossing, a polar summer goes months without darkness
+const MAX_SEARCH_DAYS: u32 = 366;
+
+const USAGE: &str = "\
+Usage: skycalc schedule [SITE] [--after TIME] [--twilight NAME]
+
+Next start and end of the darkness after TIME at the site, as JSON on stdout.
+
+Site, one of (default: the last configuration opened):
+  --config FILE           observatory of a configuration file (yaml, json or toml)
+  --site NAME             built-in site, the first one matching NAME
+  --lat DEG --lon DEG     coordinates, with --elevation M and --timezone HOURS (default 0 and UTC)
+
+Options:
+  --after TIME            UTC time like 2025-03-28T12:00:00 or with an offset (default: now)
+  --twilight NAME         sunset, civil, nautical or astronomical (default: astronomical)
+  --output FILE           write the JSON to FILE instead of stdout, the Windows release build has
+                          no console and prints only to a redirection
+  -h, --help              show this help";
+
+/// Sun altitude of a twilight given by name on the command line
+pub fn parse_twilight(name: &str) -> Result<TwilightType, String> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "sunset" | "sunrise" | "riseset" => Ok(TwilightType::RiseSet),
+        "civil" => Ok(TwilightType::CivilTwilight),
+        "nautical" => Ok(TwilightType::NauticalTwilight),
+        "astronomical" => Ok(TwilightType::AstronomicalTwilight),
+        other => Err(format!("'{}' is not a twilight, use sunset, civil, nautical or astronomical", other)),
+    }
+}
+
+fn twilight_name(twilight: TwilightType) -> &'static str {
+    match twilight {
+        TwilightType::RiseSet => "sunset",
+        TwilightType::CivilTwilight => "civil",
+        TwilightType::NauticalTwilight => "nautical",
+        TwilightType::AstronomicalTwilight => "astronomical",
+    }
+}
+
+/// ScheduleTime struct
+///
+/// Instant of a schedule in the forms scripts use: ISO 8601 in UTC and with the site offset, and
+/// the Julian Date.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScheduleTime {
+    pub utc: String,
+    pub local: String,
+    pub jd: f64,
+}
+
+impl ScheduleTime {
+    pub fn new(jd: f64, offset: f64) -> ScheduleTime {
+        ScheduleTime {
+            utc: format!("{}Z", Time::from_jd(jd).to_iso()),
+            local: LocalTime::new(jd, offset).to_string(Some("isot")),
+            jd,
+        }
+    }
+}
+
+/// Observatory the schedule was computed for
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScheduleSite {
+    pub name: Option<String>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub elevation: i64,
+    pub timezone: f64,
+}
+
+/// DarknessSchedule struct
+///
+/// # Attributes
+///
+/// * `site` - Observatory of the query
+/// * `after` - Time of the query
+/// * `twilight` - Twilight name, "sunset", "civil", "nautical" or "astronomical"
+/// * `horizon` - Sun altitude of the twilight in degrees
+/// * `dark_now` - The Sun is below the horizon angle at `after`
+/// * `start` - Next time the Sun sinks below the horizon angle, None within a year
+/// * `end` - Next time the Sun rises above the horizon angle, None within a year
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DarknessSchedule {
+    pub site: ScheduleSite,
+    pub after: ScheduleTime,
+    pub twilight: &'static str,
+    pub horizon: f64,
+    pub dark_now: bool,
+    pub start: Option<ScheduleTime>,
+    pub end: Option<ScheduleTime>,
+}
+
+/// First time after jd (UTC) the Sun crosses an altitude rising or setting, searched one day at a
+/// time for up to a year
+pub fn next_sun_crossing(observer: &Observer, jd: f64, angle: f64, rising: bool) -> Option<f64> {
+    let altitude = sun_altitude(observer.latitude, observer.longitude);
+    (0..MAX_SEARCH_DAYS).find_map(|day| {
+        let start = jd + day as f64;
+        find_crossing(&altitude, start, start + 1.0, angle, rising).ok()
+    })
+}
+
+/// Next start and end of the darkness of a twilight after jd (UTC) at the observer, sunset and
+/// sunrise with the refraction of the accuracy settings
+pub fn darkness_schedule(observer: &Observer, jd: f64, twilight: TwilightType,
+                         settings: CalculationSettings) -> DarknessSchedule {
+    let angle = settings.twilight_angle(twilight);
+    let offset = observer.timezone;
+    DarknessSchedule {
+        site: ScheduleSite {
+            name: observer.name.clone(),
+            latitude: observer.latitude,
+            longitude: observer.longitude,
+            elevation: observer.elevation,
+            timezone: offset,
+        },
+        after: ScheduleTime::new(jd, offset),
+        twilight: twilight_name(twilight),
+        horizon: angle,
+        dark_now: sun_altitude(observer.latitude, observer.longitude)(jd) < angle,
+        start: next_sun_crossing(observer, jd, angle, false).map(|start| ScheduleTime::new(start, offset)),
+        end: next_sun_crossing(observer, jd, angle, true).map(|end| ScheduleTime::new(end, offset)),
+    }
+}
+
+/// ScheduleQuery struct
+///
+/// Site, time and twilight of a `skycalc schedule` command line, the accuracy settings of the
+/// configuration (the defaults for the other sites) and the file the JSON is written to, None for
+/// stdout.
+#[derive(Debug, Clone)]
+pub struct ScheduleQuery {
+    pub observer: Observer,
+    pub jd: f64,
+    pub twilight: TwilightType,
+    pub settings: CalculationSettings,
+    pub output: Option<String>,
+}
+
+// Observatory and accuracy settings of a configuration file
+fn config_site(file_path: &str) -> Result<(Observer, CalculationSettings), String> {
+    read_config(file_path)
+        .map(|application| (application.observer, application.preferences.calculation))
+        .map_err(|e| format!("Can not read the configuration {}: {}", file_path, e))
+}
+
+/// Query of the arguments after `schedule`, the message to show when they are not valid
+pub fn parse_args(args: &[String]) -> Result<ScheduleQuery, String> {
+    let mut options: Vec<(&str, &str)> = Vec::new();
+    let mut args = args.iter();
+    while let Some(option) = args.next() {
+        match option.as_str() {
+            "--config" | "--site" | "--lat" | "--lon" | "--elevation" | "--timezone" | "--after" | "--twilight"
+            | "--output" => {
+                let value = args.next().ok_or_else(|| format!("{} needs a value", option))?;
+                options.push((option.as_str(), value.as_str()));
+            }
+            other => return Err(format!("Unknown option '{}'", other)),
+        }
+    }
+    let value = |name: &str| options.iter().rev().find(|(option, _)| *option == name).map(|(_, value)| *value);
+
+    let sources = [value("--config").is_some(), value("--site").is_some(),
+                   value("--lat").is_some() || value("--lon").is_some()];
+    if sources.iter().filter(|&&given| given).count() > 1 {
+        return Err("Give only one of --config, --site or --lat and --lon".to_string());
+    }
+    let (observer, settings) = if let Some(file_path) = value("--config") {
+        config_site(file_path)?
+    } else if let Some(name) = value("--site") {
+        let observer = search_sites(name)
+            .first()
+            .map(|site| site.to_observer())
+            .ok_or_else(|| format!("No built-in site matches '{}'", name))?;
+        (observer, CalculationSettings::default())
+    } else if sources[2] {
+        let (Some(lat), Some(lon)) = (value("--lat"), value("--lon")) else {
+            return Err("--lat and --lon go together".to_string());
+        };
+        let observer = Observer {
+            name: None,
+            latitude: try_degrees_from_str(lat, -90.0, 90.0).map_err(|e| format!("--lat: {}", e))?,
+            longitude: try_degrees_from_str(lon, -180.0, 180.0).map_err(|e| format!("--lon: {}", e))?,
+            elevation: try_elevation_from_str(value("--elevation").unwrap_or("0"))
+                .map_err(|e| format!("--elevation: {}", e))?,
+            timezone: try_timezone_from_str(value("--timezone").unwrap_or("0"))
+                .map_err(|e| format!("--timezone: {}", e))?,
+            zone: None,
+        };
+        (observer, CalculationSettings::default())
+    } else {
+        let recent = RecentConfigs::load();
+        let file_path = recent.last().ok_or("No site given and no configuration opened yet")?;
+        config_site(file_path)?
+    };
+
+    let time = match value("--after") {
+        Some(after) => parse_time(after).ok_or_else(|| format!("--after: '{}' is not a date and time", after))?,
+        None => Time::now(),
+    };
+    let twilight = parse_twilight(value("--twilight").unwrap_or("astronomical"))?;
+    Ok(ScheduleQuery { observer, jd: time.to_jd(), twilight, settings, output: value("--output").map(String::from) })
+}
+
+/// Runs `skycalc schedule` with the arguments after the command name: the schedule as JSON on
+/// stdout or in the --output file, or the error and the usage on stderr. Returns the exit code of
+/// the process.
+pub fn run(args: &[String]) -> i32 {
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return 0;
+    }
+    let query = match parse_args(args) {
+        Ok(query) => query,
+        Err(e) => {
+            eprintln!("skycalc schedule: {}\n\n{}", e, USAGE);
+            return 2;
+        }
+    };
+    let schedule = darkness_schedule(&query.observer, query.jd, query.twilight, query.settings);
+    let written = serde_json::to_string_pretty(&schedule)
+        .map_err(|e| e.to_string())
+        .and_then(|json| match &query.output {
+            Some(file_path) => std::fs::write(file_path, json + "\n")
+                .map_err(|e| format!("Can not write {}: {}", file_path, e)),
+            None => {
+                println!("{}", json);
+                Ok(())
+            }
+        });
+    match written {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("skycalc schedule: {}", e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_darkness_schedule() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let noon = Time::new(2025, 3, 20, 15, 0, 0).to_jd();
+        let schedule = darkness_schedule(&observer, noon, TwilightType::AstronomicalTwilight,
+                                         CalculationSettings::default());
+        assert!(!schedule.dark_now);
+        let start = schedule.start.unwrap();
+        let end = schedule.end.unwrap();
+        // evening and next morning, about 9.6 hours of astronomical darkness at the equinox
+        assert!(start.jd > noon && end.jd > start.jd);
+        assert!(((end.jd - start.jd) * 24.0 - 9.6).abs() < 0.3);
+        assert!(start.utc.starts_with("2025-03-20T22:") && start.local.starts_with("2025-03-20T19:"));
+        assert!(start.local.ends_with("-03:00"));
+
+        // during the darkness the end comes first and the next start is the following evening
+        let night = darkness_schedule(&observer, start.jd + 0.1, TwilightType::AstronomicalTwilight,
+                                      CalculationSettings::default());
+        assert!(night.dark_now);
+        assert!((night.end.unwrap().jd - end.jd).abs() < 1e-4);
+        assert!((night.start.unwrap().jd - start.jd - 1.0).abs() < 0.01);
+
+        // midnight Sun at Svalbard, darkness only months later
+        let svalbard = Observer::location(None, "78.2", "15.6", 0, "1");
+        let june = Time::new(2025, 6, 21, 0, 0, 0).to_jd();
+        let summer = darkness_schedule(&svalbard, june, TwilightType::RiseSet, CalculationSettings::default());
+        assert!(!summer.dark_now);
+        assert!(summer.start.unwrap().jd - june > 50.0);
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let query = parse_args(&args("--lat -23.1 --lon -46.5 --timezone -3 --after 2025-03-20T15:00:00 \
+                                      --twilight nautical")).unwrap();
+        assert_eq!(query.observer.latitude, -23.1);
+        assert_eq!(query.observer.timezone, -3.0);
+        assert_eq!(query.jd, Time::new(2025, 3, 20, 15, 0, 0).to_jd());
+        assert_eq!(query.twilight.angle(), -12.0);
+        assert_eq!(parse_args(&args("--lat 10 --lon 20")).unwrap().twilight.angle(), -18.0);
+        assert_eq!(query.output, None);
+        assert_eq!(parse_args(&args("--lat 10 --lon 20 --output dark.json")).unwrap().output.as_deref(),
+                   Some("dark.json"));
+
+        assert!(parse_args(&args("--lat 10")).is_err());
+        assert!(parse_args(&args("--lat 100 --lon 20")).is_err());
+        assert!(parse_args(&args("--lat 10 --lon 20 --twilight dusk")).is_err());
+        assert!(parse_args(&args("--lat 10 --lon 20 --site paulo")).is_err());
+        assert!(parse_args(&args("--lat 10 --lon 20 --after")).is_err());
+        assert!(parse_args(&args("--verbose")).is_err());
+        assert!(parse_args(&args("--config /nonexistent/skycalc.yaml")).is_err());
+    }
+
+    #[test]
+    fn test_run_output() {
+        let path = std::env::temp_dir().join("skycalc_test_schedule.json");
+        let line = format!("--lat -23.1 --lon -46.5 --timezone -3 --after 2025-03-20T15:00:00 --output {}",
+                           path.display());
+        assert_eq!(run(&args(&line)), 0);
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["twilight"], "astronomical");
+        assert!(json["start"]["utc"].as_str().unwrap().starts_with("2025-03-20T22:"));
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(run(&args("--lat -23.1 --lon -46.5 --output /nonexistent/dark.json")), 1);
+        assert_eq!(run(&args("--lat 100")), 2);
+    }
+}
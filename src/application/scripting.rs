@@ -0,0 +1,239 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// User scripts (rhai) adding computed columns to the Targets window and lines to the darkness
+// report. Scripts are read from the `scripts` directory next to the recent configurations list,
+// one `.rhai` file per script, and run sandboxed: no file, network or process access and limits
+// on operations, call depth and data sizes.
+//
+// A script defines any of:
+//
+//   fn column(target) { ... }  // value of the column named after the file, for each target
+//   fn report(night) { ... }   // line of the "Custom" section of the darkness report
+//
+// e.g. scripts/score.rhai:
+//
+//   fn column(target) {
+//       if target.altitude < 20.0 { return 0; }
+//       (target.altitude / 90.0 * target.moon_separation / 1.8).round()
+//   }
+
+use std::path::{Path, PathBuf};
+use rhai::{Dynamic, Engine, Map, Scope, AST};
+use crate::application::moon::{moon_illumination, moon_topocentric_position};
+use crate::application::observer::Observer;
+use crate::application::recent::config_directory;
+use crate::application::target::Target;
+use crate::application::transformations::angular_separation;
+
+pub const SCRIPTS_DIRECTORY: &str = "scripts";
+pub const SCRIPT_EXTENSION: &str = "rhai";
+const COLUMN_FUNCTION: &str = "column";
+const REPORT_FUNCTION: &str = "report";
+
+// Sandbox limits, generous for a formula but stop runaway loops and huge allocations
+const MAX_OPERATIONS: u64 = 100_000;
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_EXPRESSION_DEPTH: usize = 64;
+const MAX_STRING_SIZE: usize = 1_000;
+const MAX_ARRAY_SIZE: usize = 1_000;
+const MAX_MAP_SIZE: usize = 100;
+
+/// Script struct
+///
+/// # Attributes
+///
+/// * `name` - File name without extension, used as the column title
+/// * `ast` - Compiled script
+/// * `has_column` - The script defines `fn column(target)`
+/// * `has_report` - The script defines `fn report(night)`
+#[derive(Debug, Clone)]
+pub struct Script {
+    pub name: String,
+    ast: AST,
+    pub has_column: bool,
+    pub has_report: bool,
+}
+
+/// Scripts struct
+///
+/// Compiled user scripts sharing one sandboxed engine.
+pub struct Scripts {
+    engine: Engine,
+    pub scripts: Vec<Script>,
+}
+
+/// Directory of the user scripts
+pub fn scripts_directory() -> PathBuf {
+    config_directory().join(SCRIPTS_DIRECTORY)
+}
+
+// Engine without printing, module imports or eval, with the limits above
+fn sandboxed_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(MAX_CALL_LEVELS);
+    engine.set_max_expr_depths(MAX_EXPRESSION_DEPTH, MAX_EXPRESSION_DEPTH);
+    engine.set_max_string_size(MAX_STRING_SIZE);
+    engine.set_max_array_size(MAX_ARRAY_SIZE);
+    engine.set_max_map_size(MAX_MAP_SIZE);
+    engine.set_max_modules(0);
+    engine.disable_symbol("eval");
+    engine.on_print(|_| {});
+    engine.on_debug(|_, _, _| {});
+    engine
+}
+
+/// Script variables describing a target at a given time (JD UTC): name, ra, dec and size as in
+/// the target list, altitude, hour_angle (hours), moon_separation (degrees) and
+/// moon_illumination (%)
+pub fn target_variables(target: &Target, observer: &Observer, jd: f64) -> Map {
+    let (moon_ra, moon_dec, _) = moon_topocentric_position(
+        observer.latitude, observer.longitude, observer.elevation as f64, jd);
+    let mut map = Map::new();
+    map.insert("name".into(), target.name.clone().into());
+    map.insert("ra".into(), target.ra.into());
+    map.insert("dec".into(), target.dec.into());
+    map.insert("size".into(), target.size.into());
+    map.insert("altitude".into(), target.altitude(observer, jd).into());
+    map.insert("hour_angle".into(), (target.hour_angle(observer, jd) / 15.0).into());
+    map.insert("moon_separation".into(),
+               angular_separation(target.ra, target.dec, moon_ra, moon_dec).into());
+    map.insert("moon_illumination".into(), (moon_illumination(jd) * 100.0).into());
+    map
+}
+
+impl Scripts {
+    /// Compiles every script of a directory, a missing directory gives no scripts. Scripts that
+    /// do not compile are skipped and reported as "name: error".
+    pub fn load_from(directory: &Path) -> (Scripts, Vec<String>) {
+        let engine = sandboxed_engine();
+        let mut scripts: Vec<Script> = Vec::new();
+        let mut errors: Vec<String> = Vec::new();
+
+        let mut paths: Vec<PathBuf> = match std::fs::read_dir(directory) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().and_then(|e| e.to_str()) == Some(SCRIPT_EXTENSION))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        paths.sort();
+
+        for path in paths {
+            let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("").to_string();
+            let source = match std::fs::read_to_string(&path) {
+                Ok(source) => source,
+                Err(e) => {
+                    errors.push(format!("{}: {}", name, e));
+                    continue;
+                }
+            };
+            match engine.compile(&source) {
+                Ok(ast) => {
+                    let defines = |function: &str| {
+                        ast.iter_functions().any(|f| f.name == function && f.params.len() == 1)
+                    };
+                    let has_column = defines(COLUMN_FUNCTION);
+                    let has_report = defines(REPORT_FUNCTION);
+                    scripts.push(Script { name, ast, has_column, has_report });
+                }
+                Err(e) => errors.push(format!("{}: {}", name, e)),
+            }
+        }
+
+        (Scripts { engine, scripts }, errors)
+    }
+
+    pub fn load() -> (Scripts, Vec<String>) {
+        Scripts::load_from(&scripts_directory())
+    }
+
+    /// Titles of the computed columns
+    pub fn columns(&self) -> Vec<&str> {
+        self.scripts.iter().filter(|s| s.has_column).map(|s| s.name.as_str()).collect()
+    }
+
+    // Calls a one argument function of a script, errors are shown in place of the value
+    fn call(&self, script: &Script, function: &str, argument: Map) -> String {
+        let result = self.engine.call_fn::<Dynamic>(
+            &mut Scope::new(), &script.ast, function, (Dynamic::from_map(argument),));
+        match result {
+            Ok(value) if value.is_float() => format!("{:.2}", value.as_float().unwrap_or_default()),
+            Ok(value) => value.to_string(),
+            Err(e) => format!("error: {}", e),
+        }
+    }
+
+    /// Computed column values for a target, in the order of `columns`
+    pub fn column_values(&self, target: &Map) -> Vec<String> {
+        self.scripts
+            .iter()
+            .filter(|s| s.has_column)
+            .map(|script| self.call(script, COLUMN_FUNCTION, target.clone()))
+            .collect()
+    }
+
+    /// Report lines for a night, "name: line" for each script defining `report`
+    pub fn report_lines(&self, night: &Map) -> Vec<String> {
+        self.scripts
+            .iter()
+            .filter(|s| s.has_report)
+            .map(|script| format!("{}: {}", script.name, self.call(script, REPORT_FUNCTION, night.clone())))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_scripts() {
+        let directory = std::env::temp_dir().join("skycalc_test_scripts");
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("score.rhai"),
+                       "fn column(target) { target.altitude * 2.0 }").unwrap();
+        std::fs::write(directory.join("night.rhai"),
+                       "fn report(night) { `dark ${night.darkness} h` }").unwrap();
+        std::fs::write(directory.join("forever.rhai"), "fn column(target) { loop {} }").unwrap();
+        std::fs::write(directory.join("broken.rhai"), "fn column(target) {").unwrap();
+
+        let (scripts, errors) = Scripts::load_from(&directory);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("broken:"));
+        assert_eq!(scripts.columns(), vec!["forever", "score"]);
+
+        let mut target = Map::new();
+        target.insert("altitude".into(), 30.5_f64.into());
+        let values = scripts.column_values(&target);
+        assert!(values[0].starts_with("error:"));
+        assert_eq!(values[1], "61.00");
+
+        let mut night = Map::new();
+        night.insert("darkness".into(), 7_i64.into());
+        assert_eq!(scripts.report_lines(&night), vec!["night: dark 7 h"]);
+
+        std::fs::remove_dir_all(directory).unwrap();
+        assert!(Scripts::load_from(&std::env::temp_dir().join("skycalc_no_scripts")).0.scripts.is_empty());
+    }
+}
@@ -0,0 +1,146 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Window layout of the last session: main window geometry, appearance, night vision and open tool
+// windows. It is kept with the other preferences in the last configuration, the one loaded at the
+// next start, and written there as it changes without saving the rest of the configuration.
+
+use std::error::Error;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::application::application::{read_config, ConfigFormat};
+use crate::application::preferences::Preferences;
+use crate::application::recent::RecentConfigs;
+use crate::utils::dpi::fit_size;
+
+/// WindowGeometry struct
+///
+/// Position and size of a window in unscaled pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl WindowGeometry {
+    /// Geometry moved and shrunk to fit in a screen work area given as (x, y, width, height), so
+    /// a window saved on a larger or disconnected screen opens where it can be reached
+    pub fn fit_in(&self, area: (i32, i32, i32, i32)) -> WindowGeometry {
+        let (area_x, area_y, area_width, area_height) = area;
+        let (width, height) = fit_size(self.width, self.height, area_width, area_height);
+        WindowGeometry {
+            x: self.x.clamp(area_x, (area_x + area_width - width).max(area_x)),
+            y: self.y.clamp(area_y, (area_y + area_height - height).max(area_y)),
+            width,
+            height,
+        }
+    }
+}
+
+// Configuration the layout is kept in, the last one loaded or saved
+fn layout_config() -> Option<String> {
+    RecentConfigs::load().last().map(str::to_string)
+}
+
+/// Preferences of the last configuration with the layout of the last session, the defaults when
+/// there is none or it can not be read
+pub fn last_layout() -> Preferences {
+    layout_config()
+        .and_then(|file_path| read_config(&file_path).ok())
+        .map(|config| config.preferences)
+        .unwrap_or_default()
+}
+
+/// Changes the layout in the preferences of the last configuration. Errors are only logged, the
+/// layout is a convenience and the program works without it.
+pub fn update_layout<F: FnOnce(&mut Preferences)>(change: F) {
+    let Some(file_path) = layout_config() else {
+        return;
+    };
+    if let Err(e) = update_layout_in(Path::new(&file_path), change) {
+        log::warn!("window layout not saved path={} error={}", file_path, e);
+    }
+}
+
+/// Changes the preferences of a configuration file, the other values and the unsaved changes of
+/// the current configuration are left as they are
+pub fn update_layout_in<F: FnOnce(&mut Preferences)>(path: &Path, change: F) -> Result<(), Box<dyn Error>> {
+    let original = std::fs::read_to_string(path)?;
+    let mut config = read_config(&path.to_string_lossy())?;
+    change(&mut config.preferences);
+    let format = ConfigFormat::from_path(path).unwrap_or(ConfigFormat::Yaml);
+    std::fs::write(path, format.update(&original, &config)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_update_layout() {
+        let directory = std::env::temp_dir().join("skycalc_test_layout");
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("config.yaml");
+        let original = include_str!("../../config.yaml");
+        std::fs::write(&path, original).unwrap();
+
+        let geometry = WindowGeometry { x: 40, y: 60, width: 1024, height: 700 };
+        update_layout_in(&path, |preferences| {
+            preferences.window = Some(geometry);
+            preferences.night_vision = true;
+            preferences.open_tools = vec!["F&unctions/&Darkness\t".to_string()];
+        }).unwrap();
+        let updated = std::fs::read_to_string(&path).unwrap();
+        // the rest of the configuration keeps its text
+        assert!(updated.starts_with(original.trim_end()));
+        let preferences = read_config(&path.to_string_lossy()).unwrap().preferences;
+        assert_eq!(preferences.window, Some(geometry));
+        assert!(preferences.night_vision);
+        assert_eq!(preferences.open_tools, vec!["F&unctions/&Darkness\t".to_string()]);
+
+        update_layout_in(&path, |preferences| preferences.open_tools.clear()).unwrap();
+        let preferences = read_config(&path.to_string_lossy()).unwrap().preferences;
+        assert!(preferences.open_tools.is_empty());
+        assert_eq!(preferences.window, Some(geometry));
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert!(update_layout_in(&path, |_| {}).is_err());
+    }
+
+    #[test]
+    fn test_fit_in() {
+        let geometry = WindowGeometry { x: 40, y: 60, width: 1024, height: 700 };
+        assert_eq!(geometry.fit_in((0, 0, 1920, 1040)), geometry);
+        // saved on a second screen that is gone
+        let right = WindowGeometry { x: 2200, ..geometry };
+        assert_eq!(right.fit_in((0, 0, 1920, 1040)).x, 1920 - 1024);
+        // smaller laptop screen, shrunk and kept inside
+        let fitted = geometry.fit_in((0, 0, 1366, 728));
+        assert_eq!((fitted.width, fitted.height), (1024, 688));
+        assert_eq!(fitted.y, 40);
+        let negative = WindowGeometry { x: -500, y: -30, ..geometry };
+        assert_eq!(negative.fit_in((-1280, 0, 1280, 984)).x, -1024);
+        assert_eq!(negative.fit_in((-1280, 0, 1280, 984)).y, 0);
+    }
+}
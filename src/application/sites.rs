@@ -0,0 +1,192 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Embedded database of cities and observatories used to set up the observer by name.
+// Timezones are standard time offsets in hours, daylight saving time is not applied.
+
+use crate::application::observer::Observer;
+use crate::application::timezones::nearest_city_zone;
+
+/// Site struct
+///
+/// Known location that can be used as observer.
+///
+/// # Attributes
+///
+/// * `name` - Name of the site, with country
+/// * `latitude` - Latitude in degrees, positive north
+/// * `longitude` - Longitude in degrees, positive east
+/// * `elevation` - Elevation in meters
+/// * `timezone` - Standard time offset from UTC in hours
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Site {
+    pub name: &'static str,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub elevation: i64,
+    pub timezone: f64,
+}
+
+const fn site(name: &'static str, latitude: f64, longitude: f64, elevation: i64, timezone: f64) -> Site {
+    Site { name, latitude, longitude, elevation, timezone }
+}
+
+pub const SITES: &[Site] = &[
+    // Observatories
+    site("Apache Point Observatory, USA", 32.7803, -105.8203, 2788, -7.0),
+    site("Calar Alto Observatory, Spain", 37.2236, -2.5463, 2168, 1.0),
+    site("Cerro Pachón, Chile", -30.2407, -70.7366, 2715, -4.0),
+    site("Cerro Paranal (VLT), Chile", -24.6272, -70.4042, 2635, -4.0),
+    site("Cerro Tololo (CTIO), Chile", -30.1690, -70.8063, 2207, -4.0),
+    site("Greenwich Royal Observatory, UK", 51.4769, -0.0005, 46, 0.0),
+    site("Hakos Astro Farm, Namibia", -23.2361, 16.3614, 1825, 2.0),
+    site("Hanle Observatory (IAO), India", 32.7794, 78.9642, 4500, 5.5),
+    site("Haute-Provence Observatory, France", 43.9317, 5.7122, 650, 1.0),
+    site("Javalambre Observatory, Spain", 40.0417, -1.0162, 1957, 1.0),
+    site("Kitt Peak National Observatory, USA", 31.9583, -111.5967, 2096, -7.0),
+    site("La Silla Observatory, Chile", -29.2567, -70.7300, 2400, -4.0),
+    site("Las Campanas Observatory, Chile", -29.0146, -70.6926, 2380, -4.0),
+    site("Lick Observatory, USA", 37.3414, -121.6429, 1283, -8.0),
+    site("Lowell Observatory, USA", 35.2029, -111.6646, 2210, -7.0),
+    site("Mauna Kea Observatories, USA", 19.8207, -155.4681, 4205, -10.0),
+    site("McDonald Observatory, USA", 30.6714, -104.0225, 2070, -6.0),
+    site("Mount John Observatory, New Zealand", -43.9856, 170.4650, 1029, 12.0),
+    site("Mount Wilson Observatory, USA", 34.2258, -118.0572, 1742, -8.0),
+    site("Palomar Observatory, USA", 33.3564, -116.8650, 1712, -8.0),
+    site("Pic du Midi Observatory, France", 42.9364, 0.1425, 2877, 1.0),
+    site("Pico dos Dias Observatory, Brazil", -22.5344, -45.5825, 1864, -3.0),
+    site("Roque de los Muchachos, Spain", 28.7606, -17.8816, 2396, 0.0),
+    site("SAAO Sutherland, South Africa", -32.3794, 20.8106, 1798, 2.0),
+    site("San Pedro Mártir, Mexico", 31.0453, -115.4636, 2830, -8.0),
+    site("Siding Spring, Australia", -31.2733, 149.0617, 1165, 10.0),
+    site("Skinakas Observatory, Greece", 35.2117, 24.8997, 1750, 2.0),
+    site("Teide Observatory, Tenerife, Spain", 28.3008, -16.5119, 2390, 0.0),
+    site("Xinglong Observatory, China", 40.3958, 117.5775, 960, 8.0),
+    // Cities
+    site("Amsterdam, Netherlands", 52.3676, 4.9041, 0, 1.0),
+    site("Athens, Greece", 37.9838, 23.7275, 70, 2.0),
+    site("Auckland, New Zealand", -36.8485, 174.7633, 20, 12.0),
+    site("Bangkok, Thailand", 13.7563, 100.5018, 2, 7.0),
+    site("Barcelona, Spain", 41.3874, 2.1686, 12, 1.0),
+    site("Beijing, China", 39.9042, 116.4074, 44, 8.0),
+    site("Berlin, Germany", 52.5200, 13.4050, 34, 1.0),
+    site("Bogotá, Colombia", 4.7110, -74.0721, 2640, -5.0),
+    site("Brasília, Brazil", -15.7939, -47.8828, 1172, -3.0),
+    site("Buenos Aires, Argentina", -34.6037, -58.3816, 25, -3.0),
+    site("Cairo, Egypt", 30.0444, 31.2357, 23, 2.0),
+    site("Cape Town, South Africa", -33.9249, 18.4241, 15, 2.0),
+    site("Chicago, USA", 41.8781, -87.6298, 181, -6.0),
+    site("Denver, USA", 39.7392, -104.9903, 1609, -7.0),
+    site("Dubai, United Arab Emirates", 25.2048, 55.2708, 5, 4.0),
+    site("Helsinki, Finland", 60.1699, 24.9384, 17, 2.0),
+    site("Hong Kong, China", 22.3193, 114.1694, 32, 8.0),
+    site("Honolulu, USA", 21.3069, -157.8583, 6, -10.0),
+    site("Istanbul, Turkey", 41.0082, 28.9784, 39, 3.0),
+    site("Johannesburg, South Africa", -26.2041, 28.0473, 1753, 2.0),
+    site("Lima, Peru", -12.0464, -77.0428, 154, -5.0),
+    site("Lisbon, Portugal", 38.7223, -9.1393, 2, 0.0),
+    site("London, UK", 51.5074, -0.1278, 11, 0.0),
+    site("Longyearbyen, Svalbard, Norway", 78.2232, 15.6267, 10, 1.0),
+    site("Los Angeles, USA", 34.0522, -118.2437, 89, -8.0),
+    site("Madrid, Spain", 40.4168, -3.7038, 667, 1.0),
+    site("Mexico City, Mexico", 19.4326, -99.1332, 2240, -6.0),
+    site("Montreal, Canada", 45.5017, -73.5673, 36, -5.0),
+    site("Moscow, Russia", 55.7558, 37.6173, 156, 3.0),
+    site("Mumbai, India", 19.0760, 72.8777, 14, 5.5),
+    site("Nairobi, Kenya", -1.2921, 36.8219, 1795, 3.0),
+    site("New Delhi, India", 28.6139, 77.2090, 216, 5.5),
+    site("New York, USA", 40.7128, -74.0060, 10, -5.0),
+    site("Paris, France", 48.8566, 2.3522, 35, 1.0),
+    site("Piracaia - SP - Brazil", -23.1, -46.5, 780, -3.0),
+    site("Reykjavík, Iceland", 64.1466, -21.9426, 15, 0.0),
+    site("Rio de Janeiro, Brazil", -22.9068, -43.1729, 5, -3.0),
+    site("Rome, Italy", 41.9028, 12.4964, 21, 1.0),
+    site("Santiago, Chile", -33.4489, -70.6693, 570, -4.0),
+    site("São Paulo, Brazil", -23.5505, -46.6333, 760, -3.0),
+    site("Seoul, South Korea", 37.5665, 126.9780, 38, 9.0),
+    site("Singapore", 1.3521, 103.8198, 15, 8.0),
+    site("Stockholm, Sweden", 59.3293, 18.0686, 28, 1.0),
+    site("Sydney, Australia", -33.8688, 151.2093, 58, 10.0),
+    site("Tokyo, Japan", 35.6762, 139.6503, 40, 9.0),
+    site("Toronto, Canada", 43.6532, -79.3832, 76, -5.0),
+    site("Tromsø, Norway", 69.6492, 18.9553, 10, 1.0),
+    site("Tucson, USA", 32.2226, -110.9747, 728, -7.0),
+    site("Vancouver, Canada", 49.2827, -123.1207, 70, -8.0),
+    site("Vienna, Austria", 48.2082, 16.3738, 190, 1.0),
+];
+
+// Lower case text without the diacritics of latin letters, so "sao" matches "São"
+pub(crate) fn fold(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ã' | 'ä' | 'å' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'í' | 'ì' | 'î' | 'ï' => 'i',
+            'ó' | 'ò' | 'ô' | 'õ' | 'ö' | 'ø' => 'o',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            'ç' => 'c',
+            'ñ' => 'n',
+            _ => c,
+        })
+        .collect()
+}
+
+/// Sites whose name contains the query, ignoring case and accents. Names starting with the
+/// query come first. An empty query returns all sites.
+///
+/// # Examples
+///
+/// ```
+/// use skycalc::application::sites::search_sites;
+///
+/// let sites = search_sites("sao paulo");
+/// assert_eq!(sites[0].name, "São Paulo, Brazil");
+/// ```
+pub fn search_sites(query: &str) -> Vec<&'static Site> {
+    let query = fold(query.trim());
+    let mut starts: Vec<&'static Site> = Vec::new();
+    let mut contains: Vec<&'static Site> = Vec::new();
+    for site in SITES {
+        let name = fold(site.name);
+        if name.starts_with(&query) {
+            starts.push(site);
+        } else if name.contains(&query) {
+            contains.push(site);
+        }
+    }
+    starts.extend(contains);
+    starts
+}
+
+impl Site {
+    pub fn to_observer(self) -> Observer {
+        Observer {
+            name: Some(self.name.to_string()),
+            latitude: self.latitude,
+            longitude: self.longitude,
+            elevation: self.elevation,
+            timezone: self.timezone,
+            zone: nearest_city_zone(self.latitude, self.longitude).map(|zone| zone.name.to_string()),
+        }
+    }
+}
@@ -0,0 +1,592 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Positions, rise, transit and set, magnitude and apparent size of the Sun, the Moon and the
+// planets for the solar system ephemeris report.
+//
+// Planets use the approximate Keplerian elements of E. M. Standish (JPL, "Keplerian Elements for
+// Approximate Positions of the Major Planets", valid 1800 - 2050) with light-time correction and
+// precession to the equinox of date, accurate to about an arc minute. Nutation and aberration are
+// ignored. Magnitudes follow Meeus, Astronomical Algorithms, ch. 41, Saturn without its rings.
+
+use libm::atan2;
+use crate::application::calculation::CalculationSettings;
+use crate::application::environment::Environment;
+use crate::application::moon::{local_maxima, moon_illumination, moon_topocentric_position, MOON_HORIZON};
+use crate::application::observer::Observer;
+use crate::application::rise_set_solver::find_crossing;
+use crate::application::sun::{sun_distance_from_jd, sun_position_from_jd, RiseSetType::Next, Sun,
+                              TwilightType::RiseSet};
+use crate::application::time::{julian_centuries_tt, Time};
+use crate::application::transformations::{angular_separation, precess_from_j2000};
+use crate::utils::utils::{constrain_360, cosd, sind};
+
+// Astronomical unit in km
+const AU_KM: f64 = 149_597_870.7;
+// Light time for one astronomical unit, in days
+const LIGHT_TIME_AU: f64 = 0.005_775_518_3;
+// Obliquity of the ecliptic at J2000, in degrees
+const OBLIQUITY_J2000: f64 = 23.439_28;
+// Apparent altitude of the upper limb at rise and set, refraction included
+const SUN_HORIZON: f64 = -0.8333;
+const PLANET_HORIZON: f64 = -0.5667;
+// Sampling intervals of the ephemeris across the night, in minutes
+pub const EPHEMERIS_INTERVALS: [u32; 4] = [15, 30, 60, 120];
+
+/// Body enum
+///
+/// Solar system bodies of the ephemeris report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Body {
+    Sun,
+    Moon,
+    Mercury,
+    Venus,
+    Mars,
+    Jupiter,
+    Saturn,
+    Uranus,
+    Neptune,
+}
+
+pub const BODIES: [Body; 9] = [
+    Body::Sun,
+    Body::Moon,
+    Body::Mercury,
+    Body::Venus,
+    Body::Mars,
+    Body::Jupiter,
+    Body::Saturn,
+    Body::Uranus,
+    Body::Neptune,
+];
+
+impl Body {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Body::Sun => "Sun",
+            Body::Moon => "Moon",
+            Body::Mercury => "Mercury",
+            Body::Venus => "Venus",
+            Body::Mars => "Mars",
+            Body::Jupiter => "Jupiter",
+            Body::Saturn => "Saturn",
+            Body::Uranus => "Uranus",
+            Body::Neptune => "Neptune",
+        }
+    }
+
+    // Altitude of the body's center at rise and set
+    fn horizon(&self) -> f64 {
+        match self {
+            Body::Sun => SUN_HORIZON,
+            Body::Moon => MOON_HORIZON,
+            _ => PLANET_HORIZON,
+        }
+    }
+}
+
+// Keplerian elements at J2000 and their rates per Julian century: semi-major axis (au),
+// eccentricity, inclination, mean longitude, longitude of perihelion and longitude of the
+// ascending node (degrees)
+struct Elements {
+    a: (f64, f64),
+    e: (f64, f64),
+    i: (f64, f64),
+    l: (f64, f64),
+    perihelion: (f64, f64),
+    node: (f64, f64),
+}
+
+const MERCURY: Elements = Elements {
+    a: (0.387_099_27, 0.000_000_37),
+    e: (0.205_635_93, 0.000_019_06),
+    i: (7.004_979_02, -0.005_947_49),
+    l: (252.250_323_50, 149_472.674_111_75),
+    perihelion: (77.457_796_28, 0.160_476_89),
+    node: (48.330_765_93, -0.125_340_81),
+};
+const VENUS: Elements = Elements {
+    a: (0.723_335_66, 0.000_003_90),
+    e: (0.006_776_72, -0.000_041_07),
+    i: (3.394_676_05, -0.000_788_90),
+    l: (181.979_099_50, 58_517.815_387_29),
+    perihelion: (131.602_467_18, 0.002_683_29),
+    node: (76.679_842_55, -0.277_694_18),
+};
+const EARTH_MOON_BARYCENTER: Elements = Elements {
+    a: (1.000_002_61, 0.000_005_62),
+    e: (0.016_711_23, -0.000_043_92),
+    i: (-0.000_015_31, -0.012_946_68),
+    l: (100.464_571_66, 35_999.372_449_81),
+    perihelion: (102.937_681_93, 0.323_273_64),
+    node: (0.0, 0.0),
+};
+const MARS: Elements = Elements {
+    a: (1.523_710_34, 0.000_018_47),
+    e: (0.093_394_10, 0.000_078_82),
+    i: (1.849_691_42, -0.008_131_31),
+    l: (-4.553_432_05, 19_140.302_684_99),
+    perihelion: (-23.943_629_59, 0.444_410_88),
+    node: (49.559_538_91, -0.292_573_43),
+};
+const JUPITER: Elements = Elements {
+    a: (5.202_887_00, -0.000_116_07),
+    e: (0.048_386_24, -0.000_132_53),
+    i: (1.304_396_95, -0.001_837_14),
+    l: (34.396_440_51, 3_034.746_127_75),
+    perihelion: (14.728_479_83, 0.212_526_68),
+    node: (100.473_909_09, 0.204_691_06),
+};
+const SATURN: Elements = Elements {
+    a: (9.536_675_94, -0.001_250_60),
+    e: (0.053_861_79, -0.000_509_91),
+    i: (2.485_991_87, 0.001_936_09),
+    l: (49.954_244_23, 1_222.493_622_01),
+    perihelion: (92.598_878_31, -0.418_972_16),
+    node: (113.662_424_48, -0.288_677_94),
+};
+const URANUS: Elements = Elements {
+    a: (19.189_164_64, -0.001_961_76),
+    e: (0.047_257_44, -0.000_043_97),
+    i: (0.772_637_83, -0.002_429_39),
+    l: (313.238_104_51, 428.482_027_85),
+    perihelion: (170.954_276_30, 0.408_052_81),
+    node: (74.016_925_03, 0.042_405_89),
+};
+const NEPTUNE: Elements = Elements {
+    a: (30.069_922_76, 0.000_262_91),
+    e: (0.008_590_48, 0.000_051_05),
+    i: (1.770_043_47, 0.000_353_72),
+    l: (-55.120_029_69, 218.459_453_25),
+    perihelion: (44.964_762_27, -0.322_414_64),
+    node: (131.784_225_74, -0.005_086_64),
+};
+
+// Orbital elements, absolute magnitude, phase coefficients (per degree of phase angle, Meeus
+// ch. 41) and apparent diameter at 1 au in arc seconds of a planet
+fn planet(body: Body) -> Option<(&'static Elements, f64, [f64; 3], f64)> {
+    match body {
+        Body::Mercury => Some((&MERCURY, -0.42, [0.0380, -0.000_273, 0.000_002], 6.74)),
+        Body::Venus => Some((&VENUS, -4.40, [0.0009, 0.000_239, -0.000_000_65], 16.92)),
+        Body::Mars => Some((&MARS, -1.52, [0.016, 0.0, 0.0], 9.36)),
+        Body::Jupiter => Some((&JUPITER, -9.40, [0.005, 0.0, 0.0], 196.88)),
+        Body::Saturn => Some((&SATURN, -8.88, [0.0, 0.0, 0.0], 165.46)),
+        Body::Uranus => Some((&URANUS, -7.19, [0.0, 0.0, 0.0], 70.04)),
+        Body::Neptune => Some((&NEPTUNE, -6.87, [0.0, 0.0, 0.0], 67.0)),
+        Body::Sun | Body::Moon => None,
+    }
+}
+
+// Heliocentric ecliptic J2000 position in au, t in Julian centuries (TT) from J2000
+fn heliocentric(elements: &Elements, t: f64) -> [f64; 3] {
+    let at = |(value, rate): (f64, f64)| value + rate * t;
+    let a = at(elements.a);
+    let e = at(elements.e);
+    let i = at(elements.i);
+    let node = at(elements.node);
+    let perihelion = at(elements.perihelion);
+    let argument = perihelion - node;
+    let m = signed_degrees(at(elements.l) - perihelion).to_radians();
+
+    // Kepler's equation by Newton's method
+    let mut eccentric = m + e * m.sin();
+    for _ in 0..10 {
+        let delta = (eccentric - e * eccentric.sin() - m) / (1.0 - e * eccentric.cos());
+        eccentric -= delta;
+        if delta.abs() < 1e-12 {
+            break;
+        }
+    }
+    let x = a * (eccentric.cos() - e);
+    let y = a * (1.0 - e * e).sqrt() * eccentric.sin();
+
+    [
+        (cosd(argument) * cosd(node) - sind(argument) * sind(node) * cosd(i)) * x
+            - (sind(argument) * cosd(node) + cosd(argument) * sind(node) * cosd(i)) * y,
+        (cosd(argument) * sind(node) + sind(argument) * cosd(node) * cosd(i)) * x
+            - (sind(argument) * sind(node) - cosd(argument) * cosd(node) * cosd(i)) * y,
+        sind(argument) * sind(i) * x + cosd(argument) * sind(i) * y,
+    ]
+}
+
+// Angle in degrees from -180 to 180
+fn signed_degrees(angle: f64) -> f64 {
+    (angle + 180.0).rem_euclid(360.0) - 180.0
+}
+
+fn norm(v: [f64; 3]) -> f64 {
+    (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt()
+}
+
+// Geocentric ecliptic J2000 position of a planet when the light left it, its heliocentric
+// position and the heliocentric position of the Earth, in au, t in Julian centuries (TT)
+fn planet_vectors(elements: &Elements, t: f64) -> ([f64; 3], [f64; 3], [f64; 3]) {
+    let earth = heliocentric(&EARTH_MOON_BARYCENTER, t);
+    let geocentric = |position: [f64; 3]| [position[0] - earth[0], position[1] - earth[1], position[2] - earth[2]];
+
+    // position when the light left the planet
+    let distance = norm(geocentric(heliocentric(elements, t)));
+    let position = heliocentric(elements, t - distance * LIGHT_TIME_AU / 36_525.0);
+    (geocentric(position), position, earth)
+}
+
+// Geocentric (ra, dec) of date in degrees, distance from the Earth and from the Sun in au and
+// phase angle in degrees of a planet for a JD(UTC)
+fn planet_geocentric(elements: &Elements, jd: f64) -> (f64, f64, f64, f64, f64) {
+    let t = julian_centuries_tt(jd);
+    let ([x, y, z], position, earth) = planet_vectors(elements, t);
+    let distance = norm([x, y, z]);
+    let r = norm(position);
+    let sun_distance = norm(earth);
+    let phase = ((r * r + distance * distance - sun_distance * sun_distance) / (2.0 * r * distance))
+        .clamp(-1.0, 1.0)
+        .acos()
+        .to_degrees();
+
+    // ecliptic to equatorial J2000, then to the equinox of date
+    let equatorial_y = y * cosd(OBLIQUITY_J2000) - z * sind(OBLIQUITY_J2000);
+    let equatorial_z = y * sind(OBLIQUITY_J2000) + z * cosd(OBLIQUITY_J2000);
+    let ra = constrain_360(atan2(equatorial_y, x).to_degrees());
+    let dec = (equatorial_z / distance).asin().to_degrees();
+    let (ra, dec) = precess_from_j2000(ra, dec, jd_from_centuries(t));
+    (ra, dec, distance, r, phase)
+}
+
+// Geocentric ecliptic longitude of a planet minus the longitude of the Sun in degrees, -180 to
+// 180, for a JD(UTC): 180 at opposition
+fn longitude_from_sun(elements: &Elements, jd: f64) -> f64 {
+    let ([x, y, _], _, earth) = planet_vectors(elements, julian_centuries_tt(jd));
+    signed_degrees(atan2(y, x).to_degrees() - atan2(-earth[1], -earth[0]).to_degrees())
+}
+
+fn jd_from_centuries(t: f64) -> f64 {
+    2_451_545.0 + t * 36_525.0
+}
+
+/// BodyPosition struct
+///
+/// Position and appearance of a solar system body seen from the observer.
+///
+/// # Attributes
+///
+/// * `body` - Body
+/// * `ra` - Right ascension of date in degrees, topocentric for the Moon
+/// * `dec` - Declination of date in degrees, topocentric for the Moon
+/// * `altitude` - Altitude in degrees, without refraction
+/// * `azimuth` - Azimuth in degrees, reckoned from north through east
+/// * `distance` - Distance from the Earth in au
+/// * `magnitude` - Visual magnitude
+/// * `diameter` - Apparent diameter in arc seconds
+/// * `elongation` - Angular distance from the Sun in degrees, positive east of the Sun (evening
+///   sky) and negative west of it (morning sky), 0 for the Sun
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BodyPosition {
+    pub body: Body,
+    pub ra: f64,
+    pub dec: f64,
+    pub altitude: f64,
+    pub azimuth: f64,
+    pub distance: f64,
+    pub magnitude: f64,
+    pub diameter: f64,
+    pub elongation: f64,
+}
+
+// (ra, dec) in degrees, distance in au, magnitude and diameter in arc seconds of a body
+fn body_equatorial(body: Body, observer: &Observer, jd: f64) -> (f64, f64, f64, f64, f64) {
+    match body {
+        Body::Sun => {
+            let (ra, dec) = sun_position_from_jd(jd);
+            let distance = sun_distance_from_jd(jd);
+            (ra, dec, distance, -26.74 + 5.0 * distance.log10(), 1_919.26 / distance)
+        }
+        Body::Moon => {
+            let (ra, dec, distance) =
+                moon_topocentric_position(observer.latitude, observer.longitude, observer.elevation as f64, jd);
+            // phase angle from the illuminated fraction
+            let phase = (2.0 * moon_illumination(jd) - 1.0).clamp(-1.0, 1.0).acos().to_degrees();
+            let magnitude = -12.73 + 0.026 * phase + 4e-9 * phase.powi(4);
+            let diameter = 2.0 * (1_737.4 / distance).asin().to_degrees() * 3_600.0;
+            (ra, dec, distance / AU_KM, magnitude, diameter)
+        }
+        _ => planet_equatorial(body, jd),
+    }
+}
+
+// (ra, dec) in degrees, distance in au, magnitude and diameter in arc seconds of a planet, the
+// same for every observer
+fn planet_equatorial(body: Body, jd: f64) -> (f64, f64, f64, f64, f64) {
+    let (elements, absolute, coefficients, size) = planet(body).expect("planet");
+    let (ra, dec, distance, r, phase) = planet_geocentric(elements, jd);
+    let magnitude = absolute
+        + 5.0 * (r * distance).log10()
+        + coefficients[0] * phase
+        + coefficients[1] * phase * phase
+        + coefficients[2] * phase * phase * phase;
+    (ra, dec, distance, magnitude, size / distance)
+}
+
+/// Elongation in degrees of a body at (ra, dec) from the Sun at `jd` (UTC), positive east of the
+/// Sun and negative west of it
+pub fn elongation(ra: f64, dec: f64, jd: f64) -> f64 {
+    let (sun_ra, sun_dec) = sun_position_from_jd(jd);
+    let separation = angular_separation(ra, dec, sun_ra, sun_dec);
+    if signed_degrees(ra - sun_ra) >= 0.0 { separation } else { -separation }
+}
+
+/// Position of `body` seen from the observer at `jd` (UTC)
+pub fn body_position(body: Body, observer: &Observer, jd: f64) -> BodyPosition {
+    let (ra, dec, distance, magnitude, diameter) = body_equatorial(body, observer, jd);
+    let (altitude, azimuth) = observer.altaz(ra, dec, jd);
+    let elongation = if body == Body::Sun { 0.0 } else { elongation(ra, dec, jd) };
+    BodyPosition { body, ra, dec, altitude, azimuth, distance, magnitude, diameter, elongation }
+}
+
+/// BodyEvents struct
+///
+/// First rise, transit and set of a body in a window, UTC Julian Dates, None when the event does
+/// not happen in the window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BodyEvents {
+    pub rise: Option<f64>,
+    pub transit: Option<f64>,
+    pub set: Option<f64>,
+}
+
+/// Rise, transit and set of `body` between `jd_start` and `jd_end` (UTC)
+pub fn body_events(body: Body, observer: &Observer, jd_start: f64, jd_end: f64) -> BodyEvents {
+    let altitude = |jd: f64| body_position(body, observer, jd).altitude;
+    // hour angle -180 to 180, crossing zero upwards at the upper transit
+    let hour_angle = |jd: f64| {
+        let (ra, _, _, _, _) = body_equatorial(body, observer, jd);
+        signed_degrees(observer.hour_angle(ra, jd))
+    };
+    BodyEvents {
+        rise: find_crossing(altitude, jd_start, jd_end, body.horizon(), true).ok(),
+        transit: find_crossing(hour_angle, jd_start, jd_end, 0.0, true).ok(),
+        set: find_crossing(altitude, jd_start, jd_end, body.horizon(), false).ok(),
+    }
+}
+
+/// Night of `time` from local noon to the next local noon, UTC Julian Dates
+pub fn night_window(observer: &Observer, time: &Time) -> (f64, f64) {
+    let start = (time.to_jd() + 0.5).floor() - observer.timezone / 24.0;
+    (start, start + 1.0)
+}
+
+/// Times (UTC Julian Dates) of the ephemeris: `time` alone without an interval, otherwise every
+/// `interval` minutes from sunset to sunrise of the night, the whole night from local noon when
+/// the Sun does not set or rise
+pub fn ephemeris_times(observer: &Observer, time: &Time, environment: &Environment,
+                       interval: Option<u32>, settings: CalculationSettings) -> Vec<f64> {
+    let Some(interval) = interval.filter(|&minutes| minutes > 0) else {
+        return vec![time.to_jd()];
+    };
+    let (night_start, night_end) = night_window(observer, time);
+    let noon = Time::from_jd(night_start);
+    let sun = Sun::new(observer, &noon, environment).with_settings(settings);
+    let (start, end) = match (sun.get_sunset_utc(Next, RiseSet).jd(), sun.get_sunrise_utc(Next, RiseSet).jd()) {
+        (Some(sunset), Some(sunrise)) if sunrise > sunset => (sunset, sunrise),
+        _ => (night_start, night_end),
+    };
+    let step = interval as f64 / 1_440.0;
+    (0..)
+        .map(|i| start + i as f64 * step)
+        .take_while(|&jd| jd <= end)
+        .collect()
+}
+
+// Search ranges of the planet events, in months
+pub const EVENT_RANGES: [u32; 4] = [3, 6, 12, 24];
+// Superior planets searched for oppositions and inferior planets for greatest elongations
+pub const OPPOSITION_BODIES: [Body; 3] = [Body::Mars, Body::Jupiter, Body::Saturn];
+pub const ELONGATION_BODIES: [Body; 2] = [Body::Mercury, Body::Venus];
+
+/// PlanetEventKind enum
+///
+/// Best times to observe a planet: `Opposition` of a superior planet, opposite the Sun and up all
+/// night, and greatest elongation of an inferior planet east (`EasternElongation`, evening sky)
+/// or west (`WesternElongation`, morning sky) of the Sun.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlanetEventKind {
+    Opposition,
+    EasternElongation,
+    WesternElongation,
+}
+
+impl PlanetEventKind {
+    pub fn description(&self) -> &'static str {
+        match self {
+            PlanetEventKind::Opposition => "opposition",
+            PlanetEventKind::EasternElongation => "greatest elongation east",
+            PlanetEventKind::WesternElongation => "greatest elongation west",
+        }
+    }
+}
+
+/// PlanetEvent struct
+///
+/// Opposition or greatest elongation of a planet with its appearance at that time.
+///
+/// # Attributes
+///
+/// * `body` - Planet
+/// * `kind` - Opposition or greatest elongation
+/// * `jd` - Time of the event, UTC Julian Date
+/// * `elongation` - Elongation in degrees, positive east of the Sun
+/// * `distance` - Distance from the Earth in au
+/// * `magnitude` - Visual magnitude
+/// * `diameter` - Apparent diameter in arc seconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlanetEvent {
+    pub body: Body,
+    pub kind: PlanetEventKind,
+    pub jd: f64,
+    pub elongation: f64,
+    pub distance: f64,
+    pub magnitude: f64,
+    pub diameter: f64,
+}
+
+/// Oppositions of Mars, Jupiter and Saturn and greatest elongations of Mercury and Venus between
+/// `jd_start` and `jd_end` (UTC), in time order
+pub fn planet_events(jd_start: f64, jd_end: f64) -> Vec<PlanetEvent> {
+    const STEP: f64 = 1.0; // days, shorter than a quarter of Mercury's elongation cycle
+    let event = |body: Body, kind: PlanetEventKind| {
+        move |jd: f64| {
+            let (ra, dec, distance, magnitude, diameter) = planet_equatorial(body, jd);
+            PlanetEvent { body, kind, jd, elongation: elongation(ra, dec, jd), distance, magnitude, diameter }
+        }
+    };
+
+    let mut events: Vec<PlanetEvent> = Vec::new();
+    for body in OPPOSITION_BODIES {
+        let (elements, _, _, _) = planet(body).expect("planet");
+        // the Sun moves faster than the planet, so the difference in longitude only passes 180° at
+        // the opposition
+        let opposition = |jd: f64| -cosd(longitude_from_sun(elements, jd));
+        events.extend(local_maxima(opposition, jd_start, jd_end, STEP)
+            .into_iter()
+            .map(event(body, PlanetEventKind::Opposition)));
+    }
+    for body in ELONGATION_BODIES {
+        let (elements, _, _, _) = planet(body).expect("planet");
+        // the difference in longitude of an inferior planet stays within ±47° and runs smoothly
+        // through the conjunctions, unlike the elongation that changes sign there
+        let east = |jd: f64| longitude_from_sun(elements, jd);
+        events.extend(local_maxima(east, jd_start, jd_end, STEP)
+            .into_iter()
+            .map(event(body, PlanetEventKind::EasternElongation)));
+        events.extend(local_maxima(|jd| -east(jd), jd_start, jd_end, STEP)
+            .into_iter()
+            .map(event(body, PlanetEventKind::WesternElongation)));
+    }
+    events.sort_by(|a, b| a.jd.total_cmp(&b.jd));
+    events
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn greenwich() -> Observer {
+        Observer::location(None, "51.4769", "0.0", 0, "0")
+    }
+
+    #[test]
+    fn test_planet_position() {
+        // Venus on 1992 December 20 at 0h TD (Meeus, Astronomical Algorithms, example 33.a and 41.a):
+        // apparent ra 21h04m41.454s, dec -18°53'16.84", distance 0.910947 au, magnitude -4.2
+        let venus = body_position(Body::Venus, &greenwich(), 2_448_976.5);
+        assert!((venus.ra - 316.172_73).abs() < 0.05, "{}", venus.ra);
+        assert!((venus.dec + 18.887_46).abs() < 0.05, "{}", venus.dec);
+        assert!((venus.distance - 0.910_947).abs() < 0.001, "{}", venus.distance);
+        assert!((venus.magnitude + 4.2).abs() < 0.1, "{}", venus.magnitude);
+        assert!((venus.diameter - 18.6).abs() < 0.2, "{}", venus.diameter);
+    }
+
+    #[test]
+    fn test_rise_transit_set() {
+        // Venus at Boston on 1988 March 20 (Meeus, Astronomical Algorithms, example 15.a):
+        // rising 12h25m, transit 19h41m and setting 2h55m UT
+        let boston = Observer::location(None, "42.3333", "-71.0833", 0, "0");
+        let start = Time::new(1988, 3, 20, 0, 0, 0).to_jd();
+        let events = body_events(Body::Venus, &boston, start, start + 1.0);
+        let hours = |jd: Option<f64>| (jd.unwrap() - start) * 24.0;
+        assert!((hours(events.rise) - 12.424).abs() < 0.02);
+        assert!((hours(events.transit) - 19.675).abs() < 0.02);
+        assert!((hours(events.set) - 2.911).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_body_events() {
+        let observer = greenwich();
+        // 2024 March 20, equinox: the Sun rises near 6h and sets near 18h UTC at Greenwich
+        let start = Time::new(2024, 3, 20, 0, 0, 0).to_jd();
+        for body in BODIES {
+            let events = body_events(body, &observer, start, start + 1.0);
+            let transit = events.transit.unwrap_or_else(|| panic!("{}", body.name()));
+            assert!(body_position(body, &observer, transit).altitude > 0.0 || body == Body::Moon,
+                    "{}", body.name());
+        }
+        let sun = body_events(Body::Sun, &observer, start, start + 1.0);
+        assert!((sun.rise.unwrap() - start - 0.25).abs() < 0.01);
+        assert!((sun.set.unwrap() - start - 0.75).abs() < 0.01);
+        assert!((sun.transit.unwrap() - start - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_planet_events() {
+        // 2024 December to 2025 June: Jupiter at opposition on December 7, Venus at greatest
+        // elongation east (47.2°) on January 10 and west (45.9°) on June 1, Mars at opposition on
+        // January 16 and Mercury at greatest elongation east (18.2°) on March 8
+        let start = Time::new(2024, 12, 1, 0, 0, 0).to_jd();
+        let events = planet_events(start, start + 183.0);
+        let find = |body: Body, kind: PlanetEventKind| {
+            *events.iter().find(|e| e.body == body && e.kind == kind)
+                .unwrap_or_else(|| panic!("{}", kind.description()))
+        };
+        let days = |event: PlanetEvent, year: i64, month: u64, day: u64| {
+            (event.jd - Time::new(year, month, day, 12, 0, 0).to_jd()).abs()
+        };
+
+        let jupiter = find(Body::Jupiter, PlanetEventKind::Opposition);
+        assert!(days(jupiter, 2024, 12, 7) < 1.0);
+        assert!(jupiter.elongation.abs() > 170.0);
+        let mars = find(Body::Mars, PlanetEventKind::Opposition);
+        assert!(days(mars, 2025, 1, 16) < 1.0);
+        assert!(mars.diameter > 14.0 && mars.magnitude < -1.0);
+        let venus = find(Body::Venus, PlanetEventKind::EasternElongation);
+        assert!(days(venus, 2025, 1, 10) < 1.0);
+        assert!((venus.elongation - 47.2).abs() < 0.3);
+        let venus = find(Body::Venus, PlanetEventKind::WesternElongation);
+        assert!(days(venus, 2025, 6, 1) < 1.0);
+        assert!((venus.elongation + 45.9).abs() < 0.3);
+        let mercury = find(Body::Mercury, PlanetEventKind::EasternElongation);
+        assert!(days(mercury, 2025, 3, 8) < 1.0);
+        assert!((mercury.elongation - 18.2).abs() < 0.3);
+
+        assert!(!events.iter().any(|e| e.body == Body::Saturn));
+        assert!(events.windows(2).all(|w| w[0].jd <= w[1].jd));
+    }
+}
@@ -0,0 +1,533 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Embedded catalog of bright named stars used to suggest go-to alignment stars. Positions are
+// J2000 in degrees and visual magnitudes, about 340 stars complete to about magnitude 3 with the
+// named fainter stars of the northern and southern skies down to magnitude 3.8.
+
+use crate::application::calculation::CalculationSettings;
+use crate::application::environment::Environment;
+use crate::application::observer::Observer;
+use crate::application::sun::{RiseSetType::Next, Sun, TwilightType::NauticalTwilight};
+use crate::application::time::Time;
+
+// Alignment stars are picked between these altitudes, low stars suffer from refraction and
+// stars near the zenith from the alt-az singularity of most mounts
+pub const ALIGNMENT_MIN_ALTITUDE: f64 = 30.0;
+pub const ALIGNMENT_MAX_ALTITUDE: f64 = 80.0;
+// Alignment stars suggested for a session
+pub const ALIGNMENT_STARS: usize = 6;
+
+/// Star struct
+///
+/// Bright named star of the embedded catalog.
+///
+/// # Attributes
+///
+/// * `name` - Proper name
+/// * `designation` - Bayer designation, e.g. Alpha CMa
+/// * `ra` - Right ascension J2000 in degrees
+/// * `dec` - Declination J2000 in degrees
+/// * `magnitude` - Visual magnitude
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Star {
+    pub name: &'static str,
+    pub designation: &'static str,
+    pub ra: f64,
+    pub dec: f64,
+    pub magnitude: f64,
+}
+
+const fn star(name: &'static str, designation: &'static str, ra: f64, dec: f64, magnitude: f64) -> Star {
+    Star { name, designation, ra, dec, magnitude }
+}
+
+// Brightest first, magnitudes such as 3.14 are catalog values and not an approximation of pi
+#[allow(clippy::approx_constant)]
+pub const BRIGHT_STARS: &[Star] = &[
+    star("Sirius", "Alpha CMa", 101.2871, -16.7161, -1.46),
+    star("Canopus", "Alpha Car", 95.9879, -52.6956, -0.74),
+    star("Rigil Kentaurus", "Alpha Cen", 219.9021, -60.8339, -0.27),
+    star("Arcturus", "Alpha Boo", 213.9154, 19.1825, -0.05),
+    star("Vega", "Alpha Lyr", 279.2346, 38.7836, 0.03),
+    star("Capella", "Alpha Aur", 79.1725, 45.9981, 0.08),
+    star("Rigel", "Beta Ori", 78.6346, -8.2017, 0.13),
+    star("Procyon", "Alpha CMi", 114.8254, 5.2250, 0.34),
+    star("Achernar", "Alpha Eri", 24.4283, -57.2367, 0.46),
+    star("Betelgeuse", "Alpha Ori", 88.7929, 7.4069, 0.50),
+    star("Hadar", "Beta Cen", 210.9558, -60.3731, 0.61),
+    star("Altair", "Alpha Aql", 297.6958, 8.8683, 0.76),
+    star("Acrux", "Alpha Cru", 186.6496, -63.0992, 0.76),
+    star("Aldebaran", "Alpha Tau", 68.9800, 16.5092, 0.86),
+    star("Antares", "Alpha Sco", 247.3517, -26.4319, 0.96),
+    star("Spica", "Alpha Vir", 201.2983, -11.1614, 0.97),
+    star("Pollux", "Beta Gem", 116.3287, 28.0261, 1.14),
+    star("Fomalhaut", "Alpha PsA", 344.4125, -29.6222, 1.16),
+    star("Deneb", "Alpha Cyg", 310.3579, 45.2803, 1.25),
+    star("Mimosa", "Beta Cru", 191.9304, -59.6886, 1.25),
+    star("Regulus", "Alpha Leo", 152.0929, 11.9672, 1.35),
+    star("Adhara", "Epsilon CMa", 104.6562, -28.9722, 1.50),
+    star("Castor", "Alpha Gem", 113.6500, 31.8883, 1.58),
+    star("Shaula", "Lambda Sco", 263.4021, -37.1039, 1.62),
+    star("Gacrux", "Gamma Cru", 187.7917, -57.1133, 1.63),
+    star("Bellatrix", "Gamma Ori", 81.2829, 6.3497, 1.64),
+    star("Elnath", "Beta Tau", 81.5729, 28.6075, 1.65),
+    star("Miaplacidus", "Beta Car", 138.3000, -69.7172, 1.68),
+    star("Alnilam", "Epsilon Ori", 84.0533, -1.2019, 1.69),
+    star("Alnair", "Alpha Gru", 332.0583, -46.9611, 1.74),
+    star("Alnitak", "Zeta Ori", 85.1896, -1.9428, 1.77),
+    star("Alioth", "Epsilon UMa", 193.5071, 55.9597, 1.77),
+    star("Dubhe", "Alpha UMa", 165.9321, 61.7508, 1.79),
+    star("Mirfak", "Alpha Per", 51.0808, 49.8611, 1.79),
+    star("Wezen", "Delta CMa", 107.0979, -26.3933, 1.83),
+    star("Regor", "Gamma Vel", 122.3833, -47.3367, 1.83),
+    star("Kaus Australis", "Epsilon Sgr", 276.0429, -34.3847, 1.85),
+    star("Avior", "Epsilon Car", 125.6283, -59.5094, 1.86),
+    star("Alkaid", "Eta UMa", 206.8850, 49.3133, 1.86),
+    star("Sargas", "Theta Sco", 264.3296, -42.9978, 1.87),
+    star("Menkalinan", "Beta Aur", 89.8821, 44.9475, 1.90),
+    star("Atria", "Alpha TrA", 252.1662, -69.0278, 1.91),
+    star("Alhena", "Gamma Gem", 99.4279, 16.3992, 1.92),
+    star("Peacock", "Alpha Pav", 306.4121, -56.7350, 1.94),
+    star("Alsephina", "Delta Vel", 131.1758, -54.7083, 1.96),
+    star("Polaris", "Alpha UMi", 37.9546, 89.2642, 1.98),
+    star("Mirzam", "Beta CMa", 95.6750, -17.9558, 1.98),
+    star("Alphard", "Alpha Hya", 141.8967, -8.6586, 1.98),
+    star("Hamal", "Alpha Ari", 31.7933, 23.4625, 2.00),
+    star("Algieba", "Gamma Leo", 154.9933, 19.8414, 2.01),
+    star("Diphda", "Beta Cet", 10.8975, -17.9867, 2.02),
+    star("Nunki", "Sigma Sgr", 283.8163, -26.2967, 2.05),
+    star("Mirach", "Beta And", 17.4329, 35.6206, 2.05),
+    star("Menkent", "Theta Cen", 211.6704, -36.3700, 2.06),
+    star("Alpheratz", "Alpha And", 2.0971, 29.0906, 2.06),
+    star("Rasalhague", "Alpha Oph", 263.7337, 12.5600, 2.07),
+    star("Tiaki", "Beta Gru", 340.6671, -46.8847, 2.07),
+    star("Kochab", "Beta UMi", 222.6763, 74.1556, 2.08),
+    star("Saiph", "Kappa Ori", 86.9392, -9.6697, 2.09),
+    star("Algol", "Beta Per", 47.0421, 40.9556, 2.12),
+    star("Denebola", "Beta Leo", 177.2650, 14.5719, 2.14),
+    star("Muhlifain", "Gamma Cen", 190.3792, -48.9597, 2.17),
+    star("Aspidiske", "Iota Car", 139.2725, -59.2753, 2.21),
+    star("Suhail", "Lambda Vel", 136.9992, -43.4325, 2.21),
+    star("Alphecca", "Alpha CrB", 233.6721, 26.7147, 2.23),
+    star("Mizar", "Zeta UMa", 200.9812, 54.9253, 2.23),
+    star("Sadr", "Gamma Cyg", 305.5571, 40.2567, 2.23),
+    star("Mintaka", "Delta Ori", 83.0017, -0.2992, 2.23),
+    star("Schedar", "Alpha Cas", 10.1267, 56.5372, 2.24),
+    star("Eltanin", "Gamma Dra", 269.1517, 51.4889, 2.24),
+    star("Naos", "Zeta Pup", 120.8958, -40.0033, 2.25),
+    star("Almach", "Gamma And", 30.9750, 42.3297, 2.26),
+    star("Caph", "Beta Cas", 2.2946, 59.1497, 2.28),
+    star("Dschubba", "Delta Sco", 240.0833, -22.6217, 2.29),
+    star("Larawag", "Epsilon Sco", 252.5408, -34.2933, 2.29),
+    star("Alpha Lupi", "Alpha Lup", 220.4825, -47.3881, 2.30),
+    star("Epsilon Centauri", "Epsilon Cen", 204.9721, -53.4664, 2.30),
+    star("Eta Centauri", "Eta Cen", 218.8767, -42.1578, 2.31),
+    star("Merak", "Beta UMa", 165.4604, 56.3825, 2.37),
+    star("Izar", "Epsilon Boo", 221.2467, 27.0742, 2.37),
+    star("Enif", "Epsilon Peg", 326.0467, 9.8750, 2.38),
+    star("Girtab", "Kappa Sco", 265.6221, -39.0300, 2.39),
+    star("Ankaa", "Alpha Phe", 6.5708, -42.3061, 2.40),
+    star("Scheat", "Beta Peg", 345.9438, 28.0828, 2.42),
+    star("Sabik", "Eta Oph", 257.5946, -15.7247, 2.43),
+    star("Phecda", "Gamma UMa", 178.4575, 53.6947, 2.44),
+    star("Alderamin", "Alpha Cep", 319.6450, 62.5856, 2.45),
+    star("Aludra", "Eta CMa", 111.0238, -29.3031, 2.45),
+    star("Markeb", "Kappa Vel", 140.5283, -55.0108, 2.47),
+    star("Navi", "Gamma Cas", 14.1771, 60.7167, 2.47),
+    star("Aljanah", "Epsilon Cyg", 311.5529, 33.9703, 2.48),
+    star("Markab", "Alpha Peg", 346.1904, 15.2053, 2.49),
+    star("Delta Centauri", "Delta Cen", 182.0896, -50.7222, 2.52),
+    star("Menkar", "Alpha Cet", 45.5700, 4.0897, 2.54),
+    star("Han", "Zeta Oph", 249.2896, -10.5672, 2.54),
+    star("Zeta Centauri", "Zeta Cen", 208.8850, -47.2883, 2.55),
+    star("Zosma", "Delta Leo", 168.5271, 20.5236, 2.56),
+    star("Acrab", "Beta Sco", 241.3592, -19.8053, 2.56),
+    star("Arneb", "Alpha Lep", 83.1825, -17.8222, 2.58),
+    star("Gienah", "Gamma Crv", 183.9517, -17.5419, 2.59),
+    star("Ascella", "Zeta Sgr", 285.6529, -29.8800, 2.60),
+    star("Zubeneschamali", "Beta Lib", 229.2517, -9.3831, 2.61),
+    star("Mahasim", "Theta Aur", 89.9304, 37.2125, 2.62),
+    star("Unukalhai", "Alpha Ser", 236.0671, 6.4256, 2.63),
+    star("Sheratan", "Beta Ari", 28.6600, 20.8081, 2.64),
+    star("Phact", "Alpha Col", 84.9121, -34.0742, 2.65),
+    star("Kraz", "Beta Crv", 188.5967, -23.3967, 2.65),
+    star("Ruchbah", "Delta Cas", 21.4542, 60.2353, 2.68),
+    star("Muphrid", "Eta Boo", 208.6713, 18.3978, 2.68),
+    star("Beta Lupi", "Beta Lup", 224.6329, -43.1339, 2.68),
+    star("Hassaleh", "Iota Aur", 74.2483, 33.1661, 2.69),
+    star("Mu Velorum", "Mu Vel", 161.6925, -49.4200, 2.69),
+    star("Alpha Muscae", "Alpha Mus", 189.2958, -69.1356, 2.69),
+    star("Lesath", "Upsilon Sco", 262.6908, -37.2958, 2.70),
+    star("Pi Puppis", "Pi Pup", 109.2858, -37.0975, 2.70),
+    star("Kaus Media", "Delta Sgr", 275.2483, -29.8281, 2.70),
+    star("Tarazed", "Gamma Aql", 296.5650, 10.6133, 2.72),
+    star("Yed Prior", "Delta Oph", 243.5863, -3.6944, 2.73),
+    star("Porrima", "Gamma Vir", 190.4150, -1.4494, 2.74),
+    star("Athebyne", "Eta Dra", 245.9979, 61.5142, 2.74),
+    star("Zubenelgenubi", "Alpha Lib", 222.7196, -16.0417, 2.75),
+    star("Iota Centauri", "Iota Cen", 200.1492, -36.7122, 2.75),
+    star("Cebalrai", "Beta Oph", 265.8683, 4.5672, 2.76),
+    star("Theta Carinae", "Theta Car", 160.7392, -64.3944, 2.76),
+    star("Hatysa", "Iota Ori", 83.8583, -5.9100, 2.77),
+    star("Kornephoros", "Beta Her", 247.5550, 21.4897, 2.78),
+    star("Gamma Lupi", "Gamma Lup", 233.7850, -41.1667, 2.78),
+    star("Rastaban", "Beta Dra", 262.6083, 52.3014, 2.79),
+    star("Cursa", "Beta Eri", 76.9625, -5.0864, 2.79),
+    star("Imai", "Delta Cru", 183.7862, -58.7489, 2.79),
+    star("Beta Hydri", "Beta Hyi", 6.4379, -77.2542, 2.80),
+    star("Zeta Herculis", "Zeta Her", 250.3217, 31.6028, 2.81),
+    star("Kaus Borealis", "Lambda Sgr", 276.9925, -25.4217, 2.81),
+    star("Tureis", "Rho Pup", 121.8858, -24.3042, 2.81),
+    star("Paikauhale", "Tau Sco", 248.9708, -28.2161, 2.82),
+    star("Algenib", "Gamma Peg", 3.3092, 15.1836, 2.83),
+    star("Vindemiatrix", "Epsilon Vir", 195.5442, 10.9592, 2.83),
+    star("Nihal", "Beta Lep", 82.0613, -20.7594, 2.84),
+    star("Zeta Persei", "Zeta Per", 58.5329, 31.8836, 2.85),
+    star("Deneb Algedi", "Delta Cap", 326.7600, -16.1272, 2.85),
+    star("Beta Arae", "Beta Ara", 261.3250, -55.5300, 2.85),
+    star("Beta Trianguli Australis", "Beta TrA", 238.7858, -63.4308, 2.85),
+    star("Alpha Tucanae", "Alpha Tuc", 334.6254, -60.2597, 2.86),
+    star("Alpha Hydri", "Alpha Hyi", 29.6925, -61.5697, 2.86),
+    star("Alcyone", "Eta Tau", 56.8713, 24.1050, 2.87),
+    star("Tejat", "Mu Gem", 95.7400, 22.5136, 2.87),
+    star("Fawaris", "Delta Cyg", 296.2437, 45.1308, 2.87),
+    star("Cor Caroli", "Alpha CVn", 194.0071, 38.3183, 2.88),
+    star("Acamar", "Theta Eri", 44.5654, -40.3047, 2.88),
+    star("Epsilon Persei", "Epsilon Per", 59.4633, 40.0103, 2.89),
+    star("Gomeisa", "Beta CMi", 111.7875, 8.2894, 2.89),
+    star("Fang", "Pi Sco", 239.7129, -26.1142, 2.89),
+    star("Alniyat", "Sigma Sco", 245.2971, -25.5928, 2.89),
+    star("Albaldah", "Pi Sgr", 287.4408, -21.0236, 2.89),
+    star("Gamma Trianguli Australis", "Gamma TrA", 229.7275, -68.6794, 2.89),
+    star("Sadalsuud", "Beta Aqr", 322.8896, -5.5711, 2.90),
+    star("Upsilon Carinae", "Upsilon Car", 146.7754, -65.0719, 2.92),
+    star("Gamma Persei", "Gamma Per", 46.1992, 53.5064, 2.93),
+    star("Tau Puppis", "Tau Pup", 102.4842, -50.6147, 2.93),
+    star("Matar", "Eta Peg", 340.7504, 30.2214, 2.94),
+    star("Algorab", "Delta Crv", 187.4663, -16.5156, 2.95),
+    star("Zaurak", "Gamma Eri", 59.5075, -13.5086, 2.95),
+    star("Sadalmelik", "Alpha Aqr", 331.4458, -0.3197, 2.95),
+    star("Alpha Arae", "Alpha Ara", 262.9604, -49.8761, 2.95),
+    star("Ras Elased Australis", "Epsilon Leo", 146.4629, 23.7742, 2.98),
+    star("Mebsuta", "Epsilon Gem", 100.9829, 25.1311, 2.98),
+    star("Alnasl", "Gamma Sgr", 271.4521, -30.4242, 2.99),
+    star("Zeta Aquilae", "Zeta Aql", 286.3525, 13.8633, 2.99),
+    star("Iota Scorpii", "Iota1 Sco", 266.8963, -40.1269, 2.99),
+    star("Almaaz", "Epsilon Aur", 75.4921, 43.8233, 2.99),
+    star("Pherkad", "Gamma UMi", 230.1821, 71.8339, 3.00),
+    star("Gamma Hydrae", "Gamma Hya", 199.7304, -23.1714, 3.00),
+    star("Tianguan", "Zeta Tau", 84.4113, 21.1425, 3.00),
+    star("Xamidimura", "Mu1 Sco", 252.9675, -38.0475, 3.00),
+    star("Minkar", "Epsilon Crv", 182.5312, -22.6197, 3.00),
+    star("Beta Trianguli", "Beta Tri", 32.3858, 34.9872, 3.00),
+    star("Delta Persei", "Delta Per", 55.7313, 47.7875, 3.01),
+    star("Psi Ursae Majoris", "Psi UMa", 167.4158, 44.4986, 3.01),
+    star("Gamma Gruis", "Gamma Gru", 328.4821, -37.3650, 3.01),
+    star("Omicron2 Canis Majoris", "Omicron2 CMa", 105.7562, -23.8333, 3.02),
+    star("Furud", "Zeta CMa", 95.0783, -30.0633, 3.02),
+    star("Seginus", "Gamma Boo", 218.0196, 38.3083, 3.03),
+    star("Mu Centauri", "Mu Cen", 207.4042, -42.4736, 3.04),
+    star("Albireo", "Beta Cyg", 292.6804, 27.9597, 3.05),
+    star("Dabih", "Beta Cap", 305.2529, -14.7814, 3.05),
+    star("Beta Muscae", "Beta Mus", 191.5704, -68.1081, 3.05),
+    star("Tania Australis", "Mu UMa", 155.5821, 41.4994, 3.06),
+    star("Altais", "Delta Dra", 288.1388, 67.6617, 3.07),
+    star("Rasalgethi", "Alpha Her", 258.6621, 14.3903, 3.10),
+    star("Alpha Indi", "Alpha Ind", 309.3917, -47.2914, 3.11),
+    star("Eta Sagittarii", "Eta Sgr", 274.4067, -36.7617, 3.11),
+    star("Zeta Hydrae", "Zeta Hya", 133.8483, 5.9456, 3.11),
+    star("Nu Hydrae", "Nu Hya", 162.4062, -16.1936, 3.11),
+    star("Wazn", "Beta Col", 87.7400, -35.7683, 3.12),
+    star("Kappa Centauri", "Kappa Cen", 224.7904, -42.1042, 3.13),
+    star("Lambda Centauri", "Lambda Cen", 173.9454, -63.0197, 3.13),
+    star("Zeta Arae", "Zeta Ara", 254.6550, -55.9900, 3.13),
+    star("Sarin", "Delta Her", 258.7579, 24.8392, 3.14),
+    star("Alpha Lyncis", "Alpha Lyn", 140.2637, 34.3925, 3.14),
+    star("Talitha", "Iota UMa", 134.8021, 48.0417, 3.14),
+    star("Pi Herculis", "Pi Her", 258.7617, 36.8092, 3.16),
+    star("Phi Sagittarii", "Phi Sgr", 281.4142, -26.9908, 3.17),
+    star("Nu Puppis", "Nu Pup", 99.4404, -43.1958, 3.17),
+    star("Haedus", "Eta Aur", 76.6287, 41.2344, 3.17),
+    star("Theta Ursae Majoris", "Theta UMa", 143.2142, 51.6772, 3.17),
+    star("Aldhibah", "Zeta Dra", 257.1967, 65.7147, 3.17),
+    star("Epsilon Leporis", "Epsilon Lep", 76.3654, -22.3711, 3.19),
+    star("Tabit", "Pi3 Ori", 72.4600, 6.9614, 3.19),
+    star("Alpha Circini", "Alpha Cir", 220.6267, -64.9750, 3.19),
+    star("Kappa Ophiuchi", "Kappa Oph", 254.4171, 9.3750, 3.20),
+    star("Zeta Cygni", "Zeta Cyg", 318.2342, 30.2269, 3.21),
+    star("Fuyue", "G Sco", 267.4646, -37.0433, 3.21),
+    star("Errai", "Gamma Cep", 354.8371, 77.6325, 3.21),
+    star("Delta Lupi", "Delta Lup", 230.3429, -40.6475, 3.22),
+    star("Alfirk", "Beta Cep", 322.1650, 70.5608, 3.23),
+    star("Sulafat", "Gamma Lyr", 284.7358, 32.6894, 3.24),
+    star("Yed Posterior", "Epsilon Oph", 244.5804, -4.6925, 3.24),
+    star("Gamma Hydri", "Gamma Hyi", 56.8096, -74.2389, 3.24),
+    star("Sigma Puppis", "Sigma Pup", 112.3075, -43.3014, 3.25),
+    star("Theta Aquilae", "Theta Aql", 302.8263, -0.8214, 3.26),
+    star("Eta Serpentis", "Eta Ser", 275.3275, -2.8989, 3.26),
+    star("Skat", "Delta Aqr", 343.6625, -15.8208, 3.27),
+    star("Theta Ophiuchi", "Theta Oph", 260.5025, -24.9994, 3.27),
+    star("Pi Hydrae", "Pi Hya", 211.5929, -26.6822, 3.27),
+    star("p Carinae", "p Car", 158.0062, -61.6853, 3.27),
+    star("Delta Andromedae", "Delta And", 9.8321, 30.8611, 3.27),
+    star("Alpha Doradus", "Alpha Dor", 68.4992, -55.0450, 3.27),
+    star("Alpha Pictoris", "Alpha Pic", 102.0479, -61.9414, 3.27),
+    star("Propus", "Eta Gem", 93.7196, 22.5067, 3.28),
+    star("Brachium", "Sigma Lib", 226.0175, -25.2819, 3.29),
+    star("Omega Carinae", "Omega Car", 153.4342, -70.0378, 3.29),
+    star("Edasich", "Iota Dra", 231.2325, 58.9661, 3.29),
+    star("Megrez", "Delta UMa", 183.8567, 57.0325, 3.31),
+    star("Mu Leporis", "Mu Lep", 78.2329, -16.2056, 3.31),
+    star("Beta Phoenicis", "Beta Phe", 16.5208, -46.7186, 3.31),
+    star("Nu Ophiuchi", "Nu Oph", 269.7567, -9.7736, 3.32),
+    star("Tau Sagittarii", "Tau Sgr", 286.7350, -27.6703, 3.32),
+    star("Eta Scorpii", "Eta Sco", 258.0383, -43.2392, 3.33),
+    star("Chertan", "Theta Leo", 168.5600, 15.4294, 3.33),
+    star("Azmidi", "Xi Pup", 117.3237, -24.8597, 3.34),
+    star("Gamma Arae", "Gamma Ara", 261.3487, -56.3778, 3.34),
+    star("Muscida", "Omicron UMa", 127.5662, 60.7181, 3.35),
+    star("Zeta Cephei", "Zeta Cep", 332.7138, 58.2014, 3.35),
+    star("Alpha Reticuli", "Alpha Ret", 63.6062, -62.4739, 3.35),
+    star("Delta Aquilae", "Delta Aql", 291.3746, 3.1147, 3.36),
+    star("Eta Orionis", "Eta Ori", 81.1192, -2.3969, 3.36),
+    star("Alzirr", "Xi Gem", 101.3225, 12.8956, 3.36),
+    star("Segin", "Epsilon Cas", 28.5987, 63.6700, 3.37),
+    star("Heze", "Zeta Vir", 203.6733, -0.5958, 3.37),
+    star("Epsilon Lupi", "Epsilon Lup", 230.6704, -44.6897, 3.37),
+    star("Auva", "Delta Vir", 193.9008, 3.3975, 3.38),
+    star("Epsilon Hydrae", "Epsilon Hya", 131.6938, 6.4189, 3.38),
+    star("Gorgonea Tertia", "Rho Per", 46.2942, 38.8403, 3.39),
+    star("Meissa", "Lambda Ori", 83.7846, 9.9342, 3.39),
+    star("Homam", "Zeta Peg", 340.3654, 10.8314, 3.40),
+    star("Chamukuy", "Theta2 Tau", 67.1654, 15.8708, 3.40),
+    star("Nu Centauri", "Nu Cen", 207.3762, -41.6878, 3.41),
+    star("Mothallah", "Alpha Tri", 28.2704, 29.5789, 3.41),
+    star("Gamma Phoenicis", "Gamma Phe", 22.0913, -43.3183, 3.41),
+    star("Zeta Lupi", "Zeta Lup", 228.0712, -52.0992, 3.41),
+    star("Mu Herculis", "Mu Her", 266.6146, 27.7206, 3.42),
+    star("Beta Pavonis", "Beta Pav", 311.2396, -66.2033, 3.42),
+    star("Eta Lupi", "Eta Lup", 240.0304, -38.3967, 3.42),
+    star("Al Thalimain", "Lambda Aql", 286.5621, -4.8825, 3.43),
+    star("a Carinae", "a Car", 137.7421, -58.9669, 3.43),
+    star("Eta Cephei", "Eta Cep", 311.3225, 61.8389, 3.43),
+    star("Achird", "Eta Cas", 12.2763, 57.8153, 3.44),
+    star("Adhafera", "Zeta Leo", 154.1725, 23.4172, 3.44),
+    star("Deneb Algenubi", "Eta Cet", 17.1475, -10.1822, 3.45),
+    star("Tania Borealis", "Lambda UMa", 154.2742, 42.9144, 3.45),
+    star("Kaffaljidhma", "Gamma Cet", 40.8250, 3.2358, 3.47),
+    star("Lambda Tauri", "Lambda Tau", 60.1700, 12.4903, 3.47),
+    star("Chi Carinae", "Chi Car", 119.1946, -52.9822, 3.47),
+    star("Sigma Canis Majoris", "Sigma CMa", 105.4296, -27.9347, 3.47),
+    star("Delta Bootis", "Delta Boo", 228.8758, 33.3147, 3.47),
+    star("Eta Leonis", "Eta Leo", 151.8329, 16.7625, 3.48),
+    star("Alula Borealis", "Nu UMa", 169.6196, 33.0942, 3.48),
+    star("Epsilon Gruis", "Epsilon Gru", 342.1388, -51.3169, 3.49),
+    star("Tau Ceti", "Tau Cet", 26.0171, -15.9375, 3.50),
+    star("Nekkar", "Beta Boo", 225.4867, 40.3906, 3.50),
+    star("Sadalbari", "Mu Peg", 342.5008, 24.6017, 3.51),
+    star("Xi Sagittarii", "Xi2 Sgr", 284.4325, -21.1067, 3.51),
+    star("Sheliak", "Beta Lyr", 282.5200, 33.3628, 3.52),
+    star("Subra", "Omicron Leo", 145.2875, 9.8922, 3.52),
+    star("Phi Velorum", "Phi Vel", 149.2154, -54.5678, 3.52),
+    star("Tarf", "Beta Cnc", 124.1288, 9.1856, 3.52),
+    star("Iota Cephei", "Iota Cep", 342.4200, 66.2003, 3.52),
+    star("Biham", "Theta Peg", 332.5500, 6.1978, 3.53),
+    star("Ain", "Epsilon Tau", 67.1542, 19.1806, 3.53),
+    star("Eta Herculis", "Eta Her", 250.7242, 38.9222, 3.53),
+    star("Mu Serpentis", "Mu Ser", 237.4050, -3.4303, 3.53),
+    star("Wasat", "Delta Gem", 110.0308, 21.9822, 3.53),
+    star("Xi Serpentis", "Xi Ser", 264.3967, -15.3986, 3.54),
+    star("Xi Hydrae", "Xi Hya", 173.2504, -31.8575, 3.54),
+    star("Rana", "Delta Eri", 55.8121, -9.7633, 3.54),
+    star("Zeta Leporis", "Zeta Lep", 86.7387, -14.8219, 3.55),
+    star("Iota Ceti", "Iota Cet", 4.8571, -8.8239, 3.56),
+    star("Delta Crateris", "Delta Crt", 169.8350, -14.7786, 3.56),
+    star("Phi Eridani", "Phi Eri", 34.1275, -51.5122, 3.56),
+    star("Delta Pavonis", "Delta Pav", 302.1817, -66.1822, 3.56),
+    star("Algedi", "Alpha2 Cap", 304.5137, -12.5447, 3.57),
+    star("Kappa Geminorum", "Kappa Gem", 116.1117, 24.3981, 3.57),
+    star("Chi Draconis", "Chi Dra", 275.2642, 72.7328, 3.57),
+    star("Nembus", "51 And", 24.4983, 48.6283, 3.57),
+    star("Phi1 Lupi", "Phi1 Lup", 230.4517, -36.2614, 3.57),
+    star("Rho Bootis", "Rho Boo", 217.9575, 30.3714, 3.58),
+    star("Ginan", "Epsilon Cru", 185.3400, -60.4011, 3.59),
+    star("Theta Ceti", "Theta Cet", 21.0058, -8.1836, 3.60),
+    star("Omicron Velorum", "Omicron Vel", 130.0733, -52.9219, 3.60),
+    star("Tau Orionis", "Tau Ori", 79.4017, -6.8444, 3.60),
+    star("Zavijava", "Beta Vir", 177.6738, 1.7647, 3.61),
+    star("Lambda Hydrae", "Lambda Hya", 152.6471, -12.3542, 3.61),
+    star("Atlas", "27 Tau", 57.2904, 24.0533, 3.62),
+    star("Zeta Scorpii", "Zeta2 Sco", 253.6458, -42.3614, 3.62),
+    star("Alpherg", "Eta Psc", 22.8708, 15.3458, 3.62),
+    star("Eta Pavonis", "Eta Pav", 266.4329, -64.7239, 3.62),
+    star("Delta Arae", "Delta Ara", 262.7746, -60.6836, 3.62),
+    star("Rotanev", "Beta Del", 309.3871, 14.5953, 3.63),
+    star("Thuban", "Alpha Dra", 211.0975, 64.3758, 3.65),
+    star("Prima Hyadum", "Gamma Tau", 64.9483, 15.6275, 3.65),
+    star("Beta Indi", "Beta Ind", 313.7025, -58.4542, 3.65),
+    star("Nashira", "Gamma Cap", 325.0229, -16.6622, 3.68),
+    star("Pi4 Orionis", "Pi4 Ori", 72.8017, 5.6050, 3.69),
+    star("Gamma Piscium", "Gamma Psc", 349.2912, 3.2822, 3.69),
+    star("Electra", "17 Tau", 56.2188, 24.1133, 3.70),
+    star("Xi Herculis", "Xi Her", 269.4412, 29.2478, 3.70),
+    star("Chi Eridani", "Chi Eri", 28.9896, -51.6089, 3.70),
+    star("Alshain", "Beta Aql", 298.8283, 6.4067, 3.71),
+    star("Epsilon Serpentis", "Epsilon Ser", 237.7038, 4.4778, 3.71),
+    star("Alkaphrah", "Chi UMa", 176.5125, 47.7794, 3.71),
+    star("Xi Cygni", "Xi Cyg", 316.2329, 43.9278, 3.72),
+    star("Delta Aurigae", "Delta Aur", 89.8821, 54.2847, 3.72),
+    star("Baten Kaitos", "Zeta Cet", 27.8650, -10.3350, 3.73),
+    star("Ran", "Epsilon Eri", 53.2325, -9.4583, 3.73),
+    star("Gamma Herculis", "Gamma Her", 245.4800, 19.1531, 3.75),
+    star("Gamma Ophiuchi", "Gamma Oph", 266.9733, 2.7072, 3.75),
+    star("Saclateni", "Zeta Aur", 75.6196, 41.0758, 3.75),
+    star("Grumium", "Xi Dra", 268.3821, 56.8725, 3.75),
+    star("Miram", "Eta Per", 42.6742, 55.8956, 3.76),
+    star("Omicron Sagittarii", "Omicron Sgr", 286.1704, -21.7417, 3.76),
+    star("Beta Monocerotis", "Beta Mon", 97.2046, -7.0331, 3.76),
+    star("Beta Volantis", "Beta Vol", 126.4342, -66.1369, 3.77),
+];
+
+/// AlignmentStar struct
+///
+/// Star suggested for the go-to alignment with its position at the session start.
+///
+/// # Attributes
+///
+/// * `star` - Catalog star
+/// * `altitude` - Altitude in degrees
+/// * `azimuth` - Azimuth in degrees, reckoned from north through east
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AlignmentStar {
+    pub star: Star,
+    pub altitude: f64,
+    pub azimuth: f64,
+}
+
+// Smallest azimuth difference in degrees, 0 to 180
+fn azimuth_distance(a: f64, b: f64) -> f64 {
+    let difference = (a - b).rem_euclid(360.0);
+    difference.min(360.0 - difference)
+}
+
+/// Session start for the alignment: the next nautical dusk in UTC Julian Date, when the bright
+/// stars show and before the darkness used for imaging. None when the Sun does not go below -12°.
+pub fn session_start(observer: &Observer, time: &Time, environment: &Environment,
+                     settings: CalculationSettings) -> Option<f64> {
+    Sun::new(observer, time, environment).with_settings(settings).get_sunset_utc(Next, NauticalTwilight).jd()
+}
+
+/// Up to `count` alignment stars at `jd` (UTC) between ALIGNMENT_MIN_ALTITUDE and
+/// ALIGNMENT_MAX_ALTITUDE, spread across azimuths: the brightest star of each of `count` equal
+/// azimuth sectors, the sectors without stars are filled with the brightest stars farthest in
+/// azimuth from the ones already picked. Sorted by azimuth.
+pub fn alignment_stars(observer: &Observer, jd: f64, count: usize) -> Vec<AlignmentStar> {
+    if count == 0 {
+        return Vec::new();
+    }
+    // catalog is sorted brightest first, so is the list of candidates
+    let candidates: Vec<AlignmentStar> = BRIGHT_STARS
+        .iter()
+        .map(|star| {
+            let (altitude, azimuth) = observer.altaz(star.ra, star.dec, jd);
+            AlignmentStar { star: *star, altitude, azimuth }
+        })
+        .filter(|a| a.altitude >= ALIGNMENT_MIN_ALTITUDE && a.altitude <= ALIGNMENT_MAX_ALTITUDE)
+        .collect();
+
+    let sector = 360.0 / count as f64;
+    let mut picked: Vec<AlignmentStar> = Vec::new();
+    for i in 0..count {
+        let brightest = candidates
+            .iter()
+            .find(|a| ((a.azimuth / sector).floor() as usize).min(count - 1) == i);
+        if let Some(brightest) = brightest {
+            picked.push(*brightest);
+        }
+    }
+
+    while picked.len() < count.min(candidates.len()) {
+        let farthest = candidates
+            .iter()
+            .filter(|a| !picked.iter().any(|p| p.star.name == a.star.name))
+            .map(|a| {
+                let distance = picked
+                    .iter()
+                    .map(|p| azimuth_distance(a.azimuth, p.azimuth))
+                    .fold(180.0, f64::min);
+                (a, distance)
+            })
+            // the first (brightest) of the farthest ones
+            .fold(None, |best: Option<(&AlignmentStar, f64)>, (a, distance)| match best {
+                Some((_, best_distance)) if best_distance >= distance => best,
+                _ => Some((a, distance)),
+            });
+        match farthest {
+            Some((a, _)) => picked.push(*a),
+            None => break,
+        }
+    }
+
+    picked.sort_by(|a, b| a.azimuth.total_cmp(&b.azimuth));
+    picked
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bright_star_catalog() {
+        assert!(BRIGHT_STARS.len() >= 300);
+        for (i, star) in BRIGHT_STARS.iter().enumerate() {
+            assert!((0.0..360.0).contains(&star.ra), "{}", star.name);
+            assert!((-90.0..=90.0).contains(&star.dec), "{}", star.name);
+            assert!(!BRIGHT_STARS[i + 1..].iter().any(|s| s.name == star.name), "{}", star.name);
+        }
+        assert!(BRIGHT_STARS.windows(2).all(|w| w[0].magnitude <= w[1].magnitude));
+    }
+
+    #[test]
+    fn test_alignment_stars() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let environment = Environment::default();
+        let time = Time::new(2024, 8, 15, 12, 0, 0);
+        // nautical dusk around 18:50 local time in August
+        let jd = session_start(&observer, &time, &environment, CalculationSettings::default()).unwrap();
+        let dusk = Time::from_jd(jd - 3.0 / 24.0);
+        assert_eq!((dusk.day, dusk.hour), (15, 18));
+        let stars = alignment_stars(&observer, jd, ALIGNMENT_STARS);
+        assert_eq!(stars.len(), ALIGNMENT_STARS);
+        assert!(stars.windows(2).all(|w| w[0].azimuth <= w[1].azimuth));
+        for star in &stars {
+            assert!(star.altitude >= ALIGNMENT_MIN_ALTITUDE && star.altitude <= ALIGNMENT_MAX_ALTITUDE);
+        }
+        // spread around the sky, no two stars bunched together
+        for (i, a) in stars.iter().enumerate() {
+            for b in &stars[i + 1..] {
+                assert!(azimuth_distance(a.azimuth, b.azimuth) > 10.0, "{} {}", a.star.name, b.star.name);
+            }
+        }
+        assert!(alignment_stars(&observer, jd, 0).is_empty());
+    }
+}
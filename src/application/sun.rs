@@ -21,35 +21,147 @@
 // IN THE SOFTWARE.
 
 // TODO remove before release
-// TODO Create tests
 #![allow(dead_code, unused_variables)]
 
 use crate::application::{
+    calculation::CalculationSettings,
     environment::Environment,
+    ephemeris_cache::{cached_sun_grid, Grid},
+    formatting::{format_event, FormatTime},
     observer::Observer,
-    time::Time,
-    transformations::equatorial_to_altaz,
+    preferences::Preferences,
+    rise_set_solver::{find_crossing, NoCrossing},
+    time::{jd_tt, LocalTime, Time},
+    transformations::{equatorial_to_altaz_jd, hour_angle_jd},
 };
 use crate::utils::utils::{
     cosd,
-    cross_horizon,
     sind,
-    two_point_interpolation
 };
 use libm::atan2;
 use std::cmp::PartialEq;
+use std::rc::Rc;
 use std::f64::consts::PI;
 //https://en.wikipedia.org/wiki/Sunrise_equation#Complete_calculation_on_Earth
 //https://astrogreg.com/
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SunRS {
     NeverRise,
     NeverSet,
+    AlwaysUp,
+    AlwaysDown,
 }
 
-pub fn sun_position_from_jd(jd: f64) -> (f64, f64) {
-    let n = jd - 2_451_545.0;
+/// SunEvent enum
+///
+/// Result of a rise, set or twilight search. Events that do not happen within the search window
+/// tell why instead of collapsing to a sentinel time.
+///
+/// # Variants
+///
+/// * `At` - Julian Date (UTC) of the event, or its `LocalTime` for the `*_local` searches
+/// * `NeverRises` - No rising crossing was found although the body crosses the horizon
+/// * `NeverSets` - No setting crossing was found although the body crosses the horizon
+/// * `AlwaysUp` - The body stays above the horizon during the whole search window
+/// * `AlwaysDown` - The body stays below the horizon during the whole search window
+/// * `MoonUp` - Darkness only, the Sun gets low enough but the Moon is above the horizon meanwhile
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SunEvent<T = f64> {
+    At(T),
+    NeverRises,
+    NeverSets,
+    AlwaysUp,
+    AlwaysDown,
+    MoonUp,
+}
+
+impl SunEvent {
+    /// Julian Date of the event, None when the event does not happen
+    pub fn jd(&self) -> Option<f64> {
+        match self {
+            SunEvent::At(jd) => Some(*jd),
+            _ => None,
+        }
+    }
+
+    /// Same event in the local time of a UTC offset in hours
+    pub fn local(&self, offset: f64) -> SunEvent<LocalTime> {
+        self.map(|jd| LocalTime::new(jd, offset))
+    }
+}
+
+impl<T: Copy> SunEvent<T> {
+    /// Time of the event, None when the event does not happen
+    pub fn time(&self) -> Option<T> {
+        match self {
+            SunEvent::At(time) => Some(*time),
+            _ => None,
+        }
+    }
+
+    /// Same event with its time converted, events that do not happen are kept as they are
+    pub fn map<U>(&self, f: impl Fn(T) -> U) -> SunEvent<U> {
+        match *self {
+            SunEvent::At(time) => SunEvent::At(f(time)),
+            SunEvent::NeverRises => SunEvent::NeverRises,
+            SunEvent::NeverSets => SunEvent::NeverSets,
+            SunEvent::AlwaysUp => SunEvent::AlwaysUp,
+            SunEvent::AlwaysDown => SunEvent::AlwaysDown,
+            SunEvent::MoonUp => SunEvent::MoonUp,
+        }
+    }
+}
+
+/// Transit struct
+///
+/// Upper transit of the Sun or the Moon: time when it crosses the meridian, and its geometric
+/// altitude then, the highest of the day.
+///
+/// # Attributes
+///
+/// * `time` - Julian Date (UTC) of the transit, or its `LocalTime` for the `*_local` versions
+/// * `altitude` - Altitude at the transit in degrees, without refraction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transit<T = f64> {
+    pub time: T,
+    pub altitude: f64,
+}
+
+impl Transit {
+    /// Same transit in the local time of a UTC offset in hours
+    pub fn local(&self, offset: f64) -> Transit<LocalTime> {
+        Transit { time: LocalTime::new(self.time, offset), altitude: self.altitude }
+    }
+}
+
+impl SunRS {
+    // Reason from the rise/set solver, other_direction is NeverRise or NeverSet
+    pub(crate) fn from_no_crossing(reason: NoCrossing, other_direction: SunRS) -> SunRS {
+        match reason {
+            NoCrossing::AlwaysAbove => SunRS::AlwaysUp,
+            NoCrossing::AlwaysBelow => SunRS::AlwaysDown,
+            NoCrossing::OtherDirection => other_direction,
+        }
+    }
+}
+
+impl From<Result<f64, SunRS>> for SunEvent {
+    fn from(result: Result<f64, SunRS>) -> SunEvent {
+        match result {
+            Ok(jd) => SunEvent::At(jd),
+            Err(SunRS::NeverRise) => SunEvent::NeverRises,
+            Err(SunRS::NeverSet) => SunEvent::NeverSets,
+            Err(SunRS::AlwaysUp) => SunEvent::AlwaysUp,
+            Err(SunRS::AlwaysDown) => SunEvent::AlwaysDown,
+        }
+    }
+}
+
+/// Ecliptic longitude of the Sun in degrees for a JD (UTC), same low precision theory as
+/// sun_position_from_jd
+pub fn sun_ecliptic_longitude_from_jd(jd: f64) -> f64 {
+    let n = jd_tt(jd) - 2_451_545.0;
     let mut l = (280.460 + 0.985_647_4 * n) % 360.0;
     let mut g = ((357.528 + 0.985_600_3 * n) % 360.0).to_radians();
     if l < 0.0 {
@@ -58,8 +170,12 @@ pub fn sun_position_from_jd(jd: f64) -> (f64, f64) {
     if g < 0.0 {
         g += 2. * PI
     };
-    let lambda = (l + 1.915 * g.sin() + 0.020 * (2. * g).sin()).to_radians();
-    let bet = 0.0;
+    l + 1.915 * g.sin() + 0.020 * (2. * g).sin()
+}
+
+pub fn sun_position_from_jd(jd: f64) -> (f64, f64) {
+    let n = jd_tt(jd) - 2_451_545.0;
+    let lambda = sun_ecliptic_longitude_from_jd(jd).to_radians();
     let eps = (23.439 - 0.000_000_4 * n).to_radians();
     let mut ra = atan2(eps.cos() * lambda.sin(), lambda.cos());
     let dec = (eps.sin() * lambda.sin()).asin();
@@ -69,6 +185,33 @@ pub fn sun_position_from_jd(jd: f64) -> (f64, f64) {
     (ra.to_degrees(), dec.to_degrees())
 }
 
+/// Equation of time in minutes at a JD (UTC), apparent minus mean solar time
+///
+/// Positive when a sundial is ahead of mean solar time, from about -14 min in mid February to
+/// +16 min in early November. Same low precision theory as sun_position_from_jd, within a few
+/// seconds.
+pub fn equation_of_time(jd: f64) -> f64 {
+    const MINUTES_PER_DEGREE: f64 = 4.0;
+    let n = jd_tt(jd) - 2_451_545.0;
+    let mean_longitude = 280.460 + 0.985_647_4 * n;
+    let (ra, _) = sun_position_from_jd(jd);
+    let difference = (mean_longitude - ra + 180.0).rem_euclid(360.0) - 180.0; // -180..180
+    difference * MINUTES_PER_DEGREE
+}
+
+/// Local apparent solar time (sundial time) in hours, 0 - 24, at a JD (UTC) and longitude
+pub fn apparent_solar_time(lon: f64, jd: f64) -> f64 {
+    let utc_hours = ((jd + 0.5).fract() + 1.0).fract() * 24.0;
+    (utc_hours + lon / 15.0 + equation_of_time(jd) / 60.0).rem_euclid(24.0)
+}
+
+// Sun - Earth distance in astronomical units, same low precision theory as sun_position_from_jd
+pub fn sun_distance_from_jd(jd: f64) -> f64 {
+    let n = jd_tt(jd) - 2_451_545.0;
+    let g = ((357.528 + 0.985_600_3 * n) % 360.0).to_radians();
+    1.000_14 - 0.016_71 * g.cos() - 0.000_14 * (2. * g).cos()
+}
+
 pub fn sun_position_from_ymd(y: i64, m: u64, d: u64, h: u64, min: u64, s: u64) -> (f64, f64) {
     let date = Time::new(y, m, d, h, min, s);
     sun_position_from_jd(date.to_jd())
@@ -93,19 +236,7 @@ pub fn sun_hour_angle(lat: f64, dec: f64) -> f64 {
 }
 
 pub fn sun_alt_az_from_jd(lat: f64, lon: f64, ra: f64, dec: f64, jd: f64) -> (f64, f64) {
-    let date = Time::from_jd(jd);
-    equatorial_to_altaz(
-        lat,
-        lon,
-        ra,
-        dec,
-        date.year,
-        date.month,
-        date.day,
-        date.hour,
-        date.minute,
-        date.second,
-    )
+    equatorial_to_altaz_jd(lat, lon, ra, dec, jd)
 }
 
 pub fn sun_alt_az_grid_utc(
@@ -115,43 +246,58 @@ pub fn sun_alt_az_grid_utc(
     jd_end: f64,
     num_points: usize,
 ) -> Vec<(f64, f64, f64)> {
-    // create a null grid vector with 3 columns and num_points+1 rows
-    let mut grid: Vec<(f64, f64, f64)> = Vec::new();
+    // num_points+1 rows computed serially into one allocation, the nights of the multi-night
+    // reports are the ones spread over the processor cores
+    let mut grid: Vec<(f64, f64, f64)> = Vec::with_capacity(num_points + 1);
     let inc = (jd_end - jd_start) / num_points as f64;
     for i in 0..=num_points {
         let jd = jd_start + inc * i as f64;
         let (ra, dec) = sun_position_from_jd(jd);
-        let mut date = Time::from_jd(jd);
-        let (alt, az) = equatorial_to_altaz(
-            lat,
-            lon,
-            ra,
-            dec,
-            date.year,
-            date.month,
-            date.day,
-            date.hour,
-            date.minute,
-            date.second,
-        );
+        let (alt, az) = equatorial_to_altaz_jd(lat, lon, ra, dec, jd);
         grid.push((jd, alt, az));
     }
     grid
 }
 
+/// Solar midnight (lower transit of the Sun, hour angle 180°) in UTC nearest to jd
+///
+/// Differs from clock midnight by the longitude offset within the time zone, daylight saving
+/// time and the equation of time, by up to a few hours.
+pub fn solar_midnight_utc(lon: f64, jd: f64) -> f64 {
+    sun_hour_angle_time(lon, jd, 180.0)
+}
+
+/// Solar noon (upper transit of the Sun, hour angle 0°) in UTC nearest to jd
+pub fn solar_noon_utc(lon: f64, jd: f64) -> f64 {
+    sun_hour_angle_time(lon, jd, 0.0)
+}
+
+// Time in UTC nearest to jd when the hour angle of the Sun is hour_angle degrees
+fn sun_hour_angle_time(lon: f64, jd: f64, hour_angle: f64) -> f64 {
+    const SOLAR_DAY: f64 = 1.0; // the hour angle of the Sun grows 360° per day
+    let mut time = jd;
+    for _ in 0..3 {
+        let (ra, _) = sun_position_from_jd(time);
+        let ha = hour_angle_jd(lon, ra, time);
+        let difference = (hour_angle - ha + 540.0) % 360.0 - 180.0; // -180..180
+        time += difference / 360.0 * SOLAR_DAY;
+    }
+    time
+}
+
+// Altitude of the Sun as a function of JD, for the rise/set solver
+pub fn sun_altitude(lat: f64, lon: f64) -> impl Fn(f64) -> f64 {
+    move |jd| {
+        let (ra, dec) = sun_position_from_jd(jd);
+        sun_alt_az_from_jd(lat, lon, ra, dec, jd).0
+    }
+}
+
 pub fn sunrise_utc_grid(lat: f64, lon: f64, jd: f64, horizon: f64, tz: f64) -> Result<f64, SunRS> {
-    let num_points = 288;
-    let target_night_start = (jd + 0.5).floor() + tz / 24.0;
+    let target_night_start = (jd + 0.5).floor() - tz / 24.0; // Noon @ local time
     let target_night_end = target_night_start + 1.0;
-    let sun = sun_alt_az_grid_utc(lat, lon, target_night_start, target_night_end, num_points);
-    let v = cross_horizon(sun, horizon, true);
-    if v.is_empty() {
-        Err(SunRS::NeverRise)
-    } else {
-        Ok(two_point_interpolation(
-            v[0].0, v[0].2, v[0].1, v[0].3, horizon,
-        ))
-    }
+    find_crossing(sun_altitude(lat, lon), target_night_start, target_night_end, horizon, true)
+        .map_err(|reason| SunRS::from_no_crossing(reason, SunRS::NeverRise))
 }
 
 pub fn next_sunrise_utc(
@@ -163,15 +309,16 @@ pub fn next_sunrise_utc(
     max_days: u32,
 ) -> Result<f64, SunRS> {
     let mut current_jd = jd;
+    let mut result = Err(SunRS::NeverRise);
     for _ in 0..max_days {
         // Limit to 2 days of iterations
-        match sunrise_utc_grid(lat, lon, current_jd, horizon, tz) {
-            Ok(sunrise) => return Ok(sunrise),
-            Err(SunRS::NeverRise) => current_jd += 1.0, // Skip to the next day
-            Err(e) => return Err(e),
+        result = sunrise_utc_grid(lat, lon, current_jd, horizon, tz);
+        if result.is_ok() {
+            break;
         }
+        current_jd += 1.0; // Skip to the next day
     }
-    Err(SunRS::NeverRise) // Return error if no sunrise is found within the range
+    result // Keeps the reason if no sunrise is found within the range
 }
 
 pub fn previous_sunrise_utc(
@@ -183,15 +330,16 @@ pub fn previous_sunrise_utc(
     max_days: u32,
 ) -> Result<f64, SunRS> {
     let mut current_jd = jd - 1.0;
+    let mut result = Err(SunRS::NeverRise);
     for _ in 0..max_days {
         // Limit to 2 days of iterations
-        match sunrise_utc_grid(lat, lon, current_jd, horizon, tz) {
-            Ok(sunrise) => return Ok(sunrise),
-            Err(SunRS::NeverRise) => current_jd -= 1.0, // Skip to the next day
-            Err(e) => return Err(e),
+        result = sunrise_utc_grid(lat, lon, current_jd, horizon, tz);
+        if result.is_ok() {
+            break;
         }
+        current_jd -= 1.0; // Skip to the previous day
     }
-    Err(SunRS::NeverRise) // Return error if no sunrise is found within the range
+    result // Keeps the reason if no sunrise is found within the range
 }
 
 pub fn nearest_sunrise_utc(
@@ -221,18 +369,10 @@ pub fn nearest_sunrise_utc(
 }
 
 pub fn sunset_utc_grid(lat: f64, lon: f64, jd: f64, horizon: f64, tz: f64) -> Result<f64, SunRS> {
-    let num_points = 288;
-    let target_night_start = (jd + 0.5).floor() + tz / 24.0;
+    let target_night_start = (jd + 0.5).floor() - tz / 24.0; // Noon @ local time
     let target_night_end = target_night_start + 1.0;
-    let sun = sun_alt_az_grid_utc(lat, lon, target_night_start, target_night_end, num_points);
-    let v = cross_horizon(sun, horizon, false);
-    if v.is_empty() {
-        Err(SunRS::NeverSet)
-    } else {
-        Ok(two_point_interpolation(
-            v[0].0, v[0].2, v[0].1, v[0].3, horizon,
-        ))
-    }
+    find_crossing(sun_altitude(lat, lon), target_night_start, target_night_end, horizon, false)
+        .map_err(|reason| SunRS::from_no_crossing(reason, SunRS::NeverSet))
 }
 
 pub fn next_sunset_utc(
@@ -244,15 +384,16 @@ pub fn next_sunset_utc(
     max_days: u32,
 ) -> Result<f64, SunRS> {
     let mut current_jd = jd;
+    let mut result = Err(SunRS::NeverSet);
     for _ in 0..max_days {
         // Limit to 2 days of iterations
-        match sunset_utc_grid(lat, lon, current_jd, horizon, tz) {
-            Ok(sunset) => return Ok(sunset),
-            Err(SunRS::NeverSet) => current_jd += 1.0, // Skip to the next day
-            Err(e) => return Err(e),
+        result = sunset_utc_grid(lat, lon, current_jd, horizon, tz);
+        if result.is_ok() {
+            break;
         }
+        current_jd += 1.0; // Skip to the next day
     }
-    Err(SunRS::NeverSet) // Return error if no sunset is found within the range
+    result // Keeps the reason if no sunset is found within the range
 }
 
 pub fn previous_sunset_utc(
@@ -264,15 +405,16 @@ pub fn previous_sunset_utc(
     max_days: u32,
 ) -> Result<f64, SunRS> {
     let mut current_jd = jd - 1.0;
+    let mut result = Err(SunRS::NeverSet);
     for _ in 0..max_days {
         // Limit to 2 days of iterations
-        match sunset_utc_grid(lat, lon, current_jd, horizon, tz) {
-            Ok(sunset) => return Ok(sunset),
-            Err(SunRS::NeverSet) => current_jd -= 1.0, // Skip to the next day
-            Err(e) => return Err(e),
+        result = sunset_utc_grid(lat, lon, current_jd, horizon, tz);
+        if result.is_ok() {
+            break;
         }
+        current_jd -= 1.0; // Skip to the previous day
     }
-    Err(SunRS::NeverSet) // Return error if no sunset is found within the range
+    result // Keeps the reason if no sunset is found within the range
 }
 
 pub fn nearest_sunset_utc(
@@ -300,14 +442,38 @@ pub fn nearest_sunset_utc(
     }
 }
 
+// First rise or set of the Sun after jd, searched one day at a time. Used to find the end of a
+// polar day or a polar night.
+pub fn circumpolar_end_utc(
+    lat: f64,
+    lon: f64,
+    jd: f64,
+    horizon: f64,
+    tz: f64,
+    max_days: u32,
+) -> Option<f64> {
+    let mut current_jd = jd;
+    for _ in 0..max_days {
+        let sunrise = sunrise_utc_grid(lat, lon, current_jd, horizon, tz).ok();
+        let sunset = sunset_utc_grid(lat, lon, current_jd, horizon, tz).ok();
+        match (sunrise, sunset) {
+            (Some(sunrise), Some(sunset)) => return Some(sunrise.min(sunset)),
+            (Some(event), None) | (None, Some(event)) => return Some(event),
+            (None, None) => current_jd += 1.0, // Skip to the next day
+        }
+    }
+    None
+}
+
 #[derive(Debug, Clone)]
 pub struct Sun<'a> {
     pub observer: &'a Observer,
     pub time: &'a Time,
     pub environment: &'a Environment,
+    pub settings: CalculationSettings,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum TwilightType {
     RiseSet,
     CivilTwilight,
@@ -335,6 +501,118 @@ impl TwilightType {
     }
 }
 
+/// LightBand enum
+///
+/// Photographic light of the twilight, while the Sun is between two altitudes: the golden hour
+/// from 6° down to -4° and the blue hour from -4° down to -6°, where civil twilight ends.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightBand {
+    GoldenHour,
+    BlueHour,
+}
+
+impl LightBand {
+    /// Upper and lower altitude of the Sun during the band, in degrees
+    pub fn altitudes(&self) -> (f64, f64) {
+        match self {
+            LightBand::GoldenHour => (6.0, -4.0),
+            LightBand::BlueHour => (-4.0, TwilightType::CivilTwilight.angle()),
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        match self {
+            LightBand::GoldenHour => "Golden hour",
+            LightBand::BlueHour => "Blue hour",
+        }
+    }
+}
+
+/// SeasonEvent enum
+///
+/// Equinoxes and solstices named by their month. The season each one starts depends on the
+/// hemisphere: the June solstice starts the summer in the north and the winter in the south.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SeasonEvent {
+    MarchEquinox,
+    JuneSolstice,
+    SeptemberEquinox,
+    DecemberSolstice,
+}
+
+/// Equinoxes and solstices in the order of the year
+pub const SEASON_EVENTS: [SeasonEvent; 4] = [
+    SeasonEvent::MarchEquinox, SeasonEvent::JuneSolstice, SeasonEvent::SeptemberEquinox,
+    SeasonEvent::DecemberSolstice,
+];
+
+impl SeasonEvent {
+    /// Ecliptic longitude of the Sun at the event in degrees
+    pub fn longitude(&self) -> f64 {
+        match self {
+            SeasonEvent::MarchEquinox => 0.0,
+            SeasonEvent::JuneSolstice => 90.0,
+            SeasonEvent::SeptemberEquinox => 180.0,
+            SeasonEvent::DecemberSolstice => 270.0,
+        }
+    }
+
+    /// Name by the month, the same in both hemispheres
+    pub fn name(&self) -> &'static str {
+        match self {
+            SeasonEvent::MarchEquinox => "March equinox",
+            SeasonEvent::JuneSolstice => "June solstice",
+            SeasonEvent::SeptemberEquinox => "September equinox",
+            SeasonEvent::DecemberSolstice => "December solstice",
+        }
+    }
+
+    /// Name by the season it starts at a latitude, the equator counted with the north
+    pub fn local_name(&self, latitude: f64) -> &'static str {
+        let north = latitude >= 0.0;
+        match (self, north) {
+            (SeasonEvent::MarchEquinox, true) | (SeasonEvent::SeptemberEquinox, false) => "Spring equinox",
+            (SeasonEvent::MarchEquinox, false) | (SeasonEvent::SeptemberEquinox, true) => "Autumn equinox",
+            (SeasonEvent::JuneSolstice, true) | (SeasonEvent::DecemberSolstice, false) => "Summer solstice",
+            (SeasonEvent::JuneSolstice, false) | (SeasonEvent::DecemberSolstice, true) => "Winter solstice",
+        }
+    }
+}
+
+/// Equinoxes and solstices between two JDs (UTC) in time order, same low precision theory as
+/// sun_position_from_jd, within about 15 minutes
+pub fn season_events(jd_start: f64, jd_end: f64) -> Vec<(SeasonEvent, f64)> {
+    let mut events = Vec::new();
+    // at most one event of each kind in a month
+    let mut start = jd_start;
+    while start < jd_end {
+        let end = (start + 30.0).min(jd_end);
+        for event in SEASON_EVENTS {
+            // angle from the Sun to the event longitude, rising through 0 at the event
+            let angle = |jd: f64| (sun_ecliptic_longitude_from_jd(jd) - event.longitude() + 180.0).rem_euclid(360.0) - 180.0;
+            if let Ok(jd) = find_crossing(angle, start, end, 0.0, true) {
+                events.push((event, jd));
+            }
+        }
+        start = end;
+    }
+    events.sort_by(|a, b| a.1.total_cmp(&b.1));
+    events
+}
+
+/// CrossingDirection enum
+///
+/// Direction in which the Sun crosses an altitude: `Rising` in the morning, `Setting` in the
+/// evening.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CrossingDirection {
+    Rising,
+    Setting,
+}
+
+// Signature shared by the nearest/next/previous sunrise and sunset searches
+type CrossingFn = fn(f64, f64, f64, f64, f64, u32) -> Result<f64, SunRS>;
+
 #[derive(Debug, PartialEq)]
 pub enum RiseSetType {
     Nearest,
@@ -358,154 +636,512 @@ impl<'a> Sun<'a> {
             observer,
             time,
             environment,
+            settings: CalculationSettings::default(),
         }
     }
 
-    fn get_sun_event_utc<F>(
-        &self,
-        rise_set_type: RiseSetType,
-        twilight: TwilightType,
-        nearest_fn: F,
-        next_fn: F,
-        previous_fn: F,
-    ) -> f64
-    where
-        F: Fn(f64, f64, f64, f64, f64, u32) -> Result<f64, SunRS>,
-    {
+    /// Sun with the accuracy settings of the preferences instead of the defaults
+    pub fn with_settings(mut self, settings: CalculationSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Sun (jd, alt, az) from local noon to the next local noon in `num_points` steps, from the
+    /// shared ephemeris cache
+    pub fn night_alt_az_grid(&self, num_points: usize) -> Rc<Grid> {
+        let night_start = (self.time.to_jd() + 0.5).floor() - self.observer.timezone / 24.0;
+        cached_sun_grid(self.observer.latitude, self.observer.longitude, night_start, night_start + 1.0,
+                        num_points)
+    }
+
+    /// Time in UTC when the Sun center crosses an arbitrary altitude near the selected time
+    ///
+    /// The search uses the same one day windows (local noon to local noon) as sunrise and sunset,
+    /// up to 2 days forward and/or backward depending on `search`. Refraction is not applied, so
+    /// `angle` is the geometric altitude in degrees (e.g. -0.8333 for sunrise/sunset, -18.0 for
+    /// astronomical twilight, 6.0 for the end of the golden hour).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skycalc::application::environment::Environment;
+    /// use skycalc::application::observer::Observer;
+    /// use skycalc::application::sun::{CrossingDirection, RiseSetType, Sun};
+    /// use skycalc::application::time::Time;
+    ///
+    /// let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+    /// let time = Time::new(2024, 11, 14, 12, 0, 0);
+    /// let environment = Environment::default();
+    /// let sun = Sun::new(&observer, &time, &environment);
+    /// // start of the evening blue hour
+    /// let blue_hour = sun.crossing_time(-4.0, CrossingDirection::Setting, RiseSetType::Next);
+    /// assert!(blue_hour.jd().is_some());
+    /// ```
+    pub fn crossing_time(&self, angle: f64, direction: CrossingDirection,
+                         search: RiseSetType) -> SunEvent {
         const MAX_DAYS: u32 = 2; // number of days to look forward or backward
         let latitude = self.observer.latitude;
         let longitude = self.observer.longitude;
         let jd = self.time.to_jd();
-        let angle = twilight.angle();
         let timezone = self.observer.timezone;
 
-        match rise_set_type {
-            RiseSetType::Nearest => {
-                nearest_fn(latitude, longitude, jd, angle, timezone, MAX_DAYS).unwrap_or(0.0)
-            }
-            RiseSetType::Next => {
-                next_fn(latitude, longitude, jd, angle, timezone, MAX_DAYS).unwrap_or(0.0)
+        let (nearest_fn, next_fn, previous_fn): (CrossingFn, CrossingFn, CrossingFn) = match direction {
+            CrossingDirection::Rising => (nearest_sunrise_utc, next_sunrise_utc, previous_sunrise_utc),
+            CrossingDirection::Setting => (nearest_sunset_utc, next_sunset_utc, previous_sunset_utc),
+        };
+        let result = match search {
+            RiseSetType::Nearest => nearest_fn(latitude, longitude, jd, angle, timezone, MAX_DAYS),
+            RiseSetType::Next => next_fn(latitude, longitude, jd, angle, timezone, MAX_DAYS),
+            RiseSetType::Previous => previous_fn(latitude, longitude, jd, angle, timezone, MAX_DAYS),
+        };
+        SunEvent::from(result)
+    }
+
+    pub fn get_sunrise_utc(&self, rise_set_type: RiseSetType, twilight: TwilightType) -> SunEvent {
+        self.crossing_time(self.settings.twilight_angle(twilight), CrossingDirection::Rising, rise_set_type)
+    }
+
+    pub fn get_sunset_utc(&self, rise_set_type: RiseSetType, twilight: TwilightType) -> SunEvent {
+        self.crossing_time(self.settings.twilight_angle(twilight), CrossingDirection::Setting, rise_set_type)
+    }
+
+    /// Azimuth of the Sun in degrees from north through east at a JD (UTC)
+    pub fn azimuth(&self, jd: f64) -> f64 {
+        let (ra, dec) = sun_position_from_jd(jd);
+        sun_alt_az_from_jd(self.observer.latitude, self.observer.longitude, ra, dec, jd).1
+    }
+
+    /// Azimuth where the Sun crosses the twilight angle rising, None when it does not rise
+    pub fn get_sunrise_azimuth(&self, rise_set_type: RiseSetType, twilight: TwilightType) -> Option<f64> {
+        self.get_sunrise_utc(rise_set_type, twilight).jd().map(|jd| self.azimuth(jd))
+    }
+
+    /// Azimuth where the Sun crosses the twilight angle setting, None when it does not set
+    pub fn get_sunset_azimuth(&self, rise_set_type: RiseSetType, twilight: TwilightType) -> Option<f64> {
+        self.get_sunset_utc(rise_set_type, twilight).jd().map(|jd| self.azimuth(jd))
+    }
+
+    /// Hours between the Sun setting below and rising above the twilight angle during the night
+    ///
+    /// `RiseSet` gives the night length and `AstronomicalTwilight` the astronomical darkness. A
+    /// polar night gives 24 hours and a polar day 0, None when only one of the crossings happens.
+    pub fn night_duration(&self, twilight: TwilightType) -> Option<f64> {
+        let sunset = self.get_sunset_utc(RiseSetType::Next, twilight);
+        let sunrise = self.get_sunrise_utc(RiseSetType::Next, twilight);
+        match (sunset, sunrise) {
+            (SunEvent::At(set), SunEvent::At(rise)) if rise > set => Some((rise - set) * 24.0),
+            (SunEvent::AlwaysDown, _) => Some(24.0),
+            (SunEvent::AlwaysUp, _) => Some(0.0),
+            _ => None,
+        }
+    }
+
+    /// Hours of twilight, evening and morning together, from the previous twilight angle down to
+    /// this one (e.g. `NauticalTwilight` goes from -6° to -12°), 0 for `RiseSet`
+    pub fn twilight_duration(&self, twilight: TwilightType) -> Option<f64> {
+        let upper = match twilight {
+            TwilightType::RiseSet => return Some(0.0),
+            TwilightType::CivilTwilight => TwilightType::RiseSet,
+            TwilightType::NauticalTwilight => TwilightType::CivilTwilight,
+            TwilightType::AstronomicalTwilight => TwilightType::NauticalTwilight,
+        };
+        Some(self.night_duration(upper)? - self.night_duration(twilight)?)
+    }
+
+    /// Start and end in UTC of the next evening (`Setting`) or morning (`Rising`) light band
+    ///
+    /// In the evening the Sun crosses the upper altitude first and in the morning the lower one.
+    pub fn get_band_utc(&self, band: LightBand, direction: CrossingDirection) -> (SunEvent, SunEvent) {
+        let (upper, lower) = band.altitudes();
+        let (first, last) = match direction {
+            CrossingDirection::Setting => (upper, lower),
+            CrossingDirection::Rising => (lower, upper),
+        };
+        (self.crossing_time(first, direction, RiseSetType::Next),
+         self.crossing_time(last, direction, RiseSetType::Next))
+    }
+
+    pub fn get_band_local_str(&self, band: LightBand, direction: CrossingDirection,
+                              preferences: &Preferences) -> (String, String) {
+        let (start, end) = self.get_band_utc(band, direction);
+        (format_event(start.local(self.observer.timezone), preferences),
+         format_event(end.local(self.observer.timezone), preferences))
+    }
+
+    /// Zodiacal light window of the selected night in UTC, while the Sun is between 18° and 25°
+    /// below the horizon in the evening (`Setting`) or the morning (`Rising`)
+    ///
+    /// When the Sun does not reach -25° the window runs up to or from solar midnight, None when
+    /// the night has no astronomical darkness.
+    pub fn zodiacal_light_window_utc(&self, direction: CrossingDirection) -> Option<(f64, f64)> {
+        const LOWER: f64 = -25.0;
+        let (latitude, longitude) = (self.observer.latitude, self.observer.longitude);
+        let (jd, timezone) = (self.time.to_jd(), self.observer.timezone);
+        let dark = TwilightType::AstronomicalTwilight.angle();
+        match direction {
+            CrossingDirection::Setting => {
+                let start = sunset_utc_grid(latitude, longitude, jd, dark, timezone).ok()?;
+                let end = sunset_utc_grid(latitude, longitude, jd, LOWER, timezone)
+                    .unwrap_or_else(|_| solar_midnight_utc(longitude, start));
+                Some((start, end))
             }
-            RiseSetType::Previous => {
-                previous_fn(latitude, longitude, jd, angle, timezone, MAX_DAYS).unwrap_or(0.0)
+            CrossingDirection::Rising => {
+                let end = sunrise_utc_grid(latitude, longitude, jd, dark, timezone).ok()?;
+                let start = sunrise_utc_grid(latitude, longitude, jd, LOWER, timezone)
+                    .unwrap_or_else(|_| solar_midnight_utc(longitude, end));
+                Some((start, end))
             }
         }
     }
 
-    pub fn get_sunrise_utc(&self, rise_set_type: RiseSetType, twilight: TwilightType) -> f64 {
-        self.get_sun_event_utc(
-            rise_set_type,
-            twilight,
-            nearest_sunrise_utc as fn(f64, f64, f64, f64, f64, u32) -> Result<f64, SunRS>,
-            next_sunrise_utc as fn(f64, f64, f64, f64, f64, u32) -> Result<f64, SunRS>,
-            previous_sunrise_utc as fn(f64, f64, f64, f64, f64, u32) -> Result<f64, SunRS>,
-        )
+    /// Solar noon of the selected date in UTC, with the highest altitude of the Sun that day
+    pub fn get_transit_utc(&self) -> Transit {
+        let local_noon = (self.time.to_jd() + 0.5).floor() - self.observer.timezone / 24.0;
+        let noon = solar_noon_utc(self.observer.longitude, local_noon);
+        Transit { time: noon, altitude: sun_altitude(self.observer.latitude, self.observer.longitude)(noon) }
+    }
+
+    pub fn get_transit_local(&self) -> Transit<LocalTime> {
+        self.get_transit_utc().local(self.observer.timezone)
     }
 
-    pub fn get_sunset_utc(&self, rise_set_type: RiseSetType, twilight: TwilightType) -> f64 {
-        self.get_sun_event_utc(
-            rise_set_type,
-            twilight,
-            nearest_sunset_utc as fn(f64, f64, f64, f64, f64, u32) -> Result<f64, SunRS>,
-            next_sunset_utc as fn(f64, f64, f64, f64, f64, u32) -> Result<f64, SunRS>,
-            previous_sunset_utc as fn(f64, f64, f64, f64, f64, u32) -> Result<f64, SunRS>,
+    /// End of a polar day or polar night (first rise or set after the selected time) in UTC
+    pub fn get_circumpolar_end_utc(&self, twilight: TwilightType) -> Option<f64> {
+        const MAX_DAYS: u32 = 190; // longest polar night, at the poles
+        circumpolar_end_utc(
+            self.observer.latitude,
+            self.observer.longitude,
+            self.time.to_jd(),
+            self.settings.twilight_angle(twilight),
+            self.observer.timezone,
+            MAX_DAYS,
         )
     }
 
-    pub fn get_sunrise_local(&self, rise_set_type: RiseSetType, twilight: TwilightType) -> f64 {
-        let utc = self.get_sunrise_utc(rise_set_type, twilight);
-        if utc == 0.0 {
-            0.0
-        } else {
-            utc + self.observer.timezone / 24.0
-        }
+    pub fn get_sunrise_local(&self, rise_set_type: RiseSetType,
+                             twilight: TwilightType) -> SunEvent<LocalTime> {
+        self.get_sunrise_utc(rise_set_type, twilight).local(self.observer.timezone)
     }
 
-    pub fn get_sunset_local(&self, rise_set_type: RiseSetType, twilight: TwilightType) -> f64 {
-        let utc = self.get_sunset_utc(rise_set_type, twilight);
-        if utc == 0.0 {
-            0.0
-        } else {
-            utc + self.observer.timezone / 24.0
-        }
+    pub fn get_sunset_local(&self, rise_set_type: RiseSetType,
+                            twilight: TwilightType) -> SunEvent<LocalTime> {
+        self.get_sunset_utc(rise_set_type, twilight).local(self.observer.timezone)
     }
 
-    fn get_sun_event_str<F>(
+    fn get_sun_event_str<T, F>(
         &self,
         rise_set_type: RiseSetType,
         twilight: TwilightType,
-        format: Option<&str>,
+        preferences: &Preferences,
         event_fn: F,
-        never_message: &str,
     ) -> String
     where
-        F: Fn(&Self, RiseSetType, TwilightType) -> f64,
+        T: FormatTime,
+        F: Fn(&Self, RiseSetType, TwilightType) -> SunEvent<T>,
     {
-        let event_time = event_fn(self, rise_set_type, twilight);
-        if event_time == 0.0 {
-            never_message.to_string()
-        } else {
-            Time::from_jd(event_time).to_string(format)
-        }
+        format_event(event_fn(self, rise_set_type, twilight), preferences)
     }
 
     pub fn get_sunrise_utc_str(
         &self,
         rise_set_type: RiseSetType,
         twilight: TwilightType,
-        format: Option<&str>,
+        preferences: &Preferences,
     ) -> String {
-        self.get_sun_event_str(
-            rise_set_type,
-            twilight,
-            format,
-            Sun::get_sunrise_utc,
-            "Never Rises",
-        )
+        self.get_sun_event_str(rise_set_type, twilight, preferences, Sun::get_sunrise_utc)
     }
 
     pub fn get_sunrise_local_str(
         &self,
         rise_set_type: RiseSetType,
         twilight: TwilightType,
-        format: Option<&str>,
+        preferences: &Preferences,
     ) -> String {
-        self.get_sun_event_str(
-            rise_set_type,
-            twilight,
-            format,
-            Sun::get_sunrise_local,
-            "Never Rises",
-        )
+        self.get_sun_event_str(rise_set_type, twilight, preferences, Sun::get_sunrise_local)
     }
 
     pub fn get_sunset_utc_str(
         &self,
         rise_set_type: RiseSetType,
         twilight: TwilightType,
-        format: Option<&str>,
+        preferences: &Preferences,
     ) -> String {
-        self.get_sun_event_str(
-            rise_set_type,
-            twilight,
-            format,
-            Sun::get_sunset_utc,
-            "Never Sets",
-        )
+        self.get_sun_event_str(rise_set_type, twilight, preferences, Sun::get_sunset_utc)
     }
 
     pub fn get_sunset_local_str(
         &self,
         rise_set_type: RiseSetType,
         twilight: TwilightType,
-        format: Option<&str>,
+        preferences: &Preferences,
     ) -> String {
-        self.get_sun_event_str(
-            rise_set_type,
-            twilight,
-            format,
-            Sun::get_sunset_local,
-            "Never Sets",
-        )
+        self.get_sun_event_str(rise_set_type, twilight, preferences, Sun::get_sunset_local)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::application::environment::Environment;
+    use crate::application::observer::Observer;
+    use crate::application::sun::{season_events, sun_altitude, CrossingDirection, LightBand, RiseSetType,
+                                  SeasonEvent, Sun, SunEvent, TwilightType};
+    use crate::application::time::{delta_t, Time};
+    use crate::application::transformations::equatorial_to_altaz_time;
+
+    #[test]
+    fn test_sun_position() {
+        // Meeus, Astronomical Algorithms, example 25.a: 1992 October 13 at 0h TD, apparent
+        // ra 13h13m31.4s, dec -7°47'06" and distance 0.99760775 au
+        let jd_ut = 2_448_908.5 - delta_t(1992.78) / 86_400.0;
+        let (ra, dec) = super::sun_position_from_jd(jd_ut);
+        assert!((ra - 198.380_83).abs() < 0.01);
+        assert!((dec + 7.785_07).abs() < 0.01);
+        assert!((super::sun_distance_from_jd(jd_ut) - 0.997_607_75).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_season_events() {
+        // 2025: March 20 09:01, June 21 02:42, September 22 18:19 and December 21 15:03 UTC
+        let events = season_events(Time::new(2025, 1, 1, 0, 0, 0).to_jd(), Time::new(2026, 1, 1, 0, 0, 0).to_jd());
+        let expected = [
+            (SeasonEvent::MarchEquinox, Time::new(2025, 3, 20, 9, 1, 0)),
+            (SeasonEvent::JuneSolstice, Time::new(2025, 6, 21, 2, 42, 0)),
+            (SeasonEvent::SeptemberEquinox, Time::new(2025, 9, 22, 18, 19, 0)),
+            (SeasonEvent::DecemberSolstice, Time::new(2025, 12, 21, 15, 3, 0)),
+        ];
+        assert_eq!(events.len(), 4);
+        for ((event, jd), (expected_event, expected_time)) in events.iter().zip(expected) {
+            assert_eq!(*event, expected_event);
+            assert!((jd - expected_time.to_jd()).abs() < 0.5 / 24.0, "{:?}", event);
+        }
+
+        // a month holds its event only
+        let march = season_events(Time::new(2025, 3, 1, 0, 0, 0).to_jd(), Time::new(2025, 4, 1, 0, 0, 0).to_jd());
+        assert_eq!(march.iter().map(|(e, _)| *e).collect::<Vec<_>>(), [SeasonEvent::MarchEquinox]);
+
+        // the season depends on the hemisphere, the month name does not
+        assert_eq!(SeasonEvent::JuneSolstice.local_name(48.0), "Summer solstice");
+        assert_eq!(SeasonEvent::JuneSolstice.local_name(-23.1), "Winter solstice");
+        assert_eq!(SeasonEvent::MarchEquinox.local_name(-23.1), "Autumn equinox");
+        assert_eq!(SeasonEvent::SeptemberEquinox.local_name(0.0), "Autumn equinox");
+        assert_eq!(SeasonEvent::DecemberSolstice.name(), "December solstice");
+    }
+
+    #[test]
+    fn test_equation_of_time() {
+        // Meeus, Astronomical Algorithms, example 28.b: 1992 October 13 at 0h TD, +13m 42.6s
+        let jd_ut = 2_448_908.5 - delta_t(1992.78) / 86_400.0;
+        assert!((super::equation_of_time(jd_ut) - 13.71).abs() < 0.1);
+        // extremes of the year
+        assert!((super::equation_of_time(Time::new(2025, 2, 11, 12, 0, 0).to_jd()) + 14.2).abs() < 0.2);
+        assert!((super::equation_of_time(Time::new(2025, 11, 3, 12, 0, 0).to_jd()) - 16.4).abs() < 0.2);
+
+        // a sundial reads noon at the solar transit
+        let jd = super::solar_noon_utc(-46.5, Time::new(2025, 3, 28, 15, 0, 0).to_jd());
+        assert!((super::apparent_solar_time(-46.5, jd) - 12.0).abs() < 1.0 / 60.0);
+    }
+
+    #[test]
+    fn test_alt_az_grid() {
+        // the JD path agrees with the calendar path at whole seconds
+        let start = Time::new(2025, 3, 28, 21, 0, 0).to_jd();
+        for (jd, alt, az) in super::sun_alt_az_grid_utc(-23.1, -46.5, start, start + 1.0, 96) {
+            let (ra, dec) = super::sun_position_from_jd(jd);
+            let (expected_alt, expected_az) = equatorial_to_altaz_time(-23.1, -46.5, ra, dec, &Time::from_jd(jd));
+            assert!((alt - expected_alt).abs() < 1e-6);
+            assert!((az - expected_az).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_crossing_time_altitude() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let time = Time::new(2025, 3, 28, 12, 0, 0);
+        let environment = Environment::default();
+        let sun = Sun::new(&observer, &time, &environment);
+        let altitude = sun_altitude(observer.latitude, observer.longitude);
+
+        for angle in [6.0, -0.8333, -4.0, -18.0] {
+            for direction in [CrossingDirection::Rising, CrossingDirection::Setting] {
+                let jd = sun.crossing_time(angle, direction, RiseSetType::Next).jd().unwrap();
+                assert!((altitude(jd) - angle).abs() < 0.01);
+            }
+        }
+    }
+
+    #[test]
+    fn test_crossing_time_order() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let time = Time::new(2025, 3, 28, 12, 0, 0);
+        let environment = Environment::default();
+        let sun = Sun::new(&observer, &time, &environment);
+
+        let evening = |angle: f64| {
+            sun.crossing_time(angle, CrossingDirection::Setting, RiseSetType::Next).jd().unwrap()
+        };
+        assert!(evening(6.0) < evening(-0.8333));
+        assert!(evening(-0.8333) < evening(-4.0));
+        assert!(evening(-4.0) < evening(-18.0));
+    }
+
+    #[test]
+    fn test_transit() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let time = Time::new(2025, 3, 28, 12, 0, 0);
+        let environment = Environment::default();
+        let sun = Sun::new(&observer, &time, &environment);
+        let transit = sun.get_transit_utc();
+
+        // within the equation of time of the mean noon at 46.5° W, 15:06 UTC
+        let mean_noon = Time::new(2025, 3, 28, 15, 6, 0).to_jd();
+        assert!((transit.time - mean_noon).abs() < 17.0 / 1_440.0);
+
+        // 90° - |latitude - declination| at the meridian, the highest altitude of the day
+        let (_, dec) = super::sun_position_from_jd(transit.time);
+        assert!((transit.altitude - (90.0 - (observer.latitude - dec).abs())).abs() < 0.01);
+        let altitude = sun_altitude(observer.latitude, observer.longitude);
+        assert!(altitude(transit.time - 0.01) < transit.altitude);
+        assert!(altitude(transit.time + 0.01) < transit.altitude);
+    }
+
+    #[test]
+    fn test_zodiacal_light_window() {
+        let environment = Environment::default();
+        let time = Time::new(2025, 3, 28, 12, 0, 0);
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let sun = Sun::new(&observer, &time, &environment);
+        let altitude = sun_altitude(observer.latitude, observer.longitude);
+        let (start, end) = sun.zodiacal_light_window_utc(CrossingDirection::Setting).unwrap();
+        assert!((altitude(start) + 18.0).abs() < 0.01 && (altitude(end) + 25.0).abs() < 0.01);
+        let (start, end) = sun.zodiacal_light_window_utc(CrossingDirection::Rising).unwrap();
+        assert!((altitude(start) + 25.0).abs() < 0.01 && (altitude(end) + 18.0).abs() < 0.01);
+
+        // Paris in June, the Sun goes down to about -17°
+        let observer = Observer::location(None, "48.85", "2.35", 35, "2");
+        let time = Time::new(2025, 6, 21, 12, 0, 0);
+        let sun = Sun::new(&observer, &time, &environment);
+        assert!(sun.zodiacal_light_window_utc(CrossingDirection::Setting).is_none());
+        // Helsinki in early April reaches -18° but not -25°, the window ends at solar midnight
+        let observer = Observer::location(None, "60.17", "24.94", 10, "3");
+        let time = Time::new(2025, 4, 5, 12, 0, 0);
+        let sun = Sun::new(&observer, &time, &environment);
+        let (start, end) = sun.zodiacal_light_window_utc(CrossingDirection::Setting).unwrap();
+        assert!(end > start && (end - super::solar_midnight_utc(observer.longitude, end)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_light_bands() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let time = Time::new(2025, 3, 28, 12, 0, 0);
+        let environment = Environment::default();
+        let sun = Sun::new(&observer, &time, &environment);
+
+        // evening golden hour ends when the blue hour starts, which ends with civil twilight
+        let (golden_start, golden_end) = sun.get_band_utc(LightBand::GoldenHour, CrossingDirection::Setting);
+        let (blue_start, blue_end) = sun.get_band_utc(LightBand::BlueHour, CrossingDirection::Setting);
+        assert!(golden_start.jd().unwrap() < golden_end.jd().unwrap());
+        assert_eq!(golden_end.jd(), blue_start.jd());
+        assert_eq!(blue_end.jd(), sun.get_sunset_utc(RiseSetType::Next, TwilightType::CivilTwilight).jd());
+
+        // in the morning the blue hour comes first
+        let (blue_start, blue_end) = sun.get_band_utc(LightBand::BlueHour, CrossingDirection::Rising);
+        let (golden_start, golden_end) = sun.get_band_utc(LightBand::GoldenHour, CrossingDirection::Rising);
+        assert!(blue_start.jd().unwrap() < blue_end.jd().unwrap());
+        assert_eq!(blue_end.jd(), golden_start.jd());
+        assert!(golden_start.jd().unwrap() < golden_end.jd().unwrap());
+    }
+
+    #[test]
+    fn test_crossing_time_not_reached() {
+        // midnight sun at Longyearbyen
+        let observer = Observer::location(None, "78.22", "15.63", 10, "1");
+        let time = Time::new(2025, 6, 21, 12, 0, 0);
+        let environment = Environment::default();
+        let sun = Sun::new(&observer, &time, &environment);
+        assert_eq!(
+            sun.crossing_time(-18.0, CrossingDirection::Setting, RiseSetType::Next),
+            SunEvent::AlwaysUp
+        );
+        assert_eq!(
+            sun.crossing_time(60.0, CrossingDirection::Rising, RiseSetType::Next),
+            SunEvent::AlwaysDown
+        );
+    }
+
+    #[test]
+    fn test_night_duration() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let time = Time::new(2025, 3, 20, 0, 0, 0);
+        let environment = Environment::default();
+        let sun = Sun::new(&observer, &time, &environment);
+
+        // sunset 18:17 and sunrise 06:10 the next morning
+        let night = sun.night_duration(TwilightType::RiseSet).unwrap();
+        assert!((night - 11.9).abs() < 0.05);
+
+        let twilights: f64 = [TwilightType::CivilTwilight, TwilightType::NauticalTwilight,
+                              TwilightType::AstronomicalTwilight]
+            .iter()
+            .map(|twilight| sun.twilight_duration(*twilight).unwrap())
+            .sum();
+        let darkness = sun.night_duration(TwilightType::AstronomicalTwilight).unwrap();
+        assert!((twilights + darkness - night).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rise_set_azimuth() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let environment = Environment::default();
+
+        // due east and west at the equinox, refraction moves both by a fraction of a degree
+        let time = Time::new(2025, 3, 20, 0, 0, 0);
+        let sun = Sun::new(&observer, &time, &environment);
+        assert!((sun.get_sunrise_azimuth(RiseSetType::Next, TwilightType::RiseSet).unwrap() - 90.0).abs() < 1.0);
+        assert!((sun.get_sunset_azimuth(RiseSetType::Next, TwilightType::RiseSet).unwrap() - 270.0).abs() < 1.0);
+
+        // June solstice, cos(azimuth) = sin(23.44°) / cos(23.1°) sets the Sun at 295.6°
+        let time = Time::new(2025, 6, 21, 0, 0, 0);
+        let sun = Sun::new(&observer, &time, &environment);
+        assert!((sun.get_sunset_azimuth(RiseSetType::Next, TwilightType::RiseSet).unwrap() - 295.6).abs() < 1.0);
+
+        // no azimuth when the Sun does not set
+        let polar = Observer::location(None, "78.2", "15.6", 0, "1");
+        let sun = Sun::new(&polar, &time, &environment);
+        assert_eq!(sun.get_sunset_azimuth(RiseSetType::Next, TwilightType::RiseSet), None);
+    }
+
+    #[test]
+    fn test_solar_midnight() {
+        // at longitude 0 solar midnight is off clock midnight by the equation of time, about
+        // -14 minutes on 2025-02-11 (Sun late) and +16 minutes on 2025-11-03 (Sun early)
+        let feb = Time::new(2025, 2, 12, 0, 0, 0).to_jd();
+        assert!(((super::solar_midnight_utc(0.0, feb) - feb) * 1440.0 - 14.2).abs() < 1.0);
+        let nov = Time::new(2025, 11, 4, 0, 0, 0).to_jd();
+        assert!(((super::solar_midnight_utc(0.0, nov) - nov) * 1440.0 + 16.4).abs() < 1.0);
+        // 46.5° west is 3h06m later
+        let west = super::solar_midnight_utc(-46.5, nov + 0.125);
+        assert!(((west - nov) * 1440.0 - (186.0 - 16.4)).abs() < 1.0);
+    }
+
+    // JD (UTC) of a local date and time at a UTC offset
+    fn local_jd(year: i64, month: u64, day: u64, hour: u64, minute: u64, tz: f64) -> f64 {
+        Time::new(year, month, day, hour, minute, 0).to_jd() - tz / 24.0
+    }
+
+    #[test]
+    fn test_rise_set_local_noon() {
+        // the searches run from local noon to the next local noon: the sunset of the selected date
+        // and the sunrise after it. São Paulo (UTC-3) sets at 17:29 and rises at 06:48, Tokyo
+        // (UTC+9) sets at 19:00 and rises at 04:26 on the 2024 June solstice.
+        let jd = Time::new(2024, 6, 21, 12, 0, 0).to_jd();
+        for (lat, lon, tz, set, rise) in [(-23.55, -46.63, -3.0, (21, 17, 29), (22, 6, 48)),
+                                          (35.68, 139.69, 9.0, (21, 19, 0), (22, 4, 26))] {
+            let sunset = super::sunset_utc_grid(lat, lon, jd, -0.8333, tz).unwrap();
+            let sunrise = super::sunrise_utc_grid(lat, lon, jd, -0.8333, tz).unwrap();
+            let expected_set = local_jd(2024, 6, set.0, set.1, set.2, tz);
+            let expected_rise = local_jd(2024, 6, rise.0, rise.1, rise.2, tz);
+            assert!((sunset - expected_set).abs() * 1440.0 < 3.0, "{} {}", tz, sunset);
+            assert!((sunrise - expected_rise).abs() * 1440.0 < 3.0, "{} {}", tz, sunrise);
+        }
     }
 }
@@ -0,0 +1,165 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+use serde::{Deserialize, Serialize};
+use crate::application::observer::Observer;
+
+// Hour angle (degrees) on each side of the meridian considered near transit
+pub const NEAR_TRANSIT_HOUR_ANGLE: f64 = 15.0;
+
+/// Target struct
+///
+/// Struct representing a deep sky target.
+///
+/// # Attributes
+///
+/// * `name` - Name of the target (e.g. NGC 253)
+/// * `ra` - Right ascension in degrees
+/// * `dec` - Declination in degrees
+/// * `size` - Apparent size in arc minutes
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Target {
+    pub name: String,
+    pub ra: f64,
+    pub dec: f64,
+    #[serde(default = "default_size")]
+    pub size: f64,
+}
+
+pub fn default_size() -> f64 {
+    0.0
+}
+
+// Largest apparent size accepted in arc minutes, the Magellanic Clouds are about 650'
+pub const MAX_SIZE: f64 = 1200.0;
+
+/// Apparent size in arc minutes from the text of an input, empty is an unknown size (0), or the
+/// message shown to the user
+pub fn try_size_from_str(text: &str) -> Result<f64, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(default_size());
+    }
+    match text.parse::<f64>() {
+        Ok(size) if size.is_finite() && (0.0..=MAX_SIZE).contains(&size) => Ok(size),
+        Ok(_) => Err(format!("{} is out of range, from 0 to {} arc minutes", text, MAX_SIZE)),
+        Err(_) => Err(format!("'{}' is not a size, use arc minutes like 85 or 12.5", text)),
+    }
+}
+
+impl Target {
+    pub fn new(name: &str, ra: f64, dec: f64, size: f64) -> Target {
+        Target {
+            name: name.to_string(),
+            ra,
+            dec,
+            size,
+        }
+    }
+}
+
+/// TargetState enum
+///
+/// Position of a target relative to the meridian and the horizon at a given time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TargetState {
+    Rising,
+    NearTransit,
+    Setting,
+    BelowHorizon,
+}
+
+impl TargetState {
+    pub fn description(&self) -> &'static str {
+        match self {
+            TargetState::Rising => "Rising",
+            TargetState::NearTransit => "Near transit",
+            TargetState::Setting => "Setting",
+            TargetState::BelowHorizon => "Below horizon",
+        }
+    }
+}
+
+impl Target {
+    /// Hour angle of the target in degrees, negative east of the meridian
+    pub fn hour_angle(&self, observer: &Observer, jd: f64) -> f64 {
+        let hour_angle = observer.hour_angle(self.ra, jd);
+        if hour_angle > 180.0 { hour_angle - 360.0 } else { hour_angle }
+    }
+
+    /// Altitude of the target in degrees
+    pub fn altitude(&self, observer: &Observer, jd: f64) -> f64 {
+        observer.altaz(self.ra, self.dec, jd).0
+    }
+
+    /// State of the target at a given time derived from its hour angle
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skycalc::application::observer::Observer;
+    /// use skycalc::application::target::{Target, TargetState};
+    /// use skycalc::application::time::Time;
+    ///
+    /// let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+    /// let m42 = Target::new("M42", 83.82, -5.39, 65.0);
+    /// let jd = Time::new(2024, 12, 1, 4, 0, 0).to_jd();
+    /// assert_eq!(m42.state(&observer, jd), TargetState::NearTransit);
+    /// ```
+    pub fn state(&self, observer: &Observer, jd: f64) -> TargetState {
+        let hour_angle = self.hour_angle(observer, jd);
+        if self.altitude(observer, jd) < 0.0 {
+            TargetState::BelowHorizon
+        } else if hour_angle.abs() <= NEAR_TRANSIT_HOUR_ANGLE {
+            TargetState::NearTransit
+        } else if hour_angle < 0.0 {
+            TargetState::Rising
+        } else {
+            TargetState::Setting
+        }
+    }
+}
+
+impl std::fmt::Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}, ra: {:.4}, dec: {:.4}, size: {} arcmin",
+            self.name, self.ra, self.dec, self.size
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_try_size_from_str() {
+        assert_eq!(try_size_from_str("85"), Ok(85.0));
+        assert_eq!(try_size_from_str(" 12.5 "), Ok(12.5));
+        assert_eq!(try_size_from_str(""), Ok(0.0));
+        assert!(try_size_from_str("-3").is_err());
+        assert!(try_size_from_str("inf").is_err());
+        assert!(try_size_from_str("85'").is_err());
+    }
+}
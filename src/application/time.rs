@@ -21,11 +21,13 @@
 // IN THE SOFTWARE.
 
 use chrono::{
-    DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeZone,
+    DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone,
     Timelike, Utc,
 };
 use core::option::Option;
-use serde::{Deserialize, Deserializer, Serialize};
+use std::ops::Sub;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::utils::i18n::month_abbreviation;
 
 /// Time struct
 ///
@@ -53,11 +55,15 @@ use serde::{Deserialize, Deserializer, Serialize};
 /// * `to_gst` - Convert the Time to a Greenwich Sidereal Time
 /// * `to_utc` - Convert the Time to a `DateTime<Utc>`
 /// * `to_string` - Convert the Time to a string
+/// * `add_days` - Add (or subtract) whole days
+/// * `add_hours` - Add (or subtract) whole hours
+///
+/// Subtracting two Times gives a `chrono::Duration` and Times can be compared.
 ///
 /// # Examples
 ///
-/// ```no_run
-/// use time::Time;
+/// ```
+/// use skycalc::application::time::Time;
 ///
 /// let date = Time::new(2024, 11, 1, 0, 0, 0);
 /// assert_eq!(date.year, 2024);
@@ -67,7 +73,7 @@ use serde::{Deserialize, Deserializer, Serialize};
 /// assert_eq!(date.minute, 0);
 /// assert_eq!(date.second, 0);
 /// ```
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Time {
     pub year: i64,
     pub month: u64,
@@ -77,50 +83,165 @@ pub struct Time {
     pub second: u64,
 }
 
-// Parse from a date-time string, defaulting to current time if empty
+/// ΔT = TT - UT in seconds for a decimal year
+///
+/// Polynomial expressions of Espenak & Meeus (NASA Five Millennium Canon of Solar Eclipses).
+/// Between 2005 and 2025 observed IERS values are interpolated, then ΔT is extrapolated linearly
+/// up to the 2050 polynomial, as the 2005-2050 polynomial already overestimates ΔT by ~5 s.
+///
+/// # Examples
+///
+/// ```
+/// use skycalc::application::time::delta_t;
+///
+/// // Meeus, Astronomical Algorithms, example 10.a: 1977 February 18, ΔT = 48 s
+/// assert!((delta_t(1977.13) - 48.0).abs() < 1.0);
+/// ```
+pub fn delta_t(year: f64) -> f64 {
+    // observed ΔT every 5 years from 2005.0 to 2025.0
+    const OBSERVED: [f64; 5] = [64.69, 66.07, 67.64, 69.36, 69.20];
+
+    if year < -500.0 {
+        let u = (year - 1820.0) / 100.0;
+        -20.0 + 32.0 * u * u
+    } else if year < 500.0 {
+        let u = year / 100.0;
+        10_583.6 - 1_014.41 * u + 33.783_11 * u.powi(2) - 5.952_053 * u.powi(3)
+            - 0.179_845_2 * u.powi(4) + 0.022_174_192 * u.powi(5) + 0.009_031_652_1 * u.powi(6)
+    } else if year < 1600.0 {
+        let u = (year - 1000.0) / 100.0;
+        1_574.2 - 556.01 * u + 71.234_72 * u.powi(2) + 0.319_781 * u.powi(3)
+            - 0.850_346_3 * u.powi(4) - 0.005_050_998 * u.powi(5) + 0.008_357_207_3 * u.powi(6)
+    } else if year < 1700.0 {
+        let t = year - 1600.0;
+        120.0 - 0.980_8 * t - 0.015_32 * t.powi(2) + t.powi(3) / 7_129.0
+    } else if year < 1800.0 {
+        let t = year - 1700.0;
+        8.83 + 0.160_3 * t - 0.005_928_5 * t.powi(2) + 0.000_133_36 * t.powi(3)
+            - t.powi(4) / 1_174_000.0
+    } else if year < 1860.0 {
+        let t = year - 1800.0;
+        13.72 - 0.332_447 * t + 0.006_861_2 * t.powi(2) + 0.004_111_6 * t.powi(3)
+            - 0.000_374_36 * t.powi(4) + 0.000_012_127_2 * t.powi(5)
+            - 0.000_000_169_9 * t.powi(6) + 0.000_000_000_875 * t.powi(7)
+    } else if year < 1900.0 {
+        let t = year - 1860.0;
+        7.62 + 0.573_7 * t - 0.251_754 * t.powi(2) + 0.016_806_68 * t.powi(3)
+            - 0.000_447_362_4 * t.powi(4) + t.powi(5) / 233_174.0
+    } else if year < 1920.0 {
+        let t = year - 1900.0;
+        -2.79 + 1.494_119 * t - 0.059_893_9 * t.powi(2) + 0.006_196_6 * t.powi(3)
+            - 0.000_197 * t.powi(4)
+    } else if year < 1941.0 {
+        let t = year - 1920.0;
+        21.20 + 0.844_93 * t - 0.076_100 * t.powi(2) + 0.002_093_6 * t.powi(3)
+    } else if year < 1961.0 {
+        let t = year - 1950.0;
+        29.07 + 0.407 * t - t.powi(2) / 233.0 + t.powi(3) / 2_547.0
+    } else if year < 1986.0 {
+        let t = year - 1975.0;
+        45.45 + 1.067 * t - t.powi(2) / 260.0 - t.powi(3) / 718.0
+    } else if year < 2005.0 {
+        let t = year - 2000.0;
+        63.86 + 0.334_5 * t - 0.060_374 * t.powi(2) + 0.001_727_5 * t.powi(3)
+            + 0.000_651_814 * t.powi(4) + 0.000_023_735_99 * t.powi(5)
+    } else if year < 2025.0 {
+        let i = ((year - 2005.0) / 5.0).floor() as usize;
+        let f = (year - 2005.0) / 5.0 - i as f64;
+        OBSERVED[i] + (OBSERVED[i + 1] - OBSERVED[i]) * f
+    } else if year < 2050.0 {
+        let at_2050 = delta_t(2050.0);
+        OBSERVED[4] + (at_2050 - OBSERVED[4]) * (year - 2025.0) / 25.0
+    } else if year < 2150.0 {
+        let u = (year - 1820.0) / 100.0;
+        -20.0 + 32.0 * u * u - 0.562_8 * (2150.0 - year)
+    } else {
+        let u = (year - 1820.0) / 100.0;
+        -20.0 + 32.0 * u * u
+    }
+}
+
+/// Decimal year of a Julian Date
+pub fn decimal_year(jd: f64) -> f64 {
+    2000.0 + (jd - 2_451_545.0) / 365.25
+}
+
+/// Julian Date in Terrestrial (dynamical) Time for a Julian Date in UTC
+pub fn jd_tt(jd_utc: f64) -> f64 {
+    jd_utc + delta_t(decimal_year(jd_utc)) / 86_400.0
+}
+
+/// Julian centuries from J2000.0 in Terrestrial Time for a Julian Date in UTC, the time
+/// argument expected by the Meeus series
+pub fn julian_centuries_tt(jd_utc: f64) -> f64 {
+    (jd_tt(jd_utc) - 2_451_545.0) / 36_525.0
+}
+
+/// Greenwich mean sidereal time in degrees, 0 to 360, for a Julian Date in UTC (Meeus 12.4)
+pub fn gst_from_jd(jd: f64) -> f64 {
+    let t = (jd - 2451545.0) / 36525.0;
+    let gst = 280.46061837 + 360.98564736629 * (jd - 2451545.0) + 0.000387933 * t * t
+        - (t * t * t) / 38710000.0;
+    gst.rem_euclid(360.0)
+}
+
+// Parse from a date-time string, defaulting to current time if empty or not a date
 pub fn from_str_or_now(timestamp_str: &str) -> Time {
+    parse_time(timestamp_str).unwrap_or_default()
+}
+
+// Parse from a date-time string, None when it is not in one of the accepted formats, an empty
+// string is the current time
+pub fn parse_time(timestamp_str: &str) -> Option<Time> {
     // Define the possible date and time formats
     let date_formats = ["%Y-%m-%d", "%d/%m/%Y", "%d-%m-%Y", "%Y%m%d"];
     let time_formats = ["%H:%M:%S", "%H:%M"];
 
+    // Date and time separated by a space or by ISO 8601 'T'
     let datetime_formats: Vec<String> = date_formats
         .iter()
         .flat_map(|&date_fmt| {
-            time_formats
-                .iter()
-                .map(move |&time_fmt| format!("{} {}", date_fmt, time_fmt))
+            time_formats.iter().flat_map(move |&time_fmt| {
+                [format!("{} {}", date_fmt, time_fmt), format!("{}T{}", date_fmt, time_fmt)]
+            })
         })
         .collect();
 
+    let timestamp_str = timestamp_str.trim();
     if timestamp_str.is_empty() {
-        return Time::default();
+        return Some(Time::default());
+    }
+
+    // ISO 8601 with offset, e.g. "2024-12-11T12:00:00+00:00", converted to UTC
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(timestamp_str) {
+        return Some(Time::from_utc(datetime.with_timezone(&Utc)));
     }
 
     // Try parsing as a full date-time
     for format in &datetime_formats {
         if let Ok(datetime) = NaiveDateTime::parse_from_str(timestamp_str, format) {
-            return Time {
+            return Some(Time {
                 year: datetime.year() as i64,
                 month: datetime.month() as u64,
                 day: datetime.day() as u64,
                 hour: datetime.hour() as u64,
                 minute: datetime.minute() as u64,
                 second: datetime.second() as u64,
-            };
+            });
         }
     }
 
     // Try parsing just the date
     for format in &date_formats {
         if let Ok(date) = NaiveDate::parse_from_str(timestamp_str, format) {
-            return Time {
+            return Some(Time {
                 year: date.year() as i64,
                 month: date.month() as u64,
                 day: date.day() as u64,
                 hour: 0,
                 minute: 0,
                 second: 0,
-            };
+            });
         }
     }
 
@@ -128,22 +249,31 @@ pub fn from_str_or_now(timestamp_str: &str) -> Time {
     for format in &time_formats {
         if let Ok(time) = NaiveTime::parse_from_str(timestamp_str, format) {
             let now = Utc::now().naive_utc(); // Get the current date
-            return Time {
+            return Some(Time {
                 year: now.year() as i64,
                 month: now.month() as u64,
                 day: now.day() as u64,
                 hour: time.hour() as u64,
                 minute: time.minute() as u64,
                 second: time.second() as u64,
-            };
+            });
         }
     }
 
-    // If no format matched, default to current time
-    Time::default()
+    None
+}
+
+// Serialized as an ISO 8601 UTC string, e.g. "2024-12-11T12:00:00"
+impl Serialize for Time {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_iso())
+    }
 }
 
-// Custom deserialization for Time struct
+// Custom deserialization for Time struct, accepts the formats of `from_str_or_now`
 impl<'de> Deserialize<'de> for Time {
     fn deserialize<D>(deserializer: D) -> Result<Time, D::Error>
     where
@@ -186,8 +316,8 @@ impl Time {
     ///
     /// # Examples
     ///
-    /// ```no_run
-    /// use time::Time;
+    /// ```
+    /// use skycalc::application::time::Time;
     ///
     /// let date = Time::new(2024, 11, 1, 0, 0, 0);
     /// assert_eq!(date.year, 2024);
@@ -216,11 +346,11 @@ impl Time {
     ///
     /// # Examples
     ///
-    /// ```no_run
-    /// use time::Time;
+    /// ```
+    /// use skycalc::application::time::Time;
     ///
     /// let date = Time::now();
-    /// assert_eq!(date.year, 2024);
+    /// assert!(date.year >= 2024);
     /// ```
     pub fn now() -> Time {
         let utc = Utc::now();
@@ -246,18 +376,18 @@ impl Time {
     ///
     /// # Examples
     ///
-    /// ```no_run
-    /// use chrono::{DateTime, Datelike, Timelike, Utc, TimeZone};
-    /// use time::Time;
+    /// ```
+    /// use chrono::{Datelike, TimeZone, Timelike, Utc};
+    /// use skycalc::application::time::Time;
     ///
     /// let utc = Utc.with_ymd_and_hms(2024, 11, 1, 0, 0, 0).unwrap();
     /// let date = Time::from_utc(utc);
-    /// assert_eq!(date.year, utc.year());
-    /// assert_eq!(date.month, utc.month());
-    /// assert_eq!(date.day, utc.day());
-    /// assert_eq!(date.hour, utc.hour());
-    /// assert_eq!(date.minute, utc.minute());
-    /// assert_eq!(date.second, utc.second());
+    /// assert_eq!(date.year, utc.year() as i64);
+    /// assert_eq!(date.month, utc.month() as u64);
+    /// assert_eq!(date.day, utc.day() as u64);
+    /// assert_eq!(date.hour, utc.hour() as u64);
+    /// assert_eq!(date.minute, utc.minute() as u64);
+    /// assert_eq!(date.second, utc.second() as u64);
     /// ```
     pub fn from_utc(utc: DateTime<Utc>) -> Time {
         Time {
@@ -282,10 +412,10 @@ impl Time {
     ///
     /// # Examples
     ///
-    /// ```no_run
-    /// use time::Time;
+    /// ```
+    /// use skycalc::application::time::Time;
     ///
-    /// let isot = "2021-11-01T00:00:00Z";
+    /// let isot = "2024-11-01T00:00:00Z";
     /// let date = Time::from_isot_str(isot);
     /// assert_eq!(date.year, 2024);
     /// assert_eq!(date.month, 11);
@@ -318,8 +448,8 @@ impl Time {
     ///
     /// # Examples
     ///
-    /// ```no_run
-    /// use time::Time;
+    /// ```
+    /// use skycalc::application::time::Time;
     ///
     /// let jd = 2460564.0569609753;
     /// let date = Time::from_jd(jd);
@@ -332,31 +462,37 @@ impl Time {
     /// assert_eq!(date.second, 1);
     /// ```
     pub fn from_jd(jd: f64) -> Time {
+        // whole day number and seconds since midnight rounded to the nearest second, a time
+        // rounding up to midnight belongs to the next day
         let temp = jd + 0.5;
-        let z = temp.floor() as i32;
-        let mut f = temp - z as f64;
+        let mut z = temp.floor() as i64;
+        let mut seconds = ((temp - z as f64) * 86_400.0).round() as i64;
+        if seconds >= 86_400 {
+            z += 1;
+            seconds -= 86_400;
+        }
+
+        // calendar date (Meeus, Astronomical Algorithms, ch. 7)
         let mut a = z;
-        if z > 2299161 {
-            let alpha = ((z as f64 - 1867216.25) / 36524.25).floor() as i32;
-            a = z + 1 + alpha - (alpha / 4);
+        if z > 2_299_161 {
+            let alpha = ((z as f64 - 1_867_216.25) / 36_524.25).floor() as i64;
+            a = z + 1 + alpha - alpha.div_euclid(4);
         }
         let b = a + 1524;
-        let c = ((b as f64 - 122.1) / 365.25).floor();
-        let d = (365.25 * c) as i32;
-        let e = ((b as f64 - d as f64) / 30.6001).floor() as i32;
+        let c = ((b as f64 - 122.1) / 365.25).floor() as i64;
+        let d = (365.25 * c as f64).floor() as i64;
+        let e = ((b - d) as f64 / 30.6001).floor() as i64;
 
-        let day = b - d - ((30.6001 * e as f64) as i32) + f as i32;
+        let day = b - d - (30.6001 * e as f64).floor() as i64;
         let month = if e < 14 { e - 1 } else { e - 13 };
-        let year = if month > 2 { c - 4716.0 } else { c - 4715.0 };
+        let year = if month > 2 { c - 4716 } else { c - 4715 };
 
-        let hour = ((f * 24.0) as i32).abs();
-        f = f - (hour as f64 / 24.0);
-        let minute = ((f * 1440.0) as i32).abs();
-        f = f - (minute as f64 / 1440.0);
-        let second = ((f * 86400.0) as i32).abs();
+        let hour = seconds / 3600;
+        let minute = seconds % 3600 / 60;
+        let second = seconds % 60;
 
         Time {
-            year: year as i64,
+            year,
             month: month as u64,
             day: day as u64,
             hour: hour as u64,
@@ -377,17 +513,17 @@ impl Time {
     ///
     /// # Examples
     ///
-    /// ```no_run
-    /// use time::Time;
+    /// ```
+    /// use skycalc::application::time::Time;
     ///
     /// let mjd = 60636.5;
     /// let date = Time::from_mjd(mjd);
-    /// assert_eq!(date.year == 2024);
-    /// assert_eq!(date.month == 11);
-    /// assert_eq!(date.day == 22);
-    /// assert_eq!(date.hour == 12);
-    /// assert_eq!(date.minute == 0);
-    /// assert_eq!(date.second == 0);
+    /// assert_eq!(date.year, 2024);
+    /// assert_eq!(date.month, 11);
+    /// assert_eq!(date.day, 22);
+    /// assert_eq!(date.hour, 12);
+    /// assert_eq!(date.minute, 0);
+    /// assert_eq!(date.second, 0);
     /// ```
     pub fn from_mjd(mjd: f64) -> Time {
         Time::from_jd(mjd + 2400000.5)
@@ -401,8 +537,8 @@ impl Time {
     ///
     /// # Examples
     ///
-    /// ```no_run
-    /// use time::Time;
+    /// ```
+    /// use skycalc::application::time::Time;
     ///
     /// let date = Time::new(2024, 11, 22, 12, 0, 0);
     /// let jd = date.to_jd();
@@ -424,6 +560,11 @@ impl Time {
         jd
     }
 
+    /// Convert the Time (UTC) to a Julian Ephemeris Date in Terrestrial Time
+    pub fn to_jd_tt(&self) -> f64 {
+        jd_tt(self.to_jd())
+    }
+
     /// Convert the Time to a Modified Julian Date
     ///
     /// # Returns
@@ -432,8 +573,8 @@ impl Time {
     ///
     /// # Examples
     ///
-    /// ```no_run
-    /// use time::Time;
+    /// ```
+    /// use skycalc::application::time::Time;
     ///
     /// let date = Time::new(2024, 11, 22, 12, 0, 0);
     /// let mjd = date.to_mjd();
@@ -451,20 +592,15 @@ impl Time {
     ///
     /// # Examples
     ///
-    /// ```no_run
-    // TODO Change example
-    /// use time::Time;
+    /// ```
+    /// use skycalc::application::time::Time;
     ///
     /// let date = Time::new(2024, 8, 24, 6, 35, 34);
     /// let gst = date.to_gst();
     /// assert_eq!(gst, 71.92783272871748);
     /// ```
     pub fn to_gst(&self) -> f64 {
-        let jd = self.to_jd();
-        let t = (jd - 2451545.0) / 36525.0;
-        let gst = 280.46061837 + 360.98564736629 * (jd - 2451545.0) + 0.000387933 * t * t
-            - (t * t * t) / 38710000.0;
-        gst % 360.0
+        gst_from_jd(self.to_jd())
     }
 
     /// Convert the Time to a `DateTime<Utc>`
@@ -475,9 +611,8 @@ impl Time {
     ///
     /// # Examples
     ///
-    /// ```no_run
-    /// use time::Time;
-    /// use chrono::{DateTime, Utc};
+    /// ```
+    /// use skycalc::application::time::Time;
     ///
     /// let date = Time::new(2024, 11, 22, 12, 30, 0);
     /// let utc = date.to_utc();
@@ -496,8 +631,16 @@ impl Time {
             .unwrap()
     }
 
+    /// ISO 8601 date and time without offset, always UTC
+    pub fn to_iso(&self) -> String {
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+
     pub fn to_hhmm(&self) -> String {
-        format!("{:02}:{:02}", self.hour, self.minute)
+        TimeFormatter::default().clock(self)
     }
 
     pub fn to_yyyymmdd(&self) -> String {
@@ -505,10 +648,15 @@ impl Time {
     }
 
     pub fn to_short(&self) -> String {
-        format!(
-            "{:02}-{:02} {:02}:{:02}",
-            self.day, self.month, self.hour, self.minute
-        )
+        TimeFormatter::default().short(self)
+    }
+
+    pub fn to_hhmmss(&self) -> String {
+        TimeFormatter { seconds: true, ..TimeFormatter::default() }.clock(self)
+    }
+
+    pub fn to_short_seconds(&self) -> String {
+        TimeFormatter { seconds: true, ..TimeFormatter::default() }.short(self)
     }
 
     // TODO Add local time
@@ -524,12 +672,12 @@ impl Time {
     ///
     /// # Examples
     ///
-    /// ```no_run
-    /// use time::Time;
+    /// ```
+    /// use skycalc::application::time::Time;
     ///
     /// let date = Time::new(2024, 11, 22, 12, 0, 0);
     /// let jd_str = date.to_string(Some("jd"));
-    /// assert_eq!(jd_str, "2460637.0");
+    /// assert_eq!(jd_str, "2460637");
     ///
     /// let mjd_str = date.to_string(Some("mjd"));
     /// assert_eq!(mjd_str, "60636.5");
@@ -560,6 +708,10 @@ impl Time {
                 self.to_yyyymmdd()
             } else if format == "short" {
                 self.to_short()
+            } else if format == "hhmmss" {
+                self.to_hhmmss()
+            } else if format == "short_seconds" {
+                self.to_short_seconds()
             } else {
                 return "Invalid format".to_string();
             }
@@ -579,6 +731,78 @@ impl Time {
     pub fn get_year(&self) -> u32 {
         self.year as u32
     }
+
+    /// Add a number of days to the Time
+    ///
+    /// # Arguments
+    ///
+    /// * `days` - Number of days, negative values go back in time
+    ///
+    /// # Returns
+    ///
+    /// * `Time` - A new Time object
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skycalc::application::time::Time;
+    ///
+    /// let date = Time::new(2024, 12, 31, 22, 0, 0);
+    /// assert_eq!(date.add_days(1), Time::new(2025, 1, 1, 22, 0, 0));
+    /// assert_eq!(date.add_days(-31), Time::new(2024, 11, 30, 22, 0, 0));
+    /// ```
+    pub fn add_days(&self, days: i64) -> Time {
+        Time::from_utc(self.to_utc() + Duration::days(days))
+    }
+
+    /// Add a number of hours to the Time
+    ///
+    /// # Arguments
+    ///
+    /// * `hours` - Number of hours, negative values go back in time
+    ///
+    /// # Returns
+    ///
+    /// * `Time` - A new Time object
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skycalc::application::time::Time;
+    ///
+    /// let date = Time::new(2024, 12, 31, 22, 0, 0);
+    /// assert_eq!(date.add_hours(3), Time::new(2025, 1, 1, 1, 0, 0));
+    /// ```
+    pub fn add_hours(&self, hours: i64) -> Time {
+        Time::from_utc(self.to_utc() + Duration::hours(hours))
+    }
+}
+
+/// Difference between two Times
+///
+/// # Examples
+///
+/// ```
+/// use skycalc::application::time::Time;
+///
+/// let start = Time::new(2024, 12, 31, 22, 0, 0);
+/// let end = Time::new(2025, 1, 1, 1, 30, 0);
+/// assert_eq!((&end - &start).num_minutes(), 210);
+/// ```
+impl Sub for &Time {
+    type Output = Duration;
+
+    fn sub(self, other: &Time) -> Duration {
+        self.to_utc() - other.to_utc()
+    }
+}
+
+impl Sub for Time {
+    type Output = Duration;
+
+    fn sub(self, other: Time) -> Duration {
+        &self - &other
+    }
 }
 
 impl std::fmt::Display for Time {
@@ -590,3 +814,305 @@ impl std::fmt::Display for Time {
         )
     }
 }
+
+/// LocalTime struct
+///
+/// An instant kept as a Julian Date (UTC) together with the offset from UTC of the observer's
+/// clock at that instant. Local times used to be a JD shifted by the offset and formatted as if it
+/// were UTC, which labelled them as UTC; a LocalTime formats the wall clock date and time and,
+/// for the formats that carry a zone, the offset.
+///
+/// # Fields
+///
+/// * `jd` - Julian Date of the instant, in UTC
+/// * `offset` - Offset from UTC in hours, positive east of Greenwich
+///
+/// # Examples
+///
+/// ```
+/// use skycalc::application::time::{LocalTime, Time};
+///
+/// // 2024-11-15 01:30 UTC is still the 14th in São Paulo
+/// let local = LocalTime::new(Time::new(2024, 11, 15, 1, 30, 0).to_jd(), -3.0);
+/// assert_eq!(local.to_string(Some("yyyymmdd")), "2024-11-14");
+/// assert_eq!(local.to_string(Some("hhmm")), "22:30");
+/// assert_eq!(local.to_string(Some("isot")), "2024-11-14T22:30:00-03:00");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LocalTime {
+    pub jd: f64,
+    pub offset: f64,
+}
+
+impl LocalTime {
+    pub fn new(jd: f64, offset: f64) -> LocalTime {
+        LocalTime { jd, offset }
+    }
+
+    /// Julian Date of the wall clock time, only meant for calendar and clock arithmetic
+    pub fn local_jd(&self) -> f64 {
+        self.jd + self.offset / 24.0
+    }
+
+    /// Wall clock date and time, rounded to the nearest second
+    pub fn to_local(self) -> Time {
+        Time::from_jd(self.local_jd())
+    }
+
+    /// Date and time with the offset, None when the offset is not a valid UTC offset
+    pub fn to_datetime(self) -> Option<DateTime<FixedOffset>> {
+        let offset = FixedOffset::east_opt((self.offset * 3600.0).round() as i32)?;
+        offset.from_local_datetime(&self.to_local().to_utc().naive_utc()).single()
+    }
+
+    /// Offset from UTC as UTC±hh:mm
+    pub fn utc_offset(&self) -> String {
+        let minutes = (self.offset * 60.0).round() as i64;
+        let sign = if minutes < 0 { '-' } else { '+' };
+        format!("UTC{}{:02}:{:02}", sign, minutes.abs() / 60, minutes.abs() % 60)
+    }
+
+    /// Format the LocalTime with the formats of `Time::to_string`
+    ///
+    /// `jd` and `mjd` stay in UTC, `utc` (the default) and `isot` show the offset instead of UTC
+    /// and the other formats show the wall clock time.
+    pub fn to_string(self, format: Option<&str>) -> String {
+        match format {
+            Some("jd") => self.jd.to_string(),
+            Some("mjd") => (self.jd - 2_400_000.5).to_string(),
+            Some("utc") | None => match self.to_datetime() {
+                Some(datetime) => datetime.to_string(),
+                None => self.to_local().to_string(Some("utc")),
+            },
+            Some("isot") => match self.to_datetime() {
+                Some(datetime) => datetime.to_rfc3339(),
+                None => self.to_local().to_string(Some("isot")),
+            },
+            Some(format) => self.to_local().to_string(Some(format)),
+        }
+    }
+}
+
+impl std::fmt::Display for LocalTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} {}", self.to_local(), self.utc_offset())
+    }
+}
+
+/// TimeFormatter struct
+///
+/// How the clock formats of `Time::to_string` ("short", "hhmm" and their seconds variants)
+/// write a time, the default is the "dd-mm hh:mm" 24-hour form.
+///
+/// # Attributes
+///
+/// * `hour12` - 12-hour clock with AM/PM instead of 24 hours
+/// * `month_first` - Month before the day, "mm-dd" instead of "dd-mm"
+/// * `month_names` - Month abbreviation of the current language instead of its number, "14 Nov"
+/// * `seconds` - Seconds in every clock time, also without the seconds variant of a format
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TimeFormatter {
+    pub hour12: bool,
+    pub month_first: bool,
+    pub month_names: bool,
+    pub seconds: bool,
+}
+
+impl TimeFormatter {
+    /// Clock time, e.g. "22:05", "10:05 PM" or "10:05:09 PM"
+    pub fn clock(&self, time: &Time) -> String {
+        let (hour, suffix) = match (self.hour12, time.hour) {
+            (false, hour) => (hour, ""),
+            (true, 0) => (12, " AM"),
+            (true, hour) if hour < 12 => (hour, " AM"),
+            (true, 12) => (12, " PM"),
+            (true, hour) => (hour - 12, " PM"),
+        };
+        if self.seconds {
+            format!("{:02}:{:02}:{:02}{}", hour, time.minute, time.second, suffix)
+        } else {
+            format!("{:02}:{:02}{}", hour, time.minute, suffix)
+        }
+    }
+
+    /// Day and month in the preferred order, e.g. "14-11", "11-14", "14 Nov" or "Nov 14"
+    pub fn day_month(&self, time: &Time) -> String {
+        match (self.month_names, self.month_first) {
+            (false, false) => format!("{:02}-{:02}", time.day, time.month),
+            (false, true) => format!("{:02}-{:02}", time.month, time.day),
+            (true, false) => format!("{:02} {}", time.day, month_abbreviation(time.month)),
+            (true, true) => format!("{} {:02}", month_abbreviation(time.month), time.day),
+        }
+    }
+
+    /// Day, month and clock time, e.g. "14-11 22:05"
+    pub fn short(&self, time: &Time) -> String {
+        format!("{} {}", self.day_month(time), self.clock(time))
+    }
+
+    /// `time` in one of the `Time::to_string` formats, the clock formats written by this formatter
+    pub fn format(&self, time: &Time, format: &str) -> String {
+        let with_seconds = TimeFormatter { seconds: true, ..*self };
+        match format {
+            "short" => self.short(time),
+            "short_seconds" => with_seconds.short(time),
+            "hhmm" => self.clock(time),
+            "hhmmss" => with_seconds.clock(time),
+            other => time.to_string(Some(other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::application::moon::moon_position_high_precision;
+    use crate::application::time::{delta_t, gst_from_jd, julian_centuries_tt, LocalTime, Time, TimeFormatter};
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let date = Time::new(2024, 12, 11, 21, 5, 9);
+        let yaml = serde_yaml::to_string(&date).unwrap();
+        assert_eq!(yaml.trim(), "2024-12-11T21:05:09");
+        assert_eq!(serde_yaml::from_str::<Time>(&yaml).unwrap(), date);
+
+        // hand edited values keep working
+        assert_eq!(serde_yaml::from_str::<Time>("2024-12-11 21:05:09").unwrap(), date);
+        assert_eq!(serde_yaml::from_str::<Time>("11/12/2024 21:05:09").unwrap(), date);
+        assert_eq!(serde_yaml::from_str::<Time>("2024-12-11T18:05:09-03:00").unwrap(), date);
+    }
+
+    #[test]
+    fn test_delta_t() {
+        // Meeus, Astronomical Algorithms, example 10.a
+        assert!((delta_t(1977.13) - 48.0).abs() < 1.0);
+        // observed values
+        assert!((delta_t(2000.0) - 63.8).abs() < 0.5);
+        assert!((delta_t(2025.0) - 69.2).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_to_jd_tt() {
+        let date = Time::new(2025, 1, 1, 0, 0, 0);
+        assert!(((date.to_jd_tt() - date.to_jd()) * 86_400.0 - delta_t(2025.0)).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_julian_date() {
+        // Meeus, Astronomical Algorithms, examples 7.a and 7.c: 1957 October 4.81
+        let sputnik = Time::new(1957, 10, 4, 19, 26, 24);
+        assert!((sputnik.to_jd() - 2_436_116.31).abs() < 1e-6);
+        assert_eq!(Time::from_jd(2_436_116.31), sputnik);
+        // J2000.0
+        assert_eq!(Time::new(2000, 1, 1, 12, 0, 0).to_jd(), 2_451_545.0);
+        assert_eq!(Time::from_jd(2_451_545.0), Time::new(2000, 1, 1, 12, 0, 0));
+        // Meeus, Astronomical Algorithms, chapter 7: 1988 June 19.5
+        assert_eq!(Time::new(1988, 6, 19, 12, 0, 0).to_jd(), 2_447_332.0);
+    }
+
+    #[test]
+    fn test_jd_calendar_round_trip() {
+        // deterministic pseudo random times between 1901 and 2099, the range of to_jd
+        let mut seed: u64 = 20_240_101;
+        let mut next = |range: u64| {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+            (seed >> 33) % range
+        };
+        let days_in_month = [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+        for _ in 0..10_000 {
+            let month = next(12) + 1;
+            let time = Time::new(1901 + next(199) as i64, month, next(days_in_month[month as usize - 1] - 1) + 1,
+                                 next(24), next(60), next(60));
+            assert_eq!(Time::from_jd(time.to_jd()), time);
+        }
+        // consecutive days are one JD apart, across month, year and leap days
+        for jd in [2_451_604.5, 2_451_939.5, 2_453_064.5, 2_488_068.5] {
+            let day = Time::from_jd(jd);
+            assert_eq!(day.to_jd(), jd);
+            assert_eq!(Time::from_jd(jd + 1.0).to_jd() - day.to_jd(), 1.0);
+        }
+    }
+
+    #[test]
+    fn test_from_jd_round_trip() {
+        // any JD comes back within half a second, the resolution of Time
+        let mut jd = 2_415_385.5;
+        while jd < 2_488_069.5 {
+            let time = Time::from_jd(jd);
+            assert!((time.to_jd() - jd).abs() * 86_400.0 <= 0.5 + 1e-4, "{} {}", jd, time);
+            jd += 0.123_456_789;
+        }
+        // fractions of a second round to the nearest second, up to the next day
+        let midnight = Time::new(2024, 12, 31, 0, 0, 0).to_jd();
+        assert_eq!(Time::from_jd(midnight + 59.4 / 86_400.0), Time::new(2024, 12, 31, 0, 0, 59));
+        assert_eq!(Time::from_jd(midnight + 59.6 / 86_400.0), Time::new(2024, 12, 31, 0, 1, 0));
+        assert_eq!(Time::from_jd(midnight - 0.3 / 86_400.0), Time::new(2024, 12, 31, 0, 0, 0));
+        assert_eq!(Time::from_jd(midnight + 1.0 - 0.3 / 86_400.0), Time::new(2025, 1, 1, 0, 0, 0));
+        assert_eq!(Time::from_jd(midnight - 0.7 / 86_400.0), Time::new(2024, 12, 30, 23, 59, 59));
+    }
+
+    #[test]
+    fn test_sidereal_time() {
+        // Meeus, Astronomical Algorithms, example 12.a: 1987 April 10 at 0h UT, 13h10m46.3668s
+        assert!((gst_from_jd(2_446_895.5) - 197.693_195).abs() < 1e-6);
+        // example 12.b: 1987 April 10 at 19h21m00s UT, 8h34m57.0896s
+        assert!((gst_from_jd(2_446_896.306_25) - 128.737_873_4).abs() < 1e-6);
+        assert!((Time::new(1987, 4, 10, 19, 21, 0).to_gst() - 128.737_873_4).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_moon_position_tt() {
+        // Meeus, Astronomical Algorithms, example 47.a: 1992 April 12 at 0h TD
+        let jd_ut = 2_448_724.5 - delta_t(1992.28) / 86_400.0;
+        let (ra, dec, distance) = moon_position_high_precision(julian_centuries_tt(jd_ut));
+        assert!((ra - 134.688_470).abs() < 0.001);
+        assert!((dec - 13.768_368).abs() < 0.001);
+        assert!((distance - 368_409.7).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_local_time() {
+        // evening of the 14th in São Paulo, already the 15th in UTC
+        let jd = Time::new(2024, 11, 15, 1, 30, 0).to_jd();
+        let local = LocalTime::new(jd, -3.0);
+        assert_eq!(local.to_local(), Time::new(2024, 11, 14, 22, 30, 0));
+        assert_eq!(local.to_string(None), "2024-11-14 22:30:00 -03:00");
+        assert_eq!(local.to_string(Some("isot")), "2024-11-14T22:30:00-03:00");
+        assert_eq!(local.to_string(Some("short")), Time::new(2024, 11, 14, 22, 30, 0).to_string(Some("short")));
+        assert_eq!(local.to_string(Some("jd")), jd.to_string());
+        assert_eq!(format!("{}", local), "2024-11-14 22:30:00 UTC-03:00");
+
+        // fractional offsets
+        let local = LocalTime::new(jd, 5.5);
+        assert_eq!(local.utc_offset(), "UTC+05:30");
+        assert_eq!(local.to_string(Some("isot")), "2024-11-15T07:00:00+05:30");
+    }
+
+    #[test]
+    fn test_time_formatter() {
+        let evening = Time::new(2024, 11, 14, 22, 5, 9);
+        let midnight = Time::new(2024, 11, 14, 0, 30, 0);
+        let noon = Time::new(2024, 11, 14, 12, 0, 0);
+
+        // the default keeps the "dd-mm hh:mm" form of Time::to_string
+        let default = TimeFormatter::default();
+        assert_eq!(default.format(&evening, "short"), evening.to_string(Some("short")));
+        assert_eq!(default.format(&evening, "short"), "14-11 22:05");
+        assert_eq!(default.format(&evening, "hhmmss"), "22:05:09");
+        assert_eq!(default.format(&evening, "yyyymmdd"), "2024-11-14");
+
+        let us = TimeFormatter { hour12: true, month_first: true, ..TimeFormatter::default() };
+        assert_eq!(us.format(&evening, "short"), "11-14 10:05 PM");
+        assert_eq!(us.format(&midnight, "hhmm"), "12:30 AM");
+        assert_eq!(us.format(&noon, "hhmm"), "12:00 PM");
+        assert_eq!(us.format(&evening, "short_seconds"), "11-14 10:05:09 PM");
+
+        let seconds = TimeFormatter { seconds: true, ..TimeFormatter::default() };
+        assert_eq!(seconds.format(&evening, "short"), "14-11 22:05:09");
+
+        // month abbreviation of the current language, English in the tests
+        let names = TimeFormatter { month_names: true, ..TimeFormatter::default() };
+        assert_eq!(names.format(&evening, "short"), "14 Nov 22:05");
+        let names_first = TimeFormatter { month_names: true, ..us };
+        assert_eq!(names_first.format(&evening, "short"), "Nov 14 10:05 PM");
+    }
+}
@@ -0,0 +1,362 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Sky of a night for the timelines of the HTML report and the windows: the Sun altitude from day
+// to full darkness and the Moon above the horizon, over 24 hours from local noon or centered on
+// solar midnight. The altitude chart adds the Sun, the Moon and the targets over the same hours.
+
+use std::ops::Range;
+use crate::application::calculation::CalculationSettings;
+use crate::application::moon::{moon_altitude, moon_illumination, moon_topocentric_position};
+use crate::application::observer::Observer;
+use crate::application::sun::{solar_midnight_utc, sun_altitude, sun_position_from_jd, TwilightType};
+use crate::application::target::Target;
+use crate::application::time::{gst_from_jd, Time};
+
+/// Sky from day to full darkness following the Sun altitude
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkyBand {
+    Day,
+    Civil,
+    Nautical,
+    Astronomical,
+    Dark,
+}
+
+impl SkyBand {
+    /// Band of a Sun altitude, the day ends at the sunset altitude of the accuracy settings
+    pub fn from_sun_altitude(sun_altitude: f64, settings: CalculationSettings) -> SkyBand {
+        if sun_altitude > settings.twilight_angle(TwilightType::RiseSet) {
+            SkyBand::Day
+        } else if sun_altitude > -6.0 {
+            SkyBand::Civil
+        } else if sun_altitude > -12.0 {
+            SkyBand::Nautical
+        } else if sun_altitude > -18.0 {
+            SkyBand::Astronomical
+        } else {
+            SkyBand::Dark
+        }
+    }
+
+    /// Name of the band in the reports
+    pub fn name(&self) -> &'static str {
+        match self {
+            SkyBand::Day => "day",
+            SkyBand::Civil => "civil",
+            SkyBand::Nautical => "nautical",
+            SkyBand::Astronomical => "astronomical",
+            SkyBand::Dark => "dark",
+        }
+    }
+
+    /// Background color of the band, red, green and blue
+    pub fn rgb(&self) -> (u8, u8, u8) {
+        match self {
+            SkyBand::Day => (0x4a, 0x5a, 0x7a),
+            SkyBand::Civil => (0x34, 0x40, 0x5a),
+            SkyBand::Nautical => (0x23, 0x2b, 0x40),
+            SkyBand::Astronomical => (0x15, 0x1a, 0x28),
+            SkyBand::Dark => (0x00, 0x00, 0x00),
+        }
+    }
+
+    /// Background color of the band as #rrggbb
+    pub fn hex(&self) -> String {
+        let (r, g, b) = self.rgb();
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    }
+}
+
+/// Color of the Moon up bar, red, green and blue
+pub const MOON_UP_RGB: (u8, u8, u8) = (0x8a, 0x8a, 0x5a);
+
+/// Colors of the Sun and Moon curves of the altitude chart, red, green and blue
+pub const SUN_RGB: (u8, u8, u8) = (0xff, 0xc0, 0x30);
+pub const MOON_RGB: (u8, u8, u8) = (0xd0, 0xd0, 0xb0);
+
+/// Colors of the target curves of the altitude chart, taken in turn
+pub const TARGET_RGB: [(u8, u8, u8); 6] = [
+    (0xff, 0x50, 0x50), (0x50, 0xd0, 0x50), (0x50, 0xa0, 0xff),
+    (0xff, 0x80, 0xff), (0x40, 0xe0, 0xe0), (0xff, 0xa0, 0x60),
+];
+
+/// Start of the 24 hours shown by the timelines, from local noon or centered on solar midnight
+pub fn timeline_start(observer: &Observer, time: &Time, solar_midnight: bool) -> f64 {
+    let offset = observer.timezone / 24.0;
+    let night_start = (time.to_jd() + 0.5).floor() - offset;
+    if solar_midnight {
+        solar_midnight_utc(observer.longitude, night_start + 0.5) - 0.5
+    } else {
+        night_start
+    }
+}
+
+/// Label of the hour tick `hour` (0 - 24) from the start of the timeline, local time from noon
+/// or hours from solar midnight
+pub fn hour_label(hour: i64, solar_midnight: bool) -> String {
+    if solar_midnight {
+        match hour - 12 {
+            0 => "0".to_string(),
+            hours => format!("{:+}", hours),
+        }
+    } else {
+        format!("{:02}", (hour + 12) % 24)
+    }
+}
+
+/// NightTimeline struct
+///
+/// Sky band and Moon of the 24 hours of a night in equal steps.
+///
+/// # Attributes
+///
+/// * `start` - Start of the 24 hours, UTC Julian Date
+/// * `sky` - Sky band at the middle of each step
+/// * `moon_up` - Moon above the horizon at the middle of each step
+#[derive(Debug, Clone, PartialEq)]
+pub struct NightTimeline {
+    pub start: f64,
+    pub sky: Vec<SkyBand>,
+    pub moon_up: Vec<bool>,
+}
+
+impl NightTimeline {
+    pub fn new(observer: &Observer, time: &Time, solar_midnight: bool, steps: usize,
+               settings: CalculationSettings) -> NightTimeline {
+        let start = timeline_start(observer, time, solar_midnight);
+        let sun = sun_altitude(observer.latitude, observer.longitude);
+        let moon = moon_altitude(observer.latitude, observer.longitude, observer.elevation as f64);
+        let jd: Vec<f64> = (0..steps).map(|i| start + (i as f64 + 0.5) / steps as f64).collect();
+        NightTimeline {
+            start,
+            sky: jd.iter().map(|&jd| SkyBand::from_sun_altitude(sun(jd), settings)).collect(),
+            moon_up: jd.iter().map(|&jd| moon(jd) > 0.0).collect(),
+        }
+    }
+
+    /// Position of a JD (UTC) along the timeline, 0.0 at the start and 1.0 at the end, None
+    /// outside of it
+    pub fn position(&self, jd: f64) -> Option<f64> {
+        let position = jd - self.start;
+        (0.0..=1.0).contains(&position).then_some(position)
+    }
+}
+
+/// Altitude and azimuth in degrees of the Sun and of the topocentric Moon at a JD (UTC)
+pub fn sun_moon_alt_az(observer: &Observer, jd: f64) -> ((f64, f64), (f64, f64)) {
+    let (sun_ra, sun_dec) = sun_position_from_jd(jd);
+    let (moon_ra, moon_dec, _) = moon_topocentric_position(observer.latitude, observer.longitude,
+                                                           observer.elevation as f64, jd);
+    (observer.altaz(sun_ra, sun_dec, jd), observer.altaz(moon_ra, moon_dec, jd))
+}
+
+/// HourConditions struct
+///
+/// Sky at the start of a whole local hour of the night.
+///
+/// # Attributes
+///
+/// * `jd` - Start of the hour, UTC Julian Date
+/// * `sun_altitude` - Altitude of the Sun in degrees
+/// * `moon_altitude` - Altitude of the Moon in degrees
+/// * `moon_illumination` - Illuminated fraction of the Moon (0.0 - 1.0)
+/// * `sky` - Sky band of the Sun altitude
+/// * `lst` - Local sidereal time in hours
+/// * `target_altitudes` - Altitude of each target in degrees, in the order of the targets
+#[derive(Debug, Clone, PartialEq)]
+pub struct HourConditions {
+    pub jd: f64,
+    pub sun_altitude: f64,
+    pub moon_altitude: f64,
+    pub moon_illumination: f64,
+    pub sky: SkyBand,
+    pub lst: f64,
+    pub target_altitudes: Vec<f64>,
+}
+
+/// Conditions on the whole local hours from local noon to the next local noon with the Sun below
+/// the horizon
+pub fn hourly_conditions(observer: &Observer, time: &Time, targets: &[Target],
+                         settings: CalculationSettings) -> Vec<HourConditions> {
+    let start = timeline_start(observer, time, false);
+    let sun = sun_altitude(observer.latitude, observer.longitude);
+    let moon = moon_altitude(observer.latitude, observer.longitude, observer.elevation as f64);
+    (0..24)
+        .map(|hour| start + hour as f64 / 24.0)
+        .filter(|&jd| SkyBand::from_sun_altitude(sun(jd), settings) != SkyBand::Day)
+        .map(|jd| HourConditions {
+            jd,
+            sun_altitude: sun(jd),
+            moon_altitude: moon(jd),
+            moon_illumination: moon_illumination(jd),
+            sky: SkyBand::from_sun_altitude(sun(jd), settings),
+            lst: (gst_from_jd(jd) + observer.longitude).rem_euclid(360.0) / 15.0,
+            target_altitudes: targets.iter().map(|t| observer.altaz(t.ra, t.dec, jd).0).collect(),
+        })
+        .collect()
+}
+
+/// AltitudeChart struct
+///
+/// Altitudes in degrees over the 24 hours of a night timeline, at the middle of each of its steps.
+///
+/// # Attributes
+///
+/// * `timeline` - Sky bands and Moon of the night, the background of the chart
+/// * `sun` - Altitude of the Sun
+/// * `moon` - Altitude of the Moon
+/// * `targets` - Name and altitudes of each target
+#[derive(Debug, Clone, PartialEq)]
+pub struct AltitudeChart {
+    pub timeline: NightTimeline,
+    pub sun: Vec<f64>,
+    pub moon: Vec<f64>,
+    pub targets: Vec<(String, Vec<f64>)>,
+}
+
+/// Curve of the altitude chart as name, altitudes, color and dashed
+pub type ChartCurve<'a> = (&'a str, &'a [f64], (u8, u8, u8), bool);
+
+impl AltitudeChart {
+    pub fn new(observer: &Observer, time: &Time, targets: &[Target], solar_midnight: bool,
+               steps: usize, settings: CalculationSettings) -> AltitudeChart {
+        let timeline = NightTimeline::new(observer, time, solar_midnight, steps, settings);
+        let jd: Vec<f64> = (0..steps).map(|i| timeline.start + (i as f64 + 0.5) / steps as f64).collect();
+        let sun = sun_altitude(observer.latitude, observer.longitude);
+        let moon = moon_altitude(observer.latitude, observer.longitude, observer.elevation as f64);
+        AltitudeChart {
+            sun: jd.iter().map(|&jd| sun(jd)).collect(),
+            moon: jd.iter().map(|&jd| moon(jd)).collect(),
+            targets: targets
+                .iter()
+                .map(|t| (t.name.clone(), jd.iter().map(|&jd| observer.altaz(t.ra, t.dec, jd).0).collect()))
+                .collect(),
+            timeline,
+        }
+    }
+
+    /// Curves of the chart: the Sun and the Moon dashed, then the targets with the colors in turn
+    pub fn curves(&self) -> Vec<ChartCurve<'_>> {
+        [("Sun", self.sun.as_slice(), SUN_RGB, true), ("Moon", self.moon.as_slice(), MOON_RGB, true)]
+            .into_iter()
+            .chain(self.targets.iter().enumerate().map(|(i, (name, altitudes))| {
+                (name.as_str(), altitudes.as_slice(), TARGET_RGB[i % TARGET_RGB.len()], false)
+            }))
+            .collect()
+    }
+}
+
+/// Runs of consecutive steps above the horizon, the parts of a curve drawn on the chart
+pub fn above_horizon(altitudes: &[f64]) -> Vec<Range<usize>> {
+    let mut runs: Vec<Range<usize>> = Vec::new();
+    for (i, &altitude) in altitudes.iter().enumerate() {
+        if altitude < 0.0 {
+            continue;
+        }
+        match runs.last_mut() {
+            Some(run) if run.end == i => run.end = i + 1,
+            _ => runs.push(i..i + 1),
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_night_timeline() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let time = Time::new(2025, 3, 28, 12, 0, 0);
+        let timeline = NightTimeline::new(&observer, &time, false, 288, CalculationSettings::default());
+
+        // from local noon, daylight at both ends and full darkness in the middle
+        assert!((timeline.start - Time::new(2025, 3, 28, 15, 0, 0).to_jd()).abs() < 1e-9);
+        assert_eq!(timeline.sky.len(), 288);
+        assert_eq!(timeline.sky[0], SkyBand::Day);
+        assert_eq!(timeline.sky[144], SkyBand::Dark);
+        assert_eq!(timeline.sky[287], SkyBand::Day);
+        // the bands deepen to darkness and lighten back without skipping one
+        let changes: Vec<SkyBand> = timeline.sky.windows(2).filter(|w| w[0] != w[1]).map(|w| w[1]).collect();
+        assert_eq!(changes, [SkyBand::Civil, SkyBand::Nautical, SkyBand::Astronomical, SkyBand::Dark,
+                             SkyBand::Astronomical, SkyBand::Nautical, SkyBand::Civil, SkyBand::Day]);
+        // new Moon on the 29th, up in daylight only
+        assert!(timeline.moon_up.iter().zip(&timeline.sky).all(|(&up, &sky)| !up || sky != SkyBand::Dark));
+
+        assert_eq!(timeline.position(timeline.start + 0.25), Some(0.25));
+        assert_eq!(timeline.position(timeline.start - 0.01), None);
+
+        // Sun and Moon at the middle of the night, the same altitudes as the timeline bands
+        let middle = timeline.start + 0.5;
+        let (sun, moon) = sun_moon_alt_az(&observer, middle);
+        assert!((sun.0 - sun_altitude(observer.latitude, observer.longitude)(middle)).abs() < 1e-9);
+        assert!(sun.0 < -18.0 && (0.0..360.0).contains(&sun.1));
+        assert!((moon.0 - moon_altitude(observer.latitude, observer.longitude, 780.0)(middle)).abs() < 1e-9);
+        assert!(!timeline.moon_up[144] && moon.0 < 0.0);
+        assert_eq!(SkyBand::Day.hex(), "#4a5a7a");
+        assert_eq!(hour_label(12, true), "0");
+        assert_eq!(hour_label(2, true), "-10");
+        assert_eq!(hour_label(14, false), "02");
+    }
+
+    #[test]
+    fn test_hourly_conditions() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let time = Time::new(2025, 3, 28, 12, 0, 0);
+        let m42 = Target::new("M42", 83.82, -5.39, 85.0);
+        let hours = hourly_conditions(&observer, &time, std::slice::from_ref(&m42), CalculationSettings::default());
+
+        // sunset just after 18:00 and sunrise near 06:00 local, the hours in between on the hour
+        assert!((10..=12).contains(&hours.len()));
+        assert!(hours.iter().all(|hour| hour.sun_altitude < -0.8333 && hour.target_altitudes.len() == 1));
+        let local_hour = |jd: f64| ((jd - 0.5 + observer.timezone / 24.0).fract() * 24.0).round() as i64;
+        assert_eq!(local_hour(hours[0].jd), 19);
+        assert!(hours.iter().any(|hour| hour.sky == SkyBand::Dark));
+        assert!(hours.iter().all(|hour| (0.0..24.0).contains(&hour.lst) && hour.moon_illumination < 0.05));
+
+        // at local midnight the sidereal time is about 12h 20m in late March, M42 sets around then
+        let midnight = hours.iter().find(|hour| local_hour(hour.jd) == 0).unwrap();
+        assert!((midnight.lst - 12.33).abs() < 0.2);
+        assert!((midnight.target_altitudes[0] - observer.altaz(m42.ra, m42.dec, midnight.jd).0).abs() < 1e-9);
+        assert_eq!(SkyBand::Astronomical.name(), "astronomical");
+    }
+
+    #[test]
+    fn test_altitude_chart() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let time = Time::new(2025, 3, 28, 12, 0, 0);
+        let m42 = Target::new("M42", 83.82, -5.39, 65.0);
+        let chart = AltitudeChart::new(&observer, &time, &[m42], false, 288, CalculationSettings::default());
+
+        assert_eq!((chart.sun.len(), chart.moon.len()), (288, 288));
+        assert_eq!(chart.targets[0].0, "M42");
+        // the Sun sets and rises once, M42 is up from noon to the evening and rises in the morning
+        assert_eq!(above_horizon(&chart.sun).len(), 2);
+        let m42_runs = above_horizon(&chart.targets[0].1);
+        assert_eq!(m42_runs.len(), 2);
+        assert!(m42_runs[0].start == 0 && m42_runs[0].end < 144);
+        assert!(m42_runs[1].start > 144 && m42_runs[1].end == 288);
+        assert_eq!(above_horizon(&[-1.0, 2.0, 3.0, -1.0, 4.0]), vec![1..3, 4..5]);
+    }
+}
@@ -0,0 +1,347 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Embedded IANA timezones with their standard time offset and cities inside each zone. There are
+// no tz boundaries: the zone of a location is guessed as the one of the nearest city, so zones
+// spread over a large area or along a border where the offset changes have several cities. Near a
+// border the nearest city can be across it, and the guess is the zone of the other side; the
+// guesses are shown to be checked before they are applied. Daylight saving time is not applied,
+// like the rest of the program.
+
+use crate::application::observer::Observer;
+
+// Cities farther than this (km) are not taken as the zone of a location
+const MAX_CITY_DISTANCE: f64 = 1500.0;
+const EARTH_RADIUS: f64 = 6371.0;
+
+/// Zone struct
+///
+/// # Attributes
+///
+/// * `name` - Name of the zone in the tz database, e.g. "Europe/Paris"
+/// * `offset` - Standard time offset from UTC in hours
+/// * `points` - Latitude and longitude in degrees of cities inside the zone
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Zone {
+    pub name: &'static str,
+    pub offset: f64,
+    pub points: &'static [(f64, f64)],
+}
+
+const fn zone(name: &'static str, offset: f64, points: &'static [(f64, f64)]) -> Zone {
+    Zone { name, offset, points }
+}
+
+pub const ZONES: &[Zone] = &[
+    // Europe and Africa
+    zone("Atlantic/Azores", -1.0, &[(37.74, -25.67)]),
+    zone("Atlantic/Reykjavik", 0.0, &[(64.15, -21.94), (65.68, -18.09)]),
+    zone("Atlantic/Canary", 0.0, &[(28.12, -15.43), (28.46, -16.25), (28.68, -17.76), (28.30, -16.51)]),
+    zone("Europe/London", 0.0, &[(51.51, -0.13), (53.48, -2.24), (55.95, -3.19), (50.37, -4.14), (54.60, -5.93),
+                                  (57.48, -4.22)]),
+    zone("Europe/Dublin", 0.0, &[(53.35, -6.26), (51.90, -8.47), (53.27, -9.05)]),
+    zone("Europe/Lisbon", 0.0, &[(38.72, -9.14), (41.15, -8.61), (37.02, -7.93)]),
+    zone("Africa/Dakar", 0.0, &[(14.72, -17.47)]),
+    zone("Africa/Accra", 0.0, &[(5.60, -0.19)]),
+    zone("Europe/Paris", 1.0, &[(48.86, 2.35), (48.39, -4.49), (44.84, -0.58), (43.30, 5.37), (45.76, 4.84),
+                                 (48.57, 7.75), (50.63, 3.06), (43.60, 1.44), (47.22, -1.55), (42.94, 0.14)]),
+    zone("Europe/Madrid", 1.0, &[(40.42, -3.70), (41.39, 2.17), (37.39, -5.98), (36.84, -2.46), (43.36, -8.41),
+                                  (43.26, -2.93), (39.47, -0.38), (37.22, -2.55)]),
+    zone("Europe/Brussels", 1.0, &[(50.85, 4.35), (51.22, 4.40)]),
+    zone("Europe/Amsterdam", 1.0, &[(52.37, 4.90), (53.22, 6.57)]),
+    zone("Europe/Luxembourg", 1.0, &[(49.61, 6.13)]),
+    zone("Europe/Berlin", 1.0, &[(52.52, 13.40), (53.55, 9.99), (48.14, 11.58), (50.94, 6.96), (50.11, 8.68),
+                                  (51.05, 13.74)]),
+    zone("Europe/Zurich", 1.0, &[(47.38, 8.54), (46.20, 6.14)]),
+    zone("Europe/Rome", 1.0, &[(41.90, 12.50), (45.46, 9.19), (40.85, 14.27), (38.12, 13.36), (39.22, 9.12),
+                                (41.12, 16.87)]),
+    zone("Europe/Vienna", 1.0, &[(48.21, 16.37), (47.27, 11.40)]),
+    zone("Europe/Prague", 1.0, &[(50.08, 14.44)]),
+    zone("Europe/Bratislava", 1.0, &[(48.15, 17.11)]),
+    zone("Europe/Ljubljana", 1.0, &[(46.06, 14.51)]),
+    zone("Europe/Zagreb", 1.0, &[(45.81, 15.98)]),
+    zone("Europe/Belgrade", 1.0, &[(44.79, 20.45)]),
+    zone("Europe/Budapest", 1.0, &[(47.50, 19.04)]),
+    zone("Europe/Warsaw", 1.0, &[(52.23, 21.01), (54.35, 18.65), (50.06, 19.94), (51.11, 17.04),
+                                  (53.13, 23.16)]),
+    zone("Europe/Copenhagen", 1.0, &[(55.68, 12.57), (56.16, 10.20)]),
+    zone("Europe/Oslo", 1.0, &[(59.91, 10.75), (60.39, 5.32), (63.43, 10.40), (69.65, 18.96)]),
+    zone("Europe/Stockholm", 1.0, &[(59.33, 18.07), (57.71, 11.97), (63.83, 20.26), (67.86, 20.23)]),
+    zone("Arctic/Longyearbyen", 1.0, &[(78.22, 15.63)]),
+    zone("Africa/Algiers", 1.0, &[(36.75, 3.06)]),
+    zone("Africa/Lagos", 1.0, &[(6.52, 3.38)]),
+    zone("Africa/Kinshasa", 1.0, &[(-4.44, 15.27)]),
+    zone("Europe/Helsinki", 2.0, &[(60.17, 24.94), (65.01, 25.47)]),
+    zone("Europe/Tallinn", 2.0, &[(59.44, 24.75)]),
+    zone("Europe/Riga", 2.0, &[(56.95, 24.11)]),
+    zone("Europe/Vilnius", 2.0, &[(54.69, 25.28)]),
+    zone("Europe/Kyiv", 2.0, &[(50.45, 30.52), (49.84, 24.03), (46.48, 30.72)]),
+    zone("Europe/Bucharest", 2.0, &[(44.43, 26.10)]),
+    zone("Europe/Sofia", 2.0, &[(42.70, 23.32)]),
+    zone("Europe/Athens", 2.0, &[(37.98, 23.73), (40.64, 22.94), (35.34, 25.13)]),
+    zone("Asia/Jerusalem", 2.0, &[(31.77, 35.21)]),
+    zone("Africa/Cairo", 2.0, &[(30.04, 31.24)]),
+    zone("Africa/Windhoek", 2.0, &[(-22.56, 17.08), (-23.24, 16.36)]),
+    zone("Africa/Maputo", 2.0, &[(-25.97, 32.57)]),
+    zone("Africa/Johannesburg", 2.0, &[(-26.20, 28.05), (-33.92, 18.42), (-29.86, 31.02), (-32.38, 20.81)]),
+    zone("Europe/Istanbul", 3.0, &[(41.01, 28.98), (39.93, 32.86), (38.42, 27.14), (36.90, 30.71)]),
+    zone("Europe/Minsk", 3.0, &[(53.90, 27.56), (52.10, 23.69)]),
+    zone("Europe/Moscow", 3.0, &[(55.76, 37.62), (59.93, 30.34)]),
+    zone("Africa/Addis_Ababa", 3.0, &[(9.03, 38.74)]),
+    zone("Africa/Nairobi", 3.0, &[(-1.29, 36.82)]),
+    zone("Europe/Samara", 4.0, &[(53.20, 50.15)]),
+    zone("Indian/Mauritius", 4.0, &[(-20.16, 57.50)]),
+    // Asia and Oceania
+    zone("Asia/Riyadh", 3.0, &[(24.71, 46.68)]),
+    zone("Asia/Tehran", 3.5, &[(35.69, 51.39)]),
+    zone("Asia/Dubai", 4.0, &[(25.20, 55.27)]),
+    zone("Asia/Kabul", 4.5, &[(34.56, 69.21)]),
+    zone("Asia/Karachi", 5.0, &[(24.86, 67.01)]),
+    zone("Asia/Tashkent", 5.0, &[(41.30, 69.24)]),
+    zone("Asia/Yekaterinburg", 5.0, &[(56.84, 60.61)]),
+    zone("Asia/Kolkata", 5.5, &[(28.61, 77.21), (19.08, 72.88), (13.08, 80.27), (22.57, 88.36), (32.78, 78.96)]),
+    zone("Asia/Colombo", 5.5, &[(6.93, 79.86)]),
+    zone("Asia/Kathmandu", 5.75, &[(27.72, 85.32)]),
+    zone("Asia/Dhaka", 6.0, &[(23.81, 90.41)]),
+    zone("Asia/Yangon", 6.5, &[(16.87, 96.20)]),
+    zone("Asia/Novosibirsk", 7.0, &[(55.03, 82.92)]),
+    zone("Asia/Krasnoyarsk", 7.0, &[(56.01, 92.87)]),
+    zone("Asia/Bangkok", 7.0, &[(13.76, 100.50)]),
+    zone("Asia/Jakarta", 7.0, &[(-6.21, 106.85)]),
+    zone("Asia/Irkutsk", 8.0, &[(52.29, 104.30)]),
+    zone("Asia/Shanghai", 8.0, &[(39.90, 116.41), (31.23, 121.47), (40.40, 117.58), (30.57, 104.07),
+                                  (43.83, 87.62)]),
+    zone("Asia/Hong_Kong", 8.0, &[(22.32, 114.17)]),
+    zone("Asia/Taipei", 8.0, &[(25.03, 121.57)]),
+    zone("Asia/Manila", 8.0, &[(14.60, 120.98)]),
+    zone("Asia/Singapore", 8.0, &[(1.35, 103.82)]),
+    zone("Australia/Perth", 8.0, &[(-31.95, 115.86)]),
+    zone("Asia/Yakutsk", 9.0, &[(62.03, 129.73)]),
+    zone("Asia/Seoul", 9.0, &[(37.57, 126.98)]),
+    zone("Asia/Tokyo", 9.0, &[(35.68, 139.69), (43.06, 141.35), (33.59, 130.40)]),
+    zone("Australia/Darwin", 9.5, &[(-12.46, 130.84)]),
+    zone("Australia/Adelaide", 9.5, &[(-34.93, 138.60)]),
+    zone("Asia/Vladivostok", 10.0, &[(43.12, 131.89)]),
+    zone("Australia/Brisbane", 10.0, &[(-27.47, 153.03)]),
+    zone("Australia/Sydney", 10.0, &[(-33.87, 151.21), (-31.27, 149.06), (-35.28, 149.13)]),
+    zone("Australia/Melbourne", 10.0, &[(-37.81, 144.96)]),
+    zone("Australia/Hobart", 10.0, &[(-42.88, 147.33)]),
+    zone("Asia/Kamchatka", 12.0, &[(53.02, 158.65)]),
+    zone("Pacific/Fiji", 12.0, &[(-18.14, 178.44)]),
+    zone("Pacific/Auckland", 12.0, &[(-36.85, 174.76), (-43.53, 172.64), (-43.99, 170.47)]),
+    // Americas
+    zone("Pacific/Tahiti", -10.0, &[(-17.53, -149.57)]),
+    zone("Pacific/Honolulu", -10.0, &[(21.31, -157.86), (19.72, -155.08), (19.82, -155.47)]),
+    zone("America/Anchorage", -9.0, &[(61.22, -149.90), (64.84, -147.72)]),
+    zone("America/Vancouver", -8.0, &[(49.28, -123.12)]),
+    zone("America/Los_Angeles", -8.0, &[(34.05, -118.24), (37.77, -122.42), (47.61, -122.33), (45.52, -122.68),
+                                         (36.17, -115.14), (32.72, -117.16), (37.34, -121.64), (32.79, -115.56),
+                                         (33.61, -114.60)]),
+    zone("America/Tijuana", -8.0, &[(32.51, -117.04), (31.05, -115.46)]),
+    zone("America/Edmonton", -7.0, &[(53.55, -113.49), (51.05, -114.07)]),
+    zone("America/Boise", -7.0, &[(43.62, -116.20)]),
+    zone("America/Phoenix", -7.0, &[(33.45, -112.07), (32.22, -110.97), (35.20, -111.65), (31.96, -111.60),
+                                     (32.69, -114.62), (34.15, -114.29)]),
+    zone("America/Denver", -7.0, &[(39.74, -104.99), (40.76, -111.89), (35.08, -106.65), (31.76, -106.49),
+                                    (32.78, -105.82)]),
+    zone("America/Regina", -6.0, &[(50.45, -104.61)]),
+    zone("America/Winnipeg", -6.0, &[(49.90, -97.14)]),
+    zone("America/Chicago", -6.0, &[(41.88, -87.63), (32.78, -96.80), (29.76, -95.37), (44.98, -93.27),
+                                     (39.10, -94.58), (29.95, -90.07), (30.67, -104.02)]),
+    zone("America/Mexico_City", -6.0, &[(19.43, -99.13), (20.67, -103.35), (25.69, -100.32)]),
+    zone("America/Costa_Rica", -6.0, &[(9.93, -84.08)]),
+    zone("America/Toronto", -5.0, &[(43.65, -79.38), (45.50, -73.57), (45.42, -75.70)]),
+    zone("America/New_York", -5.0, &[(40.71, -74.01), (42.36, -71.06), (33.75, -84.39), (25.76, -80.19),
+                                      (38.91, -77.04), (42.33, -83.05)]),
+    zone("America/Havana", -5.0, &[(23.11, -82.37)]),
+    zone("America/Panama", -5.0, &[(8.98, -79.52)]),
+    zone("America/Bogota", -5.0, &[(4.71, -74.07)]),
+    zone("America/Lima", -5.0, &[(-12.05, -77.04)]),
+    zone("America/Halifax", -4.0, &[(44.65, -63.58)]),
+    zone("America/Puerto_Rico", -4.0, &[(18.47, -66.11), (18.34, -66.75)]),
+    zone("America/Caracas", -4.0, &[(10.48, -66.90)]),
+    zone("America/Manaus", -4.0, &[(-3.12, -60.02)]),
+    zone("America/Cuiaba", -4.0, &[(-15.60, -56.10)]),
+    zone("America/La_Paz", -4.0, &[(-16.49, -68.12)]),
+    zone("America/Santiago", -4.0, &[(-33.45, -70.67), (-29.90, -71.25), (-23.65, -70.40), (-24.63, -70.40),
+                                      (-41.47, -72.94), (-30.17, -70.81)]),
+    zone("America/St_Johns", -3.5, &[(47.56, -52.71)]),
+    zone("America/Sao_Paulo", -3.0, &[(-23.55, -46.63), (-22.91, -43.17), (-15.79, -47.88), (-19.92, -43.94),
+                                       (-25.43, -49.27), (-30.03, -51.23), (-22.53, -45.58)]),
+    zone("America/Bahia", -3.0, &[(-12.97, -38.50)]),
+    zone("America/Fortaleza", -3.0, &[(-3.73, -38.53)]),
+    zone("America/Belem", -3.0, &[(-1.46, -48.49)]),
+    zone("America/Montevideo", -3.0, &[(-34.90, -56.16)]),
+    zone("America/Argentina/Buenos_Aires", -3.0, &[(-34.60, -58.38)]),
+    zone("America/Argentina/Cordoba", -3.0, &[(-31.42, -64.18)]),
+    zone("America/Argentina/Mendoza", -3.0, &[(-32.89, -68.84)]),
+    zone("America/Argentina/San_Juan", -3.0, &[(-31.54, -68.54)]),
+    zone("America/Noronha", -2.0, &[(-3.85, -32.42)]),
+];
+
+// Great circle distance in km between two points in degrees
+fn distance(latitude1: f64, longitude1: f64, latitude2: f64, longitude2: f64) -> f64 {
+    let (phi1, phi2) = (latitude1.to_radians(), latitude2.to_radians());
+    let d_phi = phi2 - phi1;
+    let d_lambda = (longitude2 - longitude1).to_radians();
+    let a = (d_phi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (d_lambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS * a.sqrt().asin()
+}
+
+/// Zone of the tz database name, ignoring case
+pub fn find_zone(name: &str) -> Option<&'static Zone> {
+    let name = name.trim();
+    ZONES.iter().find(|zone| zone.name.eq_ignore_ascii_case(name))
+}
+
+/// Guess of the zone of a location in degrees: the zone of the nearest embedded city, None when
+/// there is none within 1500 km (open ocean, Antarctica or a region without embedded cities). Near a
+/// border the nearest city can be on the other side, the guess is to be confirmed by the user.
+pub fn nearest_city_zone(latitude: f64, longitude: f64) -> Option<&'static Zone> {
+    ZONES
+        .iter()
+        .flat_map(|zone| zone.points.iter().map(move |&(lat, lon)| (zone, distance(latitude, longitude, lat, lon))))
+        .filter(|(_, distance)| *distance <= MAX_CITY_DISTANCE)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(zone, _)| zone)
+}
+
+/// ZoneMigration struct
+///
+/// Named timezone guessed for a saved site that only has a numeric offset.
+///
+/// # Attributes
+///
+/// * `index` - Position of the site in the list
+/// * `zone` - Zone of the city nearest to the site
+/// * `offset` - Offset saved with the site, different from the zone one when it was wrong
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ZoneMigration {
+    pub index: usize,
+    pub zone: &'static Zone,
+    pub offset: f64,
+}
+
+impl ZoneMigration {
+    /// Line for the confirmation, e.g. "Paris: UTC+0 to Europe/Paris (UTC+1)"
+    pub fn describe(&self, site: &Observer) -> String {
+        format!("{}: UTC{:+} to {} (UTC{:+})", site.name.as_deref().unwrap_or("(no name)"), self.offset,
+                self.zone.name, self.zone.offset)
+    }
+}
+
+/// Sites without a named timezone and the zone of the city nearest to each, sites with no city
+/// near are left out
+pub fn zone_migrations(sites: &[Observer]) -> Vec<ZoneMigration> {
+    sites
+        .iter()
+        .enumerate()
+        .filter(|(_, site)| site.zone.is_none())
+        .filter_map(|(index, site)| {
+            nearest_city_zone(site.latitude, site.longitude)
+                .map(|zone| ZoneMigration { index, zone, offset: site.timezone })
+        })
+        .collect()
+}
+
+/// Sets the guessed zones and their standard offsets on the sites
+pub fn migrate_zones(sites: &mut [Observer], migrations: &[ZoneMigration]) {
+    for migration in migrations {
+        if let Some(site) = sites.get_mut(migration.index) {
+            site.zone = Some(migration.zone.name.to_string());
+            site.timezone = migration.zone.offset;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::application::observer::Observer;
+    use crate::application::timezones::{find_zone, migrate_zones, nearest_city_zone, zone_migrations, ZONES};
+
+    #[test]
+    fn test_nearest_city_zone() {
+        let name = |latitude, longitude| nearest_city_zone(latitude, longitude).map(|zone| zone.name);
+        assert_eq!(name(48.8566, 2.3522), Some("Europe/Paris"));
+        // Brest is about as far from London as from Paris, its own point decides
+        assert_eq!(name(48.39, -4.49), Some("Europe/Paris"));
+        assert_eq!(name(51.4769, -0.0005), Some("Europe/London"));
+        assert_eq!(name(-23.1, -46.5), Some("America/Sao_Paulo"));
+        assert_eq!(name(-24.6272, -70.4042), Some("America/Santiago"));
+        assert_eq!(name(30.6714, -104.0225), Some("America/Chicago"));
+        assert_eq!(name(31.9583, -111.5967), Some("America/Phoenix"));
+        assert_eq!(name(19.8207, -155.4681), Some("Pacific/Honolulu"));
+        // middle of the Pacific and Antarctica
+        assert_eq!(name(-40.0, -130.0), None);
+        assert_eq!(name(-80.0, 0.0), None);
+    }
+
+    #[test]
+    fn test_nearest_city_zone_border() {
+        let name = |latitude, longitude| nearest_city_zone(latitude, longitude).map(|zone| zone.name);
+        // towns a few km from a border where the offset changes, none of them an embedded city
+        assert_eq!(name(38.88, -6.97), Some("Europe/Madrid")); // Badajoz
+        assert_eq!(name(38.88, -7.16), Some("Europe/Lisbon")); // Elvas
+        assert_eq!(name(32.60, -114.71), Some("America/Phoenix")); // Somerton, Arizona
+        assert_eq!(name(32.98, -115.53), Some("America/Los_Angeles")); // Brawley, California
+        assert_eq!(name(34.48, -114.32), Some("America/Phoenix")); // Lake Havasu City
+        assert_eq!(name(53.40, 23.50), Some("Europe/Warsaw")); // Sokolka
+        assert_eq!(name(52.21, 24.36), Some("Europe/Minsk")); // Kobryn
+        // the nearest city is across the border: a guess, not a boundary lookup
+        assert_eq!(name(42.05, -8.64), Some("Europe/Lisbon")); // Tui, Spain, facing Valenca
+        assert_eq!(name(52.08, 23.61), Some("Europe/Minsk")); // Terespol, Poland, facing Brest
+        assert_eq!(name(34.85, -114.61), Some("America/Phoenix")); // Needles, California
+        // Kehl, Germany, facing Strasbourg: another name, the same offset
+        assert_eq!(nearest_city_zone(48.57, 7.82).map(|zone| (zone.name, zone.offset)), Some(("Europe/Paris", 1.0)));
+    }
+
+    #[test]
+    fn test_find_zone() {
+        assert_eq!(find_zone("europe/paris").map(|zone| zone.offset), Some(1.0));
+        assert_eq!(find_zone(" Asia/Kolkata ").map(|zone| zone.offset), Some(5.5));
+        assert_eq!(find_zone("Mars/Olympus_Mons"), None);
+        // every zone has points and a name of the tz database
+        for zone in ZONES {
+            assert!(!zone.points.is_empty() && zone.name.contains('/'), "{}", zone.name);
+            assert_eq!(ZONES.iter().filter(|z| z.name == zone.name).count(), 1, "{}", zone.name);
+        }
+    }
+
+    #[test]
+    fn test_zone_migrations() {
+        // imported with the timezone estimated from the longitude, Paris was put at UTC+0
+        let mut sites = vec![
+            Observer::location(Some("Paris".to_string()), "48.8566", "2.3522", 35, "0"),
+            Observer::location(Some("Piracaia".to_string()), "-23.1", "-46.5", 780, "-3"),
+            Observer::location(Some("Ocean".to_string()), "-40", "-130", 0, "-9"),
+        ];
+        sites[1].zone = Some("America/Sao_Paulo".to_string());
+
+        let migrations = zone_migrations(&sites);
+        assert_eq!(migrations.len(), 1);
+        assert_eq!(migrations[0].describe(&sites[0]), "Paris: UTC+0 to Europe/Paris (UTC+1)");
+        migrate_zones(&mut sites, &migrations);
+        assert_eq!((sites[0].zone.as_deref(), sites[0].timezone), (Some("Europe/Paris"), 1.0));
+        assert_eq!(sites[2].zone, None);
+        assert!(zone_migrations(&sites).is_empty());
+    }
+}
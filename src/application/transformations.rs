@@ -46,16 +46,28 @@
 #![allow(dead_code, unused_variables)]
 
 use libm::atan2;
-use crate::application::time::Time;
-use crate::utils::utils::{constrain_360, cosd, sind};
+use crate::application::time::{gst_from_jd, Time};
+use crate::utils::utils::{constrain_360, cosd, sind, tand};
+
+// North galactic pole (J2000 ra, dec) and galactic longitude of the north celestial pole, degrees
+const GALACTIC_POLE_RA: f64 = 192.859_48;
+const GALACTIC_POLE_DEC: f64 = 27.128_25;
+const GALACTIC_NCP_LONGITUDE: f64 = 122.931_92;
+
+// Mean obliquity of the ecliptic at J2000.0 in degrees, for J2000 coordinates
+pub const J2000_OBLIQUITY: f64 = 23.439_291_1;
+
+// Rotation of the Earth in degrees per minute of time, relative to the stars
+const SIDEREAL_RATE: f64 = 360.0 / 1_436.068_176;
 
 // in degrees
+#[deprecated(note = "use hour_angle_jd, the time as a JD keeps its fraction of a second")]
 pub fn hour_angle(lon: f64, ra: f64, y: i64, m: u64, d: u64, h: u64, min: u64, s: u64) -> f64 {
-    let date = Time::new(y, m, d, h, min, s);
-    constrain_360(date.to_gst() + lon - ra)
+    hour_angle_jd(lon, ra, Time::new(y, m, d, h, min, s).to_jd())
 }
 
 // azimuth reckoned from north
+#[deprecated(note = "use equatorial_to_altaz_jd or equatorial_to_altaz_time")]
 pub fn equatorial_to_altaz(
     lat: f64,
     lon: f64,
@@ -68,7 +80,25 @@ pub fn equatorial_to_altaz(
     min: u64,
     s: u64,
 ) -> (f64, f64) {
-    let ha = hour_angle(lon, ra, y, m, d, h, min, s);
+    equatorial_to_altaz_time(lat, lon, ra, dec, &Time::new(y, m, d, h, min, s))
+}
+
+// in degrees, for a JD(UTC) with its fraction of a second, without going through the calendar
+pub fn hour_angle_jd(lon: f64, ra: f64, jd: f64) -> f64 {
+    constrain_360(gst_from_jd(jd) + lon - ra)
+}
+
+// azimuth reckoned from north, for a JD(UTC), used where positions are computed for many JDs
+pub fn equatorial_to_altaz_jd(lat: f64, lon: f64, ra: f64, dec: f64, jd: f64) -> (f64, f64) {
+    altaz_from_hour_angle(lat, hour_angle_jd(lon, ra, jd), dec)
+}
+
+// azimuth reckoned from north, for a Time (UTC)
+pub fn equatorial_to_altaz_time(lat: f64, lon: f64, ra: f64, dec: f64, time: &Time) -> (f64, f64) {
+    equatorial_to_altaz_jd(lat, lon, ra, dec, time.to_jd())
+}
+
+fn altaz_from_hour_angle(lat: f64, ha: f64, dec: f64) -> (f64, f64) {
     let x = -cosd(ha) * cosd(dec) * sind(lat) + sind(dec) * cosd(lat);
     let y = -sind(ha) * cosd(dec);
     let z = cosd(ha) * cosd(dec) * cosd(lat) + sind(dec) * sind(lat);
@@ -80,3 +110,174 @@ pub fn equatorial_to_altaz(
     (alt, az)
 }
 
+
+// angular distance between two equatorial positions, all values in degrees
+pub fn angular_separation(ra1: f64, dec1: f64, ra2: f64, dec2: f64) -> f64 {
+    let cos_sep = sind(dec1) * sind(dec2) + cosd(dec1) * cosd(dec2) * cosd(ra1 - ra2);
+    cos_sep.clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+// J2000 (ra, dec) to galactic longitude and latitude (l, b), all values in degrees
+pub fn equatorial_to_galactic(ra: f64, dec: f64) -> (f64, f64) {
+    let sin_b = sind(dec) * sind(GALACTIC_POLE_DEC)
+        + cosd(dec) * cosd(GALACTIC_POLE_DEC) * cosd(ra - GALACTIC_POLE_RA);
+    let y = cosd(dec) * sind(ra - GALACTIC_POLE_RA);
+    let x = sind(dec) * cosd(GALACTIC_POLE_DEC) - cosd(dec) * sind(GALACTIC_POLE_DEC) * cosd(ra - GALACTIC_POLE_RA);
+    let l = constrain_360(GALACTIC_NCP_LONGITUDE - atan2(y, x).to_degrees());
+    (l, sin_b.clamp(-1.0, 1.0).asin().to_degrees())
+}
+
+// Galactic (l, b) to J2000 (ra, dec), all values in degrees
+pub fn galactic_to_equatorial(l: f64, b: f64) -> (f64, f64) {
+    let sin_dec = sind(b) * sind(GALACTIC_POLE_DEC)
+        + cosd(b) * cosd(GALACTIC_POLE_DEC) * cosd(GALACTIC_NCP_LONGITUDE - l);
+    let y = cosd(b) * sind(GALACTIC_NCP_LONGITUDE - l);
+    let x = sind(b) * cosd(GALACTIC_POLE_DEC) - cosd(b) * sind(GALACTIC_POLE_DEC) * cosd(GALACTIC_NCP_LONGITUDE - l);
+    let ra = constrain_360(GALACTIC_POLE_RA + atan2(y, x).to_degrees());
+    (ra, sin_dec.clamp(-1.0, 1.0).asin().to_degrees())
+}
+
+// (ra, dec) to ecliptic longitude and latitude for the obliquity of the same equinox, e.g.
+// J2000_OBLIQUITY for J2000 coordinates, all values in degrees
+// (Meeus, Astronomical Algorithms, eq. 13.1 and 13.2)
+pub fn equatorial_to_ecliptic(ra: f64, dec: f64, obliquity: f64) -> (f64, f64) {
+    let lon = atan2(sind(ra) * cosd(obliquity) + tand(dec) * sind(obliquity), cosd(ra));
+    let sin_lat = sind(dec) * cosd(obliquity) - cosd(dec) * sind(obliquity) * sind(ra);
+    (constrain_360(lon.to_degrees()), sin_lat.clamp(-1.0, 1.0).asin().to_degrees())
+}
+
+// Ecliptic (lon, lat) to (ra, dec) for the obliquity of the same equinox, all values in degrees
+// (Meeus, Astronomical Algorithms, eq. 13.3 and 13.4)
+pub fn ecliptic_to_equatorial(lon: f64, lat: f64, obliquity: f64) -> (f64, f64) {
+    let ra = atan2(sind(lon) * cosd(obliquity) - tand(lat) * sind(obliquity), cosd(lon));
+    let sin_dec = sind(lat) * cosd(obliquity) + cosd(lat) * sind(obliquity) * sind(lon);
+    (constrain_360(ra.to_degrees()), sin_dec.clamp(-1.0, 1.0).asin().to_degrees())
+}
+
+// Angle in degrees (0 - 90) between the ecliptic and the horizon for a latitude and a local
+// sidereal time in degrees, the same at the rising and the setting point of the ecliptic
+// (Meeus, Astronomical Algorithms, eq. 24.3)
+pub fn ecliptic_horizon_angle(lat: f64, lst: f64, obliquity: f64) -> f64 {
+    let cos_i = cosd(obliquity) * sind(lat) - sind(obliquity) * cosd(lat) * sind(lst);
+    let angle = cos_i.clamp(-1.0, 1.0).acos().to_degrees();
+    if angle > 90.0 { 180.0 - angle } else { angle }
+}
+
+// Parallactic angle in degrees (-180 - 180) for a latitude, hour angle and declination in
+// degrees, negative east of the meridian and positive west of it (Meeus, Astronomical
+// Algorithms, eq. 14.1)
+pub fn parallactic_angle(lat: f64, ha: f64, dec: f64) -> f64 {
+    atan2(sind(ha), tand(lat) * cosd(dec) - sind(dec) * cosd(ha)).to_degrees()
+}
+
+// Rate of change of the parallactic angle in degrees per minute for a latitude, altitude and
+// azimuth (from north) in degrees, the rotation of the field seen by an alt-az mount. Grows
+// without bound towards the zenith.
+pub fn field_rotation_rate(lat: f64, alt: f64, az: f64) -> f64 {
+    -SIDEREAL_RATE * cosd(lat) * cosd(az) / cosd(alt)
+}
+
+// J2000 (ra, dec) precessed to the equinox of a JD, all values in degrees
+// (Meeus, Astronomical Algorithms, eq. 21.3 and 21.4)
+pub fn precess_from_j2000(ra: f64, dec: f64, jd: f64) -> (f64, f64) {
+    let t = (jd - 2_451_545.0) / 36_525.0;
+    let zeta = (2_306.218_1 * t + 0.301_88 * t * t + 0.017_998 * t * t * t) / 3_600.0;
+    let z = (2_306.218_1 * t + 1.094_68 * t * t + 0.018_203 * t * t * t) / 3_600.0;
+    let theta = (2_004.310_9 * t - 0.426_65 * t * t - 0.041_833 * t * t * t) / 3_600.0;
+
+    let a = cosd(dec) * sind(ra + zeta);
+    let b = cosd(theta) * cosd(dec) * cosd(ra + zeta) - sind(theta) * sind(dec);
+    let c = sind(theta) * cosd(dec) * cosd(ra + zeta) + cosd(theta) * sind(dec);
+
+    (constrain_360(atan2(a, b).to_degrees() + z), c.clamp(-1.0, 1.0).asin().to_degrees())
+}
+
+// Topocentric right ascension and declination (Meeus, Astronomical Algorithms, ch. 40).
+//
+// ra, dec : geocentric equatorial coordinates (degrees)
+// parallax : equatorial horizontal parallax of the body (degrees)
+// gst : Greenwich sidereal time (degrees)
+//
+// Returns the (ra, dec) seen from the observer, in degrees.
+pub fn topocentric_equatorial(
+    lat: f64,
+    lon: f64,
+    elevation: f64,
+    ra: f64,
+    dec: f64,
+    parallax: f64,
+    gst: f64,
+) -> (f64, f64) {
+    // observer's geocentric position (Earth's flattening 1/298.257)
+    let b_over_a = 0.996_647_19;
+    let u = atan2(b_over_a * sind(lat), cosd(lat));
+    let rho_sin_phi = b_over_a * u.sin() + elevation / 6_378_140.0 * sind(lat);
+    let rho_cos_phi = u.cos() + elevation / 6_378_140.0 * cosd(lat);
+
+    let ha = gst + lon - ra;
+    let denominator = cosd(dec) - rho_cos_phi * sind(parallax) * cosd(ha);
+    let delta_ra = atan2(-rho_cos_phi * sind(parallax) * sind(ha), denominator);
+    let topo_dec = atan2(
+        (sind(dec) - rho_sin_phi * sind(parallax)) * delta_ra.cos(),
+        denominator,
+    );
+
+    (constrain_360(ra + delta_ra.to_degrees()), topo_dec.to_degrees())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_galactic() {
+        // galactic center, Sgr A* at ra 17h45m40.04s dec -29°00'28.2"
+        let (ra, dec) = galactic_to_equatorial(0.0, 0.0);
+        assert!((ra - 266.405).abs() < 0.01);
+        assert!((dec + 28.936).abs() < 0.01);
+        // north galactic pole
+        assert!((equatorial_to_galactic(GALACTIC_POLE_RA, GALACTIC_POLE_DEC).1 - 90.0).abs() < 1e-6);
+
+        // M31 at ra 10.6847 dec 41.2690 is at l 121.17 b -21.57
+        let (l, b) = equatorial_to_galactic(10.684_7, 41.269_0);
+        assert!((l - 121.17).abs() < 0.01);
+        assert!((b + 21.57).abs() < 0.01);
+        let (ra, dec) = galactic_to_equatorial(l, b);
+        assert!((ra - 10.684_7).abs() < 1e-6);
+        assert!((dec - 41.269_0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_ecliptic() {
+        // Meeus, Astronomical Algorithms, example 13.a: Pollux at ra 116.328942 dec 28.026183
+        // is at lon 113.215630 lat 6.684170
+        let (lon, lat) = equatorial_to_ecliptic(116.328_942, 28.026_183, J2000_OBLIQUITY);
+        assert!((lon - 113.215_630).abs() < 1e-5);
+        assert!((lat - 6.684_170).abs() < 1e-5);
+        let (ra, dec) = ecliptic_to_equatorial(lon, lat, J2000_OBLIQUITY);
+        assert!((ra - 116.328_942).abs() < 1e-6);
+        assert!((dec - 28.026_183).abs() < 1e-6);
+
+        // Meeus, Astronomical Algorithms, example 24.a: latitude 51°, sidereal time 5h, I = 62°
+        assert!((ecliptic_horizon_angle(51.0, 75.0, 23.44) - 62.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_parallactic_angle() {
+        // on the meridian south of the zenith the angle is 0, 180 north of it
+        assert!(parallactic_angle(40.0, 0.0, 10.0).abs() < 1e-9);
+        assert!((parallactic_angle(40.0, 0.0, 60.0).abs() - 180.0).abs() < 1e-9);
+        assert!(parallactic_angle(40.0, 330.0, 10.0) < 0.0);
+        assert!(parallactic_angle(40.0, 30.0, 10.0) > 0.0);
+
+        // the field rotation rate is the rate of change of the parallactic angle over a minute
+        let (lat, dec) = (-23.1, -30.0);
+        for ha in [300.0, 340.0, 10.0, 45.0] {
+            let q0 = parallactic_angle(lat, ha, dec);
+            let q1 = parallactic_angle(lat, ha + SIDEREAL_RATE, dec);
+            let (alt, az) = altaz_from_hour_angle(lat, ha + SIDEREAL_RATE / 2.0, dec);
+            assert!((q1 - q0 - field_rotation_rate(lat, alt, az)).abs() < 1e-4,
+                    "{} {} {}", ha, q1 - q0, field_rotation_rate(lat, alt, az));
+        }
+    }
+}
@@ -0,0 +1,168 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Undo and redo of the settings edited in the dialogs. Each edit replaces a whole settings
+// section and keeps the previous value, so undoing is swapping the section back.
+
+use crate::application::application::Application;
+use crate::application::constraint::Constraints;
+use crate::application::environment::Environment;
+use crate::application::equipment::Equipment;
+use crate::application::observer::Observer;
+use crate::application::target::Target;
+
+// Edits kept for undo, the oldest ones are dropped first
+pub const MAX_UNDO: usize = 50;
+
+/// Setting enum
+///
+/// Settings section replaced by an edit, with the value it is set to
+#[derive(Debug, Clone, PartialEq)]
+pub enum Setting {
+    Observer(Observer),
+    Constraints(Constraints),
+    Environment(Environment),
+    Targets(Vec<Target>),
+    Equipment(Equipment),
+}
+
+impl Setting {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Setting::Observer(_) => "observatory",
+            Setting::Constraints(_) => "constraints",
+            Setting::Environment(_) => "environment",
+            Setting::Targets(_) => "targets",
+            Setting::Equipment(_) => "equipment",
+        }
+    }
+}
+
+/// UndoStack struct
+///
+/// # Attributes
+///
+/// * `undo` - Values replaced by the edits, most recent last
+/// * `redo` - Values replaced by undoing, cleared by a new edit
+#[derive(Debug, Clone, Default)]
+pub struct UndoStack {
+    undo: Vec<Setting>,
+    redo: Vec<Setting>,
+}
+
+impl UndoStack {
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+impl Application {
+    // Sets a section and returns its previous value
+    fn swap_setting(&mut self, setting: Setting) -> Setting {
+        match setting {
+            Setting::Observer(observer) =>
+                Setting::Observer(std::mem::replace(&mut self.observer, observer)),
+            Setting::Constraints(constraints) =>
+                Setting::Constraints(std::mem::replace(&mut self.constraints, constraints)),
+            Setting::Environment(environment) =>
+                Setting::Environment(std::mem::replace(&mut self.environment, environment)),
+            Setting::Targets(targets) =>
+                Setting::Targets(std::mem::replace(&mut self.targets, targets)),
+            Setting::Equipment(equipment) =>
+                Setting::Equipment(std::mem::replace(&mut self.equipment, equipment)),
+        }
+    }
+
+    /// Applies an edit so it can be undone, edits that change nothing are not recorded
+    pub fn apply_setting(&mut self, setting: Setting) {
+        let previous = self.swap_setting(setting.clone());
+        if previous == setting {
+            return;
+        }
+        self.history.undo.push(previous);
+        if self.history.undo.len() > MAX_UNDO {
+            self.history.undo.remove(0);
+        }
+        self.history.redo.clear();
+    }
+
+    /// Reverts the last edit, returns the name of the section or None when there is nothing to undo
+    pub fn undo(&mut self) -> Option<&'static str> {
+        let setting = self.history.undo.pop()?;
+        let name = setting.name();
+        let current = self.swap_setting(setting);
+        self.history.redo.push(current);
+        Some(name)
+    }
+
+    /// Applies again the last undone edit, returns the name of the section or None when there is
+    /// nothing to redo
+    pub fn redo(&mut self) -> Option<&'static str> {
+        let setting = self.history.redo.pop()?;
+        let name = setting.name();
+        let current = self.swap_setting(setting);
+        self.history.undo.push(current);
+        Some(name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_undo_redo() {
+        let mut application = Application::default();
+        let original = application.observer.clone();
+        let mut observer = original.clone();
+        observer.latitude = -23.5;
+        application.apply_setting(Setting::Observer(observer.clone()));
+        application.apply_setting(Setting::Observer(observer.clone()));
+        let mut constraints = application.constraints.clone();
+        constraints.min_altitude = 30;
+        application.apply_setting(Setting::Constraints(constraints));
+
+        assert_eq!(application.undo(), Some("constraints"));
+        assert_eq!(application.constraints.min_altitude, 0);
+        assert_eq!(application.undo(), Some("observatory"));
+        assert_eq!(application.observer, original);
+        assert_eq!(application.undo(), None);
+        assert_eq!(application.redo(), Some("observatory"));
+        assert_eq!(application.observer, observer);
+
+        // A new edit drops what was undone
+        application.apply_setting(Setting::Environment(Environment { temperature: 5.0, ..Environment::default() }));
+        assert!(!application.history.can_redo());
+        assert_eq!(application.redo(), None);
+        assert_eq!(application.undo(), Some("environment"));
+        assert!(application.history.can_undo());
+    }
+}
@@ -0,0 +1,291 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+
+// Display units of the elevation, temperature and pressure. The observer and the environment stay
+// metric in the configurations and the computations, values are converted when they are shown
+// and when they are read back from the dialogs.
+
+use std::ops::RangeInclusive;
+use serde::{Deserialize, Serialize};
+use crate::application::config_check::{PRESSURE_RANGE, TEMPERATURE_RANGE};
+use crate::application::observer::{try_elevation_from_str, MAX_ELEVATION, MIN_ELEVATION};
+
+pub const METERS_PER_FOOT: f64 = 0.3048;
+pub const HPA_PER_INHG: f64 = 33.8639;
+
+pub fn feet_to_meters(feet: f64) -> f64 {
+    feet * METERS_PER_FOOT
+}
+
+pub fn meters_to_feet(meters: f64) -> f64 {
+    meters / METERS_PER_FOOT
+}
+
+pub fn fahrenheit_to_celsius(fahrenheit: f64) -> f64 {
+    (fahrenheit - 32.0) / 1.8
+}
+
+pub fn celsius_to_fahrenheit(celsius: f64) -> f64 {
+    celsius * 1.8 + 32.0
+}
+
+pub fn inhg_to_hpa(inhg: f64) -> f64 {
+    inhg * HPA_PER_INHG
+}
+
+pub fn hpa_to_inhg(hpa: f64) -> f64 {
+    hpa / HPA_PER_INHG
+}
+
+// Metric value of a number typed in a display unit rounded to `steps` per unit, the range is
+// checked on the metric value and shown in the display unit
+#[allow(clippy::too_many_arguments)]
+fn metric_step_from_str(input: &str, what: &str, symbol: &str, decimals: usize, to_metric: fn(f64) -> f64,
+                        from_metric: fn(f64) -> f64, range: RangeInclusive<i64>, steps: f64) -> Result<f64, String> {
+    let input_trimmed = input.trim();
+    let value = input_trimmed
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| format!("'{}' is not {}, use {}", input_trimmed, what, symbol))?;
+    let metric = (to_metric(value) * steps).round() / steps;
+    if !(*range.start() as f64..=*range.end() as f64).contains(&metric) {
+        return Err(format!("{} is out of range, from {:.*} to {:.*} {}", input_trimmed,
+                           decimals, from_metric(*range.start() as f64),
+                           decimals, from_metric(*range.end() as f64), symbol));
+    }
+    Ok(metric)
+}
+
+// Whole metric value of a number typed in a display unit, see `metric_step_from_str`
+fn metric_from_str(input: &str, what: &str, symbol: &str, decimals: usize, to_metric: fn(f64) -> f64,
+                   from_metric: fn(f64) -> f64, range: RangeInclusive<i64>) -> Result<i64, String> {
+    metric_step_from_str(input, what, symbol, decimals, to_metric, from_metric, range, 1.0).map(|metric| metric as i64)
+}
+
+// Temperatures are kept in tenths of °C, fine enough for a whole °F to read back the same
+const TEMPERATURE_STEPS: f64 = 10.0;
+
+/// Unit of the observatory elevation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ElevationUnit {
+    #[default]
+    Meters,
+    Feet,
+}
+
+/// Elevation units in the order of the preferences choice
+pub const ELEVATION_UNITS: [ElevationUnit; 2] = [ElevationUnit::Meters, ElevationUnit::Feet];
+
+impl ElevationUnit {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            ElevationUnit::Meters => "m",
+            ElevationUnit::Feet => "ft",
+        }
+    }
+
+    /// Elevation in meters shown in the unit, in whole units
+    pub fn format(&self, meters: i64) -> String {
+        match self {
+            ElevationUnit::Meters => meters.to_string(),
+            ElevationUnit::Feet => format!("{:.0}", meters_to_feet(meters as f64)),
+        }
+    }
+
+    /// Elevation in whole meters of a text typed in the unit
+    pub fn parse(&self, input: &str) -> Result<i64, String> {
+        match self {
+            ElevationUnit::Meters => try_elevation_from_str(input),
+            ElevationUnit::Feet => metric_from_str(input, "an elevation", "ft", 0, feet_to_meters, meters_to_feet,
+                                                   MIN_ELEVATION..=MAX_ELEVATION),
+        }
+    }
+}
+
+/// Unit of the air temperature
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+/// Temperature units in the order of the preferences choice
+pub const TEMPERATURE_UNITS: [TemperatureUnit; 2] = [TemperatureUnit::Celsius, TemperatureUnit::Fahrenheit];
+
+impl TemperatureUnit {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            TemperatureUnit::Celsius => "°C",
+            TemperatureUnit::Fahrenheit => "°F",
+        }
+    }
+
+    /// Temperature in °C converted to the unit
+    pub fn celsius_to_unit(&self, celsius: f64) -> f64 {
+        match self {
+            TemperatureUnit::Celsius => celsius,
+            TemperatureUnit::Fahrenheit => celsius_to_fahrenheit(celsius),
+        }
+    }
+
+    /// Difference of two temperatures in °C converted to the unit, without the offset of the scale
+    pub fn difference_from_celsius(&self, difference: f64) -> f64 {
+        match self {
+            TemperatureUnit::Celsius => difference,
+            TemperatureUnit::Fahrenheit => difference * 1.8,
+        }
+    }
+
+    /// Temperature in °C shown in the unit, in whole degrees Fahrenheit and in °C with a decimal
+    /// when it has one
+    pub fn format(&self, celsius: f64) -> String {
+        match self {
+            TemperatureUnit::Celsius if celsius.fract().abs() >= 0.05 => format!("{:.1}", celsius),
+            // rounded first, "-0" otherwise for a value just below zero
+            _ => (self.celsius_to_unit(celsius).round() as i64).to_string(),
+        }
+    }
+
+    /// Temperature in °C of a text typed in the unit, in tenths of degree
+    pub fn parse(&self, input: &str) -> Result<f64, String> {
+        match self {
+            TemperatureUnit::Celsius => metric_step_from_str(input, "a temperature", "°C", 0, |c| c, |c| c,
+                                                             TEMPERATURE_RANGE, TEMPERATURE_STEPS),
+            TemperatureUnit::Fahrenheit => metric_step_from_str(input, "a temperature", "°F", 0, fahrenheit_to_celsius,
+                                                                celsius_to_fahrenheit, TEMPERATURE_RANGE,
+                                                                TEMPERATURE_STEPS),
+        }
+    }
+}
+
+/// Unit of the air pressure
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PressureUnit {
+    #[default]
+    Hectopascals,
+    InchesOfMercury,
+}
+
+/// Pressure units in the order of the preferences choice
+pub const PRESSURE_UNITS: [PressureUnit; 2] = [PressureUnit::Hectopascals, PressureUnit::InchesOfMercury];
+
+impl PressureUnit {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            PressureUnit::Hectopascals => "hPa",
+            PressureUnit::InchesOfMercury => "inHg",
+        }
+    }
+
+    /// Pressure in hPa shown in the unit, inches of mercury with two decimals
+    pub fn format(&self, hpa: i64) -> String {
+        match self {
+            PressureUnit::Hectopascals => hpa.to_string(),
+            PressureUnit::InchesOfMercury => format!("{:.2}", hpa_to_inhg(hpa as f64)),
+        }
+    }
+
+    /// Pressure in whole hPa of a text typed in the unit
+    pub fn parse(&self, input: &str) -> Result<i64, String> {
+        match self {
+            PressureUnit::Hectopascals => metric_from_str(input, "a pressure", "hPa", 0, |hpa| hpa, |hpa| hpa,
+                                                          PRESSURE_RANGE),
+            PressureUnit::InchesOfMercury => metric_from_str(input, "a pressure", "inHg", 2, inhg_to_hpa, hpa_to_inhg,
+                                                             PRESSURE_RANGE),
+        }
+    }
+}
+
+/// Units struct
+///
+/// Units the elevation, temperature and pressure are shown and typed in.
+///
+/// # Attributes
+///
+/// * `elevation` - Unit of the observatory elevation
+/// * `temperature` - Unit of the air temperature and the dew point
+/// * `pressure` - Unit of the air pressure
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Units {
+    #[serde(default)]
+    pub elevation: ElevationUnit,
+    #[serde(default)]
+    pub temperature: TemperatureUnit,
+    #[serde(default)]
+    pub pressure: PressureUnit,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        assert!((feet_to_meters(1000.0) - 304.8).abs() < 1e-9);
+        assert!((meters_to_feet(feet_to_meters(5280.0)) - 5280.0).abs() < 1e-9);
+        assert!((celsius_to_fahrenheit(-40.0) + 40.0).abs() < 1e-9);
+        assert!((fahrenheit_to_celsius(212.0) - 100.0).abs() < 1e-9);
+        assert!((hpa_to_inhg(1013.25) - 29.92).abs() < 0.005);
+        assert!((inhg_to_hpa(hpa_to_inhg(1010.0)) - 1010.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_units_round_trip() {
+        // whole meters survive the conversion to feet and back
+        for meters in [0, 1, 760, 2635, MAX_ELEVATION] {
+            assert_eq!(ElevationUnit::Feet.parse(&ElevationUnit::Feet.format(meters)), Ok(meters));
+        }
+        assert_eq!(ElevationUnit::Feet.format(1000), "3281");
+        assert_eq!(ElevationUnit::Feet.parse("5280"), Ok(1609));
+        assert_eq!(ElevationUnit::Meters.parse("1609"), Ok(1609));
+        assert_eq!(ElevationUnit::Feet.parse("30000"), Err("30000 is out of range, from 0 to 29528 ft".to_string()));
+        assert_eq!(ElevationUnit::Feet.parse("high"), Err("'high' is not an elevation, use ft".to_string()));
+
+        // whole degrees Fahrenheit survive the conversion to tenths of °C and back
+        for fahrenheit in -76..=140 {
+            let celsius = TemperatureUnit::Fahrenheit.parse(&fahrenheit.to_string()).unwrap();
+            assert_eq!(TemperatureUnit::Fahrenheit.format(celsius), fahrenheit.to_string());
+        }
+        assert_eq!(TemperatureUnit::Fahrenheit.format(20.0), "68");
+        assert_eq!(TemperatureUnit::Fahrenheit.parse("50"), Ok(10.0));
+        assert_eq!(TemperatureUnit::Fahrenheit.parse("51"), Ok(10.6));
+        assert_eq!(TemperatureUnit::Celsius.parse(" 12 "), Ok(12.0));
+        assert_eq!(TemperatureUnit::Celsius.format(12.0), "12");
+        assert_eq!(TemperatureUnit::Celsius.format(10.6), "10.6");
+        assert_eq!(TemperatureUnit::Fahrenheit.parse("150"), Err("150 is out of range, from -76 to 140 °F".to_string()));
+        assert!((TemperatureUnit::Fahrenheit.difference_from_celsius(5.0) - 9.0).abs() < 1e-9);
+
+        for hpa in [0, 850, 1010, 1100] {
+            assert_eq!(PressureUnit::InchesOfMercury.parse(&PressureUnit::InchesOfMercury.format(hpa)), Ok(hpa));
+        }
+        assert_eq!(PressureUnit::InchesOfMercury.format(1013), "29.91");
+        assert_eq!(PressureUnit::InchesOfMercury.parse("29.92"), Ok(1013));
+        assert_eq!(PressureUnit::Hectopascals.parse("1200"), Err("1200 is out of range, from 0 to 1100 hPa".to_string()));
+        assert_eq!(PressureUnit::InchesOfMercury.parse("40"), Err("40 is out of range, from 0.00 to 32.48 inHg".to_string()));
+    }
+}
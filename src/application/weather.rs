@@ -0,0 +1,241 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Hourly weather forecast for the selected night. Providers are behind the ForecastProvider
+// trait, Open-Meteo (https://open-meteo.com) is queried over HTTPS without an API key. The
+// requests block until they are answered or time out, the windows make them from a worker thread.
+
+use std::time::Duration;
+use chrono::NaiveDateTime;
+use serde_json::Value;
+use crate::application::{
+    observer::Observer,
+    sun::sun_altitude,
+    time::Time,
+};
+use crate::utils::definers::APP_VERSION;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+const OPEN_METEO_URL: &str = "https://api.open-meteo.com/v1/forecast";
+
+/// HourlyForecast struct
+///
+/// Forecast for one hour.
+///
+/// # Attributes
+///
+/// * `jd` - Start of the hour (JD UTC)
+/// * `cloud_cover` - Total cloud cover in percent
+/// * `temperature` - Air temperature at 2 m in °C, None when not forecast
+/// * `humidity` - Relative humidity at 2 m in percent, None when not forecast
+#[derive(Debug, Clone, PartialEq)]
+pub struct HourlyForecast {
+    pub jd: f64,
+    pub cloud_cover: f64,
+    pub temperature: Option<f64>,
+    pub humidity: Option<f64>,
+}
+
+/// Source of hourly forecasts
+pub trait ForecastProvider {
+    fn name(&self) -> &str;
+
+    /// Hourly forecast between jd_start and jd_end (JD UTC)
+    fn hourly_forecast(&self, latitude: f64, longitude: f64, jd_start: f64,
+                       jd_end: f64) -> Result<Vec<HourlyForecast>, String>;
+}
+
+/// Open-Meteo forecast API, cloud cover, temperature and humidity
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OpenMeteo;
+
+impl ForecastProvider for OpenMeteo {
+    fn name(&self) -> &str {
+        "Open-Meteo"
+    }
+
+    fn hourly_forecast(&self, latitude: f64, longitude: f64, jd_start: f64,
+                       jd_end: f64) -> Result<Vec<HourlyForecast>, String> {
+        let url = format!(
+            "{}?latitude={:.4}&longitude={:.4}&hourly=cloud_cover,temperature_2m,relative_humidity_2m&timezone=GMT&start_date={}&end_date={}",
+            OPEN_METEO_URL,
+            latitude,
+            longitude,
+            Time::from_jd(jd_start).to_string(Some("yyyymmdd")),
+            Time::from_jd(jd_end).to_string(Some("yyyymmdd"))
+        );
+        let body = http_get(&url)?;
+        Ok(parse_open_meteo(&body)?
+            .into_iter()
+            .filter(|hour| hour.jd >= jd_start && hour.jd < jd_end)
+            .collect())
+    }
+}
+
+// Agent of the requests, a server that does not connect or answer in time fails the request
+// instead of blocking the caller
+fn http_agent() -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout_connect(CONNECT_TIMEOUT)
+        .timeout_read(HTTP_TIMEOUT)
+        .timeout_write(HTTP_TIMEOUT)
+        .user_agent(&format!("SkyCalc/{}", APP_VERSION))
+        .build()
+}
+
+// GET of an http or https URL, the response body is returned when the status is 200
+pub(crate) fn http_get(url: &str) -> Result<String, String> {
+    http_request("GET", url, None)
+}
+
+// Request with an optional form encoded body, redirects are followed and the response body is
+// returned when the status is 200
+pub(crate) fn http_request(method: &str, url: &str, form: Option<&[(&str, &str)]>) -> Result<String, String> {
+    let request = http_agent().request(method, url).set("Accept", "application/json");
+    let response = match form {
+        Some(form) => request.send_form(form),
+        None => request.call(),
+    };
+    match response {
+        Ok(response) if response.status() == 200 => response.into_string().map_err(|e| e.to_string()),
+        Ok(response) | Err(ureq::Error::Status(_, response)) => {
+            Err(format!("{} returned {} {}", response.get_url(), response.status(), response.status_text()))
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// Hourly cloud cover from an Open-Meteo response with times in GMT, with the temperature and
+// humidity of the hours that have them
+pub(crate) fn parse_open_meteo(json: &str) -> Result<Vec<HourlyForecast>, String> {
+    let value: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let hourly = value.get("hourly").ok_or("Missing hourly forecast")?;
+    let times = hourly.get("time").and_then(|t| t.as_array()).ok_or("Missing forecast times")?;
+    let clouds = hourly
+        .get("cloud_cover")
+        .and_then(|c| c.as_array())
+        .ok_or("Missing cloud cover")?;
+    let optional = |key: &str, i: usize| {
+        hourly.get(key).and_then(|values| values.as_array()).and_then(|values| values.get(i)?.as_f64())
+    };
+
+    let mut forecast: Vec<HourlyForecast> = Vec::new();
+    for (i, (time, cloud)) in times.iter().zip(clouds.iter()).enumerate() {
+        let (time, cloud_cover) = match (time.as_str(), cloud.as_f64()) {
+            (Some(time), Some(cloud_cover)) => (time, cloud_cover),
+            _ => continue, // hours without data
+        };
+        let utc = NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M").map_err(|e| e.to_string())?;
+        forecast.push(HourlyForecast {
+            jd: Time::from_utc(utc.and_utc()).to_jd(),
+            cloud_cover,
+            temperature: optional("temperature_2m", i),
+            humidity: optional("relative_humidity_2m", i),
+        });
+    }
+    Ok(forecast)
+}
+
+/// Mean cloud cover of the forecast hours in percent, None when there are none
+pub fn mean_cloud_cover(hours: &[HourlyForecast]) -> Option<f64> {
+    if hours.is_empty() {
+        return None;
+    }
+    Some(hours.iter().map(|hour| hour.cloud_cover).sum::<f64>() / hours.len() as f64)
+}
+
+/// Forecast for the hours of the selected night with the Sun below the horizon. The night goes
+/// from local noon to the next local noon.
+pub fn night_forecast(provider: &dyn ForecastProvider, observer: &Observer,
+                      time: &Time) -> Result<Vec<HourlyForecast>, String> {
+    let offset = observer.timezone / 24.0;
+    let night_start = (time.to_jd() + 0.5).floor() - offset;
+    let sun = sun_altitude(observer.latitude, observer.longitude);
+    let forecast = provider.hourly_forecast(
+        observer.latitude,
+        observer.longitude,
+        night_start,
+        night_start + 1.0,
+    )?;
+    Ok(forecast
+        .into_iter()
+        .filter(|hour| sun(hour.jd + 0.5 / 24.0) < -0.8333)
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::application::observer::Observer;
+    use crate::application::time::Time;
+    use crate::application::weather::{night_forecast, parse_open_meteo, ForecastProvider,
+                                      HourlyForecast};
+
+    // Same cloud cover for every hour of the requested window
+    struct Overcast;
+
+    impl ForecastProvider for Overcast {
+        fn name(&self) -> &str {
+            "Overcast"
+        }
+
+        fn hourly_forecast(&self, _latitude: f64, _longitude: f64, jd_start: f64,
+                           jd_end: f64) -> Result<Vec<HourlyForecast>, String> {
+            let hours = ((jd_end - jd_start) * 24.0).round() as usize;
+            Ok((0..hours)
+                .map(|h| HourlyForecast {
+                    jd: jd_start + h as f64 / 24.0,
+                    cloud_cover: 100.0,
+                    temperature: None,
+                    humidity: None,
+                })
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_parse_open_meteo() {
+        let json = r#"{"hourly":{"time":["2025-03-28T22:00","2025-03-28T23:00"],
+                       "cloud_cover":[35,null]}}"#;
+        let forecast = parse_open_meteo(json).unwrap();
+        assert_eq!(forecast.len(), 1);
+        assert_eq!(forecast[0].cloud_cover, 35.0);
+        assert_eq!((forecast[0].temperature, forecast[0].humidity), (None, None));
+
+        // temperature and humidity of the hours that have them
+        let json = r#"{"hourly":{"time":["2025-03-28T22:00","2025-03-28T23:00"],"cloud_cover":[35,40],
+                       "temperature_2m":[12.5,null],"relative_humidity_2m":[88,90]}}"#;
+        let forecast = parse_open_meteo(json).unwrap();
+        assert_eq!((forecast[0].temperature, forecast[0].humidity), (Some(12.5), Some(88.0)));
+        assert_eq!((forecast[1].temperature, forecast[1].humidity), (None, Some(90.0)));
+        assert!((forecast[0].jd - Time::new(2025, 3, 28, 22, 0, 0).to_jd()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_night_forecast() {
+        let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+        let time = Time::new(2025, 3, 28, 12, 0, 0);
+        let forecast = night_forecast(&Overcast, &observer, &time).unwrap();
+        // about 12 hours of night at the end of March
+        assert!(forecast.len() >= 11 && forecast.len() <= 13);
+    }
+}
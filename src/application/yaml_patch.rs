@@ -0,0 +1,244 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Updates the values of an existing YAML configuration in place, so comments, key order and the
+// formatting of the values that did not change are kept. The document is walked by indentation,
+// which covers the block style YAML of the configuration files.
+
+use serde_yaml::{Mapping, Value};
+
+// Lines of one `key: value` entry, the lines after the key line are its children or the rest of
+// a multi-line value
+struct Entry {
+    key: String,
+    start: usize,
+    end: usize,
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+// Blank lines and comments do not end a block
+fn is_content(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty() && !trimmed.starts_with('#')
+}
+
+fn is_sequence_item(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed == "-" || trimmed.starts_with("- ")
+}
+
+// Key and position of the colon of a `key: value` line, None for other lines
+fn split_key(line: &str) -> Option<(String, usize)> {
+    let offset = indent_of(line);
+    let trimmed = line.trim_start();
+    if is_sequence_item(trimmed) || !is_content(trimmed) {
+        return None;
+    }
+
+    // Quoted keys end at the closing quote, plain keys at the first ": "
+    let (key, after_key) = match trimmed.chars().next() {
+        Some(quote @ ('"' | '\'')) => {
+            let close = trimmed[1..].find(quote)? + 1;
+            (trimmed[1..close].to_string(), close + 1)
+        }
+        _ => {
+            let colon = trimmed
+                .char_indices()
+                .find(|&(i, c)| c == ':' && trimmed[i + 1..].chars().next().is_none_or(char::is_whitespace))?
+                .0;
+            (trimmed[..colon].trim_end().to_string(), colon)
+        }
+    };
+    if !trimmed[after_key..].trim_start().starts_with(':') {
+        return None;
+    }
+    let colon = after_key + trimmed[after_key..].find(':')?;
+    Some((key, offset + colon))
+}
+
+// Splits the text after the colon into the value and the comment, with the spaces before it
+fn split_comment(rest: &str) -> (&str, &str) {
+    let (mut single, mut double) = (false, false);
+    let mut previous_space = true;
+    for (i, c) in rest.char_indices() {
+        match c {
+            '\'' if !double => single = !single,
+            '"' if !single => double = !double,
+            '#' if !single && !double && previous_space => {
+                let value = rest[..i].trim_end();
+                return (value, &rest[value.len()..]);
+            }
+            _ => {}
+        }
+        previous_space = c.is_whitespace();
+    }
+    (rest.trim_end(), "")
+}
+
+// Entries of the block of lines at the given indentation
+fn entries(lines: &[&str], start: usize, end: usize, indent: usize) -> Vec<Entry> {
+    let mut result: Vec<Entry> = Vec::new();
+    let mut i = start;
+    while i < end {
+        let line = lines[i];
+        let key = match split_key(line) {
+            Some((key, _)) if indent_of(line) == indent => key,
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+
+        // Children are indented more, sequences may start at the same indentation as their key
+        let mut j = i + 1;
+        while j < end {
+            let next = lines[j];
+            if is_content(next) && indent_of(next) < indent
+                || is_content(next) && indent_of(next) == indent && !is_sequence_item(next) {
+                break;
+            }
+            j += 1;
+        }
+
+        // Comments and blank lines after the entry belong to the next one
+        while j > i + 1 && !is_content(lines[j - 1]) {
+            j -= 1;
+        }
+        result.push(Entry { key, start: i, end: j });
+        i = j;
+    }
+    result
+}
+
+// Block style text of a single entry
+fn entry_lines(key: &Value, value: &Value, indent: usize) -> Vec<String> {
+    let mut mapping = Mapping::new();
+    mapping.insert(key.clone(), value.clone());
+    serde_yaml::to_string(&mapping)
+        .unwrap_or_default()
+        .lines()
+        .map(|line| format!("{}{}", " ".repeat(indent), line))
+        .collect()
+}
+
+// Inline text of a scalar, None for collections and multi-line strings
+fn scalar_text(value: &Value) -> Option<String> {
+    if value.is_mapping() || value.is_sequence() {
+        return None;
+    }
+    let text = serde_yaml::to_string(value).ok()?;
+    let text = text.trim_end();
+    (!text.contains('\n')).then(|| text.to_string())
+}
+
+fn patch_entry(lines: &[&str], entry: &Entry, indent: usize, baseline: Option<&Value>,
+               updated: Option<&Value>, out: &mut Vec<String>) {
+    let keep = lines[entry.start..entry.end].iter().map(|line| line.to_string());
+    let updated = match (updated, baseline) {
+        // Written by this version but not any more, e.g. the selected date once it is not remembered
+        (None, Some(_)) => return,
+        // Not written by this version, e.g. sections of older formats, kept as they are
+        (None, None) => return out.extend(keep),
+        (Some(updated), _) if baseline == Some(updated) => return out.extend(keep),
+        (Some(updated), _) => updated,
+    };
+
+    let line = lines[entry.start];
+    let Some((_, colon)) = split_key(line) else {
+        return out.extend(keep);
+    };
+    let (value, comment) = split_comment(&line[colon + 1..]);
+    let children = (entry.start + 1..entry.end).find(|&i| is_content(lines[i]));
+
+    match (updated, children) {
+        // Nested mapping, only the values that changed are patched
+        (Value::Mapping(mapping), Some(first)) if value.is_empty() && !is_sequence_item(lines[first]) => {
+            out.push(line.to_string());
+            patch_block(lines, entry.start + 1, entry.end, indent_of(lines[first]),
+                        baseline.and_then(Value::as_mapping), mapping, out);
+        }
+        _ => match (scalar_text(updated), children) {
+            (Some(text), None) => out.push(format!("{}: {}{}", &line[..colon], text, comment)),
+            _ => out.extend(entry_lines(&Value::String(entry.key.clone()), updated, indent)),
+        },
+    }
+}
+
+fn patch_block(lines: &[&str], start: usize, end: usize, indent: usize, baseline: Option<&Mapping>,
+               updated: &Mapping, out: &mut Vec<String>) {
+    let mut position = start;
+    let mut seen: Vec<String> = Vec::new();
+    for entry in entries(lines, start, end, indent) {
+        out.extend(lines[position..entry.start].iter().map(|line| line.to_string()));
+        patch_entry(lines, &entry, indent, baseline.and_then(|b| b.get(entry.key.as_str())),
+                    updated.get(entry.key.as_str()), out);
+        position = entry.end;
+        seen.push(entry.key);
+    }
+
+    // New keys go after the last entry of the block
+    for (key, value) in updated {
+        if !key.as_str().is_some_and(|key| seen.iter().any(|s| s == key)) {
+            out.extend(entry_lines(key, value, indent));
+        }
+    }
+    out.extend(lines[position..end].iter().map(|line| line.to_string()));
+}
+
+/// Writes `updated` over the YAML document `original`. `baseline` is what `original` holds once
+/// loaded and saved again, values that are the same in both keep their original text.
+pub fn patch_yaml(original: &str, baseline: &Value, updated: &Value) -> String {
+    let (Some(baseline), Some(updated)) = (baseline.as_mapping(), updated.as_mapping()) else {
+        return serde_yaml::to_string(updated).unwrap_or_default();
+    };
+    let lines: Vec<&str> = original.lines().collect();
+    let mut out: Vec<String> = Vec::new();
+    patch_block(&lines, 0, lines.len(), 0, Some(baseline), updated, &mut out);
+    out.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_patch_yaml() {
+        let original = "# Site\nobserver:\n  name: Home # mine\n  latitude: 23d 06m S\n\n\
+                        time: 2024-12-11T12:00:00\ntargets:\n- name: M42\n  ra: 83.8\nothers:\n  output_dir: output\n";
+        let baseline: Value = serde_yaml::from_str(
+            "observer: {name: Home, latitude: -23.1}\ntime: 2024-12-11T12:00:00\ntargets: [{name: M42, ra: 83.8}]").unwrap();
+        let updated: Value = serde_yaml::from_str(
+            "observer: {name: 'Away: 2', latitude: -23.1, elevation: 780}\n\
+             targets: [{name: M42, ra: 83.8}, {name: M31, ra: 10.7}]\nconfig_version: 1").unwrap();
+
+        let patched = patch_yaml(original, &baseline, &updated);
+        assert!(patched.starts_with("# Site\nobserver:\n  name: 'Away: 2' # mine\n  latitude: 23d 06m S\n"));
+        assert!(patched.contains("  elevation: 780\n\ntargets:\n"));
+        assert!(patched.contains("others:\n  output_dir: output\nconfig_version: 1\n"));
+        let reloaded: Value = serde_yaml::from_str(&patched).unwrap();
+        assert_eq!(reloaded["targets"], updated["targets"]);
+        assert_eq!(reloaded["observer"]["name"], updated["observer"]["name"]);
+    }
+}
@@ -0,0 +1,17 @@
+// SkyCalc calculations without the interface, for automation scripts and other programs. The
+// scheduling queries answered by `skycalc schedule` are in `application::schedule`, e.g. the
+// next astronomical darkness at a site:
+//
+//   use skycalc::application::calculation::CalculationSettings;
+//   use skycalc::application::observer::Observer;
+//   use skycalc::application::schedule::darkness_schedule;
+//   use skycalc::application::sun::TwilightType;
+//   use skycalc::application::time::Time;
+//
+//   let observer = Observer::location(None, "-23.1", "-46.5", 780, "-3");
+//   let schedule = darkness_schedule(&observer, Time::now().to_jd(), TwilightType::AstronomicalTwilight,
+//                                    CalculationSettings::default());
+//   println!("{:?} {:?}", schedule.start, schedule.end);
+
+pub mod application;
+pub mod utils;
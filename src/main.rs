@@ -1,34 +1,64 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
-mod application;
 mod menu;
-mod utils;
 mod widgets;
 
-use crate::application::application::{load_from_yaml, save_to_yaml, Application};
-use fltk::{app, enums::Shortcut, menu::MenuBar, menu::MenuFlag, prelude::*, window::Window};
-use fltk_theme::{color_themes, ColorTheme, ThemeType, WidgetTheme};
+// The calculations are the library of the crate, shared with the `skycalc schedule` command
+use skycalc::{application, utils};
+
+use crate::application::application::Application;
+use crate::application::autosave::{autosave_path, Autosave};
+use crate::application::recent::RecentConfigs;
+use crate::application::preferences::{Appearance, THEMES, WIDGET_STYLES};
+use crate::application::session::{last_layout, update_layout, WindowGeometry};
+use fltk::{app, enums::Event, enums::Shortcut, menu::MenuBar, menu::MenuFlag, prelude::*, window::Window};
 use menu::about;
+use menu::window_manager::add_tool_menu;
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::io::Write;
 use std::rc::Rc;
-use utils::definers::{APP_TITLE, MENU_HEIGHT};
+use utils::definers::{APP_TITLE, MENU_HEIGHT, STATUS_BAR_HEIGHT};
+use utils::i18n::menu_path;
+use widgets::status_bar::StatusBar;
+use widgets::timeline::TimelineWidget;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // `skycalc schedule ...` answers automation scripts on stdout without opening a window
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some(application::schedule::SCHEDULE_COMMAND) {
+        std::process::exit(application::schedule::run(&args[1..]));
+    }
+
+    // the window still opens when the log file can not be written
+    if let Err(e) = application::logging::init_logging() {
+        eprintln!("Logging unavailable: {}", e);
+    }
+
     let app = app::App::default().with_scheme(app::Scheme::Gtk);
 
-    // start with the initial dark theme
-    let theme = ColorTheme::new(color_themes::BLACK_THEME);
-    theme.apply();
+    // start with the appearance of the last session, the black theme the first time, then the one
+    // saved with the configuration loaded. The layout is kept in the preferences of the last
+    // configuration, also when it is not loaded at startup.
+    let layout = last_layout();
+    let appearance = layout.appearance.unwrap_or_default();
+    widgets::theme::apply_appearance(appearance);
 
     let application = Rc::new(RefCell::new(Application::default()));
+    menu::file::config::auto_load_last(&mut Rc::clone(&application));
+    widgets::theme::apply_loaded_appearance(&application, appearance);
+
+    // Language, interface scale and font size before any window is created
+    utils::i18n::set_language(application.borrow().preferences.language);
+    utils::i18n::set_menu_language(application.borrow().preferences.language);
+    widgets::scale::apply_ui_scale(application.borrow().preferences.ui_scale);
+    let (width, height) = widgets::scale::fit_to_screen(800, 600);
 
     let mut wind = Window::default()
-        .with_size(800, 600)
+        .with_size(width, height)
         .with_label(APP_TITLE)
         .center_screen();
 
-    let mut menu = MenuBar::new(0, 0, 800, MENU_HEIGHT, "");
+    let mut menu = MenuBar::new(0, 0, width, MENU_HEIGHT, "");
 
     // Window call back to avoid program termination when ESC is pressed
     // from FLTK Book - FAQ
@@ -41,45 +71,72 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // File -> Config -> load
     let mut application_load_conf = Rc::clone(&application);
     menu.add(
-        "&File/&Configuration",
+        &menu_path("&File/&Configuration"),
         Shortcut::None,
         MenuFlag::Submenu,
         |_| {}
     );
 
     menu.add(
-        "File/Configuration/&Load\t",
+        &menu_path("File/Configuration/&Load\t"),
         Shortcut::Ctrl | 'l',
         MenuFlag::Normal,
-        move |_| {
+        move |m| {
             menu::file::config::handle_load_configuration(&mut application_load_conf);
+            menu::file::config::refresh_recent_menu(m, &application_load_conf);
         },
     );
 
     // File -> Config -> Save
     let mut application_save_conf = Rc::clone(&application);
     menu.add(
-        "File/Configuration/&Save\t",
+        &menu_path("File/Configuration/&Save\t"),
         Shortcut::Ctrl | 's',
         MenuFlag::Normal,
-        move |_| {
+        move |m| {
             menu::file::config::handle_save_configuration(&mut application_save_conf);
+            menu::file::config::refresh_recent_menu(m, &application_save_conf);
         },
     );
 
+    // File -> Config -> Recent, rebuilt whenever a configuration is loaded or saved
+    menu.add(
+        &menu_path(menu::file::config::RECENT_MENU),
+        Shortcut::None,
+        MenuFlag::Submenu | MenuFlag::MenuDivider,
+        |_| {}
+    );
+    menu::file::config::update_recent_menu(&mut menu, &application);
+
+    // File -> Config -> Load last at startup
+    menu.add(
+        &menu_path(menu::file::config::AUTO_LOAD_MENU),
+        Shortcut::None,
+        MenuFlag::Toggle,
+        |m| {
+            menu::file::config::handle_auto_load(m);
+        },
+    );
+    if RecentConfigs::load().auto_load {
+        if let Some(mut item) = menu.find_item(&menu_path(menu::file::config::AUTO_LOAD_MENU)) {
+            item.set();
+        }
+    }
+
     // File -> Preferences
+    let mut application_preferences = Rc::clone(&application);
     menu.add(
-        "&File/&Preferences\t",
+        &menu_path("&File/&Preferences\t"),
         Shortcut::Ctrl | 'p',
         MenuFlag::MenuDivider,
-        |_| {
-            // menu::file::exit::handle_preferences();
+        move |_| {
+            menu::file::preferences::handle_preferences(&mut application_preferences);
         },
     );
 
     // File -> Exit
     menu.add(
-        "&File/E&xit\t",
+        &menu_path("&File/E&xit\t"),
         Shortcut::Ctrl | 'x',
         MenuFlag::Normal,
         |_| {
@@ -87,74 +144,227 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         },
     );
 
-    // Functions -> Observatory
-    let mut application_observatory = Rc::clone(&application);
+    // Edit -> Undo
+    let mut application_undo = Rc::clone(&application);
+    let wind_undo = wind.clone();
     menu.add(
-        "F&unctions/&Observatory\t",
-        Shortcut::Ctrl | 'o',
+        &menu_path("&Edit/&Undo\t"),
+        Shortcut::Ctrl | 'z',
         MenuFlag::Normal,
         move |_| {
-            menu::functions::observatory::handle_observatory(&mut application_observatory);
+            menu::edit::undo::handle_undo(&mut application_undo, &wind_undo);
         },
     );
 
-    // Functions -> Constraints
-    let mut application_constraints = Rc::clone(&application);
+    // Edit -> Redo
+    let mut application_redo = Rc::clone(&application);
+    let wind_redo = wind.clone();
     menu.add(
-        "F&unctions/&Constraints\t",
-        Shortcut::Ctrl | 'c',
+        &menu_path("&Edit/&Redo\t"),
+        Shortcut::Ctrl | 'y',
         MenuFlag::Normal,
         move |_| {
-            // menu::functions::constraint::handle_constraint(&mut application_constraints);
+            menu::edit::undo::handle_redo(&mut application_redo, &wind_redo);
         },
     );
 
+    // Functions -> Observatory
+    let mut application_observatory = Rc::clone(&application);
+    add_tool_menu(&mut menu, "F&unctions/&Observatory\t", Shortcut::Ctrl | 'o', move || {
+        menu::functions::observatory::handle_observatory(&mut application_observatory)
+    });
+
+    // Functions -> Constraints
+    let mut application_constraints = Rc::clone(&application);
+    add_tool_menu(&mut menu, "F&unctions/&Constraints\t", Shortcut::Ctrl | 'c', move || {
+        menu::functions::constraint::handle_constraint(&mut application_constraints)
+    });
+
+    // Functions -> Environment
+    let mut application_environment = Rc::clone(&application);
+    add_tool_menu(&mut menu, "F&unctions/E&nvironment\t", Shortcut::None, move || {
+        menu::functions::environment::handle_environment(&mut application_environment)
+    });
+
+    // Functions -> Equipment
+    let mut application_equipment = Rc::clone(&application);
+    add_tool_menu(&mut menu, "F&unctions/&Equipment\t", Shortcut::Ctrl | 'f', move || {
+        menu::functions::equipment::handle_equipment(&mut application_equipment)
+    });
+
+    // Functions -> Mosaic
+    let mut application_mosaic = Rc::clone(&application);
+    add_tool_menu(&mut menu, "F&unctions/Mo&saic\t", Shortcut::Ctrl | 'u', move || {
+        menu::functions::mosaic::handle_mosaic(&mut application_mosaic)
+    });
+
     // Functions -> Darkness
     let mut application_darkness = Rc::clone(&application);
-    menu.add(
-        "F&unctions/&Darkness\t",
-        Shortcut::Ctrl | 'd',
-        MenuFlag::Normal,
-        move |_| {
-            menu::functions::darkness::handle_darkness(&mut application_darkness);
-        },
-    );
+    add_tool_menu(&mut menu, "F&unctions/&Darkness\t", Shortcut::Ctrl | 'd', move || {
+        menu::functions::darkness::handle_darkness(&mut application_darkness)
+    });
 
-    // Theme Options
-    // menu.add("&View/&Themes/Color Themes/Dark", Shortcut::None, MenuFlag::Normal, |_| {
-    menu.add("&View/&Themes/Dark", Shortcut::None, MenuFlag::Normal, |_| {
-        let theme = ColorTheme::new(color_themes::DARK_THEME);
-        theme.apply();
+    // Functions -> Altitude chart
+    let mut application_altitude_chart = Rc::clone(&application);
+    add_tool_menu(&mut menu, "F&unctions/Altitude cha&rt\t", Shortcut::Ctrl | 'i', move || {
+        menu::functions::altitude_chart::handle_altitude_chart(&mut application_altitude_chart)
     });
 
-    // menu.add("&View/&Themes/Color Themes/Black", Shortcut::None, MenuFlag::Normal, |_| {
-    menu.add("&View/&Themes/Black", Shortcut::None, MenuFlag::Normal, |_| {
-    let theme = ColorTheme::new(color_themes::BLACK_THEME);
-        theme.apply();
+    // Functions -> Moon separation
+    let mut application_moon_separation = Rc::clone(&application);
+    add_tool_menu(&mut menu, "F&unctions/&Moon separation\t", Shortcut::Ctrl | 'm', move || {
+        menu::functions::moon_separation::handle_moon_separation(&mut application_moon_separation)
     });
 
-    // menu.add("&View/&Themes/Color Themes/Gray", Shortcut::None, MenuFlag::Normal, |_| {
-    menu.add("&View/&Themes/Gray", Shortcut::None, MenuFlag::Normal, |_| {
-            let theme = ColorTheme::new(color_themes::GRAY_THEME);
-        theme.apply();
+    // Functions -> Darkness calendar
+    let mut application_darkness_calendar = Rc::clone(&application);
+    add_tool_menu(&mut menu, "F&unctions/Darkness ca&lendar\t", Shortcut::Ctrl | 'n', move || {
+        menu::functions::darkness_calendar::handle_darkness_calendar(&mut application_darkness_calendar)
     });
 
-    // menu.add("&View/&Themes/Widget Themes/Dark", Shortcut::None, MenuFlag::Normal, |_| {
-    //     let widget_theme = WidgetTheme::new(ThemeType::Dark);
-    //     widget_theme.apply();
-    // });
-    //
-    // menu.add("&View/&Themes/Widget Themes/Classic", Shortcut::None, MenuFlag::Normal, |_| {
-    //     let widget_theme = WidgetTheme::new(ThemeType::Classic);
-    //     widget_theme.apply();
-    // });
+    // Functions -> Monthly almanac
+    let mut application_almanac = Rc::clone(&application);
+    add_tool_menu(&mut menu, "F&unctions/Mont&hly almanac\t", Shortcut::Ctrl | 'h', move || {
+        menu::functions::almanac::handle_almanac(&mut application_almanac)
+    });
 
+    // Functions -> Compare sites
+    let mut application_site_comparison = Rc::clone(&application);
+    add_tool_menu(&mut menu, "F&unctions/Com&pare sites\t", Shortcut::Ctrl | 'b', move || {
+        menu::functions::site_comparison::handle_site_comparison(&mut application_site_comparison)
+    });
+
+    // Functions -> Night quality trends
+    let mut application_quality_trends = Rc::clone(&application);
+    add_tool_menu(&mut menu, "F&unctions/Night &quality trends\t", Shortcut::Ctrl | 'q', move || {
+        menu::functions::quality_trends::handle_quality_trends(&mut application_quality_trends)
+    });
+
+    // Functions -> Sky status
+    let mut application_sky_status = Rc::clone(&application);
+    add_tool_menu(&mut menu, "F&unctions/Sky s&tatus\t", Shortcut::Ctrl | 't', move || {
+        menu::functions::sky_status::handle_sky_status(&mut application_sky_status)
+    });
+
+    // Functions -> Zenith and meridian
+    let mut application_meridian = Rc::clone(&application);
+    add_tool_menu(&mut menu, "F&unctions/&Zenith and meridian\t", Shortcut::Ctrl | 'w', move || {
+        menu::functions::meridian::handle_meridian(&mut application_meridian)
+    });
+
+    // Functions -> Countdowns
+    let mut application_countdown = Rc::clone(&application);
+    add_tool_menu(&mut menu, "F&unctions/Countdo&wns\t", Shortcut::Ctrl | 'k', move || {
+        menu::functions::countdown::handle_countdown(&mut application_countdown)
+    });
+
+    // Functions -> Targets
+    let mut application_targets = Rc::clone(&application);
+    add_tool_menu(&mut menu, "F&unctions/T&argets\t", Shortcut::Ctrl | 'g', move || {
+        menu::functions::targets::handle_targets(&mut application_targets)
+    });
+
+    // Functions -> Alignment stars
+    let mut application_alignment = Rc::clone(&application);
+    add_tool_menu(&mut menu, "F&unctions/Al&ignment stars\t", Shortcut::Ctrl | 'j', move || {
+        menu::functions::alignment::handle_alignment(&mut application_alignment)
+    });
+
+    let mut application_solar_system = Rc::clone(&application);
+    add_tool_menu(&mut menu, "F&unctions/Solar s&ystem\t", Shortcut::Ctrl | 'e', move || {
+        menu::functions::solar_system::handle_solar_system(&mut application_solar_system)
+    });
+
+    // Theme Options, the choice is kept with the configuration and for the next start
+    for theme in THEMES {
+        let application_theme = Rc::clone(&application);
+        menu.add(
+            &menu_path(&format!("&View/&Themes/{}", theme.name())),
+            Shortcut::None,
+            MenuFlag::Normal,
+            move |m| {
+                widgets::night_vision::leave_night_vision(m);
+                let current = widgets::theme::current_appearance(&application_theme.borrow());
+                let appearance = Appearance { theme, ..current };
+                widgets::theme::choose_appearance(&application_theme, appearance);
+            },
+        );
+    }
+
+    // Widget themes, drawn under the color theme
+    for style in WIDGET_STYLES {
+        let application_widgets = Rc::clone(&application);
+        menu.add(
+            &menu_path(&format!("&View/&Themes/&Widgets/{}", style.name())),
+            Shortcut::None,
+            MenuFlag::Normal,
+            move |m| {
+                widgets::night_vision::leave_night_vision(m);
+                let current = widgets::theme::current_appearance(&application_widgets.borrow());
+                let appearance = Appearance { widgets: style, ..current };
+                widgets::theme::choose_appearance(&application_widgets, appearance);
+            },
+        );
+    }
+
+    // Night vision, all red and dimmed over the current theme to keep dark adaptation in the field
+    let application_night_vision = Rc::clone(&application);
+    menu.add(
+        &menu_path(widgets::night_vision::NIGHT_VISION_MENU),
+        Shortcut::Ctrl | 'r',
+        MenuFlag::Toggle,
+        move |m| {
+            widgets::night_vision::handle_night_vision(m);
+            let night_vision = widgets::night_vision::night_vision_active();
+            application_night_vision.borrow_mut().preferences.night_vision = night_vision;
+            update_layout(|preferences| preferences.night_vision = night_vision);
+        },
+    );
+    if layout.night_vision {
+        widgets::night_vision::set_night_vision(true);
+        if let Some(mut item) = menu.find_item(&menu_path(widgets::night_vision::NIGHT_VISION_MENU)) {
+            item.set();
+        }
+    }
+
+    // What's New
+    let mut application_whats_new = Rc::clone(&application);
+    menu.add(
+        &menu_path("&Help/&What's New\t"),
+        Shortcut::None,
+        MenuFlag::Normal,
+        move |_| {
+            menu::about::whats_new::handle_whats_new(&mut application_whats_new);
+        },
+    );
+
+    // Calculation inspector
+    let mut application_inspector = Rc::clone(&application);
+    menu.add(
+        &menu_path("&Help/Calculation &inspector\t"),
+        Shortcut::None,
+        MenuFlag::Normal,
+        move |_| {
+            menu::about::inspector::handle_inspector(&mut application_inspector);
+        },
+    );
+
+    // Diagnostics
+    let mut application_diagnostics = Rc::clone(&application);
+    menu.add(
+        &menu_path("&Help/&Diagnostics\t"),
+        Shortcut::None,
+        MenuFlag::Normal,
+        move |_| {
+            menu::about::diagnostics::handle_diagnostics(&mut application_diagnostics);
+        },
+    );
 
     // About
     let mut menu_about = menu.clone();
     let wind_about = wind.clone();
     menu.add(
-        "&Help/&About\t",
+        &menu_path("&Help/&About\t"),
         Shortcut::Ctrl | 'a',
         MenuFlag::Normal, {
         move |_| {
@@ -162,16 +372,75 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     });
 
+    // Drag and drop a configuration file onto the main window to load it
+    let mut application_drop_conf = Rc::clone(&application);
+    let menu_drop_conf = menu.clone();
+    let window_color = wind.color();
+    wind.handle(move |w, ev| match ev {
+        Event::DndEnter => {
+            // Highlight window while a file is dragged over it
+            w.set_color(window_color.lighter());
+            w.set_label(&format!("{} - drop configuration file to load", APP_TITLE));
+            w.redraw();
+            true
+        }
+        Event::DndDrag | Event::DndRelease => true,
+        Event::DndLeave => {
+            w.set_color(window_color);
+            w.set_label(APP_TITLE);
+            w.redraw();
+            true
+        }
+        Event::Paste => {
+            w.set_color(window_color);
+            w.set_label(APP_TITLE);
+            w.redraw();
+            menu::file::config::handle_drop_configuration(&app::event_text(), &mut application_drop_conf);
+            menu::file::config::refresh_recent_menu(&menu_drop_conf, &application_drop_conf);
+            true
+        }
+        _ => false,
+    });
+
+    // Timeline of the selected night with the current time
+    let timeline = TimelineWidget::new(10, MENU_HEIGHT + 10, width - 20, 80);
+    timeline.start(Rc::clone(&application));
+
+    // Status bar with the clock, the observatory and the next event
+    let status_bar = StatusBar::new(0, height - STATUS_BAR_HEIGHT, width, STATUS_BAR_HEIGHT);
+    status_bar.start(Rc::clone(&application));
+
     wind.end();
     wind.make_resizable(true);
+
+    // Size and position of the last session, moved onto a screen that is still connected
+    if let Some(geometry) = layout.window {
+        let screen = app::screen_num(geometry.x + geometry.width / 2, geometry.y + geometry.height / 2);
+        let geometry = geometry.fit_in(app::screen_work_area(screen));
+        wind.resize(geometry.x, geometry.y, geometry.width, geometry.height);
+    }
     wind.show();
 
+    // Tool windows left open in the last session
+    menu::window_manager::reopen_tools(&layout.open_tools);
+
+    // Settings left unsaved by a crash, compared with the configuration loaded at start so the
+    // recovered ones are autosaved again until they are saved or the program exits cleanly
+    let mut autosave = Autosave::new(autosave_path(), &application.borrow());
+    menu::file::config::recover_autosave(&mut Rc::clone(&application));
+
     while app.wait(){
+        autosave.tick(&application.borrow());
         // Reduce frame updated to reduce CPU consumption
         std::thread::sleep(std::time::Duration::from_millis(32));
     }
+    autosave.finish();
 
     // app.run().unwrap();
 
+    // Main window geometry for the next start
+    let geometry = WindowGeometry { x: wind.x(), y: wind.y(), width: wind.w(), height: wind.h() };
+    update_layout(|preferences| preferences.window = Some(geometry));
+
     Ok(())
 }
@@ -0,0 +1,80 @@
+// src/menu/about/diagnostics.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use fltk::enums::{Font, Shortcut};
+use fltk::prelude::{DisplayExt, GroupExt, WidgetBase, WidgetExt};
+use fltk::text::{TextBuffer, TextDisplay};
+use fltk::{app, button, enums};
+use fltk_evented::Listener;
+use crate::application::application::Application;
+use crate::application::diagnostics::diagnostics;
+use crate::menu::window_manager::new_tool_window;
+use crate::widgets::keyboard::close_on_escape;
+
+pub fn handle_diagnostics(application: &mut Rc<RefCell<Application>>) -> bool {
+    let Some(mut window) = new_tool_window("Diagnostics", 720, 520) else {
+        return false;
+    };
+
+    // Settings, intermediate values and recent log lines
+    let mut buffer = TextBuffer::default();
+    buffer.set_text(&diagnostics(&application.borrow()).join(""));
+    let mut display = TextDisplay::new(10, 10, 700, 450, "");
+    display.set_buffer(buffer.clone());
+    display.set_text_font(Font::Courier);
+
+    // Copy button, to paste into a bug report
+    let mut btn_copy: Listener<_> = button::Button::new(10, 475, 120, 30, "Copy to clipboard").into();
+    btn_copy.clear_visible_focus();
+
+    // Close button
+    let mut btn_close: Listener<_> = button::Button::new(660, 475, 50, 30, "&Close").into();
+    btn_close.clear_visible_focus();
+    btn_close.set_shortcut(Shortcut::Alt | 'c');
+
+    window.end();
+    window.show();
+
+    let mut window_clone = window.clone();
+
+    // Close with the window close button or Esc
+    close_on_escape(&mut window);
+
+    // Handlers for Copy button
+    // preserve button's original color
+    let btn_copy_color = btn_copy.color();
+    btn_copy.on_click(move |_| {
+        app::copy(&buffer.text());
+    });
+
+    // change color on hover
+    btn_copy.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_copy.on_leave(move |b| {
+        b.set_color(btn_copy_color);
+    });
+
+    // Handlers for Close button
+    // preserve button's original color
+    let btn_close_color = btn_close.color();
+    // close window when clicked or with Alt+C
+    btn_close.set_callback(move |_| {
+        window_clone.hide();
+    });
+
+    // change color on hover
+    btn_close.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_close.on_leave(move |b| {
+        b.set_color(btn_close_color);
+    });
+
+    true
+}
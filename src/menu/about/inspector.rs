@@ -0,0 +1,65 @@
+// src/menu/about/inspector.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use fltk::enums::Font;
+use fltk::prelude::{DisplayExt, GroupExt, WidgetBase, WidgetExt};
+use fltk::text::{TextBuffer, TextDisplay};
+use fltk::{button, enums};
+use fltk_evented::Listener;
+use crate::application::application::Application;
+use crate::application::inspector::inspect_night;
+use crate::menu::window_manager::new_tool_window;
+
+pub fn handle_inspector(application: &mut Rc<RefCell<Application>>) -> bool {
+    let Some(mut window) = new_tool_window("Calculation inspector", 720, 520) else {
+        return false;
+    };
+
+    // Intermediate values of the selected night
+    let mut buffer = TextBuffer::default();
+    {
+        let app = application.borrow();
+        buffer.set_text(&inspect_night(&app.observer, &app.time, &app.environment, &app.preferences).join(""));
+    }
+    let mut display = TextDisplay::new(10, 10, 700, 450, "");
+    display.set_buffer(buffer);
+    display.set_text_font(Font::Courier);
+
+    // Close button
+    let mut btn_close: Listener<_> = button::Button::new(660, 475, 50, 30, "Close").into();
+    btn_close.clear_visible_focus();
+
+    window.end();
+    window.show();
+
+    let mut window_clone = window.clone();
+
+    // Window call back to avoid program termination when ESC is pressed
+    // from FLTK Book - FAQ
+    window.set_callback(|w| {
+        if fltk::app::event() == fltk::enums::Event::Close {
+            w.hide();
+        }
+    });
+
+    // Handlers for Close button
+    // preserve button's original color
+    let btn_close_color = btn_close.color();
+    // close window when clicked
+    btn_close.on_click(move |_| {
+        window_clone.hide();
+    });
+
+    // change color on hover
+    btn_close.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_close.on_leave(move |b| {
+        b.set_color(btn_close_color);
+    });
+
+    true
+}
@@ -1,2 +1,5 @@
 // src/menu/about/mod.rs
-pub mod about;
\ No newline at end of file
+pub mod about;
+pub mod inspector;
+pub mod diagnostics;
+pub mod whats_new;
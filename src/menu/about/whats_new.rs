@@ -0,0 +1,76 @@
+// src/menu/about/whats_new.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use fltk::prelude::{DisplayExt, GroupExt, WidgetBase, WidgetExt};
+use fltk::text::{TextBuffer, TextDisplay};
+use fltk::{button, enums};
+use fltk_evented::Listener;
+use crate::application::application::Application;
+use crate::menu::window_manager::new_tool_window;
+
+const CHANGELOG: &str = include_str!("../../../CHANGELOG.md");
+
+// Changes applied to the loaded configuration, if any, followed by the changelog
+fn whats_new_text(application: &Application) -> String {
+    let mut text = String::new();
+    if !application.migration_notes.is_empty() {
+        text.push_str("# Configuration updated\n\n");
+        for note in &application.migration_notes {
+            text.push_str(&format!("- {}\n", note));
+        }
+        text.push('\n');
+    }
+    text.push_str(CHANGELOG);
+    text
+}
+
+pub fn handle_whats_new(application: &mut Rc<RefCell<Application>>) -> bool {
+    let Some(mut window) = new_tool_window("What's New", 520, 420) else {
+        return false;
+    };
+
+    // Changelog
+    let mut buffer = TextBuffer::default();
+    buffer.set_text(&whats_new_text(&application.borrow()));
+    let mut display = TextDisplay::new(10, 10, 500, 350, "");
+    display.set_buffer(buffer);
+    display.wrap_mode(fltk::text::WrapMode::AtBounds, 0);
+
+    // Close button
+    let mut btn_close: Listener<_> = button::Button::new(460, 375, 50, 30, "Close").into();
+    btn_close.clear_visible_focus();
+
+    window.end();
+    window.show();
+
+    let mut window_clone = window.clone();
+
+    // Window call back to avoid program termination when ESC is pressed
+    // from FLTK Book - FAQ
+    window.set_callback(|w| {
+        if fltk::app::event() == fltk::enums::Event::Close {
+            w.hide();
+        }
+    });
+
+    // Handlers for Close button
+    // preserve button's original color
+    let btn_close_color = btn_close.color();
+    // close window when clicked
+    btn_close.on_click(move |_| {
+        window_clone.hide();
+    });
+
+    // change color on hover
+    btn_close.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_close.on_leave(move |b| {
+        b.set_color(btn_close_color);
+    });
+
+    true
+}
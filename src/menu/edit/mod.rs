@@ -0,0 +1,2 @@
+// src/menu/edit/mod.rs
+pub mod undo;
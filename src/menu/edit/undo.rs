@@ -0,0 +1,33 @@
+// src/menu/edit/undo.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use fltk::app;
+use fltk::prelude::WidgetExt;
+use fltk::window::Window;
+use crate::application::application::Application;
+use crate::utils::definers::APP_TITLE;
+
+// Seconds the result of an undo or redo stays in the window title
+const STATUS_SECONDS: f64 = 3.0;
+
+// Shows what was undone or redone in the main window title for a few seconds
+fn show_status(window: &Window, status: &str) {
+    let mut window = window.clone();
+    window.set_label(&format!("{} - {}", APP_TITLE, status));
+    app::add_timeout3(STATUS_SECONDS, move |_| window.set_label(APP_TITLE));
+}
+
+pub fn handle_undo(application: &mut Rc<RefCell<Application>>, window: &Window) {
+    match application.borrow_mut().undo() {
+        Some(name) => show_status(window, &format!("undid {} edit", name)),
+        None => show_status(window, "nothing to undo"),
+    }
+}
+
+pub fn handle_redo(application: &mut Rc<RefCell<Application>>, window: &Window) {
+    match application.borrow_mut().redo() {
+        Some(name) => show_status(window, &format!("redid {} edit", name)),
+        None => show_status(window, "nothing to redo"),
+    }
+}
@@ -1,35 +1,246 @@
 use std::cell::RefCell;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use fltk::dialog::{FileDialog, FileDialogType};
-use crate::application::application::{load_from_yaml, save_to_yaml, Application};
+use fltk::app;
+use fltk::dialog::{alert_default, choice2_default, message_default, FileDialog, FileDialogType};
+use fltk::enums::Shortcut;
+use fltk::menu::{MenuBar, MenuFlag};
+use fltk::prelude::MenuExt;
+use crate::application::application::{load_config, save_config, Application, ConfigFormat,
+                                      CONFIG_EXTENSIONS};
+use crate::application::autosave::{autosave_path, discard_autosave};
+use crate::application::preferences::Appearance;
+use crate::application::recent::RecentConfigs;
+use crate::menu::window_manager::open_tool_paths;
+use crate::utils::i18n::menu_path;
+use crate::widgets::theme::{apply_loaded_appearance, current_appearance};
+
+pub(crate) const CONFIG_FILTER: &str = "Configuration Files\t*.{yaml,yml,json,toml}";
+// Full item paths, FLTK looks items up by their labels including the '&' shortcut markers
+pub const RECENT_MENU: &str = "&File/&Configuration/&Recent";
+pub const AUTO_LOAD_MENU: &str = "&File/&Configuration/Load last at st&artup";
 
 pub fn handle_save_configuration(application: &mut Rc<RefCell<Application>>) {
     let mut dialog = FileDialog::new(FileDialogType::BrowseSaveFile);
-    dialog.set_filter("Configuration Files\t*.{yaml}");
+    dialog.set_filter(CONFIG_FILTER);
     dialog.show();
 
     if let Some(filename) = dialog.filename().to_str() {
+        if filename.is_empty() {
+            return;
+        }
         let mut path = PathBuf::from(filename);
 
-        if let Some(extension) = path.extension() {
-            if extension.to_str() != Some("yaml") {
-                path.set_extension("yaml");
-            }
-        } else {
-            path.set_extension("yaml");
+        // The format follows the extension, YAML when there is none or it is not known
+        if ConfigFormat::from_path(&path).is_none() {
+            path.set_extension(ConfigFormat::Yaml.extension());
         }
 
-        save_to_yaml(path, application).expect("Failed to save configuration file");
+        // the open tool windows are kept as they change, the saved preferences have the current ones
+        application.borrow_mut().preferences.open_tools = open_tool_paths();
+        save_config(path.clone(), application).expect("Failed to save configuration file");
+        remember_recent(&path);
     }
 }
 
 pub fn handle_load_configuration(application: &mut Rc<RefCell<Application>>) {
     let mut dialog = FileDialog::new(FileDialogType::BrowseFile);
-    dialog.set_filter("Configuration Files\t*.{yaml}");
+    dialog.set_filter(CONFIG_FILTER);
     dialog.show();
 
     if let Some(filename) = dialog.filename().to_str() {
-        load_from_yaml(filename, application).expect("Failed to load configuration file");
+        if filename.is_empty() {
+            return;
+        }
+        let previous = current_appearance(&application.borrow());
+        match load_config(filename, application) {
+            Ok(()) => {
+                notify_loaded(application, previous);
+                remember_recent(Path::new(filename));
+            }
+            Err(e) => alert_default(&format!("Failed to load configuration file:\n{}", e)),
+        }
+    }
+}
+
+// Moves a configuration to the top of the recent list
+fn remember_recent(file_path: &Path) {
+    let mut recent = RecentConfigs::load();
+    recent.add(file_path);
+    if let Err(e) = recent.save() {
+        log::warn!("recent configurations not saved error={}", e);
+    }
+}
+
+// Menu label of a path, FLTK reads '/', '\\', '&' and '_' as menu markup
+fn menu_label(file_path: &str) -> String {
+    file_path
+        .replace('\\', "\\\\")
+        .replace('/', "\\/")
+        .replace('&', "&&")
+        .replace('_', "\\_")
+}
+
+pub fn handle_load_recent(file_path: &str, application: &mut Rc<RefCell<Application>>) {
+    if !Path::new(file_path).is_file() {
+        alert_default(&format!("Configuration file not found:\n{}", file_path));
+        let mut recent = RecentConfigs::load();
+        recent.remove(file_path);
+        let _ = recent.save();
+        return;
+    }
+
+    let previous = current_appearance(&application.borrow());
+    match load_config(file_path, application) {
+        Ok(()) => {
+            notify_loaded(application, previous);
+            remember_recent(Path::new(file_path));
+        }
+        Err(e) => alert_default(&format!("Failed to load configuration file:\n{}", e)),
+    }
+}
+
+// Loads the most recent configuration at startup when enabled, a missing file is skipped
+pub fn auto_load_last(application: &mut Rc<RefCell<Application>>) {
+    let recent = RecentConfigs::load();
+    if !recent.auto_load {
+        return;
+    }
+    if let Some(file_path) = recent.last() {
+        if Path::new(file_path).is_file() {
+            if let Err(e) = load_config(file_path, application) {
+                log::warn!("last configuration not loaded path={} error={}", file_path, e);
+            }
+        }
+    }
+}
+
+// Offers the state autosaved by a session that did not close properly. A recovered autosave is
+// kept until the next clean exit, so a second crash before saving does not lose it.
+pub fn recover_autosave(application: &mut Rc<RefCell<Application>>) {
+    let path = autosave_path();
+    if !path.is_file() {
+        return;
+    }
+    let question = "SkyCalc did not close properly and has settings that were not saved.\n\
+                    Recover them?";
+    if choice2_default(question, "Discard", "Recover", "") != Some(1) {
+        log::info!("autosave discarded path={}", path.display());
+        discard_autosave(&path);
+        return;
+    }
+    let previous = current_appearance(&application.borrow());
+    match path.to_str().map(|file_path| load_config(file_path, application)) {
+        Some(Ok(())) => {
+            log::info!("autosave recovered path={}", path.display());
+            apply_loaded_appearance(application, previous);
+        }
+        Some(Err(e)) => {
+            alert_default(&format!("Failed to recover the autosaved settings:\n{}", e));
+            discard_autosave(&path);
+        }
+        None => discard_autosave(&path),
+    }
+}
+
+// Rebuilds File/Configuration/Recent from the saved list
+pub fn update_recent_menu(menu: &mut MenuBar, application: &Rc<RefCell<Application>>) {
+    let recent_menu = menu_path(RECENT_MENU);
+    let index = menu.find_index(&recent_menu);
+    if index >= 0 {
+        let _ = menu.clear_submenu(index);
+    }
+
+    let recent = RecentConfigs::load();
+    if recent.files.is_empty() {
+        menu.add(&format!("{}/{}", recent_menu, menu_path("(empty)")), Shortcut::None, MenuFlag::Inactive, |_| {});
+    }
+    for (i, file_path) in recent.files.iter().enumerate() {
+        let mut application_recent = Rc::clone(application);
+        let file_path = file_path.clone();
+        menu.add(
+            &format!("{}/&{} {}", recent_menu, i + 1, menu_label(&file_path)),
+            Shortcut::None,
+            MenuFlag::Normal,
+            move |m| {
+                handle_load_recent(&file_path, &mut application_recent);
+                refresh_recent_menu(m, &application_recent);
+            },
+        );
+    }
+}
+
+// Rebuilds the recent list after the current menu callback returns, the picked item must not
+// be freed while FLTK still uses it
+pub fn refresh_recent_menu(menu: &MenuBar, application: &Rc<RefCell<Application>>) {
+    let mut menu = menu.clone();
+    let application = Rc::clone(application);
+    app::add_timeout3(0.0, move |_| update_recent_menu(&mut menu, &application));
+}
+
+pub fn handle_auto_load(menu: &MenuBar) {
+    if let Some(item) = menu.find_item(&menu_path(AUTO_LOAD_MENU)) {
+        let mut recent = RecentConfigs::load();
+        recent.auto_load = item.value();
+        if let Err(e) = recent.save() {
+            alert_default(&format!("Failed to save the startup option:\n{}", e));
+        }
+    }
+}
+
+// Applies the appearance saved with the loaded configuration over the `previous` one, then tells the
+// user when it was written in an older format, and which of its fields were ignored
+fn notify_loaded(application: &Rc<RefCell<Application>>, previous: Appearance) {
+    apply_loaded_appearance(application, previous);
+    if !application.borrow().migration_notes.is_empty() {
+        message_default("The configuration was written in an older format and was updated.\n\
+                         See Help/What's New for the changes.");
+    }
+    let warnings = application.borrow().config_warnings.clone();
+    if !warnings.is_empty() {
+        alert_default(&format!("The configuration was loaded, these fields are not known and were ignored:\n{}",
+                               warnings.join("\n")));
+    }
+}
+
+// Path of the first file dropped on a window, FLTK gives a new line separated list of paths or
+// file:// URIs depending on the platform
+pub fn dropped_path(text: &str) -> Option<PathBuf> {
+    let first = text.lines().next()?.trim();
+    let path = first.strip_prefix("file://").unwrap_or(first);
+    let path = path.replace("%20", " ");
+    if path.is_empty() {
+        None
+    } else {
+        Some(PathBuf::from(path))
+    }
+}
+
+pub fn handle_drop_configuration(text: &str, application: &mut Rc<RefCell<Application>>) {
+    let path = match dropped_path(text) {
+        Some(path) => path,
+        None => return,
+    };
+
+    // Same checks as the Load dialog: only existing configuration files are accepted
+    if ConfigFormat::from_path(&path).is_none() {
+        alert_default(&format!("Not a configuration file (*.{}):\n{}",
+                               CONFIG_EXTENSIONS.join(", *."), path.display()));
+        return;
+    }
+    if !path.is_file() {
+        alert_default(&format!("Configuration file not found:\n{}", path.display()));
+        return;
+    }
+
+    if let Some(filename) = path.to_str() {
+        let previous = current_appearance(&application.borrow());
+        match load_config(filename, application) {
+            Ok(()) => {
+                notify_loaded(application, previous);
+                remember_recent(&path);
+            }
+            Err(e) => alert_default(&format!("Failed to load configuration file:\n{}", e)),
+        }
     }
 }
\ No newline at end of file
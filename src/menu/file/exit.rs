@@ -2,6 +2,6 @@
 use fltk::app;
 
 pub fn handle_exit() {
-    println!("Quiting application");
+    log::info!("exit requested");
     app::quit();
 }
@@ -1,3 +1,4 @@
 // src/menu/file/mod.rs
 pub mod exit;
-pub mod config;
\ No newline at end of file
+pub mod config;
+pub mod preferences;
\ No newline at end of file
@@ -0,0 +1,434 @@
+// src/menu/file/preferences.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use fltk::button::CheckButton;
+use fltk::dialog::{FileDialog, FileDialogType};
+use fltk::group::Flex;
+use fltk::input::{Input, IntInput};
+use fltk::menu::Choice;
+use fltk::prelude::{ButtonExt, GroupExt, InputExt, MenuExt, WidgetBase, WidgetExt};
+use fltk::{button, enums};
+use fltk_evented::Listener;
+use crate::application::application::Application;
+use crate::application::calculation::ACCURACY_PRESETS;
+use crate::application::constraint::MOON_SEPARATION_RANGE;
+use crate::application::preferences::DATE_ROLLOVERS;
+use crate::application::scripting::scripts_directory;
+use crate::application::units::{Units, ELEVATION_UNITS, PRESSURE_UNITS, TEMPERATURE_UNITS};
+use crate::utils::dpi::{scale_index, scale_label, UI_SCALES};
+use crate::utils::i18n::{set_language, tr, LANGUAGES};
+use crate::widgets::layout::{fixed_label, scaled, spacer, FormLayout, BUTTON_HEIGHT, BUTTON_WIDTH, INPUT_HEIGHT,
+                             SPACING};
+use crate::widgets::scale::apply_ui_scale;
+use crate::menu::window_manager::new_tool_window;
+
+// Event time formats offered: 12-hour clock, month before day, month name and the label
+const TIME_FORMATS: [(bool, bool, bool, &str); 8] = [
+    (false, false, false, "dd-mm 24 h"),
+    (false, true, false, "mm-dd 24 h"),
+    (true, false, false, "dd-mm 12 h"),
+    (true, true, false, "mm-dd 12 h"),
+    (false, false, true, "dd mmm 24 h"),
+    (false, true, true, "mmm dd 24 h"),
+    (true, false, true, "dd mmm 12 h"),
+    (true, true, true, "mmm dd 12 h"),
+];
+
+// Half of a row, the window shows the settings in two columns
+fn half_row() -> Flex {
+    let mut half = Flex::default().row();
+    half.set_pad(scaled(SPACING));
+    half
+}
+
+// Check box of a setting
+fn new_check(text: &str, checked: bool, hint: &str) -> CheckButton {
+    let mut check = CheckButton::default().with_label(text);
+    check.set_checked(checked);
+    check.set_tooltip(hint);
+    check
+}
+
+pub fn handle_preferences(application: &mut Rc<RefCell<Application>>) -> bool {
+    let Some(mut window) = new_tool_window(tr("Preferences"), scaled(580), 480) else {
+        return false;
+    };
+    let preferences = application.borrow().preferences.clone();
+    let mut layout = FormLayout::new(&window);
+
+    // Time precision and selected date
+    let row = layout.row(INPUT_HEIGHT);
+    let show_seconds = new_check(tr("Show seconds in times"), preferences.show_seconds,
+                                 tr("When unchecked times are rounded to the nearest minute"));
+    let remember_date = new_check(tr("Remember selected date"), preferences.remember_date,
+                                  tr("Save the selected date with the configuration instead of starting at now"));
+    row.end();
+
+    // Exports
+    let row = layout.row(INPUT_HEIGHT);
+    let confirm_overwrite = new_check(tr("Confirm overwriting exports"), preferences.confirm_overwrite,
+                                      tr("Ask before replacing an export of the same night"));
+    let html_timeline = new_check(tr("Timeline in HTML report"), preferences.html_timeline,
+                                  tr("Draw twilight and Moon up bars in the HTML darkness report"));
+    row.end();
+
+    // Weather and timeline
+    let row = layout.row(INPUT_HEIGHT);
+    let weather_forecast = new_check(tr("Weather forecast (Open-Meteo)"), preferences.weather_forecast,
+                                     tr("Hourly cloud cover for the night, needs internet access"));
+    let solar_midnight = new_check(tr("Timeline centered on solar midnight"), preferences.solar_midnight,
+                                   tr("Hours from solar midnight instead of clock time, same layout in every time zone"));
+    row.end();
+
+    // User scripts and coordinates
+    let row = layout.row(INPUT_HEIGHT);
+    let scripts_hint = format!("{}\n{}", tr("Custom target columns and report lines from the .rhai files in"),
+                               scripts_directory().display());
+    let scripts = new_check(tr("Run user scripts"), preferences.scripts, &scripts_hint);
+    let dms_coordinates = new_check(tr("Coordinates in D° M' S\""), preferences.dms_coordinates,
+                                    tr("Show latitude and longitude as D° M' S\" with N/S and E/W instead of decimal degrees"));
+    row.end();
+
+    // Satellite passes, golden and blue hours
+    let row = layout.row(INPUT_HEIGHT);
+    let satellites = new_check(tr("Satellite passes in report"), preferences.satellites,
+                               tr("Visible passes of the ISS and the bright satellites in the darkness report"));
+    let light_bands = new_check(tr("Golden and blue hours"), preferences.light_bands,
+                                tr("Photography light bands of the twilight in the darkness window and report"));
+    row.end();
+
+    // Zodiacal light and lunar observing
+    let row = layout.row(INPUT_HEIGHT);
+    let zodiacal_light = new_check(tr("Zodiacal light in report"), preferences.zodiacal_light,
+                                   tr("Evening and morning windows with the Sun 18° to 25° below the horizon and the ecliptic angle"));
+    let lunar_observing = new_check(tr("Lunar observing in report"), preferences.lunar_observing,
+                                    tr("Colongitude of the Moon and the named features near the terminator at local midnight"));
+    row.end();
+
+    // Alignment stars and field rotation
+    let row = layout.row(INPUT_HEIGHT);
+    let alignment_stars = new_check(tr("Alignment stars in report"), preferences.alignment_stars,
+                                    tr("Go-to alignment stars spread around the sky at nautical dusk"));
+    let field_rotation = new_check(tr("Field rotation in target details"), preferences.field_rotation,
+                                   tr("Rotation of the field seen by an alt-az mount during the observation window"));
+    row.end();
+    layout.divider();
+
+    let row = layout.row(INPUT_HEIGHT);
+
+    // Clock and date order of the event times
+    let mut half = half_row();
+    fixed_label(&mut half, tr("Time format"), 110);
+    let mut time_format = Choice::default();
+    for (_, _, _, label) in TIME_FORMATS {
+        time_format.add_choice(label);
+    }
+    let selected = TIME_FORMATS.iter().position(|(hour12, month_first, month_names, _)| {
+        *hour12 == preferences.hour12 && *month_first == preferences.month_first
+            && *month_names == preferences.month_names
+    });
+    time_format.set_value(selected.unwrap_or(0) as i32);
+    time_format.set_tooltip(tr("Clock, day-month order and month names of the event times in the windows and reports"));
+    half.end();
+
+    // Interface scale
+    let mut half = half_row();
+    fixed_label(&mut half, tr("Interface scale"), 110);
+    let mut ui_scale = Choice::default();
+    for scale in UI_SCALES {
+        ui_scale.add_choice(&scale_label(scale));
+    }
+    ui_scale.set_value(scale_index(preferences.ui_scale) as i32);
+    ui_scale.set_tooltip(tr("Size of windows and text, System follows the display settings of each screen"));
+    half.end();
+    row.end();
+
+    let row = layout.row(INPUT_HEIGHT);
+
+    // Language
+    let mut half = half_row();
+    fixed_label(&mut half, tr("Language"), 110);
+    let mut language = Choice::default();
+    for l in LANGUAGES {
+        language.add_choice(l.name());
+    }
+    let selected = LANGUAGES.iter().position(|l| *l == preferences.language);
+    language.set_value(selected.unwrap_or(0) as i32);
+    language.set_tooltip(tr("Language of the windows and reports, the menus change on the next start"));
+    half.end();
+
+    // Accuracy of the night computations, hand edited settings show as Custom
+    let mut half = half_row();
+    fixed_label(&mut half, tr("Accuracy"), 110);
+    let mut accuracy = Choice::default();
+    for preset in ACCURACY_PRESETS {
+        accuracy.add_choice(tr(preset.name));
+    }
+    let calculation = preferences.calculation;
+    match calculation.preset() {
+        Some(index) => accuracy.set_value(index as i32),
+        None => {
+            accuracy.add_choice(tr("Custom"));
+            accuracy.set_value(ACCURACY_PRESETS.len() as i32);
+        }
+    }
+    accuracy.set_tooltip(tr("Fast: darkness every 5 minutes with a low precision Moon, for slow machines\nStandard: every minute\nPrecise: every 10 seconds"));
+    half.end();
+    row.end();
+
+    let row = layout.row(INPUT_HEIGHT);
+
+    // New observing night
+    let mut half = half_row();
+    fixed_label(&mut half, tr("New night"), 110);
+    let mut date_rollover = Choice::default();
+    for rollover in DATE_ROLLOVERS {
+        date_rollover.add_choice(tr(rollover.name()));
+    }
+    let selected = DATE_ROLLOVERS.iter().position(|r| *r == preferences.date_rollover);
+    date_rollover.set_value(selected.unwrap_or(0) as i32);
+    date_rollover.set_tooltip(tr("What the darkness window does when a new observing night starts while tonight is shown"));
+    let mut rollover_hour = IntInput::default();
+    rollover_hour.set_maximum_size(2);
+    rollover_hour.set_value(&preferences.rollover_hour.to_string());
+    rollover_hour.set_tooltip(tr("Local hour the observing night changes at, 0 to 23"));
+    half.fixed(&rollover_hour, scaled(40));
+    half.end();
+
+    // Avoidance zone of the targets and the sky chart
+    let mut half = half_row();
+    fixed_label(&mut half, tr("Moon avoidance radius (°)"), 180);
+    spacer();
+    let mut moon_avoidance = IntInput::default();
+    moon_avoidance.set_maximum_size(3);
+    moon_avoidance.set_value(&preferences.moon_avoidance.to_string());
+    moon_avoidance.set_tooltip(tr("Targets closer than this to the Moon during the observation window are flagged"));
+    half.fixed(&moon_avoidance, scaled(50));
+    half.end();
+    row.end();
+
+    let row = layout.row(INPUT_HEIGHT);
+
+    // Units of the elevation, temperature and pressure, the configuration stays metric
+    let mut half = half_row();
+    fixed_label(&mut half, tr("Units"), 60);
+    let units = preferences.units;
+    let mut elevation_unit = Choice::default();
+    for unit in ELEVATION_UNITS {
+        elevation_unit.add_choice(unit.symbol());
+    }
+    elevation_unit.set_value(ELEVATION_UNITS.iter().position(|u| *u == units.elevation).unwrap_or(0) as i32);
+    elevation_unit.set_tooltip(tr("Observatory elevation in meters or feet"));
+    let mut temperature_unit = Choice::default();
+    for unit in TEMPERATURE_UNITS {
+        temperature_unit.add_choice(unit.symbol());
+    }
+    temperature_unit.set_value(TEMPERATURE_UNITS.iter().position(|u| *u == units.temperature).unwrap_or(0) as i32);
+    temperature_unit.set_tooltip(tr("Temperature and dew point in degrees Celsius or Fahrenheit"));
+    let mut pressure_unit = Choice::default();
+    for unit in PRESSURE_UNITS {
+        pressure_unit.add_choice(unit.symbol());
+    }
+    pressure_unit.set_value(PRESSURE_UNITS.iter().position(|u| *u == units.pressure).unwrap_or(0) as i32);
+    pressure_unit.set_tooltip(tr("Air pressure in hectopascals or inches of mercury"));
+    half.end();
+
+    // Refraction of the night computations
+    let refraction = new_check(tr("Refraction at rise and set"), calculation.refraction,
+                               tr("When unchecked rise and set times are geometric, a few minutes later at sunrise and earlier at sunset"));
+    row.end();
+    layout.divider();
+
+    // Light pollution and elevation lookup
+    layout.labels([tr("Sky brightness grid (CSV)"), tr("SRTM elevation tiles directory")]);
+    let row = layout.row(INPUT_HEIGHT);
+    let mut half = half_row();
+    let mut sky_brightness_file = Input::default();
+    sky_brightness_file.set_value(preferences.sky_brightness_file.as_deref().unwrap_or(""));
+    sky_brightness_file.set_tooltip(tr("Latitude, longitude and SQM per line, empty to disable"));
+    let mut btn_browse: Listener<_> = button::Button::default().with_label("...").into();
+    btn_browse.clear_visible_focus();
+    half.fixed(&*btn_browse, scaled(30));
+    half.end();
+    let mut half = half_row();
+    let mut dem_directory = Input::default();
+    dem_directory.set_value(preferences.dem_directory.as_deref().unwrap_or(""));
+    dem_directory.set_tooltip(tr("Directory with .hgt tiles named like S24W047.hgt, empty to disable"));
+    let mut btn_browse_dem: Listener<_> = button::Button::default().with_label("...").into();
+    btn_browse_dem.clear_visible_focus();
+    half.fixed(&*btn_browse_dem, scaled(30));
+    half.end();
+    row.end();
+
+    // Satellite elements and mount driver
+    layout.labels([tr("Satellite elements (TLE)"), tr("Mount driver")]);
+    let row = layout.row(INPUT_HEIGHT);
+    let mut half = half_row();
+    let mut tle_file = Input::default();
+    tle_file.set_value(preferences.tle_file.as_deref().unwrap_or(""));
+    tle_file.set_tooltip(tr("Two-line element file, empty to download the Celestrak visual group once a day"));
+    let mut btn_browse_tle: Listener<_> = button::Button::default().with_label("...").into();
+    btn_browse_tle.clear_visible_focus();
+    half.fixed(&*btn_browse_tle, scaled(30));
+    half.end();
+    let mut mount_address = Input::default();
+    mount_address.set_value(preferences.mount_address.as_deref().unwrap_or(""));
+    mount_address.set_tooltip(tr("alpaca://host:port/device or indi://host:port/device, to sync the observatory site"));
+    row.end();
+    layout.divider();
+
+    let mut row = layout.row(BUTTON_HEIGHT);
+
+    // Apply button
+    let mut btn_apply: Listener<_> = button::Button::default().with_label(tr("Apply")).into();
+    btn_apply.clear_visible_focus();
+    row.fixed(&*btn_apply, scaled(BUTTON_WIDTH));
+    spacer();
+
+    // Close button
+    let mut btn_close: Listener<_> = button::Button::default().with_label(tr("Close")).into();
+    btn_close.clear_visible_focus();
+    row.fixed(&*btn_close, scaled(BUTTON_WIDTH));
+    row.end();
+
+    layout.end(&mut window);
+    window.show();
+    window.end();
+
+    let mut window_clone = window.clone();
+
+    // Window call back to avoid program termination when ESC is pressed
+    // from FLTK Book - FAQ
+    window.set_callback(|w| {
+        if fltk::app::event() == fltk::enums::Event::Close {
+            w.hide();
+        }
+    });
+
+    // Handlers for Close button
+    // preserve button's original color
+    let btn_close_color = btn_close.color();
+    // close window when clicked
+    btn_close.on_click(move |_| {
+        window_clone.hide();
+    });
+
+    // change color on hover
+    btn_close.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_close.on_leave(move |b| {
+        b.set_color(btn_close_color);
+    });
+
+    // Handlers for Browse button
+    let mut sky_brightness_browse_clone = sky_brightness_file.clone();
+    btn_browse.on_click(move |_| {
+        let mut dialog = FileDialog::new(FileDialogType::BrowseFile);
+        dialog.set_filter("Sky Brightness Grids\t*.{csv}");
+        dialog.show();
+
+        if let Some(filename) = dialog.filename().to_str() {
+            if !filename.is_empty() {
+                sky_brightness_browse_clone.set_value(filename);
+            }
+        }
+    });
+
+    let mut dem_browse_clone = dem_directory.clone();
+    btn_browse_dem.on_click(move |_| {
+        let mut dialog = FileDialog::new(FileDialogType::BrowseDir);
+        dialog.show();
+
+        if let Some(directory) = dialog.filename().to_str() {
+            if !directory.is_empty() {
+                dem_browse_clone.set_value(directory);
+            }
+        }
+    });
+
+    let mut tle_browse_clone = tle_file.clone();
+    btn_browse_tle.on_click(move |_| {
+        let mut dialog = FileDialog::new(FileDialogType::BrowseFile);
+        dialog.set_filter("Two-Line Elements\t*.{tle,txt}");
+        dialog.show();
+
+        if let Some(filename) = dialog.filename().to_str() {
+            if !filename.is_empty() {
+                tle_browse_clone.set_value(filename);
+            }
+        }
+    });
+
+    // Handlers for Apply button
+    // preserve button's original color
+    let btn_apply_color = btn_apply.color();
+    // Apply changes
+    let app_clone = Rc::clone(&application);
+    btn_apply.on_click(move |_| {
+        let mut app = app_clone.borrow_mut();
+        app.preferences.show_seconds = show_seconds.is_checked();
+        if let Some((hour12, month_first, month_names, _)) = TIME_FORMATS.get(time_format.value().max(0) as usize) {
+            app.preferences.hour12 = *hour12;
+            app.preferences.month_first = *month_first;
+            app.preferences.month_names = *month_names;
+        }
+        app.preferences.confirm_overwrite = confirm_overwrite.is_checked();
+        app.preferences.html_timeline = html_timeline.is_checked();
+        app.preferences.solar_midnight = solar_midnight.is_checked();
+        app.preferences.weather_forecast = weather_forecast.is_checked();
+        app.preferences.remember_date = remember_date.is_checked();
+        app.preferences.date_rollover =
+            DATE_ROLLOVERS.get(date_rollover.value().max(0) as usize).copied().unwrap_or_default();
+        if let Ok(hour) = rollover_hour.value().trim().parse::<u32>() {
+            app.preferences.rollover_hour = hour.min(23);
+        }
+        if let Ok(radius) = moon_avoidance.value().trim().parse::<i64>() {
+            app.preferences.moon_avoidance = radius.clamp(*MOON_SEPARATION_RANGE.start(), *MOON_SEPARATION_RANGE.end());
+        }
+        app.preferences.scripts = scripts.is_checked();
+        app.preferences.dms_coordinates = dms_coordinates.is_checked();
+        app.preferences.ui_scale = UI_SCALES.get(ui_scale.value().max(0) as usize).copied().flatten();
+        apply_ui_scale(app.preferences.ui_scale);
+        let file_path = sky_brightness_file.value().trim().to_string();
+        app.preferences.sky_brightness_file = if file_path.is_empty() { None } else { Some(file_path) };
+        let directory = dem_directory.value().trim().to_string();
+        app.preferences.dem_directory = if directory.is_empty() { None } else { Some(directory) };
+        app.preferences.satellites = satellites.is_checked();
+        app.preferences.light_bands = light_bands.is_checked();
+        app.preferences.zodiacal_light = zodiacal_light.is_checked();
+        app.preferences.lunar_observing = lunar_observing.is_checked();
+        app.preferences.alignment_stars = alignment_stars.is_checked();
+        app.preferences.field_rotation = field_rotation.is_checked();
+        let tle_path = tle_file.value().trim().to_string();
+        app.preferences.tle_file = if tle_path.is_empty() { None } else { Some(tle_path) };
+        let mount = mount_address.value().trim().to_string();
+        app.preferences.mount_address = if mount.is_empty() { None } else { Some(mount) };
+        app.preferences.language = LANGUAGES.get(language.value().max(0) as usize).copied().unwrap_or_default();
+        set_language(app.preferences.language);
+        if let Some(preset) = ACCURACY_PRESETS.get(accuracy.value().max(0) as usize) {
+            app.preferences.calculation = app.preferences.calculation.with_preset(preset);
+        }
+        app.preferences.calculation.refraction = refraction.is_checked();
+        app.preferences.units = Units {
+            elevation: ELEVATION_UNITS.get(elevation_unit.value().max(0) as usize).copied().unwrap_or_default(),
+            temperature: TEMPERATURE_UNITS.get(temperature_unit.value().max(0) as usize).copied().unwrap_or_default(),
+            pressure: PRESSURE_UNITS.get(pressure_unit.value().max(0) as usize).copied().unwrap_or_default(),
+        };
+    });
+
+    // change color on hover
+    btn_apply.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_apply.on_leave(move |b| {
+        b.set_color(btn_apply_color);
+    });
+
+    true
+}
@@ -0,0 +1,122 @@
+// src/menu/functions/alignment.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use fltk::browser::HoldBrowser;
+use fltk::prelude::{BrowserExt, GroupExt, WidgetBase, WidgetExt};
+use fltk::{button, enums};
+use fltk::enums::Align;
+use fltk::window::Window;
+use fltk_evented::Listener;
+use crate::application::application::Application;
+use crate::application::formatting::format_local;
+use crate::application::reports::{alignment_report, export_night, ALIGNMENT_STARS_FILE};
+use crate::application::stars::{alignment_stars, session_start, ALIGNMENT_MAX_ALTITUDE,
+                                ALIGNMENT_MIN_ALTITUDE, ALIGNMENT_STARS};
+use crate::menu::functions::export::confirm_export;
+use crate::utils::i18n::tr;
+use crate::widgets::label::Label;
+use crate::menu::window_manager::new_tool_window;
+
+pub fn handle_alignment(application: &mut Rc<RefCell<Application>>) -> Option<Window> {
+    let Some(mut window) = new_tool_window(tr("Alignment stars"), 450, 280) else {
+        return None;
+    };
+
+    // Session start, stars are suggested for the nautical dusk of the selected night
+    let session = {
+        let app = application.borrow();
+        session_start(&app.observer, &app.time, &app.environment, app.preferences.calculation)
+    };
+    Label::new(10, 10, 110, 20, tr("Nautical dusk:"), Align::Left | Align::Inside);
+    let mut start_label = Label::new(130, 10, 310, 20, "", Align::Left | Align::Inside);
+
+    // Star table
+    let mut table = HoldBrowser::new(10, 40, 430, 180, "");
+    table.set_column_widths(&[130, 110, 55, 65, 65]);
+    table.set_column_char('\t');
+    table.set_tooltip(&format!(
+        "{} {:.0}° - {:.0}°",
+        tr("Brightest stars spread across azimuths, altitude"), ALIGNMENT_MIN_ALTITUDE, ALIGNMENT_MAX_ALTITUDE
+    ));
+    table.add(&format!("@b{}\t@b{}\t@b{}\t@b{}\t@b{}", tr("Name"), tr("Star"), tr("Mag"), tr("Alt"), tr("Az")));
+    match session {
+        Some(jd) => {
+            let app = application.borrow();
+            start_label.set_label(&format!(
+                "{} ({})",
+                format_local(app.observer.local_time(jd), &app.preferences),
+                tr("local")
+            ));
+            for a in alignment_stars(&app.observer, jd, ALIGNMENT_STARS) {
+                table.add(&format!("{}\t{}\t{:.2}\t{:.1}°\t{:.1}°", a.star.name, a.star.designation,
+                                   a.star.magnitude, a.altitude, a.azimuth));
+            }
+        }
+        None => start_label.set_label(tr("No nautical dusk this night")),
+    }
+
+    // Export button
+    let mut btn_export: Listener<_> = button::Button::new(20, 235, 50, 30, tr("Export")).into();
+    btn_export.clear_visible_focus();
+    btn_export.set_tooltip(tr("Export the alignment stars to skycalc_alignment.csv"));
+
+    // Close button
+    let mut btn_close: Listener<_> = button::Button::new(380, 235, 50, 30, tr("Close")).into();
+    btn_close.clear_visible_focus();
+
+    window.end();
+    window.show();
+
+    let mut window_clone = window.clone();
+
+    // Window call back to avoid program termination when ESC is pressed
+    // from FLTK Book - FAQ
+    window.set_callback(|w| {
+        if fltk::app::event() == fltk::enums::Event::Close {
+            w.hide();
+        }
+    });
+
+    // Handlers for Close button
+    // preserve button's original color
+    let btn_close_color = btn_close.color();
+    // close window when clicked
+    btn_close.on_click(move |_| {
+        window_clone.hide();
+    });
+
+    // change color on hover
+    btn_close.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_close.on_leave(move |b| {
+        b.set_color(btn_close_color);
+    });
+
+    // Handlers for Export button
+    // preserve button's original color
+    let btn_export_color = btn_export.color();
+    // Export to file when clicked
+    let app_clone = Rc::clone(&application);
+    btn_export.on_click(move |_| {
+        let app = app_clone.borrow();
+        if confirm_export(&app, ALIGNMENT_STARS_FILE, &export_night(&app.time)) {
+            alignment_report(&app.observer, &app.time, &app.environment, &app.preferences);
+        }
+    });
+
+    // change color on hover
+    btn_export.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_export.on_leave(move |b| {
+        b.set_color(btn_export_color);
+    });
+
+    Some(window)
+}
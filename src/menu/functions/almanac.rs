@@ -0,0 +1,116 @@
+// src/menu/functions/almanac.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use fltk::button::CheckButton;
+use fltk::menu::Choice;
+use fltk::prelude::{ButtonExt, GroupExt, InputExt, MenuExt, WidgetBase, WidgetExt};
+use fltk::{button, enums};
+use fltk::enums::Shortcut;
+use fltk::input::IntInput;
+use fltk::window::Window;
+use fltk_evented::Listener;
+use crate::application::application::Application;
+use crate::application::reports::{almanac_report, export_month, ALMANAC_REPORT_FILE};
+use crate::menu::functions::export::{confirm_export, open_export};
+use crate::utils::i18n::{month_name, tr};
+use crate::widgets::keyboard::close_on_escape;
+use crate::widgets::layout::{scaled, spacer, FormLayout, BUTTON_HEIGHT, BUTTON_WIDTH, INPUT_HEIGHT};
+use crate::menu::window_manager::new_tool_window;
+
+pub fn handle_almanac(application: &mut Rc<RefCell<Application>>) -> Option<Window> {
+    let Some(mut window) = new_tool_window(tr("Monthly almanac"), scaled(290), 160) else {
+        return None;
+    };
+    let mut layout = FormLayout::new(&window);
+
+    // Month and year
+    layout.labels([tr("Month"), tr("Year")]);
+    let row = layout.row(INPUT_HEIGHT);
+    let mut month = Choice::default();
+    for i in 1..=12 {
+        month.add_choice(month_name(i));
+    }
+    month.set_value(application.borrow().time.month as i32 - 1);
+    let mut year = IntInput::default();
+    year.set_maximum_size(4);
+    year.set_value(&application.borrow().time.year.to_string());
+    row.end();
+
+    // CSV export
+    let mut csv = CheckButton::default().with_label(tr("Also as CSV"));
+    csv.set_checked(false);
+    csv.set_tooltip(tr("Write the same table to skycalc_almanac.csv, times in 24 hours"));
+    layout.fixed(&csv, INPUT_HEIGHT);
+
+    let mut row = layout.row(BUTTON_HEIGHT);
+
+    // Export button
+    let mut btn_export: Listener<_> = button::Button::default().with_label(tr("Export")).into();
+    btn_export.clear_visible_focus();
+    btn_export.set_tooltip(tr("Export twilights, sunrise and sunset, moonrise and moonset of every day to \
+skycalc_almanac.txt"));
+    row.fixed(&*btn_export, scaled(BUTTON_WIDTH));
+    spacer();
+
+    // Close button
+    let mut btn_close: Listener<_> = button::Button::default().with_label(tr("&Close")).into();
+    btn_close.clear_visible_focus();
+    btn_close.set_shortcut(Shortcut::Alt | 'c');
+    row.fixed(&*btn_close, scaled(BUTTON_WIDTH));
+    row.end();
+
+    layout.end(&mut window);
+    window.show();
+    window.end();
+
+    let mut window_clone = window.clone();
+
+    // Close with the window close button or Esc
+    close_on_escape(&mut window);
+
+    // Handlers for Close button
+    // preserve button's original color
+    let btn_close_color = btn_close.color();
+    // close window when clicked or with Alt+C
+    btn_close.set_callback(move |_| {
+        window_clone.hide();
+    });
+
+    // change color on hover
+    btn_close.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_close.on_leave(move |b| {
+        b.set_color(btn_close_color);
+    });
+
+    // Handlers for Export button
+    // preserve button's original color
+    let btn_export_color = btn_export.color();
+    // Export to file and open it when clicked
+    let app_clone = Rc::clone(application);
+    btn_export.on_click(move |_| {
+        let app = app_clone.borrow();
+        let year_value = year.value().parse::<i64>().unwrap_or(app.time.year);
+        let month_value = month.value().clamp(0, 11) as u64 + 1;
+        if confirm_export(&app, ALMANAC_REPORT_FILE, &export_month(year_value, month_value)) {
+            almanac_report(&app.observer, year_value, month_value, csv.is_checked(), &app.preferences);
+            open_export(ALMANAC_REPORT_FILE);
+        }
+    });
+
+    // change color on hover
+    btn_export.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_export.on_leave(move |b| {
+        b.set_color(btn_export_color);
+    });
+
+    Some(window)
+}
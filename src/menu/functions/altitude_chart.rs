@@ -0,0 +1,198 @@
+// src/menu/functions/altitude_chart.rs
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::Write;
+use std::rc::Rc;
+use fltk::browser::CheckBrowser;
+use fltk::dialog::alert_default;
+use fltk::enums::{FrameType, Shortcut};
+use fltk::frame::Frame;
+use fltk::menu::Choice;
+use fltk::prelude::{BrowserExt, GroupExt, MenuExt, WidgetBase, WidgetExt};
+use fltk::{button, enums};
+use fltk::window::Window;
+use fltk_evented::Listener;
+use crate::application::application::Application;
+use crate::application::reports::{altitude_chart_report, altitude_chart_title, export_night, export_stamp,
+                                  hourly_conditions_report, ALTITUDE_CHART_PNG_FILE, ALTITUDE_CHART_STEPS,
+                                  ALTITUDE_CHART_SVG_FILE, HOURLY_CONDITIONS_FILE};
+use crate::application::target::Target;
+use crate::application::time::Time;
+use crate::application::timeline::{AltitudeChart, TARGET_RGB};
+use crate::menu::functions::export::{confirm_export, open_export};
+use crate::menu::window_manager::new_tool_window;
+use crate::widgets::altitude_chart::{altitude_chart_png, draw_altitude_chart};
+use crate::widgets::keyboard::close_on_escape;
+use crate::widgets::layout::{fixed_label, scaled, spacer, FormLayout, BUTTON_HEIGHT, BUTTON_WIDTH};
+use crate::widgets::night_vision::night_color;
+
+// Width of the PNG export in pixels, the height follows
+const PNG_WIDTH: i32 = 1000;
+// File formats of the export, in the order of the choice, CSV is the hour by hour table
+const FORMATS: [&str; 3] = ["PNG", "SVG", "CSV"];
+
+// Targets checked in the list
+fn checked_targets(list: &CheckBrowser, targets: &[Target]) -> Vec<Target> {
+    targets
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| list.checked(*i as i32 + 1))
+        .map(|(_, t)| t.clone())
+        .collect()
+}
+
+// Chart of the checked targets in the selected night, with its title and time axis
+fn night_chart(app: &Application, list: &CheckBrowser, targets: &[Target]) -> (AltitudeChart, String, bool) {
+    let solar_midnight = app.preferences.solar_midnight;
+    let chart = AltitudeChart::new(&app.observer, &app.time, &checked_targets(list, targets), solar_midnight,
+                                   ALTITUDE_CHART_STEPS, app.preferences.calculation);
+    (chart, altitude_chart_title(&app.observer, &app.time), solar_midnight)
+}
+
+pub fn handle_altitude_chart(application: &mut Rc<RefCell<Application>>) -> Option<Window> {
+    let Some(mut window) = new_tool_window("Altitude chart", scaled(640), 520) else {
+        return None;
+    };
+    let targets = application.borrow().targets.clone();
+    let mut layout = FormLayout::new(&window);
+
+    // Targets of the configuration, the first ones checked
+    layout.labels(["Targets"]);
+    let mut list = CheckBrowser::default();
+    list.set_tooltip("Checked targets are drawn, each with its own color");
+    layout.fixed(&*list, scaled(100));
+    for (i, target) in targets.iter().enumerate() {
+        list.add(&target.name, i < TARGET_RGB.len());
+    }
+
+    // Preview of the chart of the selected night
+    let mut preview = Frame::default();
+    preview.set_frame(FrameType::FlatBox);
+    preview.set_color(enums::Color::Black);
+    layout.stretch(250);
+
+    let mut row = layout.row(BUTTON_HEIGHT);
+    fixed_label(&mut row, "Format", 50);
+    let mut format = Choice::default();
+    for name in FORMATS {
+        format.add_choice(name);
+    }
+    format.set_value(0);
+    format.set_tooltip("PNG to attach to posts and messages, SVG to scale or edit, CSV for the hour by hour \
+conditions with the checked targets");
+    row.fixed(&format, scaled(70));
+    spacer();
+
+    // Export button
+    let mut btn_export: Listener<_> = button::Button::default().with_label("Export").into();
+    btn_export.clear_visible_focus();
+    btn_export.set_tooltip("Export the chart to skycalc_altitude.png or skycalc_altitude.svg, or the hour by hour \
+table to skycalc_hourly.csv");
+    row.fixed(&*btn_export, scaled(BUTTON_WIDTH));
+    spacer();
+
+    // Close button
+    let mut btn_close: Listener<_> = button::Button::default().with_label("&Close").into();
+    btn_close.clear_visible_focus();
+    btn_close.set_shortcut(Shortcut::Alt | 'c');
+    row.fixed(&*btn_close, scaled(BUTTON_WIDTH));
+    row.end();
+
+    layout.end(&mut window);
+    window.show();
+    window.end();
+
+    let mut window_clone = window.clone();
+
+    // Close with the window close button or Esc
+    close_on_escape(&mut window);
+
+    // Chart of the checked targets, computed again when the checks change
+    let chart = Rc::new(RefCell::new(night_chart(&application.borrow(), &list, &targets)));
+    let (app_list_clone, chart_list_clone, targets_list_clone) = (Rc::clone(application), Rc::clone(&chart), targets.clone());
+    let mut preview_list_clone = preview.clone();
+    list.set_callback(move |list| {
+        *chart_list_clone.borrow_mut() = night_chart(&app_list_clone.borrow(), list, &targets_list_clone);
+        preview_list_clone.redraw();
+    });
+
+    let chart_draw = Rc::clone(&chart);
+    preview.draw(move |f| {
+        let (chart, title, solar_midnight) = &*chart_draw.borrow();
+        draw_altitude_chart(f.x(), f.y(), f.w(), f.h(), chart, title, *solar_midnight, night_color);
+    });
+
+    // Handlers for Close button
+    // preserve button's original color
+    let btn_close_color = btn_close.color();
+    // close window when clicked or with Alt+C
+    btn_close.set_callback(move |_| {
+        window_clone.hide();
+    });
+
+    // change color on hover
+    btn_close.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_close.on_leave(move |b| {
+        b.set_color(btn_close_color);
+    });
+
+    // Handlers for Export button
+    // preserve button's original color
+    let btn_export_color = btn_export.color();
+    // Export to file and open it when clicked, the chart is of the night selected now
+    let app_clone = Rc::clone(application);
+    btn_export.set_callback(move |_| {
+        let app = app_clone.borrow();
+        let night = export_night(&app.time);
+        match format.value() {
+            1 => {
+                if confirm_export(&app, ALTITUDE_CHART_SVG_FILE, &night) {
+                    altitude_chart_report(&app.observer, &app.time, &checked_targets(&list, &targets),
+                                          &app.preferences);
+                    open_export(ALTITUDE_CHART_SVG_FILE);
+                }
+                return;
+            }
+            2 => {
+                if confirm_export(&app, HOURLY_CONDITIONS_FILE, &night) {
+                    hourly_conditions_report(&app.observer, &app.time, &checked_targets(&list, &targets),
+                                             &app.preferences);
+                    open_export(HOURLY_CONDITIONS_FILE);
+                }
+                return;
+            }
+            _ => {}
+        }
+        if !confirm_export(&app, ALTITUDE_CHART_PNG_FILE, &night) {
+            return;
+        }
+        let (chart, title, solar_midnight) = night_chart(&app, &list, &targets);
+        let stamp = export_stamp(&app.observer, &night, &Time::now());
+        let written = altitude_chart_png(&chart, &title, solar_midnight, PNG_WIDTH, &stamp).and_then(|png| {
+            File::create(ALTITUDE_CHART_PNG_FILE)
+                .and_then(|mut f| f.write_all(&png))
+                .map_err(|e| e.to_string())
+        });
+        match written {
+            Ok(()) => open_export(ALTITUDE_CHART_PNG_FILE),
+            Err(e) => alert_default(&format!("Unable to write {}: {}", ALTITUDE_CHART_PNG_FILE, e)),
+        }
+    });
+
+    // change color on hover
+    btn_export.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_export.on_leave(move |b| {
+        b.set_color(btn_export_color);
+    });
+
+    Some(window)
+}
@@ -1,43 +1,115 @@
 use std::cell::RefCell;
+use std::ops::RangeInclusive;
 use std::rc::Rc;
-use fltk::prelude::{GroupExt, WidgetBase, WidgetExt, WindowExt};
-use fltk::{app, button, enums, window};
+use fltk::prelude::{ButtonExt, GroupExt, InputExt, WidgetBase, WidgetExt};
+use fltk::{button, dialog, enums};
+use fltk::button::CheckButton;
+use fltk::enums::Shortcut;
+use fltk::input::Input;
+use fltk::window::Window;
 use fltk_evented::Listener;
 use crate::application::application::Application;
+use crate::application::constraint::{Constraints, ALTITUDE_RANGE, MAX_TARGETS_RANGE, MOON_SEPARATION_RANGE,
+                                     OBSERVABLE_TIME_RANGE, SIZE_RANGE};
+use crate::application::undo::Setting;
+use crate::utils::i18n::tr;
+use crate::widgets::keyboard::{close_on_escape, enter_moves_focus};
+use crate::widgets::layout::{scaled, spacer, FormLayout, BUTTON_HEIGHT, BUTTON_WIDTH, INPUT_HEIGHT};
+use crate::widgets::validated::mark_input;
+use crate::menu::window_manager::new_tool_window;
 
-pub fn handle_constraint(application: &mut Rc<RefCell<Application>>) -> bool {
-    let mut window = window::Window::default()
-        .with_label("Constraint setup")
-        .with_size(290, 250)
-        .center_screen();
-    window.make_modal(true);
+// Whole number of the input within the range, the input is marked when it is not
+fn int_field(input: &mut Input, range: &RangeInclusive<i64>) -> Option<i64> {
+    let hint = format!("{} {} {}", range.start(), tr("to"), range.end());
+    match input.value().trim().parse::<i64>() {
+        Ok(value) if range.contains(&value) => {
+            mark_input(input, None, &hint);
+            Some(value)
+        }
+        _ => {
+            mark_input(input, Some(&format!("{} {}", tr("Enter a whole number from"), hint)), &hint);
+            None
+        }
+    }
+}
+
+// Input for an integer constraint
+fn new_field(value: i64) -> Input {
+    let mut input = Input::default();
+    input.set_maximum_size(4);
+    input.set_value(&value.to_string());
+    enter_moves_focus(&mut input);
+    input
+}
+
+pub fn handle_constraint(application: &mut Rc<RefCell<Application>>) -> Option<Window> {
+    let Some(mut window) = new_tool_window(tr("Constraint setup"), scaled(290), 250) else {
+        return None;
+    };
+    let constraints = application.borrow().constraints.clone();
+    let mut layout = FormLayout::new(&window);
+
+    // Altitude, degrees above the horizon
+    layout.labels([tr("Min altitude (°)"), tr("Max altitude (°)")]);
+    let row = layout.row(INPUT_HEIGHT);
+    let mut min_altitude = new_field(constraints.min_altitude);
+    let mut max_altitude = new_field(constraints.max_altitude);
+    row.end();
+
+    // Apparent size, arcminutes
+    layout.labels([tr("Min size (')"), tr("Max size (')")]);
+    let row = layout.row(INPUT_HEIGHT);
+    let mut min_size = new_field(constraints.min_size);
+    let mut max_size = new_field(constraints.max_size);
+    row.end();
+
+    // Moon distance and share of the night the target is up
+    layout.labels([tr("Moon separation (°)"), tr("Observable time (%)")]);
+    let row = layout.row(INPUT_HEIGHT);
+    let mut moon_separation = new_field(constraints.moon_separation);
+    let mut frac_observable_time = new_field(constraints.frac_observable_time);
+    frac_observable_time.set_tooltip(tr("Share of the observation window the target meets the constraints"));
+    row.end();
+
+    // Targets listed and observation window
+    layout.labels([tr("Max targets"), ""]);
+    let row = layout.row(INPUT_HEIGHT);
+    let mut max_targets = new_field(constraints.max_targets);
+    let mut use_darkness = CheckButton::default().with_label(tr("Darkness only"));
+    use_darkness.set_checked(constraints.use_darkness);
+    use_darkness.set_tooltip(tr("Observe in astronomical darkness instead of from sunset to sunrise"));
+    row.end();
+
+    let mut row = layout.row(BUTTON_HEIGHT);
 
     // Apply button
-    let mut btn_apply: Listener<_> = button::Button::new(20, 200, 50, 30, "Apply").into();
+    let mut btn_apply: Listener<_> = button::Button::default().with_label(tr("&Apply")).into();
     btn_apply.clear_visible_focus();
+    btn_apply.set_shortcut(Shortcut::Alt | 'a');
+    row.fixed(&*btn_apply, scaled(BUTTON_WIDTH));
+    spacer();
 
     // Close button
-    let mut btn_close: Listener<_> = button::Button::new(220, 200, 50, 30, "Close").into();
+    let mut btn_close: Listener<_> = button::Button::default().with_label(tr("&Close")).into();
     btn_close.clear_visible_focus();
+    btn_close.set_shortcut(Shortcut::Alt | 'c');
+    row.fixed(&*btn_close, scaled(BUTTON_WIDTH));
+    row.end();
 
+    layout.end(&mut window);
     window.show();
     window.end();
 
     let mut window_clone = window.clone();
 
-    // Window call back to avoid program termination when ESC is pressed
-    // from FLTK Book - FAQ
-    window.set_callback( |w| {
-        if fltk::app::event() == fltk::enums::Event::Close {
-            w.hide();
-        }
-    });
+    // Close with the window close button or Esc
+    close_on_escape(&mut window);
 
     // Handlers for Close button
     // preserve button's original color
     let btn_color = btn_close.color();
-    // close window when clicked
-    btn_close.on_click(move |b| {
+    // close window when clicked or with Alt+C
+    btn_close.set_callback(move |_| {
         window_clone.hide();
     });
 
@@ -54,11 +126,41 @@ pub fn handle_constraint(application: &mut Rc<RefCell<Application>>) -> bool {
     // Handlers for Apply button
     // preserve button's original color
     let btn_apply_color = btn_apply.color();
-    // Apply changes
-    let mut app_clone = Rc::clone(&application);
-    btn_apply.set_callback( move |_| {
-        todo!();
-     });
+    // Apply changes when clicked or with Alt+A
+    let app_clone = Rc::clone(application);
+    btn_apply.set_callback(move |_| {
+        // every field is checked so all invalid ones are marked, nothing is applied until they are fixed
+        let values = [
+            int_field(&mut min_altitude, &ALTITUDE_RANGE),
+            int_field(&mut max_altitude, &ALTITUDE_RANGE),
+            int_field(&mut min_size, &SIZE_RANGE),
+            int_field(&mut max_size, &SIZE_RANGE),
+            int_field(&mut moon_separation, &MOON_SEPARATION_RANGE),
+            int_field(&mut frac_observable_time, &OBSERVABLE_TIME_RANGE),
+            int_field(&mut max_targets, &MAX_TARGETS_RANGE),
+        ];
+        let [Some(min_altitude), Some(max_altitude), Some(min_size), Some(max_size), Some(moon_separation),
+             Some(frac_observable_time), Some(max_targets)] = values else {
+            return;
+        };
+
+        // update constraints, as one edit that Edit/Undo reverts
+        let constraints: Constraints = app_clone.borrow().constraints.clone().new(
+            min_altitude,
+            max_altitude,
+            min_size,
+            max_size,
+            moon_separation,
+            frac_observable_time,
+            max_targets,
+            use_darkness.is_checked(),
+        );
+        if let Err(e) = constraints.check() {
+            dialog::alert_default(&e);
+            return;
+        }
+        app_clone.borrow_mut().apply_setting(Setting::Constraints(constraints));
+    });
 
     // change color on hover
     btn_apply.on_hover(|b| {
@@ -70,11 +172,5 @@ pub fn handle_constraint(application: &mut Rc<RefCell<Application>>) -> bool {
         b.set_color(btn_apply_color);
     });
 
-    while window.shown() {
-        app::wait();
-        // Reduce frame updated to reduce CPU consumption
-        std::thread::sleep(std::time::Duration::from_millis(32));
-    }
-
-    true
+    Some(window)
 }
@@ -0,0 +1,155 @@
+// src/menu/functions/countdown.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use fltk::button::CheckButton;
+use fltk::prelude::{ButtonExt, GroupExt, WidgetBase, WidgetExt, WindowExt};
+use fltk::{app, button, enums};
+use fltk::enums::{Align, Event, Shortcut};
+use fltk::window::Window;
+use fltk_evented::Listener;
+use crate::application::application::Application;
+use crate::application::clock::WallClock;
+use crate::application::countdown::{format_countdown, upcoming_events, Countdown};
+use crate::application::formatting::format_local;
+use crate::application::time::Time;
+use crate::utils::i18n::tr;
+use crate::widgets::keyboard::close_on_escape;
+use crate::widgets::label::Label;
+use crate::menu::window_manager::{every_frame, new_tool_window};
+
+// Number of upcoming events listed
+const ROWS: i32 = 6;
+const ROW_HEIGHT: i32 = 22;
+// Window heights with and without the buttons
+const COMPACT_HEIGHT: i32 = 10 + ROWS * ROW_HEIGHT;
+const FULL_HEIGHT: i32 = COMPACT_HEIGHT + 60;
+
+// Compact mode hides the border and the buttons and shrinks the window to the events
+fn set_compact(window: &mut Window, compact: &mut CheckButton, btn_close: &mut button::Button, on: bool) {
+    let (x, y, w) = (window.x(), window.y(), window.w());
+    compact.set_checked(on);
+    if on {
+        compact.hide();
+        btn_close.hide();
+    } else {
+        compact.show();
+        btn_close.show();
+    }
+    window.set_border(!on);
+    window.resize(x, y, w, if on { COMPACT_HEIGHT } else { FULL_HEIGHT });
+}
+
+pub fn handle_countdown(application: &mut Rc<RefCell<Application>>) -> Option<Window> {
+    let Some(mut window) = new_tool_window(tr("Countdowns"), 310, FULL_HEIGHT) else {
+        return None;
+    };
+
+    // Event, local time and time left
+    let mut rows: Vec<(Label, Label, Label)> = (0..ROWS)
+        .map(|row| {
+            let y = 10 + row * ROW_HEIGHT;
+            (
+                Label::new(10, y, 140, 20, "", Align::Left | Align::Inside),
+                Label::new(150, y, 80, 20, "", Align::Right | Align::Inside),
+                Label::new(230, y, 70, 20, "", Align::Right | Align::Inside),
+            )
+        })
+        .collect();
+
+    // Compact mode, only the events without border and buttons
+    let mut compact = CheckButton::new(10, FULL_HEIGHT - 45, 120, 30, tr("Compact"));
+    compact.set_tooltip(tr("Hide the window border and buttons, double click the events to show them again, \
+ESC closes the window"));
+
+    // Close button
+    let mut btn_close: Listener<_> = button::Button::new(250, FULL_HEIGHT - 45, 50, 30, tr("&Close")).into();
+    btn_close.clear_visible_focus();
+    btn_close.set_shortcut(Shortcut::Alt | 'c');
+
+    // the panel stays over the other windows during the setup at the telescope, instead of
+    // blocking them like the other tool windows
+    window.make_modal(false);
+    window.show();
+    window.end();
+    window.set_on_top();
+
+    let mut window_clone = window.clone();
+
+    // Close with the window close button or Esc
+    close_on_escape(&mut window);
+
+    // Toggle compact mode with the check box, and back to the full window with a double click
+    let mut window_compact_clone = window.clone();
+    let mut btn_close_compact_clone = btn_close.clone();
+    compact.set_callback(move |c| {
+        let on = c.is_checked();
+        set_compact(&mut window_compact_clone, c, &mut btn_close_compact_clone, on);
+    });
+    let mut compact_clone = compact.clone();
+    let mut btn_close_full_clone = btn_close.clone();
+    window.handle(move |w, ev| {
+        if ev == Event::Push && app::event_clicks() && compact_clone.is_checked() {
+            set_compact(w, &mut compact_clone, &mut btn_close_full_clone, false);
+            true
+        } else {
+            false
+        }
+    });
+
+    // Handlers for Close button
+    // preserve button's original color
+    let btn_close_color = btn_close.color();
+    // close window when clicked or with Alt+C
+    btn_close.on_click(move |_| {
+        window_clone.hide();
+    });
+
+    // change color on hover
+    btn_close.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_close.on_leave(move |b| {
+        b.set_color(btn_close_color);
+    });
+
+    let mut events: Vec<Countdown> = Vec::new();
+    let mut clock = WallClock::default();
+    let app_clone = Rc::clone(application);
+    let mut window_frame = window.clone();
+    every_frame(&window, move || {
+        let now = Time::now().to_jd();
+        {
+            let app = app_clone.borrow();
+
+            // Search the events again once the first one has passed, or after a sleep so that
+            // the events missed meanwhile are skipped instead of counted down below zero
+            let resumed = clock.tick().is_some();
+            if resumed || events.first().is_none_or(|first| first.jd <= now) {
+                events = upcoming_events(&app.observer, &app.environment, app.preferences.calculation, now);
+            }
+
+            for (row, (event, time, left)) in rows.iter_mut().enumerate() {
+                match events.get(row) {
+                    Some(countdown) => {
+                        event.set_label(tr(countdown.event));
+                        time.set_label(&format_local(app.observer.local_time(countdown.jd), &app.preferences));
+                        left.set_label(&format_countdown(countdown.jd - now));
+                    }
+                    None => {
+                        event.set_label("");
+                        time.set_label("");
+                        left.set_label("");
+                    }
+                }
+            }
+        }
+
+        //Redraw window to update labels
+        window_frame.redraw();
+    });
+
+    Some(window)
+}
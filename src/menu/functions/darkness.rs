@@ -1,190 +1,319 @@
 // src/menu/functions/darkness.rs
 
 use crate::application::application::Application;
-use crate::utils::definers::TOOLTIP_DATE_INPUT;
-use crate::widgets::{date::DateInput, label::Label};
-use fltk::enums::{Align, Event, FrameType, Key};
+use crate::application::clock::{observing_night_at, observing_night_date, WallClock};
+use crate::application::preferences::DateRollover;
+use crate::application::undo::Setting;
+use crate::widgets::{date::DateInput, label::Label, timeline::TimelineWidget, timezone::TimezoneInput};
+use crate::widgets::keyboard::{close_on_escape, focus_next};
+use crate::widgets::layout::{fixed_label, label, relayout, scaled, spacer, FormLayout, BUTTON_HEIGHT, BUTTON_WIDTH,
+                             INPUT_HEIGHT, LABEL_HEIGHT};
+use crate::widgets::validated::ValidatedInput;
+use crate::utils::i18n::tr;
+use fltk::enums::{Event, FrameType, Key, Shortcut};
 use fltk::frame::Frame;
-use fltk::input::FloatInput;
-use fltk::prelude::{GroupExt, InputExt, WidgetBase, WidgetExt, WindowExt};
-use fltk::{app, button, enums, window};
+use fltk::input::Input;
+use fltk::menu::MenuItem;
+use fltk::prelude::{GroupExt, InputExt, ValuatorExt, WidgetBase, WidgetExt};
+use fltk::valuator::HorNiceSlider;
+use fltk::{app, button, dialog, enums};
+use fltk::window::Window;
 use fltk_evented::Listener;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::fmt::Display;
 use std::rc::Rc;
-use crate::application::darkness::Darkness;
-use crate::application::moon::Moon;
-use crate::application::reports::darkness_report;
-use crate::application::sun::RiseSetType::{Next};
-use crate::application::sun::Sun;
-use crate::application::sun::TwilightType::{AstronomicalTwilight, CivilTwilight, NauticalTwilight, RiseSet};
+use crate::application::formatting::{format_alt_az, format_elevation, format_latitude, format_local, format_longitude,
+                                     night_summary, NightEvents};
+use crate::application::observability::night_plan;
+use crate::application::observer::Observer;
+use crate::application::reports::{darkness_html_report, darkness_plan_report, darkness_print_report,
+                                  darkness_report, export_night, nina_sequence_report, DARKNESS_HTML_REPORT_FILE,
+                                  DARKNESS_PRINT_REPORT_FILE, DARKNESS_REPORT_FILE, NINA_SEQUENCE_FILE};
 use crate::application::time::Time;
+use crate::application::timeline::{sun_moon_alt_az, timeline_start};
 use crate::menu;
-
-fn calculate_sun(application: &Application) -> (String, String, String, String, String, String, String, String) {
-    let sun = Sun::new(&application.observer, &application.time, &application.environment);
-
-    // Rise/Set
-    let sunrise = sun.get_sunrise_local_str(Next, RiseSet, Some("short"));
-    let sunset = sun.get_sunset_local_str(Next, RiseSet, Some("short"));
-
-    // Civil twilight
-    let civ_tw_start = sun.get_sunset_local_str(Next, CivilTwilight, Some("short"));
-    let civ_tw_end = sun.get_sunrise_local_str(Next, CivilTwilight, Some("short"));
-
-    // Nautical twilight
-    let naut_tw_start = sun.get_sunset_local_str(Next, NauticalTwilight, Some("short"));
-    let naut_tw_end = sun.get_sunrise_local_str(Next, NauticalTwilight, Some("short"));
-
-    // Astronomical twilight
-    let astro_tw_start = sun.get_sunset_local_str(Next, AstronomicalTwilight, Some("short"));
-    let astro_tw_end = sun.get_sunrise_local_str(Next, AstronomicalTwilight, Some("short"));
-
-    (sunrise, sunset, civ_tw_start, civ_tw_end, naut_tw_start, naut_tw_end,
-     astro_tw_start, astro_tw_end)
+use crate::menu::functions::export::{confirm_export, open_export};
+use crate::application::quality::{NightQuality, QualityHistory};
+use crate::application::weather::{mean_cloud_cover, night_forecast, HourlyForecast, OpenMeteo};
+use crate::menu::window_manager::{every_frame, new_tool_window};
+use std::sync::mpsc::{channel, Receiver};
+
+// Number of hourly cells in the weather strip
+const WEATHER_CELLS: usize = 16;
+// Steps of the simulated time slider over the 24 hours, 5 minutes each
+const SIMULATION_STEPS: i32 = 288;
+
+// Cloud cover strip: one cell per night hour, light cells are cloudy and dark cells are clear
+fn update_weather_strip(cells: &mut [Frame], status: &mut Label,
+                        forecast: &Result<Vec<HourlyForecast>, String>, observer: &Observer) {
+    for cell in cells.iter_mut() {
+        cell.hide();
+    }
+    match forecast {
+        Ok(hours) if hours.is_empty() => status.set_label("Weather: no forecast for this night"),
+        Ok(hours) => {
+            status.set_label("");
+            for (cell, hour) in cells.iter_mut().zip(hours.iter()) {
+                let shade = (40.0 + hour.cloud_cover * 1.8) as u8;
+                let local = observer.local_time(hour.jd).to_local();
+                cell.set_color(enums::Color::from_rgb(shade, shade, shade));
+                cell.set_label_color(if hour.cloud_cover > 50.0 { enums::Color::Black } else { enums::Color::White });
+                cell.set_label(&format!("{:02}", local.hour));
+                cell.set_tooltip(&format!("{:02}h - clouds {:.0}%", local.hour, hour.cloud_cover));
+                cell.show();
+            }
+        }
+        Err(e) => status.set_label(&format!("Weather: {}", e)),
+    }
 }
 
-fn calculate_moon(application: &Application) -> (String, String) {
-    let moon = Moon::new(&application.observer, &application.time, &application.environment);
-    let moonrise = moon.get_moonrise_local_str(Next, Some("short"));
-    let moonset = moon.get_moonset_local_str(Next, Some("short"));
+// Records the quality of the selected night in the history, with the cloud cover of the forecast
+// when there is one. The night is computed and the file written on a worker thread, the window
+// keeps updating meanwhile.
+fn record_quality(application: &Application, forecast: &[HourlyForecast]) {
+    let (observer, time) = (application.observer.clone(), application.time.clone());
+    let cloud_cover = mean_cloud_cover(forecast);
+    std::thread::spawn(move || {
+        let quality = NightQuality::compute(&observer, &time, cloud_cover);
+        if let Err(e) = QualityHistory::record_night(&observer, quality) {
+            log::warn!("night quality history not saved error={}", e);
+        }
+    });
+}
 
-    (moonrise, moonset)
+// Places the plain text summary of the selected night on the clipboard
+fn copy_summary(application: &Application) {
+    let events = NightEvents::new(&application.observer, &application.time, &application.environment,
+                                  &application.preferences);
+    app::copy(&night_summary(&application.observer, &application.time, &events));
 }
 
-fn calculate_darkness(application: &Application) -> (String, String, String, String) {
-    let darkness = Darkness::new(&application.observer, &application.time, &application.environment);
-    let astronomical_dso_start = darkness.get_darkness_local_astronomical_start_str(Some("short"));
-    let astronomical_dso_end = darkness.get_darkness_local_astronomical_end_str(Some("short"));
-    let nautical_dso_start = darkness.get_darkness_local_nautical_start_str(Some("short"));
-    let nautical_dso_end = darkness.get_darkness_local_nautical_end_str(Some("short"));
+// Moves the darkness window to the observing night in progress, the date input shows its local date
+fn move_to_tonight(application: &Rc<RefCell<Application>>, date_input: &mut Input) {
+    let tonight = {
+        let app = application.borrow();
+        observing_night_date(Time::now().to_jd(), app.observer.timezone, app.preferences.rollover_hour as f64)
+    };
+    date_input.set_value(&tonight.to_string(Some("yyyymmdd")));
+    application.borrow_mut().time = tonight;
+}
 
-    (astronomical_dso_start, astronomical_dso_end, nautical_dso_start, nautical_dso_end)
+// Sets the observer timezone as an edit that Edit/Undo reverts
+fn apply_timezone(application: &Rc<RefCell<Application>>, timezone: f64) {
+    let mut observer = application.borrow().observer.clone();
+    observer.timezone = timezone;
+    application.borrow_mut().apply_setting(Setting::Observer(observer));
 }
 
-pub fn handle_darkness(mut application: &mut Rc<RefCell<Application>>) -> bool {
-    let mut window = window::Window::default()
-        .with_label("Darkness Calculator")
-        .with_size(450, 480)
-        .center_screen();
-    window.make_modal(true);
+pub fn handle_darkness(mut application: &mut Rc<RefCell<Application>>) -> Option<Window> {
+    let light_bands = application.borrow().preferences.light_bands;
+    let Some(mut window) = new_tool_window(tr("Darkness Calculator"), scaled(500), 620) else {
+        return None;
+    };
+    let mut layout = FormLayout::new(&window);
 
     // Observatory
-    Label::new(10, 10, 60, 20, "Observatory:", Align::Left | Align::Inside);
-    let mut _observatory = Label::new(100, 10, 200, 20, "", Align::Left | Align::Inside);
+    let mut row = layout.row(LABEL_HEIGHT);
+    fixed_label(&mut row, tr("Observatory:"), 90);
+    let mut _observatory = label("");
     if let Some(name_str) = &application.borrow_mut().observer.name {
         _observatory.set_label(name_str.as_str());
     }
-
-    // Latitude
-    Label::new(10, 35, 60, 20, "Latitude:", Align::Left | Align::Inside);
-    let mut _latitude = Label::new(75, 35, 130, 20, "", Align::Left | Align::Inside);
-    _latitude.set_label(&format!("{:.6}",&application.borrow_mut().observer.latitude));
-
-    // Longitude
-    Label::new(160, 35, 60, 20, "Longitude:", Align::Left | Align::Inside);
-    let mut _longitude = Label::new(235, 35, 130, 20, "", Align::Left | Align::Inside);
-    _longitude.set_label(&format!("{:.6}",&application.borrow_mut().observer.longitude));
-
-    // Elevation
-    Label::new(330, 35, 60, 20, "Elevation:", Align::Left | Align::Inside);
-    let mut _elevation = Label::new(400, 35, 130, 20, "", Align::Left | Align::Inside);
-    _elevation.set_label(&application.borrow_mut().observer.elevation.to_string());
-
-    // Date
-    Label::new(10, 65, 80, 20, "Date:", Align::Left | Align::Inside);
-    let mut date = DateInput::new(60, 65, 100, 20, "");
+    row.end();
+
+    // Latitude, longitude and elevation
+    let mut row = layout.row(LABEL_HEIGHT);
+    fixed_label(&mut row, "Latitude:", 60);
+    let mut _latitude = label("");
+    _latitude.set_label(&format_latitude(application.borrow().observer.latitude, &application.borrow().preferences));
+    fixed_label(&mut row, "Longitude:", 70);
+    let mut _longitude = label("");
+    _longitude.set_label(&format_longitude(application.borrow().observer.longitude, &application.borrow().preferences));
+    fixed_label(&mut row, tr("Elevation:"), 65);
+    let mut _elevation = fixed_label(&mut row, "", 55);
+    _elevation.set_label(&format_elevation(application.borrow().observer.elevation, &application.borrow().preferences));
+    row.end();
+
+    // Date, timezone and observatory button
+    let mut row = layout.row(INPUT_HEIGHT);
+    fixed_label(&mut row, tr("Date:"), 45);
+    let mut date = DateInput::new(0, 0, 0, 0, "");
     date.validate(); // populate date input field with now() when window is opened
-    date.set_tooltip(TOOLTIP_DATE_INPUT);
-
-    // Timezone
-    Label::new(180, 65, 80, 20, "Timezone:", Align::Left | Align::Inside);
-    let mut timezone = FloatInput::new(260, 65, 50, 20, "");
+    row.fixed(&date.date_input, scaled(100));
+    spacer();
+    fixed_label(&mut row, tr("Timezone:"), 75);
+    let mut timezone = TimezoneInput::new(0, 0, 0, 0, "");
     timezone.set_value(&application.borrow_mut().observer.timezone.to_string());
-
-    // Observatory button
-    let mut btn_observatory: Listener<_> = button::Button::new(350, 65, 80, 20, "Obs. Setup").into();
+    row.fixed(&timezone.timezone_input, scaled(50));
+    spacer();
+    let mut btn_observatory: Listener<_> = button::Button::default().with_label(tr("Obs. Setup")).into();
     btn_observatory.clear_visible_focus();
+    row.fixed(&*btn_observatory, scaled(80));
+    row.end();
+
+    layout.divider();
+
+    // Sunrise / sunset, then the twilights: evening on the left and morning on the right
+    let [_, mut sunset_label, _, mut sunrise_label] = layout.labels([tr("Sunset"), "", tr("Sunrise"), ""]);
+    let [mut sunset_az, mut sunset_direction_label, mut sunrise_az, mut sunrise_direction_label] =
+        layout.labels([tr("Sunset az."), "", tr("Sunrise az."), ""]);
+    sunset_az.set_tooltip(tr("Azimuth from north through east where the Sun sets"));
+    sunrise_az.set_tooltip(tr("Azimuth from north through east where the Sun rises"));
+    let [_, mut civ_tw_start_label, _, mut civ_tw_end_label] =
+        layout.labels([tr("Civ Tw end"), "", tr("Civ Tw start"), ""]);
+    let [_, mut naut_tw_start_label, _, mut naut_tw_end_label] =
+        layout.labels([tr("Naut Tw end"), "", tr("Naut Tw start"), ""]);
+    let [_, mut astro_tw_start_label, _, mut astro_tw_end_label] =
+        layout.labels([tr("Astro Tw end"), "", tr("Astro Tw start"), ""]);
+
+    // Golden and blue hours, evening on the left and morning on the right like the twilights
+    let mut band_labels = light_bands.then(|| {
+        layout.divider();
+        let [mut golden_start, golden_evening_label, mut golden_end, golden_morning_label] =
+            layout.labels([tr("Golden start"), "", tr("Golden end"), ""]);
+        golden_start.set_tooltip(tr("Evening golden hour, Sun from 6° down to -4°"));
+        golden_end.set_tooltip(tr("Morning golden hour, Sun from -4° up to 6°"));
+        let [mut blue_start, blue_evening_label, mut blue_end, blue_morning_label] =
+            layout.labels([tr("Blue start"), "", tr("Blue end"), ""]);
+        blue_start.set_tooltip(tr("Evening blue hour, Sun from -4° down to -6° at the end of civil twilight"));
+        blue_end.set_tooltip(tr("Morning blue hour, Sun from -6° at the start of civil twilight up to -4°"));
+        [golden_evening_label, golden_morning_label, blue_evening_label, blue_morning_label]
+    });
 
-    // Divider
-    Frame::new(10, 100, 430, 1, "").set_frame(FrameType::BorderBox);
-
-    // Sunrise / sunset
-    Label::new(10, 110, 80, 20, "Sunset", Align::Left | Align::Inside);
-    let mut sunset_label = Label::new(120, 110, 80, 20, "", Align::Left | Align::Inside);
-    Label::new(230, 110, 80, 20, "Sunrise", Align::Left | Align::Inside);
-    let mut sunrise_label = Label::new(340, 110, 80, 20, "", Align::Left | Align::Inside);
-
-    // Civil twilight
-    Label::new(10, 130, 80, 20, "Civ Tw end", Align::Left | Align::Inside);
-    let mut civ_tw_start_label = Label::new(120, 130, 80, 20, "", Align::Left | Align::Inside);
-    Label::new(230, 130, 80, 20, "Civ Tw start", Align::Left | Align::Inside);
-    let mut civ_tw_end_label = Label::new(340, 130, 80, 20, "", Align::Left | Align::Inside);
-
-    // Nautical twilight
-    Label::new(10, 150, 80, 20, "Naut Tw end", Align::Left | Align::Inside);
-    let mut naut_tw_start_label = Label::new(120, 150, 80, 20, "", Align::Left | Align::Inside);
-    Label::new(230, 150, 80, 20, "Naut Tw start", Align::Left | Align::Inside);
-    let mut naut_tw_end_label = Label::new(340, 150, 80, 20, "", Align::Left | Align::Inside);
-
-    // Astronomical twilight
-    Label::new(10, 170, 80, 20, "Astro Tw end", Align::Left | Align::Inside);
-    let mut astro_tw_start_label = Label::new(120, 170, 80, 20, "", Align::Left | Align::Inside);
-    Label::new(230, 170, 80, 20, "Astro Tw start", Align::Left | Align::Inside);
-    let mut astro_tw_end_label = Label::new(340, 170, 80, 20, "", Align::Left | Align::Inside);
-
-    // Divider
-    Frame::new(10, 200, 430, 1, "").set_frame(FrameType::BorderBox);
+    layout.divider();
 
     // Moon rise / Moon set
-    Label::new(10, 210, 80, 20, "Moon rise", Align::Left | Align::Inside);
-    let mut moonrise_label = Label::new(120, 210, 80, 20, "", Align::Left | Align::Inside);
-    Label::new(230, 210, 80, 20, "Moon set", Align::Left | Align::Inside);
-    let mut moonset_label = Label::new(340, 210, 80, 20, "", Align::Left | Align::Inside);
+    let [_, mut moonrise_label, _, mut moonset_label] = layout.labels([tr("Moon rise"), "", tr("Moon set"), ""]);
+    let [mut moonrise_az, mut moonrise_direction_label, mut moonset_az, mut moonset_direction_label] =
+        layout.labels([tr("Moon rise az."), "", tr("Moon set az."), ""]);
+    moonrise_az.set_tooltip(tr("Azimuth from north through east where the Moon rises"));
+    moonset_az.set_tooltip(tr("Azimuth from north through east where the Moon sets"));
+    let [_, mut libration_label, _, mut moon_axis_label] = layout.labels([tr("Libration"), "", tr("Axis PA"), ""]);
+    libration_label.set_tooltip(tr("Selenographic longitude and latitude of the sub-Earth point"));
 
-    // Divider
-    Frame::new(10, 240, 430, 1, "").set_frame(FrameType::BorderBox);
+    layout.divider();
 
     // DSO Astro - Deep Sky Object darkness for astronomical rise and set
-    Label::new(10, 250, 80, 20, "DSO Astro start", Align::Left | Align::Inside);
-    let mut astronomical_dso_start_label = Label::new(120, 250, 80, 20, "", Align::Left | Align::Inside);
-    Label::new(230, 250, 80, 20, "DSO Astro end", Align::Left | Align::Inside);
-    let mut astronomical_dso_end_label = Label::new(340, 250, 80, 20, "", Align::Left | Align::Inside);
+    let [_, mut astronomical_dso_start_label, _, mut astronomical_dso_end_label] =
+        layout.labels([tr("DSO Astro start"), "", tr("DSO Astro end"), ""]);
     // DSO Naut - Deep Sky Object darkness for nautical rise and set
-    Label::new(10, 270, 80, 20, "DSO Naut start", Align::Left | Align::Inside);
-    let mut nautical_dso_start_label = Label::new(120, 270, 80, 20, "", Align::Left | Align::Inside);
-    Label::new(230, 270, 80, 20, "DSO Naut end", Align::Left | Align::Inside);
-    let mut nautical_dso_end_label = Label::new(340, 270, 80, 20, "dd-mm hh:mm", Align::Left | Align::Inside);
+    let [_, mut nautical_dso_start_label, _, mut nautical_dso_end_label] =
+        layout.labels([tr("DSO Naut start"), "", tr("DSO Naut end"), "dd-mm hh:mm"]);
 
-    // Divider
-    Frame::new(10, 300, 430, 1, "").set_frame(FrameType::BorderBox);
+    layout.divider();
 
     // NB Astro - Narrow band darkness for astronomical rise and set
-    Label::new(10, 310, 80, 20, "NB Astro start", Align::Left | Align::Inside);
-    let mut astronomical_nb_start_label = Label::new(120, 310, 80, 20, "", Align::Left | Align::Inside);
-    Label::new(230, 310, 80, 20, "NB Astro end", Align::Left | Align::Inside);
-    let mut astronomical_nb_end_label = Label::new(340, 310, 80, 20, "", Align::Left | Align::Inside);
+    let [_, mut astronomical_nb_start_label, _, mut astronomical_nb_end_label] =
+        layout.labels([tr("NB Astro start"), "", tr("NB Astro end"), ""]);
     // NB Naut - Narrow band darkness for nautical rise and set
-    Label::new(10, 330, 80, 20, "NB Naut start", Align::Left | Align::Inside);
-    let mut nautical_nb_start_label = Label::new(120, 330, 80, 20, "", Align::Left | Align::Inside);
-    Label::new(230, 330, 80, 20, "NB Naut end", Align::Left | Align::Inside);
-    let mut nautical_nb_end_label = Label::new(340, 330, 80, 20, "", Align::Left | Align::Inside);
-
-    // Divider
-    Frame::new(10, 360, 430, 1, "").set_frame(FrameType::BorderBox);
+    let [_, mut nautical_nb_start_label, _, mut nautical_nb_end_label] =
+        layout.labels([tr("NB Naut start"), "", tr("NB Naut end"), ""]);
+
+    layout.divider();
+
+    // Polar day/night and circumpolar Moon, empty when Sun and Moon rise and set normally
+    let [mut sun_circumpolar_label] = layout.labels([""]);
+    let [mut moon_circumpolar_label] = layout.labels([""]);
+
+    // Weather summary strip, filled in the background when the forecast preference is enabled.
+    // The status takes the width the hourly cells shown leave.
+    let mut weather_row = layout.row(LABEL_HEIGHT);
+    let mut weather_status = label("");
+    let mut weather_cells: Vec<Frame> = (0..WEATHER_CELLS)
+        .map(|_| {
+            let mut cell = Frame::default();
+            cell.set_frame(FrameType::FlatBox);
+            cell.set_label_size(10);
+            cell.hide();
+            weather_row.fixed(&cell, scaled(26));
+            cell
+        })
+        .collect();
+    weather_row.end();
+
+    // Night timeline with the current time, it grows with the window
+    let timeline = TimelineWidget::new(0, 0, 0, 0);
+    layout.stretch(64);
+
+    // Simulated time: the slider scrubs through the 24 hours of the timeline, the Sun and Moon
+    // readouts and the timeline cursor follow it until Now is clicked
+    let mut row = layout.row(INPUT_HEIGHT);
+    fixed_label(&mut row, tr("Simulate:"), 65);
+    let mut simulate = HorNiceSlider::default();
+    simulate.set_range(0.0, 1.0);
+    simulate.set_step(1.0, SIMULATION_STEPS);
+    simulate.set_tooltip(tr("Drag to see the Sun and Moon at any time of the night, the date and the clock are kept"));
+    let mut simulated_label = fixed_label(&mut row, "", 110);
+    let mut btn_now: Listener<_> = button::Button::default().with_label(tr("Now")).into();
+    btn_now.clear_visible_focus();
+    btn_now.set_tooltip(tr("Back to the current time"));
+    row.fixed(&*btn_now, scaled(BUTTON_WIDTH));
+    row.end();
+    let [_, mut sun_alt_az_label, _, mut moon_alt_az_label] =
+        layout.labels([tr("Sun alt/az"), "", tr("Moon alt/az"), ""]);
+
+    let mut row = layout.row(BUTTON_HEIGHT);
 
     // Export button
-    let mut btn_export: Listener<_> = button::Button::new(20, 430, 50, 30, "Export").into();
+    let mut btn_export: Listener<_> = button::Button::default().with_label(tr("Export")).into();
     btn_export.clear_visible_focus();
-
-    // TODO Add buttons previous day - today - next day
+    row.fixed(&*btn_export, scaled(BUTTON_WIDTH));
+
+    // N.I.N.A. sequence export button
+    let mut btn_nina: Listener<_> = button::Button::default().with_label("NINA").into();
+    btn_nina.clear_visible_focus();
+    btn_nina.set_tooltip("Export targets as a N.I.N.A. sequence for the dark window");
+    row.fixed(&*btn_nina, scaled(BUTTON_WIDTH));
+
+    // HTML export button
+    let mut btn_html: Listener<_> = button::Button::default().with_label("HTML").into();
+    btn_html.clear_visible_focus();
+    btn_html.set_tooltip("Export the report as a dark themed web page to skycalc.html");
+    row.fixed(&*btn_html, scaled(BUTTON_WIDTH));
+    spacer();
+
+    // Day stepping buttons
+    let mut btn_previous_day: Listener<_> = button::Button::default().with_label("@<").into();
+    btn_previous_day.clear_visible_focus();
+    btn_previous_day.set_tooltip(tr("Previous day"));
+    row.fixed(&*btn_previous_day, scaled(BUTTON_WIDTH));
+    let mut btn_today: Listener<_> = button::Button::default().with_label(tr("Today")).into();
+    btn_today.clear_visible_focus();
+    btn_today.set_tooltip(tr("Today"));
+    row.fixed(&*btn_today, scaled(BUTTON_WIDTH));
+    let mut btn_next_day: Listener<_> = button::Button::default().with_label("@>").into();
+    btn_next_day.clear_visible_focus();
+    btn_next_day.set_tooltip(tr("Next day"));
+    row.fixed(&*btn_next_day, scaled(BUTTON_WIDTH));
+    spacer();
+
+    // Print button
+    let mut btn_print: Listener<_> = button::Button::default().with_label("@fileprint").into();
+    btn_print.clear_visible_focus();
+    btn_print.set_tooltip("Print or save as PDF: opens a printable page of the report in the web browser");
+    row.fixed(&*btn_print, scaled(32));
+
+    // Copy button
+    let mut btn_copy: Listener<_> = button::Button::default().with_label(tr("Copy")).into();
+    btn_copy.clear_visible_focus();
+    btn_copy.set_tooltip(tr("Copy a summary of the night to the clipboard"));
+    row.fixed(&*btn_copy, scaled(40));
 
     // Close button
-    let mut btn_close: Listener<_> = button::Button::new(380, 430, 50, 30, "Close").into();
+    let mut btn_close: Listener<_> = button::Button::default().with_label(tr("&Close")).into();
     btn_close.clear_visible_focus();
+    btn_close.set_shortcut(Shortcut::Alt | 'c');
+    row.fixed(&*btn_close, scaled(BUTTON_WIDTH));
+    row.end();
+
+    layout.end(&mut window);
+    let mut weather_night: Option<String> = None;
+    let mut quality_night: Option<String> = None;
+    let mut weather_receiver: Option<Receiver<Result<Vec<HourlyForecast>, String>>> = None;
 
     window.end();
     window.show();
+    timeline.start(Rc::clone(&application));
 
     let mut window_clone = window.clone();
     let date_input_clone = date.date_input.clone();
@@ -193,13 +322,8 @@ pub fn handle_darkness(mut application: &mut Rc<RefCell<Application>>) -> bool {
     let mut application_clone = Rc::clone(&application);
     let mut application_clone_calculations = Rc::clone(&application);
 
-    // Window call back to avoid program termination when ESC is pressed
-    // from FLTK Book - FAQ
-    window.set_callback( |w| {
-        if fltk::app::event() == enums::Event::Close {
-            w.hide();
-        }
-    });
+    // Close with the window close button or Esc
+    close_on_escape(&mut window);
 
     // Listener::from_widget(date_input_clone).on(enums::Event::Unfocus, move |_| {
     //     date.validate();
@@ -215,32 +339,27 @@ pub fn handle_darkness(mut application: &mut Rc<RefCell<Application>>) -> bool {
     //     application_clone.borrow_mut().time.year = date.get_year();
     // });
 
-    date_input_clone.clone().handle(move |_, ev| {
+    date_input_clone.clone().handle(move |i, ev| {
         match ev {
             Event::Unfocus => {
-                date.validate();
-                application_clone.borrow_mut().time.day = date.get_day();
-                application_clone.borrow_mut().time.month = date.get_month();
-                application_clone.borrow_mut().time.year = date.get_year();
-
-                // TODO Add this code to the "increment a day" button
-                // let new_date = Time::from_jd(application_clone.borrow().time.to_jd() + 1.0);
-                // date.set_value(&new_date.to_string(Some("yyyymmdd")));
-
+                // an invalid date is flagged and the selected date is kept
+                if date.validate() {
+                    application_clone.borrow_mut().time.day = date.get_day();
+                    application_clone.borrow_mut().time.month = date.get_month();
+                    application_clone.borrow_mut().time.year = date.get_year();
+                }
                 true
             }
             Event::KeyDown => {
                 let key = app::event_key();
-                if key == Key::Enter {
-                    date.validate();
+                if key == Key::Enter && date.validate() {
                     let mut app = application_clone.borrow_mut();
                     app.time.day = date.get_day();
                     app.time.month = date.get_month();
                     app.time.year = date.get_year();
 
-                    // Optionally move focus
-                    // next_widget.take_focus();
-
+                    // committed, on to the timezone
+                    focus_next(i);
                     true
                 } else {
                     false
@@ -256,23 +375,22 @@ pub fn handle_darkness(mut application: &mut Rc<RefCell<Application>>) -> bool {
     //     app_clone.borrow_mut().observer.timezone = timezone_value;
     //     timezone.set_value(&app_clone.borrow_mut().observer.timezone.to_string());
     // });
-    timezone_input_clone.clone().handle(move |_, ev| {
+    timezone_input_clone.clone().handle(move |i, ev| {
         match ev {
             Event::Unfocus => {
-                let timezone_value = timezone.value().parse::<f64>().unwrap_or(0.0); // Handle potential parse errors. Default to 0.0
-                app_clone.borrow_mut().observer.timezone = timezone_value;
-                timezone.set_value(&app_clone.borrow_mut().observer.timezone.to_string());
+                // an invalid offset is flagged and the observer keeps its timezone
+                if let Some(timezone_value) = timezone.check() {
+                    apply_timezone(&app_clone, timezone_value);
+                }
                 true
             }
             Event::KeyDown => {
                 let key = app::event_key();
                 if key == Key::Enter {
-                    let timezone_value = timezone.value().parse::<f64>().unwrap_or(0.0); // Handle potential parse errors. Default to 0.0
-                    app_clone.borrow_mut().observer.timezone = timezone_value;
-                    timezone.set_value(&app_clone.borrow_mut().observer.timezone.to_string());
-
-                    // Optionally move focus
-                    // next_widget.take_focus();
+                    if let Some(timezone_value) = timezone.check() {
+                        apply_timezone(&app_clone, timezone_value);
+                        focus_next(i);
+                    }
                     true
                 } else {
                     false
@@ -283,11 +401,28 @@ pub fn handle_darkness(mut application: &mut Rc<RefCell<Application>>) -> bool {
     });
 
 
+    // Context menu, a right click outside the inputs copies a summary of the night to the clipboard
+    let application_clone_copy = application.clone();
+    let date_input_copy = date_input_clone.clone();
+    let timezone_input_copy = timezone_input_clone.clone();
+    window.handle(move |_, ev| {
+        if ev == Event::Push && app::event_mouse_button() == app::MouseButton::Right
+            && !app::event_inside_widget(&date_input_copy) && !app::event_inside_widget(&*timezone_input_copy) {
+            let menu = MenuItem::new(&[tr("Copy summary")]);
+            if menu.popup(app::event_x(), app::event_y()).is_some() {
+                copy_summary(&application_clone_copy.borrow());
+            }
+            true
+        } else {
+            false
+        }
+    });
+
     // Handlers for Close button
     // preserve button's original color
     let btn_close_color = btn_close.color();
-    // close window when clicked
-    btn_close.on_click(move |b| {
+    // close window when clicked or with Alt+C
+    btn_close.set_callback(move |_| {
         window_clone.hide();
     });
 
@@ -305,11 +440,22 @@ pub fn handle_darkness(mut application: &mut Rc<RefCell<Application>>) -> bool {
     // preserve button's original color
     let btn_export_color = btn_export.color();
     // Export to file when clicked
-    let mut application_clone_darkness_report = application.clone();
+    let application_clone_darkness_report = application.clone();
     btn_export.on_click(move |_| {
-        darkness_report(&application_clone_darkness_report.borrow().observer,
-                        &application_clone_darkness_report.borrow().time,
-                        &application_clone_darkness_report.borrow().environment);
+        let app = application_clone_darkness_report.borrow();
+        if confirm_export(&app, DARKNESS_REPORT_FILE, &export_night(&app.time)) {
+            // the report downloads the forecast and the satellite elements, so it is written on a
+            // worker thread and the window stays responsive meanwhile
+            let (observer, time, environment) = (app.observer.clone(), app.time.clone(), app.environment.clone());
+            let (constraints, targets) = (app.constraints.clone(), app.targets.clone());
+            let preferences = app.preferences.clone();
+            std::thread::spawn(move || {
+                match night_plan(&observer, &time, &environment, &constraints, &targets) {
+                    Some(plan) => darkness_plan_report(&observer, &time, &environment, &preferences, &plan, &targets),
+                    None => darkness_report(&observer, &time, &environment, &preferences),
+                }
+            });
+        }
     });
 
     // change color on hover
@@ -322,20 +468,147 @@ pub fn handle_darkness(mut application: &mut Rc<RefCell<Application>>) -> bool {
         b.set_color(btn_export_color);
     });
 
+    // Handlers for N.I.N.A. export button
+    // preserve button's original color
+    let btn_nina_color = btn_nina.color();
+    // Export sequence to file when clicked
+    let application_clone_nina_report = application.clone();
+    btn_nina.on_click(move |_| {
+        let app = application_clone_nina_report.borrow();
+        if confirm_export(&app, NINA_SEQUENCE_FILE, &export_night(&app.time)) {
+            nina_sequence_report(&app.observer, &app.time, &app.environment, &app.targets);
+        }
+    });
+
+    // change color on hover
+    btn_nina.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_nina.on_leave(move |b| {
+        b.set_color(btn_nina_color);
+    });
+
+    // Handlers for HTML export button
+    // preserve button's original color
+    let btn_html_color = btn_html.color();
+    // Export web page when clicked
+    let application_clone_html_report = application.clone();
+    btn_html.on_click(move |_| {
+        let app = application_clone_html_report.borrow();
+        if confirm_export(&app, DARKNESS_HTML_REPORT_FILE, &export_night(&app.time)) {
+            darkness_html_report(&app.observer, &app.time, &app.environment, &app.preferences);
+        }
+    });
+
+    // change color on hover
+    btn_html.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_html.on_leave(move |b| {
+        b.set_color(btn_html_color);
+    });
+
+    // Handlers for Print button
+    // preserve button's original color
+    let btn_print_color = btn_print.color();
+    // Export the printable page and open it in the browser when clicked
+    let application_clone_print_report = application.clone();
+    btn_print.on_click(move |_| {
+        let app = application_clone_print_report.borrow();
+        if confirm_export(&app, DARKNESS_PRINT_REPORT_FILE, &export_night(&app.time)) {
+            darkness_print_report(&app.observer, &app.time, &app.environment, &app.constraints, &app.targets,
+                                  &app.preferences);
+            open_export(DARKNESS_PRINT_REPORT_FILE);
+        }
+    });
+
+    // change color on hover
+    btn_print.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_print.on_leave(move |b| {
+        b.set_color(btn_print_color);
+    });
+
+    // Handlers for Copy button
+    // preserve button's original color
+    let btn_copy_color = btn_copy.color();
+    // Copy the summary of the selected night when clicked
+    let application_clone_copy_button = application.clone();
+    btn_copy.on_click(move |_| {
+        copy_summary(&application_clone_copy_button.borrow());
+    });
+
+    // change color on hover
+    btn_copy.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_copy.on_leave(move |b| {
+        b.set_color(btn_copy_color);
+    });
+
+    // Handlers for day stepping buttons, the date input shows the selected day
+    let mut application_previous_day = Rc::clone(&application);
+    let mut date_previous_day = date_input_clone.clone();
+    btn_previous_day.on_click(move |_| {
+        let time = application_previous_day.borrow().time.add_days(-1);
+        date_previous_day.set_value(&time.to_string(Some("yyyymmdd")));
+        application_previous_day.borrow_mut().time = time;
+    });
+
+    let application_today = Rc::clone(&application);
+    let mut date_today = date_input_clone.clone();
+    btn_today.on_click(move |_| {
+        move_to_tonight(&application_today, &mut date_today);
+    });
+
+    let mut application_next_day = Rc::clone(&application);
+    let mut date_next_day = date_input_clone.clone();
+    btn_next_day.on_click(move |_| {
+        let time = application_next_day.borrow().time.add_days(1);
+        date_next_day.set_value(&time.to_string(Some("yyyymmdd")));
+        application_next_day.borrow_mut().time = time;
+    });
+
+    // Handlers for the simulated time, dragging the slider moves the timeline cursor and Now
+    // brings it back to the current time
+    let mut timeline_simulate = timeline.clone();
+    simulate.set_callback(move |s| {
+        timeline_simulate.simulate(Some(s.value()));
+    });
+
+    let mut timeline_now = timeline.clone();
+    btn_now.on_click(move |_| {
+        timeline_now.simulate(None);
+    });
+
+    // change color on hover, reset color on leave
+    for btn in [&mut btn_previous_day, &mut btn_today, &mut btn_next_day, &mut btn_now] {
+        let btn_color = btn.color();
+        btn.on_hover(|b| {
+            b.set_color(enums::Color::Blue);
+        });
+        btn.on_leave(move |b| {
+            b.set_color(btn_color);
+        });
+    }
+
     let mut application_observatory = Rc::clone(&application);
     // Handle for Observatory button
     // preserve button's original color
     let btn_observatory_color = btn_observatory.color();
     // Show Observatory dialog when button clicked
-    btn_observatory.on_click(move |b| {
+    // the labels follow the observer on the next frame once the setup is applied
+    btn_observatory.on_click(move |_| {
         menu::functions::observatory::handle_observatory(&mut application_observatory);
-        if let Some(name_str) = &application_observatory.borrow_mut().observer.name {
-            _observatory.set_label(name_str.as_str());
-        }
-        _elevation.set_label(&application_observatory.borrow_mut().observer.elevation.to_string());
-        _latitude.set_label(&application_observatory.borrow_mut().observer.latitude.to_string());
-        _longitude.set_label(&application_observatory.borrow_mut().observer.longitude.to_string());
-        timezone_observatory_clone.set_value(&application_observatory.borrow_mut().observer.timezone.to_string());
     });
 
     // change color on hover
@@ -349,53 +622,184 @@ pub fn handle_darkness(mut application: &mut Rc<RefCell<Application>>) -> bool {
     });
 
 
-    while window.shown() {
-        // Update calculations
-        let (sunrise, sunset, civ_tw_start, civ_tw_end,
-            naut_tw_start, naut_tw_end, astro_tw_start, astro_tw_end) =
-            calculate_sun(&application_clone_calculations.borrow_mut());
+    let mut clock = WallClock::default();
+    let mut last_now = Time::now().to_jd();
+    // a rollover prompt is open, the night may change again before it is answered
+    let prompting = Rc::new(Cell::new(false));
+    let mut shown_observer = application.borrow().observer.clone();
+    let mut window_frame = window.clone();
+    every_frame(&window, move || {
+        // Update the observatory labels when the observer changes, from the Observatory setup or
+        // Edit/Undo, the timezone is only rewritten when it is not the value being edited here
+        if application_clone_calculations.borrow().observer != shown_observer {
+            let app = application_clone_calculations.borrow();
+            _observatory.set_label(app.observer.name.as_deref().unwrap_or(""));
+            _elevation.set_label(&format_elevation(app.observer.elevation, &app.preferences));
+            _latitude.set_label(&format_latitude(app.observer.latitude, &app.preferences));
+            _longitude.set_label(&format_longitude(app.observer.longitude, &app.preferences));
+            if timezone_observatory_clone.value().trim().parse::<f64>().ok() != Some(app.observer.timezone) {
+                timezone_observatory_clone.set_value(&app.observer.timezone.to_string());
+            }
+            shown_observer = app.observer.clone();
+        }
 
-        let (moonrise, moonset) =
-            calculate_moon(&application_clone_calculations.borrow_mut());
+        // After a sleep, or when the night changes at the rollover hour while tonight is shown,
+        // move on to the current night as set in preferences and query the forecast again, it is
+        // stale after a sleep
+        let resumed = clock.tick().is_some();
+        let now = Time::now();
+        let (night_changed, showing_tonight, rollover) = {
+            let app = application_clone_calculations.borrow();
+            let (timezone, hour) = (app.observer.timezone, app.preferences.rollover_hour as f64);
+            // tonight is shown by its local date, or by the current time the window opened with
+            let (tonight, tonight_date) = (observing_night_at(last_now, timezone, hour),
+                                           observing_night_date(last_now, timezone, hour));
+            (observing_night_at(now.to_jd(), timezone, hour) != tonight,
+             observing_night_at(app.time.to_jd(), timezone, hour) == tonight
+                 || (app.time.year, app.time.month, app.time.day)
+                     == (tonight_date.year, tonight_date.month, tonight_date.day),
+             app.preferences.date_rollover)
+        };
+        last_now = now.to_jd();
+        if night_changed && showing_tonight {
+            match rollover {
+                DateRollover::Advance => {
+                    move_to_tonight(&application_clone_calculations, &mut date_input_clone.clone())
+                }
+                // the dialog waits for the user, so it is asked from its own timeout and the window
+                // keeps updating meanwhile
+                DateRollover::Prompt if !prompting.get() => {
+                    prompting.set(true);
+                    let prompting = Rc::clone(&prompting);
+                    let application = Rc::clone(&application_clone_calculations);
+                    let mut date_input = date_input_clone.clone();
+                    app::add_timeout3(0.0, move |_| {
+                        if dialog::choice2_default(tr("A new observing night started. Move to tonight?"),
+                                                   tr("Keep the date"), tr("Move"), "") == Some(1) {
+                            move_to_tonight(&application, &mut date_input);
+                        }
+                        prompting.set(false);
+                    });
+                }
+                DateRollover::Prompt | DateRollover::Off => {}
+            }
+        }
+        if resumed || night_changed {
+            weather_night = None;
+            quality_night = None;
+        }
 
-        let (astronomical_dso_start, astronomical_dso_end,
-            nautical_dso_start, nautical_dso_end) =
-            calculate_darkness(&application_clone_calculations.borrow_mut());
+        // Update calculations
+        let events = {
+            let app = application_clone_calculations.borrow();
+            NightEvents::new(&app.observer, &app.time, &app.environment, &app.preferences)
+        };
 
         // Update Sun labels
-        sunrise_label.set_label(&sunrise);
-        sunset_label.set_label(&sunset);
-        civ_tw_start_label.set_label(&civ_tw_start);
-        civ_tw_end_label.set_label(&civ_tw_end);
-        naut_tw_start_label.set_label(&naut_tw_start);
-        naut_tw_end_label.set_label(&naut_tw_end);
-        astro_tw_start_label.set_label(&astro_tw_start);
-        astro_tw_end_label.set_label(&astro_tw_end);
+        sunrise_label.set_label(&events.sunrise);
+        sunset_label.set_label(&events.sunset);
+        sunrise_direction_label.set_label(&events.sunrise_direction);
+        sunset_direction_label.set_label(&events.sunset_direction);
+        civ_tw_start_label.set_label(&events.civil_end);
+        civ_tw_end_label.set_label(&events.civil_start);
+        naut_tw_start_label.set_label(&events.nautical_end);
+        naut_tw_end_label.set_label(&events.nautical_start);
+        astro_tw_start_label.set_label(&events.astronomical_end);
+        astro_tw_end_label.set_label(&events.astronomical_start);
+
+        // Update golden and blue hour labels
+        if let Some([golden_evening, golden_morning, blue_evening, blue_morning]) = band_labels.as_mut() {
+            golden_evening.set_label(&events.golden_evening_start);
+            golden_morning.set_label(&events.golden_morning_end);
+            blue_evening.set_label(&events.blue_evening_start);
+            blue_morning.set_label(&events.blue_morning_end);
+        }
 
         // Update Moon labels
-        moonrise_label.set_label(&moonrise);
-        moonset_label.set_label(&moonset);
+        moonrise_label.set_label(&events.moonrise);
+        moonset_label.set_label(&events.moonset);
+        moonrise_direction_label.set_label(&events.moonrise_direction);
+        moonset_direction_label.set_label(&events.moonset_direction);
+        libration_label.set_label(&events.moon_libration);
+        moon_axis_label.set_label(&events.moon_axis);
 
         // Update Darkness labels
-        astronomical_dso_start_label.set_label(&astronomical_dso_start);
-        astronomical_dso_end_label.set_label(&astronomical_dso_end);
-        nautical_dso_start_label.set_label(&nautical_dso_start);
-        nautical_dso_end_label.set_label(&nautical_dso_end);
+        astronomical_dso_start_label.set_label(&events.dso_astronomical_start);
+        astronomical_dso_end_label.set_label(&events.dso_astronomical_end);
+        nautical_dso_start_label.set_label(&events.dso_nautical_start);
+        nautical_dso_end_label.set_label(&events.dso_nautical_end);
+
+        astronomical_nb_start_label.set_label(&events.astronomical_end);
+        astronomical_nb_end_label.set_label(&events.astronomical_start);
+        nautical_nb_start_label.set_label(&events.nautical_end);
+        nautical_nb_end_label.set_label(&events.nautical_start);
+
+        // Update polar day/night labels
+        sun_circumpolar_label.set_label(events.sun_circumpolar.as_deref().unwrap_or(""));
+        moon_circumpolar_label.set_label(events.moon_circumpolar.as_deref().unwrap_or(""));
+
+        // Update Sun and Moon readouts at the simulated time, or at the current time with the
+        // slider following it along the night shown
+        {
+            let app = application_clone_calculations.borrow();
+            let start = timeline_start(&app.observer, &app.time, app.preferences.solar_midnight);
+            let jd = match timeline.simulated() {
+                Some(position) => start + position,
+                None => {
+                    simulate.set_value((last_now - start).clamp(0.0, 1.0));
+                    last_now
+                }
+            };
+            let (sun, moon) = sun_moon_alt_az(&app.observer, jd);
+            sun_alt_az_label.set_label(&format_alt_az(sun));
+            moon_alt_az_label.set_label(&format_alt_az(moon));
+            simulated_label.set_label(&format_local(app.observer.local_time(jd), &app.preferences));
+        }
 
-        astronomical_nb_start_label.set_label(&astro_tw_start);
-        astronomical_nb_end_label.set_label(&astro_tw_end);
-        nautical_nb_start_label.set_label(&naut_tw_start);
-        nautical_nb_end_label.set_label(&naut_tw_end);
+        // Query the forecast again when the night or the observatory changes
+        let (night_key, weather_key) = {
+            let app = application_clone_calculations.borrow();
+            let key = format!("{} {} {}", export_night(&app.time), app.observer.latitude, app.observer.longitude);
+            let weather_key = app.preferences.weather_forecast.then(|| key.clone());
+            (key, weather_key)
+        };
+        // Night quality history, updated with the cloud cover once the forecast arrives
+        if quality_night.as_ref() != Some(&night_key) {
+            quality_night = Some(night_key);
+            record_quality(&application_clone_calculations.borrow(), &[]);
+        }
+        if weather_key != weather_night {
+            weather_night = weather_key.clone();
+            for cell in weather_cells.iter_mut() {
+                cell.hide();
+            }
+            weather_status.set_label(if weather_key.is_some() { "Weather: loading forecast..." } else { "" });
+            relayout(&mut weather_row);
+            weather_receiver = weather_key.map(|_| {
+                let app = application_clone_calculations.borrow();
+                let (observer, time) = (app.observer.clone(), app.time.clone());
+                let (sender, receiver) = channel();
+                std::thread::spawn(move || {
+                    // the receiver is gone if the night changed meanwhile
+                    let _ = sender.send(night_forecast(&OpenMeteo, &observer, &time));
+                });
+                receiver
+            });
+        }
+        if let Some(Ok(forecast)) = weather_receiver.as_ref().map(|r| r.try_recv()) {
+            update_weather_strip(&mut weather_cells, &mut weather_status, &forecast,
+                                 &application_clone_calculations.borrow().observer);
+            relayout(&mut weather_row);
+            if let Ok(hours) = &forecast {
+                record_quality(&application_clone_calculations.borrow(), hours);
+            }
+            weather_receiver = None;
+        }
 
         //Redraw window to update labels
-        window.redraw();
-
-        fltk::app::wait();
-
-        // Reduce frame updated to reduce CPU consumption
-        std::thread::sleep(std::time::Duration::from_millis(32));
-    }
+        window_frame.redraw();
+    });
 
-    true
+    Some(window)
 }
 
@@ -0,0 +1,98 @@
+// src/menu/functions/darkness_calendar.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use fltk::button::CheckButton;
+use fltk::prelude::{ButtonExt, GroupExt, InputExt, WidgetBase, WidgetExt};
+use fltk::{button, enums};
+use fltk::enums::Align;
+use fltk::input::IntInput;
+use fltk::window::Window;
+use fltk_evented::Listener;
+use crate::application::application::Application;
+use crate::application::reports::{darkness_calendar_report, DARKNESS_CALENDAR_FILE};
+use crate::menu::functions::export::confirm_export;
+use crate::widgets::label::Label;
+use crate::menu::window_manager::new_tool_window;
+
+pub fn handle_darkness_calendar(application: &mut Rc<RefCell<Application>>) -> Option<Window> {
+    let Some(mut window) = new_tool_window("Darkness calendar", 290, 150) else {
+        return None;
+    };
+
+    // Year
+    Label::new(10, 10, 80, 20, "Year", Align::Left | Align::Inside);
+    let mut year = IntInput::new(10, 30, 80, 25, "");
+    year.set_maximum_size(4);
+    year.set_value(&application.borrow().time.year.to_string());
+
+    // Calendar image
+    let mut image = CheckButton::new(110, 30, 170, 25, "Calendar image (SVG)");
+    image.set_checked(true);
+    image.set_tooltip("Heatmap of the Moon free darkness by month and day");
+
+    // Export button
+    let mut btn_export: Listener<_> = button::Button::new(20, 100, 50, 30, "Export").into();
+    btn_export.clear_visible_focus();
+    btn_export.set_tooltip("Export darkness hours and Moon illumination of every night to skycalc_darkness_calendar.csv");
+
+    // Close button
+    let mut btn_close: Listener<_> = button::Button::new(220, 100, 50, 30, "Close").into();
+    btn_close.clear_visible_focus();
+
+    window.show();
+    window.end();
+
+    let mut window_clone = window.clone();
+
+    // Window call back to avoid program termination when ESC is pressed
+    // from FLTK Book - FAQ
+    window.set_callback(|w| {
+        if fltk::app::event() == fltk::enums::Event::Close {
+            w.hide();
+        }
+    });
+
+    // Handlers for Close button
+    // preserve button's original color
+    let btn_close_color = btn_close.color();
+    // close window when clicked
+    btn_close.on_click(move |_| {
+        window_clone.hide();
+    });
+
+    // change color on hover
+    btn_close.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_close.on_leave(move |b| {
+        b.set_color(btn_close_color);
+    });
+
+    // Handlers for Export button
+    // preserve button's original color
+    let btn_export_color = btn_export.color();
+    // Export to file when clicked
+    let app_clone = Rc::clone(&application);
+    btn_export.on_click(move |_| {
+        let app = app_clone.borrow();
+        let year_value = year.value().parse::<i64>().unwrap_or(app.time.year);
+        if confirm_export(&app, DARKNESS_CALENDAR_FILE, &year_value.to_string()) {
+            darkness_calendar_report(&app.observer, year_value, image.is_checked());
+        }
+    });
+
+    // change color on hover
+    btn_export.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_export.on_leave(move |b| {
+        b.set_color(btn_export_color);
+    });
+
+    Some(window)
+}
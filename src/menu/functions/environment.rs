@@ -0,0 +1,160 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use fltk::prelude::{GroupExt, InputExt, WidgetBase, WidgetExt};
+use fltk::{button, enums};
+use fltk::enums::Shortcut;
+use fltk::input::Input;
+use fltk::window::Window;
+use fltk_evented::Listener;
+use crate::application::application::Application;
+use crate::application::config_check::HUMIDITY_RANGE;
+use crate::application::environment::Environment;
+use crate::application::undo::Setting;
+use crate::application::units::Units;
+use crate::utils::i18n::tr;
+use crate::widgets::keyboard::{close_on_escape, enter_moves_focus};
+use crate::widgets::layout::{fixed_label, label, scaled, spacer, FormLayout, BUTTON_HEIGHT, BUTTON_WIDTH,
+                             INPUT_HEIGHT, LABEL_HEIGHT};
+use crate::widgets::validated::mark_input;
+use crate::menu::window_manager::new_tool_window;
+
+// Metric value of the input read in its display unit, the input is marked when it is not valid
+fn unit_field<T>(input: &mut Input, parse: impl Fn(&str) -> Result<T, String>, hint: &str) -> Option<T> {
+    let result = parse(&input.value());
+    mark_input(input, result.as_ref().err().map(String::as_str), hint);
+    result.ok()
+}
+
+// Input for an environment value
+fn new_field(text: &str, hint: &str) -> Input {
+    let mut input = Input::default();
+    input.set_maximum_size(6);
+    input.set_value(text);
+    input.set_tooltip(hint);
+    enter_moves_focus(&mut input);
+    input
+}
+
+// Dew point of the environment in the temperature unit
+fn dew_point_text(environment: &Environment, units: &Units) -> String {
+    format!("{:.1} {}", units.temperature.celsius_to_unit(environment.dew_point()), units.temperature.symbol())
+}
+
+pub fn handle_environment(application: &mut Rc<RefCell<Application>>) -> Option<Window> {
+    let Some(mut window) = new_tool_window(tr("Environment"), scaled(290), 190) else {
+        return None;
+    };
+    let (environment, units) = {
+        let app = application.borrow();
+        (app.environment.clone(), app.preferences.units)
+    };
+    let temperature_hint = format!("{} {}", tr("Air temperature at sunset in"), units.temperature.symbol());
+    let pressure_hint = format!("{} {}", tr("Air pressure at the observatory in"), units.pressure.symbol());
+    let humidity_range = format!("{} {} {}", HUMIDITY_RANGE.start(), tr("to"), HUMIDITY_RANGE.end());
+    let humidity_hint = format!("{}, {}", tr("Relative humidity in %"), humidity_range);
+    let mut layout = FormLayout::new(&window);
+
+    // Temperature and humidity, the temperature in the unit of the preferences
+    let [mut temperature_label, _] = layout.labels(["", tr("Humidity (%)")]);
+    temperature_label.set_label(&format!("{} ({})", tr("Temperature"), units.temperature.symbol()));
+    let row = layout.row(INPUT_HEIGHT);
+    let mut temperature = new_field(&units.temperature.format(environment.temperature), &temperature_hint);
+    let mut humidity = new_field(&environment.humidity.to_string(), &humidity_hint);
+    row.end();
+
+    // Pressure in the unit of the preferences
+    let [mut pressure_label] = layout.labels([""]);
+    pressure_label.set_label(&format!("{} ({})", tr("Pressure"), units.pressure.symbol()));
+    let mut row = layout.row(INPUT_HEIGHT);
+    let mut pressure = new_field(&units.pressure.format(environment.pressure), &pressure_hint);
+    row.fixed(&pressure, scaled(135));
+    spacer();
+    row.end();
+
+    // Dew point of the applied values
+    let mut row = layout.row(LABEL_HEIGHT);
+    fixed_label(&mut row, tr("Dew point"), 135);
+    let mut dew_point = label("");
+    dew_point.set_label(&dew_point_text(&environment, &units));
+    row.end();
+
+    let mut row = layout.row(BUTTON_HEIGHT);
+
+    // Apply button
+    let mut btn_apply: Listener<_> = button::Button::default().with_label(tr("&Apply")).into();
+    btn_apply.clear_visible_focus();
+    btn_apply.set_shortcut(Shortcut::Alt | 'a');
+    row.fixed(&*btn_apply, scaled(BUTTON_WIDTH));
+    spacer();
+
+    // Close button
+    let mut btn_close: Listener<_> = button::Button::default().with_label(tr("&Close")).into();
+    btn_close.clear_visible_focus();
+    btn_close.set_shortcut(Shortcut::Alt | 'c');
+    row.fixed(&*btn_close, scaled(BUTTON_WIDTH));
+    row.end();
+
+    layout.end(&mut window);
+    window.show();
+    window.end();
+
+    let mut window_clone = window.clone();
+
+    // Close with the window close button or Esc
+    close_on_escape(&mut window);
+
+    // Handlers for Close button
+    // preserve button's original color
+    let btn_color = btn_close.color();
+    // close window when clicked or with Alt+C
+    btn_close.set_callback(move |_| {
+        window_clone.hide();
+    });
+
+    // change color on hover
+    btn_close.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_close.on_leave(move |b| {
+        b.set_color(btn_color);
+    });
+
+    // Handlers for Apply button
+    // preserve button's original color
+    let btn_apply_color = btn_apply.color();
+    // Apply changes when clicked or with Alt+A, the values are converted back to metric
+    let app_clone = Rc::clone(application);
+    btn_apply.set_callback(move |_| {
+        // every field is checked so all invalid ones are marked, nothing is applied until they are fixed
+        let values = (
+            unit_field(&mut temperature, |text| units.temperature.parse(text), &temperature_hint),
+            unit_field(&mut humidity, |text| match text.trim().parse::<i64>() {
+                Ok(value) if HUMIDITY_RANGE.contains(&value) => Ok(value),
+                _ => Err(format!("{} {}", tr("Enter a whole number from"), humidity_range)),
+            }, &humidity_hint),
+            unit_field(&mut pressure, |text| units.pressure.parse(text), &pressure_hint),
+        );
+        let (Some(temperature), Some(humidity), Some(pressure)) = values else {
+            return;
+        };
+
+        // update environment, as one edit that Edit/Undo reverts
+        let environment = app_clone.borrow().environment.clone().new(pressure, temperature, humidity);
+        dew_point.set_label(&dew_point_text(&environment, &units));
+        app_clone.borrow_mut().apply_setting(Setting::Environment(environment));
+    });
+
+    // change color on hover
+    btn_apply.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_apply.on_leave(move |b| {
+        b.set_color(btn_apply_color);
+    });
+
+    Some(window)
+}
@@ -0,0 +1,295 @@
+// src/menu/functions/equipment.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use fltk::prelude::{GroupExt, InputExt, MenuExt, WidgetBase, WidgetExt};
+use fltk::{button, dialog, enums};
+use fltk::enums::Shortcut;
+use fltk::input::Input;
+use fltk::menu::Choice;
+use fltk::window::Window;
+use fltk_evented::Listener;
+use crate::application::application::Application;
+use crate::application::equipment::{field_of_view, Camera, Equipment, Telescope, APERTURE_RANGE,
+                                    FOCAL_LENGTH_RANGE, PIXEL_SIZE_RANGE, RESOLUTION_RANGE};
+use crate::application::undo::Setting;
+use crate::widgets::keyboard::{close_on_escape, enter_moves_focus};
+use crate::widgets::layout::{fixed_label, label, scaled, spacer, FormLayout, BUTTON_HEIGHT, BUTTON_WIDTH,
+                             INPUT_HEIGHT};
+use crate::widgets::label::Label;
+use crate::widgets::validated::number_field;
+use crate::menu::window_manager::new_tool_window;
+
+// Last entry of the profile choices, clears the fields to enter a new profile
+const NEW_TELESCOPE: &str = "New telescope";
+const NEW_CAMERA: &str = "New camera";
+
+fn new_field(width: i32) -> Input {
+    let mut input = Input::default();
+    input.set_maximum_size(width);
+    enter_moves_focus(&mut input);
+    input
+}
+
+// Profile names followed by the new profile entry, the active profile selected
+fn fill_choice<'a>(choice: &mut Choice, names: impl Iterator<Item = &'a str>, new_label: &str,
+                   active: Option<&str>) {
+    choice.clear();
+    let mut selected = None;
+    for (i, name) in names.enumerate() {
+        // '/' and '&' are menu markup
+        choice.add_choice(&name.replace('/', "\\/").replace('&', "&&"));
+        if Some(name) == active {
+            selected = Some(i);
+        }
+    }
+    choice.add_choice(new_label);
+    choice.set_value(selected.unwrap_or(choice.size() as usize - 1) as i32);
+}
+
+// Field of view of the active setup
+fn setup_text(equipment: &Equipment) -> String {
+    match equipment.active() {
+        Some((telescope, camera)) => format!("{}, f/{:.1}", field_of_view(telescope, camera).description(),
+                                             telescope.focal_ratio()),
+        None => "Apply a telescope and a camera to select the setup".to_string(),
+    }
+}
+
+// Inputs of a telescope profile
+#[derive(Clone)]
+struct TelescopeFields {
+    name: Input,
+    focal_length: Input,
+    aperture: Input,
+}
+
+impl TelescopeFields {
+    // Values of the telescope, empty for a new one
+    fn show(&mut self, telescope: Option<&Telescope>) {
+        self.name.set_value(telescope.map_or("", |t| t.name.as_str()));
+        self.focal_length.set_value(&telescope.map_or(String::new(), |t| t.focal_length.to_string()));
+        self.aperture.set_value(&telescope.map_or(String::new(), |t| t.aperture.to_string()));
+    }
+
+    // Telescope of the inputs, None with the invalid inputs marked
+    fn read(&mut self) -> Option<Telescope> {
+        let focal_length = number_field(&mut self.focal_length, &FOCAL_LENGTH_RANGE);
+        let aperture = number_field(&mut self.aperture, &APERTURE_RANGE);
+        Some(Telescope { name: self.name.value().trim().to_string(), focal_length: focal_length?, aperture: aperture? })
+    }
+}
+
+// Inputs of a camera profile
+#[derive(Clone)]
+struct CameraFields {
+    name: Input,
+    pixel_size: Input,
+    width: Input,
+    height: Input,
+}
+
+impl CameraFields {
+    // Values of the camera, empty for a new one
+    fn show(&mut self, camera: Option<&Camera>) {
+        self.name.set_value(camera.map_or("", |c| c.name.as_str()));
+        self.pixel_size.set_value(&camera.map_or(String::new(), |c| c.pixel_size.to_string()));
+        self.width.set_value(&camera.map_or(String::new(), |c| c.width.to_string()));
+        self.height.set_value(&camera.map_or(String::new(), |c| c.height.to_string()));
+    }
+
+    // Camera of the inputs, None with the invalid inputs marked
+    fn read(&mut self) -> Option<Camera> {
+        let pixel_size = number_field(&mut self.pixel_size, &PIXEL_SIZE_RANGE);
+        let width = number_field(&mut self.width, &RESOLUTION_RANGE);
+        let height = number_field(&mut self.height, &RESOLUTION_RANGE);
+        Some(Camera { name: self.name.value().trim().to_string(), pixel_size: pixel_size?, width: width?,
+                      height: height? })
+    }
+}
+
+// Widgets showing the equipment, filled again after an edit
+#[derive(Clone)]
+struct EquipmentView {
+    telescopes: Choice,
+    cameras: Choice,
+    telescope: TelescopeFields,
+    camera: CameraFields,
+    setup: Label,
+}
+
+impl EquipmentView {
+    fn show(&mut self, equipment: &Equipment) {
+        fill_choice(&mut self.telescopes, equipment.telescopes.iter().map(|t| t.name.as_str()), NEW_TELESCOPE,
+                    equipment.active_telescope.as_deref());
+        fill_choice(&mut self.cameras, equipment.cameras.iter().map(|c| c.name.as_str()), NEW_CAMERA,
+                    equipment.active_camera.as_deref());
+        self.telescope.show(equipment.telescopes.get(self.telescopes.value().max(0) as usize));
+        self.camera.show(equipment.cameras.get(self.cameras.value().max(0) as usize));
+        self.setup.set_label(&setup_text(equipment));
+    }
+}
+
+pub fn handle_equipment(application: &mut Rc<RefCell<Application>>) -> Option<Window> {
+    let Some(mut window) = new_tool_window("Equipment", scaled(360), 320) else {
+        return None;
+    };
+    let mut layout = FormLayout::new(&window);
+
+    // Telescope profiles
+    layout.labels(["Telescope"]);
+    let mut row = layout.row(INPUT_HEIGHT);
+    let telescopes = Choice::default();
+    let mut btn_remove_telescope: Listener<_> = button::Button::default().with_label("Remove").into();
+    btn_remove_telescope.clear_visible_focus();
+    row.fixed(&*btn_remove_telescope, scaled(60));
+    row.end();
+    layout.labels(["Name", "Focal length (mm)", "Aperture (mm)"]);
+    let row = layout.row(INPUT_HEIGHT);
+    let telescope = TelescopeFields { name: new_field(35), focal_length: new_field(8), aperture: new_field(8) };
+    row.end();
+
+    layout.divider();
+
+    // Camera profiles
+    layout.labels(["Camera"]);
+    let mut row = layout.row(INPUT_HEIGHT);
+    let cameras = Choice::default();
+    let mut btn_remove_camera: Listener<_> = button::Button::default().with_label("Remove").into();
+    btn_remove_camera.clear_visible_focus();
+    row.fixed(&*btn_remove_camera, scaled(60));
+    row.end();
+    layout.labels(["Name", "Pixel (µm)", "Width (px)", "Height (px)"]);
+    let row = layout.row(INPUT_HEIGHT);
+    let camera = CameraFields { name: new_field(35), pixel_size: new_field(6), width: new_field(6), height: new_field(6) };
+    row.end();
+
+    layout.divider();
+
+    // Field of view and image scale of the active setup
+    let mut row = layout.row(INPUT_HEIGHT);
+    fixed_label(&mut row, "Field", 50);
+    let mut setup = label("");
+    setup.set_tooltip("Targets up to 90% of the short side fit, the up tonight report flags the others");
+    row.end();
+
+    let mut row = layout.row(BUTTON_HEIGHT);
+
+    // Apply button
+    let mut btn_apply: Listener<_> = button::Button::default().with_label("&Apply").into();
+    btn_apply.clear_visible_focus();
+    btn_apply.set_shortcut(Shortcut::Alt | 'a');
+    btn_apply.set_tooltip("Save the telescope and the camera and use them as the active setup");
+    row.fixed(&*btn_apply, scaled(BUTTON_WIDTH));
+    spacer();
+
+    // Close button
+    let mut btn_close: Listener<_> = button::Button::default().with_label("&Close").into();
+    btn_close.clear_visible_focus();
+    btn_close.set_shortcut(Shortcut::Alt | 'c');
+    row.fixed(&*btn_close, scaled(BUTTON_WIDTH));
+    row.end();
+
+    layout.end(&mut window);
+    window.show();
+    window.end();
+
+    let mut window_clone = window.clone();
+
+    // Close with the window close button or Esc
+    close_on_escape(&mut window);
+
+    let mut view = EquipmentView { telescopes, cameras, telescope, camera, setup };
+    view.show(&application.borrow().equipment);
+    view.telescope.focal_length.set_tooltip("Effective focal length, with the reducer or barlow in use");
+
+    // Selecting a profile shows it
+    let (app_select_clone, mut telescope_select_clone) = (Rc::clone(application), view.telescope.clone());
+    view.telescopes.set_callback(move |c| {
+        telescope_select_clone.show(app_select_clone.borrow().equipment.telescopes.get(c.value().max(0) as usize));
+    });
+    let (app_select_clone, mut camera_select_clone) = (Rc::clone(application), view.camera.clone());
+    view.cameras.set_callback(move |c| {
+        camera_select_clone.show(app_select_clone.borrow().equipment.cameras.get(c.value().max(0) as usize));
+    });
+
+    // Handlers for Remove buttons, as edits that Edit/Undo reverts
+    let (app_remove_clone, mut view_remove_clone) = (Rc::clone(application), view.clone());
+    btn_remove_telescope.on_click(move |_| {
+        let mut equipment = app_remove_clone.borrow().equipment.clone();
+        let index = view_remove_clone.telescopes.value().max(0) as usize;
+        let Some(name) = equipment.telescopes.get(index).map(|t| t.name.clone()) else {
+            return;
+        };
+        equipment.remove_telescope(&name);
+        app_remove_clone.borrow_mut().apply_setting(Setting::Equipment(equipment));
+        view_remove_clone.show(&app_remove_clone.borrow().equipment);
+    });
+    let (app_remove_clone, mut view_remove_clone) = (Rc::clone(application), view.clone());
+    btn_remove_camera.on_click(move |_| {
+        let mut equipment = app_remove_clone.borrow().equipment.clone();
+        let index = view_remove_clone.cameras.value().max(0) as usize;
+        let Some(name) = equipment.cameras.get(index).map(|c| c.name.clone()) else {
+            return;
+        };
+        equipment.remove_camera(&name);
+        app_remove_clone.borrow_mut().apply_setting(Setting::Equipment(equipment));
+        view_remove_clone.show(&app_remove_clone.borrow().equipment);
+    });
+
+    // Handlers for Close button
+    // preserve button's original color
+    let btn_color = btn_close.color();
+    // close window when clicked or with Alt+C
+    btn_close.set_callback(move |_| {
+        window_clone.hide();
+    });
+
+    // change color on hover
+    btn_close.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_close.on_leave(move |b| {
+        b.set_color(btn_color);
+    });
+
+    // Handlers for Apply button
+    // preserve button's original color
+    let btn_apply_color = btn_apply.color();
+    // Save both profiles and make them the active setup when clicked or with Alt+A
+    let app_clone = Rc::clone(application);
+    btn_apply.set_callback(move |_| {
+        // both are read so all invalid inputs are marked, nothing is applied until they are fixed
+        let (telescope, camera) = (view.telescope.read(), view.camera.read());
+        let (Some(telescope), Some(camera)) = (telescope, camera) else {
+            return;
+        };
+        if let Err(e) = telescope.check().and_then(|_| camera.check()) {
+            dialog::alert_default(&e);
+            return;
+        }
+
+        // update the equipment, as one edit that Edit/Undo reverts
+        let mut equipment = app_clone.borrow().equipment.clone();
+        equipment.active_telescope = Some(telescope.name.clone());
+        equipment.active_camera = Some(camera.name.clone());
+        equipment.save_telescope(telescope);
+        equipment.save_camera(camera);
+        app_clone.borrow_mut().apply_setting(Setting::Equipment(equipment));
+        view.show(&app_clone.borrow().equipment);
+    });
+
+    // change color on hover
+    btn_apply.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_apply.on_leave(move |b| {
+        b.set_color(btn_apply_color);
+    });
+
+    Some(window)
+}
@@ -0,0 +1,40 @@
+// src/menu/functions/export.rs
+
+use std::path::{self, PathBuf};
+use std::process::Command;
+use fltk::dialog::{alert_default, choice2_default};
+use crate::application::application::Application;
+use crate::application::reports::existing_export_night;
+
+// Asks before replacing an export of the same night, when enabled in the preferences.
+// Returns true when the export can be written.
+pub(crate) fn confirm_export(application: &Application, file_path: &str, night: &str) -> bool {
+    if !application.preferences.confirm_overwrite {
+        return true;
+    }
+    match existing_export_night(file_path) {
+        Some(existing) if existing == night => {
+            let message = format!("{} already holds an export for night {}.\nOverwrite it?",
+                                  file_path, night);
+            choice2_default(&message, "Cancel", "Overwrite", "") == Some(1)
+        }
+        _ => true,
+    }
+}
+
+// Opens an exported file with the default application of the system, e.g. a web page in the
+// browser. Failures are reported in a dialog, the export itself is already written.
+pub(crate) fn open_export(file_path: &str) {
+    // absolute and not canonical, Windows canonical paths start with \\?\ which `start` does not open
+    let path = path::absolute(file_path).unwrap_or_else(|_| PathBuf::from(file_path));
+    let result = if cfg!(target_os = "windows") {
+        Command::new("cmd").arg("/C").arg("start").arg("").arg(&path).spawn()
+    } else if cfg!(target_os = "macos") {
+        Command::new("open").arg(&path).spawn()
+    } else {
+        Command::new("xdg-open").arg(&path).spawn()
+    };
+    if let Err(error) = result {
+        alert_default(&format!("Unable to open {}: {}", file_path, error));
+    }
+}
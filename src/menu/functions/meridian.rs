@@ -0,0 +1,146 @@
+// src/menu/functions/meridian.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use fltk::browser::HoldBrowser;
+use fltk::prelude::{BrowserExt, GroupExt, WidgetBase, WidgetExt};
+use fltk::{button, enums};
+use fltk::enums::Shortcut;
+use fltk::window::Window;
+use fltk_evented::Listener;
+use crate::application::application::Application;
+use crate::application::meridian::{near_meridian, zenith};
+use crate::application::time::Time;
+use crate::utils::angle::{format_dec_dms, format_ra_hms};
+use crate::utils::i18n::tr;
+use crate::widgets::keyboard::close_on_escape;
+use crate::widgets::layout::{fixed_label, label, scaled, spacer, FormLayout, BUTTON_HEIGHT, BUTTON_WIDTH,
+                             LABEL_HEIGHT};
+use crate::menu::window_manager::{every_seconds, new_tool_window};
+
+// Seconds between two updates, the targets are searched again on each one
+const MERIDIAN_UPDATE_SECONDS: f64 = 1.0;
+
+// Column titles of the table
+fn table_header() -> String {
+    ["Target", "Hour angle", "Altitude", "Dec"]
+        .into_iter()
+        .map(|title| format!("@b{}", tr(title)))
+        .collect::<Vec<String>>()
+        .join("\t")
+}
+
+// Rows of the targets near the meridian now, east to west
+fn meridian_rows(app: &Application, jd: f64) -> Vec<String> {
+    near_meridian(&app.observer, jd, &app.targets, &app.constraints)
+        .iter()
+        .map(|m| format!("@.{}\t{:+.2} h\t{:.1}°\t{}", m.target.name, m.hour_angle, m.altitude,
+                         format_dec_dms(m.target.dec)))
+        .collect()
+}
+
+pub fn handle_meridian(application: &mut Rc<RefCell<Application>>) -> Option<Window> {
+    let Some(mut window) = new_tool_window(tr("Zenith and meridian"), scaled(360), 330) else {
+        return None;
+    };
+    let mut layout = FormLayout::new(&window);
+
+    // Zenith and meridian now
+    let mut row = layout.row(LABEL_HEIGHT);
+    fixed_label(&mut row, tr("Zenith"), 110);
+    let mut zenith_label = label("");
+    zenith_label.set_tooltip(tr("Right ascension and declination straight overhead, equinox of date"));
+    row.end();
+    let mut row = layout.row(LABEL_HEIGHT);
+    fixed_label(&mut row, tr("Meridian RA"), 110);
+    let mut meridian_label = label("");
+    meridian_label.set_tooltip(tr("Right ascension crossing the meridian, the local sidereal time"));
+    row.end();
+    layout.divider();
+
+    // Targets near the meridian within the altitude constraints
+    let mut row = layout.row(LABEL_HEIGHT);
+    let mut summary = label("");
+    row.end();
+    let mut table = HoldBrowser::default();
+    table.set_column_widths(&[scaled(130), scaled(85), scaled(65), scaled(80)]);
+    table.set_column_char('\t');
+    table.set_tooltip(tr("Targets within an hour of the meridian and within the altitude constraints, at their \
+highest now. Negative hour angles are still to transit."));
+    table.add(&table_header());
+    layout.stretch(150);
+
+    let mut row = layout.row(BUTTON_HEIGHT);
+    spacer();
+
+    // Close button
+    let mut btn_close: Listener<_> = button::Button::default().with_label(tr("&Close")).into();
+    btn_close.clear_visible_focus();
+    btn_close.set_shortcut(Shortcut::Alt | 'c');
+    row.fixed(&*btn_close, scaled(BUTTON_WIDTH));
+    row.end();
+
+    layout.end(&mut window);
+    window.show();
+    window.end();
+
+    let mut window_clone = window.clone();
+
+    // Close with the window close button or Esc
+    close_on_escape(&mut window);
+
+    // Handlers for Close button
+    // preserve button's original color
+    let btn_close_color = btn_close.color();
+    // close window when clicked or with Alt+C
+    btn_close.set_callback(move |_| {
+        window_clone.hide();
+    });
+
+    // change color on hover
+    btn_close.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_close.on_leave(move |b| {
+        b.set_color(btn_close_color);
+    });
+
+    // Follow the clock once a second, the table is filled again only when its rows change so the
+    // selection stays
+    let app_clone = Rc::clone(application);
+    let mut shown_rows: Option<Vec<String>> = None;
+    let mut window_frame = window.clone();
+    every_seconds(&window, MERIDIAN_UPDATE_SECONDS, move || {
+        let app = app_clone.borrow();
+        let jd = Time::now().to_jd();
+        let (ra, dec) = zenith(&app.observer, jd);
+        zenith_label.set_label(&format!("{} {}   {} {}", tr("RA"), format_ra_hms(ra), tr("Dec"), format_dec_dms(dec)));
+        meridian_label.set_label(&format_ra_hms(ra));
+
+        let rows = meridian_rows(&app, jd);
+        if shown_rows.as_ref() != Some(&rows) {
+            summary.set_label(&match (rows.len(), app.targets.is_empty()) {
+                (_, true) => tr("Add targets in the targets window first").to_string(),
+                (0, false) => format!("{} {}° {} {}°", tr("No target near the meridian between"),
+                                      app.constraints.min_altitude, tr("and"), app.constraints.max_altitude),
+                (count, false) => format!("{} {} {} {}", count, tr("of"), app.targets.len(),
+                                          tr("targets near the meridian")),
+            });
+            let selected = table.value();
+            table.clear();
+            table.add(&table_header());
+            for row in &rows {
+                table.add(row);
+            }
+            if selected > 1 && selected <= table.size() {
+                table.select(selected);
+            }
+            shown_rows = Some(rows);
+        }
+        window_frame.redraw();
+    });
+
+    Some(window)
+}
@@ -1,3 +1,22 @@
+pub mod alignment;
+pub mod altitude_chart;
+pub mod almanac;
+pub mod countdown;
 pub mod darkness;
+pub mod darkness_calendar;
+pub mod environment;
+pub mod equipment;
+pub mod meridian;
 pub mod observatory;
-pub(crate) mod constraint;
\ No newline at end of file
+pub(crate) mod constraint;
+pub mod moon_separation;
+pub mod mosaic;
+pub mod quality_trends;
+pub mod site_comparison;
+pub mod sky_chart;
+pub mod sky_status;
+pub mod solar_system;
+pub mod target_details;
+pub mod target_editor;
+pub mod targets;
+pub(crate) mod export;
\ No newline at end of file
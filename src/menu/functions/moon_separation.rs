@@ -0,0 +1,124 @@
+// src/menu/functions/moon_separation.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use fltk::prelude::{GroupExt, InputExt, WidgetBase, WidgetExt};
+use fltk::{button, enums};
+use fltk::enums::Align;
+use fltk::input::Input;
+use fltk::window::Window;
+use fltk_evented::Listener;
+use crate::application::application::Application;
+use crate::application::reports::{export_night_range, moon_separation_report,
+                                  MOON_SEPARATION_REPORT_FILE};
+use crate::application::target::Target;
+use crate::menu::functions::export::confirm_export;
+use crate::widgets::date::DateInput;
+use crate::widgets::label::Label;
+use crate::widgets::radec::{Coordinate, RaDecInput};
+use crate::widgets::validated::ValidatedInput;
+use crate::menu::window_manager::new_tool_window;
+
+pub fn handle_moon_separation(application: &mut Rc<RefCell<Application>>) -> Option<Window> {
+    let Some(mut window) = new_tool_window("Moon separation", 290, 250) else {
+        return None;
+    };
+
+    // Target name
+    Label::new(10, 10, 80, 20, "Target", Align::Left | Align::Inside);
+    let mut name = Input::new(10, 30, 270, 25, "");
+    name.set_maximum_size(35);
+
+    // Right ascension
+    Label::new(10, 60, 80, 20, "RA", Align::Left | Align::Inside);
+    let mut ra = RaDecInput::new(10, 80, 130, 25, "", Coordinate::RightAscension);
+
+    // Declination
+    Label::new(150, 60, 80, 20, "Dec", Align::Left | Align::Inside);
+    let mut dec = RaDecInput::new(150, 80, 130, 25, "", Coordinate::Declination);
+
+    // Date range
+    Label::new(10, 110, 80, 20, "From", Align::Left | Align::Inside);
+    let mut start_date = DateInput::new(10, 130, 100, 25, "");
+    start_date.set_value(&application.borrow().time.to_string(Some("yyyymmdd")));
+
+    Label::new(150, 110, 80, 20, "To", Align::Left | Align::Inside);
+    let mut end_date = DateInput::new(150, 130, 100, 25, "");
+    end_date.set_value(&application.borrow().time.to_string(Some("yyyymmdd")));
+
+    // Export button
+    let mut btn_export: Listener<_> = button::Button::new(20, 200, 50, 30, "Export").into();
+    btn_export.clear_visible_focus();
+    btn_export.set_tooltip("Export nightly Moon separation to skycalc_moon_separation.csv");
+
+    // Close button
+    let mut btn_close: Listener<_> = button::Button::new(220, 200, 50, 30, "Close").into();
+    btn_close.clear_visible_focus();
+
+    window.show();
+    window.end();
+
+    let mut window_clone = window.clone();
+
+    // Window call back to avoid program termination when ESC is pressed
+    // from FLTK Book - FAQ
+    window.set_callback(|w| {
+        if fltk::app::event() == fltk::enums::Event::Close {
+            w.hide();
+        }
+    });
+
+    // Flag invalid values when leaving a field
+    ra.check_on_leave();
+    dec.check_on_leave();
+    start_date.check_on_leave();
+    end_date.check_on_leave();
+
+    // Handlers for Close button
+    // preserve button's original color
+    let btn_close_color = btn_close.color();
+    // close window when clicked
+    btn_close.on_click(move |_| {
+        window_clone.hide();
+    });
+
+    // change color on hover
+    btn_close.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_close.on_leave(move |b| {
+        b.set_color(btn_close_color);
+    });
+
+    // Handlers for Export button
+    // preserve button's original color
+    let btn_export_color = btn_export.color();
+    // Export to file when clicked
+    let app_clone = Rc::clone(&application);
+    btn_export.on_click(move |_| {
+        // nothing is exported while a value is invalid, the fields show what is wrong
+        let (Some(ra_value), Some(dec_value), Some(start), Some(end)) =
+            (ra.check(), dec.check(), start_date.check(), end_date.check()) else {
+            return;
+        };
+        let target = Target::new(&name.value(), ra_value, dec_value, 0.0);
+        let app = app_clone.borrow();
+        if confirm_export(&app, MOON_SEPARATION_REPORT_FILE, &export_night_range(&start, &end)) {
+            moon_separation_report(&app.observer, &target, &start, &end, &app.preferences);
+        }
+    });
+
+    // change color on hover
+    btn_export.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_export.on_leave(move |b| {
+        b.set_color(btn_export_color);
+    });
+
+    Some(window)
+}
@@ -0,0 +1,260 @@
+// src/menu/functions/mosaic.rs
+
+use std::cell::RefCell;
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+use fltk::browser::HoldBrowser;
+use fltk::prelude::{BrowserExt, GroupExt, InputExt, MenuExt, WidgetBase, WidgetExt};
+use fltk::{button, dialog, enums};
+use fltk::enums::Shortcut;
+use fltk::input::Input;
+use fltk::menu::Choice;
+use fltk::window::Window;
+use fltk_evented::Listener;
+use crate::application::application::Application;
+use crate::application::formatting::format_local;
+use crate::application::equipment::FieldOfView;
+use crate::application::mosaic::{night_panel_windows, plan_mosaic, Mosaic, DEFAULT_OVERLAP, OVERLAP_RANGE};
+use crate::application::reports::{export_night, mosaic_report, MOSAIC_REPORT_FILE};
+use crate::application::target::{Target, MAX_SIZE};
+use crate::menu::functions::export::{confirm_export, open_export};
+use crate::utils::angle::{format_dec_dms, format_ra_hms};
+use crate::widgets::keyboard::{close_on_escape, enter_moves_focus};
+use crate::widgets::layout::{fixed_label, label, scaled, spacer, FormLayout, BUTTON_HEIGHT, BUTTON_WIDTH,
+                             INPUT_HEIGHT};
+use crate::widgets::validated::number_field;
+use crate::menu::window_manager::new_tool_window;
+
+const TABLE_HEADER: &str = "@bPanel\t@bRA\t@bDec\t@bStart\t@bEnd\t@bHours";
+
+// Area covered in arc minutes
+const AREA_RANGE: RangeInclusive<f64> = 1.0..=MAX_SIZE;
+
+fn new_field(value: &str) -> Input {
+    let mut input = Input::default();
+    input.set_maximum_size(6);
+    input.set_value(value);
+    enter_moves_focus(&mut input);
+    input
+}
+
+// Size of a target for the area inputs, empty when unknown
+fn size_text(target: Option<&Target>) -> String {
+    target.filter(|t| t.size > 0.0).map_or(String::new(), |t| t.size.to_string())
+}
+
+// Inputs of the mosaic
+#[derive(Clone)]
+struct MosaicFields {
+    target: Choice,
+    width: Input,
+    height: Input,
+    overlap: Input,
+}
+
+impl MosaicFields {
+    // Mosaic of the inputs with the field of view, None with the invalid inputs marked
+    fn plan(&mut self, targets: &[Target], fov: Option<FieldOfView>) -> Option<(Target, Mosaic)> {
+        let target = targets.get(self.target.value().max(0) as usize)?.clone();
+        let values = (
+            number_field(&mut self.width, &AREA_RANGE),
+            number_field(&mut self.height, &AREA_RANGE),
+            number_field(&mut self.overlap, &OVERLAP_RANGE),
+        );
+        let (Some(width), Some(height), Some(overlap)) = values else {
+            return None;
+        };
+        match plan_mosaic(&target, width, height, &fov?, overlap) {
+            Ok(mosaic) => Some((target, mosaic)),
+            Err(e) => {
+                dialog::alert_default(&e);
+                None
+            }
+        }
+    }
+}
+
+pub fn handle_mosaic(application: &mut Rc<RefCell<Application>>) -> Option<Window> {
+    let Some(mut window) = new_tool_window("Mosaic", scaled(400), 400) else {
+        return None;
+    };
+    let (targets, fov) = {
+        let app = application.borrow();
+        (app.targets.clone(), app.equipment.active_field_of_view())
+    };
+    let mut layout = FormLayout::new(&window);
+
+    // Target of the configuration, its size is the default area
+    layout.labels(["Target"]);
+    let row = layout.row(INPUT_HEIGHT);
+    let mut target = Choice::default();
+    for t in &targets {
+        // '/' and '&' are menu markup
+        target.add_choice(&t.name.replace('/', "\\/").replace('&', "&&"));
+    }
+    target.set_value(0);
+    target.set_tooltip("Targets of the configuration, add others in the targets window");
+    row.end();
+
+    // Area to cover and overlap between panels
+    layout.labels(["Width (')", "Height (')", "Overlap (%)"]);
+    let row = layout.row(INPUT_HEIGHT);
+    let mut width = new_field(&size_text(targets.first()));
+    width.set_tooltip("Along the right ascension, with north up");
+    let height = new_field(&size_text(targets.first()));
+    let mut overlap = new_field(&DEFAULT_OVERLAP.to_string());
+    overlap.set_tooltip("Shared by neighbour panels for stitching, in percent of the field");
+    row.end();
+
+    // Field of view of the active setup
+    let mut row = layout.row(INPUT_HEIGHT);
+    fixed_label(&mut row, "Field", 50);
+    let mut summary = label(&fov.map_or("Select a telescope and a camera in Functions/Equipment".to_string(),
+                                        |fov| fov.description()));
+    row.end();
+
+    // Panels with their windows in the night
+    let mut table = HoldBrowser::default();
+    table.set_column_widths(&[scaled(50), scaled(80), scaled(80), scaled(60), scaled(60), scaled(45)]);
+    table.set_column_char('\t');
+    table.set_tooltip("Panels north to south, east to west in a row, with the long side of the sensor along \
+the right ascension");
+    table.add(TABLE_HEADER);
+    layout.stretch(150);
+
+    let mut row = layout.row(BUTTON_HEIGHT);
+
+    // Plan button
+    let mut btn_plan: Listener<_> = button::Button::default().with_label("&Plan").into();
+    btn_plan.clear_visible_focus();
+    btn_plan.set_shortcut(Shortcut::Alt | 'p');
+    btn_plan.set_tooltip("Lay the panels out and find when each one meets the altitude constraints");
+    row.fixed(&*btn_plan, scaled(BUTTON_WIDTH));
+    spacer();
+
+    // Export button
+    let mut btn_export: Listener<_> = button::Button::default().with_label("Export").into();
+    btn_export.clear_visible_focus();
+    btn_export.set_tooltip("Export the panel centers and windows to skycalc_mosaic.csv");
+    row.fixed(&*btn_export, scaled(BUTTON_WIDTH));
+    spacer();
+
+    // Close button
+    let mut btn_close: Listener<_> = button::Button::default().with_label("&Close").into();
+    btn_close.clear_visible_focus();
+    btn_close.set_shortcut(Shortcut::Alt | 'c');
+    row.fixed(&*btn_close, scaled(BUTTON_WIDTH));
+    row.end();
+
+    layout.end(&mut window);
+    window.show();
+    window.end();
+
+    let mut window_clone = window.clone();
+
+    // Close with the window close button or Esc
+    close_on_escape(&mut window);
+
+    if targets.is_empty() || fov.is_none() {
+        btn_plan.deactivate();
+        btn_export.deactivate();
+        if targets.is_empty() {
+            summary.set_label("Add a target in the targets window first");
+        }
+    }
+
+    let fields = MosaicFields { target: target.clone(), width, height, overlap };
+
+    // Selecting a target covers its size
+    let (mut width_clone, mut height_clone, targets_clone) = (fields.width.clone(), fields.height.clone(), targets.clone());
+    target.set_callback(move |c| {
+        let size = size_text(targets_clone.get(c.value().max(0) as usize));
+        width_clone.set_value(&size);
+        height_clone.set_value(&size);
+    });
+
+    // Handlers for Close button
+    // preserve button's original color
+    let btn_close_color = btn_close.color();
+    // close window when clicked or with Alt+C
+    btn_close.set_callback(move |_| {
+        window_clone.hide();
+    });
+
+    // change color on hover
+    btn_close.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_close.on_leave(move |b| {
+        b.set_color(btn_close_color);
+    });
+
+    // Handlers for Plan button
+    // preserve button's original color
+    let btn_plan_color = btn_plan.color();
+    // Fill the table with the panels of the night when clicked or with Alt+P
+    let (app_clone, mut fields_clone, targets_clone) = (Rc::clone(application), fields.clone(), targets.clone());
+    btn_plan.set_callback(move |_| {
+        let Some((_, mosaic)) = fields_clone.plan(&targets_clone, fov) else {
+            return;
+        };
+        let app = app_clone.borrow();
+        let windows = night_panel_windows(&mosaic, &app.observer, &app.time, &app.environment, &app.constraints);
+        let local = |jd: Option<f64>| {
+            jd.map_or("-".to_string(), |jd| format_local(app.observer.local_time(jd), &app.preferences))
+        };
+        table.clear();
+        table.add(TABLE_HEADER);
+        for (i, panel) in mosaic.panels.iter().enumerate() {
+            let window = windows.as_ref().and_then(|windows| windows.get(i));
+            table.add(&format!("{}\t{}\t{}\t{}\t{}\t{}", panel.name(), format_ra_hms(panel.ra),
+                               format_dec_dms(panel.dec), local(window.and_then(|w| w.start)),
+                               local(window.and_then(|w| w.end)),
+                               window.map_or("-".to_string(), |w| format!("{:.1}", w.hours))));
+        }
+        summary.set_label(&format!("{} x {} panels covering {:.1}' x {:.1}'{}", mosaic.columns, mosaic.rows,
+                                   mosaic.width, mosaic.height,
+                                   if windows.is_none() { ", no observation window tonight" } else { "" }));
+    });
+
+    // change color on hover
+    btn_plan.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_plan.on_leave(move |b| {
+        b.set_color(btn_plan_color);
+    });
+
+    // Handlers for Export button
+    // preserve button's original color
+    let btn_export_color = btn_export.color();
+    // Export to file and open it when clicked
+    let (app_clone, mut fields_clone) = (Rc::clone(application), fields.clone());
+    btn_export.set_callback(move |_| {
+        let Some((target, mosaic)) = fields_clone.plan(&targets, fov) else {
+            return;
+        };
+        let app = app_clone.borrow();
+        if confirm_export(&app, MOSAIC_REPORT_FILE, &export_night(&app.time)) {
+            mosaic_report(&app.observer, &app.time, &app.environment, &app.constraints, &target, &mosaic,
+                          &app.preferences);
+            open_export(MOSAIC_REPORT_FILE);
+        }
+    });
+
+    // change color on hover
+    btn_export.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_export.on_leave(move |b| {
+        b.set_color(btn_export_color);
+    });
+
+    Some(window)
+}
@@ -1,201 +1,441 @@
 use std::cell::RefCell;
 use std::rc::Rc;
-use fltk::prelude::{GroupExt, InputExt, WidgetBase, WidgetExt, WindowExt};
-use fltk::{app, button, enums, window};
-use fltk::enums::{Align, Event, Key};
-use fltk::input::{FloatInput, Input, IntInput};
+use std::sync::mpsc::channel;
+use fltk::browser::HoldBrowser;
+use fltk::prelude::{BrowserExt, GroupExt, InputExt, WidgetBase, WidgetExt};
+use fltk::{button, dialog, enums};
+use fltk::dialog::{FileDialog, FileDialogType};
+use fltk::enums::{CallbackTrigger, Shortcut};
+use fltk::input::Input;
+use fltk::window::Window;
 use fltk_evented::Listener;
-use libm::fabs;
 use crate::application::application::Application;
+use crate::application::elevation::lookup_elevation;
+use crate::application::light_pollution::{sky_brightness_at, SkyBrightnessGrid};
+use crate::application::mount::{mount_driver, MountSite};
+use crate::application::observatory_library::{import_sites, ObservatoryLibrary};
+use crate::application::observer::{degrees_from_str, Observer};
+use crate::application::time::Time;
+use crate::application::timezones::{find_zone, migrate_zones, zone_migrations};
+use crate::application::undo::Setting;
+use crate::application::units::ElevationUnit;
+use crate::utils::i18n::tr;
 use crate::widgets::angle::AngleInput;
-use crate::widgets::label::Label;
+use crate::widgets::elevation::ElevationInput;
+use crate::widgets::keyboard::{close_on_escape, enter_moves_focus};
+use crate::widgets::timezone::TimezoneInput;
+use crate::widgets::validated::ValidatedInput;
+use crate::widgets::layout::{fixed_label, label, scaled, spacer, FormLayout, BUTTON_HEIGHT, BUTTON_WIDTH, INPUT_HEIGHT,
+                             LABEL_HEIGHT};
+use crate::menu::window_manager::{every_frame, new_tool_window};
 
-pub fn handle_observatory(mut application: &mut Rc<RefCell<Application>>) -> bool {
-    let mut window = window::Window::default()
-        .with_label("Observatory setup")
-        .with_size(290, 250)
-        .center_screen();
-    window.make_modal(true);
+// Sites listed at most in the timezone confirmation
+const MAX_MIGRATION_LINES: usize = 15;
+
+// Reply of the mount driver, read on a worker thread
+enum MountReply {
+    Pulled(Result<MountSite, String>),
+    Pushed(Result<String, String>),
+}
+
+// Adds the sites to the list, the ones of the library in italics
+fn list_sites(list: &mut HoldBrowser, sites: &[(Observer, bool)]) {
+    list.clear();
+    for (site, imported) in sites {
+        let name = site.name.as_deref().unwrap_or_default();
+        list.add(&if *imported { format!("@i@.{}", name) } else { format!("@.{}", name) });
+    }
+}
+
+pub fn handle_observatory(mut application: &mut Rc<RefCell<Application>>) -> Option<Window> {
+    let Some(mut window) = new_tool_window(tr("Observatory setup"), scaled(290), 430) else {
+        return None;
+    };
+    let mut layout = FormLayout::new(&window);
 
     // Name
-    Label::new(10, 10, 80, 20, "Name", Align::Left | Align::Inside);
-    let mut name = Input::new(10, 30, 270, 25, "");
+    layout.labels([tr("Name")]);
+    let row = layout.row(INPUT_HEIGHT);
+    let mut name = Input::default();
     name.set_maximum_size(35);
     if let Some(name_str) = &application.borrow_mut().observer.name {
         name.set_value(name_str.as_str());
     }
+    row.end();
 
-    // Elevation
-    Label::new(10, 60, 80, 20, "Elevation (m)", Align::Left | Align::Inside);
-    let mut elevation = IntInput::new(10, 80, 80, 25, "");
-    elevation.set_maximum_size(4);
-    elevation.set_value(&application.borrow_mut().observer.elevation.to_string());
-
-    // Latitude
-    Label::new(150, 60, 80, 20, "Latitude", Align::Left | Align::Inside);
-    let mut latitude = AngleInput::new(150, 80, 130, 25, "", -90., 90.);
-    latitude.set_value(&format!("{:.6}",&application.borrow_mut().observer.latitude));
+    // Elevation and latitude, the lookup button sits between them. The elevation is typed in the
+    // unit of the preferences and kept in meters.
+    let elevation_unit = application.borrow().preferences.units.elevation;
+    let mut row = layout.row(LABEL_HEIGHT);
+    fixed_label(&mut row, tr(match elevation_unit {
+        ElevationUnit::Meters => "Elevation (m)",
+        ElevationUnit::Feet => "Elevation (ft)",
+    }), 135);
+    label(tr("Latitude"));
+    row.end();
+    let mut row = layout.row(INPUT_HEIGHT);
+    let mut elevation = ElevationInput::new(0, 0, 0, 0, "").with_unit(elevation_unit);
+    elevation.set_elevation(application.borrow().observer.elevation);
+    row.fixed(&elevation.elevation_input, scaled(80));
+    let mut btn_lookup: Listener<_> = button::Button::default().with_label(tr("Lookup")).into();
+    btn_lookup.clear_visible_focus();
+    btn_lookup.set_tooltip(tr("Elevation at the latitude and longitude from the SRTM tiles set in preferences"));
+    row.fixed(&*btn_lookup, scaled(BUTTON_WIDTH));
+    let dms = application.borrow().preferences.dms_coordinates;
+    let mut latitude = AngleInput::new(0, 0, 0, 0, "", -90., 90.);
+    if dms {
+        latitude = latitude.with_dms(true);
+    }
+    latitude.set_angle(application.borrow().observer.latitude);
+    row.end();
 
-    // Timezone
-    Label::new(10, 110, 80, 20, "TZ", Align::Left | Align::Inside);
-    let mut timezone = FloatInput::new(10, 130, 50, 25, "");
+    // Timezone and longitude, in the columns of the row above
+    let mut row = layout.row(LABEL_HEIGHT);
+    fixed_label(&mut row, tr("TZ"), 135);
+    label(tr("Longitude"));
+    row.end();
+    let mut row = layout.row(INPUT_HEIGHT);
+    let mut timezone = TimezoneInput::new(0, 0, 0, 0, "");
     timezone.set_value(&application.borrow_mut().observer.timezone.to_string());
+    row.fixed(&timezone.timezone_input, scaled(50));
+    let timezone_spacer = spacer();
+    row.fixed(&timezone_spacer, scaled(80));
+    let mut longitude = AngleInput::new(0, 0, 0, 0, "", -180., 180.);
+    if dms {
+        longitude = longitude.with_dms(false);
+    }
+    longitude.set_angle(application.borrow().observer.longitude);
+    row.end();
 
-    // Longitude
-    Label::new(150, 110, 80, 20, "Longitude", Align::Left | Align::Inside);
-    let mut longitude = AngleInput::new(150, 130, 130, 25, "", -180., 180.);
-    longitude.set_value(&format!("{:.6}",&application.borrow_mut().observer.longitude));
+    // Site picker, selecting a site fills the fields above. Imported sites are listed first.
+    layout.labels([tr("Find site")]);
+    let mut row = layout.row(INPUT_HEIGHT);
+    let mut search = Input::default();
+    search.set_tooltip(tr("Type part of a city or observatory name"));
+    search.set_trigger(CallbackTrigger::Changed);
+    let mut btn_import: Listener<_> = button::Button::default().with_label(tr("Import")).into();
+    btn_import.clear_visible_focus();
+    btn_import.set_tooltip(tr("Add the sites of a Stellarium location file, a N.I.N.A. profile or a Cartes du Ciel \
+configuration to the library"));
+    row.fixed(&*btn_import, scaled(BUTTON_WIDTH));
+    let mut btn_zones: Listener<_> = button::Button::default().with_label(tr("Zones")).into();
+    btn_zones.clear_visible_focus();
+    btn_zones.set_tooltip(tr("Set the timezone of the library sites saved with a UTC offset only, guessed from \
+the nearest city"));
+    row.fixed(&*btn_zones, scaled(BUTTON_WIDTH));
+    row.end();
+    let mut sites = HoldBrowser::default();
+    layout.stretch(130);
+    let library = Rc::new(RefCell::new(ObservatoryLibrary::load()));
+    list_sites(&mut sites, &library.borrow().search(""));
+
+    // Sky brightness from the grid set in preferences, follows the latitude and longitude fields
+    let sky_grid = application
+        .borrow()
+        .preferences
+        .sky_brightness_file
+        .as_ref()
+        .map(|file_path| SkyBrightnessGrid::load(file_path));
+    let mut row = layout.row(LABEL_HEIGHT);
+    fixed_label(&mut row, tr("Sky"), 50);
+    let mut sky_label = label("");
+    if sky_grid.is_none() {
+        sky_label.set_label(tr("Set a sky brightness grid in preferences"));
+    }
+    row.end();
+
+    let mut row = layout.row(BUTTON_HEIGHT);
 
     // Apply button
-    let mut btn_apply: Listener<_> = button::Button::new(20, 200, 50, 30, "Apply").into();
+    let mut btn_apply: Listener<_> = button::Button::default().with_label(tr("&Apply")).into();
     btn_apply.clear_visible_focus();
+    btn_apply.set_shortcut(Shortcut::Alt | 'a');
+    row.fixed(&*btn_apply, scaled(BUTTON_WIDTH));
+    spacer();
+
+    // Mount buttons, the driver is set in preferences
+    let mut btn_pull: Listener<_> = button::Button::default().with_label(tr("Pull")).into();
+    btn_pull.clear_visible_focus();
+    btn_pull.set_tooltip(tr("Fill the fields with the site set in the mount driver"));
+    row.fixed(&*btn_pull, scaled(BUTTON_WIDTH));
+    let mut btn_push: Listener<_> = button::Button::default().with_label(tr("Push")).into();
+    btn_push.clear_visible_focus();
+    btn_push.set_tooltip(tr("Send the site in the fields and the clock to the mount driver"));
+    row.fixed(&*btn_push, scaled(BUTTON_WIDTH));
+    spacer();
 
     // Close button
-    let mut btn_close: Listener<_> = button::Button::new(220, 200, 50, 30, "Close").into();
+    let mut btn_close: Listener<_> = button::Button::default().with_label(tr("&Close")).into();
     btn_close.clear_visible_focus();
+    btn_close.set_shortcut(Shortcut::Alt | 'c');
+    row.fixed(&*btn_close, scaled(BUTTON_WIDTH));
+    row.end();
 
+    layout.end(&mut window);
     window.show();
     window.end();
 
     let mut window_clone = window.clone();
-    let name_input_clone = name.clone();
-    let latitude_input_clone = latitude.angle_input.clone();
-    let latitude_update_clone = latitude.angle_input.clone();
-    let longitude_input_clone = longitude.angle_input.clone();
-    let longitude_update_clone = longitude.angle_input.clone();
-    let timezone_input_clone = timezone.clone();
-    let timezone_update_clone = timezone.clone();
-    let elevation_input_clone = elevation.clone();
-    let elevation_update_clone = elevation.clone();
-
-    // Window call back to avoid program termination when ESC is pressed
-    // from FLTK Book - FAQ
-    window.set_callback(|w| {
-        if fltk::app::event() == fltk::enums::Event::Close {
-            w.hide();
+    let mut latitude_update_clone = latitude.clone();
+    let mut longitude_update_clone = longitude.clone();
+    let mut timezone_update_clone = timezone.clone();
+    let mut elevation_update_clone = elevation.clone();
+    let latitude_sky_clone = latitude.angle_input.clone();
+    let longitude_sky_clone = longitude.angle_input.clone();
+
+    // Fill the elevation from the SRTM tiles
+    let mut elevation_lookup_clone = elevation.clone();
+    let mut latitude_lookup_clone = latitude.clone();
+    let mut longitude_lookup_clone = longitude.clone();
+    let app_lookup_clone = Rc::clone(&application);
+    btn_lookup.on_click(move |_| {
+        let directory = app_lookup_clone.borrow().preferences.dem_directory.clone();
+        let Some(directory) = directory else {
+            dialog::alert_default(tr("Set the SRTM elevation tiles directory in preferences"));
+            return;
+        };
+        let (Some(latitude_value), Some(longitude_value)) =
+            (latitude_lookup_clone.check(), longitude_lookup_clone.check()) else {
+            return;
+        };
+        match lookup_elevation(&directory, latitude_value, longitude_value) {
+            Ok(elevation_value) => {
+                elevation_lookup_clone.set_elevation(elevation_value.max(0));
+                elevation_lookup_clone.check();
+            }
+            Err(e) => dialog::alert_default(&e),
         }
     });
 
-    // Listener::from_widget(latitude_input_clone).on(fltk::enums::Event::Unfocus, move |_| {
-    //     latitude.validate();
-    // });
+    // change color on hover
+    let btn_lookup_color = btn_lookup.color();
+    btn_lookup.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
 
-    latitude_input_clone.clone().handle(move |_, ev| {
-        match ev {
-            Event::Unfocus => {
-                latitude.validate();
-                true
-            }
-            Event::KeyDown => {
-                let key = app::event_key();
-                if key == Key::Enter {
-                    latitude.validate();
-                    true
-                } else {
-                    false
-                }
-            }
-            _ => false
-        }
+    // reset color on leave
+    btn_lookup.on_leave(move |b| {
+        b.set_color(btn_lookup_color);
     });
 
+    // The mount driver is called on a worker thread, the buttons are off until it replies
+    let (mount_sender, mount_receiver) = channel();
+    let mut btn_pull_busy_clone = (*btn_pull).clone();
+    let mut btn_push_busy_clone = (*btn_push).clone();
+    let mut btn_pull_reply_clone = (*btn_pull).clone();
+    let mut btn_push_reply_clone = (*btn_push).clone();
+
+    // Fill the fields with the site of the mount, changes are kept when Apply is clicked
+    let app_pull_clone = Rc::clone(&application);
+    let pull_sender = mount_sender.clone();
+    btn_pull.on_click(move |b| {
+        let address = app_pull_clone.borrow().preferences.mount_address.clone();
+        let Some(address) = address else {
+            dialog::alert_default(tr("Set the mount driver in preferences"));
+            return;
+        };
+        b.deactivate();
+        btn_push_busy_clone.deactivate();
+        let sender = pull_sender.clone();
+        std::thread::spawn(move || {
+            // the receiver is gone if the window was closed meanwhile
+            let _ = sender.send(MountReply::Pulled(mount_driver(&address).and_then(|driver| driver.pull_site())));
+        });
+    });
 
-    // Listener::from_widget(longitude_input_clone).on(fltk::enums::Event::Unfocus, move |_| {
-    //     longitude.validate();
-    // });
+    // Send the site in the fields to the mount, nothing is sent while a value is invalid
+    let mut elevation_push_clone = elevation.clone();
+    let mut latitude_push_clone = latitude.clone();
+    let mut longitude_push_clone = longitude.clone();
+    let mut timezone_push_clone = timezone.clone();
+    let app_push_clone = Rc::clone(&application);
+    btn_push.on_click(move |b| {
+        let address = app_push_clone.borrow().preferences.mount_address.clone();
+        let Some(address) = address else {
+            dialog::alert_default(tr("Set the mount driver in preferences"));
+            return;
+        };
+        let (Some(latitude), Some(longitude), Some(timezone), Some(elevation)) = (
+            latitude_push_clone.check(),
+            longitude_push_clone.check(),
+            timezone_push_clone.check(),
+            elevation_push_clone.check(),
+        ) else {
+            return;
+        };
+        let site = MountSite { latitude, longitude, elevation: elevation as f64, utc_offset: Some(timezone) };
+        b.deactivate();
+        btn_pull_busy_clone.deactivate();
+        let sender = mount_sender.clone();
+        std::thread::spawn(move || {
+            let pushed = mount_driver(&address).and_then(|driver| {
+                driver.push_site(&site, &Time::now()).map(|_| driver.name())
+            });
+            let _ = sender.send(MountReply::Pushed(pushed));
+        });
+    });
 
-    longitude_input_clone.clone().handle(move |_, ev| {
-        match ev {
-            Event::Unfocus => {
-                longitude.validate();
-                true
-            }
-            Event::KeyDown => {
-                let key = app::event_key();
-                if key == Key::Enter {
-                    longitude.validate();
-                    true
-                } else {
-                    false
-                }
+    // change color on hover
+    let btn_pull_color = btn_pull.color();
+    btn_pull.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_pull.on_leave(move |b| {
+        b.set_color(btn_pull_color);
+    });
+
+    // change color on hover
+    let btn_push_color = btn_push.color();
+    btn_push.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_push.on_leave(move |b| {
+        b.set_color(btn_push_color);
+    });
+
+    // Filter sites while typing
+    let mut sites_search_clone = sites.clone();
+    let library_search_clone = Rc::clone(&library);
+    search.set_callback(move |s| {
+        list_sites(&mut sites_search_clone, &library_search_clone.borrow().search(&s.value()));
+    });
+
+    // Add the sites of a file of another software to the library, kept for the next sessions
+    let mut sites_import_clone = sites.clone();
+    let search_import_clone = search.clone();
+    let library_import_clone = Rc::clone(&library);
+    btn_import.on_click(move |_| {
+        let mut chooser = FileDialog::new(FileDialogType::BrowseFile);
+        chooser.set_filter(&format!("{}\t*.{{txt,profile,xml,ini,cdc}}", tr("Site definitions")));
+        chooser.show();
+        let file_path = chooser.filename();
+        if file_path.as_os_str().is_empty() {
+            return;
+        }
+        let import = std::fs::read_to_string(&file_path)
+            .map_err(|e| format!("Unable to read {}: {}", file_path.display(), e))
+            .and_then(|contents| import_sites(&contents));
+        let import = match import {
+            Ok(import) => import,
+            Err(e) => {
+                dialog::alert_default(&e);
+                return;
             }
-            _ => false
+        };
+        let mut library = library_import_clone.borrow_mut();
+        let added = library.add(&import.sites);
+        if let Err(e) = library.save() {
+            dialog::alert_default(&format!("{}: {}", tr("Unable to save the observatory library"), e));
+        }
+        list_sites(&mut sites_import_clone, &library.search(&search_import_clone.value()));
+        let mut message = format!("{} {}: {}\n{}: {}, {}: {}", tr("Sites read from the file of"),
+                                  import.format.name(), import.sites.len(), tr("New"), added, tr("Updated"),
+                                  import.sites.len() - added);
+        for note in &import.notes {
+            message.push_str(&format!("\n{}", note));
         }
+        dialog::message_default(&message);
     });
 
-    // Listener::from_widget(timezone_input_clone).on(fltk::enums::Event::Unfocus, move |_| {
-    //     let timezone_value = timezone.value().parse::<f64>().unwrap_or(0.0);
-    //     if fabs(timezone_value) > 12.0 { timezone.set_value( "0.0" )};
-    // });
-
-    let mut app_clone = application.clone();
-    timezone_input_clone.clone().handle(move |_, ev| {
-        match ev {
-            Event::Unfocus => {
-                let timezone_value = timezone.value().parse::<f64>().unwrap_or(0.0);
-                if fabs(timezone_value) > 12.0 { timezone.set_value( "0.0" )};
-                app_clone.borrow_mut().observer.timezone = timezone_value;
-                timezone.set_value(&app_clone.borrow_mut().observer.timezone.to_string());
-                true
-            }
-            Event::KeyDown => {
-                let key = app::event_key();
-                if key == Key::Enter {
-                    let timezone_value = timezone.value().parse::<f64>().unwrap_or(0.0); // Handle potential parse errors. Default to 0.0
-                    if fabs(timezone_value) > 12.0 { timezone.set_value( "0.0" )};
-                    app_clone.borrow_mut().observer.timezone = timezone_value;
-                    timezone.set_value(&app_clone.borrow_mut().observer.timezone.to_string());
-
-                    // Optionally move focus
-                    // next_widget.take_focus();
-                    true
-                } else {
-                    false
-                }
-            }
-            _ => false,
+    // Named timezones for the library sites with a UTC offset only, the list is confirmed first
+    let mut sites_zones_clone = sites.clone();
+    let search_zones_clone = search.clone();
+    let library_zones_clone = Rc::clone(&library);
+    btn_zones.on_click(move |_| {
+        let mut library = library_zones_clone.borrow_mut();
+        let migrations = zone_migrations(&library.sites);
+        if migrations.is_empty() {
+            dialog::message_default(tr("Every library site has a timezone or none is known near it"));
+            return;
+        }
+        let lines: Vec<String> = migrations.iter().map(|m| m.describe(&library.sites[m.index])).collect();
+        let shown = lines.iter().take(MAX_MIGRATION_LINES).cloned().collect::<Vec<String>>().join("\n");
+        let more = match lines.len().saturating_sub(MAX_MIGRATION_LINES) {
+            0 => String::new(),
+            hidden => format!("\n... (+{})", hidden),
+        };
+        let question = format!("{}\n\n{}{}", tr("Set these timezones, the offsets are the standard time of each zone?"),
+                               shown, more);
+        if dialog::choice2_default(&question, tr("Cancel"), tr("Update"), "") != Some(1) {
+            return;
+        }
+        migrate_zones(&mut library.sites, &migrations);
+        if let Err(e) = library.save() {
+            dialog::alert_default(&format!("{}: {}", tr("Unable to save the observatory library"), e));
         }
+        list_sites(&mut sites_zones_clone, &library.search(&search_zones_clone.value()));
     });
 
+    // change color on hover, reset color on leave
+    let btn_zones_color = btn_zones.color();
+    btn_zones.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+    btn_zones.on_leave(move |b| {
+        b.set_color(btn_zones_color);
+    });
 
-    // Listener::from_widget(elevation_input_clone).on(fltk::enums::Event::Unfocus, move |_| {
-    //     let elevation_value = elevation.value().parse::<f64>().unwrap_or(0.0);
-    //     if elevation_value < 0.0 { elevation.set_value("0.0") };
-    // });
-    let mut app_clone = application.clone();
-    elevation_input_clone.clone().handle(move |_, ev| {
-        match ev {
-            Event::Unfocus => {
-                let elevation_value = elevation.value().parse::<f64>().unwrap_or(0.0);
-                if elevation_value < 0.0 { elevation.set_value("0.0") };
-                app_clone.borrow_mut().observer.elevation = elevation_value as i64;
-                elevation.set_value(&app_clone.borrow_mut().observer.elevation.to_string());
-                true
-            }
-            Event::KeyDown => {
-                let key = app::event_key();
-                if key == Key::Enter {
-                    let elevation_value = elevation.value().parse::<f64>().unwrap_or(0.0);
-                    if elevation_value < 0.0 { elevation.set_value("0.0") };
-                    app_clone.borrow_mut().observer.elevation = elevation_value as i64;
-                    elevation.set_value(&app_clone.borrow_mut().observer.elevation.to_string());
-
-                    // Optionally move focus
-                    // next_widget.take_focus();
-                    true
-                } else {
-                    false
-                }
-            }
-            _ => false,
+    // change color on hover
+    let btn_import_color = btn_import.color();
+    btn_import.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_import.on_leave(move |b| {
+        b.set_color(btn_import_color);
+    });
+
+    // Fill the fields with the selected site, changes are kept when Apply is clicked
+    let search_clone = search.clone();
+    let mut name_site_clone = name.clone();
+    let mut elevation_site_clone = elevation.clone();
+    let mut latitude_site_clone = latitude.clone();
+    let mut longitude_site_clone = longitude.clone();
+    let mut timezone_site_clone = timezone.clone();
+    let library_site_clone = Rc::clone(&library);
+    let zone = Rc::new(RefCell::new(application.borrow().observer.zone.clone()));
+    let zone_site_clone = Rc::clone(&zone);
+    sites.set_callback(move |b| {
+        let line = b.value();
+        if line < 1 {
+            return;
+        }
+        if let Some((site, _)) = library_site_clone.borrow().search(&search_clone.value()).get(line as usize - 1) {
+            name_site_clone.set_value(site.name.as_deref().unwrap_or_default());
+            elevation_site_clone.set_elevation(site.elevation);
+            latitude_site_clone.set_angle(site.latitude);
+            longitude_site_clone.set_angle(site.longitude);
+            timezone_site_clone.set_value(&site.timezone.to_string());
+            *zone_site_clone.borrow_mut() = site.zone.clone();
+            elevation_site_clone.check();
+            latitude_site_clone.check();
+            longitude_site_clone.check();
+            timezone_site_clone.check();
         }
     });
 
+    // Close with the window close button or Esc
+    close_on_escape(&mut window);
+
+    // Flag invalid values when leaving a field, Apply is blocked until they are fixed. Enter moves
+    // to the next field in tab order
+    enter_moves_focus(&mut name);
+    enter_moves_focus(&mut search);
+    latitude.check_on_leave();
+    longitude.check_on_leave();
+    timezone.check_on_leave();
+    elevation.check_on_leave();
+
     // Handlers for Close button
     // preserve button's original color
     let btn_close_color = btn_close.color();
-    // close window when clicked
-    btn_close.on_click(move |b| {
+    // close window when clicked or with Alt+C
+    btn_close.set_callback(move |_| {
         window_clone.hide();
     });
 
@@ -212,15 +452,29 @@ pub fn handle_observatory(mut application: &mut Rc<RefCell<Application>>) -> boo
     // Handlers for Apply button
     // preserve button's original color
     let btn_apply_color = btn_apply.color();
-    // Apply changes
+    // Apply changes when clicked or with Alt+A
     let mut app_clone = Rc::clone(&application);
     btn_apply.set_callback( move |_| {
-        // update observer
-        app_clone.borrow_mut().observer.name = Some(name.value().to_string());
-        app_clone.borrow_mut().observer.elevation = elevation_update_clone.value().parse().unwrap_or(0); // Handle parsing errors
-        app_clone.borrow_mut().observer.latitude = latitude_update_clone.value().parse().unwrap_or(0.0);
-        app_clone.borrow_mut().observer.longitude = longitude_update_clone.value().parse().unwrap_or(0.0);
-        app_clone.borrow_mut().observer.timezone = timezone_update_clone.value().parse().unwrap_or(0.0); // Handle parsing errors
+        // nothing is applied while a value is invalid, the fields show what is wrong
+        let (Some(latitude), Some(longitude), Some(timezone), Some(elevation)) = (
+            latitude_update_clone.check(),
+            longitude_update_clone.check(),
+            timezone_update_clone.check(),
+            elevation_update_clone.check(),
+        ) else {
+            return;
+        };
+
+        // update observer, as one edit that Edit/Undo reverts
+        let mut observer = app_clone.borrow().observer.clone();
+        observer.name = Some(name.value().to_string());
+        observer.elevation = elevation;
+        observer.latitude = latitude;
+        observer.longitude = longitude;
+        observer.timezone = timezone;
+        // the named zone is dropped when the offset typed is not its standard time
+        observer.zone = zone.borrow().clone().filter(|name| find_zone(name).is_some_and(|z| z.offset == timezone));
+        app_clone.borrow_mut().apply_setting(Setting::Observer(observer));
     });
 
     // change color on hover
@@ -233,12 +487,52 @@ pub fn handle_observatory(mut application: &mut Rc<RefCell<Application>>) -> boo
         b.set_color(btn_apply_color);
     });
 
-    while window.shown() {
-        fltk::app::wait();
+    let mut sky_location = String::new();
+    let mut window_frame = window.clone();
+    let mut elevation_mount_clone = elevation.clone();
+    let mut latitude_mount_clone = latitude.clone();
+    let mut longitude_mount_clone = longitude.clone();
+    let mut timezone_mount_clone = timezone.clone();
+    every_frame(&window, move || {
+        // Reply of the mount driver
+        if let Ok(reply) = mount_receiver.try_recv() {
+            btn_pull_reply_clone.activate();
+            btn_push_reply_clone.activate();
+            match reply {
+                MountReply::Pulled(Ok(site)) => {
+                    elevation_mount_clone.set_elevation(site.elevation.round() as i64);
+                    latitude_mount_clone.set_angle(site.latitude);
+                    longitude_mount_clone.set_angle(site.longitude);
+                    if let Some(utc_offset) = site.utc_offset {
+                        timezone_mount_clone.set_value(&utc_offset.to_string());
+                    }
+                    elevation_mount_clone.check();
+                    latitude_mount_clone.check();
+                    longitude_mount_clone.check();
+                    timezone_mount_clone.check();
+                }
+                MountReply::Pushed(Ok(name)) => dialog::message_default(&format!("{} {}", tr("Site sent to"), name)),
+                MountReply::Pulled(Err(e)) | MountReply::Pushed(Err(e)) => dialog::alert_default(&e),
+            }
+        }
 
-        // Reduce frame updated to reduce CPU consumption
-        std::thread::sleep(std::time::Duration::from_millis(32));
-    }
+        // Update sky brightness when the location changes
+        if let Some(grid) = &sky_grid {
+            let location = format!("{} {}", latitude_sky_clone.value(), longitude_sky_clone.value());
+            if location != sky_location {
+                match grid {
+                    Ok(grid) => sky_label.set_label(&sky_brightness_at(
+                        grid,
+                        degrees_from_str(&latitude_sky_clone.value(), -90.0, 90.0),
+                        degrees_from_str(&longitude_sky_clone.value(), -180.0, 180.0),
+                    )),
+                    Err(e) => sky_label.set_label(e),
+                }
+                sky_location = location;
+                window_frame.redraw();
+            }
+        }
+    });
 
-    true
+    Some(window)
 }
@@ -0,0 +1,172 @@
+// src/menu/functions/quality_trends.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::mpsc::channel;
+use fltk::prelude::{GroupExt, WidgetBase, WidgetExt};
+use fltk::{button, enums};
+use fltk::enums::{Align, Color, FrameType};
+use fltk::frame::Frame;
+use fltk::window::Window;
+use fltk_evented::Listener;
+use crate::application::application::Application;
+use crate::application::quality::{best_stretch, quality_trend, NightQuality, QualityHistory, TREND_DAYS};
+use crate::application::time::Time;
+use crate::widgets::label::Label;
+use crate::widgets::night_vision::night_color;
+use crate::menu::window_manager::{every_frame, new_tool_window};
+
+// Chart geometry, one bar per night and 2 pixels per score point
+const BAR_WIDTH: i32 = 4;
+const CHART_LEFT: i32 = 38;
+const CHART_TOP: i32 = 40;
+const CHART_HEIGHT: i32 = 200;
+// Nights of the best stretch looked for ahead
+const STRETCH_NIGHTS: usize = 7;
+
+// Bar color, from dark red (poor) to bright green (perfect), nights with a forecast are brighter
+fn score_color(night: &NightQuality) -> Color {
+    let level = night.score / 100.0;
+    let boost = if night.cloud_cover.is_some() { 40.0 } else { 0.0 };
+    Color::from_rgb(
+        (160.0 * (1.0 - level) + boost).min(255.0) as u8,
+        (40.0 + 180.0 * level + boost).min(255.0) as u8,
+        (40.0 + boost) as u8,
+    )
+}
+
+pub fn handle_quality_trends(application: &mut Rc<RefCell<Application>>) -> Option<Window> {
+    // Local date of today
+    let observer = application.borrow().observer.clone();
+    let today = observer.local_time(Time::now().to_jd()).to_local();
+    let chart_width = BAR_WIDTH * (2 * TREND_DAYS + 1) as i32;
+
+    let Some(mut window) = new_tool_window("Night quality trends", CHART_LEFT + chart_width + 38, 340) else {
+        return None;
+    };
+
+    // Title
+    let mut title = Label::new(10, 10, chart_width, 20, "", Align::Left | Align::Inside);
+    title.set_label(&format!(
+        "{} - {} nights before and after {}",
+        observer.name.as_deref().unwrap_or("My observatory"),
+        TREND_DAYS,
+        today.to_string(Some("yyyymmdd"))
+    ));
+
+    // Score axis
+    for (score, y) in [(100, CHART_TOP), (50, CHART_TOP + CHART_HEIGHT / 2), (0, CHART_TOP + CHART_HEIGHT)] {
+        Label::new(4, y - 10, 30, 20, &score.to_string(), Align::Right | Align::Inside);
+    }
+    let mut baseline = Frame::new(CHART_LEFT, CHART_TOP + CHART_HEIGHT, chart_width, 1, "");
+    baseline.set_frame(FrameType::FlatBox);
+    baseline.set_color(Color::Dark2);
+
+    // Shown until the nights are computed
+    let mut computing = Label::new(CHART_LEFT, CHART_TOP + CHART_HEIGHT / 2 - 10, chart_width, 20,
+                                   "Computing the nights...", Align::Center | Align::Inside);
+
+    // Date axis
+    let mut first_date = Label::new(CHART_LEFT, CHART_TOP + CHART_HEIGHT + 5, 100, 20, "", Align::Left | Align::Inside);
+    Label::new(CHART_LEFT + chart_width / 2 - 50, CHART_TOP + CHART_HEIGHT + 5, 100, 20, "today",
+               Align::Center | Align::Inside);
+    let mut last_date = Label::new(CHART_LEFT + chart_width - 100, CHART_TOP + CHART_HEIGHT + 5, 100, 20, "",
+                                   Align::Right | Align::Inside);
+
+    // Best stretch ahead and recorded nights
+    let mut best = Label::new(10, 280, chart_width - 40, 20, "", Align::Left | Align::Inside);
+    let mut recorded = Label::new(10, 300, chart_width - 40, 20, "", Align::Left | Align::Inside);
+
+    // Close button
+    let mut btn_close: Listener<_> = button::Button::new(CHART_LEFT + chart_width - 50, 295, 50, 30, "Close").into();
+    btn_close.clear_visible_focus();
+
+    window.end();
+    window.show();
+
+    let mut window_clone = window.clone();
+
+    // Window call back to avoid program termination when ESC is pressed
+    // from FLTK Book - FAQ
+    window.set_callback(|w| {
+        if fltk::app::event() == fltk::enums::Event::Close {
+            w.hide();
+        }
+    });
+
+    // Handlers for Close button
+    // preserve button's original color
+    let btn_close_color = btn_close.color();
+    // close window when clicked
+    btn_close.on_click(move |_| {
+        window_clone.hide();
+    });
+
+    // change color on hover
+    btn_close.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_close.on_leave(move |b| {
+        b.set_color(btn_close_color);
+    });
+
+    // The nights are computed on a worker thread and charted once they arrive, so the window
+    // opens at once
+    let (sender, receiver) = channel();
+    std::thread::spawn(move || {
+        // the receiver is gone if the window was closed meanwhile
+        let _ = sender.send(quality_trend(&observer, &QualityHistory::load(), &today, TREND_DAYS, TREND_DAYS));
+    });
+    let mut receiver = Some(receiver);
+    let mut window_chart = window.clone();
+    every_frame(&window, move || {
+        let Some(Ok(nights)) = receiver.as_ref().map(|r| r.try_recv()) else {
+            return;
+        };
+        receiver = None;
+        computing.hide();
+
+        // One bar per night
+        window_chart.begin();
+        for (i, night) in nights.iter().enumerate() {
+            let height = (night.score / 100.0 * CHART_HEIGHT as f64).round().max(1.0) as i32;
+            let mut bar = Frame::new(CHART_LEFT + i as i32 * BAR_WIDTH, CHART_TOP + CHART_HEIGHT - height,
+                                     BAR_WIDTH - 1, height, "");
+            bar.set_frame(FrameType::FlatBox);
+            bar.set_color(night_color(score_color(night)));
+            bar.set_tooltip(&night.description());
+        }
+
+        // Today
+        let mut today_marker = Frame::new(CHART_LEFT + TREND_DAYS as i32 * BAR_WIDTH + BAR_WIDTH / 2,
+                                          CHART_TOP - 4, 1, CHART_HEIGHT + 8, "");
+        today_marker.set_frame(FrameType::FlatBox);
+        today_marker.set_color(Color::Red);
+        window_chart.end();
+
+        let dates = [nights.first(), nights.last()].map(|night| night.map(|n| n.date.clone()).unwrap_or_default());
+        first_date.set_label(&dates[0]);
+        last_date.set_label(&dates[1]);
+
+        let ahead = &nights[(TREND_DAYS + 1).min(nights.len())..];
+        if let Some((start, mean)) = best_stretch(ahead, STRETCH_NIGHTS) {
+            best.set_label(&format!(
+                "Best {} nights ahead: {} to {}, mean score {:.0}",
+                STRETCH_NIGHTS,
+                ahead[start].date,
+                ahead[start + STRETCH_NIGHTS - 1].date,
+                mean
+            ));
+        }
+        let forecasts = nights.iter().filter(|night| night.cloud_cover.is_some()).count();
+        recorded.set_label(&format!(
+            "{} nights with a recorded forecast, nights are recorded from the Darkness window",
+            forecasts
+        ));
+        window_chart.redraw();
+    });
+
+    Some(window)
+}
@@ -0,0 +1,193 @@
+// src/menu/functions/site_comparison.rs
+
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+use fltk::browser::CheckBrowser;
+use fltk::dialog::{alert_default, FileDialog, FileDialogType};
+use fltk::enums::Shortcut;
+use fltk::prelude::{ButtonExt, GroupExt, WidgetBase, WidgetExt};
+use fltk::{button, enums};
+use fltk::window::Window;
+use fltk_evented::Listener;
+use crate::application::application::{read_config, Application};
+use crate::application::observatory_library::ObservatoryLibrary;
+use crate::application::observer::Observer;
+use crate::application::reports::{export_night, site_comparison_report, SITE_COMPARISON_REPORT_FILE};
+use crate::menu::file::config::CONFIG_FILTER;
+use crate::menu::functions::export::{confirm_export, open_export};
+use crate::menu::window_manager::new_tool_window;
+use crate::utils::i18n::tr;
+use crate::widgets::keyboard::close_on_escape;
+use crate::widgets::layout::{label, scaled, spacer, FormLayout, BUTTON_HEIGHT, BUTTON_WIDTH, LABEL_HEIGHT};
+
+// Site of the list, a saved observatory of the library or a configuration file added with Add
+enum ComparedSite {
+    Library(Observer),
+    Config(String),
+}
+
+// Column title of a site, the observatory name or the configuration file name
+fn site_name(observer: &Observer, file_path: &str) -> String {
+    observer.name.clone().unwrap_or_else(|| {
+        Path::new(file_path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| file_path.to_string())
+    })
+}
+
+pub fn handle_site_comparison(application: &mut Rc<RefCell<Application>>) -> Option<Window> {
+    let Some(mut window) = new_tool_window(tr("Compare sites"), scaled(420), 300) else {
+        return None;
+    };
+    let mut layout = FormLayout::new(&window);
+
+    // Night and observatory of the current configuration, always the first column
+    let row = layout.row(LABEL_HEIGHT);
+    {
+        let app = application.borrow();
+        let current = app.observer.name.clone().unwrap_or_else(|| tr("Current observatory").to_string());
+        label(&format!("{} {}, {}", tr("Night of"), export_night(&app.time), current));
+    }
+    row.end();
+
+    // Saved observatories, the checked ones are compared with the current observatory
+    layout.labels([tr("Compare with")]);
+    let mut configurations = CheckBrowser::default();
+    configurations.set_tooltip(tr("Observatories saved in the library, add configuration files with Add"));
+    layout.stretch(150);
+    let mut compared: Vec<ComparedSite> = Vec::new();
+    for site in ObservatoryLibrary::load().sites {
+        configurations.add(site.name.as_deref().unwrap_or_default(), false);
+        compared.push(ComparedSite::Library(site));
+    }
+    let compared = Rc::new(RefCell::new(compared));
+
+    let mut row = layout.row(BUTTON_HEIGHT);
+
+    // Add button
+    let mut btn_add: Listener<_> = button::Button::default().with_label(tr("Add...")).into();
+    btn_add.clear_visible_focus();
+    btn_add.set_tooltip(tr("Add a configuration file to the list"));
+    row.fixed(&*btn_add, scaled(BUTTON_WIDTH + 20));
+    spacer();
+
+    // Export button
+    let mut btn_export: Listener<_> = button::Button::default().with_label(tr("Export")).into();
+    btn_export.clear_visible_focus();
+    btn_export.set_tooltip(tr("Export the darkness of the night at every site to skycalc_sites.txt"));
+    row.fixed(&*btn_export, scaled(BUTTON_WIDTH));
+    spacer();
+
+    // Close button
+    let mut btn_close: Listener<_> = button::Button::default().with_label(tr("&Close")).into();
+    btn_close.clear_visible_focus();
+    btn_close.set_shortcut(Shortcut::Alt | 'c');
+    row.fixed(&*btn_close, scaled(BUTTON_WIDTH));
+    row.end();
+
+    layout.end(&mut window);
+    window.show();
+    window.end();
+
+    let mut window_clone = window.clone();
+
+    // Close with the window close button or Esc
+    close_on_escape(&mut window);
+
+    // Handlers for Close button
+    // preserve button's original color
+    let btn_close_color = btn_close.color();
+    // close window when clicked or with Alt+C
+    btn_close.set_callback(move |_| {
+        window_clone.hide();
+    });
+
+    // change color on hover
+    btn_close.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_close.on_leave(move |b| {
+        b.set_color(btn_close_color);
+    });
+
+    // Handlers for Add button
+    // preserve button's original color
+    let btn_add_color = btn_add.color();
+    // pick a configuration file, it is added checked
+    let mut configurations_add = configurations.clone();
+    let compared_add = Rc::clone(&compared);
+    btn_add.set_callback(move |_| {
+        let mut dialog = FileDialog::new(FileDialogType::BrowseFile);
+        dialog.set_filter(CONFIG_FILTER);
+        dialog.show();
+        if let Some(filename) = dialog.filename().to_str() {
+            if !filename.is_empty() {
+                configurations_add.add(filename, true);
+                compared_add.borrow_mut().push(ComparedSite::Config(filename.to_string()));
+            }
+        }
+    });
+
+    // change color on hover
+    btn_add.on_hover(|b| {
+        b.set_color(enums::Color::Blue.lighter());
+    });
+
+    // reset color on leave
+    btn_add.on_leave(move |b| {
+        b.set_color(btn_add_color);
+    });
+
+    // Handlers for Export button
+    // preserve button's original color
+    let btn_export_color = btn_export.color();
+    // Export the comparison when clicked, configurations that can not be read are left out
+    let app_clone = Rc::clone(application);
+    btn_export.set_callback(move |_| {
+        let app = app_clone.borrow();
+        let current = app.observer.name.clone().unwrap_or_else(|| tr("Current observatory").to_string());
+        let mut sites: Vec<(String, Observer)> = vec![(current, app.observer.clone())];
+        let mut errors: Vec<String> = Vec::new();
+        for (i, site) in compared.borrow().iter().enumerate() {
+            if !configurations.checked(i as i32 + 1) {
+                continue;
+            }
+            match site {
+                ComparedSite::Library(observer) => {
+                    sites.push((observer.name.clone().unwrap_or_default(), observer.clone()))
+                }
+                ComparedSite::Config(file_path) => match read_config(file_path) {
+                    Ok(config) => sites.push((site_name(&config.observer, file_path), config.observer)),
+                    Err(e) => errors.push(format!("{}: {}", file_path, e)),
+                },
+            }
+        }
+        if !errors.is_empty() {
+            alert_default(&format!("{}\n{}", tr("These configurations could not be read:"), errors.join("\n")));
+        }
+        if sites.len() < 2 {
+            alert_default(tr("Check at least one configuration to compare with"));
+            return;
+        }
+        if confirm_export(&app, SITE_COMPARISON_REPORT_FILE, &export_night(&app.time)) {
+            site_comparison_report(&sites, &app.time, &app.environment, &app.preferences);
+            open_export(SITE_COMPARISON_REPORT_FILE);
+        }
+    });
+
+    // change color on hover
+    btn_export.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_export.on_leave(move |b| {
+        b.set_color(btn_export_color);
+    });
+
+    Some(window)
+}
@@ -0,0 +1,85 @@
+// src/menu/functions/sky_chart.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use fltk::enums::{FrameType, Shortcut};
+use fltk::frame::Frame;
+use fltk::prelude::{GroupExt, WidgetBase, WidgetExt};
+use fltk::{button, enums};
+use fltk_evented::Listener;
+use crate::application::application::Application;
+use crate::application::avoidance::{avoidance_zones, AvoidanceChart};
+use crate::application::reports::export_night;
+use crate::menu::window_manager::new_tool_window;
+use crate::utils::i18n::tr;
+use crate::widgets::keyboard::close_on_escape;
+use crate::widgets::layout::{scaled, spacer, FormLayout, BUTTON_HEIGHT, BUTTON_WIDTH};
+use crate::widgets::night_vision::night_color;
+use crate::widgets::sky_chart::draw_sky_chart;
+
+/// Sky chart of the target paths and the Moon avoidance zones during the observation window
+/// (JD UTC) of the Targets window
+pub fn handle_sky_chart(application: &Rc<RefCell<Application>>, window_utc: (f64, f64)) -> bool {
+    let Some(mut window) = new_tool_window(tr("Sky chart"), scaled(460), 520) else {
+        return false;
+    };
+    let (chart, title) = {
+        let app = application.borrow();
+        let zones = avoidance_zones(app.preferences.moon_avoidance);
+        let chart = AvoidanceChart::new(&app.observer, &app.targets, &zones, window_utc.0, window_utc.1);
+        let title = format!("{} {}° {}", tr("Moon avoidance"), app.preferences.moon_avoidance, export_night(&app.time));
+        (chart, title)
+    };
+    let mut layout = FormLayout::new(&window);
+
+    // Chart of the observation window
+    let mut preview = Frame::default();
+    preview.set_frame(FrameType::FlatBox);
+    preview.set_color(enums::Color::Black);
+    preview.set_tooltip(tr("Paths of the targets during the observation window, the ones entering the Moon \
+avoidance zone in red"));
+    layout.stretch(440);
+
+    let mut row = layout.row(BUTTON_HEIGHT);
+    spacer();
+
+    // Close button
+    let mut btn_close: Listener<_> = button::Button::default().with_label(tr("&Close")).into();
+    btn_close.clear_visible_focus();
+    btn_close.set_shortcut(Shortcut::Alt | 'c');
+    row.fixed(&*btn_close, scaled(BUTTON_WIDTH));
+    row.end();
+
+    layout.end(&mut window);
+    window.show();
+    window.end();
+
+    let mut window_clone = window.clone();
+
+    // Close with the window close button or Esc
+    close_on_escape(&mut window);
+
+    preview.draw(move |f| {
+        draw_sky_chart(f.x(), f.y(), f.w(), f.h(), &chart, &title, night_color);
+    });
+
+    // Handlers for Close button
+    // preserve button's original color
+    let btn_close_color = btn_close.color();
+    // close window when clicked or with Alt+C
+    btn_close.set_callback(move |_| {
+        window_clone.hide();
+    });
+
+    // change color on hover
+    btn_close.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_close.on_leave(move |b| {
+        b.set_color(btn_close_color);
+    });
+
+    true
+}
@@ -0,0 +1,161 @@
+// src/menu/functions/sky_status.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use fltk::prelude::{GroupExt, WidgetBase, WidgetExt};
+use fltk::{button, enums};
+use fltk::enums::{Align, FrameType};
+use fltk::frame::Frame;
+use fltk::window::Window;
+use fltk_evented::Listener;
+use crate::application::application::Application;
+use crate::application::moon::moon_topocentric_position;
+use crate::application::formatting::format_equation_of_time;
+use crate::application::sun::{apparent_solar_time, equation_of_time, sun_alt_az_from_jd, sun_position_from_jd};
+use crate::application::time::Time;
+use crate::application::transformations::equatorial_to_altaz_time;
+use crate::utils::angle::format_hms;
+use crate::utils::i18n::tr;
+use crate::widgets::label::Label;
+use crate::widgets::radec::{Coordinate, RaDecInput};
+use crate::widgets::validated::ValidatedInput;
+use crate::menu::window_manager::{every_frame, new_tool_window};
+
+// Sun and Moon (alt, az) in degrees for a given time
+fn calculate_sun_moon(application: &Application, time: &Time) -> ((f64, f64), (f64, f64)) {
+    let latitude = application.observer.latitude;
+    let longitude = application.observer.longitude;
+    let jd = time.to_jd();
+
+    let (sun_ra, sun_dec) = sun_position_from_jd(jd);
+    let sun = sun_alt_az_from_jd(latitude, longitude, sun_ra, sun_dec, jd);
+
+    let elevation = application.observer.elevation as f64;
+    let (moon_ra, moon_dec, _) = moon_topocentric_position(latitude, longitude, elevation, jd);
+    let moon = equatorial_to_altaz_time(latitude, longitude, moon_ra, moon_dec, time);
+
+    (sun, moon)
+}
+
+pub fn handle_sky_status(application: &mut Rc<RefCell<Application>>) -> Option<Window> {
+    let Some(mut window) = new_tool_window(tr("Sky status"), 290, 290) else {
+        return None;
+    };
+
+    // Observatory
+    Label::new(10, 10, 80, 20, tr("Observatory:"), Align::Left | Align::Inside);
+    let mut observatory = Label::new(100, 10, 180, 20, "", Align::Left | Align::Inside);
+    if let Some(name_str) = &application.borrow().observer.name {
+        observatory.set_label(name_str.as_str());
+    }
+
+    // UTC and local sidereal time
+    Label::new(10, 35, 80, 20, "UTC", Align::Left | Align::Inside);
+    let mut utc_label = Label::new(100, 35, 180, 20, "", Align::Left | Align::Inside);
+    Label::new(10, 55, 80, 20, tr("LST"), Align::Left | Align::Inside);
+    let mut lst_label = Label::new(100, 55, 180, 20, "", Align::Left | Align::Inside);
+
+    // Divider
+    Frame::new(10, 85, 270, 1, "").set_frame(FrameType::BorderBox);
+
+    // Sun and Moon
+    Label::new(10, 95, 80, 20, tr("Sun alt/az"), Align::Left | Align::Inside);
+    let mut sun_label = Label::new(100, 95, 180, 20, "", Align::Left | Align::Inside);
+    Label::new(10, 115, 80, 20, tr("Moon alt/az"), Align::Left | Align::Inside);
+    let mut moon_label = Label::new(100, 115, 180, 20, "", Align::Left | Align::Inside);
+
+    // Equation of time and sundial time, a sundial reads the clock plus the longitude and
+    // equation of time corrections
+    let mut eot_caption = Label::new(10, 135, 80, 20, tr("Eq. of time"), Align::Left | Align::Inside);
+    eot_caption.set_tooltip(tr("Apparent minus mean solar time, add it to mean solar time to read a sundial"));
+    let mut eot_label = Label::new(100, 135, 180, 20, "", Align::Left | Align::Inside);
+    let mut sundial_caption = Label::new(10, 155, 80, 20, tr("Sundial"), Align::Left | Align::Inside);
+    sundial_caption.set_tooltip(tr("Local apparent solar time, in parentheses the sundial minus clock difference"));
+    let mut sundial_label = Label::new(100, 155, 180, 20, "", Align::Left | Align::Inside);
+
+    // Divider
+    Frame::new(10, 185, 270, 1, "").set_frame(FrameType::BorderBox);
+
+    // Hour angle for a given right ascension
+    Label::new(10, 195, 80, 20, tr("RA"), Align::Left | Align::Inside);
+    let mut ra = RaDecInput::new(100, 195, 130, 20, "", Coordinate::RightAscension);
+    Label::new(10, 215, 80, 20, tr("Hour angle"), Align::Left | Align::Inside);
+    let mut hour_angle_label = Label::new(100, 215, 180, 20, "", Align::Left | Align::Inside);
+
+    // Close button
+    let mut btn_close: Listener<_> = button::Button::new(220, 250, 50, 30, tr("Close")).into();
+    btn_close.clear_visible_focus();
+
+    window.show();
+    window.end();
+
+    let mut window_clone = window.clone();
+
+    // Window call back to avoid program termination when ESC is pressed
+    // from FLTK Book - FAQ
+    window.set_callback(|w| {
+        if fltk::app::event() == fltk::enums::Event::Close {
+            w.hide();
+        }
+    });
+
+    // Flag an invalid right ascension when leaving the field
+    ra.check_on_leave();
+
+    // Handlers for Close button
+    // preserve button's original color
+    let btn_close_color = btn_close.color();
+    // close window when clicked
+    btn_close.on_click(move |_| {
+        window_clone.hide();
+    });
+
+    // change color on hover
+    btn_close.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_close.on_leave(move |b| {
+        b.set_color(btn_close_color);
+    });
+
+    let app_clone = Rc::clone(&application);
+    let mut window_frame = window.clone();
+    every_frame(&window, move || {
+        // Update calculations for current time
+        let now = Time::now();
+        let lst = app_clone.borrow().observer.local_sidereal_time(&now);
+        let (sun, moon) = calculate_sun_moon(&app_clone.borrow(), &now);
+
+        // Sundial minus clock, in minutes: longitude from the zone meridian plus equation of time
+        let (longitude, timezone) = (app_clone.borrow().observer.longitude, app_clone.borrow().observer.timezone);
+        let equation = equation_of_time(now.to_jd());
+        let sundial_correction = equation + longitude * 4.0 - timezone * 60.0;
+
+        // Hour angle in hours, negative east of the meridian, none while the right ascension is invalid
+        let hour_angle = ra.get_degrees().map(|ra| {
+            let mut hour_angle = (lst - ra) / 15.0;
+            if hour_angle >= 12.0 {
+                hour_angle -= 24.0;
+            } else if hour_angle < -12.0 {
+                hour_angle += 24.0;
+            }
+            hour_angle
+        });
+
+        utc_label.set_label(&now.to_string(Some("utc")));
+        lst_label.set_label(&format_hms(lst / 15.0));
+        sun_label.set_label(&format!("{:.2}° / {:.2}°", sun.0, sun.1));
+        moon_label.set_label(&format!("{:.2}° / {:.2}°", moon.0, moon.1));
+        eot_label.set_label(&format_equation_of_time(equation));
+        sundial_label.set_label(&format!("{} ({})", format_hms(apparent_solar_time(longitude, now.to_jd())),
+                                         format_equation_of_time(sundial_correction)));
+        hour_angle_label.set_label(&hour_angle.map(format_hms).unwrap_or_else(|_| "-".to_string()));
+
+        //Redraw window to update labels
+        window_frame.redraw();
+    });
+
+    Some(window)
+}
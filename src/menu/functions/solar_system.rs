@@ -0,0 +1,217 @@
+// src/menu/functions/solar_system.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use fltk::browser::HoldBrowser;
+use fltk::menu::Choice;
+use fltk::prelude::{BrowserExt, GroupExt, MenuExt, WidgetBase, WidgetExt};
+use fltk::{button, enums};
+use fltk::enums::Align;
+use fltk::window::Window;
+use fltk_evented::Listener;
+use crate::application::application::Application;
+use crate::application::formatting::{format_diameter, format_elongation, format_equation_of_time, format_local};
+use crate::application::reports::{export_night, planet_events_report, solar_system_csv_report, solar_system_events,
+                                  solar_system_report, PLANET_EVENTS_REPORT_FILE, SOLAR_SYSTEM_CSV_FILE,
+                                  SOLAR_SYSTEM_REPORT_FILE};
+use crate::application::solar_system::{body_position, Body, BODIES, EPHEMERIS_INTERVALS, EVENT_RANGES};
+use crate::application::sun::equation_of_time;
+use crate::menu::functions::export::{confirm_export, open_export};
+use crate::utils::angle::{format_dec_dms, format_ra_hms};
+use crate::utils::i18n::tr;
+use crate::widgets::label::Label;
+use crate::menu::window_manager::new_tool_window;
+
+// Sampling interval of the exports, None for the selected time only
+fn selected_interval(choice: &Choice) -> Option<u32> {
+    match choice.value() {
+        index if index > 0 => EPHEMERIS_INTERVALS.get(index as usize - 1).copied(),
+        _ => None,
+    }
+}
+
+pub fn handle_solar_system(application: &mut Rc<RefCell<Application>>) -> Option<Window> {
+    let Some(mut window) = new_tool_window(tr("Solar system"), 960, 330) else {
+        return None;
+    };
+
+    // Selected time
+    let mut time_label = Label::new(10, 10, 940, 20, "", Align::Left | Align::Inside);
+
+    // Ephemeris table at the selected time, rise, transit and set during the night
+    let mut table = HoldBrowser::new(10, 40, 940, 200, "");
+    table.set_column_widths(&[75, 95, 100, 55, 55, 100, 100, 60, 100, 55, 60, 70]);
+    table.set_column_char('\t');
+    table.set_tooltip(tr("Times in local time, rise, transit and set from local noon to the next local noon, max alt \
+at the transit, elongation east or west of the Sun"));
+    let titles: Vec<String> = ["Body", "RA", "Dec", "Alt", "Az", "Rise", "Transit", "Max alt", "Set", "Mag", "Size",
+                               "Elong"]
+        .into_iter()
+        .map(|title| format!("@b{}", tr(title)))
+        .collect();
+    table.add(&titles.join("\t"));
+    {
+        let app = application.borrow();
+        let jd = app.time.to_jd();
+        let local = |jd: Option<f64>| {
+            jd.map_or("-".to_string(), |jd| format_local(app.observer.local_time(jd), &app.preferences))
+        };
+        time_label.set_label(&format!("{} {} ({}), {} {}", tr("Positions at"), local(Some(jd)), tr("local"),
+                                      tr("equation of time"), format_equation_of_time(equation_of_time(jd))));
+        let events = solar_system_events(&app.observer, &app.time);
+        for (body, event) in BODIES.iter().zip(&events) {
+            let p = body_position(*body, &app.observer, jd);
+            let max_altitude = event.transit.map_or("-".to_string(), |transit| {
+                format!("{:.1}°", body_position(*body, &app.observer, transit).altitude)
+            });
+            let elongation = if *body == Body::Sun { "-".to_string() } else { format_elongation(p.elongation) };
+            table.add(&format!("{}\t{}\t{}\t{:.1}°\t{:.1}°\t{}\t{}\t{}\t{}\t{:.1}\t{}\t{}", tr(body.name()),
+                               format_ra_hms(p.ra), format_dec_dms(p.dec), p.altitude, p.azimuth,
+                               local(event.rise), local(event.transit), max_altitude, local(event.set),
+                               p.magnitude, format_diameter(p.diameter), elongation));
+        }
+    }
+
+    // Export interval
+    Label::new(10, 250, 100, 25, tr("Export"), Align::Left | Align::Inside);
+    let mut interval = Choice::new(110, 250, 170, 25, "");
+    interval.add_choice(tr("Selected time only"));
+    for minutes in EPHEMERIS_INTERVALS {
+        if minutes < 60 {
+            interval.add_choice(&format!("{} {} min", tr("Every"), minutes));
+        } else {
+            interval.add_choice(&format!("{} {} h", tr("Every"), minutes / 60));
+        }
+    }
+    interval.set_value(0);
+    interval.set_tooltip(tr("Positions at the selected time or from sunset to sunrise at an interval"));
+
+    // Range of the planet events
+    Label::new(320, 250, 170, 25, tr("Oppositions and elongations"), Align::Left | Align::Inside);
+    let mut range = Choice::new(500, 250, 130, 25, "");
+    for months in EVENT_RANGES {
+        range.add_choice(&format!("{} {} {}", tr("Next"), months, tr("months")));
+    }
+    range.set_value(2);
+    range.set_tooltip(tr("Search oppositions of Mars, Jupiter and Saturn and greatest elongations of Mercury and \
+Venus from the selected time"));
+
+    // Export buttons
+    let mut btn_export_text: Listener<_> = button::Button::new(20, 285, 50, 30, tr("Text")).into();
+    btn_export_text.clear_visible_focus();
+    btn_export_text.set_tooltip(tr("Export the ephemeris to skycalc_solar_system.txt"));
+    let mut btn_export_csv: Listener<_> = button::Button::new(80, 285, 50, 30, "CSV").into();
+    btn_export_csv.clear_visible_focus();
+    btn_export_csv.set_tooltip(tr("Export the ephemeris to skycalc_solar_system.csv"));
+    let mut btn_events: Listener<_> = button::Button::new(320, 285, 60, 30, tr("Events")).into();
+    btn_events.clear_visible_focus();
+    btn_events.set_tooltip(tr("Export the planet events of the range to skycalc_planet_events.txt"));
+
+    // Close button
+    let mut btn_close: Listener<_> = button::Button::new(890, 285, 50, 30, tr("Close")).into();
+    btn_close.clear_visible_focus();
+
+    window.end();
+    window.show();
+
+    let mut window_clone = window.clone();
+
+    // Window call back to avoid program termination when ESC is pressed
+    // from FLTK Book - FAQ
+    window.set_callback(|w| {
+        if fltk::app::event() == fltk::enums::Event::Close {
+            w.hide();
+        }
+    });
+
+    // Handlers for Close button
+    // preserve button's original color
+    let btn_close_color = btn_close.color();
+    // close window when clicked
+    btn_close.on_click(move |_| {
+        window_clone.hide();
+    });
+
+    // change color on hover
+    btn_close.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_close.on_leave(move |b| {
+        b.set_color(btn_close_color);
+    });
+
+    // Handlers for Text export button
+    // preserve button's original color
+    let btn_export_text_color = btn_export_text.color();
+    // Export to file when clicked
+    let app_clone = Rc::clone(&application);
+    let interval_clone = interval.clone();
+    btn_export_text.on_click(move |_| {
+        let app = app_clone.borrow();
+        if confirm_export(&app, SOLAR_SYSTEM_REPORT_FILE, &export_night(&app.time)) {
+            solar_system_report(&app.observer, &app.time, &app.environment, selected_interval(&interval_clone),
+                                &app.preferences);
+        }
+    });
+
+    // change color on hover
+    btn_export_text.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_export_text.on_leave(move |b| {
+        b.set_color(btn_export_text_color);
+    });
+
+    // Handlers for CSV export button
+    // preserve button's original color
+    let btn_export_csv_color = btn_export_csv.color();
+    // Export to file when clicked
+    let app_clone = Rc::clone(&application);
+    btn_export_csv.on_click(move |_| {
+        let app = app_clone.borrow();
+        if confirm_export(&app, SOLAR_SYSTEM_CSV_FILE, &export_night(&app.time)) {
+            solar_system_csv_report(&app.observer, &app.time, &app.environment, selected_interval(&interval),
+                                    &app.preferences);
+        }
+    });
+
+    // change color on hover
+    btn_export_csv.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_export_csv.on_leave(move |b| {
+        b.set_color(btn_export_csv_color);
+    });
+
+    // Handlers for Events button
+    // preserve button's original color
+    let btn_events_color = btn_events.color();
+    // Export the events of the selected range and open the file when clicked
+    let app_clone = Rc::clone(&application);
+    btn_events.on_click(move |_| {
+        let app = app_clone.borrow();
+        let months = EVENT_RANGES.get(range.value().max(0) as usize).copied().unwrap_or(EVENT_RANGES[2]);
+        if confirm_export(&app, PLANET_EVENTS_REPORT_FILE, &export_night(&app.time)) {
+            planet_events_report(&app.observer, &app.time, months);
+            open_export(PLANET_EVENTS_REPORT_FILE);
+        }
+    });
+
+    // change color on hover
+    btn_events.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_events.on_leave(move |b| {
+        b.set_color(btn_events_color);
+    });
+
+    Some(window)
+}
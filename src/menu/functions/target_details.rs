@@ -0,0 +1,198 @@
+// src/menu/functions/target_details.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use fltk::prelude::{GroupExt, WidgetBase, WidgetExt};
+use fltk::{button, enums};
+use fltk::enums::{Align, Color, FrameType};
+use fltk::frame::Frame;
+use fltk_evented::Listener;
+use crate::application::application::Application;
+use crate::application::formatting::format_local;
+use crate::application::observability::{best_months, filter_hours, night_field_rotation, night_filter_plan,
+                                        yearly_observability, FilterClass, MonthObservability, MONTH_NAMES};
+use crate::application::reports::filter_segments_text;
+use crate::application::target::Target;
+use crate::utils::angle::{format_dec_dms, format_ra_hms};
+use crate::utils::i18n::tr;
+use crate::widgets::label::Label;
+use crate::widgets::night_vision::night_color;
+use crate::menu::window_manager::new_tool_window;
+
+// Chart geometry, one bar per month, the bar height is the observable fraction of the darkness
+const BAR_WIDTH: i32 = 28;
+const BAR_STEP: i32 = 36;
+const CHART_LEFT: i32 = 44;
+const CHART_TOP: i32 = 70;
+const CHART_HEIGHT: i32 = 150;
+// Months named as the best ones
+const BEST_MONTHS: usize = 3;
+// Field rotation allowed during an exposure, in degrees
+const ROTATION_TOLERANCE: f64 = 0.1;
+
+// Bar color, brighter for the best months
+fn month_color(month: &MonthObservability, best: &[MonthObservability]) -> Color {
+    if best.iter().any(|m| m.month == month.month) {
+        Color::from_rgb(80, 200, 80)
+    } else {
+        Color::from_rgb(40, 110, 160)
+    }
+}
+
+pub fn handle_target_details(application: &Rc<RefCell<Application>>, target: &Target) -> bool {
+    let (observer, constraints, time, environment, preferences) = {
+        let app = application.borrow();
+        (app.observer.clone(), app.constraints.clone(), app.time.clone(), app.environment.clone(),
+         app.preferences.clone())
+    };
+    let year = time.year;
+    let months = yearly_observability(target, &observer, year, &constraints);
+    let filters = night_filter_plan(&observer, &time, &environment, &constraints, std::slice::from_ref(target))
+        .and_then(|mut plan| plan.pop());
+    let best = best_months(&months, BEST_MONTHS);
+    let rotation_row = if preferences.field_rotation { 25 } else { 0 };
+    let chart_width = BAR_STEP * 12;
+
+    let Some(mut window) = new_tool_window("Target details", CHART_LEFT + chart_width + 20, 355 + rotation_row)
+    else {
+        return false;
+    };
+
+    // Target
+    let mut title = Label::new(10, 10, chart_width, 20, "", Align::Left | Align::Inside);
+    title.set_label(&format!(
+        "{} - RA {} Dec {}{}",
+        target.name,
+        format_ra_hms(target.ra),
+        format_dec_dms(target.dec),
+        if target.size > 0.0 { format!(" - {}'", target.size) } else { String::new() }
+    ));
+    let mut subtitle = Label::new(10, 30, chart_width, 20, "", Align::Left | Align::Inside);
+    subtitle.set_label(&format!(
+        "Darkness with the target between {}° and {}° in {}",
+        constraints.min_altitude, constraints.max_altitude, year
+    ));
+
+    // Fraction axis
+    for (percent, y) in [(100, CHART_TOP), (50, CHART_TOP + CHART_HEIGHT / 2), (0, CHART_TOP + CHART_HEIGHT)] {
+        Label::new(4, y - 10, 36, 20, &format!("{}%", percent), Align::Right | Align::Inside);
+    }
+    let mut baseline = Frame::new(CHART_LEFT, CHART_TOP + CHART_HEIGHT, chart_width, 1, "");
+    baseline.set_frame(FrameType::FlatBox);
+    baseline.set_color(Color::Dark2);
+
+    // One bar per month
+    for (i, month) in months.iter().enumerate() {
+        let x = CHART_LEFT + i as i32 * BAR_STEP;
+        let height = (month.fraction() * CHART_HEIGHT as f64).round().max(1.0) as i32;
+        let mut bar = Frame::new(x, CHART_TOP + CHART_HEIGHT - height, BAR_WIDTH, height, "");
+        bar.set_frame(FrameType::FlatBox);
+        bar.set_color(night_color(month_color(month, &best)));
+        bar.set_tooltip(&format!(
+            "{}: {:.0}% of {:.0} h of darkness, {:.1} h",
+            MONTH_NAMES[i],
+            month.fraction() * 100.0,
+            month.dark_hours,
+            month.observable_hours
+        ));
+        Label::new(x - 4, CHART_TOP + CHART_HEIGHT + 5, BAR_WIDTH + 8, 20, MONTH_NAMES[i],
+                   Align::Center | Align::Inside);
+    }
+
+    // Best months
+    let mut best_label = Label::new(10, CHART_TOP + CHART_HEIGHT + 35, chart_width, 20, "", Align::Left | Align::Inside);
+    if best.is_empty() {
+        best_label.set_label("Never within the altitude constraints in darkness");
+    } else {
+        let names: Vec<String> = best
+            .iter()
+            .map(|m| format!("{} ({:.0} h)", MONTH_NAMES[m.month as usize - 1], m.observable_hours))
+            .collect();
+        best_label.set_label(&format!("Best months: {}", names.join(", ")));
+    }
+
+    // Filters tonight, broadband or narrowband only with the Moon up
+    let mut filters_label = Label::new(10, CHART_TOP + CHART_HEIGHT + 60, chart_width, 20, "",
+                                       Align::Left | Align::Inside);
+    match filters {
+        None => filters_label.set_label("Tonight: no darkness"),
+        Some(segments) if segments.is_empty() => {
+            filters_label.set_label("Tonight: never within the altitude constraints")
+        }
+        Some(segments) => {
+            filters_label.set_label(&format!(
+                "Tonight: {:.1} h broadband, {:.1} h narrowband only",
+                filter_hours(&segments, FilterClass::Broadband),
+                filter_hours(&segments, FilterClass::Narrowband)
+            ));
+            filters_label.set_tooltip(&filter_segments_text(&segments, &observer, &preferences)
+                .replace("; ", "\n"));
+        }
+    }
+
+    // Field rotation tonight for alt-az mounts
+    if preferences.field_rotation {
+        let mut rotation_label = Label::new(10, CHART_TOP + CHART_HEIGHT + 85, chart_width, 20, "",
+                                            Align::Left | Align::Inside);
+        match night_field_rotation(&observer, &time, &environment, &constraints, target) {
+            None => rotation_label.set_label(&format!("{}: {}", tr("Field rotation"),
+                                                      tr("not within the altitude constraints tonight"))),
+            Some(rotation) => {
+                rotation_label.set_label(&format!(
+                    "{} {:.2}°/min {} {}, {:.0}° {}",
+                    tr("Field rotation: up to"),
+                    rotation.max_rate,
+                    tr("at"),
+                    format_local(observer.local_time(rotation.max_rate_jd), &preferences),
+                    rotation.total,
+                    tr("in total")
+                ));
+                rotation_label.set_tooltip(&format!(
+                    "{} {}° {}: {:.0} s",
+                    tr("Longest exposure for"),
+                    ROTATION_TOLERANCE,
+                    tr("of rotation at the highest rate"),
+                    rotation.max_exposure(ROTATION_TOLERANCE)
+                ));
+            }
+        }
+    }
+
+    // Close button
+    let mut btn_close: Listener<_> =
+        button::Button::new(CHART_LEFT + chart_width - 40, 310 + rotation_row, 50, 30, "Close").into();
+    btn_close.clear_visible_focus();
+
+    window.end();
+    window.show();
+
+    let mut window_clone = window.clone();
+
+    // Window call back to avoid program termination when ESC is pressed
+    // from FLTK Book - FAQ
+    window.set_callback(|w| {
+        if fltk::app::event() == fltk::enums::Event::Close {
+            w.hide();
+        }
+    });
+
+    // Handlers for Close button
+    // preserve button's original color
+    let btn_close_color = btn_close.color();
+    // close window when clicked
+    btn_close.on_click(move |_| {
+        window_clone.hide();
+    });
+
+    // change color on hover
+    btn_close.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_close.on_leave(move |b| {
+        b.set_color(btn_close_color);
+    });
+
+    true
+}
@@ -0,0 +1,134 @@
+// src/menu/functions/target_editor.rs
+
+use fltk::prelude::{GroupExt, InputExt, WidgetBase, WidgetExt};
+use fltk::{button, enums};
+use fltk::enums::Align;
+use fltk::input::Input;
+use fltk_evented::Listener;
+use crate::application::target::{try_size_from_str, Target};
+use crate::widgets::label::Label;
+use crate::widgets::radec::{Coordinate, RaDecInput};
+use crate::widgets::validated::{mark_input, ValidatedInput};
+use crate::menu::window_manager::new_tool_window;
+
+const TOOLTIP_NAME: &str = "Name shown in the target list and the exports, e.g. NGC 253";
+const TOOLTIP_SIZE: &str = "Apparent size in arc minutes, empty when unknown";
+
+// Checks the name and the size, marking the inputs with what is wrong
+fn check_name(name: &mut Input) -> Option<String> {
+    let value = name.value().trim().to_string();
+    let error = value.is_empty().then_some("Enter the target name");
+    mark_input(name, error, TOOLTIP_NAME);
+    (!value.is_empty()).then_some(value)
+}
+
+fn check_size(size: &mut Input) -> Option<f64> {
+    let result = try_size_from_str(&size.value());
+    mark_input(size, result.as_ref().err().map(String::as_str), TOOLTIP_SIZE);
+    result.ok()
+}
+
+/// Dialog to enter a new target or edit `target`, `on_ok` receives the target entered,
+/// nothing happens when cancelled
+pub fn handle_target_editor<F: FnMut(Target) + 'static>(target: Option<&Target>, mut on_ok: F) -> bool {
+    let title = if target.is_some() { "Edit target" } else { "Add target" };
+    let Some(mut window) = new_tool_window(title, 290, 220) else {
+        return false;
+    };
+
+    // Name
+    Label::new(10, 10, 80, 20, "Name", Align::Left | Align::Inside);
+    let mut name = Input::new(10, 30, 270, 25, "");
+    name.set_maximum_size(35);
+    name.set_tooltip(TOOLTIP_NAME);
+
+    // Right ascension and declination
+    Label::new(10, 60, 80, 20, "RA", Align::Left | Align::Inside);
+    let mut ra = RaDecInput::new(10, 80, 130, 25, "", Coordinate::RightAscension);
+    Label::new(150, 60, 80, 20, "Dec", Align::Left | Align::Inside);
+    let mut dec = RaDecInput::new(150, 80, 130, 25, "", Coordinate::Declination);
+
+    // Apparent size
+    Label::new(10, 110, 80, 20, "Size (')", Align::Left | Align::Inside);
+    let mut size = Input::new(10, 130, 80, 25, "");
+    size.set_maximum_size(8);
+    size.set_tooltip(TOOLTIP_SIZE);
+
+    if let Some(target) = target {
+        name.set_value(&target.name);
+        ra.set_degrees(target.ra);
+        dec.set_degrees(target.dec);
+        if target.size > 0.0 {
+            size.set_value(&target.size.to_string());
+        }
+    }
+
+    // OK button
+    let mut btn_ok: Listener<_> = button::Button::new(20, 175, 50, 30, "OK").into();
+    btn_ok.clear_visible_focus();
+
+    // Cancel button
+    let mut btn_cancel: Listener<_> = button::Button::new(220, 175, 50, 30, "Cancel").into();
+    btn_cancel.clear_visible_focus();
+
+    window.show();
+    window.end();
+
+    let mut window_clone = window.clone();
+
+    // Window call back to avoid program termination when ESC is pressed
+    // from FLTK Book - FAQ
+    window.set_callback(|w| {
+        if fltk::app::event() == fltk::enums::Event::Close {
+            w.hide();
+        }
+    });
+
+    // Flag invalid values when leaving a field
+    ra.check_on_leave();
+    dec.check_on_leave();
+
+    // Handlers for Cancel button
+    // preserve button's original color
+    let btn_cancel_color = btn_cancel.color();
+    // close window when clicked
+    let mut window_cancel_clone = window.clone();
+    btn_cancel.on_click(move |_| {
+        window_cancel_clone.hide();
+    });
+
+    // change color on hover
+    btn_cancel.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_cancel.on_leave(move |b| {
+        b.set_color(btn_cancel_color);
+    });
+
+    // Handlers for OK button
+    // preserve button's original color
+    let btn_ok_color = btn_ok.color();
+    // Hand over the target and close, nothing is handed over while a value is invalid
+    btn_ok.on_click(move |_| {
+        let (Some(name_value), Some(ra_value), Some(dec_value), Some(size_value)) =
+            (check_name(&mut name), ra.check(), dec.check(), check_size(&mut size)) else {
+            return;
+        };
+        window_clone.hide();
+        on_ok(Target::new(&name_value, ra_value, dec_value, size_value));
+    });
+
+    // change color on hover
+    btn_ok.on_hover(|b| {
+        b.set_color(enums::Color::Green.lighter());
+    });
+
+    // reset color on leave
+    btn_ok.on_leave(move |b| {
+        b.set_color(btn_ok_color);
+    });
+
+    true
+}
@@ -0,0 +1,439 @@
+// src/menu/functions/targets.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use fltk::browser::MultiBrowser;
+use fltk::prelude::{BrowserExt, GroupExt, WidgetBase, WidgetExt};
+use fltk::{app, button, dialog, enums};
+use fltk::enums::{Align, Color};
+use fltk::window::Window;
+use fltk_evented::Listener;
+use crate::application::application::Application;
+use crate::application::avoidance::{avoidance_conflicts, avoidance_zones};
+use crate::application::darkness::Darkness;
+use crate::application::formatting::format_local;
+use crate::application::observability::{observability, observation_window as constraints_window, NightSamples};
+use crate::application::reports::{astroplanner_report, export_night, skysafari_report, up_tonight_report,
+                                  ASTROPLANNER_LIST_FILE, SKYSAFARI_LIST_FILE, UP_TONIGHT_REPORT_FILE};
+use crate::application::scripting::{target_variables, Scripts};
+use crate::application::target::{Target, TargetState};
+use crate::application::undo::Setting;
+use crate::menu::functions::export::confirm_export;
+use crate::menu::functions::sky_chart::handle_sky_chart;
+use crate::menu::functions::target_details::handle_target_details;
+use crate::menu::functions::target_editor::handle_target_editor;
+use crate::utils::angle::format_hms;
+use crate::utils::i18n::tr;
+use crate::widgets::label::Label;
+use crate::menu::window_manager::new_tool_window;
+
+// Script columns shown at most, each one 80 pixels wide
+const MAX_SCRIPT_COLUMNS: usize = 3;
+const SCRIPT_COLUMN_WIDTH: i32 = 80;
+
+// Badge color for each target state
+fn state_color(state: TargetState) -> Color {
+    match state {
+        TargetState::Rising => Color::DarkGreen,
+        TargetState::NearTransit => Color::Green,
+        TargetState::Setting => Color::DarkYellow,
+        TargetState::BelowHorizon => Color::Red,
+    }
+}
+
+// Planned observation window in UTC: darkness, or the selected time if there is none
+fn observation_window(application: &Application) -> (f64, f64) {
+    let darkness = Darkness::new(&application.observer, &application.time, &application.environment)
+        .with_settings(application.preferences.calculation);
+    let (_, (start, end)) = darkness.get_darkness_utc_astronomical_or_nautical();
+    match (start.jd(), end.jd()) {
+        (Some(start), Some(end)) => (start, end),
+        _ => (application.time.to_jd(), application.time.to_jd()),
+    }
+}
+
+// Avoidance zones entered during the window, e.g. "Moon 23°"
+fn avoidance_flag(application: &Application, target: &Target, window: (f64, f64)) -> String {
+    let zones = avoidance_zones(application.preferences.moon_avoidance);
+    avoidance_conflicts(target, &application.observer, &zones, window.0, window.1)
+        .iter()
+        .map(|c| format!("@C{}@.{} {:.0}°", Color::Red.bits(), tr(c.body.name()), c.separation))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// Values of the script columns, after the built in columns
+fn script_values(application: &Application, target: &Target, jd: f64, scripts: Option<&Scripts>) -> String {
+    match scripts {
+        Some(scripts) => scripts
+            .column_values(&target_variables(target, &application.observer, jd))
+            .iter()
+            .take(MAX_SCRIPT_COLUMNS)
+            .map(|value| format!("\t{}", value))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+// Percentage of the observation window each target spends within the altitude constraints,
+// "-" when there is no window that night
+fn observable_values(application: &Application) -> Vec<String> {
+    let window = constraints_window(&application.observer, &application.time, &application.environment,
+                                    &application.constraints);
+    let samples = window.map(|window| NightSamples::new(&application.observer, window));
+    application
+        .targets
+        .iter()
+        .map(|target| match &samples {
+            Some(samples) => format!("{:.0}%", observability(target, &application.observer, samples,
+                                                             &application.constraints).fraction * 100.0),
+            None => "-".to_string(),
+        })
+        .collect()
+}
+
+// One browser line per target, columns separated by tabs
+fn target_lines(application: &Application, window: (f64, f64), scripts: Option<&Scripts>) -> Vec<String> {
+    let jd = window.0;
+    application
+        .targets
+        .iter()
+        .zip(observable_values(application))
+        .map(|(target, observable)| {
+            let state = target.state(&application.observer, jd);
+            format!(
+                "@C{}@.\u{25CF} {}\t{}\t{:.2}\t{:.2}\t{}\t{}\t{}{}",
+                state_color(state).bits(),
+                tr(state.description()),
+                target.name,
+                target.ra,
+                target.dec,
+                format_hms(target.hour_angle(&application.observer, jd) / 15.0),
+                observable,
+                avoidance_flag(application, target, window),
+                script_values(application, target, jd, scripts)
+            )
+        })
+        .collect()
+}
+
+// Scripts that compiled, without the errors of the others
+fn loaded_scripts(scripts: &Option<(Scripts, Vec<String>)>) -> Option<&Scripts> {
+    scripts.as_ref().map(|(scripts, _)| scripts)
+}
+
+// Header and one line per target, the selection is kept when the line still exists
+fn fill_table(table: &mut MultiBrowser, application: &Application, script_columns: &[String],
+              scripts: Option<&Scripts>) {
+    let selected = selected_targets(table);
+    table.clear();
+    let script_titles: String = script_columns.iter().map(|c| format!("\t@b{}", c)).collect();
+    let titles: Vec<String> = ["State", "Name", "RA", "Dec", "HA", "Obs", "Avoid"]
+        .into_iter()
+        .map(|title| format!("@b{}", tr(title)))
+        .collect();
+    table.add(&format!("{}{}", titles.join("\t"), script_titles));
+    for line in target_lines(application, observation_window(application), scripts) {
+        table.add(&line);
+    }
+    for index in selected {
+        if index + 2 <= table.size() as usize {
+            table.select(index as i32 + 2);
+        }
+    }
+}
+
+// Target of the line clicked last when it is selected, the first line is the header
+fn selected_target(table: &MultiBrowser) -> Option<usize> {
+    let line = table.value();
+    (line > 1 && table.selected(line)).then(|| (line - 2) as usize)
+}
+
+// Targets of all the selected lines, in list order
+fn selected_targets(table: &MultiBrowser) -> Vec<usize> {
+    (2..=table.size()).filter(|&line| table.selected(line)).map(|line| (line - 2) as usize).collect()
+}
+
+// Selected targets for the observing list exports, none after telling the user to select them
+fn export_targets(application: &Application, table: &MultiBrowser) -> Option<Vec<Target>> {
+    let targets: Vec<Target> = selected_targets(table)
+        .into_iter()
+        .filter_map(|index| application.targets.get(index).cloned())
+        .collect();
+    if targets.is_empty() {
+        dialog::message_default(tr("Select the targets to export, Ctrl+click selects several."));
+        return None;
+    }
+    Some(targets)
+}
+
+// Replaces the target list as an edit that Edit/Undo reverts
+fn apply_targets(application: &Rc<RefCell<Application>>, targets: Vec<Target>) {
+    application.borrow_mut().apply_setting(Setting::Targets(targets));
+}
+
+// Opens the editor for the selected target, on_edited runs once the edit is applied
+fn edit_selected<F: FnMut() + 'static>(application: &Rc<RefCell<Application>>, table: &MultiBrowser,
+                                       mut on_edited: F) {
+    let Some(index) = selected_target(table) else {
+        return;
+    };
+    let Some(target) = application.borrow().targets.get(index).cloned() else {
+        return;
+    };
+    let app_clone = Rc::clone(application);
+    handle_target_editor(Some(&target), move |edited| {
+        let mut targets = app_clone.borrow().targets.clone();
+        if let Some(slot) = targets.get_mut(index) {
+            *slot = edited;
+            apply_targets(&app_clone, targets);
+            on_edited();
+        }
+    });
+}
+
+pub fn handle_targets(application: &mut Rc<RefCell<Application>>) -> Option<Window> {
+    // User script columns, when enabled in the preferences
+    let scripts = application.borrow().preferences.scripts.then(Scripts::load);
+    let script_columns: Vec<String> = match &scripts {
+        Some((scripts, _)) => scripts.columns().iter().take(MAX_SCRIPT_COLUMNS).map(|c| c.to_string()).collect(),
+        None => Vec::new(),
+    };
+    let extra_width = SCRIPT_COLUMN_WIDTH * script_columns.len() as i32;
+
+    let Some(mut window) = new_tool_window(tr("Targets"), 680 + extra_width, 355) else {
+        return None;
+    };
+
+    // Planned observation start
+    let window_utc = observation_window(&application.borrow());
+    let jd = window_utc.0;
+    Label::new(10, 10, 120, 20, tr("Observation start:"), Align::Left | Align::Inside);
+    let mut start_label = Label::new(140, 10, 200, 20, "", Align::Left | Align::Inside);
+    start_label.set_label(&format!(
+        "{} ({})",
+        format_local(application.borrow().observer.local_time(jd), &application.borrow().preferences),
+        tr("local")
+    ));
+
+    // Target table
+    let widths: &'static [i32] = &[130, 150, 70, 70, 80, 60, 100, SCRIPT_COLUMN_WIDTH,
+                                   SCRIPT_COLUMN_WIDTH, SCRIPT_COLUMN_WIDTH];
+    let mut table = MultiBrowser::new(10, 40, 660 + extra_width, 230, "");
+    table.set_column_widths(widths);
+    table.set_column_char('\t');
+    table.set_tooltip(tr("Obs is the part of the observation window within the altitude constraints,\n\
+double click a target to edit it, Ctrl+click selects several for the observing lists"));
+    let scripts = Rc::new(scripts);
+    let script_columns = Rc::new(script_columns);
+    fill_table(&mut table, &application.borrow(), &script_columns, loaded_scripts(&scripts));
+
+    // Target list buttons
+    let mut btn_add: Listener<_> = button::Button::new(10, 285, 50, 30, tr("Add")).into();
+    btn_add.clear_visible_focus();
+    let mut btn_edit: Listener<_> = button::Button::new(65, 285, 50, 30, tr("Edit")).into();
+    btn_edit.clear_visible_focus();
+    let mut btn_remove: Listener<_> = button::Button::new(120, 285, 60, 30, tr("Remove")).into();
+    btn_remove.clear_visible_focus();
+    let mut btn_up_tonight: Listener<_> = button::Button::new(185, 285, 80, 30, tr("Up tonight")).into();
+    btn_up_tonight.clear_visible_focus();
+    btn_up_tonight.set_tooltip(tr("Export the targets within the constraints tonight to skycalc_up_tonight.csv"));
+
+    // Observing list exports with the best time of each target
+    let mut btn_skysafari: Listener<_> = button::Button::new(270, 285, 75, 30, "SkySafari").into();
+    btn_skysafari.clear_visible_focus();
+    btn_skysafari.set_tooltip(tr("Export the selected targets as a SkySafari observing list to skycalc.skylist"));
+    let mut btn_astroplanner: Listener<_> = button::Button::new(350, 285, 90, 30, "AstroPlanner").into();
+    btn_astroplanner.clear_visible_focus();
+    btn_astroplanner.set_tooltip(tr("Export the selected targets for the AstroPlanner text import to \
+skycalc_astroplanner.txt"));
+
+    // Best months of the selected target
+    let mut btn_details: Listener<_> = button::Button::new(445, 285, 60, 30, tr("Details")).into();
+    btn_details.clear_visible_focus();
+    btn_details.set_tooltip(tr("Darkness with the selected target within the altitude constraints month by month"));
+
+    // Target paths and the Moon avoidance zone
+    let mut btn_sky_chart: Listener<_> = button::Button::new(510, 285, 75, 30, tr("Sky chart")).into();
+    btn_sky_chart.clear_visible_focus();
+    btn_sky_chart.set_tooltip(tr("Paths of the targets and the Moon avoidance zone during the observation window"));
+
+    // Scripts that do not compile
+    let mut script_errors = Label::new(10, 325, 600 + extra_width, 20, "", Align::Left | Align::Inside);
+    if let Some((_, errors)) = &*scripts {
+        if !errors.is_empty() {
+            script_errors.set_label(&format!("{}: {}", tr("Script errors"), errors.join("; ")));
+            script_errors.set_tooltip(&errors.join("\n"));
+        }
+    }
+
+    // Close button
+    let mut btn_close: Listener<_> = button::Button::new(620 + extra_width, 285, 50, 30, tr("Close")).into();
+    btn_close.clear_visible_focus();
+
+    window.end();
+    window.show();
+
+    let mut window_clone = window.clone();
+
+    // Window call back to avoid program termination when ESC is pressed
+    // from FLTK Book - FAQ
+    window.set_callback(|w| {
+        if fltk::app::event() == fltk::enums::Event::Close {
+            w.hide();
+        }
+    });
+
+    // Handlers for Close button
+    // preserve button's original color
+    let btn_close_color = btn_close.color();
+    // close window when clicked
+    btn_close.on_click(move |_| {
+        window_clone.hide();
+    });
+
+    // change color on hover
+    btn_close.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+
+    // reset color on leave
+    btn_close.on_leave(move |b| {
+        b.set_color(btn_close_color);
+    });
+
+    // Handlers for Add button, the new target goes to the end of the list
+    let app_add_clone = Rc::clone(&application);
+    let table_add_clone = table.clone();
+    let (scripts_add, columns_add) = (Rc::clone(&scripts), Rc::clone(&script_columns));
+    btn_add.on_click(move |_| {
+        let (app, mut table) = (Rc::clone(&app_add_clone), table_add_clone.clone());
+        let (scripts, columns) = (Rc::clone(&scripts_add), Rc::clone(&columns_add));
+        handle_target_editor(None, move |target| {
+            let mut targets = app.borrow().targets.clone();
+            targets.push(target);
+            apply_targets(&app, targets);
+            fill_table(&mut table, &app.borrow(), &columns, loaded_scripts(&scripts));
+            let last = table.size();
+            table.select(last);
+        });
+    });
+
+    // Handlers for Edit button and double click on a target
+    let app_edit_clone = Rc::clone(&application);
+    let table_edit_clone = table.clone();
+    let (scripts_edit, columns_edit) = (Rc::clone(&scripts), Rc::clone(&script_columns));
+    btn_edit.on_click(move |_| {
+        let (app, mut table) = (Rc::clone(&app_edit_clone), table_edit_clone.clone());
+        let (scripts, columns) = (Rc::clone(&scripts_edit), Rc::clone(&columns_edit));
+        edit_selected(&app_edit_clone, &table_edit_clone, move || {
+            fill_table(&mut table, &app.borrow(), &columns, loaded_scripts(&scripts));
+        });
+    });
+
+    let app_click_clone = Rc::clone(&application);
+    let (scripts_click, columns_click) = (Rc::clone(&scripts), Rc::clone(&script_columns));
+    table.set_callback(move |t| {
+        if app::event_clicks() {
+            let (app, mut table) = (Rc::clone(&app_click_clone), t.clone());
+            let (scripts, columns) = (Rc::clone(&scripts_click), Rc::clone(&columns_click));
+            edit_selected(&app_click_clone, t, move || {
+                fill_table(&mut table, &app.borrow(), &columns, loaded_scripts(&scripts));
+            });
+        }
+    });
+
+    // Handlers for Remove button
+    let app_remove_clone = Rc::clone(&application);
+    let mut table_remove_clone = table.clone();
+    let (scripts_remove, columns_remove) = (Rc::clone(&scripts), Rc::clone(&script_columns));
+    btn_remove.on_click(move |_| {
+        let Some(index) = selected_target(&table_remove_clone) else {
+            return;
+        };
+        let mut targets = app_remove_clone.borrow().targets.clone();
+        let question = format!("{} {}?", tr("Remove from the target list:"), targets[index].name);
+        if dialog::choice2_default(&question, tr("Cancel"), tr("Remove"), "") != Some(1) {
+            return;
+        }
+        targets.remove(index);
+        apply_targets(&app_remove_clone, targets);
+        fill_table(&mut table_remove_clone, &app_remove_clone.borrow(), &columns_remove,
+                   loaded_scripts(&scripts_remove));
+    });
+
+    // Handlers for Up tonight button
+    let app_up_tonight_clone = Rc::clone(&application);
+    btn_up_tonight.on_click(move |_| {
+        let app = app_up_tonight_clone.borrow();
+        if confirm_export(&app, UP_TONIGHT_REPORT_FILE, &export_night(&app.time)) {
+            up_tonight_report(&app.observer, &app.time, &app.environment, &app.constraints, &app.targets,
+                              &app.equipment, &app.preferences);
+        }
+    });
+
+    // Handlers for observing list buttons
+    let app_skysafari_clone = Rc::clone(&application);
+    let table_skysafari_clone = table.clone();
+    btn_skysafari.on_click(move |_| {
+        let app = app_skysafari_clone.borrow();
+        let Some(targets) = export_targets(&app, &table_skysafari_clone) else {
+            return;
+        };
+        if confirm_export(&app, SKYSAFARI_LIST_FILE, &export_night(&app.time)) {
+            skysafari_report(&app.observer, &app.time, &app.environment, &app.constraints, &targets,
+                             &app.preferences);
+        }
+    });
+
+    let app_astroplanner_clone = Rc::clone(&application);
+    let table_astroplanner_clone = table.clone();
+    btn_astroplanner.on_click(move |_| {
+        let app = app_astroplanner_clone.borrow();
+        let Some(targets) = export_targets(&app, &table_astroplanner_clone) else {
+            return;
+        };
+        if confirm_export(&app, ASTROPLANNER_LIST_FILE, &export_night(&app.time)) {
+            astroplanner_report(&app.observer, &app.time, &app.environment, &app.constraints, &targets,
+                                &app.preferences);
+        }
+    });
+
+    // Handlers for Details button
+    let app_details_clone = Rc::clone(&application);
+    let table_details_clone = table.clone();
+    btn_details.on_click(move |_| {
+        let target = selected_target(&table_details_clone)
+            .and_then(|index| app_details_clone.borrow().targets.get(index).cloned());
+        if let Some(target) = target {
+            handle_target_details(&app_details_clone, &target);
+        }
+    });
+
+    // Handlers for Sky chart button
+    let app_sky_chart_clone = Rc::clone(&application);
+    btn_sky_chart.on_click(move |_| {
+        handle_sky_chart(&app_sky_chart_clone, window_utc);
+    });
+
+    // change color on hover, reset color on leave
+    for btn in [&mut btn_add, &mut btn_edit, &mut btn_up_tonight, &mut btn_skysafari, &mut btn_astroplanner,
+                &mut btn_details, &mut btn_sky_chart] {
+        let btn_color = btn.color();
+        btn.on_hover(|b| {
+            b.set_color(enums::Color::Green.lighter());
+        });
+        btn.on_leave(move |b| {
+            b.set_color(btn_color);
+        });
+    }
+    let btn_remove_color = btn_remove.color();
+    btn_remove.on_hover(|b| {
+        b.set_color(enums::Color::Red.lighter());
+    });
+    btn_remove.on_leave(move |b| {
+        b.set_color(btn_remove_color);
+    });
+
+    Some(window)
+}
@@ -1,5 +1,7 @@
 // src/menu/mod.rs
 pub mod file;
+pub mod edit;
 pub mod about;
 pub mod functions;
 
+pub mod window_manager;
@@ -0,0 +1,125 @@
+// src/menu/window_manager.rs
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use fltk::{app, window};
+use fltk::enums::Shortcut;
+use fltk::menu::{MenuBar, MenuFlag};
+use fltk::prelude::{MenuExt, WidgetBase, WidgetExt, WindowExt};
+use crate::application::session::update_layout;
+use crate::utils::i18n::menu_path;
+
+// Interval of the per-frame updates of the tool windows, in seconds
+const FRAME_INTERVAL: f64 = 0.032;
+// Interval of the checks for closed tool windows opened from the menu, in seconds
+const CLOSE_CHECK_INTERVAL: f64 = 0.5;
+
+type ToolOpener = Rc<RefCell<dyn FnMut() -> Option<window::Window>>>;
+
+thread_local! {
+    // Tool windows created so far with their titles, hidden ones are dropped on the next lookup
+    static OPEN_WINDOWS: RefCell<Vec<(String, window::Window)>> = RefCell::new(Vec::new());
+    // Menu paths of the tools and what opens them, to open again the ones left open last time
+    static TOOL_OPENERS: RefCell<Vec<(&'static str, ToolOpener)>> = RefCell::new(Vec::new());
+    // Tool windows opened from the menu with their menu paths
+    static OPEN_TOOLS: RefCell<Vec<(&'static str, window::Window)>> = RefCell::new(Vec::new());
+}
+
+// Creates a modal tool window centered on the screen. When a window with the same title
+// is already open it is raised instead and None is returned, so each tool opens once.
+pub fn new_tool_window(title: &str, width: i32, height: i32) -> Option<window::Window> {
+    let open = OPEN_WINDOWS.with(|windows| {
+        let mut windows = windows.borrow_mut();
+        windows.retain(|(_, w)| w.shown());
+        windows.iter().find(|(t, _)| t == title).map(|(_, w)| w.clone())
+    });
+    if let Some(mut window) = open {
+        window.show();
+        return None;
+    }
+
+    let mut window = window::Window::default()
+        .with_label(title)
+        .with_size(width, height)
+        .center_screen();
+    window.make_modal(true);
+    OPEN_WINDOWS.with(|windows| windows.borrow_mut().push((title.to_string(), window.clone())));
+    Some(window)
+}
+
+// Runs frame from the main event loop at the frame interval while the window is shown,
+// for windows whose content follows the clock or the application state
+pub fn every_frame<F: FnMut() + 'static>(window: &window::Window, frame: F) {
+    every_seconds(window, FRAME_INTERVAL, frame);
+}
+
+// Runs frame from the main event loop every `seconds` while the window is shown, for windows
+// whose content is too slow to compute at the frame interval
+pub fn every_seconds<F: FnMut() + 'static>(window: &window::Window, seconds: f64, mut frame: F) {
+    let window = window.clone();
+    app::add_timeout3(0.0, move |handle| {
+        if window.shown() {
+            frame();
+            app::repeat_timeout3(seconds, handle);
+        }
+    });
+}
+
+// Adds a menu item opening a tool window with `open`, which returns the window or None when it was
+// already open. The tools left open are saved in the preferences and opened again at the next
+// start, also after the program was ended by the system with a tool window open.
+pub fn add_tool_menu<F>(menu: &mut MenuBar, path: &'static str, shortcut: Shortcut, open: F)
+where
+    F: FnMut() -> Option<window::Window> + 'static,
+{
+    let opener: ToolOpener = Rc::new(RefCell::new(open));
+    TOOL_OPENERS.with(|openers| openers.borrow_mut().push((path, Rc::clone(&opener))));
+    menu.add(&menu_path(path), shortcut, MenuFlag::Normal, move |_| open_tool(path, &opener));
+}
+
+// Opens the tools of the menu paths saved in the preferences, unknown paths are skipped
+pub fn reopen_tools(paths: &[String]) {
+    for path in paths {
+        let opener = TOOL_OPENERS.with(|openers| {
+            openers.borrow().iter().find(|(p, _)| p == path).map(|(p, opener)| (*p, Rc::clone(opener)))
+        });
+        if let Some((path, opener)) = opener {
+            open_tool(path, &opener);
+        }
+    }
+}
+
+fn open_tool(path: &'static str, opener: &ToolOpener) {
+    let opened = {
+        let mut open = opener.borrow_mut();
+        (*open)()
+    };
+    let Some(window) = opened else {
+        return;
+    };
+    OPEN_TOOLS.with(|tools| tools.borrow_mut().push((path, window.clone())));
+    save_open_tools();
+
+    // saved again once the window is closed
+    app::add_timeout3(CLOSE_CHECK_INTERVAL, move |handle| {
+        if window.shown() {
+            app::repeat_timeout3(CLOSE_CHECK_INTERVAL, handle);
+        } else {
+            save_open_tools();
+        }
+    });
+}
+
+/// Menu paths of the tool windows open now, in the order they were opened
+pub fn open_tool_paths() -> Vec<String> {
+    OPEN_TOOLS.with(|tools| {
+        let mut tools = tools.borrow_mut();
+        tools.retain(|(_, w)| w.shown());
+        tools.iter().map(|(path, _)| path.to_string()).collect()
+    })
+}
+
+fn save_open_tools() {
+    let paths = open_tool_paths();
+    update_layout(|preferences| preferences.open_tools = paths);
+}
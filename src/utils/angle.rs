@@ -35,10 +35,12 @@ pub fn format_dms(angle: f64, is_latitude: bool) -> String {
             direction = "W"
         }
     }
-    let d = angle.trunc().abs();
-    let remainder = angle.abs() - d;
-    let m = (remainder * 60.0).trunc();
-    let s = ((remainder * 60.0) - m) * 60.0;
+    // Rounded to tenths of a second first, so 59.96" carries into the minutes instead of
+    // showing as 60.0"
+    let tenths = (angle.abs() * 36000.0).round();
+    let d = (tenths / 36000.0).trunc();
+    let m = ((tenths - d * 36000.0) / 600.0).trunc();
+    let s = (tenths - d * 36000.0 - m * 600.0) / 10.0;
     format!("{}° {}' {:.1}\" {}", d, m, s, direction.to_string())
 }
 
@@ -55,7 +57,7 @@ impl Degrees {
 
         // First, try parsing as decimal degrees
         if let Ok(deg) = input.parse::<f64>() {
-            if deg < min || deg > max {
+            if !deg.is_finite() || deg < min || deg > max {
                 return Self { value: 0.0 };
             }
             return Self { value: deg };
@@ -105,10 +107,204 @@ impl Degrees {
         let decimal_deg = direction * (deg + min_val / 60.0 + sec / 3600.0);
 
         // Ensure the value is within the specified range
-        if decimal_deg < min || decimal_deg > max {
+        if !decimal_deg.is_finite() || decimal_deg < min || decimal_deg > max {
             return Self { value: 0.0 };
         }
 
         Self { value: decimal_deg }
     }
 }
+
+/// Splits a decimal value (degrees or hours) into its sexagesimal components.
+///
+/// Returns `(is_negative, whole, minutes, seconds)` where the components are always positive,
+/// so that values like -0° 30' keep their sign.
+pub fn split_sexagesimal(value: f64) -> (bool, u64, u64, f64) {
+    let is_negative = value < 0.0;
+    let total_seconds = value.abs() * 3600.0;
+    let whole = (total_seconds / 3600.0).trunc();
+    let minutes = ((total_seconds - whole * 3600.0) / 60.0).trunc();
+    let seconds = total_seconds - whole * 3600.0 - minutes * 60.0;
+    (is_negative, whole as u64, minutes as u64, seconds)
+}
+
+/// Formats a value in hours as `hh:mm:ss`, with a leading `-` for negative values.
+pub fn format_hms(hours: f64) -> String {
+    let (is_negative, h, m, s) = split_sexagesimal(hours);
+    let mut s = s.round() as u64;
+    let mut m = m;
+    let mut h = h;
+    if s == 60 {
+        s = 0;
+        m += 1;
+    }
+    if m == 60 {
+        m = 0;
+        h += 1;
+    }
+    format!("{}{:02}:{:02}:{:02}", if is_negative { "-" } else { "" }, h, m, s)
+}
+
+/// Parses `hh:mm:ss.s` or `±dd:mm:ss` (colons or spaces, minutes and seconds optional) into a
+/// signed decimal value in the unit of the first field.
+///
+/// Returns `None` when a field is not a number, when minutes or seconds are 60 or more, or when a
+/// sign appears anywhere but in front.
+pub fn parse_sexagesimal(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let (sign, unsigned) = match text.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let parts: Vec<&str> = unsigned.split([':', ' ']).filter(|part| !part.is_empty()).collect();
+    if parts.is_empty() || parts.len() > 3 {
+        return None;
+    }
+
+    let mut value = 0.0;
+    for (i, (part, scale)) in parts.iter().zip([1.0, 60.0, 3600.0]).enumerate() {
+        // only digits and one decimal point, "1e3", "inf" and signs are rejected
+        if !part.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            return None;
+        }
+        let field = part.parse::<f64>().ok()?;
+        if i > 0 && field >= 60.0 {
+            return None;
+        }
+        value += field / scale;
+    }
+    Some(sign * value)
+}
+
+// Sexagesimal input is recognized by its separators, plain numbers are decimal degrees
+fn is_sexagesimal(text: &str) -> bool {
+    text.trim().contains([':', ' '])
+}
+
+/// Right ascension in degrees from `hh:mm:ss.s` hours or decimal degrees, or the message shown
+/// to the user.
+pub fn try_ra_from_str(text: &str) -> Result<f64, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("Enter a right ascension".to_string());
+    }
+    let degrees = if is_sexagesimal(text) {
+        let hours = parse_sexagesimal(text)
+            .ok_or_else(|| format!("'{}' is not a right ascension, use hh:mm:ss.s or degrees", text))?;
+        if !(0.0..24.0).contains(&hours) {
+            return Err(format!("{} is out of range, from 00:00:00 to 23:59:59.9", text));
+        }
+        hours * 15.0
+    } else {
+        let degrees = text
+            .parse::<f64>()
+            .ok()
+            .filter(|d| d.is_finite())
+            .ok_or_else(|| format!("'{}' is not a right ascension, use hh:mm:ss.s or degrees", text))?;
+        if !(0.0..360.0).contains(&degrees) {
+            return Err(format!("{} is out of range, from 0 to 360", text));
+        }
+        degrees
+    };
+    Ok(degrees)
+}
+
+/// Declination in degrees from `±dd:mm:ss` or decimal degrees, or the message shown to the user.
+pub fn try_dec_from_str(text: &str) -> Result<f64, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("Enter a declination".to_string());
+    }
+    let degrees = if is_sexagesimal(text) {
+        parse_sexagesimal(text)
+    } else {
+        text.parse::<f64>().ok().filter(|d| d.is_finite())
+    }
+    .ok_or_else(|| format!("'{}' is not a declination, use ±dd:mm:ss or degrees", text))?;
+    if !(-90.0..=90.0).contains(&degrees) {
+        return Err(format!("{} is out of range, from -90 to +90", text));
+    }
+    Ok(degrees)
+}
+
+/// Formats a right ascension in degrees as `hh:mm:ss.s`.
+pub fn format_ra_hms(degrees: f64) -> String {
+    // rounded to tenths of a second first so 59.96s carries over, 24h wraps to 0h
+    let tenths = ((degrees.rem_euclid(360.0) / 15.0) * 36000.0).round() as u64 % (24 * 36000);
+    format!("{:02}:{:02}:{:04.1}", tenths / 36000, tenths % 36000 / 600, (tenths % 600) as f64 / 10.0)
+}
+
+/// Formats a declination in degrees as `±dd:mm:ss`.
+pub fn format_dec_dms(degrees: f64) -> String {
+    let seconds = (degrees.abs() * 3600.0).round() as u64;
+    format!("{}{:02}:{:02}:{:02}",
+            if degrees < 0.0 && seconds > 0 { "-" } else { "+" },
+            seconds / 3600, seconds % 3600 / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::fuzz::{Fuzzer, TEXT_TOKENS};
+
+    #[test]
+    fn test_ra_dec_parsing() {
+        assert_eq!(parse_sexagesimal("05:35:17.3"), Some(5.0 + 35.0 / 60.0 + 17.3 / 3600.0));
+        assert_eq!(parse_sexagesimal("-00:30"), Some(-0.5));
+        assert_eq!(parse_sexagesimal("+12 30 00"), Some(12.5));
+        assert_eq!(parse_sexagesimal("05:60:00"), None);
+        assert_eq!(parse_sexagesimal("05:-3:00"), None);
+        assert_eq!(parse_sexagesimal("1:2:3:4"), None);
+
+        // M42, sexagesimal hours or decimal degrees
+        let ra = try_ra_from_str("05:35:17.3").unwrap();
+        assert!((ra - 83.822083).abs() < 1e-6);
+        assert_eq!(try_ra_from_str("83.8"), Ok(83.8));
+        assert!(try_ra_from_str("24:00:00").is_err());
+        assert!(try_ra_from_str("360").is_err());
+        assert!(try_ra_from_str("").is_err());
+
+        let dec = try_dec_from_str("-05:23:28").unwrap();
+        assert!((dec + 5.391111).abs() < 1e-6);
+        assert_eq!(try_dec_from_str("-0 30"), Ok(-0.5));
+        assert_eq!(try_dec_from_str("+90:00:00"), Ok(90.0));
+        assert!(try_dec_from_str("-90:00:01").is_err());
+        assert!(try_dec_from_str("nan").is_err());
+    }
+
+    #[test]
+    fn test_ra_dec_formatting() {
+        assert_eq!(format_ra_hms(83.822083), "05:35:17.3");
+        assert_eq!(format_ra_hms(359.99999), "00:00:00.0");
+        assert_eq!(format_ra_hms(-15.0), "23:00:00.0");
+        assert_eq!(format_dec_dms(-5.391111), "-05:23:28");
+        assert_eq!(format_dec_dms(-0.00001), "+00:00:00");
+        assert_eq!(format_dec_dms(90.0), "+90:00:00");
+
+        // formatted values parse back to the same coordinates
+        for degrees in [0.0, 10.5, 83.822083, 201.3, 359.9] {
+            let ra = try_ra_from_str(&format_ra_hms(degrees)).unwrap();
+            assert!((ra - degrees).abs() < 0.001, "{}", degrees);
+        }
+        for degrees in [-89.99, -5.391111, 0.0, 41.269, 90.0] {
+            let dec = try_dec_from_str(&format_dec_dms(degrees)).unwrap();
+            assert!((dec - degrees).abs() < 0.001, "{}", degrees);
+        }
+    }
+
+    #[test]
+    fn test_ra_dec_fuzz() {
+        let mut fuzzer = Fuzzer::new(0xD1B5_4A32_D192_ED03);
+        for _ in 0..50_000 {
+            let input = fuzzer.string(TEXT_TOKENS, 8);
+            if let Ok(ra) = try_ra_from_str(&input) {
+                assert!((0.0..360.0).contains(&ra), "{:?}", input);
+                format_ra_hms(ra);
+            }
+            if let Ok(dec) = try_dec_from_str(&input) {
+                assert!((-90.0..=90.0).contains(&dec), "{:?}", input);
+                format_dec_dms(dec);
+            }
+        }
+    }
+}
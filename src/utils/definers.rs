@@ -1,6 +1,13 @@
 pub const MENU_HEIGHT: i32 = 25;
+pub const STATUS_BAR_HEIGHT: i32 = 22;
 pub const APP_TITLE: &str = "Skycalc";
 pub const APP_VERSION: &str = "0.0.3";
 pub const APP_COPYRIGHT: &str = "Copyright 2024-2025 - R. N. Cernic";
 pub const TOOLTIP_DATE_INPUT: &str = "Accepted date formats:\n\nYYYY-MM-DD,\nDD/MM/YYYY,\
-\nDD-MM-YYYY,\nYYYYMMDD";
\ No newline at end of file
+\nDD-MM-YYYY,\nYYYYMMDD";
+pub const TOOLTIP_ANGLE_INPUT: &str = "Decimal degrees or DMS, e.g. -23.1 or 23d 06m S";
+pub const TOOLTIP_RA_INPUT: &str = "Hours as hh:mm:ss.s or decimal degrees, e.g. 05:35:17.3 or 83.82";
+pub const TOOLTIP_DEC_INPUT: &str = "Degrees as ±dd:mm:ss or decimal degrees, e.g. -05:23:28 or -5.39";
+pub const TOOLTIP_TIMEZONE_INPUT: &str = "Offset from UTC in hours, e.g. -3, 5.5 or +05:30";
+pub const TOOLTIP_ELEVATION_INPUT: &str = "Elevation in whole meters";
+pub const TOOLTIP_ELEVATION_FEET_INPUT: &str = "Elevation in feet, kept in whole meters";
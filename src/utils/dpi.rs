@@ -0,0 +1,141 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Interface scale factors and font sizes for high DPI screens
+
+use crate::utils::i18n::tr;
+
+/// Interface scales offered in the preferences, `None` follows the system setting of each screen
+pub const UI_SCALES: [Option<f32>; 6] = [None, Some(1.0), Some(1.25), Some(1.5), Some(1.75), Some(2.0)];
+
+/// FLTK default label and text size, in unscaled pixels
+pub const BASE_FONT_SIZE: i32 = 14;
+
+// Screens report odd factors like 1.4999, anything outside this range is a bad value
+const MIN_SCALE: f32 = 0.5;
+const MAX_SCALE: f32 = 4.0;
+
+/// Label used in the preferences for an interface scale, e.g. "150%"
+pub fn scale_label(scale: Option<f32>) -> String {
+    match scale {
+        Some(scale) => format!("{:.0}%", scale * 100.0),
+        None => tr("System").to_string(),
+    }
+}
+
+/// Position of a saved interface scale in `UI_SCALES`, unknown values fall back to the system setting
+pub fn scale_index(scale: Option<f32>) -> usize {
+    UI_SCALES
+        .iter()
+        .position(|s| match (s, scale) {
+            (Some(a), Some(b)) => (a - b).abs() < 0.01,
+            (None, None) => true,
+            _ => false,
+        })
+        .unwrap_or(0)
+}
+
+/// Scale factor clamped to a usable range, 1.0 for values that are not a number
+pub fn sanitize_scale(scale: f32) -> f32 {
+    if scale.is_finite() {
+        scale.clamp(MIN_SCALE, MAX_SCALE)
+    } else {
+        1.0
+    }
+}
+
+/// Font size close to `base` that becomes a whole number of pixels once scaled.
+///
+/// Text is drawn at `size * scale` pixels, at 125% a 14 px font would land on 17.5 px and
+/// render blurry. One size smaller is used when it scales closer to whole pixels, a larger font
+/// would not fit in the layouts, which are designed for `base`.
+pub fn crisp_font_size(base: i32, scale: f32) -> i32 {
+    let scale = sanitize_scale(scale);
+    let error = |size: i32| {
+        let scaled = size as f32 * scale;
+        (scaled - scaled.round()).abs()
+    };
+    if base > 1 && error(base - 1) < error(base) - 0.01 {
+        base - 1
+    } else {
+        base
+    }
+}
+
+/// Window size that fits inside a work area, both in unscaled pixels.
+///
+/// A 1366 x 768 laptop at 150% only has 910 x 512 pixels for windows, larger windows are
+/// shrunk so that their title bar and borders stay on the screen.
+pub fn fit_size(width: i32, height: i32, area_width: i32, area_height: i32) -> (i32, i32) {
+    // space left for the window decorations
+    const DECORATIONS: i32 = 40;
+    let max_width = (area_width - DECORATIONS).max(1);
+    let max_height = (area_height - DECORATIONS).max(1);
+    (width.min(max_width), height.min(max_height))
+}
+
+/// Layout size designed for the base font size, grown or shrunk in proportion to `font_size`.
+///
+/// With a 16 px font instead of 14, a 20 px label row becomes 23 px so the text is not clipped.
+pub fn font_scaled(size: i32, font_size: i32) -> i32 {
+    (size * font_size + BASE_FONT_SIZE / 2) / BASE_FONT_SIZE
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_crisp_font_size() {
+        // whole pixels already
+        assert_eq!(crisp_font_size(14, 1.0), 14);
+        assert_eq!(crisp_font_size(14, 1.5), 14);
+        assert_eq!(crisp_font_size(14, 2.0), 14);
+        // 17.5 px at 125% and 24.5 px at 175%, 13 gives 16.25 and 22.75 px, never larger
+        assert_eq!(crisp_font_size(14, 1.25), 13);
+        assert_eq!(crisp_font_size(14, 1.75), 13);
+        assert_eq!(crisp_font_size(12, 1.25), 12);
+        assert!((1..=4).all(|i| crisp_font_size(14, 1.0 + i as f32 * 0.25) <= 14));
+        // bad factors from the system are ignored
+        assert_eq!(crisp_font_size(14, f32::NAN), 14);
+    }
+
+    #[test]
+    fn test_scale_choices() {
+        assert_eq!(scale_index(None), 0);
+        assert_eq!(scale_index(Some(1.5)), 3);
+        assert_eq!(scale_index(Some(1.33)), 0);
+        assert_eq!(scale_label(Some(1.25)), "125%");
+        assert_eq!(scale_label(None), "System");
+        assert_eq!(sanitize_scale(10.0), 4.0);
+        assert_eq!(fit_size(800, 600, 1366, 728), (800, 600));
+        assert_eq!(fit_size(800, 600, 910, 485), (800, 445));
+    }
+
+    #[test]
+    fn test_font_scaled() {
+        assert_eq!(font_scaled(20, BASE_FONT_SIZE), 20);
+        assert_eq!(font_scaled(20, 16), 23);
+        assert_eq!(font_scaled(25, 12), 21);
+        assert_eq!(font_scaled(1, 16), 1);
+    }
+}
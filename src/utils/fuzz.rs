@@ -0,0 +1,74 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Deterministic input generator for the parser fuzz tests, there is no fuzzing crate in the
+// dependencies so the inputs come from a xorshift generator seeded with a constant to keep
+// failures reproducible.
+
+pub struct Fuzzer {
+    state: u64,
+}
+
+impl Fuzzer {
+    pub fn new(seed: u64) -> Fuzzer {
+        Fuzzer { state: seed.max(1) }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n.max(1) as u64) as usize
+    }
+
+    /// String made of up to max_tokens tokens picked at random
+    pub fn string(&mut self, tokens: &[&str], max_tokens: usize) -> String {
+        let count = self.below(max_tokens + 1);
+        (0..count).map(|_| tokens[self.below(tokens.len())]).collect()
+    }
+
+    /// Copy of base with a few characters removed and tokens inserted at random positions
+    pub fn mutate(&mut self, base: &str, tokens: &[&str]) -> String {
+        let mut chars: Vec<char> = base.chars().collect();
+        for _ in 0..=self.below(5) {
+            let position = self.below(chars.len() + 1);
+            if self.next_u64().is_multiple_of(2) && position < chars.len() {
+                chars.remove(position);
+            }
+            let token = tokens[self.below(tokens.len())];
+            for (i, c) in token.chars().enumerate() {
+                chars.insert(position + i, c);
+            }
+        }
+        chars.into_iter().collect()
+    }
+}
+
+// Tokens that exercise the separators, signs and number formats accepted by the text fields
+pub const TEXT_TOKENS: &[&str] = &[
+    "0", "1", "9", "-", "+", ":", "/", ".", "d", "m", "s", "°", "'", "\"", "n", "e", "w", " ",
+    "inf", "nan", "é", "2024", "13", "60", "99999999999", "24:00", "1e309", "\u{0}",
+];
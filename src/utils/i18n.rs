@@ -0,0 +1,787 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// Localization of the interface and the reports: the English text is the key of a catalog
+// per language, anything missing from a catalog is shown in English
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use serde::{Deserialize, Serialize};
+
+/// Languages of the interface, saved with the preferences by their language tag
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Language {
+    #[default]
+    #[serde(rename = "en")]
+    English,
+    #[serde(rename = "pt-BR")]
+    Portuguese,
+}
+
+/// Languages offered in the preferences, in the order of the enum
+pub const LANGUAGES: [Language; 2] = [Language::English, Language::Portuguese];
+
+impl Language {
+    /// Name shown in the preferences, in the language itself
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Portuguese => "Português (Brasil)",
+        }
+    }
+
+    fn catalog(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::English => &[],
+            Language::Portuguese => PORTUGUESE,
+        }
+    }
+}
+
+// Current language, an index in LANGUAGES so that report threads read it too
+static LANGUAGE: AtomicU8 = AtomicU8::new(0);
+
+// Language the menu bar was built in, menus are looked up by path and keep it until restart
+static MENU_LANGUAGE: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_language(language: Language) {
+    LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+
+pub fn current_language() -> Language {
+    stored_language(&LANGUAGE)
+}
+
+/// Language of the menu bar, set once before it is built
+pub fn set_menu_language(language: Language) {
+    MENU_LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+
+fn stored_language(language: &AtomicU8) -> Language {
+    LANGUAGES.get(language.load(Ordering::Relaxed) as usize).copied().unwrap_or_default()
+}
+
+/// `text` in `language`, `text` itself when the catalog has no translation
+pub fn translate(language: Language, text: &'static str) -> &'static str {
+    language
+        .catalog()
+        .iter()
+        .find(|(english, _)| *english == text)
+        .map_or(text, |(_, translated)| translated)
+}
+
+/// `text` in the current language
+pub fn tr(text: &'static str) -> &'static str {
+    translate(current_language(), text)
+}
+
+/// Menu path like "&File/Configuration/&Load\t" in `language`, segment by segment.
+///
+/// FLTK matches parent menus ignoring the '&' of the shortcut letter, so a segment is also
+/// looked up without it, and keeps its shortcut letter only when it had one.
+pub fn translate_path(language: Language, path: &str) -> String {
+    path.split('/')
+        .map(|segment| translate_segment(language, segment))
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+/// Menu path in the language of the menu bar
+pub fn menu_path(path: &str) -> String {
+    translate_path(stored_language(&MENU_LANGUAGE), path)
+}
+
+fn translate_segment(language: Language, segment: &str) -> String {
+    let label = segment.trim_end_matches('\t');
+    let suffix = &segment[label.len()..];
+    let plain = label.replace('&', "");
+    let catalog = language.catalog();
+    let exact = catalog.iter().find(|(english, _)| *english == label);
+    match exact.or_else(|| catalog.iter().find(|(english, _)| english.replace('&', "") == plain)) {
+        Some((_, translated)) if label.contains('&') => format!("{}{}", translated, suffix),
+        Some((_, translated)) => format!("{}{}", translated.replace('&', ""), suffix),
+        None => segment.to_string(),
+    }
+}
+
+/// English month names, the keys of `month_name`
+pub const MONTHS: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+
+/// Name of a month (1 - 12) in the current language
+pub fn month_name(month: u64) -> &'static str {
+    tr(MONTHS[(month.clamp(1, 12) - 1) as usize])
+}
+
+/// First three letters of the month name in the current language, e.g. "Fev" in Portuguese
+pub fn month_abbreviation(month: u64) -> String {
+    month_name(month).chars().take(3).collect()
+}
+
+// Brazilian Portuguese, keys with a shortcut letter ('&') are translated with one
+const PORTUGUESE: &[(&str, &str)] = &[
+    // Menus
+    ("&File", "&Arquivo"),
+    ("&Configuration", "&Configuração"),
+    ("&Load", "&Carregar"),
+    ("&Save", "&Salvar"),
+    ("&Recent", "&Recentes"),
+    ("(empty)", "(vazio)"),
+    ("Load last at st&artup", "Carregar a última ao i&niciar"),
+    ("&Preferences", "&Preferências"),
+    ("E&xit", "Sa&ir"),
+    ("&Edit", "&Editar"),
+    ("&Undo", "&Desfazer"),
+    ("&Redo", "&Refazer"),
+    ("F&unctions", "F&unções"),
+    ("&Observatory", "&Observatório"),
+    ("&Constraints", "&Restrições"),
+    ("E&nvironment", "Am&biente"),
+    ("&Equipment", "&Equipamento"),
+    ("Mo&saic", "Mosai&co"),
+    ("Altitude cha&rt", "Grá&fico de altitude"),
+    ("&Darkness", "E&scuridão"),
+    ("&Moon separation", "Separação da &Lua"),
+    ("Darkness ca&lendar", "Calen&dário de escuridão"),
+    ("Mont&hly almanac", "Almanaque &mensal"),
+    ("Com&pare sites", "Com&parar locais"),
+    ("Night &quality trends", "Tendências de &qualidade"),
+    ("Sky s&tatus", "Es&tado do céu"),
+    ("&Zenith and meridian", "&Zênite e meridiano"),
+    ("Countdo&wns", "Contagens re&gressivas"),
+    ("T&argets", "Al&vos"),
+    ("Al&ignment stars", "Estrelas de al&inhamento"),
+    ("Solar s&ystem", "Sistema sol&ar"),
+    ("&View", "E&xibir"),
+    ("&Themes", "&Temas"),
+    ("Dark", "Escuro"),
+    ("Black", "Preto"),
+    ("Gray", "Cinza"),
+    ("&Night vision", "Visão &noturna"),
+    ("&Widgets", "&Controles"),
+    ("Classic", "Clássico"),
+    ("&Help", "A&juda"),
+    ("&What's New", "&Novidades"),
+    ("Calculation &inspector", "&Inspetor de cálculos"),
+    ("&Diagnostics", "&Diagnóstico"),
+    ("&About", "&Sobre"),
+    // Dialog buttons
+    ("Apply", "Aplicar"),
+    ("Close", "Fechar"),
+    ("&Apply", "&Aplicar"),
+    ("&Close", "Fe&char"),
+    ("Export", "Exportar"),
+    ("Today", "Hoje"),
+    ("Previous day", "Dia anterior"),
+    ("Copy", "Copiar"),
+    ("Copy summary", "Copiar resumo"),
+    ("Copy a summary of the night to the clipboard", "Copiar um resumo da noite para a área de transferência"),
+    ("Next day", "Próximo dia"),
+    // Preferences
+    ("Preferences", "Preferências"),
+    ("Show seconds in times", "Mostrar segundos nos horários"),
+    ("When unchecked times are rounded to the nearest minute",
+     "Desmarcado, os horários são arredondados para o minuto mais próximo"),
+    ("Time format", "Formato de hora"),
+    ("Clock, day-month order and month names of the event times in the windows and reports",
+     "Relógio, ordem dia-mês e nomes dos meses dos horários nas janelas e relatórios"),
+    ("Confirm overwriting exports", "Confirmar ao sobrescrever exportações"),
+    ("Ask before replacing an export of the same night",
+     "Perguntar antes de substituir uma exportação da mesma noite"),
+    ("Timeline in HTML report", "Linha do tempo no relatório HTML"),
+    ("Draw twilight and Moon up bars in the HTML darkness report",
+     "Desenhar as faixas de crepúsculo e de Lua no céu no relatório HTML"),
+    ("Weather forecast (Open-Meteo)", "Previsão do tempo (Open-Meteo)"),
+    ("Hourly cloud cover for the night, needs internet access",
+     "Nebulosidade hora a hora durante a noite, requer acesso à internet"),
+    ("Timeline centered on solar midnight", "Linha do tempo na meia-noite solar"),
+    ("Hours from solar midnight instead of clock time, same layout in every time zone",
+     "Horas a partir da meia-noite solar em vez do relógio, o mesmo layout em qualquer fuso"),
+    ("Remember selected date", "Lembrar a data selecionada"),
+    ("Save the selected date with the configuration instead of starting at now",
+     "Salvar a data selecionada com a configuração em vez de começar no momento atual"),
+    ("Run user scripts", "Executar scripts do usuário"),
+    ("Custom target columns and report lines from the .rhai files in",
+     "Colunas de alvos e linhas de relatório dos arquivos .rhai em"),
+    ("Coordinates in D° M' S\"", "Coordenadas em G° M' S\""),
+    ("Show latitude and longitude as D° M' S\" with N/S and E/W instead of decimal degrees",
+     "Mostrar latitude e longitude como G° M' S\" com N/S e E/W em vez de graus decimais"),
+    ("Satellite passes in report", "Passagens de satélites no relatório"),
+    ("Visible passes of the ISS and the bright satellites in the darkness report",
+     "Passagens visíveis da ISS e dos satélites brilhantes no relatório de escuridão"),
+    ("Golden and blue hours", "Hora dourada e hora azul"),
+    ("Photography light bands of the twilight in the darkness window and report",
+     "Faixas de luz do crepúsculo para fotografia na janela e no relatório de escuridão"),
+    ("Zodiacal light in report", "Luz zodiacal no relatório"),
+    ("Evening and morning windows with the Sun 18° to 25° below the horizon and the ecliptic angle",
+     "Janelas da tarde e da manhã com o Sol de 18° a 25° abaixo do horizonte e o ângulo da eclíptica"),
+    ("Lunar observing in report", "Observação lunar no relatório"),
+    ("Alignment stars in report", "Estrelas de alinhamento no relatório"),
+    ("Go-to alignment stars spread around the sky at nautical dusk",
+     "Estrelas de alinhamento do go-to espalhadas pelo céu no crepúsculo náutico"),
+    ("Colongitude of the Moon and the named features near the terminator at local midnight",
+     "Colongitude da Lua e as formações nomeadas perto do terminador à meia-noite local"),
+    ("Field rotation", "Rotação de campo"),
+    ("Field rotation in target details", "Rotação de campo nos detalhes do alvo"),
+    ("Field rotation: up to", "Rotação de campo: até"),
+    ("not within the altitude constraints tonight", "fora das restrições de altitude esta noite"),
+    ("at", "às"),
+    ("in total", "no total"),
+    ("Longest exposure for", "Exposição mais longa para"),
+    ("of rotation at the highest rate", "de rotação na taxa mais alta"),
+    ("Rotation of the field seen by an alt-az mount during the observation window",
+     "Rotação do campo vista por uma montagem alt-az durante a janela de observação"),
+    ("Mount driver", "Driver da montagem"),
+    ("alpaca://host:port/device or indi://host:port/device, to sync the observatory site",
+     "alpaca://host:porta/dispositivo ou indi://host:porta/dispositivo, para sincronizar o local do observatório"),
+    ("New night", "Nova noite"),
+    ("Move to it", "Mudar para ela"),
+    ("Ask", "Perguntar"),
+    ("Keep the date", "Manter a data"),
+    ("What the darkness window does when a new observing night starts while tonight is shown",
+     "O que a janela de escuridão faz quando uma nova noite de observação começa enquanto esta noite é mostrada"),
+    ("Local hour the observing night changes at, 0 to 23", "Hora local em que a noite de observação muda, de 0 a 23"),
+    ("A new observing night started. Move to tonight?", "Uma nova noite de observação começou. Mudar para esta noite?"),
+    ("Move", "Mudar"),
+    ("Interface scale", "Escala da interface"),
+    ("System", "Sistema"),
+    ("Size of windows and text, System follows the display settings of each screen",
+     "Tamanho das janelas e do texto, Sistema segue a configuração de cada tela"),
+    ("Sky brightness grid (CSV)", "Grade de brilho do céu (CSV)"),
+    ("Latitude, longitude and SQM per line, empty to disable",
+     "Latitude, longitude e SQM por linha, vazio para desativar"),
+    ("SRTM elevation tiles directory", "Diretório de blocos de altitude SRTM"),
+    ("Directory with .hgt tiles named like S24W047.hgt, empty to disable",
+     "Diretório com blocos .hgt nomeados como S24W047.hgt, vazio para desativar"),
+    ("Satellite elements (TLE)", "Elementos de satélites (TLE)"),
+    ("Two-line element file, empty to download the Celestrak visual group once a day",
+     "Arquivo de elementos TLE, vazio para baixar o grupo visual da Celestrak uma vez por dia"),
+    ("Language", "Idioma"),
+    ("Language of the windows and reports, the menus change on the next start",
+     "Idioma das janelas e relatórios, os menus mudam na próxima inicialização"),
+    ("Accuracy", "Precisão"),
+    ("Fast", "Rápida"),
+    ("Standard", "Padrão"),
+    ("Precise", "Alta"),
+    ("Custom", "Personalizada"),
+    ("Fast: darkness every 5 minutes with a low precision Moon, for slow machines\nStandard: every minute\nPrecise: every 10 seconds",
+     "Rápida: escuridão a cada 5 minutos com a Lua de baixa precisão, para computadores lentos\nPadrão: a cada minuto\nAlta: a cada 10 segundos"),
+    ("Refraction at rise and set", "Refração no nascer e ocaso"),
+    ("When unchecked rise and set times are geometric, a few minutes later at sunrise and earlier at sunset",
+     "Desmarcado, o nascer e o ocaso são geométricos, alguns minutos mais tarde no nascer e mais cedo no ocaso"),
+    ("Units", "Unidades"),
+    ("Observatory elevation in meters or feet", "Altitude do observatório em metros ou pés"),
+    ("Temperature and dew point in degrees Celsius or Fahrenheit",
+     "Temperatura e ponto de orvalho em graus Celsius ou Fahrenheit"),
+    ("Air pressure in hectopascals or inches of mercury", "Pressão do ar em hectopascais ou polegadas de mercúrio"),
+    ("Moon avoidance radius (°)", "Raio de exclusão da Lua (°)"),
+    ("Targets closer than this to the Moon during the observation window are flagged",
+     "Alvos mais próximos que isso da Lua durante a janela de observação são marcados"),
+    // Darkness Calculator
+    ("Darkness Calculator", "Calculadora de escuridão"),
+    ("Observatory:", "Observatório:"),
+    ("Elevation:", "Elevação:"),
+    ("Date:", "Data:"),
+    ("Timezone:", "Fuso:"),
+    ("Obs. Setup", "Config. obs."),
+    ("Sunset", "Pôr do Sol"),
+    ("Sunrise", "Nascer do Sol"),
+    ("Sunset az.", "Az. pôr do Sol"),
+    ("Sunrise az.", "Az. nascer do Sol"),
+    ("Azimuth from north through east where the Sun sets", "Azimute, do norte para o leste, onde o Sol se põe"),
+    ("Azimuth from north through east where the Sun rises", "Azimute, do norte para o leste, onde o Sol nasce"),
+    ("Civ Tw end", "Crep civ fim"),
+    ("Civ Tw start", "Crep civ início"),
+    ("Naut Tw end", "Crep náut fim"),
+    ("Naut Tw start", "Crep náut início"),
+    ("Astro Tw end", "Crep astro fim"),
+    ("Astro Tw start", "Crep astro início"),
+    ("Moon rise", "Nascer da Lua"),
+    ("Moon set", "Pôr da Lua"),
+    ("Moon rise az.", "Az. nascer da Lua"),
+    ("Moon set az.", "Az. pôr da Lua"),
+    ("Azimuth from north through east where the Moon rises", "Azimute, do norte para o leste, onde a Lua nasce"),
+    ("Azimuth from north through east where the Moon sets", "Azimute, do norte para o leste, onde a Lua se põe"),
+    ("Selenographic longitude and latitude of the sub-Earth point",
+     "Longitude e latitude selenográficas do ponto sub-terrestre"),
+    ("Sun alt/az", "Sol alt/az"),
+    ("Moon alt/az", "Lua alt/az"),
+    ("Import", "Importar"),
+    ("Add the sites of a Stellarium location file, a N.I.N.A. profile or a Cartes du Ciel configuration to the \
+library",
+     "Adiciona à biblioteca os locais de um arquivo de locais do Stellarium, de um perfil do N.I.N.A. ou de uma \
+configuração do Cartes du Ciel"),
+    ("Simulate:", "Simular:"),
+    ("Drag to see the Sun and Moon at any time of the night, the date and the clock are kept",
+     "Arraste para ver o Sol e a Lua a qualquer hora da noite, a data e o relógio são mantidos"),
+    ("Now", "Agora"),
+    ("Back to the current time", "Volta para a hora atual"),
+    ("DSO Astro start", "DSO astro início"),
+    ("DSO Astro end", "DSO astro fim"),
+    ("DSO Naut start", "DSO náut início"),
+    ("DSO Naut end", "DSO náut fim"),
+    ("Golden start", "Dourada início"),
+    ("Golden end", "Dourada fim"),
+    ("Blue start", "Azul início"),
+    ("Blue end", "Azul fim"),
+    ("Evening golden hour, Sun from 6° down to -4°", "Hora dourada da tarde, Sol de 6° até -4°"),
+    ("Morning golden hour, Sun from -4° up to 6°", "Hora dourada da manhã, Sol de -4° até 6°"),
+    ("Evening blue hour, Sun from -4° down to -6° at the end of civil twilight",
+     "Hora azul da tarde, Sol de -4° até -6° no fim do crepúsculo civil"),
+    ("Morning blue hour, Sun from -6° at the start of civil twilight up to -4°",
+     "Hora azul da manhã, Sol de -6° no início do crepúsculo civil até -4°"),
+    ("NB Astro start", "NB astro início"),
+    ("NB Astro end", "NB astro fim"),
+    ("NB Naut start", "NB náut início"),
+    ("NB Naut end", "NB náut fim"),
+    // Compare sites
+    ("Compare sites", "Comparar locais"),
+    ("Current observatory", "Observatório atual"),
+    ("Night of", "Noite de"),
+    ("Compare with", "Comparar com"),
+    ("Observatories saved in the library, add configuration files with Add",
+     "Observatórios salvos na biblioteca, inclua arquivos de configuração com Incluir"),
+    ("Add...", "Incluir..."),
+    ("Add a configuration file to the list", "Inclui um arquivo de configuração na lista"),
+    ("Export the darkness of the night at every site to skycalc_sites.txt",
+     "Exporta a escuridão da noite em cada local para skycalc_sites.txt"),
+    ("These configurations could not be read:", "Estas configurações não puderam ser lidas:"),
+    ("Check at least one configuration to compare with", "Marque ao menos uma configuração para comparar"),
+    // Report sections
+    ("Observatory", "Observatório"),
+    ("Site", "Local"),
+    ("Sky brightness", "Brilho do céu"),
+    ("Info for night", "Informações da noite"),
+    ("to", "a"),
+    ("in local time", "no horário local"),
+    ("Sun", "Sol"),
+    ("Moon", "Lua"),
+    ("Rise", "Nascer"),
+    ("Set", "Pôr"),
+    ("Set azimuth", "Azimute do pôr"),
+    ("Rise azimuth", "Azimute do nascer"),
+    ("start", "início"),
+    ("end", "fim"),
+    ("Transit", "Trânsito"),
+    ("Max altitude", "Altitude máx."),
+    ("Libration", "Libração"),
+    ("Axis PA", "AP do eixo"),
+    ("Civil Tw end", "Fim crep. civil"),
+    ("Nautical Tw end", "Fim crep. náutico"),
+    ("Astronomical Tw end", "Fim crep. astronômico"),
+    ("Site comparison", "Comparação de locais"),
+    ("Observatories", "Observatórios"),
+    ("Darkness start", "Início da escuridão"),
+    ("Darkness end", "Fim da escuridão"),
+    ("Moon up in darkness", "Lua acima na escuridão"),
+    ("Most Moon free darkness", "Mais escuridão sem Lua"),
+    ("Golden hour evening", "Hora dourada da tarde"),
+    ("Blue hour evening", "Hora azul da tarde"),
+    ("Blue hour morning", "Hora azul da manhã"),
+    ("Golden hour morning", "Hora dourada da manhã"),
+    ("Almanac", "Almanaque"),
+    ("Monthly almanac", "Almanaque mensal"),
+    ("Month", "Mês"),
+    ("Year", "Ano"),
+    ("Also as CSV", "Também em CSV"),
+    ("Write the same table to skycalc_almanac.csv, times in 24 hours",
+     "Gravar a mesma tabela em skycalc_almanac.csv, horários em 24 horas"),
+    ("Export twilights, sunrise and sunset, moonrise and moonset of every day to skycalc_almanac.txt",
+     "Exportar crepúsculos, nascer e pôr do Sol, nascer e ocaso da Lua de cada dia para skycalc_almanac.txt"),
+    ("local time", "hora local"),
+    ("Date", "Data"),
+    ("Astro", "Astro"),
+    ("Naut", "Náut"),
+    ("Civil", "Civil"),
+    ("Moonrise", "Nasc. Lua"),
+    ("Moonset", "Ocaso Lua"),
+    ("Illum", "Ilum"),
+    ("the event does not happen that day", "o evento não acontece no dia"),
+    ("January", "Janeiro"),
+    ("February", "Fevereiro"),
+    ("March", "Março"),
+    ("April", "Abril"),
+    ("May", "Maio"),
+    ("June", "Junho"),
+    ("July", "Julho"),
+    ("August", "Agosto"),
+    ("September", "Setembro"),
+    ("October", "Outubro"),
+    ("November", "Novembro"),
+    ("December", "Dezembro"),
+    ("Seasons", "Estações"),
+    ("March equinox", "Equinócio de março"),
+    ("June solstice", "Solstício de junho"),
+    ("September equinox", "Equinócio de setembro"),
+    ("December solstice", "Solstício de dezembro"),
+    ("Spring equinox", "Equinócio de primavera"),
+    ("Autumn equinox", "Equinócio de outono"),
+    ("Summer solstice", "Solstício de verão"),
+    ("Winter solstice", "Solstício de inverno"),
+    ("Lunar observing at local midnight", "Observação lunar à meia-noite local"),
+    ("Colongitude", "Colongitude"),
+    ("Illumination", "Iluminação"),
+    ("Subsolar latitude", "Latitude subsolar"),
+    ("No listed features near the terminator", "Nenhuma formação da lista perto do terminador"),
+    ("sunrise", "nascer"),
+    ("sunset", "ocaso"),
+    ("Zodiacal light", "Luz zodiacal"),
+    ("Evening", "Tarde"),
+    ("Morning", "Manhã"),
+    ("no astronomical darkness", "sem escuridão astronômica"),
+    ("ecliptic", "eclíptica"),
+    ("favorable", "favorável"),
+    ("Weather", "Previsão do tempo"),
+    ("No forecast for this night", "Sem previsão para esta noite"),
+    ("clouds", "nuvens"),
+    ("Forecast unavailable", "Previsão indisponível"),
+    ("Satellite passes", "Passagens de satélites"),
+    ("brighter than magnitude", "mais brilhantes que a magnitude"),
+    ("No visible passes this night", "Nenhuma passagem visível esta noite"),
+    ("visible", "visível"),
+    ("max", "máx"),
+    ("Elements unavailable", "Elementos indisponíveis"),
+    // Zenith and meridian
+    ("Zenith", "Zênite"),
+    ("Meridian RA", "AR no meridiano"),
+    ("Zenith and meridian", "Zênite e meridiano"),
+    ("Right ascension and declination straight overhead, equinox of date",
+     "Ascensão reta e declinação logo acima, equinócio da data"),
+    ("Right ascension crossing the meridian, the local sidereal time",
+     "Ascensão reta cruzando o meridiano, o tempo sideral local"),
+    ("Targets within an hour of the meridian and within the altitude constraints, at their \
+highest now. Negative hour angles are still to transit.",
+     "Alvos a menos de uma hora do meridiano e dentro das restrições de altitude, no ponto mais \
+alto agora. Ângulos horários negativos ainda vão transitar."),
+    ("Target", "Alvo"),
+    ("Altitude", "Altitude"),
+    ("Add targets in the targets window first", "Adicione alvos na janela de alvos primeiro"),
+    ("No target near the meridian between", "Nenhum alvo perto do meridiano entre"),
+    ("and", "e"),
+    ("of", "de"),
+    ("targets near the meridian", "alvos perto do meridiano"),
+    // Hour by hour
+    ("Hour by hour", "Hora a hora"),
+    ("The Sun does not set this night", "O Sol não se põe nesta noite"),
+    ("Illum.", "Ilum."),
+    ("Sky", "Céu"),
+    ("LST", "TSL"),
+    ("Dew", "Orvalho"),
+    ("day", "dia"),
+    ("civil", "civil"),
+    ("nautical", "náutico"),
+    ("astronomical", "astronômico"),
+    ("dark", "escuro"),
+    // Dew risk
+    ("Dew risk", "Risco de orvalho"),
+    ("Dew point", "Ponto de orvalho"),
+    ("temperature at sunset", "temperatura no pôr do Sol"),
+    ("humidity", "umidade"),
+    ("clear sky cooling", "resfriamento de céu limpo"),
+    ("rough estimate", "estimativa grosseira"),
+    ("forecast temperature and humidity", "temperatura e umidade da previsão"),
+    ("forecast on the other hours", "previsão nas demais horas"),
+    ("No sunset and sunrise this night", "Sem pôr e nascer do Sol nesta noite"),
+    ("gap", "margem"),
+    ("low", "baixo"),
+    ("moderate", "moderado"),
+    ("high", "alto"),
+    ("frost", "geada"),
+    ("Dew heaters from", "Resistências a partir de"),
+    // Environment
+    ("Environment", "Ambiente"),
+    ("Temperature", "Temperatura"),
+    ("Humidity (%)", "Umidade (%)"),
+    ("Pressure", "Pressão"),
+    ("Air temperature at sunset in", "Temperatura do ar no pôr do Sol em"),
+    ("Air pressure at the observatory in", "Pressão do ar no observatório em"),
+    ("Relative humidity in %", "Umidade relativa em %"),
+    ("not needed", "não necessárias"),
+    ("fair", "razoável"),
+    ("poor", "ruim"),
+    ("Moon up", "Lua acima do horizonte"),
+    ("Darkness", "Escuridão"),
+    ("Astronomical", "Astronômico"),
+    ("Nautical", "Náutico"),
+    ("Durations", "Durações"),
+    ("Night", "Noite"),
+    ("Astronomical darkness", "Escuridão astronômica"),
+    ("Moon free darkness", "Escuridão sem Lua"),
+    ("Civil twilight", "Crepúsculo civil"),
+    ("Nautical twilight", "Crepúsculo náutico"),
+    ("Astronomical twilight", "Crepúsculo astronômico"),
+    ("Civil twilight end", "Fim do crepúsculo civil"),
+    ("Nautical twilight end", "Fim do crepúsculo náutico"),
+    ("Astronomical twilight end", "Fim do crepúsculo astronômico"),
+    ("DSO astronomical start", "Início DSO astronômico"),
+    ("DSO astronomical end", "Fim DSO astronômico"),
+    ("DSO nautical start", "Início DSO náutico"),
+    ("NB astronomical start", "Início NB astronômico"),
+    ("NB nautical start", "Início NB náutico"),
+    // Observatory setup
+    ("Observatory setup", "Configuração do observatório"),
+    ("Name", "Nome"),
+    ("Elevation (m)", "Altitude (m)"),
+    ("Elevation (ft)", "Altitude (pés)"),
+    ("Latitude", "Latitude"),
+    ("Longitude", "Longitude"),
+    ("TZ", "Fuso"),
+    ("Lookup", "Buscar"),
+    ("Elevation at the latitude and longitude from the SRTM tiles set in preferences",
+     "Altitude na latitude e longitude pelos blocos SRTM definidos nas preferências"),
+    ("Find site", "Procurar local"),
+    ("Type part of a city or observatory name", "Digite parte do nome de uma cidade ou observatório"),
+    ("Zones", "Fusos"),
+    ("Set the timezone of the library sites saved with a UTC offset only, guessed from the nearest city",
+     "Define o fuso dos locais da biblioteca salvos só com a diferença para o UTC, estimado pela cidade mais próxima"),
+    ("Set a sky brightness grid in preferences", "Defina uma grade de brilho do céu nas preferências"),
+    ("Pull", "Ler"),
+    ("Fill the fields with the site set in the mount driver", "Preenche os campos com o local definido no driver da montagem"),
+    ("Push", "Enviar"),
+    ("Send the site in the fields and the clock to the mount driver",
+     "Envia o local dos campos e o relógio para o driver da montagem"),
+    ("Set the SRTM elevation tiles directory in preferences",
+     "Defina o diretório de blocos de altitude SRTM nas preferências"),
+    ("Set the mount driver in preferences", "Defina o driver da montagem nas preferências"),
+    ("Site sent to", "Local enviado para"),
+    ("Site definitions", "Definições de locais"),
+    ("Unable to save the observatory library", "Não foi possível salvar a biblioteca de observatórios"),
+    ("Sites read from the file of", "Locais lidos do arquivo do"),
+    ("New", "Novos"),
+    ("Updated", "Atualizados"),
+    ("Every library site has a timezone or none is known near it",
+     "Todos os locais da biblioteca têm fuso ou nenhum é conhecido perto deles"),
+    ("Set these timezones, the offsets are the standard time of each zone?",
+     "Definir estes fusos? As diferenças são a hora padrão de cada fuso"),
+    ("Cancel", "Cancelar"),
+    ("Update", "Atualizar"),
+    // Constraint setup
+    ("Constraint setup", "Configuração de restrições"),
+    ("Enter a whole number from", "Digite um número inteiro de"),
+    ("Min altitude (°)", "Altitude mín. (°)"),
+    ("Max altitude (°)", "Altitude máx. (°)"),
+    ("Min size (')", "Tamanho mín. (')"),
+    ("Max size (')", "Tamanho máx. (')"),
+    ("Moon separation (°)", "Separação da Lua (°)"),
+    ("Observable time (%)", "Tempo observável (%)"),
+    ("Share of the observation window the target meets the constraints",
+     "Parte da janela de observação em que o alvo atende às restrições"),
+    ("Max targets", "Máx. de alvos"),
+    ("Darkness only", "Só na escuridão"),
+    ("Observe in astronomical darkness instead of from sunset to sunrise",
+     "Observar na escuridão astronômica em vez do pôr ao nascer do Sol"),
+    // Targets
+    ("Targets", "Alvos"),
+    ("Rising", "Subindo"),
+    ("Near transit", "Perto do trânsito"),
+    ("Setting", "Descendo"),
+    ("Below horizon", "Abaixo do horizonte"),
+    ("State", "Estado"),
+    ("RA", "AR"),
+    ("Dec", "Dec"),
+    ("HA", "AH"),
+    ("Obs", "Obs"),
+    ("Avoid", "Evitar"),
+    ("Select the targets to export, Ctrl+click selects several.",
+     "Selecione os alvos a exportar, Ctrl+clique seleciona vários."),
+    ("Observation start:", "Início obs.:"),
+    ("local", "local"),
+    ("Obs is the part of the observation window within the altitude constraints,\n\
+double click a target to edit it, Ctrl+click selects several for the observing lists",
+     "Obs é a parte da janela de observação dentro das restrições de altitude,\n\
+clique duplo edita um alvo, Ctrl+clique seleciona vários para as listas de observação"),
+    ("Add", "Incluir"),
+    ("Edit", "Editar"),
+    ("Remove", "Remover"),
+    ("Up tonight", "Esta noite"),
+    ("Export the targets within the constraints tonight to skycalc_up_tonight.csv",
+     "Exporta os alvos dentro das restrições esta noite para skycalc_up_tonight.csv"),
+    ("Export the selected targets as a SkySafari observing list to skycalc.skylist",
+     "Exporta os alvos selecionados como lista de observação do SkySafari para skycalc.skylist"),
+    ("Export the selected targets for the AstroPlanner text import to skycalc_astroplanner.txt",
+     "Exporta os alvos selecionados para a importação de texto do AstroPlanner para skycalc_astroplanner.txt"),
+    ("Details", "Detalhes"),
+    ("Darkness with the selected target within the altitude constraints month by month",
+     "Escuridão com o alvo selecionado dentro das restrições de altitude mês a mês"),
+    ("Sky chart", "Mapa do céu"),
+    ("Paths of the targets and the Moon avoidance zone during the observation window",
+     "Trajetórias dos alvos e zona de exclusão da Lua durante a janela de observação"),
+    ("Script errors", "Erros de scripts"),
+    ("Remove from the target list:", "Remover da lista de alvos:"),
+    ("Moon avoidance", "Exclusão da Lua"),
+    ("Paths of the targets during the observation window, the ones entering the Moon avoidance zone in red",
+     "Trajetórias dos alvos durante a janela de observação, em vermelho as que entram na zona de exclusão da Lua"),
+    // Countdowns
+    ("Countdowns", "Contagens regressivas"),
+    ("Compact", "Compacto"),
+    ("Hide the window border and buttons, double click the events to show them again, ESC closes the window",
+     "Esconde a borda e os botões da janela, clique duplo nos eventos os mostra de novo, ESC fecha a janela"),
+    ("Civil twilight start", "Início do crepúsculo civil"),
+    ("Nautical twilight start", "Início do crepúsculo náutico"),
+    ("Astronomical twilight start", "Início do crepúsculo astronômico"),
+    // Sky status
+    ("Sky status", "Estado do céu"),
+    ("Eq. of time", "Eq. do tempo"),
+    ("Apparent minus mean solar time, add it to mean solar time to read a sundial",
+     "Tempo solar aparente menos o médio, some ao tempo solar médio para ler um relógio de sol"),
+    ("Sundial", "Relógio de sol"),
+    ("Local apparent solar time, in parentheses the sundial minus clock difference",
+     "Tempo solar aparente local, entre parênteses a diferença relógio de sol menos relógio"),
+    ("Hour angle", "Ângulo horário"),
+    // Alignment stars
+    ("Alignment stars", "Estrelas de alinhamento"),
+    ("Nautical dusk:", "Crepúsculo náutico:"),
+    ("Brightest stars spread across azimuths, altitude", "Estrelas mais brilhantes espalhadas em azimute, altitude"),
+    ("Star", "Estrela"),
+    ("Mag", "Mag"),
+    ("Alt", "Alt"),
+    ("Az", "Az"),
+    ("No nautical dusk this night", "Sem crepúsculo náutico nesta noite"),
+    ("Export the alignment stars to skycalc_alignment.csv",
+     "Exporta as estrelas de alinhamento para skycalc_alignment.csv"),
+    // Solar system
+    ("Solar system", "Sistema solar"),
+    ("Times in local time, rise, transit and set from local noon to the next local noon, max alt at the transit, \
+elongation east or west of the Sun",
+     "Horários locais, nascer, trânsito e ocaso do meio-dia local ao seguinte, alt máx no trânsito, elongação a \
+leste ou oeste do Sol"),
+    ("Body", "Corpo"),
+    ("Max alt", "Alt máx"),
+    ("Size", "Tamanho"),
+    ("Elong", "Elong"),
+    ("Positions at", "Posições em"),
+    ("equation of time", "equação do tempo"),
+    ("Rise, transit and set", "Nascer, trânsito e ocaso"),
+    ("rise", "nascer"),
+    ("transit", "trânsito"),
+    ("set", "ocaso"),
+    ("Oppositions and greatest elongations from", "Oposições e máximas elongações de"),
+    ("No oppositions or greatest elongations in this range", "Nenhuma oposição ou máxima elongação neste período"),
+    ("opposition", "oposição"),
+    ("greatest elongation east", "máxima elongação leste"),
+    ("greatest elongation west", "máxima elongação oeste"),
+    ("Mercury", "Mercúrio"),
+    ("Venus", "Vênus"),
+    ("Mars", "Marte"),
+    ("Jupiter", "Júpiter"),
+    ("Saturn", "Saturno"),
+    ("Uranus", "Urano"),
+    ("Neptune", "Netuno"),
+    ("Selected time only", "Só a hora selecionada"),
+    ("Every", "A cada"),
+    ("Positions at the selected time or from sunset to sunrise at an interval",
+     "Posições na hora selecionada ou do pôr ao nascer do Sol em intervalos"),
+    ("Oppositions and elongations", "Oposições e elongações"),
+    ("Next", "Próximos"),
+    ("months", "meses"),
+    ("Search oppositions of Mars, Jupiter and Saturn and greatest elongations of Mercury and Venus from the \
+selected time",
+     "Busca oposições de Marte, Júpiter e Saturno e máximas elongações de Mercúrio e Vênus a partir da hora \
+selecionada"),
+    ("Text", "Texto"),
+    ("Export the ephemeris to skycalc_solar_system.txt", "Exporta as efemérides para skycalc_solar_system.txt"),
+    ("Export the ephemeris to skycalc_solar_system.csv", "Exporta as efemérides para skycalc_solar_system.csv"),
+    ("Events", "Eventos"),
+    ("Export the planet events of the range to skycalc_planet_events.txt",
+     "Exporta os eventos planetários do período para skycalc_planet_events.txt"),
+    // Eclipses, target plan and alignment report sections
+    ("Eclipses", "Eclipses"),
+    ("WARNING", "ATENÇÃO"),
+    ("Penumbral lunar eclipse", "Eclipse lunar penumbral"),
+    ("Partial lunar eclipse", "Eclipse lunar parcial"),
+    ("Total lunar eclipse", "Eclipse lunar total"),
+    ("Partial solar eclipse", "Eclipse solar parcial"),
+    ("Annular solar eclipse", "Eclipse solar anular"),
+    ("Total solar eclipse", "Eclipse solar total"),
+    ("Hybrid solar eclipse", "Eclipse solar híbrido"),
+    ("magnitude", "magnitude"),
+    ("local magnitude", "magnitude local"),
+    ("Moon below horizon", "Lua abaixo do horizonte"),
+    ("Max", "Máx"),
+    ("Start", "Início"),
+    ("End", "Fim"),
+    ("Target plan", "Plano de alvos"),
+    ("Available", "Disponível"),
+    ("Allocated", "Alocado"),
+    ("Idle", "Livre"),
+    ("Idle gap", "Intervalo livre"),
+    ("not within the constraints", "fora das restrições"),
+    ("Alignment stars at nautical dusk", "Estrelas de alinhamento no crepúsculo náutico"),
+    ("mag", "mag"),
+    ("alt", "alt"),
+    ("az", "az"),
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_translate() {
+        assert_eq!(translate(Language::English, "Sunset"), "Sunset");
+        assert_eq!(translate(Language::Portuguese, "Sunset"), "Pôr do Sol");
+        // missing from the catalog
+        assert_eq!(translate(Language::Portuguese, "Meridian flip"), "Meridian flip");
+        // month names, their first three letters are the usual abbreviations
+        assert_eq!(translate(Language::Portuguese, MONTHS[1]), "Fevereiro");
+        let abbreviations: Vec<String> =
+            MONTHS.iter().map(|m| translate(Language::Portuguese, m).chars().take(3).collect()).collect();
+        assert_eq!(abbreviations[4..8], ["Mai", "Jun", "Jul", "Ago"]);
+
+        // shortcut letters follow the segment, the tab before the shortcut text is kept
+        assert_eq!(translate_path(Language::Portuguese, "File/Configuration/&Load\t"),
+                   "Arquivo/Configuração/&Carregar\t");
+        assert_eq!(translate_path(Language::Portuguese, "&File/&Preferences\t"), "&Arquivo/&Preferências\t");
+        assert_eq!(translate_path(Language::English, "&File/E&xit\t"), "&File/E&xit\t");
+
+        for language in LANGUAGES {
+            let catalog = language.catalog();
+            for (i, (english, translated)) in catalog.iter().enumerate() {
+                assert!(!translated.is_empty(), "{}", english);
+                assert_eq!(english.matches('&').count(), translated.matches('&').count(), "{}", english);
+                assert!(catalog[..i].iter().all(|(other, _)| other != english), "{} twice", english);
+            }
+        }
+    }
+
+    #[test]
+    fn test_functions_menu_shortcuts() {
+        // entries of the Functions menu, each with its own shortcut letter in every language
+        const FUNCTIONS: [&str; 18] = [
+            "&Observatory", "&Constraints", "E&nvironment", "&Equipment", "Mo&saic", "&Darkness",
+            "Altitude cha&rt", "&Moon separation", "Darkness ca&lendar", "Mont&hly almanac", "Com&pare sites",
+            "Night &quality trends", "Sky s&tatus", "&Zenith and meridian", "Countdo&wns", "T&argets",
+            "Al&ignment stars", "Solar s&ystem",
+        ];
+        for language in LANGUAGES {
+            let mut letters: Vec<char> = Vec::new();
+            for entry in FUNCTIONS {
+                let label = translate_path(language, entry);
+                let letter = label.split('&').nth(1).and_then(|rest| rest.chars().next()).unwrap();
+                let letter = letter.to_lowercase().next().unwrap();
+                assert!(!letters.contains(&letter), "{:?} '{}' of {} twice", language, letter, label);
+                letters.push(letter);
+            }
+        }
+    }
+}
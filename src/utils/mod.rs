@@ -2,4 +2,9 @@
 
 pub mod angle;
 pub mod definers;
+pub mod dpi;
+pub mod i18n;
+pub mod png;
 pub mod utils;
+#[cfg(test)]
+pub mod fuzz;
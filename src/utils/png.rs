@@ -0,0 +1,81 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2024 Ricardo Cernic
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to
+// deal in the Software without restriction, including without limitation the
+// rights to use, copy, modify, merge, publish, distribute, sublicense, and/or
+// sell copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS
+// IN THE SOFTWARE.
+
+// PNG writer for the chart exports, 8 bit RGB pixels with the export stamp as text, encoded by
+// the png crate.
+
+use png::{BitDepth, ColorType, Encoder};
+
+/// PNG file of an 8 bit RGB image, `rgb` holding the `width` x `height` pixels row by row from
+/// the top. Each of `text` is written as a tEXt chunk, e.g. ("Comment", export stamp).
+pub fn encode_png(width: u32, height: u32, rgb: &[u8], text: &[(&str, &str)]) -> Result<Vec<u8>, String> {
+    if width == 0 || height == 0 || rgb.len() != width as usize * 3 * height as usize {
+        return Err(format!("{} bytes do not hold a {} x {} RGB image", rgb.len(), width, height));
+    }
+
+    let mut png = Vec::new();
+    let mut encoder = Encoder::new(&mut png, width, height);
+    encoder.set_color(ColorType::Rgb);
+    encoder.set_depth(BitDepth::Eight);
+    for (keyword, value) in text {
+        // Latin-1 text, other characters are replaced
+        let value: String = value.chars().map(|c| if (c as u32) < 256 { c } else { '?' }).collect();
+        encoder.add_text_chunk(keyword.to_string(), value).map_err(|e| e.to_string())?;
+    }
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(rgb).map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(png)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use png::Decoder;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_encode_png() {
+        let rgb = [255, 0, 0, 0, 0, 255];
+        let png = encode_png(2, 1, &rgb, &[("Comment", "SkyCalc 2025-01-20 → Paris")]).unwrap();
+        assert_eq!(png[..8], [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a]);
+
+        // the pixels and the text read back
+        let mut reader = Decoder::new(Cursor::new(png)).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        let frame = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((frame.width, frame.height), (2, 1));
+        assert_eq!((frame.color_type, frame.bit_depth), (ColorType::Rgb, BitDepth::Eight));
+        assert_eq!(pixels[..frame.buffer_size()], rgb);
+        let text = &reader.info().uncompressed_latin1_text;
+        assert_eq!(text.len(), 1);
+        assert_eq!((text[0].keyword.as_str(), text[0].text.as_str()), ("Comment", "SkyCalc 2025-01-20 ? Paris"));
+
+        // larger images compress
+        let large = vec![128; 300 * 100 * 3];
+        let png = encode_png(300, 100, &large, &[]).unwrap();
+        assert!(png.len() < large.len());
+
+        assert!(encode_png(2, 2, &rgb, &[]).is_err());
+        assert!(encode_png(0, 1, &[], &[]).is_err());
+    }
+}
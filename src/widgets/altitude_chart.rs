@@ -0,0 +1,112 @@
+use fltk::draw;
+use fltk::draw::LineStyle;
+use fltk::enums::{Align, Color, ColorDepth, Font};
+use fltk::prelude::{ImageExt, SurfaceDevice};
+use fltk::surface::ImageSurface;
+use crate::application::timeline::{above_horizon, hour_label, AltitudeChart};
+use crate::utils::png::encode_png;
+
+// Margins of the plot in pixels, the title above and the hour labels and legend below
+const LEFT: i32 = 40;
+const TOP: i32 = 36;
+const RIGHT: i32 = 16;
+const LEGEND_ROW: i32 = 18;
+// Curves per legend row
+const LEGEND_COLUMNS: usize = 4;
+
+fn rgb_color((r, g, b): (u8, u8, u8)) -> Color {
+    Color::from_rgb(r, g, b)
+}
+
+/// Height below the plot taken by the hour labels and the legend of the chart
+pub fn legend_height(chart: &AltitudeChart) -> i32 {
+    24 + chart.curves().len().div_ceil(LEGEND_COLUMNS) as i32 * LEGEND_ROW
+}
+
+/// Draws the altitude chart in the area, the same layout as the SVG export. `color` maps the
+/// colors, night vision on screen and unchanged for the PNG export.
+pub fn draw_altitude_chart(x: i32, y: i32, w: i32, h: i32, chart: &AltitudeChart, title: &str,
+                           solar_midnight: bool, color: fn(Color) -> Color) {
+    let curves = chart.curves();
+    let plot_width = (w - LEFT - RIGHT).max(1);
+    let plot_height = (h - TOP - legend_height(chart)).max(1);
+    let (plot_x, plot_y) = (x + LEFT, y + TOP);
+    let steps = chart.timeline.sky.len().max(1) as i32;
+    let column_x = |i: i32| plot_x + i * plot_width / steps;
+    let point_x = |i: usize| plot_x as f64 + (i as f64 + 0.5) * plot_width as f64 / steps as f64;
+    let point_y = |altitude: f64| plot_y as f64 + plot_height as f64 * (1.0 - altitude.clamp(0.0, 90.0) / 90.0);
+    let text_color = color(Color::from_rgb(0xc0, 0x30, 0x30));
+
+    draw::draw_rect_fill(x, y, w, h, Color::Black);
+    draw::set_font(Font::Helvetica, 14);
+    draw::set_draw_color(color(Color::from_rgb(0xff, 0x40, 0x40)));
+    draw::draw_text2(title, plot_x, y + 6, plot_width, 20, Align::Left | Align::Inside);
+
+    // twilight bands
+    for (i, sky) in chart.timeline.sky.iter().enumerate() {
+        let (left, right) = (column_x(i as i32), column_x(i as i32 + 1));
+        draw::draw_rect_fill(left, plot_y, right - left, plot_height, color(rgb_color(sky.rgb())));
+    }
+
+    // altitude grid every 15 degrees and hour ticks every 2 hours
+    draw::set_font(Font::Helvetica, 11);
+    for altitude in (0..=90).step_by(15) {
+        let line_y = point_y(altitude as f64).round() as i32;
+        draw::set_draw_color(color(Color::from_rgb(0x55, 0x11, 0x11)));
+        draw::draw_line(plot_x, line_y, plot_x + plot_width, line_y);
+        draw::set_draw_color(text_color);
+        draw::draw_text2(&format!("{}°", altitude), x, line_y - 8, LEFT - 4, 16, Align::Right | Align::Inside);
+    }
+    for hour in (0..=24).step_by(2) {
+        let tick_x = plot_x + hour as i32 * plot_width / 24;
+        draw::set_draw_color(color(Color::from_rgb(0x55, 0x11, 0x11)));
+        draw::draw_line(tick_x, plot_y, tick_x, plot_y + plot_height);
+        draw::set_draw_color(text_color);
+        draw::draw_text2(&hour_label(hour, solar_midnight), tick_x - 12, plot_y + plot_height + 2, 24, 16,
+                         Align::Center);
+    }
+
+    // curves above the horizon
+    for &(_, altitudes, rgb, dashed) in &curves {
+        draw::set_draw_color(color(rgb_color(rgb)));
+        draw::set_line_style(if dashed { LineStyle::Dash } else { LineStyle::Solid }, 2);
+        for run in above_horizon(altitudes) {
+            draw::begin_line();
+            for i in run {
+                draw::vertex(point_x(i), point_y(altitudes[i]));
+            }
+            draw::end_line();
+        }
+    }
+
+    // legend
+    let legend_y = plot_y + plot_height + 24;
+    for (i, &(name, _, rgb, dashed)) in curves.iter().enumerate() {
+        let item_x = plot_x + (i % LEGEND_COLUMNS) as i32 * plot_width / LEGEND_COLUMNS as i32;
+        let item_y = legend_y + (i / LEGEND_COLUMNS) as i32 * LEGEND_ROW;
+        draw::set_draw_color(color(rgb_color(rgb)));
+        draw::set_line_style(if dashed { LineStyle::Dash } else { LineStyle::Solid }, 2);
+        draw::draw_line(item_x, item_y + LEGEND_ROW / 2, item_x + 20, item_y + LEGEND_ROW / 2);
+        draw::set_draw_color(text_color);
+        draw::draw_text2(name, item_x + 26, item_y, plot_width / LEGEND_COLUMNS as i32 - 30, LEGEND_ROW,
+                         Align::Left | Align::Inside | Align::Clip);
+    }
+    draw::set_line_style(LineStyle::Solid, 0);
+}
+
+/// PNG file of the altitude chart drawn offscreen, `stamp` is kept as the PNG comment
+pub fn altitude_chart_png(chart: &AltitudeChart, title: &str, solar_midnight: bool, width: i32,
+                          stamp: &str) -> Result<Vec<u8>, String> {
+    let height = width / 2 + legend_height(chart);
+    let surface = ImageSurface::new(width, height, false);
+    ImageSurface::push_current(&surface);
+    draw_altitude_chart(0, 0, width, height, chart, title, solar_midnight, |c| c);
+    let image = surface.image();
+    ImageSurface::pop_current();
+
+    let image = image.ok_or_else(|| "Unable to draw the chart".to_string())?;
+    if image.depth() != ColorDepth::Rgb8 {
+        return Err(format!("Unexpected {:?} image of the chart", image.depth()));
+    }
+    encode_png(image.data_w() as u32, image.data_h() as u32, &image.to_rgb_data(), &[("Comment", stamp)])
+}
@@ -1,4 +1,7 @@
-use crate::application::observer::degrees_from_str;
+use crate::application::observer::{degrees_from_str, try_degrees_from_str};
+use crate::utils::angle::format_dms;
+use crate::utils::definers::TOOLTIP_ANGLE_INPUT;
+use crate::widgets::validated::ValidatedInput;
 use fltk::input::Input;
 use fltk::prelude::*;
 use std::ops::{Deref, DerefMut};
@@ -7,6 +10,8 @@ pub struct AngleInput {
     pub angle_input: Input,
     pub min: f64,
     pub max: f64,
+    // Shown as D° M' S" with the hemisphere letter, Some(true) for latitudes
+    pub dms: Option<bool>,
 }
 
 impl Deref for AngleInput {
@@ -22,20 +27,56 @@ impl DerefMut for AngleInput {
     }
 }
 
+impl ValidatedInput for AngleInput {
+    type Value = f64;
+
+    fn parse(&self) -> Result<f64, String> {
+        try_degrees_from_str(&self.angle_input.value(), self.min, self.max)
+    }
+
+    fn hint(&self) -> &str {
+        TOOLTIP_ANGLE_INPUT
+    }
+
+    fn input(&mut self) -> &mut Input {
+        &mut self.angle_input
+    }
+
+    fn normalize(&mut self, angle: &f64) {
+        self.set_angle(*angle);
+    }
+}
+
 impl AngleInput {
     pub fn new(x: i32, y: i32, w: i32, h: i32, label: &str, min: f64, max: f64) -> AngleInput {
         let mut input = Input::new(x, y, w, h, label);
-        input.set_maximum_size(14); // max size YYYY-MM-DD
+        input.set_maximum_size(20); // max size DDD° MM' SS.S" W
         input.set_value("0.000000"); // set initial value
-        AngleInput { angle_input: input, min, max }
+        input.set_tooltip(TOOLTIP_ANGLE_INPUT);
+        AngleInput { angle_input: input, min, max, dms: None }
+    }
+
+    // Shows the angle in degrees, minutes and seconds with N/S or E/W
+    pub fn with_dms(mut self, is_latitude: bool) -> AngleInput {
+        self.dms = Some(is_latitude);
+        self
+    }
+
+    pub fn set_angle(&mut self, angle: f64) {
+        match self.dms {
+            Some(is_latitude) => self.angle_input.set_value(&format_dms(angle, is_latitude)),
+            None => self.angle_input.set_value(&format!("{:.6}", angle)),
+        }
     }
 
     pub fn get_angle(&mut self) -> f64 {
         degrees_from_str(&self.angle_input.value(), self.min, self.max)
     }
 
-    pub fn validate(&mut self) {
-        let angle = degrees_from_str(&self.angle_input.value(), self.min, self.max);
-        self.angle_input.set_value(&format!("{:.6}", &angle));
+    // Checks the text and marks the input, true when it is an angle from min to max. A valid angle
+    // is written again in the format of the input, empty, invalid or out of range text is kept and
+    // flagged with the error as tooltip.
+    pub fn validate(&mut self) -> bool {
+        self.check().is_some()
     }
 }
@@ -1,7 +1,9 @@
 use std::ops::{Deref, DerefMut};
 use fltk::input::Input;
 use fltk::prelude::*;
-use crate::application::time::{from_str_or_now};
+use crate::application::time::{from_str_or_now, parse_time, Time};
+use crate::utils::definers::TOOLTIP_DATE_INPUT;
+use crate::widgets::validated::ValidatedInput;
 
 #[derive(Clone)]
 pub struct DateInput {
@@ -21,17 +23,41 @@ impl DerefMut for DateInput {
     }
 }
 
+impl ValidatedInput for DateInput {
+    type Value = Time;
+
+    fn parse(&self) -> Result<Time, String> {
+        let value = self.date_input.value();
+        parse_time(&value).ok_or_else(|| format!("'{}' is not a date", value.trim()))
+    }
+
+    fn hint(&self) -> &str {
+        TOOLTIP_DATE_INPUT
+    }
+
+    fn input(&mut self) -> &mut Input {
+        &mut self.date_input
+    }
+
+    fn normalize(&mut self, time: &Time) {
+        self.date_input.set_value(&time.to_string(Some("yyyymmdd")));
+    }
+}
+
 impl DateInput {
     pub fn new(x: i32, y: i32, w: i32, h: i32, label: &str) -> DateInput {
         let mut input = Input::new(x, y, w, h, label);
         input.set_maximum_size(10); // max size YYYY-MM-DD
         input.set_value(""); // set initial value
+        input.set_tooltip(TOOLTIP_DATE_INPUT);
         DateInput { date_input: input }
     }
 
-    pub fn validate(&mut self) {
-        let date_str = from_str_or_now(&self.date_input.value()).to_string(Some("yyyymmdd"));
-        self.date_input.set_value(&date_str);
+    // Checks the text and marks the input, true when it is a date or empty. A valid date is written
+    // again as YYYY-MM-DD without the time, empty text becomes today's UTC date and invalid text is
+    // kept and flagged with the error as tooltip.
+    pub fn validate(&mut self) -> bool {
+        self.check().is_some()
     }
 
     pub fn get_day(&self) -> u64 {
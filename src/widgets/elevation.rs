@@ -0,0 +1,70 @@
+use std::ops::{Deref, DerefMut};
+use fltk::input::Input;
+use fltk::prelude::*;
+use crate::application::units::ElevationUnit;
+use crate::utils::definers::{TOOLTIP_ELEVATION_FEET_INPUT, TOOLTIP_ELEVATION_INPUT};
+use crate::widgets::validated::ValidatedInput;
+
+#[derive(Clone)]
+pub struct ElevationInput {
+    pub elevation_input: Input,
+    pub unit: ElevationUnit,
+}
+
+impl Deref for ElevationInput {
+    type Target = Input;
+    fn deref(&self) -> &Self::Target {
+        &self.elevation_input
+    }
+}
+
+impl DerefMut for ElevationInput {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.elevation_input
+    }
+}
+
+impl ValidatedInput for ElevationInput {
+    type Value = i64;
+
+    fn parse(&self) -> Result<i64, String> {
+        self.unit.parse(&self.elevation_input.value())
+    }
+
+    fn hint(&self) -> &str {
+        match self.unit {
+            ElevationUnit::Meters => TOOLTIP_ELEVATION_INPUT,
+            ElevationUnit::Feet => TOOLTIP_ELEVATION_FEET_INPUT,
+        }
+    }
+
+    fn input(&mut self) -> &mut Input {
+        &mut self.elevation_input
+    }
+}
+
+impl ElevationInput {
+    pub fn new(x: i32, y: i32, w: i32, h: i32, label: &str) -> ElevationInput {
+        let mut input = Input::new(x, y, w, h, label);
+        input.set_maximum_size(4); // max size 9000
+        input.set_tooltip(TOOLTIP_ELEVATION_INPUT);
+        ElevationInput { elevation_input: input, unit: ElevationUnit::Meters }
+    }
+
+    // Elevation typed and shown in feet, the value stays in meters
+    pub fn with_unit(mut self, unit: ElevationUnit) -> ElevationInput {
+        self.unit = unit;
+        if unit == ElevationUnit::Feet {
+            self.elevation_input.set_maximum_size(5); // max size 29528
+        }
+        let hint = self.hint().to_string();
+        self.elevation_input.set_tooltip(&hint);
+        self
+    }
+
+    // Shows an elevation in meters in the unit of the input
+    pub fn set_elevation(&mut self, elevation: i64) {
+        let text = self.unit.format(elevation);
+        self.elevation_input.set_value(&text);
+    }
+}
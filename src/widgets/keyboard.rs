@@ -0,0 +1,50 @@
+// src/widgets/keyboard.rs
+use fltk::app;
+use fltk::enums::{Event, Key};
+use fltk::input::Input;
+use fltk::prelude::*;
+use fltk::window::Window;
+
+// Dialog windows close with the window close button or Esc. The main window keeps its own
+// callback, Esc must not end the program.
+pub fn close_on_escape(window: &mut Window) {
+    window.set_callback(|w| {
+        if app::event() == Event::Close || app::event_key() == Key::Escape {
+            w.hide();
+        }
+    });
+}
+
+// Moves the focus to the next widget of the same group that takes it, in creation order like Tab,
+// back to the first one after the last. The focus moves once the current event is handled, so
+// the Unfocus handler of the widget does not run inside its own KeyDown handler.
+pub fn focus_next<W: WidgetExt + Clone + 'static>(widget: &W) {
+    let widget = widget.clone();
+    app::add_timeout3(0.0, move |_| move_focus(&widget));
+}
+
+fn move_focus<W: WidgetExt>(widget: &W) {
+    let Some(group) = widget.parent() else {
+        return;
+    };
+    let count = group.children();
+    let index = group.find(widget);
+    for step in 1..count {
+        if let Some(mut next) = group.child((index + step) % count) {
+            if next.visible() && next.active() && next.has_visible_focus() && next.take_focus().is_ok() {
+                return;
+            }
+        }
+    }
+}
+
+// Enter in an input without validation moves the focus on
+pub fn enter_moves_focus(input: &mut Input) {
+    input.handle(|i, ev| match ev {
+        Event::KeyDown if app::event_key() == Key::Enter => {
+            focus_next(i);
+            true
+        }
+        _ => false,
+    });
+}
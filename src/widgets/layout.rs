@@ -0,0 +1,118 @@
+// src/widgets/layout.rs
+use fltk::app;
+use fltk::enums::{Align, FrameType};
+use fltk::frame::Frame;
+use fltk::group::Flex;
+use fltk::prelude::{GroupExt, WidgetBase, WidgetExt};
+use fltk::window::Window;
+use crate::utils::dpi::font_scaled;
+use crate::widgets::label::Label;
+
+// Sizes of the dialog rows at the base font size, in unscaled pixels. FLTK applies the screen
+// scale factor, they only follow the font size.
+pub const MARGIN: i32 = 10;
+pub const SPACING: i32 = 5;
+pub const LABEL_HEIGHT: i32 = 20;
+pub const INPUT_HEIGHT: i32 = 25;
+pub const BUTTON_HEIGHT: i32 = 30;
+pub const BUTTON_WIDTH: i32 = 50;
+
+/// Size designed at the base font size, for the current font size
+pub fn scaled(size: i32) -> i32 {
+    font_scaled(size, app::font_size())
+}
+
+/// Label for a layout row, the row sets its position and size
+pub fn label(text: &str) -> Label {
+    Label::new(0, 0, 0, 0, text, Align::Left | Align::Inside)
+}
+
+/// Label keeping its width in a row, for the titles in front of values
+pub fn fixed_label(row: &mut Flex, text: &str, width: i32) -> Label {
+    let label = label(text);
+    row.fixed(&*label, scaled(width));
+    label
+}
+
+/// Lays a row out again after widgets in it were shown or hidden, hidden widgets take no space
+pub fn relayout(row: &mut Flex) {
+    let (x, y, w, h) = (row.x(), row.y(), row.w(), row.h());
+    row.resize(x, y, w, h);
+}
+
+/// Empty space taking the width left in a row
+pub fn spacer() -> Frame {
+    Frame::default()
+}
+
+/// Dialog content laid out in rows from top to bottom.
+///
+/// Rows have a height given at the base font size and share their width between the widgets
+/// created in them, widgets given a fixed width keep it. Widgets added to the column directly
+/// take the height left, so tables and lists grow with the window. `end` sizes the window to the
+/// rows and lets it be resized larger.
+pub struct FormLayout {
+    column: Flex,
+    height: i32,
+}
+
+impl FormLayout {
+    /// Starts the column in the window, it must be the current group
+    pub fn new(window: &Window) -> FormLayout {
+        let mut column = Flex::new(0, 0, window.w(), window.h(), "").column();
+        column.set_margin(scaled(MARGIN));
+        column.set_pad(scaled(SPACING));
+        FormLayout { column, height: 2 * scaled(MARGIN) - scaled(SPACING) }
+    }
+
+    /// Adds a row, the widgets created next go into it until `end` is called on it
+    pub fn row(&mut self, height: i32) -> Flex {
+        let mut row = Flex::default().row();
+        row.set_pad(scaled(SPACING));
+        self.fixed(&row, height);
+        row
+    }
+
+    /// Row with a label for each text, sharing the width evenly
+    pub fn labels<const N: usize>(&mut self, texts: [&str; N]) -> [Label; N] {
+        let row = self.row(LABEL_HEIGHT);
+        let labels = texts.map(label);
+        row.end();
+        labels
+    }
+
+    /// Horizontal line between sections
+    pub fn divider(&mut self) {
+        let mut line = Frame::default();
+        line.set_frame(FrameType::BorderBox);
+        self.column.fixed(&line, 1);
+        self.height += 1 + scaled(SPACING);
+    }
+
+    /// Keeps a widget of the column at the height given at the base font size
+    pub fn fixed<W: WidgetExt>(&mut self, widget: &W, height: i32) {
+        self.column.fixed(widget, scaled(height));
+        self.height += scaled(height) + scaled(SPACING);
+    }
+
+    /// Widget of the column taking the height left, the window opens with at least `min_height` for it
+    pub fn stretch(&mut self, min_height: i32) {
+        self.height += scaled(min_height) + scaled(SPACING);
+    }
+
+    /// Ends the column and sizes the window to fit the rows, centered on the screen. The window
+    /// can then be made larger but not smaller.
+    pub fn end(mut self, window: &mut Window) {
+        self.column.end();
+        let (width, height) = (window.w(), self.height);
+        let (screen_width, screen_height) = app::screen_size();
+        window.resizable(&self.column);
+        window.resize(
+            (screen_width as i32 - width) / 2,
+            (screen_height as i32 - height) / 2,
+            width,
+            height,
+        );
+        window.size_range(width, height, 0, 0);
+    }
+}
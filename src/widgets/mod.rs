@@ -1,4 +1,17 @@
 // src/widgets/mod.rs
 pub mod date;
 pub mod angle;
-pub mod label;
\ No newline at end of file
+pub mod label;
+pub mod timezone;
+pub mod elevation;
+pub mod radec;
+pub mod validated;
+pub mod scale;
+pub mod status_bar;
+pub mod timeline;
+pub mod altitude_chart;
+pub mod sky_chart;
+pub mod keyboard;
+pub mod night_vision;
+pub mod layout;
+pub mod theme;
@@ -0,0 +1,80 @@
+// src/widgets/night_vision.rs
+use std::cell::RefCell;
+use fltk::app;
+use fltk::enums::Color;
+use fltk::menu::MenuBar;
+use fltk::prelude::MenuExt;
+use crate::utils::i18n::menu_path;
+
+pub const NIGHT_VISION_MENU: &str = "&View/&Themes/&Night vision";
+
+// Brightness kept from the luminance of each color, low enough to keep the eyes dark adapted
+const NIGHT_VISION_BRIGHTNESS: f64 = 0.55;
+
+thread_local! {
+    // Palette in use before night vision, restored when it is turned off. None while it is off.
+    static SAVED_PALETTE: RefCell<Option<Vec<(u8, u8, u8)>>> = RefCell::new(None);
+}
+
+// Dimmed red with the luminance of the color, so contrasts between widgets are kept
+pub fn night_vision_rgb(r: u8, g: u8, b: u8) -> (u8, u8, u8) {
+    let luminance = 0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64;
+    ((luminance * NIGHT_VISION_BRIGHTNESS).round() as u8, 0, 0)
+}
+
+pub fn night_vision_active() -> bool {
+    SAVED_PALETTE.with(|saved| saved.borrow().is_some())
+}
+
+// Turns the all red palette on or off. The 256 palette entries used by the themes and the widgets
+// are replaced, the ones of the current theme are saved and put back when it is turned off.
+pub fn set_night_vision(on: bool) {
+    if on == night_vision_active() {
+        return;
+    }
+    SAVED_PALETTE.with(|saved| {
+        let mut saved = saved.borrow_mut();
+        match saved.take() {
+            Some(palette) => {
+                for (i, (r, g, b)) in palette.into_iter().enumerate() {
+                    app::set_color(Color::by_index(i as u8), r, g, b);
+                }
+            }
+            None => {
+                let palette: Vec<(u8, u8, u8)> = (0..=255u8).map(|i| Color::by_index(i).to_rgb()).collect();
+                for (i, &(r, g, b)) in palette.iter().enumerate() {
+                    let (r, g, b) = night_vision_rgb(r, g, b);
+                    app::set_color(Color::by_index(i as u8), r, g, b);
+                }
+                *saved = Some(palette);
+            }
+        }
+    });
+    app::redraw();
+}
+
+// View -> Themes -> Night vision toggled, from the menu or its shortcut
+pub fn handle_night_vision(menu: &MenuBar) {
+    if let Some(item) = menu.find_item(&menu_path(NIGHT_VISION_MENU)) {
+        set_night_vision(item.value());
+    }
+}
+
+// Another theme was chosen, night vision goes off first so the new theme starts from its own colors
+pub fn leave_night_vision(menu: &MenuBar) {
+    set_night_vision(false);
+    if let Some(mut item) = menu.find_item(&menu_path(NIGHT_VISION_MENU)) {
+        item.clear();
+    }
+}
+
+// Colors given as RGB bypass the palette, widgets and drawings using them go through this one
+pub fn night_color(color: Color) -> Color {
+    if night_vision_active() {
+        let (r, g, b) = color.to_rgb();
+        let (r, g, b) = night_vision_rgb(r, g, b);
+        Color::from_rgb(r, g, b)
+    } else {
+        color
+    }
+}
@@ -0,0 +1,87 @@
+use crate::utils::angle::{format_dec_dms, format_ra_hms, try_dec_from_str, try_ra_from_str};
+use crate::utils::definers::{TOOLTIP_DEC_INPUT, TOOLTIP_RA_INPUT};
+use crate::widgets::validated::ValidatedInput;
+use fltk::input::Input;
+use fltk::prelude::*;
+use std::ops::{Deref, DerefMut};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Coordinate {
+    RightAscension,
+    Declination,
+}
+
+/// Right ascension or declination input, in hh:mm:ss.s / ±dd:mm:ss or decimal degrees.
+/// Values are always returned in degrees.
+#[derive(Clone)]
+pub struct RaDecInput {
+    pub radec_input: Input,
+    pub coordinate: Coordinate,
+}
+
+impl Deref for RaDecInput {
+    type Target = Input;
+    fn deref(&self) -> &Self::Target {
+        &self.radec_input
+    }
+}
+
+impl DerefMut for RaDecInput {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.radec_input
+    }
+}
+
+impl ValidatedInput for RaDecInput {
+    type Value = f64;
+
+    fn parse(&self) -> Result<f64, String> {
+        match self.coordinate {
+            Coordinate::RightAscension => try_ra_from_str(&self.radec_input.value()),
+            Coordinate::Declination => try_dec_from_str(&self.radec_input.value()),
+        }
+    }
+
+    fn hint(&self) -> &str {
+        match self.coordinate {
+            Coordinate::RightAscension => TOOLTIP_RA_INPUT,
+            Coordinate::Declination => TOOLTIP_DEC_INPUT,
+        }
+    }
+
+    fn input(&mut self) -> &mut Input {
+        &mut self.radec_input
+    }
+
+    // Sexagesimal text is rewritten as hh:mm:ss.s / ±dd:mm:ss, decimal degrees are kept as typed
+    fn normalize(&mut self, degrees: &f64) {
+        if self.radec_input.value().trim().contains([':', ' ']) {
+            self.set_degrees(*degrees);
+        }
+    }
+}
+
+impl RaDecInput {
+    pub fn new(x: i32, y: i32, w: i32, h: i32, label: &str, coordinate: Coordinate) -> RaDecInput {
+        let mut input = Input::new(x, y, w, h, label);
+        input.set_maximum_size(16); // max size -dd:mm:ss.ss or hh:mm:ss.sss
+        let mut radec = RaDecInput { radec_input: input, coordinate };
+        radec.set_degrees(0.0); // set initial value
+        let hint = radec.hint().to_string();
+        radec.set_tooltip(&hint);
+        radec
+    }
+
+    // Shows the coordinate in sexagesimal notation
+    pub fn set_degrees(&mut self, degrees: f64) {
+        match self.coordinate {
+            Coordinate::RightAscension => self.radec_input.set_value(&format_ra_hms(degrees)),
+            Coordinate::Declination => self.radec_input.set_value(&format_dec_dms(degrees)),
+        }
+    }
+
+    // Coordinate in degrees, or the message shown while the text is invalid
+    pub fn get_degrees(&self) -> Result<f64, String> {
+        self.parse()
+    }
+}
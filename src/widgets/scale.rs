@@ -0,0 +1,70 @@
+use std::sync::OnceLock;
+use fltk::app;
+use fltk::input::Input;
+use fltk::menu::MenuBar;
+use fltk::prelude::{GroupExt, InputExt, MenuExt, WidgetBase, WidgetExt};
+use crate::utils::dpi::{crisp_font_size, fit_size, sanitize_scale, BASE_FONT_SIZE};
+
+// Factors FLTK detected for each screen at startup, restored when going back to the system setting
+static SYSTEM_SCALES: OnceLock<Vec<f32>> = OnceLock::new();
+
+fn system_scales() -> &'static [f32] {
+    SYSTEM_SCALES.get_or_init(|| {
+        (0..app::screen_count()).map(|n| sanitize_scale(app::screen_scale(n))).collect()
+    })
+}
+
+/// Applies the interface scale to every screen, `None` keeps the factor the system reports for
+/// each one (e.g. 150% on a laptop panel and 100% on an external monitor).
+///
+/// FLTK is per-monitor DPI aware on Windows, it rescales open windows when the factor changes and
+/// when a window is moved to a screen with another factor, so window layouts stay in unscaled
+/// pixels. The default font size is set for the primary screen, the widgets of the open windows
+/// still at the previous default size take the new one.
+pub fn apply_ui_scale(ui_scale: Option<f32>) {
+    let system = system_scales();
+    for (n, detected) in system.iter().enumerate() {
+        app::set_screen_scale(n as i32, ui_scale.map(sanitize_scale).unwrap_or(*detected));
+    }
+
+    let primary = ui_scale.unwrap_or(system.first().copied().unwrap_or(1.0));
+    let previous = app::font_size();
+    let size = crisp_font_size(BASE_FONT_SIZE, primary);
+    app::set_font_size(size);
+    if size != previous {
+        for mut window in app::windows().unwrap_or_default() {
+            set_font_sizes(&mut window, previous, size);
+            window.redraw();
+        }
+    }
+}
+
+// Widgets at the previous default size take the new one, other sizes were chosen on purpose
+fn set_font_sizes<W: WidgetExt>(widget: &mut W, previous: i32, size: i32) {
+    if widget.label_size() == previous {
+        widget.set_label_size(size);
+    }
+    if let Some(mut input) = Input::from_dyn_widget(widget) {
+        if input.text_size() == previous {
+            input.set_text_size(size);
+        }
+    }
+    if let Some(mut menu) = MenuBar::from_dyn_widget(widget) {
+        if menu.text_size() == previous {
+            menu.set_text_size(size);
+        }
+    }
+    if let Some(group) = widget.as_group() {
+        for i in 0..group.children() {
+            if let Some(mut child) = group.child(i) {
+                set_font_sizes(&mut child, previous, size);
+            }
+        }
+    }
+}
+
+/// Window size that fits in the work area of the primary screen, in unscaled pixels
+pub fn fit_to_screen(width: i32, height: i32) -> (i32, i32) {
+    let (_, _, area_width, area_height) = app::screen_work_area(0);
+    fit_size(width, height, area_width, area_height)
+}
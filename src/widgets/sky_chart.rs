@@ -0,0 +1,82 @@
+use fltk::draw;
+use fltk::draw::LineStyle;
+use fltk::enums::{Align, Color, Font};
+use crate::application::avoidance::{chart_position, AvoidanceChart};
+use crate::application::timeline::{above_horizon, MOON_RGB, TARGET_RGB};
+
+// Space for the title above the chart and the cardinal points around it
+const TOP: i32 = 30;
+const MARGIN: i32 = 20;
+
+fn rgb_color((r, g, b): (u8, u8, u8)) -> Color {
+    Color::from_rgb(r, g, b)
+}
+
+// Draws the parts of a path above the horizon, `to_xy` maps (altitude, azimuth) to the screen
+fn draw_path(path: &[(f64, f64)], to_xy: &impl Fn(f64, f64) -> (f64, f64)) {
+    let altitudes: Vec<f64> = path.iter().map(|p| p.0).collect();
+    for run in above_horizon(&altitudes) {
+        draw::begin_line();
+        for i in run {
+            let (x, y) = to_xy(path[i].0, path[i].1);
+            draw::vertex(x, y);
+        }
+        draw::end_line();
+    }
+}
+
+/// Draws the sky chart of the observation window in the area: the horizon circle with the zenith
+/// at the center, the Moon avoidance zones in red, the Moon path dashed and the target paths,
+/// with the names of the targets entering a zone in red. `color` maps the colors for night vision.
+pub fn draw_sky_chart(x: i32, y: i32, w: i32, h: i32, chart: &AvoidanceChart, title: &str,
+                      color: fn(Color) -> Color) {
+    let radius = ((w - 2 * MARGIN).min(h - TOP - 2 * MARGIN) / 2).max(1);
+    let (center_x, center_y) = (x + w / 2, y + TOP + MARGIN + radius);
+    let to_xy = |altitude: f64, azimuth: f64| {
+        let (px, py) = chart_position(altitude, azimuth);
+        (center_x as f64 + px * radius as f64, center_y as f64 + py * radius as f64)
+    };
+    let grid_color = color(Color::from_rgb(0x55, 0x11, 0x11));
+    let text_color = color(Color::from_rgb(0xc0, 0x30, 0x30));
+
+    draw::draw_rect_fill(x, y, w, h, Color::Black);
+    draw::set_font(Font::Helvetica, 14);
+    draw::set_draw_color(color(Color::from_rgb(0xff, 0x40, 0x40)));
+    draw::draw_text2(title, x + MARGIN, y + 6, w - 2 * MARGIN, 20, Align::Left | Align::Inside);
+
+    // horizon, altitude rings every 30 degrees and cardinal points
+    draw::set_draw_color(grid_color);
+    for altitude in [0, 30, 60] {
+        let ring = radius * (90 - altitude) / 90;
+        draw::draw_arc(center_x - ring, center_y - ring, 2 * ring, 2 * ring, 0.0, 360.0);
+    }
+    draw::set_font(Font::Helvetica, 11);
+    draw::set_draw_color(text_color);
+    for (label, azimuth) in [("N", 0.0), ("E", 90.0), ("S", 180.0), ("W", 270.0)] {
+        let (label_x, label_y) = to_xy(-8.0, azimuth);
+        draw::draw_text2(label, label_x as i32 - 8, label_y as i32 - 8, 16, 16, Align::Center);
+    }
+
+    // avoidance zones at the start, middle and end of the window
+    draw::set_draw_color(color(Color::from_rgb(0xd0, 0x20, 0x20)));
+    draw::set_line_style(LineStyle::Solid, 1);
+    for (_, outline) in &chart.zones {
+        draw_path(outline, &to_xy);
+    }
+    draw::set_draw_color(color(rgb_color(MOON_RGB)));
+    draw::set_line_style(LineStyle::Dash, 2);
+    draw_path(&chart.moon, &to_xy);
+
+    // target paths, the name at the start of the window
+    for (i, (name, path, conflict)) in chart.targets.iter().enumerate() {
+        draw::set_draw_color(color(rgb_color(TARGET_RGB[i % TARGET_RGB.len()])));
+        draw::set_line_style(LineStyle::Solid, 2);
+        draw_path(path, &to_xy);
+        if let Some(&(altitude, azimuth)) = path.iter().find(|p| p.0 >= 0.0) {
+            let (label_x, label_y) = to_xy(altitude, azimuth);
+            draw::set_draw_color(if *conflict { text_color } else { color(Color::from_rgb(0x90, 0x90, 0x90)) });
+            draw::draw_text2(name, label_x as i32 + 4, label_y as i32 - 14, 120, 14, Align::Left | Align::Inside);
+        }
+    }
+    draw::set_line_style(LineStyle::Solid, 0);
+}
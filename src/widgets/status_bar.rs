@@ -0,0 +1,69 @@
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+use fltk::app;
+use fltk::enums::{Align, FrameType};
+use fltk::frame;
+use fltk::prelude::{WidgetBase, WidgetExt};
+use crate::application::application::Application;
+use crate::application::calculation::CalculationSettings;
+use crate::application::countdown::{status_line, upcoming_events, Countdown};
+use crate::application::environment::Environment;
+use crate::application::observer::Observer;
+use crate::application::time::Time;
+
+// Seconds between two updates of the status bar
+const STATUS_UPDATE_SECONDS: f64 = 1.0;
+
+/// Status bar at the bottom of the main window with the current time, the active observatory and
+/// the countdown to the next event
+#[derive(Clone)]
+pub struct StatusBar {
+    pub frame: frame::Frame,
+}
+
+impl Deref for StatusBar {
+    type Target = frame::Frame;
+    fn deref(&self) -> &Self::Target {
+        &self.frame
+    }
+}
+
+impl DerefMut for StatusBar {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.frame
+    }
+}
+
+impl StatusBar {
+    pub fn new(x: i32, y: i32, w: i32, h: i32) -> StatusBar {
+        let mut frame = frame::Frame::new(x, y, w, h, "").with_align(Align::Left | Align::Inside);
+        frame.set_frame(FrameType::ThinDownBox);
+        StatusBar { frame }
+    }
+
+    /// Updates the bar once per second from a FLTK timer. The upcoming events are only searched
+    /// again when the next one is over, every hour when there is none (polar day or night), or
+    /// when the observatory changes.
+    pub fn start(&self, application: Rc<RefCell<Application>>) {
+        let mut frame = self.frame.clone();
+        let mut events: Vec<Countdown> = Vec::new();
+        let mut events_for: Option<(Observer, Environment, CalculationSettings)> = None;
+        let mut refresh_jd = 0.0;
+        app::add_timeout3(0.0, move |handle| {
+            // a dialog may be changing the application, the next tick catches up
+            if let Ok(app) = application.try_borrow() {
+                let now = Time::now();
+                let jd = now.to_jd();
+                let settings = (app.observer.clone(), app.environment.clone(), app.preferences.calculation);
+                if events_for.as_ref() != Some(&settings) || jd >= refresh_jd {
+                    events = upcoming_events(&app.observer, &app.environment, app.preferences.calculation, jd);
+                    events_for = Some(settings);
+                    refresh_jd = events.first().map_or(jd + 1.0 / 24.0, |e| e.jd);
+                }
+                frame.set_label(&format!(" {}", status_line(&app.observer, &now, events.first())));
+            }
+            app::repeat_timeout3(STATUS_UPDATE_SECONDS, handle);
+        });
+    }
+}
@@ -0,0 +1,102 @@
+// src/widgets/theme.rs
+use std::cell::RefCell;
+use std::rc::Rc;
+use fltk::{app, draw};
+use fltk::enums::{Color, FrameType};
+use fltk_theme::{color_themes, ColorTheme, ThemeType, WidgetTheme};
+use crate::application::application::Application;
+use crate::application::preferences::{Appearance, Theme, WidgetStyle};
+use crate::application::session::update_layout;
+use crate::widgets::night_vision::{night_vision_active, set_night_vision};
+
+/// Appearance shown, the one of the preferences and the standard one when there is none
+pub fn current_appearance(application: &Application) -> Appearance {
+    application.preferences.appearance.unwrap_or_default()
+}
+
+// Applies one of the color themes of View/Themes to the whole program
+pub fn apply_theme(theme: Theme) {
+    let colors = match theme {
+        Theme::Dark => color_themes::DARK_THEME,
+        Theme::Black => color_themes::BLACK_THEME,
+        Theme::Gray => color_themes::GRAY_THEME,
+    };
+    ColorTheme::new(colors).apply();
+}
+
+// Engraved and embossed boxes and frames drawn as FLTK does, the widget themes draw them their own way
+fn engraved_frame(x: i32, y: i32, w: i32, h: i32, _: Color) {
+    draw::draw_frame("HHWWWWHH", x, y, w, h);
+}
+
+fn embossed_frame(x: i32, y: i32, w: i32, h: i32, _: Color) {
+    draw::draw_frame("WWHHHHWW", x, y, w, h);
+}
+
+fn engraved_box(x: i32, y: i32, w: i32, h: i32, color: Color) {
+    engraved_frame(x, y, w, h, color);
+    draw::draw_rect_fill(x + 2, y + 2, w - 4, h - 4, color);
+}
+
+fn embossed_box(x: i32, y: i32, w: i32, h: i32, color: Color) {
+    embossed_frame(x, y, w, h, color);
+    draw::draw_rect_fill(x + 2, y + 2, w - 4, h - 4, color);
+}
+
+// Box types of the standard widgets. Loading a scheme defines its box types again, the plain ones
+// with Base and the GTK ones with Gtk, the engraved and embossed ones are set back here.
+fn restore_standard_boxes() {
+    app::set_scheme(app::Scheme::Base);
+    app::set_scheme(app::Scheme::Gtk);
+    app::set_frame_type_cb(FrameType::EngravedBox, engraved_box, 2, 2, 4, 4);
+    app::set_frame_type_cb(FrameType::EmbossedBox, embossed_box, 2, 2, 4, 4);
+    app::set_frame_type_cb(FrameType::EngravedFrame, engraved_frame, 2, 2, 4, 4);
+    app::set_frame_type_cb(FrameType::EmbossedFrame, embossed_frame, 2, 2, 4, 4);
+}
+
+// Widget themes set their own colors, so the style goes first and the color theme over it
+pub fn apply_appearance(appearance: Appearance) {
+    let theme_type = match appearance.widgets {
+        WidgetStyle::Standard => None,
+        WidgetStyle::Classic => Some(ThemeType::Classic),
+        WidgetStyle::Aero => Some(ThemeType::Aero),
+        WidgetStyle::Metro => Some(ThemeType::Metro),
+        WidgetStyle::Greybird => Some(ThemeType::Greybird),
+        WidgetStyle::Dark => Some(ThemeType::Dark),
+    };
+    match theme_type {
+        Some(theme_type) => WidgetTheme::new(theme_type).apply(),
+        None => restore_standard_boxes(),
+    }
+    apply_theme(appearance.theme);
+    app::redraw();
+}
+
+// View/Themes choice, kept in the preferences and written to the last configuration for the next
+// start. Night vision is turned off by the menu before, so the new theme starts from its own colors.
+pub fn choose_appearance(application: &Rc<RefCell<Application>>, appearance: Appearance) {
+    apply_appearance(appearance);
+    {
+        let mut app = application.borrow_mut();
+        app.preferences.appearance = Some(appearance);
+        app.preferences.night_vision = false;
+    }
+    update_layout(|preferences| {
+        preferences.appearance = Some(appearance);
+        preferences.night_vision = false;
+    });
+}
+
+// Appearance saved with a loaded configuration. One without keeps the appearance shown before it
+// was loaded, `previous`, which becomes the one of its preferences. Night vision stays on over the
+// new theme, the user may be at the telescope. The configuration already holds it for the next start.
+pub fn apply_loaded_appearance(application: &Rc<RefCell<Application>>, previous: Appearance) {
+    let appearance = *application.borrow_mut().preferences.appearance.get_or_insert(previous);
+    if appearance == previous {
+        return;
+    }
+    let night_vision = night_vision_active();
+    set_night_vision(false);
+    apply_appearance(appearance);
+    set_night_vision(night_vision);
+}
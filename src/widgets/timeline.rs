@@ -0,0 +1,144 @@
+use std::cell::{Cell, RefCell};
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+use fltk::app;
+use fltk::draw;
+use fltk::enums::{Align, Color, Font, FrameType};
+use fltk::frame;
+use fltk::prelude::{WidgetBase, WidgetExt};
+use crate::application::application::Application;
+use crate::application::calculation::CalculationSettings;
+use crate::application::observer::Observer;
+use crate::application::time::Time;
+use crate::application::timeline::{hour_label, timeline_start, NightTimeline, MOON_UP_RGB};
+use crate::widgets::night_vision::night_color;
+
+// Seconds between two moves of the now cursor
+const TIMELINE_UPDATE_SECONDS: f64 = 1.0;
+// Steps of the 24 hours, 10 minutes each
+const TIMELINE_STEPS: usize = 144;
+// Height of the hour labels under the bars, in pixels
+const LABEL_HEIGHT: i32 = 14;
+
+/// Timeline of the selected night with the twilight bands, the Moon up bar and a cursor at the
+/// current time or at a simulated time, following the observatory and the date of the application
+#[derive(Clone)]
+pub struct TimelineWidget {
+    pub frame: frame::Frame,
+    simulated: Rc<Cell<Option<f64>>>,
+}
+
+impl Deref for TimelineWidget {
+    type Target = frame::Frame;
+    fn deref(&self) -> &Self::Target {
+        &self.frame
+    }
+}
+
+impl DerefMut for TimelineWidget {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.frame
+    }
+}
+
+impl TimelineWidget {
+    pub fn new(x: i32, y: i32, w: i32, h: i32) -> TimelineWidget {
+        let mut frame = frame::Frame::new(x, y, w, h, "");
+        frame.set_frame(FrameType::FlatBox);
+        frame.set_color(Color::Black);
+        TimelineWidget { frame, simulated: Rc::new(Cell::new(None)) }
+    }
+
+    /// Moves the cursor to a simulated time, a position along the night from 0.0 to 1.0, or back
+    /// to the current time with None
+    pub fn simulate(&mut self, position: Option<f64>) {
+        self.simulated.set(position);
+        self.frame.redraw();
+    }
+
+    /// Position of the simulated time along the night, None when the cursor is at the current time
+    pub fn simulated(&self) -> Option<f64> {
+        self.simulated.get()
+    }
+
+    /// Draws the night and moves the cursor once per second from a FLTK timer, the night is only
+    /// computed again when the observatory, the date or the solar midnight preference change.
+    /// The timer stops when the window of the widget is closed.
+    pub fn start(&self, application: Rc<RefCell<Application>>) {
+        let timeline: Rc<RefCell<Option<(NightTimeline, bool)>>> = Rc::new(RefCell::new(None));
+        let mut timeline_for: Option<(Observer, f64, bool, CalculationSettings)> = None;
+
+        let timeline_draw = Rc::clone(&timeline);
+        let simulated = Rc::clone(&self.simulated);
+        let mut frame = self.frame.clone();
+        frame.draw(move |f| {
+            if let Some((timeline, solar_midnight)) = timeline_draw.borrow().as_ref() {
+                draw_timeline(f, timeline, *solar_midnight, simulated.get());
+            }
+        });
+
+        app::add_timeout3(0.0, move |handle| {
+            if !frame.window().map_or(false, |w| w.shown()) {
+                return;
+            }
+            // a dialog may be changing the application, the next tick catches up
+            if let Ok(app) = application.try_borrow() {
+                let solar_midnight = app.preferences.solar_midnight;
+                let start = timeline_start(&app.observer, &app.time, solar_midnight);
+                let settings = (app.observer.clone(), start, solar_midnight, app.preferences.calculation);
+                if timeline_for.as_ref() != Some(&settings) {
+                    let night = NightTimeline::new(&app.observer, &app.time, solar_midnight, TIMELINE_STEPS,
+                                                   app.preferences.calculation);
+                    *timeline.borrow_mut() = Some((night, solar_midnight));
+                    timeline_for = Some(settings);
+                }
+                frame.redraw();
+            }
+            app::repeat_timeout3(TIMELINE_UPDATE_SECONDS, handle);
+        });
+    }
+}
+
+// Twilight bands on the upper part, the Moon up bar under them, hour ticks every 2 hours and the
+// cursor: yellow at the simulated position, red at the current time when it is within the night shown
+fn draw_timeline(f: &frame::Frame, timeline: &NightTimeline, solar_midnight: bool, simulated: Option<f64>) {
+    let (x, y, w, h) = (f.x(), f.y(), f.w(), f.h());
+    let bars = h - LABEL_HEIGHT;
+    let sky_height = bars * 2 / 3;
+    let moon_y = y + sky_height + 2;
+    let moon_height = (bars - sky_height - 4).max(2);
+    let steps = timeline.sky.len().max(1) as i32;
+    let column_x = |i: i32| x + i * w / steps;
+
+    draw::draw_rect_fill(x, y, w, h, Color::Black);
+    let (r, g, b) = MOON_UP_RGB;
+    for (i, (sky, &moon_up)) in timeline.sky.iter().zip(&timeline.moon_up).enumerate() {
+        let (left, right) = (column_x(i as i32), column_x(i as i32 + 1));
+        let (sr, sg, sb) = sky.rgb();
+        draw::draw_rect_fill(left, y, right - left, sky_height, night_color(Color::from_rgb(sr, sg, sb)));
+        if moon_up {
+            draw::draw_rect_fill(left, moon_y, right - left, moon_height, night_color(Color::from_rgb(r, g, b)));
+        }
+    }
+
+    draw::set_font(Font::Helvetica, 10);
+    for hour in (0..=24).step_by(2) {
+        let tick_x = x + (hour as i32 * w / 24).min(w - 1);
+        draw::set_draw_color(night_color(Color::from_rgb(0x55, 0x11, 0x11)));
+        draw::draw_line(tick_x, y, tick_x, y + bars);
+        draw::set_draw_color(Color::Light2);
+        let label_x = (tick_x - 12).clamp(x, x + w - 24);
+        draw::draw_text2(&hour_label(hour, solar_midnight), label_x, y + bars, 24, LABEL_HEIGHT, Align::Center);
+    }
+
+    let cursor = match simulated {
+        Some(position) => Some((position, night_color(Color::Yellow))),
+        None => timeline.position(Time::now().to_jd()).map(|position| (position, Color::Red)),
+    };
+    if let Some((position, color)) = cursor {
+        let cursor_x = x + (position * w as f64).round() as i32;
+        draw::set_draw_color(color);
+        draw::draw_line(cursor_x, y, cursor_x, y + bars);
+        draw::draw_line(cursor_x + 1, y, cursor_x + 1, y + bars);
+    }
+}
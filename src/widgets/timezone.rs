@@ -0,0 +1,49 @@
+use std::ops::{Deref, DerefMut};
+use fltk::input::Input;
+use fltk::prelude::*;
+use crate::application::observer::try_timezone_from_str;
+use crate::utils::definers::TOOLTIP_TIMEZONE_INPUT;
+use crate::widgets::validated::ValidatedInput;
+
+#[derive(Clone)]
+pub struct TimezoneInput {
+    pub timezone_input: Input
+}
+
+impl Deref for TimezoneInput {
+    type Target = Input;
+    fn deref(&self) -> &Self::Target {
+        &self.timezone_input
+    }
+}
+
+impl DerefMut for TimezoneInput {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.timezone_input
+    }
+}
+
+impl ValidatedInput for TimezoneInput {
+    type Value = f64;
+
+    fn parse(&self) -> Result<f64, String> {
+        try_timezone_from_str(&self.timezone_input.value())
+    }
+
+    fn hint(&self) -> &str {
+        TOOLTIP_TIMEZONE_INPUT
+    }
+
+    fn input(&mut self) -> &mut Input {
+        &mut self.timezone_input
+    }
+}
+
+impl TimezoneInput {
+    pub fn new(x: i32, y: i32, w: i32, h: i32, label: &str) -> TimezoneInput {
+        let mut input = Input::new(x, y, w, h, label);
+        input.set_maximum_size(6); // max size +05:45
+        input.set_tooltip(TOOLTIP_TIMEZONE_INPUT);
+        TimezoneInput { timezone_input: input }
+    }
+}
@@ -0,0 +1,98 @@
+// src/widgets/validated.rs
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use fltk::enums::{Color, Event, Key};
+use fltk::input::Input;
+use fltk::prelude::*;
+use fltk::{app, draw};
+use crate::widgets::keyboard::focus_next;
+
+// Marks an input as invalid with a red border and the error as tooltip, or restores the hint
+pub fn mark_input(input: &mut Input, error: Option<&str>, hint: &str) {
+    match error {
+        Some(error) => {
+            input.set_tooltip(error);
+            input.draw(|i| {
+                draw::set_draw_color(Color::Red);
+                draw::draw_rect(i.x(), i.y(), i.w(), i.h());
+                draw::draw_rect(i.x() + 1, i.y() + 1, i.w() - 2, i.h() - 2);
+            });
+        }
+        None => {
+            input.set_tooltip(hint);
+            input.draw(|_| {});
+        }
+    }
+    input.redraw();
+}
+
+// Number of the input within the range, the input is marked when it is not
+pub fn number_field<T>(input: &mut Input, range: &RangeInclusive<T>) -> Option<T>
+where
+    T: FromStr + PartialOrd + Display,
+{
+    let hint = format!("{} to {}", range.start(), range.end());
+    match input.value().trim().parse::<T>() {
+        Ok(value) if range.contains(&value) => {
+            mark_input(input, None, &hint);
+            Some(value)
+        }
+        _ => {
+            mark_input(input, Some(&format!("Enter a number from {}", hint)), &hint);
+            None
+        }
+    }
+}
+
+/// ValidatedInput trait
+///
+/// Input widget that checks its text instead of substituting a default for bad values. Invalid
+/// values are flagged on the input itself and dialogs do not apply them.
+pub trait ValidatedInput {
+    type Value;
+
+    /// Value of the text, or the message shown to the user
+    fn parse(&self) -> Result<Self::Value, String>;
+
+    /// Tooltip while the value is valid
+    fn hint(&self) -> &str;
+
+    fn input(&mut self) -> &mut Input;
+
+    /// Rewrites a valid value in the canonical format, nothing by default
+    fn normalize(&mut self, _value: &Self::Value) {}
+
+    /// Checks the text and marks the input, the value when it is valid
+    fn check(&mut self) -> Option<Self::Value> {
+        let result = self.parse();
+        let hint = self.hint().to_string();
+        mark_input(self.input(), result.as_ref().err().map(String::as_str), &hint);
+        if let Ok(value) = &result {
+            self.normalize(value);
+        }
+        result.ok()
+    }
+
+    /// Checks the text when the input loses focus or Enter is pressed, Enter on a valid value
+    /// moves the focus to the next field
+    fn check_on_leave(&mut self)
+    where
+        Self: Clone + 'static,
+    {
+        let mut validated = self.clone();
+        self.input().handle(move |i, ev| match ev {
+            Event::Unfocus => {
+                validated.check();
+                true
+            }
+            Event::KeyDown if app::event_key() == Key::Enter => {
+                if validated.check().is_some() {
+                    focus_next(i);
+                }
+                true
+            }
+            _ => false,
+        });
+    }
+}